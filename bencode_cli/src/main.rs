@@ -0,0 +1,365 @@
+//! Command line tool exposing the library's inspection, conversion, validation
+//! and editing features.
+//!
+//! ```text
+//! bencode-cli inspect <file>
+//! bencode-cli convert <file> <json|yaml|xml|toml>
+//! bencode-cli convert <file> --from <yaml|xml> [-o <output>]
+//! bencode-cli validate <file>
+//! bencode-cli info-hash <file>
+//! bencode-cli info <file>
+//! bencode-cli set <file> <path> <bencoded value> [-o <output>]
+//! bencode-cli delete <file> <path> [-o <output>]
+//! ```
+//!
+//! A file of `-` is read from standard input. Paths are dot separated
+//! dictionary keys with numeric list indexes (e.g. `info.files.0.length`).
+//! Edited documents, and documents converted back to bencode, are written to
+//! standard output unless `-o` names a file. Only YAML and XML can be converted
+//! back: the library has no JSON or TOML reader.
+//! Sizes are printed in binary units and dates in UTC, whatever the locale.
+
+use std::fs;
+use std::io::{self, Read, Write};
+use std::process::ExitCode;
+
+use bencode_lib::torrent::{TorrentFile, info_hash, to_hex};
+use bencode_lib::{
+    BufferDestination, ParseError, TreeOptions, from_xml, from_yaml, human_date, human_size, parse_bytes, print_tree,
+    stringify_to_bytes, to_json, to_toml, to_xml, to_yaml, validate_canonical,
+};
+
+/// Usage text printed for missing or unknown arguments
+const USAGE: &str = "usage:
+  bencode-cli inspect <file>
+  bencode-cli convert <file> <json|yaml|xml|toml>
+  bencode-cli convert <file> --from <yaml|xml> [-o <output>]
+  bencode-cli validate <file>
+  bencode-cli info-hash <file>
+  bencode-cli info <file>
+  bencode-cli set <file> <path> <bencoded value> [-o <output>]
+  bencode-cli delete <file> <path> [-o <output>]";
+
+/// Error for converting from a format the library cannot read
+const ERR_UNREADABLE_FORMAT: &str = "only yaml and xml can be converted back to bencode; json and toml are not supported";
+
+/// Text formats a document can be converted to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Json,
+    Yaml,
+    Xml,
+    Toml,
+}
+
+/// Action requested on the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Command {
+    /// Print an indented listing with offsets and lengths
+    Inspect,
+    /// Print the document in another format
+    Convert(Format),
+    /// Read a text document (YAML or XML) and write it as bencode
+    ConvertFrom(Format),
+    /// Check that the document is canonical bencode
+    Validate,
+    /// Print the hex v1 info hash of a torrent
+    InfoHash,
+    /// Print a summary of a torrent
+    Info,
+    /// Set the value at a path, given as bencode
+    Set { path: String, value: Vec<u8> },
+    /// Remove the value at a path
+    Delete { path: String },
+}
+
+/// Parsed command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Invocation {
+    command: Command,
+    /// Input file, or `-` for standard input
+    input: String,
+    /// Where an edited document is written; standard output if None
+    output: Option<String>,
+}
+
+/// Reads a format name
+fn parse_format(name: &str) -> Result<Format, String> {
+    match name {
+        "json" => Ok(Format::Json),
+        "yaml" => Ok(Format::Yaml),
+        "xml" => Ok(Format::Xml),
+        "toml" => Ok(Format::Toml),
+        _ => Err(format!("unknown format '{}'", name)),
+    }
+}
+
+/// Reads the command line arguments (without the program name)
+fn parse_args(args: &[String]) -> Result<Invocation, String> {
+    let mut output = None;
+    let mut from = None;
+    let mut positional = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "-o" || arg == "--output" {
+            output = Some(args.next().ok_or("missing file after -o")?.clone());
+        } else if arg == "--from" {
+            from = Some(args.next().ok_or("missing format after --from")?.as_str());
+        } else {
+            positional.push(arg.as_str());
+        }
+    }
+    let (command, input) = match (positional.as_slice(), from) {
+        (["convert", input], Some(from)) => match parse_format(from)? {
+            Format::Json | Format::Toml => return Err(ERR_UNREADABLE_FORMAT.to_string()),
+            format => (Command::ConvertFrom(format), input),
+        },
+        (_, Some(_)) => return Err("--from applies only to convert".to_string()),
+        (positional, None) => match positional {
+            ["inspect", input] => (Command::Inspect, input),
+            ["convert", input, format] => (Command::Convert(parse_format(format)?), input),
+            ["validate", input] => (Command::Validate, input),
+            ["info-hash", input] => (Command::InfoHash, input),
+            ["info", input] => (Command::Info, input),
+            ["set", input, path, value] => {
+                let command = Command::Set {
+                    path: path.to_string(),
+                    value: value.as_bytes().to_vec(),
+                };
+                (command, input)
+            }
+            ["delete", input, path] => (Command::Delete { path: path.to_string() }, input),
+            _ => return Err(USAGE.to_string()),
+        },
+    };
+    if output.is_some() && !matches!(command, Command::ConvertFrom(_) | Command::Set { .. } | Command::Delete { .. }) {
+        return Err("-o applies only to convert --from, set and delete".to_string());
+    }
+    Ok(Invocation {
+        command,
+        input: input.to_string(),
+        output,
+    })
+}
+
+/// Formats a parse error with the offset it was found at
+fn describe(error: ParseError) -> String {
+    match error.path {
+        Some(path) => format!("{} at offset {} (path '{}')", error.message, error.offset, path),
+        None => format!("{} at offset {}", error.message, error.offset),
+    }
+}
+
+/// Describes a torrent's name, size, pieces, creation and trackers
+fn summarize(torrent: &TorrentFile) -> String {
+    let info = &torrent.info;
+    let mut lines = vec![
+        format!("name: {}", info.name),
+        format!("size: {} in {} file(s)", human_size(torrent.total_length()), info.files.len().max(1)),
+        format!("pieces: {} of {}", info.piece_count(), human_size(info.piece_length)),
+    ];
+    if let Some(date) = torrent.creation_date.and_then(|date| i64::try_from(date).ok()) {
+        lines.push(format!("created: {}", human_date(date)));
+    }
+    if let Some(created_by) = &torrent.created_by {
+        lines.push(format!("created by: {}", created_by));
+    }
+    lines.extend(torrent.trackers().iter().map(|url| format!("tracker: {}", url)));
+    let mut summary = lines.join("\n");
+    summary.push('\n');
+    summary
+}
+
+/// Runs a command on the input document, returning what to write out
+fn execute(command: &Command, data: &[u8]) -> Result<Vec<u8>, String> {
+    match command {
+        Command::Inspect => {
+            let mut destination = BufferDestination::new();
+            print_tree(data, &mut destination, &TreeOptions::new()).map_err(describe)?;
+            Ok(destination.into_bytes())
+        }
+        Command::Convert(format) => {
+            let node = parse_bytes(data).map_err(describe)?;
+            let mut destination = BufferDestination::new();
+            match format {
+                Format::Json => to_json(&node, &mut destination),
+                Format::Yaml => to_yaml(&node, &mut destination),
+                Format::Xml => to_xml(&node, &mut destination),
+                Format::Toml => to_toml(&node, &mut destination),
+            }?;
+            let mut output = destination.into_bytes();
+            output.push(b'\n');
+            Ok(output)
+        }
+        Command::ConvertFrom(format) => {
+            let text = std::str::from_utf8(data).map_err(|_| "input is not UTF-8 text".to_string())?;
+            let node = match format {
+                Format::Yaml => from_yaml(text),
+                Format::Xml => from_xml(text),
+                Format::Json | Format::Toml => return Err(ERR_UNREADABLE_FORMAT.to_string()),
+            }
+            .map_err(describe)?;
+            stringify_to_bytes(&node).map_err(describe)
+        }
+        Command::Validate => {
+            validate_canonical(data).map_err(describe)?;
+            Ok(b"canonical\n".to_vec())
+        }
+        Command::InfoHash => {
+            let hash = info_hash(data).map_err(describe)?;
+            Ok(format!("{}\n", to_hex(&hash)).into_bytes())
+        }
+        Command::Info => {
+            let torrent = TorrentFile::from_bytes(data)?;
+            Ok(summarize(&torrent).into_bytes())
+        }
+        Command::Set { path, value } => {
+            let mut node = parse_bytes(data).map_err(describe)?;
+            let value = parse_bytes(value).map_err(|e| format!("value is not bencode: {}", describe(e)))?;
+            node.insert_path(path, value)?;
+            stringify_to_bytes(&node).map_err(describe)
+        }
+        Command::Delete { path } => {
+            let mut node = parse_bytes(data).map_err(describe)?;
+            node.remove_path(path).ok_or_else(|| format!("no value at '{}'", path))?;
+            stringify_to_bytes(&node).map_err(describe)
+        }
+    }
+}
+
+/// Reads the input named on the command line
+fn read_input(input: &str) -> Result<Vec<u8>, String> {
+    if input == "-" {
+        let mut data = Vec::new();
+        io::stdin().read_to_end(&mut data).map_err(|e| e.to_string())?;
+        Ok(data)
+    } else {
+        fs::read(input).map_err(|e| format!("{}: {}", input, e))
+    }
+}
+
+/// Runs one invocation, writing its result
+fn run(invocation: &Invocation) -> Result<(), String> {
+    let data = read_input(&invocation.input)?;
+    let result = execute(&invocation.command, &data)?;
+    match &invocation.output {
+        Some(output) => fs::write(output, result).map_err(|e| format!("{}: {}", output, e)),
+        None => io::stdout().write_all(&result).map_err(|e| e.to_string()),
+    }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let invocation = match parse_args(&args) {
+        Ok(invocation) => invocation,
+        Err(error) => {
+            eprintln!("{}", error);
+            return ExitCode::from(2);
+        }
+    };
+    match run(&invocation) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TORRENT: &[u8] = b"d8:announce3:url4:infod6:lengthi5e4:name1:xee";
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn arguments_select_a_command() {
+        let invocation = parse_args(&args("set a.torrent info.private i1e -o b.torrent")).unwrap();
+        assert_eq!(
+            invocation,
+            Invocation {
+                command: Command::Set {
+                    path: "info.private".to_string(),
+                    value: b"i1e".to_vec()
+                },
+                input: "a.torrent".to_string(),
+                output: Some("b.torrent".to_string()),
+            }
+        );
+        assert_eq!(parse_args(&args("convert - yaml")).unwrap().command, Command::Convert(Format::Yaml));
+        let from = parse_args(&args("convert a.xml --from xml -o a.torrent")).unwrap();
+        assert_eq!((from.command, from.output), (Command::ConvertFrom(Format::Xml), Some("a.torrent".to_string())));
+        assert_eq!(parse_args(&args("convert a.json --from json")).unwrap_err(), ERR_UNREADABLE_FORMAT);
+        for bad in [
+            "",
+            "inspect",
+            "convert a csv",
+            "convert a json --from yaml",
+            "inspect a --from yaml",
+            "inspect a -o b",
+            "delete a b -o",
+            "frobnicate a",
+        ] {
+            assert!(parse_args(&args(bad)).is_err(), "{}", bad);
+        }
+    }
+
+    #[test]
+    fn documents_are_inspected_converted_and_hashed() {
+        let listing = String::from_utf8(execute(&Command::Inspect, TORRENT).unwrap()).unwrap();
+        assert!(listing.contains("name: \"x\""));
+        let json = execute(&Command::Convert(Format::Json), TORRENT).unwrap();
+        assert!(json.starts_with(b"{") && json.ends_with(b"}\n"));
+        let hash = execute(&Command::InfoHash, TORRENT).unwrap();
+        assert_eq!(hash.len(), 41);
+        assert!(execute(&Command::Info, b"i1e").is_err());
+        assert_eq!(execute(&Command::Validate, TORRENT).unwrap(), b"canonical\n");
+        let error = execute(&Command::Validate, b"i01e").unwrap_err();
+        assert!(error.ends_with("at offset 0"), "{}", error);
+    }
+
+    #[test]
+    fn yaml_and_xml_convert_back_to_bencode() {
+        for format in [Format::Yaml, Format::Xml] {
+            let text = execute(&Command::Convert(format), TORRENT).unwrap();
+            assert_eq!(execute(&Command::ConvertFrom(format), &text).unwrap(), TORRENT, "{:?}", format);
+        }
+        assert!(execute(&Command::ConvertFrom(Format::Yaml), b"\xff").is_err());
+    }
+
+    #[test]
+    fn documents_are_edited_by_path() {
+        let set = Command::Set {
+            path: "info.private".to_string(),
+            value: b"i1e".to_vec(),
+        };
+        let edited = execute(&set, TORRENT).unwrap();
+        assert_eq!(edited, b"d8:announce3:url4:infod6:lengthi5e4:name1:x7:privatei1eee");
+        let deleted = execute(&Command::Delete { path: "announce".to_string() }, &edited).unwrap();
+        assert_eq!(deleted, b"d4:infod6:lengthi5e4:name1:x7:privatei1eee");
+
+        assert!(execute(&Command::Delete { path: "missing".to_string() }, TORRENT).is_err());
+        let not_bencode = Command::Set {
+            path: "x".to_string(),
+            value: b"text".to_vec(),
+        };
+        assert!(execute(&not_bencode, TORRENT).unwrap_err().starts_with("value is not bencode"));
+    }
+
+    #[test]
+    fn torrents_are_summarized_with_readable_sizes_and_dates() {
+        let torrent = b"d8:announce21:udp://tracker.example13:creation datei1700000000e4:infod6:lengthi1572864e4:name5:x.iso12:piece lengthi262144e6:pieces120:";
+        let mut data = torrent.to_vec();
+        data.extend_from_slice(&[0; 120]);
+        data.extend_from_slice(b"ee");
+        let summary = String::from_utf8(execute(&Command::Info, &data).unwrap()).unwrap();
+        assert_eq!(
+            summary,
+            "name: x.iso\nsize: 1.5 MiB in 1 file(s)\npieces: 6 of 256.0 KiB\ncreated: 2023-11-14 22:13:20 UTC\ntracker: udp://tracker.example\n"
+        );
+    }
+}
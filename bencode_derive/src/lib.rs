@@ -0,0 +1,126 @@
+//! Derive macros for `bencode_lib::ToBencode` and `bencode_lib::FromBencode`.
+//!
+//! Use them through `bencode_lib` with its `derive` feature enabled; the field
+//! attributes are described in the `bencode_lib::convert` module.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Generics, Ident, LitStr, parse_macro_input, parse_quote};
+
+/// A struct field and the `#[bencode(...)]` options given for it
+struct Field {
+    ident: Ident,
+    key: String,
+    skip: bool,
+    default: bool,
+}
+
+impl Field {
+    /// Reads a named field and its attributes
+    fn parse(field: &syn::Field) -> syn::Result<Self> {
+        let ident = field.ident.clone().expect("named field");
+        let mut parsed = Field {
+            key: ident.to_string().trim_start_matches("r#").to_string(),
+            ident,
+            skip: false,
+            default: false,
+        };
+        for attr in field.attrs.iter().filter(|attr| attr.path().is_ident("bencode")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    parsed.key = meta.value()?.parse::<LitStr>()?.value();
+                } else if meta.path.is_ident("skip") {
+                    parsed.skip = true;
+                } else if meta.path.is_ident("default") {
+                    parsed.default = true;
+                } else {
+                    return Err(meta.error("expected `rename = \"...\"`, `skip` or `default`"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(parsed)
+    }
+}
+
+/// Returns the fields of a struct with named fields
+fn named_fields(input: &DeriveInput) -> syn::Result<Vec<Field>> {
+    match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().map(Field::parse).collect(),
+            _ => Err(syn::Error::new_spanned(&input.ident, "bencode derives need a struct with named fields")),
+        },
+        _ => Err(syn::Error::new_spanned(&input.ident, "bencode derives need a struct with named fields")),
+    }
+}
+
+/// Adds `bound` to every type parameter
+fn with_bound(generics: &Generics, bound: syn::TypeParamBound) -> Generics {
+    let mut generics = generics.clone();
+    for param in generics.type_params_mut() {
+        param.bounds.push(bound.clone());
+    }
+    generics
+}
+
+/// Derives `ToBencode`, encoding the struct as a dictionary of its fields.
+#[proc_macro_derive(ToBencode, attributes(bencode))]
+pub fn derive_to_bencode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    to_bencode(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn to_bencode(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = with_bound(&input.generics, parse_quote!(::bencode_lib::ToBencode));
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    let fields = named_fields(input)?;
+    let entries = fields.iter().filter(|field| !field.skip).map(|field| {
+        let (ident, key) = (&field.ident, &field.key);
+        quote! { ::bencode_lib::convert::encode_field(&mut dict, #key, &self.#ident)?; }
+    });
+    Ok(quote! {
+        impl #impl_generics ::bencode_lib::ToBencode for #name #type_generics #where_clause {
+            fn to_bencode(&self) -> ::core::result::Result<::bencode_lib::Node, ::bencode_lib::ParseError> {
+                #[allow(unused_mut)]
+                let mut dict: ::bencode_lib::Dictionary = ::core::default::Default::default();
+                #(#entries)*
+                ::core::result::Result::Ok(::bencode_lib::Node::Dictionary(dict))
+            }
+        }
+    })
+}
+
+/// Derives `FromBencode`, decoding the struct from a dictionary of its fields.
+#[proc_macro_derive(FromBencode, attributes(bencode))]
+pub fn derive_from_bencode(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    from_bencode(&input).unwrap_or_else(syn::Error::into_compile_error).into()
+}
+
+fn from_bencode(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let name = &input.ident;
+    let generics = with_bound(&input.generics, parse_quote!(::bencode_lib::FromBencode));
+    let (impl_generics, type_generics, where_clause) = generics.split_for_impl();
+    let fields = named_fields(input)?;
+    let values = fields.iter().map(|field| {
+        let (ident, key) = (&field.ident, &field.key);
+        if field.skip {
+            quote! { #ident: ::core::default::Default::default() }
+        } else if field.default {
+            quote! { #ident: ::bencode_lib::convert::decode_field_or_default(dict, #key)? }
+        } else {
+            quote! { #ident: ::bencode_lib::convert::decode_field(dict, #key)? }
+        }
+    });
+    Ok(quote! {
+        impl #impl_generics ::bencode_lib::FromBencode for #name #type_generics #where_clause {
+            fn from_bencode(node: &::bencode_lib::Node) -> ::core::result::Result<Self, ::bencode_lib::ParseError> {
+                #[allow(unused_variables)]
+                let dict = ::bencode_lib::convert::expect_dictionary(node)?;
+                ::core::result::Result::Ok(Self { #(#values),* })
+            }
+        }
+    })
+}
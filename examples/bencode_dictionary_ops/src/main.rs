@@ -2,7 +2,7 @@
 //! This shows how to create, query, modify, and iterate through dictionary nodes
 //! in various scenarios commonly needed when working with bencode data.
 
-use bencode_lib::{make_node, Node};
+use bencode_lib::{human_size, make_node, Node};
 use std::collections::HashMap;
 
 fn main() {
@@ -163,7 +163,7 @@ fn demonstrate_iteration() {
         // Count entries by type
         let mut int_count = 0;
         let mut str_count = 0;
-        for (_, value) in dict {
+        for value in dict.values() {
             if value.is_integer() {
                 int_count += 1;
             } else if value.is_string() {
@@ -214,10 +214,8 @@ fn demonstrate_nested_operations() {
     println!("After updating zip: {}", node);
 
     // Add to nested dictionary
-    if let Some(address) = node.get_mut("address") {
-        if let Node::Dictionary(dict) = address {
-            dict.insert("country".to_string(), make_node("USA"));
-        }
+    if let Some(Node::Dictionary(dict)) = node.get_mut("address") {
+        dict.insert("country".to_string(), make_node("USA"));
     }
     println!("After adding country: {}\n", node);
 }
@@ -269,8 +267,8 @@ fn demonstrate_torrent_metadata() {
             println!("Torrent name: {}", name);
         }
         if let Some(piece_length) = info.get("piece length") {
-            if let Some(len) = piece_length.as_integer() {
-                println!("Piece length: {} bytes ({} KB)", len, len / 1024);
+            if let Ok(len) = piece_length.as_u64() {
+                println!("Piece length: {}", human_size(len));
             }
         }
     }
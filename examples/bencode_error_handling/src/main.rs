@@ -47,7 +47,7 @@ fn demonstrate_parse_errors() {
         print!("{}: ", description);
         match parse_bytes(data) {
             Ok(node) => println!("Unexpectedly succeeded: {}", node),
-            Err(e) => println!("Error (as expected): {}", e),
+            Err(e) => println!("Error (as expected): {} [{:?}] at byte {}", e, e.kind(), e.offset),
         }
     }
 
@@ -213,7 +213,7 @@ fn validate_torrent_metadata(node: &Node) -> Result<(), String> {
 
     info.get("pieces")
         .ok_or("Missing 'info.pieces' field")?
-        .as_string()
+        .as_bytes()
         .ok_or("'info.pieces' must be a string")?;
 
     Ok(())
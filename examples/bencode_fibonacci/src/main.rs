@@ -20,11 +20,11 @@ fn read_sequence(file_path: &Path) -> Result<Node, String> {
     }
 
     // Try to open and parse the existing file
-    match FileSource::new(&file_path.to_string_lossy()) {
+    match FileSource::new(file_path) {
         Ok(mut file) => match parse(&mut file) {
             Ok(Node::List(list)) => Ok(Node::List(list)),
             Ok(_) => Err("Invalid file format: expected a list".to_string()),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         },
         Err(e) => Err(format!("Failed to open file: {}", e)),
     }
@@ -42,13 +42,10 @@ fn add_next(sequence: &mut Node) {
         if items.len() < 2 {
             return;
         }
-        match (&items[items.len() - 2], &items[items.len() - 1]) {
-            (Node::Integer(a), Node::Integer(b)) => {
-                if let Some(sum) = a.checked_add(*b) {
-                    items.push(Node::Integer(sum));
-                }
-            }
-            _ => {}
+        if let (Node::Integer(a), Node::Integer(b)) = (&items[items.len() - 2], &items[items.len() - 1])
+            && let Some(sum) = a.checked_add(*b)
+        {
+            items.push(Node::Integer(sum));
         }
     }
 
@@ -64,10 +61,10 @@ fn add_next(sequence: &mut Node) {
 /// * `Ok(())` - Write operation succeeded
 /// * `Err(String)` - Error message if writing fails
 fn write_sequence(file_path: &Path, sequence: &Node) -> Result<(), String> {
-    // Create a new file destination, falling back to empty string if the path is invalid
-    let  file = FileDestination::new(file_path.to_str().unwrap_or(""));
+    // Create a new file destination
+    let  file = FileDestination::new(file_path);
     match file {
-        Ok(mut f) => { stringify(&sequence, &mut f)?; Ok(()) }
+        Ok(mut f) => { stringify(sequence, &mut f)?; Ok(()) }
         Err(e) => { Err( e.to_string())}
     }
 }
@@ -83,7 +80,6 @@ fn main() {
             add_next(&mut sequence);
             if let Err(e) = write_sequence(file_path, &sequence) {
                 eprintln!("Failed to write sequence: {}", e);
-                return;
             }
         }
         Err(e) => eprintln!("Failed to read sequence: {}", e)
@@ -2,7 +2,7 @@
 //! This shows how to convert bencode data to JSON, TOML, XML, YAML and back to bencode.
 
 use bencode_lib::{
-    make_node, parse_str, stringify_to_string, to_json, to_toml, to_xml, to_yaml,
+    human_size, make_node, parse_str, stringify_to_string, to_json, to_toml, to_xml, to_yaml,
     BufferDestination, Node,
 };
 use std::collections::HashMap;
@@ -63,7 +63,7 @@ fn print_all_formats(description: &str, node: &Node) {
 
     // JSON
     let mut json_dest = BufferDestination::new();
-    let _ = to_json(node, &mut json_dest).expect("Failed to convert to JSON");
+    to_json(node, &mut json_dest).expect("Failed to convert to JSON");
     println!(
         "  JSON:    {}",
         String::from_utf8_lossy(&json_dest.buffer)
@@ -71,7 +71,7 @@ fn print_all_formats(description: &str, node: &Node) {
 
     // TOML
     let mut toml_dest = BufferDestination::new();
-    let _ = to_toml(node, &mut toml_dest).expect("Failed to convert to TOML");
+    to_toml(node, &mut toml_dest).expect("Failed to convert to TOML");
     println!(
         "  TOML:    {}",
         String::from_utf8_lossy(&toml_dest.buffer)
@@ -79,7 +79,7 @@ fn print_all_formats(description: &str, node: &Node) {
 
     // XML
     let mut xml_dest = BufferDestination::new();
-    let _ = to_xml(node, &mut xml_dest).expect("Failed to convert to XML");
+    to_xml(node, &mut xml_dest).expect("Failed to convert to XML");
     let xml_output = String::from_utf8_lossy(&xml_dest.buffer);
     // Compact display for readability
     let xml_compact = xml_output.replace('\n', "").replace("  ", "");
@@ -87,7 +87,7 @@ fn print_all_formats(description: &str, node: &Node) {
 
     // YAML
     let mut yaml_dest = BufferDestination::new();
-    let _ = to_yaml(node, &mut yaml_dest).expect("Failed to convert to YAML");
+    to_yaml(node, &mut yaml_dest).expect("Failed to convert to YAML");
     let yaml_output = String::from_utf8_lossy(&yaml_dest.buffer);
     // Show first line only for compact display
     let yaml_first_line = yaml_output.lines().next().unwrap_or("");
@@ -124,22 +124,22 @@ fn demonstrate_complex_conversions() {
 
     println!("\n=== JSON Format ===");
     let mut json_dest = BufferDestination::new();
-    let _ = to_json(&complex, &mut json_dest).expect("Failed to convert to JSON");
+    to_json(&complex, &mut json_dest).expect("Failed to convert to JSON");
     println!("{}", String::from_utf8_lossy(&json_dest.buffer));
 
     println!("=== TOML Format ===");
     let mut toml_dest = BufferDestination::new();
-    let _ = to_toml(&complex, &mut toml_dest).expect("Failed to convert to TOML");
+    to_toml(&complex, &mut toml_dest).expect("Failed to convert to TOML");
     println!("{}", String::from_utf8_lossy(&toml_dest.buffer));
 
     println!("=== XML Format ===");
     let mut xml_dest = BufferDestination::new();
-    let _ = to_xml(&complex, &mut xml_dest).expect("Failed to convert to XML");
+    to_xml(&complex, &mut xml_dest).expect("Failed to convert to XML");
     println!("{}", String::from_utf8_lossy(&xml_dest.buffer));
 
     println!("=== YAML Format ===");
     let mut yaml_dest = BufferDestination::new();
-    let _ = to_yaml(&complex, &mut yaml_dest).expect("Failed to convert to YAML");
+    to_yaml(&complex, &mut yaml_dest).expect("Failed to convert to YAML");
     println!("{}", String::from_utf8_lossy(&yaml_dest.buffer));
 }
 
@@ -195,28 +195,28 @@ fn demonstrate_format_comparison() {
 
     // Bencode
     if let Ok(bencode) = stringify_to_string(&node) {
-        println!("  Bencode: {} bytes", bencode.len());
+        println!("  Bencode: {}", human_size(bencode.len() as u64));
     }
 
     // JSON
     let mut json_dest = BufferDestination::new();
-    let _ = to_json(&node, &mut json_dest).expect("Failed to convert to JSON");
-    println!("  JSON:    {} bytes", &json_dest.buffer.len());
+    to_json(&node, &mut json_dest).expect("Failed to convert to JSON");
+    println!("  JSON:    {}", human_size(json_dest.buffer.len() as u64));
 
     // TOML
     let mut toml_dest = BufferDestination::new();
-    let _ = to_toml(&node, &mut toml_dest).expect("Failed to convert to TOML");
-    println!("  TOML:    {} bytes", &toml_dest.buffer.len());
+    to_toml(&node, &mut toml_dest).expect("Failed to convert to TOML");
+    println!("  TOML:    {}", human_size(toml_dest.buffer.len() as u64));
 
     // XML
     let mut xml_dest = BufferDestination::new();
-    let _ = to_xml(&node, &mut xml_dest).expect("Failed to convert to XML");
-    println!("  XML:     {} bytes", &xml_dest.buffer.len());
+    to_xml(&node, &mut xml_dest).expect("Failed to convert to XML");
+    println!("  XML:     {}", human_size(xml_dest.buffer.len() as u64));
 
     // YAML
     let mut yaml_dest = BufferDestination::new();
-    let _ = to_yaml(&node, &mut yaml_dest).expect("Failed to convert to YAML");
-    println!("  YAML:    {} bytes", &yaml_dest.buffer.len());
+    to_yaml(&node, &mut yaml_dest).expect("Failed to convert to YAML");
+    println!("  YAML:    {}", human_size(yaml_dest.buffer.len() as u64));
 
     println!("\nNote: Bencode is typically the most compact format.");
     println!();
@@ -263,12 +263,12 @@ fn demonstrate_torrent_conversion() {
 
     println!("=== As JSON (human-readable) ===");
     let mut json_dest = BufferDestination::new();
-    let _ = to_json(&torrent_node, &mut json_dest).expect("Failed to convert to JSON");
+    to_json(&torrent_node, &mut json_dest).expect("Failed to convert to JSON");
     println!("{}\n", String::from_utf8_lossy(&json_dest.buffer));
 
     println!("=== As YAML (human-readable) ===");
     let mut yaml_dest = BufferDestination::new();
-    let _ = to_yaml(&torrent_node, &mut yaml_dest).expect("Failed to convert to YAML");
+    to_yaml(&torrent_node, &mut yaml_dest).expect("Failed to convert to YAML");
     println!("{}", String::from_utf8_lossy(&yaml_dest.buffer));
 
     println!("These conversions are useful for:");
@@ -124,7 +124,7 @@ fn main() {
 /// Simulates error reporting in an embedded system with limited resources
 fn simulate_embedded_error_reporting() {
     // In embedded systems, you might log error codes instead of strings
-    let test_data = vec![
+    let test_data = [
         b"i42e" as &[u8], // Valid
         b"i42",           // Invalid
         b"",              // Empty
@@ -80,7 +80,7 @@ fn demonstrate_access() {
         println!("Is empty: {}", items.is_empty());
 
         // Access by index
-        if let Some(first) = items.get(0) {
+        if let Some(first) = items.first() {
             println!("First element: {}", first);
         }
 
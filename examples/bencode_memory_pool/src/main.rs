@@ -193,19 +193,19 @@ fn main() {
     println!("\n=== Recommendations for Embedded Systems ===\n");
     println!("1. Use parse_borrowed() instead of parse()");
     println!("   → Eliminates string allocation overhead");
-    println!("");
+    println!();
     println!("2. Use MemoryTracker to enforce budgets");
     println!("   → Prevent out-of-memory errors");
-    println!("");
+    println!();
     println!("3. Use Arena for temporary allocations");
     println!("   → Reduce fragmentation, batch free");
-    println!("");
+    println!();
     println!("4. Use StackBuffer for small, bounded data");
     println!("   → Zero heap usage for predictable sizes");
-    println!("");
+    println!();
     println!("5. Use validate_bencode() before parsing");
     println!("   → Check validity without committing memory");
-    println!("");
+    println!();
     println!("6. Combine techniques for maximum efficiency");
     println!("   → Stack buffer + zero-copy + validation = minimal footprint");
 }
@@ -35,11 +35,11 @@ fn main() {
 
     // Parse a bencode dictionary
     let dict = b"d3:agei25e4:name4:Johne";
-    if let Ok(node) = parse_bytes(dict) {
-        if let Ok(output) = stringify_to_bytes(&node) {
-            assert_eq!(output, dict);
-            println!("Parsed and stringified dictionary successfully");
-        }
+    if let Ok(node) = parse_bytes(dict)
+        && let Ok(output) = stringify_to_bytes(&node)
+    {
+        assert_eq!(output, dict);
+        println!("Parsed and stringified dictionary successfully");
     }
 
     // This minimal build is perfect for:
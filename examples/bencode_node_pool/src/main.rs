@@ -0,0 +1,64 @@
+//! Benchmark of NodePool against plain parsing of small tracker messages.
+//!
+//! **Best Practice:** Use `NodePool` in services that parse many small messages
+//! and drop them straight away; recycling each tree lets the next parse reuse its
+//! allocations instead of going back to the allocator.
+//!
+//! Run with `cargo run --release --package bencode_node_pool` for meaningful timings.
+
+use bencode_lib::{parse_bytes, NodePool};
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+/// Number of messages parsed in each run
+const ITERATIONS: usize = 200_000;
+
+/// Typical tracker responses: an announce reply with a peer list and a failure
+const MESSAGES: [&[u8]; 3] = [
+    b"d8:completei12e10:incompletei3e8:intervali1800e12:min intervali900e5:peersld2:ip8:10.0.0.17:peer id20:-XX0001-aaaaaaaaaaaa4:porti6881eed2:ip8:10.0.0.27:peer id20:-XX0001-bbbbbbbbbbbb4:porti6882eeee",
+    b"d8:intervali1800e5:peers12:\x0a\x00\x00\x01\x1a\xe1\x0a\x00\x00\x02\x1a\xe2e",
+    b"d14:failure reason17:torrent not founde",
+];
+
+/// Parses every message ITERATIONS times, returning the elapsed time
+fn run(mut parse: impl FnMut(&[u8])) -> Duration {
+    let start = Instant::now();
+    for index in 0..ITERATIONS {
+        parse(MESSAGES[index % MESSAGES.len()]);
+    }
+    start.elapsed()
+}
+
+fn main() {
+    println!("=== NodePool Benchmark ===\n");
+    println!("Parsing {} tracker messages\n", ITERATIONS);
+
+    let before = run(|message| {
+        black_box(parse_bytes(message).unwrap());
+    });
+
+    // Parsing through a pool without recycling separates the gain from the
+    // slice based parser from the gain from reusing allocations
+    let mut unrecycled = NodePool::new();
+    let no_recycle = run(|message| {
+        black_box(unrecycled.parse(message).unwrap());
+    });
+
+    let mut pool = NodePool::new();
+    let after = run(|message| {
+        let node = pool.parse(message).unwrap();
+        pool.recycle(black_box(node));
+    });
+
+    let per_message = |elapsed: Duration| elapsed.as_nanos() / ITERATIONS as u128;
+    println!("   parse_bytes:      {:>8.2?} ({} ns/message)", before, per_message(before));
+    println!("   without recycle:  {:>8.2?} ({} ns/message)", no_recycle, per_message(no_recycle));
+    println!("   with recycle:     {:>8.2?} ({} ns/message)", after, per_message(after));
+    println!(
+        "   Speedup:          {:.2}x\n",
+        before.as_secs_f64() / after.as_secs_f64()
+    );
+    println!("   Allocations served from the pool: {}", pool.reused());
+    println!("   Allocations made by the pool:     {}", pool.allocated());
+    println!("   Free allocations retained:        {}", pool.retained());
+}
@@ -2,8 +2,9 @@
 //! This program processes all .torrent files in the "files" directory and displays their contents
 //! in a human-readable format.
 
-use bencode_utility_lib::get_torrent_file_list;
-use torrent_file::TorrentFile;
+use bencode_utility_lib::TorrentDirectory;
+use bencode_lib::torrent::{to_hex, TorrentFile};
+use bencode_lib::{human_date, human_size};
 
 /// Prints all metadata fields from a parsed torrent file in a human-readable format
 ///
@@ -11,34 +12,33 @@ use torrent_file::TorrentFile;
 ///
 /// * `torrent` - Reference to a TorrentFile struct containing the parsed metadata
 pub fn print_details(torrent: &TorrentFile) {
+    let text = |value: &Option<String>| value.clone().unwrap_or_default();
     println!("Successfully parsed torrent file:");
-    println!("Announce URL: {}", torrent.announce);
-    println!("Announce List URLs:");
-    for url in &torrent.announce_list {
-        println!("  - {}", url);
+    let announce = torrent.announce.as_ref().map(|url| url.to_string());
+    println!("Announce URL: {}", text(&announce));
+    println!("Announce List Tiers:");
+    for (index, tier) in torrent.announce_list.iter().enumerate() {
+        let urls: Vec<&str> = tier.iter().map(|url| url.as_str()).collect();
+        println!("  {}: {}", index + 1, urls.join(", "));
     }
-    println!("Encoding: {}", torrent.encoding);
-    println!("Attribute: {}", torrent.attribute);
-    println!("Comment: {}", torrent.comment);
-    println!("Creation Date: {}", torrent.creation_date);
-    println!("Created By: {}", torrent.created_by);
-    println!("Length: {} bytes", torrent.length);
-    println!("Name: {}", torrent.name);
-    println!("Piece Length: {}", torrent.piece_length);
-    println!(
-        "Pieces: {}",
-        torrent
-            .pieces
-            .as_bytes()
-            .iter()
-            .map(|b| format!("{:02x}", b))
-            .collect::<String>()
-    );
-    println!("Private Bit Mask: {}", torrent.private_flag);
-    println!("Source: {}", torrent.source);
+    println!("Tracker Hosts:");
+    for (host, urls) in torrent.trackers_by_host() {
+        println!("  {}: {} URL(s)", host, urls.len());
+    }
+    println!("Encoding: {}", text(&torrent.encoding));
+    println!("Comment: {}", text(&torrent.comment));
+    let creation_date = torrent.creation_date.and_then(|date| i64::try_from(date).ok());
+    println!("Creation Date: {}", creation_date.map(human_date).unwrap_or_default());
+    println!("Created By: {}", text(&torrent.created_by));
+    println!("Length: {}", human_size(torrent.total_length()));
+    println!("Name: {}", torrent.info.name);
+    println!("Piece Length: {}", human_size(torrent.info.piece_length));
+    println!("Pieces: {}", to_hex(&torrent.info.pieces));
+    println!("Private: {}", torrent.info.private);
+    println!("Source: {}", text(&torrent.info.source));
     println!("Files:");
-    for file in &torrent.files {
-        println!("  - {} ({} bytes)", file.path, file.length);
+    for file in &torrent.info.files {
+        println!("  - {} ({})", file.path.join("/"), human_size(file.length));
     }
 }
 
@@ -47,10 +47,17 @@ pub fn print_details(torrent: &TorrentFile) {
 /// 2. Attempts to parse each file as a torrent file
 /// 3. Displays the parsed metadata or any parsing errors
 fn main() {
-    let torrent_files = get_torrent_file_list("files");
-    for file_path in torrent_files {
+    let mut directory = TorrentDirectory::new("files");
+    let torrents = match directory.torrents() {
+        Ok(torrents) => torrents,
+        Err(e) => {
+            eprintln!("Error listing torrent files: {}", e);
+            return;
+        }
+    };
+    for (file_path, torrent) in torrents {
         println!("\nProcessing {:?}:", file_path);
-        match TorrentFile::from_file(file_path.as_ref()) {
+        match torrent {
             Ok(torrent) => {
                 print_details(&torrent);
             }
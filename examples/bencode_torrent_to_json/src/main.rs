@@ -3,7 +3,7 @@
 
 use std::path::Path;
 use bencode_lib::{FileSource, parse, FileDestination, to_json};
-use bencode_utility_lib::get_torrent_file_list;
+use bencode_utility_lib::TorrentScanner;
 
 /// Converts a single torrent file from bencode format to JSON format
 ///
@@ -13,13 +13,13 @@ use bencode_utility_lib::get_torrent_file_list;
 /// # Returns
 /// * `Ok(())` if conversion was successful
 /// * `Err(String)` containing the error message if conversion failed
-fn process_torrent_file(file_path: &str) -> Result<(), String> {
+fn process_torrent_file(file_path: &Path) -> Result<(), String> {
     // Create a source reader for the torrent file
     let mut source = FileSource::new(file_path).map_err(|e| e.to_string())?;
     // Parse the bencode data into an in-memory node structure
     let node = parse(&mut source).map_err(|e| e.to_string())?;
     // Create a destination writer for the JSON file with the same name but .json extension
-    let mut destination = FileDestination::new(Path::new(file_path).with_extension("json").to_string_lossy().as_ref()).map_err(|e| e.to_string())?;
+    let mut destination = FileDestination::new(file_path.with_extension("json")).map_err(|e| e.to_string())?;
     // Write the parsed data as JSON to the destination file
     to_json(&node, &mut destination).map_err(|e| e.to_string())?;
     Ok(())
@@ -28,12 +28,18 @@ fn process_torrent_file(file_path: &str) -> Result<(), String> {
 /// Main function that processes all torrent files in the "files" directory
 fn main() {
     // Get a list of torrent files from the "files" directory
-    let torrent_files = get_torrent_file_list("files");
+    let torrent_files = match TorrentScanner::new("files").scan() {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("Error listing torrent files: {}", e);
+            return;
+        }
+    };
     // Process each torrent file
     for file_path in torrent_files {
         match process_torrent_file(&file_path) {
-            Ok(()) => println!("Successfully converted {}", file_path),
-            Err(e) => eprintln!("Failed to convert {}: {}", file_path, e),
+            Ok(()) => println!("Successfully converted {}", file_path.display()),
+            Err(e) => eprintln!("Failed to convert {}: {}", file_path.display(), e),
         }
     }
 }
@@ -2,7 +2,7 @@
 //! It processes files from a specified directory and creates corresponding XML outputs.
 
 use bencode_lib::{FileDestination, FileSource, parse, to_xml};
-use bencode_utility_lib::get_torrent_file_list;
+use bencode_utility_lib::TorrentScanner;
 use std::path::Path;
 
 /// Converts a single torrent file from bencode format to XML format.
@@ -13,18 +13,13 @@ use std::path::Path;
 /// # Returns
 /// * `Ok(())` if conversion was successful
 /// * `Err(String)` containing an error message if conversion failed
-fn process_torrent_file(file_path: &str) -> Result<(), String> {
+fn process_torrent_file(file_path: &Path) -> Result<(), String> {
     // Create a file source for reading the torrent file
     let mut source = FileSource::new(file_path).map_err(|e| e.to_string())?;
     // Parse the bencode content into a node structure
     let node = parse(&mut source).map_err(|e| e.to_string())?;
     // Create a destination file with .xml extension
-    let mut destination = FileDestination::new(
-        Path::new(file_path)
-            .with_extension("xml")
-            .to_string_lossy()
-            .as_ref(),
-    )
+    let mut destination = FileDestination::new(file_path.with_extension("xml"))
     .map_err(|e| e.to_string())?;
     // Convert the node structure to XML and write to destination
     to_xml(&node, &mut destination).map_err(|e| e.to_string())?;
@@ -33,12 +28,18 @@ fn process_torrent_file(file_path: &str) -> Result<(), String> {
 
 fn main() {
     // Get a list of torrent files from the 'files' directory
-    let torrent_files = get_torrent_file_list("files");
+    let torrent_files = match TorrentScanner::new("files").scan() {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("Error listing torrent files: {}", e);
+            return;
+        }
+    };
     // Process each torrent file and convert to XML
     for file_path in torrent_files {
         match process_torrent_file(&file_path) {
-            Ok(()) => println!("Successfully converted {}", file_path),
-            Err(e) => eprintln!("Failed to convert {}: {}", file_path, e),
+            Ok(()) => println!("Successfully converted {}", file_path.display()),
+            Err(e) => eprintln!("Failed to convert {}: {}", file_path.display(), e),
         }
     }
 }
@@ -3,7 +3,7 @@
 
 use std::path::Path;
 use bencode_lib::{FileSource, parse, FileDestination, to_yaml};
-use bencode_utility_lib::get_torrent_file_list;
+use bencode_utility_lib::TorrentScanner;
 
 /// Converts a single torrent file from bencode to YAML format
 ///
@@ -15,24 +15,30 @@ use bencode_utility_lib::get_torrent_file_list;
 ///
 /// * `Ok(())` if conversion was successful
 /// * `Err(String)` containing an error message if conversion failed
-fn process_torrent_file(file_path: &str) -> Result<(), String> {
+fn process_torrent_file(file_path: &Path) -> Result<(), String> {
     // Open and read the source torrent file
     let mut source = FileSource::new(file_path).map_err(|e| e.to_string())?;
     // Parse the bencode data into an internal representation
     let node = parse(&mut source).map_err(|e| e.to_string())?;
     // Create output YAML file with same name but .yaml extension
-    let mut destination = FileDestination::new(Path::new(file_path).with_extension("yaml").to_string_lossy().as_ref()).map_err(|e| e.to_string())?;
+    let mut destination = FileDestination::new(file_path.with_extension("yaml")).map_err(|e| e.to_string())?;
     // Convert and write the data in YAML format
     to_yaml(&node, &mut destination)?;
     Ok(())
 }
 
 fn main() {
-    let torrent_files = get_torrent_file_list("files");
+    let torrent_files = match TorrentScanner::new("files").scan() {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("Error listing torrent files: {}", e);
+            return;
+        }
+    };
     for file_path in torrent_files {
         match process_torrent_file(&file_path) {
-            Ok(()) => println!("Successfully converted {}", file_path),
-            Err(e) => eprintln!("Failed to convert {}: {}", file_path, e),
+            Ok(()) => println!("Successfully converted {}", file_path.display()),
+            Err(e) => eprintln!("Failed to convert {}: {}", file_path.display(), e),
         }
     }
 }
@@ -0,0 +1,210 @@
+//! Cached scanning of a directory of torrent files.
+//!
+//! [`TorrentDirectory`] remembers the result of its last scan together with the
+//! modification time of every directory it read, and only rescans once one of
+//! those times changes. The scanning itself is done by a [`TorrentScanner`],
+//! so symbolic links are followed and each directory is read at most once. It
+//! never creates or modifies anything on disk.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use bencode_lib::torrent::TorrentFile;
+
+use crate::scanner::TorrentScanner;
+
+/// Default extension of the files listed by a `TorrentDirectory`
+pub const TORRENT_EXTENSION: &str = "torrent";
+
+/// Result of the last scan and the directory times it is valid for
+struct Scan {
+    /// Modification time of every directory read during the scan
+    directories: Vec<(PathBuf, SystemTime)>,
+    /// Matching files, sorted
+    paths: Vec<PathBuf>,
+}
+
+impl Scan {
+    /// Returns true if no scanned directory has changed since the scan
+    fn is_current(&self) -> bool {
+        self.directories
+            .iter()
+            .all(|(dir, mtime)| modified(dir).is_ok_and(|now| now == *mtime))
+    }
+}
+
+/// Returns the modification time of a file or directory
+fn modified(path: &Path) -> Result<SystemTime, String> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// A directory of torrent files with scanning options and a cached file list.
+///
+/// # Example
+/// ```no_run
+/// use bencode_utility_lib::TorrentDirectory;
+///
+/// let mut directory = TorrentDirectory::new("files").with_recursive(true);
+/// for (path, torrent) in directory.torrents().unwrap() {
+///     match torrent {
+///         Ok(torrent) => println!("{}: {}", path.display(), torrent.info.name),
+///         Err(e) => eprintln!("{}: {}", path.display(), e),
+///     }
+/// }
+/// ```
+pub struct TorrentDirectory {
+    scanner: TorrentScanner,
+    cache: Option<Scan>,
+}
+
+impl TorrentDirectory {
+    /// Creates a scanner for `.torrent` files directly inside `root`.
+    ///
+    /// Nothing is read until the first call to [`paths`](Self::paths) or
+    /// [`torrents`](Self::torrents).
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            scanner: TorrentScanner::new(root),
+            cache: None,
+        }
+    }
+
+    /// Sets the extension (without the dot) of the files to list
+    pub fn with_extension(mut self, extension: &str) -> Self {
+        self.scanner = self.scanner.with_extension(extension);
+        self.cache = None;
+        self
+    }
+
+    /// Sets whether subdirectories are scanned too
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.scanner = self.scanner.with_recursive(recursive);
+        self.cache = None;
+        self
+    }
+
+    /// Returns the directory being scanned
+    pub fn root(&self) -> &Path {
+        self.scanner.root()
+    }
+
+    /// Discards the cached file list so the next call rescans.
+    ///
+    /// Only needed when files may change without changing their directory's
+    /// modification time (e.g. on filesystems with coarse timestamps).
+    pub fn refresh(&mut self) {
+        self.cache = None;
+    }
+
+    /// Returns the matching files in sorted order, rescanning only if a scanned
+    /// directory has changed since the last call.
+    ///
+    /// # Returns
+    /// * `Ok(Iterator)` - The paths of the matching files
+    /// * `Err(String)` - The root (or a subdirectory) could not be read; a
+    ///   missing root is an error rather than being created
+    pub fn paths(&mut self) -> Result<impl Iterator<Item = PathBuf> + '_, String> {
+        Ok(self.scan()?.iter().cloned())
+    }
+
+    /// Returns each matching file together with its lazily parsed contents.
+    ///
+    /// Files are only read and parsed as the iterator advances, so a caller can
+    /// stop early without touching the rest.
+    pub fn torrents(&mut self) -> Result<impl Iterator<Item = (PathBuf, Result<TorrentFile, String>)> + '_, String> {
+        Ok(self.paths()?.map(|path| {
+            let torrent = TorrentFile::from_file(&path);
+            (path, torrent)
+        }))
+    }
+
+    /// Returns the cached file list, rescanning first if it is stale
+    fn scan(&mut self) -> Result<&[PathBuf], String> {
+        if !self.cache.as_ref().is_some_and(Scan::is_current) {
+            let mut directories = Vec::new();
+            // Read each time before its directory so a change during the scan invalidates it
+            let paths = self
+                .scanner
+                .scan_visiting(|dir| {
+                    let mtime = fs::metadata(dir)?.modified()?;
+                    directories.push((dir.to_path_buf(), mtime));
+                    Ok(())
+                })
+                .map_err(|e| format!("Failed to read {}: {}", self.scanner.root().display(), e))?;
+            self.cache = Some(Scan { directories, paths });
+        }
+        Ok(self.cache.as_ref().map_or(&[], |scan| scan.paths.as_slice()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_dir;
+
+    const TORRENT: &[u8] = b"d8:announce17:http://t/announce4:infod6:lengthi0e4:name4:demo12:piece lengthi16e6:pieces0:ee";
+
+    #[test]
+    fn lists_matching_files_sorted() {
+        let dir = create_test_dir(
+            "test_directory_list",
+            &[("b.torrent", TORRENT), ("a.torrent", TORRENT), ("c.txt", b""), ("sub/d.torrent", TORRENT)],
+        );
+        let mut directory = TorrentDirectory::new(&dir);
+        let paths: Vec<PathBuf> = directory.paths().unwrap().collect();
+        let mut recursive = TorrentDirectory::new(&dir).with_recursive(true);
+        let all = recursive.paths().unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(paths, vec![dir.join("a.torrent"), dir.join("b.torrent")]);
+        assert_eq!(all, 3);
+    }
+
+    #[test]
+    fn missing_directory_is_an_error_and_not_created() {
+        let mut directory = TorrentDirectory::new("test_directory_missing");
+        assert!(directory.paths().is_err());
+        assert!(!Path::new("test_directory_missing").exists());
+    }
+
+    #[test]
+    fn torrents_are_parsed_lazily() {
+        let dir = create_test_dir("test_directory_parse", &[("good.torrent", TORRENT), ("bad.torrent", b"i1e")]);
+        let mut directory = TorrentDirectory::new(&dir);
+        let results: Vec<_> = directory.torrents().unwrap().map(|(_, torrent)| torrent).collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(results[0].is_err());
+        assert_eq!(results[1].as_ref().map(|t| t.info.name.as_str()), Ok("demo"));
+    }
+
+    #[test]
+    fn cache_is_reused_until_refreshed() {
+        let dir = create_test_dir("test_directory_cache", &[("a.torrent", TORRENT)]);
+        let mut directory = TorrentDirectory::new(&dir);
+        assert_eq!(directory.paths().unwrap().count(), 1);
+        // Coarse directory timestamps may not change within the test, so refresh
+        // explicitly rather than relying on mtime invalidation
+        fs::write(dir.join("b.torrent"), TORRENT).unwrap();
+        directory.refresh();
+        let count = directory.paths().unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(count, 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn linked_directories_are_listed_once() {
+        let dir = create_test_dir("test_directory_symlink_loop", &[("a.torrent", TORRENT), ("sub/b.torrent", TORRENT)]);
+        std::os::unix::fs::symlink("..", dir.join("sub/back")).unwrap();
+        let mut directory = TorrentDirectory::new(&dir).with_recursive(true);
+        let paths: Vec<PathBuf> = directory.paths().unwrap().collect();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(paths, vec![dir.join("a.torrent"), dir.join("sub").join("b.torrent")]);
+    }
+}
@@ -0,0 +1,252 @@
+//! One-off listing of the torrent files below a directory.
+//!
+//! [`TorrentScanner`] walks a directory, optionally recursively, and returns the
+//! files that match its filter in sorted order. Unlike [`TorrentDirectory`]
+//! it keeps no cache and reports I/O errors as `std::io::Error`. It never
+//! creates or modifies anything on disk.
+//!
+//! [`TorrentDirectory`]: crate::TorrentDirectory
+
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::directory::TORRENT_EXTENSION;
+
+/// Bytes most torrent files start with: a root dictionary whose first key is `announce`
+pub const TORRENT_MAGIC: &[u8] = b"d8:announce";
+
+/// Which files a scan lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScanFilter {
+    /// Files with this extension (without the dot)
+    Extension(String),
+    /// Files whose contents start with these bytes, whatever their name
+    Prefix(Vec<u8>),
+}
+
+/// What a scan does with symbolic links.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+    /// List links to matching files and, when recursive, descend into linked
+    /// directories, visiting each directory at most once. Dangling links are skipped
+    #[default]
+    Follow,
+    /// Ignore links altogether
+    Skip,
+}
+
+/// Lists the torrent files below a directory.
+///
+/// # Example
+/// ```no_run
+/// use bencode_utility_lib::{ScanFilter, TORRENT_MAGIC, TorrentScanner};
+///
+/// let files = TorrentScanner::new("files")
+///     .with_recursive(true)
+///     .with_filter(ScanFilter::Prefix(TORRENT_MAGIC.to_vec()))
+///     .scan()
+///     .unwrap();
+/// for path in files {
+///     println!("{}", path.display());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TorrentScanner {
+    root: PathBuf,
+    recursive: bool,
+    filter: ScanFilter,
+    symlinks: SymlinkPolicy,
+}
+
+impl TorrentScanner {
+    /// Creates a scanner for `.torrent` files directly inside `root`, following
+    /// symbolic links.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            recursive: false,
+            filter: ScanFilter::Extension(TORRENT_EXTENSION.to_string()),
+            symlinks: SymlinkPolicy::Follow,
+        }
+    }
+
+    /// Sets whether subdirectories are scanned too
+    pub fn with_recursive(mut self, recursive: bool) -> Self {
+        self.recursive = recursive;
+        self
+    }
+
+    /// Sets the extension (without the dot) of the files to list
+    pub fn with_extension(self, extension: &str) -> Self {
+        self.with_filter(ScanFilter::Extension(extension.to_string()))
+    }
+
+    /// Sets which files are listed
+    pub fn with_filter(mut self, filter: ScanFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Sets what is done with symbolic links
+    pub fn with_symlinks(mut self, symlinks: SymlinkPolicy) -> Self {
+        self.symlinks = symlinks;
+        self
+    }
+
+    /// Returns the directory being scanned
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the matching files in sorted order.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<PathBuf>)` - The paths of the matching files, exactly as the
+    ///   operating system reported them (file names need not be valid UTF-8)
+    /// * `Err(io::Error)` - The root or a subdirectory could not be read, or a
+    ///   file could not be opened to check its prefix; a missing root is an
+    ///   error rather than being created
+    pub fn scan(&self) -> io::Result<Vec<PathBuf>> {
+        self.scan_visiting(|_| Ok(()))
+    }
+
+    /// Scans as [`scan`](Self::scan) does, calling `visit` with each directory
+    /// before it is read
+    pub(crate) fn scan_visiting(&self, mut visit: impl FnMut(&Path) -> io::Result<()>) -> io::Result<Vec<PathBuf>> {
+        let mut visited = HashSet::new();
+        let mut paths = Vec::new();
+        self.scan_directory(&self.root, &mut visited, &mut visit, &mut paths)?;
+        paths.sort();
+        Ok(paths)
+    }
+
+    /// Adds the matching files in `dir` (and its subdirectories if recursive) to `paths`
+    fn scan_directory(
+        &self,
+        dir: &Path,
+        visited: &mut HashSet<PathBuf>,
+        visit: &mut dyn FnMut(&Path) -> io::Result<()>,
+        paths: &mut Vec<PathBuf>,
+    ) -> io::Result<()> {
+        // A directory reached again through a link has already been listed
+        if !visited.insert(fs::canonicalize(dir)?) {
+            return Ok(());
+        }
+        visit(dir)?;
+        for entry in fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            let mut file_type = entry.file_type()?;
+            if file_type.is_symlink() {
+                if self.symlinks == SymlinkPolicy::Skip {
+                    continue;
+                }
+                match fs::metadata(&path) {
+                    Ok(metadata) => file_type = metadata.file_type(),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+                    Err(e) => return Err(e),
+                }
+            }
+            if file_type.is_dir() {
+                if self.recursive {
+                    self.scan_directory(&path, visited, visit, paths)?;
+                }
+            } else if file_type.is_file() && self.matches(&path)? {
+                paths.push(path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns true if the file passes the filter
+    fn matches(&self, path: &Path) -> io::Result<bool> {
+        match &self.filter {
+            ScanFilter::Extension(extension) => Ok(path.extension().is_some_and(|ext| ext == extension.as_str())),
+            ScanFilter::Prefix(prefix) => {
+                let mut start = Vec::with_capacity(prefix.len());
+                File::open(path)?.take(prefix.len() as u64).read_to_end(&mut start)?;
+                Ok(start == *prefix)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_dir;
+
+    #[test]
+    fn files_are_listed_by_extension_in_sorted_order() {
+        let dir = create_test_dir(
+            "test_scanner_extension",
+            &[("b.torrent", b""), ("a.torrent", b""), ("c.txt", b""), ("sub/d.torrent", b"")],
+        );
+        let flat = TorrentScanner::new(&dir).scan().unwrap();
+        let recursive = TorrentScanner::new(&dir).with_recursive(true).scan().unwrap();
+        let text = TorrentScanner::new(&dir).with_extension("txt").scan().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(flat, [dir.join("a.torrent"), dir.join("b.torrent")]);
+        assert_eq!(recursive.len(), 3);
+        assert_eq!(recursive[2], dir.join("sub").join("d.torrent"));
+        assert_eq!(text, [dir.join("c.txt")]);
+    }
+
+    #[test]
+    fn files_are_listed_by_prefix_whatever_their_name() {
+        let dir = create_test_dir(
+            "test_scanner_prefix",
+            &[("a.torrent", b"d8:announce3:urle"), ("b.torrent", b"de"), ("c.bin", b"d8:announce0:e"), ("d", b"d8")],
+        );
+        let files = TorrentScanner::new(&dir)
+            .with_filter(ScanFilter::Prefix(TORRENT_MAGIC.to_vec()))
+            .scan()
+            .unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(files, [dir.join("a.torrent"), dir.join("c.bin")]);
+    }
+
+    #[test]
+    fn missing_directory_is_an_error_and_not_created() {
+        let error = TorrentScanner::new("test_scanner_missing").scan().unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert!(!Path::new("test_scanner_missing").exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_file_names_are_kept() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        let dir = create_test_dir("test_scanner_non_utf8", &[]);
+        let name = OsStr::from_bytes(b"caf\xe9.torrent");
+        File::create(dir.join(name)).unwrap();
+        let files = TorrentScanner::new(&dir).scan();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(files.unwrap(), [dir.join(name)]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_follow_the_policy() {
+        use std::os::unix::fs::symlink;
+        let dir = create_test_dir("test_scanner_symlinks", &[("real/a.torrent", b"")]);
+        let absolute = fs::canonicalize(&dir).unwrap();
+        symlink(absolute.join("real/a.torrent"), dir.join("link.torrent")).unwrap();
+        symlink(absolute.join("missing.torrent"), dir.join("dangling.torrent")).unwrap();
+        // A link back to the root would loop forever if directories were revisited
+        symlink(&absolute, dir.join("real/loop")).unwrap();
+        let scanner = TorrentScanner::new(&dir).with_recursive(true);
+        let followed = scanner.scan();
+        let skipped = scanner.with_symlinks(SymlinkPolicy::Skip).scan();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(followed.unwrap(), [dir.join("link.torrent"), dir.join("real/a.torrent")]);
+        assert_eq!(skipped.unwrap(), [dir.join("real/a.torrent")]);
+    }
+}
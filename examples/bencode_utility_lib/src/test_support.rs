@@ -0,0 +1,20 @@
+//! Helpers shared by the unit tests.
+
+use std::fs;
+use std::path::PathBuf;
+
+/// Creates a fresh directory for the test `name` below the system temporary
+/// directory, holding `files` (paths relative to it, parent directories
+/// created as needed). The directory is unique to this process and emptied
+/// of anything an earlier run left there.
+pub(crate) fn create_test_dir(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("bencode_utility_lib_{}_{}", std::process::id(), name));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    for (file, content) in files {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+    dir
+}
@@ -3,7 +3,7 @@
 //! Demonstrates the new validation helper methods that make it easier
 //! to extract and validate required/optional fields from bencode dictionaries.
 
-use bencode_lib::{parse_bytes, Node};
+use bencode_lib::{human_size, parse_bytes, Node};
 
 fn main() {
     println!("=== Validation Helpers Demo ===\n");
@@ -18,8 +18,8 @@ fn main() {
                 println!("   ✓ Torrent validation passed");
                 println!("   - Announce: {}", info.announce);
                 println!("   - Name: {}", info.name);
-                println!("   - Size: {} bytes", info.length);
-                println!("   - Piece length: {}", info.piece_length);
+                println!("   - Size: {}", human_size(info.length as u64));
+                println!("   - Piece length: {}", human_size(info.piece_length as u64));
             }
             Err(e) => println!("   ✗ Validation failed: {}", e),
         },
@@ -177,7 +177,7 @@ fn validate_torrent(node: &Node) -> Result<TorrentInfo, String> {
     let piece_length = info.get_int_required("piece length")?;
 
     // Optional: validate pieces field exists
-    let _pieces = info.get_string_required("pieces")?;
+    let _pieces = info.get_bytes_required("pieces")?;
 
     // Validate values
     if length <= 0 {
@@ -4,7 +4,7 @@
 //! bencode data without allocating or copying the input data. This is ideal
 //! for memory-constrained embedded systems.
 
-use bencode_lib::{parse_borrowed, validate_bencode, BorrowedNode};
+use bencode_lib::{human_size, parse_borrowed, validate_bencode, BorrowedNode};
 
 fn main() {
     println!("=== Zero-Copy Bencode Parsing Example ===\n");
@@ -139,8 +139,10 @@ fn main() {
 
                 // Access info dictionary
                 if let Some(BorrowedNode::Dictionary(info)) = dict.get(&b"info"[..]) {
-                    if let Some(BorrowedNode::Integer(length)) = info.get(&b"length"[..]) {
-                        println!("File size: {} bytes", length);
+                    if let Some(BorrowedNode::Integer(length)) = info.get(&b"length"[..])
+                        && let Ok(length) = u64::try_from(*length)
+                    {
+                        println!("File size: {}", human_size(length));
                     }
                     if let Some(BorrowedNode::Bytes(name)) = info.get(&b"name"[..]) {
                         println!("File name: {}", core::str::from_utf8(name).unwrap());
@@ -0,0 +1,411 @@
+//! Batch processing of bencode files with per-file structured reports.
+//!
+//! Each processed file yields a [`FileReport`] describing how long it took to
+//! parse, how large it was, how many nodes it contained and any warnings raised,
+//! so that pathological files in large collections can be spotted programmatically.
+
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use crate::io::destinations::buffer::Buffer;
+use crate::io::traits::IDestination;
+use crate::misc::human_size;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::nodes::node::Node;
+use crate::nodes::string_kind::StringKind;
+use crate::parser::borrowed::parse_borrowed_prefix;
+
+/// Warning raised when bytes follow the root value of a file
+pub const WARN_TRAILING_DATA: &str = "Trailing data after root value";
+/// Warning raised when a dictionary key is not valid UTF-8, so the parsed
+/// tree holds it decoded lossily (binary values are kept exactly)
+pub const WARN_NON_UTF8_KEY: &str = "Dictionary key is not valid UTF-8 and was decoded lossily";
+/// Warning raised when a `name` field holds binary rather than text
+pub const WARN_BINARY_NAME: &str = "Name field is not valid UTF-8 text";
+/// Warning raised when the file is empty
+pub const WARN_EMPTY_FILE: &str = "File is empty";
+
+/// Structured result of processing a single bencode file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileReport {
+    /// Path of the processed file
+    pub path: PathBuf,
+    /// Size of the file in bytes
+    pub size: u64,
+    /// Time spent parsing the file (excluding the read from disk)
+    pub duration: Duration,
+    /// Total number of nodes (integers, strings, lists and dictionaries) parsed
+    pub node_count: usize,
+    /// Maximum nesting depth of the parsed tree
+    pub max_depth: usize,
+    /// Non-fatal anomalies detected in the file
+    pub warnings: Vec<String>,
+    /// Error message if the file could not be read, parsed or handled
+    pub error: Option<String>,
+}
+
+impl FileReport {
+    /// Creates an empty report for the given path
+    fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            size: 0,
+            duration: Duration::ZERO,
+            node_count: 0,
+            max_depth: 0,
+            warnings: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Returns true if the file was processed without error
+    pub fn is_ok(&self) -> bool {
+        self.error.is_none()
+    }
+
+    /// Returns true if the file raised any warnings
+    pub fn has_warnings(&self) -> bool {
+        !self.warnings.is_empty()
+    }
+}
+
+impl fmt::Display for FileReport {
+    /// Writes a one line summary, such as
+    /// `a.torrent: 1.2 MiB, 340 nodes, depth 5, parsed in 1.03ms`, followed by
+    /// the error or any warnings.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.path.display(), human_size(self.size))?;
+        if let Some(error) = &self.error {
+            return write!(f, ", error: {}", error);
+        }
+        write!(
+            f,
+            ", {} nodes, depth {}, parsed in {:.2?}",
+            self.node_count, self.max_depth, self.duration
+        )?;
+        if self.has_warnings() {
+            write!(f, ", warnings: {}", self.warnings.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads and parses every file in `paths`, handing each parsed tree to `handler`.
+///
+/// A failure in one file (read, parse or handler error) is recorded in that file's
+/// report and does not stop the rest of the batch.
+///
+/// # Arguments
+/// * `paths` - The files to process
+/// * `handler` - Called with the path and parsed root node of each file
+///
+/// # Returns
+/// One `FileReport` per input path, in the same order
+pub fn process_files<P, F>(paths: &[P], mut handler: F) -> Vec<FileReport>
+where
+    P: AsRef<Path>,
+    F: FnMut(&Path, &Node) -> Result<(), String>,
+{
+    process_file_data(paths, |path, _, node| handler(path, node))
+}
+
+/// Processes files as [`process_files`] does, also handing `handler` the bytes
+/// each tree was parsed from
+pub(crate) fn process_file_data<P, F>(paths: &[P], mut handler: F) -> Vec<FileReport>
+where
+    P: AsRef<Path>,
+    F: FnMut(&Path, &[u8], &Node) -> Result<(), String>,
+{
+    paths
+        .iter()
+        .map(|path| {
+            let path = path.as_ref();
+            let mut report = FileReport::new(path);
+            let data = match fs::read(path) {
+                Ok(data) => data,
+                Err(e) => {
+                    report.error = Some(e.to_string());
+                    return report;
+                }
+            };
+            report.size = data.len() as u64;
+            if data.is_empty() {
+                report.warnings.push(WARN_EMPTY_FILE.to_string());
+            }
+
+            // One zero-copy parse serves both the warnings, which need the exact
+            // key bytes and where the root value ends, and the owned tree
+            let start = Instant::now();
+            let parsed = parse_borrowed_prefix(&data).map(|(root, end)| (Node::from(&root), root, end));
+            report.duration = start.elapsed();
+
+            match parsed {
+                Ok((node, root, end)) => {
+                    let (count, depth) = count_nodes(&node);
+                    report.node_count = count;
+                    report.max_depth = depth;
+                    collect_warnings(&root, end < data.len(), &mut report.warnings);
+                    if let Err(e) = handler(path, &data, &node) {
+                        report.error = Some(e);
+                    }
+                }
+                Err(e) => report.error = Some(e.into()),
+            }
+            report
+        })
+        .collect()
+}
+
+/// Reads and parses every file in `paths`, returning only the per-file reports.
+///
+/// # Arguments
+/// * `paths` - The files to parse
+///
+/// # Returns
+/// One `FileReport` per input path, in the same order
+pub fn parse_files<P: AsRef<Path>>(paths: &[P]) -> Vec<FileReport> {
+    process_files(paths, |_, _| Ok(()))
+}
+
+/// Converts every file in `paths` with `converter`, writing the output into
+/// `output_dir` using the input file stem and the given extension.
+///
+/// # Arguments
+/// * `paths` - The bencode files to convert
+/// * `output_dir` - Directory the converted files are written to
+/// * `extension` - Extension of the converted files (e.g. "json")
+/// * `converter` - Conversion function such as `to_json` or `to_yaml`
+///
+/// # Returns
+/// One `FileReport` per input path, in the same order
+pub fn convert_files<P: AsRef<Path>>(
+    paths: &[P],
+    output_dir: &Path,
+    extension: &str,
+    converter: fn(&Node, &mut dyn IDestination) -> Result<(), String>,
+) -> Vec<FileReport> {
+    process_files(paths, |path, node| {
+        let mut destination = Buffer::new();
+        converter(node, &mut destination)?;
+        let stem = path.file_stem().unwrap_or(path.as_os_str());
+        let output = output_dir.join(stem).with_extension(extension);
+        fs::write(&output, &destination.buffer).map_err(|e| e.to_string())
+    })
+}
+
+/// Scans a directory (non-recursively) for files with the given extension and
+/// parses each one.
+///
+/// # Arguments
+/// * `dir` - The directory to scan
+/// * `extension` - Extension of the files to parse (e.g. "torrent")
+///
+/// # Returns
+/// * `Ok(Vec<FileReport>)` - One report per matching file, sorted by path
+/// * `Err(std::io::Error)` - If the directory could not be read
+pub fn scan_directory(dir: &Path, extension: &str) -> std::io::Result<Vec<FileReport>> {
+    Ok(parse_files(&list_files(dir, extension)?))
+}
+
+/// Lists the files in a directory (non-recursively) with the given extension, sorted by path
+pub(crate) fn list_files(dir: &Path, extension: &str) -> std::io::Result<Vec<PathBuf>> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == extension))
+        .collect();
+    paths.sort();
+    Ok(paths)
+}
+
+/// Returns up to `n` successfully parsed reports ordered by descending parse time.
+pub fn slowest(reports: &[FileReport], n: usize) -> Vec<&FileReport> {
+    let mut sorted: Vec<_> = reports.iter().filter(|r| r.is_ok()).collect();
+    sorted.sort_by_key(|r| core::cmp::Reverse(r.duration));
+    sorted.truncate(n);
+    sorted
+}
+
+/// Counts the nodes in a tree and measures its maximum nesting depth.
+fn count_nodes(node: &Node) -> (usize, usize) {
+    match node {
+        Node::List(items) => items.iter().fold((1, 1), |(count, depth), item| {
+            let (c, d) = count_nodes(item);
+            (count + c, depth.max(d + 1))
+        }),
+        Node::Dictionary(items) => items.values().fold((1, 1), |(count, depth), item| {
+            let (c, d) = count_nodes(item);
+            (count + c, depth.max(d + 1))
+        }),
+        Node::None => (0, 0),
+        _ => (1, 1),
+    }
+}
+
+/// Detects non-fatal anomalies in a parsed root value, which `trailing` tells
+/// was followed by more data.
+fn collect_warnings(root: &BorrowedNode, trailing: bool, warnings: &mut Vec<String>) {
+    if trailing {
+        warnings.push(WARN_TRAILING_DATA.to_string());
+    }
+    if has_non_utf8_key(root) {
+        warnings.push(WARN_NON_UTF8_KEY.to_string());
+    }
+    if has_binary_name(root) {
+        warnings.push(WARN_BINARY_NAME.to_string());
+    }
+}
+
+/// Returns true if any dictionary key in the tree is not valid UTF-8.
+fn has_non_utf8_key(node: &BorrowedNode) -> bool {
+    match node {
+        BorrowedNode::List(items) => items.iter().any(has_non_utf8_key),
+        BorrowedNode::Dictionary(items) => items
+            .iter()
+            .any(|(k, v)| !StringKind::of_bytes(k).is_text() || has_non_utf8_key(v)),
+        _ => false,
+    }
+}
+
+/// Returns true if any `name` entry in the tree (torrent or file name) is binary.
+fn has_binary_name(node: &BorrowedNode) -> bool {
+    match node {
+        BorrowedNode::List(items) => items.iter().any(has_binary_name),
+        BorrowedNode::Dictionary(items) => items.iter().any(|(k, v)| {
+            (*k == b"name" && v.string_kind() == Some(StringKind::Binary)) || has_binary_name(v)
+        }),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_dir;
+
+    #[test]
+    fn parse_files_reports_size_and_node_count() {
+        let dir = create_test_dir("test_batch_counts", &[("a.torrent", b"d1:ali1ei2ee1:bi3ee")]);
+        let reports = parse_files(&[dir.join("a.torrent")]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].is_ok());
+        assert_eq!(reports[0].size, 19);
+        assert_eq!(reports[0].node_count, 5);
+        assert_eq!(reports[0].max_depth, 3);
+        assert!(!reports[0].has_warnings());
+    }
+
+    #[test]
+    fn parse_files_isolates_errors() {
+        let dir = create_test_dir("test_batch_errors", &[("good.torrent", b"i1e"), ("bad.torrent", b"i1")]);
+        let reports = parse_files(&[
+            dir.join("bad.torrent"),
+            dir.join("missing.torrent"),
+            dir.join("good.torrent"),
+        ]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(reports[0].error.as_deref(), Some("Unterminated integer"));
+        assert!(reports[1].error.is_some());
+        assert!(reports[2].is_ok());
+    }
+
+    #[test]
+    fn parse_files_collects_warnings() {
+        let dir = create_test_dir(
+            "test_batch_warnings",
+            &[("trailing.torrent", b"i1eXX"), ("key.torrent", b"d2:\xFF\xFEi1ee"), ("empty.torrent", b"")],
+        );
+        let reports = parse_files(&[
+            dir.join("trailing.torrent"),
+            dir.join("key.torrent"),
+            dir.join("empty.torrent"),
+        ]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(reports[0].warnings, vec![WARN_TRAILING_DATA.to_string()]);
+        assert_eq!(reports[1].warnings, vec![WARN_NON_UTF8_KEY.to_string()]);
+        assert!(reports[2].warnings.contains(&WARN_EMPTY_FILE.to_string()));
+        assert!(!reports[2].is_ok());
+    }
+
+    #[test]
+    fn parse_files_warns_about_binary_names() {
+        let dir = create_test_dir(
+            "test_batch_binary_name",
+            &[("name.torrent", b"d4:infod4:name2:\xFF\xFEee"), ("pieces.torrent", b"d6:pieces1:\xFFe")],
+        );
+        let reports = parse_files(&[dir.join("name.torrent"), dir.join("pieces.torrent")]);
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Binary values such as pieces are kept exactly, so only the name is flagged
+        assert_eq!(reports[0].warnings, vec![WARN_BINARY_NAME.to_string()]);
+        assert!(!reports[1].has_warnings());
+    }
+
+    #[test]
+    fn scan_directory_filters_by_extension() {
+        let dir = create_test_dir(
+            "test_batch_scan",
+            &[("b.torrent", b"i2e"), ("a.torrent", b"i1e"), ("c.txt", b"i3e")],
+        );
+        let reports = scan_directory(&dir, "torrent").unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(reports.len(), 2);
+        assert!(reports[0].path.ends_with("a.torrent"));
+        assert!(reports[1].path.ends_with("b.torrent"));
+    }
+
+    #[test]
+    fn scan_missing_directory_fails() {
+        assert!(scan_directory(Path::new("test_batch_missing_dir"), "torrent").is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn convert_files_writes_output() {
+        let dir = create_test_dir("test_batch_convert", &[("a.torrent", b"d1:ai1ee")]);
+        let reports = convert_files(&[dir.join("a.torrent")], &dir, "json", crate::to_json);
+        let output = fs::read_to_string(dir.join("a.json"));
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(reports[0].is_ok());
+        assert_eq!(output.unwrap(), "{\"a\":1}");
+    }
+
+    #[test]
+    fn slowest_orders_by_duration() {
+        let mut fast = FileReport::new(Path::new("fast"));
+        fast.duration = Duration::from_millis(1);
+        let mut slow = FileReport::new(Path::new("slow"));
+        slow.duration = Duration::from_millis(10);
+        let mut failed = FileReport::new(Path::new("failed"));
+        failed.duration = Duration::from_millis(100);
+        failed.error = Some("error".to_string());
+
+        let reports = [fast, slow, failed];
+        let top = slowest(&reports, 5);
+        assert_eq!(top.len(), 2);
+        assert_eq!(top[0].path, PathBuf::from("slow"));
+    }
+
+    #[test]
+    fn reports_display_human_sizes() {
+        let mut report = FileReport::new(Path::new("a.torrent"));
+        report.size = 1536;
+        report.node_count = 12;
+        report.max_depth = 3;
+        report.duration = Duration::from_millis(2);
+        report.warnings.push(WARN_TRAILING_DATA.to_string());
+        assert_eq!(
+            report.to_string(),
+            "a.torrent: 1.5 KiB, 12 nodes, depth 3, parsed in 2.00ms, warnings: Trailing data after root value"
+        );
+        report.error = Some("Unterminated integer".to_string());
+        assert_eq!(report.to_string(), "a.torrent: 1.5 KiB, error: Unterminated integer");
+    }
+}
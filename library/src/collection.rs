@@ -0,0 +1,222 @@
+//! Aggregate statistics across a directory of torrent files.
+//!
+//! [`analyze`] parses every `.torrent` file in a directory with the batch
+//! machinery and folds the results into a [`CollectionStats`]: how payload sizes
+//! are distributed, which trackers the collection leans on, which piece lengths
+//! are in use and which torrents are the same torrent stored more than once.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::batch::{FileReport, list_files, process_file_data};
+use crate::misc::human_size;
+use crate::storage::{InfoHash, TORRENT_EXTENSION};
+use crate::torrent::metainfo::TorrentFile;
+use crate::torrent::{info_hash, to_hex};
+
+/// Statistics gathered over a collection of torrent files.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CollectionStats {
+    /// One report per file, sorted by path; a file that is not a valid torrent
+    /// carries the reason as its error
+    pub reports: Vec<FileReport>,
+    /// Number of valid torrents
+    pub torrents: usize,
+    /// Total payload length of the valid torrents in bytes
+    pub total_length: u64,
+    /// Number of torrents per payload size bucket, keyed by the bucket's lower
+    /// bound: the largest power of two not above the payload length (0 for empty payloads)
+    pub size_distribution: BTreeMap<u64, usize>,
+    /// Number of torrents announcing to each tracker host, most used first
+    pub trackers: Vec<(String, usize)>,
+    /// Number of torrents per piece length
+    pub piece_lengths: BTreeMap<u64, usize>,
+    /// Info hashes found in more than one file, with those files in path order
+    pub duplicates: Vec<(InfoHash, Vec<PathBuf>)>,
+}
+
+impl CollectionStats {
+    /// Returns the reports of files that could not be read, parsed or read as a torrent
+    pub fn failures(&self) -> impl Iterator<Item = &FileReport> {
+        self.reports.iter().filter(|report| !report.is_ok())
+    }
+
+    /// Returns the number of files that are not duplicates of an earlier one
+    pub fn unique_torrents(&self) -> usize {
+        self.torrents - self.duplicates.iter().map(|(_, paths)| paths.len() - 1).sum::<usize>()
+    }
+}
+
+impl fmt::Display for CollectionStats {
+    /// Writes a multi line summary of the collection
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} files, {} torrents ({} unique), {} failed, {} of payload",
+            self.reports.len(),
+            self.torrents,
+            self.unique_torrents(),
+            self.failures().count(),
+            human_size(self.total_length)
+        )?;
+        writeln!(f, "Payload sizes:")?;
+        for (bucket, count) in &self.size_distribution {
+            writeln!(f, "  >= {}: {}", human_size(*bucket), count)?;
+        }
+        writeln!(f, "Piece lengths:")?;
+        for (piece_length, count) in &self.piece_lengths {
+            writeln!(f, "  {}: {}", human_size(*piece_length), count)?;
+        }
+        writeln!(f, "Trackers:")?;
+        for (host, count) in &self.trackers {
+            writeln!(f, "  {}: {}", host, count)?;
+        }
+        writeln!(f, "Duplicates:")?;
+        for (hash, paths) in &self.duplicates {
+            let paths: Vec<_> = paths.iter().map(|path| path.display().to_string()).collect();
+            writeln!(f, "  {}: {}", to_hex(hash), paths.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Returns the lower bound of the payload size bucket holding `length`
+fn size_bucket(length: u64) -> u64 {
+    match length {
+        0 => 0,
+        _ => 1 << length.ilog2(),
+    }
+}
+
+/// Parses every `.torrent` file in `dir` (non-recursively) and gathers
+/// statistics over the collection.
+///
+/// Info hashes are taken over the bytes of each file's `info` dictionary, as
+/// [`info_hash`] does, so a torrent has the same identity here as in a
+/// [`TorrentStore`](crate::storage::TorrentStore).
+///
+/// # Arguments
+/// * `dir` - The directory holding the torrents
+///
+/// # Returns
+/// * `Ok(CollectionStats)` - Statistics over the files in the directory
+/// * `Err(std::io::Error)` - If the directory could not be read
+///
+/// # Example
+/// ```no_run
+/// use bencode_lib::collection::analyze;
+///
+/// let stats = analyze("torrents".as_ref()).unwrap();
+/// println!("{}", stats);
+/// for (hash, paths) in &stats.duplicates {
+///     println!("{} files share one info hash: {:?}", paths.len(), paths);
+/// }
+/// ```
+pub fn analyze(dir: &Path) -> std::io::Result<CollectionStats> {
+    let paths = list_files(dir, TORRENT_EXTENSION)?;
+    let mut stats = CollectionStats::default();
+    let mut trackers: BTreeMap<String, usize> = BTreeMap::new();
+    let mut hashes: BTreeMap<InfoHash, Vec<PathBuf>> = BTreeMap::new();
+    stats.reports = process_file_data(&paths, |path, data, node| {
+        let torrent = TorrentFile::from_node(node)?;
+        let hash = info_hash(data)?;
+        let length = torrent.total_length();
+        stats.torrents += 1;
+        stats.total_length = stats.total_length.saturating_add(length);
+        *stats.size_distribution.entry(size_bucket(length)).or_default() += 1;
+        *stats.piece_lengths.entry(torrent.info.piece_length).or_default() += 1;
+        for (host, _) in torrent.trackers_by_host() {
+            *trackers.entry(host.to_string()).or_default() += 1;
+        }
+        hashes.entry(hash).or_default().push(path.to_path_buf());
+        Ok(())
+    });
+
+    stats.trackers = trackers.into_iter().collect();
+    stats.trackers.sort_by_key(|(_, count)| core::cmp::Reverse(*count));
+    stats.duplicates = hashes.into_iter().filter(|(_, paths)| paths.len() > 1).collect();
+    stats.duplicates.sort_by(|a, b| a.1.cmp(&b.1));
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_dir;
+    use std::fs;
+
+    /// Encodes a single file torrent
+    fn torrent(tracker: &str, name: &str, length: u64, piece_length: u64) -> Vec<u8> {
+        let pieces = "h".repeat(20 * length.div_ceil(piece_length) as usize);
+        format!(
+            "d8:announce{}:{}4:infod6:lengthi{}e4:name{}:{}12:piece lengthi{}e6:pieces{}:{}ee",
+            tracker.len(),
+            tracker,
+            length,
+            name.len(),
+            name,
+            piece_length,
+            pieces.len(),
+            pieces
+        )
+        .into_bytes()
+    }
+
+    #[test]
+    fn aggregates_over_the_valid_torrents() {
+        let dir = create_test_dir(
+            "collection_aggregate",
+            &[
+                ("a.torrent", &torrent("udp://one:6969", "a", 3000, 1024)),
+                ("b.torrent", &torrent("http://one/announce", "b", 1024, 1024)),
+                ("c.torrent", &torrent("udp://two", "c", 1500, 512)),
+                ("d.torrent", &torrent("udp://one:6969", "a", 3000, 1024)),
+                ("e.torrent", b"d4:infoi1ee"),
+                ("f.torrent", b"l"),
+                ("notes.txt", b"not a torrent"),
+            ],
+        );
+        let stats = analyze(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(stats.reports.len(), 6);
+        assert_eq!(stats.torrents, 4);
+        assert_eq!(stats.unique_torrents(), 3);
+        assert_eq!(stats.total_length, 8524);
+        let failed: Vec<_> = stats.failures().map(|report| report.path.file_name().unwrap()).collect();
+        assert_eq!(failed, ["e.torrent", "f.torrent"]);
+        assert_eq!(stats.size_distribution, BTreeMap::from([(1024, 2), (2048, 2)]));
+        assert_eq!(stats.piece_lengths, BTreeMap::from([(512, 1), (1024, 3)]));
+        assert_eq!(stats.trackers, [("one".to_string(), 3), ("two".to_string(), 1)]);
+        assert_eq!(stats.duplicates.len(), 1);
+        assert_eq!(stats.duplicates[0].1, [dir.join("a.torrent"), dir.join("d.torrent")]);
+    }
+
+    #[test]
+    fn duplicates_are_identified_by_the_hash_of_the_file() {
+        // A key that is not UTF-8 is decoded lossily, so re-encoding the info dictionary changes its hash
+        let data = b"d4:infod6:lengthi1e4:name1:a12:piece lengthi1e6:pieces20:hhhhhhhhhhhhhhhhhhhh1:\xffi0eee";
+        let dir = create_test_dir("collection_non_canonical", &[("a.torrent", data), ("b.torrent", data)]);
+        let stats = analyze(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(stats.torrents, 2);
+        assert_eq!(stats.duplicates.len(), 1);
+        assert_eq!(stats.duplicates[0].0, info_hash(data).unwrap());
+        assert_ne!(stats.duplicates[0].0, crate::torrent::info_hash_v1(&crate::parse_bytes(data).unwrap()).unwrap());
+    }
+
+    #[test]
+    fn summary_lists_each_statistic() {
+        let dir = create_test_dir("collection_summary", &[("a.torrent", &torrent("udp://one", "a", 0, 16384))]);
+        let summary = analyze(&dir).unwrap().to_string();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(summary.starts_with("1 files, 1 torrents (1 unique), 0 failed, 0 B of payload\n"));
+        assert!(summary.contains("Payload sizes:\n  >= 0 B: 1\n"));
+        assert!(summary.contains("Piece lengths:\n  16.0 KiB: 1\n"));
+        assert!(summary.contains("Trackers:\n  one: 1\nDuplicates:\n"));
+        assert!(analyze(&dir).is_err());
+    }
+}
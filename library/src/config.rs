@@ -1,23 +1,82 @@
 //! Configuration options for bencode parsing and encoding
 
-/// Configuration for the bencode parser
-#[derive(Debug, Clone, Copy)]
+/// Configuration for the bencode parser, enforced by
+/// [`parse_with_config`](crate::parse_with_config) and
+/// [`parse_bytes_with_config`](crate::parse_bytes_with_config)
+///
+/// Use [`ParserConfig::untrusted`] for input from the network or any other
+/// source that could be hostile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ParserConfig {
     /// Maximum depth of nested structures (default: 100)
     /// Set to prevent stack overflow from malicious deeply nested data
     pub max_depth: usize,
+    /// Maximum length in bytes of a string or dictionary key (default: no limit)
+    /// Checked against the length prefix, before any of the string is read
+    pub max_string_length: usize,
+    /// Maximum number of values in the document, containers included and
+    /// dictionary keys excluded (default: no limit)
+    pub max_elements: usize,
+    /// Maximum number of input bytes the parsed value may span (default: no limit)
+    pub max_input_size: usize,
+    /// Accept integers written with a leading `+`, such as `i+42e` (default: false)
+    pub allow_plus_sign: bool,
+    /// Accept whitespace before a value or the end of a list or dictionary (default: false)
+    pub allow_whitespace: bool,
+    /// Keep integers too large for the node integer type as `Node::BigInteger`
+    /// instead of failing with `IntegerOverflow` or `IntegerOutOfRange` (default: false)
+    pub big_integers: bool,
 }
 
 impl Default for ParserConfig {
     fn default() -> Self {
-        Self { max_depth: 100 }
+        Self::new()
     }
 }
 
 impl ParserConfig {
     /// Create a new parser configuration with default settings
     pub const fn new() -> Self {
-        Self { max_depth: 100 }
+        Self {
+            max_depth: 100,
+            max_string_length: usize::MAX,
+            max_elements: usize::MAX,
+            max_input_size: usize::MAX,
+            allow_plus_sign: false,
+            allow_whitespace: false,
+            big_integers: false,
+        }
+    }
+
+    /// Create a configuration with conservative limits for untrusted input,
+    /// recommended for anything network facing: nesting depth 32, strings up to
+    /// 8 MiB, at most 1 million values and 64 MiB of input.
+    ///
+    /// Strict key handling needs no setting, as the parser always rejects
+    /// dictionary keys that are repeated or out of order. To also reject
+    /// leading zeros and data after the root value, check the input with
+    /// [`validate_canonical`](crate::validate_canonical).
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{parse_bytes_with_config, ErrorKind, ParserConfig};
+    ///
+    /// let config = ParserConfig::untrusted();
+    /// assert!(parse_bytes_with_config(b"d4:porti6881ee", &config).is_ok());
+    /// let error = parse_bytes_with_config(b"d1:ai1e1:ai2ee", &config).unwrap_err();
+    /// assert_eq!(error.kind(), ErrorKind::DictKeysOrder);
+    /// ```
+    pub const fn untrusted() -> Self {
+        Self::new()
+            .with_max_depth(32)
+            .with_max_string_length(8 * 1024 * 1024)
+            .with_max_elements(1_000_000)
+            .with_max_input_size(64 * 1024 * 1024)
+    }
+
+    /// Create a configuration that enforces no limits at all
+    pub const fn unlimited() -> Self {
+        Self::new().with_max_depth(usize::MAX)
     }
     
     /// Set the maximum nesting depth
@@ -25,49 +84,243 @@ impl ParserConfig {
         self.max_depth = max_depth;
         self
     }
+
+    /// Set the maximum length of a string or dictionary key
+    pub const fn with_max_string_length(mut self, max_string_length: usize) -> Self {
+        self.max_string_length = max_string_length;
+        self
+    }
+
+    /// Set the maximum number of values in the document
+    pub const fn with_max_elements(mut self, max_elements: usize) -> Self {
+        self.max_elements = max_elements;
+        self
+    }
+
+    /// Set the maximum size of the input in bytes
+    pub const fn with_max_input_size(mut self, max_input_size: usize) -> Self {
+        self.max_input_size = max_input_size;
+        self
+    }
+
+    /// Set whether integers with a leading `+` are accepted, each reported as a
+    /// [`Deviation::PlusSign`](crate::Deviation::PlusSign) warning
+    pub const fn with_allow_plus_sign(mut self, allow: bool) -> Self {
+        self.allow_plus_sign = allow;
+        self
+    }
+
+    /// Set whether whitespace between values is skipped, each run reported as a
+    /// [`Deviation::Whitespace`](crate::Deviation::Whitespace) warning
+    pub const fn with_allow_whitespace(mut self, allow: bool) -> Self {
+        self.allow_whitespace = allow;
+        self
+    }
+
+    /// Set whether integers too large for the node integer type are kept as
+    /// [`Node::BigInteger`](crate::Node::BigInteger) rather than rejected
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{parse_bytes_with_config, stringify_to_bytes, ErrorKind, Node, ParserConfig};
+    ///
+    /// let data = b"d8:uploadedi99999999999999999999ee";
+    /// let error = parse_bytes_with_config(data, &ParserConfig::new()).unwrap_err();
+    /// assert_eq!(error.kind(), ErrorKind::IntegerOverflow);
+    ///
+    /// let node = parse_bytes_with_config(data, &ParserConfig::new().with_big_integers(true)).unwrap();
+    /// assert_eq!(node.get("uploaded"), Some(&Node::BigInteger("99999999999999999999".to_string())));
+    /// assert_eq!(stringify_to_bytes(&node).unwrap(), data);
+    /// ```
+    pub const fn with_big_integers(mut self, keep: bool) -> Self {
+        self.big_integers = keep;
+        self
+    }
+}
+
+/// How binary strings (byte strings that are not UTF-8) are written in the
+/// text formats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BinaryEncoding {
+    /// Printable ASCII as is, other bytes as `\u00XX` escapes (default)
+    #[default]
+    Escaped,
+    /// Lowercase hexadecimal, two digits per byte
+    Hex,
+    /// Standard base64 with padding
+    Base64,
 }
 
-/// Configuration for the bencode encoder
-#[derive(Debug, Clone, Copy)]
+/// Configuration for the encoders, used by
+/// [`stringify_with_config`](crate::stringify_with_config) and the
+/// `to_json_with_config` family of text conversions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct EncoderConfig {
-    /// Enforce canonical bencode format (default: true)
-    /// - Dictionary keys must be sorted
-    /// - No leading zeros in integers (except "0")
+    /// Has no effect: encoders always write canonical bencode
+    #[deprecated(note = "encoders always write canonical bencode; will be removed in the next breaking release")]
     pub enforce_canonical: bool,
-    
+
     /// Verify dictionary key ordering during encoding (default: true)
+    /// Pre-encoded raw values, which are copied verbatim, are checked
     pub verify_dict_order: bool,
+
+    /// Has no effect: every encoder writes dictionary entries in canonical key
+    /// order (see `Node::iter_sorted`)
+    #[deprecated(note = "encoders always sort dictionary keys; will be removed in the next breaking release")]
+    pub sort_keys: bool,
+
+    /// Write JSON over several lines, indented by `indent` (default: false)
+    pub pretty: bool,
+
+    /// Spaces per level of nesting in pretty JSON and in YAML (default: 2)
+    pub indent: usize,
+
+    /// How binary strings are written in JSON, YAML, XML and TOML (default: escaped)
+    pub binary: BinaryEncoding,
 }
 
 impl Default for EncoderConfig {
     fn default() -> Self {
-        Self {
-            enforce_canonical: true,
-            verify_dict_order: true,
-        }
+        Self::new()
     }
 }
 
 impl EncoderConfig {
     /// Create a new encoder configuration with default settings
+    #[allow(deprecated)]
     pub const fn new() -> Self {
         Self {
             enforce_canonical: true,
             verify_dict_order: true,
+            sort_keys: true,
+            pretty: false,
+            indent: 2,
+            binary: BinaryEncoding::Escaped,
         }
     }
     
-    /// Set whether to enforce canonical bencode format
+    /// Set whether to enforce canonical bencode format (no effect; output is always canonical)
+    #[deprecated(note = "encoders always write canonical bencode; will be removed in the next breaking release")]
+    #[allow(deprecated)]
     pub const fn with_canonical(mut self, enforce: bool) -> Self {
         self.enforce_canonical = enforce;
         self
     }
-    
+
     /// Set whether to verify dictionary key ordering
     pub const fn with_dict_order_verification(mut self, verify: bool) -> Self {
         self.verify_dict_order = verify;
         self
     }
+
+    /// Set whether dictionary entries are sorted by key (no effect; entries are always sorted)
+    #[deprecated(note = "encoders always sort dictionary keys; will be removed in the next breaking release")]
+    #[allow(deprecated)]
+    pub const fn with_sort_keys(mut self, sort: bool) -> Self {
+        self.sort_keys = sort;
+        self
+    }
+
+    /// Set whether JSON is written over several indented lines
+    pub const fn with_pretty(mut self, pretty: bool) -> Self {
+        self.pretty = pretty;
+        self
+    }
+
+    /// Set the spaces per level of nesting
+    pub const fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+
+    /// Set how binary strings are written in the text formats
+    pub const fn with_binary(mut self, binary: BinaryEncoding) -> Self {
+        self.binary = binary;
+        self
+    }
+}
+
+/// Limits on the work a parse may do, for parsing untrusted input on a service thread
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseBudget {
+    /// Maximum number of parser steps (roughly one per value or dictionary entry), or None for no limit
+    pub max_steps: Option<usize>,
+    /// Instant after which parsing is abandoned, or None for no deadline
+    #[cfg(feature = "std")]
+    pub deadline: Option<std::time::Instant>,
+}
+
+impl ParseBudget {
+    /// Create an unlimited budget
+    pub const fn new() -> Self {
+        Self {
+            max_steps: None,
+            #[cfg(feature = "std")]
+            deadline: None,
+        }
+    }
+
+    /// Set the maximum number of parser steps
+    pub const fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = Some(max_steps);
+        self
+    }
+
+    /// Set the instant after which parsing is abandoned
+    #[cfg(feature = "std")]
+    pub const fn with_deadline(mut self, deadline: std::time::Instant) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Set a deadline `timeout` from now
+    #[cfg(feature = "std")]
+    pub fn with_timeout(self, timeout: std::time::Duration) -> Self {
+        self.with_deadline(std::time::Instant::now() + timeout)
+    }
+}
+
+/// What is kept of a file before a save helper overwrites it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BackupPolicy {
+    /// Overwrite the file without a backup (default)
+    #[default]
+    None,
+    /// Copy the file to `<name>.bak`, replacing any earlier backup
+    Single,
+    /// Copy the file to `<name>.<milliseconds since the epoch>.bak`, keeping
+    /// at most this many backups and removing the oldest
+    Timestamped(usize),
+}
+
+/// Options for the helpers that write files: [`write_file_with_options`](crate::write_file_with_options),
+/// [`FileDestination::with_options`](crate::FileDestination::with_options) and
+/// [`TorrentFile::write_file_with_options`](crate::torrent::TorrentFile::write_file_with_options)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WriteOptions {
+    /// Backup taken when an existing file is overwritten (default: none)
+    pub backup: BackupPolicy,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WriteOptions {
+    /// Create options that overwrite files without a backup
+    pub const fn new() -> Self {
+        Self {
+            backup: BackupPolicy::None,
+        }
+    }
+
+    /// Set the backup taken before a file is overwritten
+    pub const fn with_backup(mut self, backup: BackupPolicy) -> Self {
+        self.backup = backup;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -86,19 +339,79 @@ mod tests {
         assert_eq!(config.max_depth, 50);
     }
 
+    #[test]
+    fn parser_config_limits() {
+        let config = ParserConfig::new()
+            .with_max_string_length(64)
+            .with_max_elements(1000)
+            .with_max_input_size(4096);
+        assert_eq!(config.max_depth, 100);
+        assert_eq!(config.max_string_length, 64);
+        assert_eq!(config.max_elements, 1000);
+        assert_eq!(config.max_input_size, 4096);
+        assert_eq!(ParserConfig::unlimited().max_depth, usize::MAX);
+    }
+
+    #[test]
+    fn parser_config_untrusted() {
+        let config = ParserConfig::untrusted();
+        assert_eq!((config.max_depth, config.max_elements), (32, 1_000_000));
+        assert_eq!((config.max_string_length, config.max_input_size), (8 << 20, 64 << 20));
+        let nested = [&[b'l'; 33][..], &[b'e'; 33][..]].concat();
+        let error = crate::parse_bytes_with_config(&nested, &config).unwrap_err();
+        assert_eq!(error.kind(), crate::ErrorKind::TooDeep);
+        assert!(crate::parse_bytes_with_config(&nested[1..65], &config).is_ok());
+    }
+
+    #[test]
+    fn write_options_default() {
+        assert_eq!(WriteOptions::default(), WriteOptions::new());
+        assert_eq!(WriteOptions::new().backup, BackupPolicy::None);
+        let options = WriteOptions::new().with_backup(BackupPolicy::Timestamped(3));
+        assert_eq!(options.backup, BackupPolicy::Timestamped(3));
+    }
+
     #[test]
     fn encoder_config_default() {
         let config = EncoderConfig::default();
-        assert!(config.enforce_canonical);
         assert!(config.verify_dict_order);
     }
 
+    #[test]
+    #[allow(deprecated)]
+    fn deprecated_encoder_options_are_kept() {
+        let config = EncoderConfig::new().with_canonical(false).with_sort_keys(false);
+        assert!(!config.enforce_canonical && !config.sort_keys);
+        assert!(EncoderConfig::new().enforce_canonical && EncoderConfig::new().sort_keys);
+    }
+
     #[test]
     fn encoder_config_custom() {
-        let config = EncoderConfig::new()
-            .with_canonical(false)
-            .with_dict_order_verification(false);
-        assert!(!config.enforce_canonical);
+        let config = EncoderConfig::new().with_dict_order_verification(false);
         assert!(!config.verify_dict_order);
+        let config = EncoderConfig::new()
+            .with_pretty(true)
+            .with_indent(4)
+            .with_binary(BinaryEncoding::Base64);
+        assert_eq!((config.pretty, config.indent), (true, 4));
+        assert_eq!(config.binary, BinaryEncoding::Base64);
+        assert_eq!(EncoderConfig::default(), EncoderConfig::new());
+    }
+
+    #[test]
+    fn parse_budget_default_is_unlimited() {
+        let budget = ParseBudget::default();
+        assert_eq!(budget, ParseBudget::new());
+        assert_eq!(budget.max_steps, None);
+        assert_eq!(budget.deadline, None);
+    }
+
+    #[test]
+    fn parse_budget_custom() {
+        let budget = ParseBudget::new()
+            .with_max_steps(10)
+            .with_timeout(std::time::Duration::from_secs(1));
+        assert_eq!(budget.max_steps, Some(10));
+        assert!(budget.deadline.is_some());
     }
 }
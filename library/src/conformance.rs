@@ -0,0 +1,211 @@
+//! Test vectors for checking a parser against the bencode specification (BEP 3).
+//!
+//! [`VECTORS`] holds the edge cases a conforming parser must get right: values
+//! it must accept, with the value each decodes to, and inputs it must reject.
+//! [`run`] feeds every vector to any parser that produces a [`Node`], so a
+//! custom parser or a fork of this one can be checked from its own tests.
+//!
+//! Rejections are compared by whether the input [ended mid-value](ParseError::is_incomplete)
+//! rather than by message, as parsers word their errors differently. Some
+//! inputs break BEP 3 but are widely accepted, this library's parsers among
+//! them; those vectors are [`Expectation::Lenient`] and pass either way.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::error::kind::ErrorKind;
+use crate::error::parse::ParseError;
+use crate::nodes::node::Node;
+use crate::stringify::default::stringify_to_bytes;
+
+/// What a conforming parser does with a vector's input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expectation {
+    /// The input is valid and decodes to the value with this canonical encoding
+    Accept(&'static [u8]),
+    /// The input is invalid; the kind is the one this library reports, and a
+    /// parser's error must agree with it on whether the input is incomplete
+    Reject(ErrorKind),
+    /// The input breaks BEP 3 but is commonly accepted: a parser may reject it,
+    /// or accept it as the value with this canonical encoding
+    Lenient(&'static [u8]),
+}
+
+/// One specification edge case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Vector {
+    /// Short description of the case
+    pub name: &'static str,
+    /// The bencode input
+    pub input: &'static [u8],
+    /// What a conforming parser does with the input
+    pub expectation: Expectation,
+}
+
+impl Vector {
+    /// Checks a parser's outcome for this vector's input.
+    ///
+    /// # Arguments
+    /// * `outcome` - What the parser returned for [`input`](Vector::input)
+    ///
+    /// # Returns
+    /// True if the outcome meets the vector's [`Expectation`]
+    pub fn check(&self, outcome: &Result<Node, ParseError>) -> bool {
+        let decodes_to = |node: &Node, encoding: &[u8]| stringify_to_bytes(node).is_ok_and(|bytes| bytes == encoding);
+        match (self.expectation, outcome) {
+            (Expectation::Accept(encoding) | Expectation::Lenient(encoding), Ok(node)) => decodes_to(node, encoding),
+            (Expectation::Accept(_), Err(_)) => false,
+            (Expectation::Reject(kind), Err(error)) => kind.is_incomplete() == error.is_incomplete(),
+            (Expectation::Reject(_), Ok(_)) => false,
+            (Expectation::Lenient(_), Err(_)) => true,
+        }
+    }
+}
+
+/// Builds a vector
+const fn vector(name: &'static str, input: &'static [u8], expectation: Expectation) -> Vector {
+    Vector {
+        name,
+        input,
+        expectation,
+    }
+}
+
+/// The specification edge cases, valid ones first.
+pub const VECTORS: &[Vector] = &[
+    vector("zero", b"i0e", Expectation::Accept(b"i0e")),
+    vector("negative integer", b"i-42e", Expectation::Accept(b"i-42e")),
+    vector("largest 32 bit integer", b"i2147483647e", Expectation::Accept(b"i2147483647e")),
+    vector("empty string", b"0:", Expectation::Accept(b"0:")),
+    vector("binary string", b"4:\xff\xfe\x00\x01", Expectation::Accept(b"4:\xff\xfe\x00\x01")),
+    vector("string holding bencode", b"5:li1ee", Expectation::Accept(b"5:li1ee")),
+    vector("empty list", b"le", Expectation::Accept(b"le")),
+    vector("empty dictionary", b"de", Expectation::Accept(b"de")),
+    vector("nested lists", b"lli1eelee", Expectation::Accept(b"lli1eelee")),
+    vector("dictionary in list", b"ld1:ai1eee", Expectation::Accept(b"ld1:ai1eee")),
+    vector("empty key", b"d0:0:e", Expectation::Accept(b"d0:0:e")),
+    vector("sorted keys", b"d1:ai1e1:bi2ee", Expectation::Accept(b"d1:ai1e1:bi2ee")),
+    vector("keys sorted as raw bytes", b"d1:Bi0e1:ai0ee", Expectation::Accept(b"d1:Bi0e1:ai0ee")),
+    vector("empty input", b"", Expectation::Reject(ErrorKind::EmptyInput)),
+    vector("negative zero", b"i-0e", Expectation::Reject(ErrorKind::InvalidInteger)),
+    vector("integer without digits", b"ie", Expectation::Reject(ErrorKind::InvalidInteger)),
+    vector("minus without digits", b"i-e", Expectation::Reject(ErrorKind::InvalidInteger)),
+    vector("integer with plus sign", b"i+1e", Expectation::Reject(ErrorKind::InvalidInteger)),
+    vector("fractional integer", b"i1.5e", Expectation::Reject(ErrorKind::InvalidInteger)),
+    vector("unterminated integer", b"i12", Expectation::Reject(ErrorKind::UnterminatedInteger)),
+    vector("unterminated string length", b"12", Expectation::Reject(ErrorKind::UnterminatedStringLength)),
+    vector("string shorter than its length", b"4:abc", Expectation::Reject(ErrorKind::StringTooShort)),
+    vector("negative string length", b"-1:a", Expectation::Reject(ErrorKind::UnexpectedCharacter)),
+    vector("unterminated list", b"li1e", Expectation::Reject(ErrorKind::UnterminatedList)),
+    vector("unterminated dictionary", b"d1:ai1e", Expectation::Reject(ErrorKind::UnterminatedDictionary)),
+    vector("integer key", b"di1ei2ee", Expectation::Reject(ErrorKind::DictKeyMustBeString)),
+    vector("key without value", b"d1:ae", Expectation::Reject(ErrorKind::UnexpectedCharacter)),
+    vector("unsorted keys", b"d1:bi1e1:ai2ee", Expectation::Reject(ErrorKind::DictKeysOrder)),
+    vector("duplicate keys", b"d1:ai1e1:ai2ee", Expectation::Reject(ErrorKind::DictKeysOrder)),
+    vector("end without value", b"e", Expectation::Reject(ErrorKind::UnexpectedCharacter)),
+    vector("unknown type", b"x", Expectation::Reject(ErrorKind::UnexpectedCharacter)),
+    vector("leading whitespace", b" i1e", Expectation::Reject(ErrorKind::UnexpectedCharacter)),
+    vector("integer with leading zero", b"i03e", Expectation::Lenient(b"i3e")),
+    vector("string length with leading zero", b"03:abc", Expectation::Lenient(b"3:abc")),
+    vector("data after the value", b"i1ei2e", Expectation::Lenient(b"i1e")),
+];
+
+/// A vector a parser did not meet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Failure {
+    /// The vector
+    pub vector: &'static Vector,
+    /// What the parser returned for its input
+    pub outcome: Result<Node, ParseError>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: expected ", self.vector.name)?;
+        match self.vector.expectation {
+            Expectation::Accept(encoding) => write!(f, "{}", encoding.escape_ascii())?,
+            Expectation::Reject(kind) => write!(f, "{:?}", kind)?,
+            Expectation::Lenient(encoding) => write!(f, "{} or an error", encoding.escape_ascii())?,
+        }
+        match &self.outcome {
+            Ok(node) => write!(f, ", got Ok({})", node),
+            Err(e) => write!(f, ", got Err({})", e),
+        }
+    }
+}
+
+/// Runs a parser over every vector in [`VECTORS`].
+///
+/// # Arguments
+/// * `parser` - Parses a whole input into a tree
+///
+/// # Returns
+/// The vectors the parser did not meet, in order; empty if it conforms
+///
+/// # Example
+/// ```
+/// use bencode_lib::conformance::run;
+/// use bencode_lib::{parse_borrowed, parse_bytes};
+///
+/// assert!(run(parse_bytes).is_empty());
+/// assert!(run(|data| parse_borrowed(data).map(|node| node.to_node())).is_empty());
+///
+/// // A parser that accepts anything fails every invalid vector
+/// let failures = run(|_| Ok(bencode_lib::make_node(0)));
+/// assert!(failures.iter().any(|failure| failure.vector.name == "negative zero"));
+/// ```
+pub fn run<F>(mut parser: F) -> Vec<Failure>
+where
+    F: FnMut(&[u8]) -> Result<Node, ParseError>,
+{
+    VECTORS
+        .iter()
+        .filter_map(|vector| {
+            let outcome = parser(vector.input);
+            (!vector.check(&outcome)).then_some(Failure { vector, outcome })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::messages::ERR_INVALID_INTEGER;
+    use crate::make_node;
+    use crate::parser::borrowed::parse_borrowed;
+    use crate::parser::default::parse_bytes;
+    use crate::parser::iterative::parse_bytes_iterative;
+
+    #[test]
+    fn every_parser_conforms() {
+        assert_eq!(run(parse_bytes), []);
+        assert_eq!(run(parse_bytes_iterative), []);
+        assert_eq!(run(|data| parse_borrowed(data).map(|node| node.to_node())), []);
+    }
+
+    #[test]
+    fn rejections_are_reported_as_this_library_reports_them() {
+        for vector in VECTORS {
+            if let Expectation::Reject(kind) = vector.expectation {
+                assert_eq!(parse_bytes(vector.input).unwrap_err().kind(), kind, "{}", vector.name);
+            }
+        }
+    }
+
+    #[test]
+    fn failures_name_the_vector_and_outcome() {
+        let failures = run(|data| {
+            if data == b"i0e" {
+                Ok(make_node(1))
+            } else {
+                Err(ParseError::new(ERR_INVALID_INTEGER, 0))
+            }
+        });
+        assert!(failures.iter().all(|failure| !matches!(failure.vector.expectation, Expectation::Lenient(_))));
+        assert_eq!(failures[0].to_string(), "zero: expected i0e, got Ok(1)");
+        // Incomplete inputs rejected as invalid fail too
+        assert!(failures.iter().any(|failure| failure.vector.name == "unterminated list"));
+        assert!(!failures.iter().any(|failure| failure.vector.name == "negative zero"));
+    }
+}
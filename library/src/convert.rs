@@ -0,0 +1,357 @@
+//! Conversion of user types to and from node trees.
+//!
+//! [`ToBencode`] and [`FromBencode`] are implemented here for integers,
+//! booleans, strings, byte vectors, lists, string keyed maps and `Option`,
+//! and can be derived for structs with named fields (requires `derive`
+//! feature). A derived struct maps to a dictionary with one entry per field,
+//! keyed by the field name unless renamed:
+//!
+//! * `#[bencode(rename = "piece length")]` - use another dictionary key
+//! * `#[bencode(skip)]` - leave the field out, filling it with `Default` when decoding
+//! * `#[bencode(default)]` - use `Default` when the key is missing
+//!
+//! An `Option` field that is `None` is left out of the dictionary, and read
+//! back as `None` when its key is missing. `Vec<u8>` is a byte string; other
+//! vectors are lists. Errors are [`ParseError`]s with offset 0 whose path names
+//! the field or element that could not be converted.
+//!
+//! Unlike the [`serde`](crate::serde) support, conversions go through a
+//! [`Node`], so a decoded document can also be inspected or edited as a tree.
+//!
+//! # Example
+//! ```
+//! # #[cfg(feature = "derive")] {
+//! use bencode_lib::{FromBencode, ToBencode, parse_bytes, stringify_to_bytes};
+//!
+//! #[derive(ToBencode, FromBencode, Debug, PartialEq)]
+//! struct Info {
+//!     name: String,
+//!     #[bencode(rename = "piece length")]
+//!     piece_length: u32,
+//!     pieces: Vec<u8>,
+//!     private: Option<bool>,
+//! }
+//!
+//! let data = b"d4:name4:demo12:piece lengthi16e6:pieces2:\x01\x02e";
+//! let info = Info::from_bencode(&parse_bytes(data).unwrap()).unwrap();
+//! assert_eq!(info.piece_length, 16);
+//! assert_eq!(info.private, None);
+//! assert_eq!(stringify_to_bytes(&info.to_bencode().unwrap()).unwrap(), data);
+//! # }
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use crate::Dictionary;
+use crate::error::messages::{ERR_INTEGER_OUT_OF_RANGE, ERR_INVALID_BOOLEAN, ERR_MISSING_FIELD, ERR_UNEXPECTED_TYPE};
+use crate::error::parse::ParseError;
+use crate::nodes::integer::Integer;
+use crate::nodes::node::Node;
+use crate::parser::extract::PATH_SEPARATOR;
+
+/// A type that can be written as a node tree.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{Node, ToBencode};
+///
+/// assert_eq!(vec!["a", "b"].to_bencode().unwrap(), Node::from(["a", "b"]));
+/// assert_eq!(b"\x01\x02".to_vec().to_bencode().unwrap(), Node::Bytes(vec![1, 2]));
+/// ```
+pub trait ToBencode {
+    /// Converts the value to a node
+    fn to_bencode(&self) -> Result<Node, ParseError>;
+
+    /// Converts the value to a dictionary entry's value, or None to leave the entry out
+    fn to_bencode_field(&self) -> Result<Option<Node>, ParseError> {
+        self.to_bencode().map(Some)
+    }
+}
+
+/// A type that can be read from a node tree.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{FromBencode, parse_bytes};
+///
+/// let node = parse_bytes(b"li1ei2ee").unwrap();
+/// assert_eq!(Vec::<u32>::from_bencode(&node).unwrap(), [1, 2]);
+/// assert!(String::from_bencode(&node).is_err());
+/// ```
+pub trait FromBencode: Sized {
+    /// Converts a node to the value
+    fn from_bencode(node: &Node) -> Result<Self, ParseError>;
+
+    /// Returns the value of a dictionary field whose key is missing, or None if the field is required
+    fn from_missing_field() -> Option<Self> {
+        None
+    }
+}
+
+/// Error for a value of the wrong type
+fn unexpected_type() -> ParseError {
+    ParseError::new(ERR_UNEXPECTED_TYPE, 0)
+}
+
+/// Prefixes the path of an error raised inside the value at `segment`
+fn nested(error: ParseError, segment: &str) -> ParseError {
+    let path = match &error.path {
+        Some(path) if !path.is_empty() => format!("{}{}{}", segment, PATH_SEPARATOR, path),
+        _ => segment.to_string(),
+    };
+    error.with_path(path)
+}
+
+/// Returns the entries of a dictionary node, for decoding a struct.
+pub fn expect_dictionary(node: &Node) -> Result<&Dictionary, ParseError> {
+    node.as_dictionary().ok_or_else(unexpected_type)
+}
+
+/// Adds a field to a dictionary being encoded, unless the value leaves itself out.
+pub fn encode_field<T: ToBencode + ?Sized>(dict: &mut Dictionary, key: &str, value: &T) -> Result<(), ParseError> {
+    if let Some(node) = value.to_bencode_field().map_err(|e| nested(e, key))? {
+        dict.insert(key.to_string(), node);
+    }
+    Ok(())
+}
+
+/// Decodes a field of a dictionary; a missing key is an error unless the type
+/// has a value for it (as `Option` does).
+pub fn decode_field<T: FromBencode>(dict: &Dictionary, key: &str) -> Result<T, ParseError> {
+    match dict.get(key) {
+        Some(node) => T::from_bencode(node).map_err(|e| nested(e, key)),
+        None => T::from_missing_field().ok_or_else(|| ParseError::new(ERR_MISSING_FIELD, 0).with_path(key)),
+    }
+}
+
+/// Decodes a field of a dictionary, using the type's default when the key is missing.
+pub fn decode_field_or_default<T: FromBencode + Default>(dict: &Dictionary, key: &str) -> Result<T, ParseError> {
+    match dict.get(key) {
+        Some(node) => T::from_bencode(node).map_err(|e| nested(e, key)),
+        None => Ok(T::default()),
+    }
+}
+
+impl ToBencode for Node {
+    fn to_bencode(&self) -> Result<Node, ParseError> {
+        Ok(self.clone())
+    }
+}
+
+impl FromBencode for Node {
+    fn from_bencode(node: &Node) -> Result<Self, ParseError> {
+        Ok(node.clone())
+    }
+}
+
+/// Implements the conversions for integer types, which must fit both the node
+/// integer type and the target type
+macro_rules! integer_conversions {
+    ($($type:ty),*) => {
+        $(
+            impl ToBencode for $type {
+                #[allow(clippy::useless_conversion)]
+                fn to_bencode(&self) -> Result<Node, ParseError> {
+                    Integer::try_from(*self)
+                        .map(Node::Integer)
+                        .map_err(|_| ParseError::new(ERR_INTEGER_OUT_OF_RANGE, 0))
+                }
+            }
+
+            impl FromBencode for $type {
+                #[allow(clippy::useless_conversion)]
+                fn from_bencode(node: &Node) -> Result<Self, ParseError> {
+                    let value = node.as_integer().copied().ok_or_else(unexpected_type)?;
+                    <$type>::try_from(value).map_err(|_| ParseError::new(ERR_INTEGER_OUT_OF_RANGE, 0))
+                }
+            }
+        )*
+    };
+}
+
+// u8 is left out so that Vec<u8> can be a byte string
+integer_conversions!(i8, i16, i32, i64, isize, u16, u32, u64, usize);
+
+impl ToBencode for bool {
+    fn to_bencode(&self) -> Result<Node, ParseError> {
+        Ok(Node::Integer(Integer::from(*self)))
+    }
+}
+
+impl FromBencode for bool {
+    fn from_bencode(node: &Node) -> Result<Self, ParseError> {
+        match node.as_integer().copied().ok_or_else(unexpected_type)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ParseError::new(ERR_INVALID_BOOLEAN, 0)),
+        }
+    }
+}
+
+impl ToBencode for str {
+    fn to_bencode(&self) -> Result<Node, ParseError> {
+        Ok(Node::from(self))
+    }
+}
+
+impl ToBencode for String {
+    fn to_bencode(&self) -> Result<Node, ParseError> {
+        Ok(Node::from(self.as_str()))
+    }
+}
+
+/// Strings are read from byte strings holding valid UTF-8
+impl FromBencode for String {
+    fn from_bencode(node: &Node) -> Result<Self, ParseError> {
+        match node {
+            Node::Str(text) => Ok(text.clone()),
+            Node::Bytes(bytes) => String::from_utf8(bytes.clone()).map_err(|_| unexpected_type()),
+            _ => Err(unexpected_type()),
+        }
+    }
+}
+
+impl ToBencode for Vec<u8> {
+    fn to_bencode(&self) -> Result<Node, ParseError> {
+        Ok(Node::Bytes(self.clone()))
+    }
+}
+
+impl FromBencode for Vec<u8> {
+    fn from_bencode(node: &Node) -> Result<Self, ParseError> {
+        match node {
+            Node::Bytes(bytes) => Ok(bytes.clone()),
+            Node::Str(text) => Ok(text.as_bytes().to_vec()),
+            _ => Err(unexpected_type()),
+        }
+    }
+}
+
+impl<T: ToBencode> ToBencode for Vec<T> {
+    fn to_bencode(&self) -> Result<Node, ParseError> {
+        self.iter()
+            .enumerate()
+            .map(|(index, item)| item.to_bencode().map_err(|e| nested(e, &index.to_string())))
+            .collect::<Result<Vec<_>, _>>()
+            .map(Node::List)
+    }
+}
+
+impl<T: FromBencode> FromBencode for Vec<T> {
+    fn from_bencode(node: &Node) -> Result<Self, ParseError> {
+        let list = node.as_list().ok_or_else(unexpected_type)?;
+        list.iter()
+            .enumerate()
+            .map(|(index, item)| T::from_bencode(item).map_err(|e| nested(e, &index.to_string())))
+            .collect()
+    }
+}
+
+impl<T: ToBencode> ToBencode for BTreeMap<String, T> {
+    fn to_bencode(&self) -> Result<Node, ParseError> {
+        let mut dict = Dictionary::new();
+        for (key, value) in self {
+            dict.insert(key.clone(), value.to_bencode().map_err(|e| nested(e, key))?);
+        }
+        Ok(Node::Dictionary(dict))
+    }
+}
+
+impl<T: FromBencode> FromBencode for BTreeMap<String, T> {
+    fn from_bencode(node: &Node) -> Result<Self, ParseError> {
+        expect_dictionary(node)?
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), T::from_bencode(value).map_err(|e| nested(e, key))?)))
+            .collect()
+    }
+}
+
+/// None is `Node::None`, which encodes as nothing; as a field it leaves the entry out
+impl<T: ToBencode> ToBencode for Option<T> {
+    fn to_bencode(&self) -> Result<Node, ParseError> {
+        match self {
+            Some(value) => value.to_bencode(),
+            None => Ok(Node::None),
+        }
+    }
+
+    fn to_bencode_field(&self) -> Result<Option<Node>, ParseError> {
+        self.as_ref().map(T::to_bencode).transpose()
+    }
+}
+
+impl<T: FromBencode> FromBencode for Option<T> {
+    fn from_bencode(node: &Node) -> Result<Self, ParseError> {
+        match node {
+            Node::None => Ok(None),
+            node => T::from_bencode(node).map(Some),
+        }
+    }
+
+    fn from_missing_field() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: ToBencode + ?Sized> ToBencode for &T {
+    fn to_bencode(&self) -> Result<Node, ParseError> {
+        (**self).to_bencode()
+    }
+
+    fn to_bencode_field(&self) -> Result<Option<Node>, ParseError> {
+        (**self).to_bencode_field()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    #[test]
+    fn standard_types_round_trip() {
+        fn round_trip<T: ToBencode + FromBencode + PartialEq + core::fmt::Debug>(value: T) {
+            assert_eq!(T::from_bencode(&value.to_bencode().unwrap()).unwrap(), value);
+        }
+        round_trip(-5i8);
+        round_trip(7usize);
+        round_trip(true);
+        round_trip(String::from("text"));
+        round_trip(vec![0u8, 255]);
+        round_trip(vec![vec![1u16], vec![]]);
+        round_trip(BTreeMap::from([("a".to_string(), Some(1i32)), ("b".to_string(), None)]));
+    }
+
+    #[test]
+    fn errors_name_the_element_that_failed() {
+        let node = Node::from([("a", Node::from([Node::from(1), Node::from("x")]))]);
+        let error = BTreeMap::<String, Vec<i64>>::from_bencode(&node).unwrap_err();
+        assert_eq!((error.kind(), error.path.as_deref()), (ErrorKind::UnexpectedType, Some("a.1")));
+
+        assert_eq!(u16::from_bencode(&Node::Integer(-1)).unwrap_err().kind(), ErrorKind::IntegerOutOfRange);
+        assert_eq!(bool::from_bencode(&Node::Integer(2)).unwrap_err().kind(), ErrorKind::InvalidBoolean);
+        assert_eq!(u64::MAX.to_bencode().unwrap_err().kind(), ErrorKind::IntegerOutOfRange);
+    }
+
+    #[test]
+    fn fields_are_required_unless_optional() {
+        let mut dict = Dictionary::new();
+        encode_field(&mut dict, "name", "x").unwrap();
+        encode_field(&mut dict, "comment", &None::<String>).unwrap();
+        assert_eq!(dict.len(), 1);
+
+        assert_eq!(decode_field::<String>(&dict, "name").unwrap(), "x");
+        assert_eq!(decode_field::<Option<String>>(&dict, "comment").unwrap(), None);
+        assert_eq!(decode_field_or_default::<u32>(&dict, "length").unwrap(), 0);
+        let error = decode_field::<u32>(&dict, "length").unwrap_err();
+        assert_eq!((error.kind(), error.path.as_deref()), (ErrorKind::MissingField, Some("length")));
+    }
+}
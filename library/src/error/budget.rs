@@ -0,0 +1,68 @@
+//! Error returned by parsing limited by a [`ParseBudget`](crate::config::ParseBudget).
+
+use core::fmt;
+
+use crate::error::kind::ErrorKind;
+use crate::error::messages::ERR_TIMEOUT;
+use crate::error::parse::ParseError;
+
+/// Error raised by a budgeted parse: either the budget ran out or the input is malformed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BudgetError {
+    /// The step limit or deadline was reached before parsing finished
+    Timeout {
+        /// Number of parser steps taken before giving up
+        steps: usize,
+    },
+    /// The input is malformed
+    Parse(ParseError),
+}
+
+impl BudgetError {
+    /// Returns true if parsing stopped because the budget ran out
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, BudgetError::Timeout { .. })
+    }
+
+    /// Returns the stable kind of this error
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            BudgetError::Timeout { .. } => ErrorKind::Timeout,
+            BudgetError::Parse(error) => error.kind(),
+        }
+    }
+}
+
+impl fmt::Display for BudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BudgetError::Timeout { steps } => write!(f, "{} after {} steps", ERR_TIMEOUT, steps),
+            BudgetError::Parse(error) => error.fmt(f),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BudgetError {}
+
+impl From<ParseError> for BudgetError {
+    fn from(error: ParseError) -> Self {
+        BudgetError::Parse(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::messages::ERR_EMPTY_INPUT;
+
+    #[test]
+    fn budget_error_display() {
+        assert_eq!(BudgetError::Timeout { steps: 10 }.to_string(), "Parse budget exceeded after 10 steps");
+        assert_eq!(BudgetError::from(ParseError::new(ERR_EMPTY_INPUT, 0)).to_string(), ERR_EMPTY_INPUT);
+        assert!(BudgetError::Timeout { steps: 1 }.is_timeout());
+        assert!(!BudgetError::Parse(ParseError::new(ERR_EMPTY_INPUT, 0)).is_timeout());
+        assert_eq!(BudgetError::Timeout { steps: 1 }.kind(), ErrorKind::Timeout);
+        assert_eq!(BudgetError::from(ParseError::new(ERR_EMPTY_INPUT, 0)).kind(), ErrorKind::EmptyInput);
+    }
+}
@@ -127,6 +127,13 @@ impl From<String> for BencodeError {
     }
 }
 
+/// Conversion from structured parse errors, dropping the offset
+impl From<crate::error::parse::ParseError> for BencodeError {
+    fn from(error: crate::error::parse::ParseError) -> BencodeError {
+        BencodeError::from(error.message.as_str())
+    }
+}
+
 impl From<&str> for BencodeError {
     fn from(s: &str) -> BencodeError {
         match s {
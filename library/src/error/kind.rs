@@ -0,0 +1,409 @@
+//! Stable classification of library error messages.
+//!
+//! Error messages are prose and may be reworded; [`ErrorKind`] gives callers a
+//! value to match on instead. Every message in [`error::messages`](crate::error::messages)
+//! maps to exactly one kind.
+
+use crate::error::messages::*;
+
+/// The kind of error a message describes.
+///
+/// New kinds may be added in later releases, so matches need a wildcard arm.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_bytes, ErrorKind};
+///
+/// let error = parse_bytes(b"i12").unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::UnterminatedInteger);
+/// assert_eq!(ErrorKind::from_message(&error.to_string()), error.kind());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Input was empty or ended before a value was complete
+    EmptyInput,
+    /// Integer text was malformed (e.g. `i-0e`, `i+1e` or non-digits)
+    InvalidInteger,
+    /// Integer does not fit the node integer type
+    IntegerOutOfRange,
+    /// Integer is well formed but does not fit 64 bits (see `ParserConfig::big_integers`)
+    IntegerOverflow,
+    /// Integer was not terminated with `e`
+    UnterminatedInteger,
+    /// String length prefix was malformed
+    InvalidStringLength,
+    /// Input ended before a string length prefix reached its `:`
+    UnterminatedStringLength,
+    /// String content was shorter than its declared length
+    StringTooShort,
+    /// List was not terminated with `e`
+    UnterminatedList,
+    /// Dictionary was not terminated with `e`
+    UnterminatedDictionary,
+    /// Dictionary keys were not in ascending order
+    DictKeysOrder,
+    /// Dictionary key was not a byte string
+    DictKeyMustBeString,
+    /// Bytes followed the root value
+    TrailingData,
+    /// A byte that cannot start or end a value was found
+    UnexpectedCharacter,
+    /// A file could not be found
+    FileNotFound,
+    /// A flat node buffer was corrupt
+    FlatInvalid,
+    /// A value was too large for a flat node buffer
+    FlatTooLarge,
+    /// A flat node buffer or archive was written by a newer, unknown format version
+    UnsupportedFormatVersion,
+    /// The root value was not a dictionary
+    NotADictionary,
+    /// A torrent had no info dictionary
+    MissingInfo,
+    /// A torrent's info value was not a dictionary
+    InfoNotDictionary,
+    /// No signature was found for the requested signer
+    SignatureNotFound,
+    /// A signature entry was malformed
+    InvalidSignatureEntry,
+    /// A torrent's piece length was missing or not positive
+    InvalidPieceLength,
+    /// A torrent's pieces field did not hold one hash per piece
+    InvalidPieces,
+    /// A v2 torrent's piece layers were missing, malformed or did not hash to
+    /// the pieces roots of its file tree
+    InvalidPieceLayers,
+    /// A torrent's meta version was not one the library reads
+    UnsupportedMetaVersion,
+    /// A torrent's name, length or file list entry was missing or malformed
+    InvalidFileEntry,
+    /// Downloaded data was written past the end of a torrent's payload
+    WriteOutOfRange,
+    /// A piece written to disk had the wrong index or length
+    InvalidPieceData,
+    /// A torrent's file path could escape the download directory
+    UnsafePath,
+    /// A tracker response had neither an interval nor a failure reason
+    InvalidTrackerResponse,
+    /// A torrent field had the wrong type or an out of range value
+    InvalidTorrentField,
+    /// A tracker URL had no scheme or host, or an invalid port
+    InvalidTrackerUrl,
+    /// An extension protocol message was malformed or missing a required field
+    InvalidExtensionMessage,
+    /// A DHT (KRPC) message was malformed or missing a required field
+    InvalidKrpcMessage,
+    /// An archive file was malformed or held a key unusable as a file name
+    InvalidArchive,
+    /// Two records written to an archive had the same key
+    DuplicateArchiveKey,
+    /// A value had a type bencode cannot hold, such as a float or a bare None
+    UnsupportedType,
+    /// Two dictionary entries being encoded had the same key
+    DuplicateKey,
+    /// An integer decoded as a boolean was not 0 or 1
+    InvalidBoolean,
+    /// A list or dictionary had entries left after the decoded type was complete
+    TooManyEntries,
+    /// A dictionary decoded with FromBencode lacked a required field
+    MissingField,
+    /// A value decoded with FromBencode was not of the type the field needs
+    UnexpectedType,
+    /// No parser can handle the input within the given stack and memory limits
+    NoParserFits,
+    /// Input had more nodes than a bounded tree or parser configuration allows
+    TooManyNodes,
+    /// Input was nested deeper than a bounded tree or parser configuration allows
+    TooDeep,
+    /// A string was longer than the parser configuration allows
+    StringTooLong,
+    /// Input was larger than the parser configuration allows
+    InputTooLarge,
+    /// An integer or string length was not written in canonical form
+    NotCanonical,
+    /// Encoded output would exceed the byte limit of a bounded stringify
+    OutputLimit,
+    /// A node path ran through a scalar or past the end of a list
+    InvalidPath,
+    /// An arena had no space left for a parsed tree
+    ArenaFull,
+    /// A typed accessor was used on a value that is not an integer
+    NotAnInteger,
+    /// A numeric accessor was used on a value that is neither an integer nor a number written as text
+    NotANumber,
+    /// A string could not be parsed as the requested type
+    UnparsableString,
+    /// A value had a type not seen in the schema, or lacked a key the schema requires
+    SchemaMismatch,
+    /// A patch document was not a list of well formed operations
+    InvalidPatch,
+    /// A patch operation named a path the document does not allow
+    PatchConflict,
+    /// YAML input was not in the block style subset the library reads
+    InvalidYaml,
+    /// XML input did not have the layout the library writes
+    InvalidXml,
+    /// A budgeted parse ran out of steps or time
+    Timeout,
+    /// Any other message, such as an I/O error from the operating system
+    Other,
+}
+
+impl ErrorKind {
+    /// Classifies an error message produced by this library.
+    ///
+    /// # Arguments
+    /// * `message` - The error message (e.g. the `Err` string of `parse_bytes`)
+    ///
+    /// # Returns
+    /// The matching kind, or `ErrorKind::Other` for messages from elsewhere
+    pub fn from_message(message: &str) -> ErrorKind {
+        match message {
+            ERR_EMPTY_INPUT => ErrorKind::EmptyInput,
+            ERR_INVALID_INTEGER => ErrorKind::InvalidInteger,
+            ERR_INTEGER_OUT_OF_RANGE => ErrorKind::IntegerOutOfRange,
+            ERR_INTEGER_OVERFLOW => ErrorKind::IntegerOverflow,
+            ERR_UNTERMINATED_INTEGER => ErrorKind::UnterminatedInteger,
+            ERR_INVALID_STRING_LENGTH => ErrorKind::InvalidStringLength,
+            ERR_UNTERMINATED_STRING_LENGTH => ErrorKind::UnterminatedStringLength,
+            ERR_STRING_TOO_SHORT => ErrorKind::StringTooShort,
+            ERR_UNTERMINATED_LIST => ErrorKind::UnterminatedList,
+            ERR_UNTERMINATED_DICTIONARY => ErrorKind::UnterminatedDictionary,
+            ERR_DICT_KEYS_ORDER => ErrorKind::DictKeysOrder,
+            ERR_DICT_KEY_MUST_BE_STRING => ErrorKind::DictKeyMustBeString,
+            ERR_TRAILING_DATA => ErrorKind::TrailingData,
+            FILE_NOT_FOUND => ErrorKind::FileNotFound,
+            ERR_FLAT_INVALID => ErrorKind::FlatInvalid,
+            ERR_FLAT_TOO_LARGE => ErrorKind::FlatTooLarge,
+            ERR_UNSUPPORTED_FORMAT_VERSION => ErrorKind::UnsupportedFormatVersion,
+            ERR_NOT_A_DICTIONARY => ErrorKind::NotADictionary,
+            ERR_MISSING_INFO => ErrorKind::MissingInfo,
+            ERR_INFO_NOT_DICTIONARY => ErrorKind::InfoNotDictionary,
+            ERR_SIGNATURE_NOT_FOUND => ErrorKind::SignatureNotFound,
+            ERR_INVALID_SIGNATURE_ENTRY => ErrorKind::InvalidSignatureEntry,
+            ERR_INVALID_PIECE_LENGTH => ErrorKind::InvalidPieceLength,
+            ERR_INVALID_PIECES => ErrorKind::InvalidPieces,
+            ERR_INVALID_PIECE_LAYERS => ErrorKind::InvalidPieceLayers,
+            ERR_UNSUPPORTED_META_VERSION => ErrorKind::UnsupportedMetaVersion,
+            ERR_INVALID_FILE_ENTRY => ErrorKind::InvalidFileEntry,
+            ERR_WRITE_OUT_OF_RANGE => ErrorKind::WriteOutOfRange,
+            ERR_INVALID_PIECE_DATA => ErrorKind::InvalidPieceData,
+            ERR_UNSAFE_PATH => ErrorKind::UnsafePath,
+            ERR_INVALID_TRACKER_RESPONSE => ErrorKind::InvalidTrackerResponse,
+            ERR_INVALID_TORRENT_FIELD => ErrorKind::InvalidTorrentField,
+            ERR_INVALID_TRACKER_URL => ErrorKind::InvalidTrackerUrl,
+            ERR_INVALID_EXTENSION_MESSAGE => ErrorKind::InvalidExtensionMessage,
+            ERR_INVALID_KRPC_MESSAGE => ErrorKind::InvalidKrpcMessage,
+            ERR_INVALID_ARCHIVE => ErrorKind::InvalidArchive,
+            ERR_DUPLICATE_ARCHIVE_KEY => ErrorKind::DuplicateArchiveKey,
+            ERR_UNSUPPORTED_TYPE => ErrorKind::UnsupportedType,
+            ERR_DUPLICATE_KEY => ErrorKind::DuplicateKey,
+            ERR_INVALID_BOOLEAN => ErrorKind::InvalidBoolean,
+            ERR_TOO_MANY_ENTRIES => ErrorKind::TooManyEntries,
+            ERR_MISSING_FIELD => ErrorKind::MissingField,
+            ERR_UNEXPECTED_TYPE => ErrorKind::UnexpectedType,
+            ERR_NO_PARSER_FITS => ErrorKind::NoParserFits,
+            ERR_TOO_MANY_NODES => ErrorKind::TooManyNodes,
+            ERR_TOO_DEEP => ErrorKind::TooDeep,
+            ERR_STRING_TOO_LONG => ErrorKind::StringTooLong,
+            ERR_INPUT_TOO_LARGE => ErrorKind::InputTooLarge,
+            ERR_NOT_CANONICAL => ErrorKind::NotCanonical,
+            ERR_OUTPUT_LIMIT => ErrorKind::OutputLimit,
+            ERR_INVALID_PATH => ErrorKind::InvalidPath,
+            ERR_ARENA_FULL => ErrorKind::ArenaFull,
+            ERR_NOT_AN_INTEGER => ErrorKind::NotAnInteger,
+            ERR_NOT_A_NUMBER => ErrorKind::NotANumber,
+            ERR_UNPARSABLE_STRING => ErrorKind::UnparsableString,
+            ERR_SCHEMA_MISMATCH => ErrorKind::SchemaMismatch,
+            ERR_INVALID_PATCH => ErrorKind::InvalidPatch,
+            ERR_PATCH_CONFLICT => ErrorKind::PatchConflict,
+            ERR_INVALID_YAML => ErrorKind::InvalidYaml,
+            ERR_INVALID_XML => ErrorKind::InvalidXml,
+            _ if message.starts_with(ERR_UNEXPECTED_CHARACTER) => ErrorKind::UnexpectedCharacter,
+            _ if message.starts_with(ERR_TIMEOUT) => ErrorKind::Timeout,
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Returns the message (or message prefix) this kind is classified from,
+    /// or None for `ErrorKind::Other`
+    pub const fn message(&self) -> Option<&'static str> {
+        Some(match self {
+            ErrorKind::EmptyInput => ERR_EMPTY_INPUT,
+            ErrorKind::InvalidInteger => ERR_INVALID_INTEGER,
+            ErrorKind::IntegerOutOfRange => ERR_INTEGER_OUT_OF_RANGE,
+            ErrorKind::IntegerOverflow => ERR_INTEGER_OVERFLOW,
+            ErrorKind::UnterminatedInteger => ERR_UNTERMINATED_INTEGER,
+            ErrorKind::InvalidStringLength => ERR_INVALID_STRING_LENGTH,
+            ErrorKind::UnterminatedStringLength => ERR_UNTERMINATED_STRING_LENGTH,
+            ErrorKind::StringTooShort => ERR_STRING_TOO_SHORT,
+            ErrorKind::UnterminatedList => ERR_UNTERMINATED_LIST,
+            ErrorKind::UnterminatedDictionary => ERR_UNTERMINATED_DICTIONARY,
+            ErrorKind::DictKeysOrder => ERR_DICT_KEYS_ORDER,
+            ErrorKind::DictKeyMustBeString => ERR_DICT_KEY_MUST_BE_STRING,
+            ErrorKind::TrailingData => ERR_TRAILING_DATA,
+            ErrorKind::UnexpectedCharacter => ERR_UNEXPECTED_CHARACTER,
+            ErrorKind::FileNotFound => FILE_NOT_FOUND,
+            ErrorKind::FlatInvalid => ERR_FLAT_INVALID,
+            ErrorKind::FlatTooLarge => ERR_FLAT_TOO_LARGE,
+            ErrorKind::UnsupportedFormatVersion => ERR_UNSUPPORTED_FORMAT_VERSION,
+            ErrorKind::NotADictionary => ERR_NOT_A_DICTIONARY,
+            ErrorKind::MissingInfo => ERR_MISSING_INFO,
+            ErrorKind::InfoNotDictionary => ERR_INFO_NOT_DICTIONARY,
+            ErrorKind::SignatureNotFound => ERR_SIGNATURE_NOT_FOUND,
+            ErrorKind::InvalidSignatureEntry => ERR_INVALID_SIGNATURE_ENTRY,
+            ErrorKind::InvalidPieceLength => ERR_INVALID_PIECE_LENGTH,
+            ErrorKind::InvalidPieces => ERR_INVALID_PIECES,
+            ErrorKind::InvalidPieceLayers => ERR_INVALID_PIECE_LAYERS,
+            ErrorKind::UnsupportedMetaVersion => ERR_UNSUPPORTED_META_VERSION,
+            ErrorKind::InvalidFileEntry => ERR_INVALID_FILE_ENTRY,
+            ErrorKind::WriteOutOfRange => ERR_WRITE_OUT_OF_RANGE,
+            ErrorKind::InvalidPieceData => ERR_INVALID_PIECE_DATA,
+            ErrorKind::UnsafePath => ERR_UNSAFE_PATH,
+            ErrorKind::InvalidTrackerResponse => ERR_INVALID_TRACKER_RESPONSE,
+            ErrorKind::InvalidTorrentField => ERR_INVALID_TORRENT_FIELD,
+            ErrorKind::InvalidTrackerUrl => ERR_INVALID_TRACKER_URL,
+            ErrorKind::InvalidExtensionMessage => ERR_INVALID_EXTENSION_MESSAGE,
+            ErrorKind::InvalidKrpcMessage => ERR_INVALID_KRPC_MESSAGE,
+            ErrorKind::InvalidArchive => ERR_INVALID_ARCHIVE,
+            ErrorKind::DuplicateArchiveKey => ERR_DUPLICATE_ARCHIVE_KEY,
+            ErrorKind::UnsupportedType => ERR_UNSUPPORTED_TYPE,
+            ErrorKind::DuplicateKey => ERR_DUPLICATE_KEY,
+            ErrorKind::InvalidBoolean => ERR_INVALID_BOOLEAN,
+            ErrorKind::TooManyEntries => ERR_TOO_MANY_ENTRIES,
+            ErrorKind::MissingField => ERR_MISSING_FIELD,
+            ErrorKind::UnexpectedType => ERR_UNEXPECTED_TYPE,
+            ErrorKind::NoParserFits => ERR_NO_PARSER_FITS,
+            ErrorKind::TooManyNodes => ERR_TOO_MANY_NODES,
+            ErrorKind::TooDeep => ERR_TOO_DEEP,
+            ErrorKind::StringTooLong => ERR_STRING_TOO_LONG,
+            ErrorKind::InputTooLarge => ERR_INPUT_TOO_LARGE,
+            ErrorKind::NotCanonical => ERR_NOT_CANONICAL,
+            ErrorKind::OutputLimit => ERR_OUTPUT_LIMIT,
+            ErrorKind::InvalidPath => ERR_INVALID_PATH,
+            ErrorKind::ArenaFull => ERR_ARENA_FULL,
+            ErrorKind::NotAnInteger => ERR_NOT_AN_INTEGER,
+            ErrorKind::NotANumber => ERR_NOT_A_NUMBER,
+            ErrorKind::UnparsableString => ERR_UNPARSABLE_STRING,
+            ErrorKind::SchemaMismatch => ERR_SCHEMA_MISMATCH,
+            ErrorKind::InvalidPatch => ERR_INVALID_PATCH,
+            ErrorKind::PatchConflict => ERR_PATCH_CONFLICT,
+            ErrorKind::InvalidYaml => ERR_INVALID_YAML,
+            ErrorKind::InvalidXml => ERR_INVALID_XML,
+            ErrorKind::Timeout => ERR_TIMEOUT,
+            ErrorKind::Other => return None,
+        })
+    }
+
+    /// Returns true if the kind means the input ended in the middle of a value,
+    /// so more input could still complete it, rather than holding a byte that
+    /// no continuation can make valid.
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::parse_bytes;
+    ///
+    /// assert!(parse_bytes(b"d4:name3:de").unwrap_err().kind().is_incomplete());
+    /// assert!(!parse_bytes(b"d4:name3x").unwrap_err().kind().is_incomplete());
+    /// ```
+    pub const fn is_incomplete(&self) -> bool {
+        matches!(
+            self,
+            ErrorKind::EmptyInput
+                | ErrorKind::UnterminatedInteger
+                | ErrorKind::UnterminatedStringLength
+                | ErrorKind::StringTooShort
+                | ErrorKind::UnterminatedList
+                | ErrorKind::UnterminatedDictionary
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::default::parse_bytes;
+
+    #[test]
+    fn every_message_round_trips() {
+        let kinds = [
+            ErrorKind::EmptyInput,
+            ErrorKind::InvalidInteger,
+            ErrorKind::IntegerOutOfRange,
+            ErrorKind::IntegerOverflow,
+            ErrorKind::UnterminatedInteger,
+            ErrorKind::InvalidStringLength,
+            ErrorKind::UnterminatedStringLength,
+            ErrorKind::StringTooShort,
+            ErrorKind::UnterminatedList,
+            ErrorKind::UnterminatedDictionary,
+            ErrorKind::DictKeysOrder,
+            ErrorKind::DictKeyMustBeString,
+            ErrorKind::TrailingData,
+            ErrorKind::UnexpectedCharacter,
+            ErrorKind::FileNotFound,
+            ErrorKind::FlatInvalid,
+            ErrorKind::FlatTooLarge,
+            ErrorKind::UnsupportedFormatVersion,
+            ErrorKind::NotADictionary,
+            ErrorKind::MissingInfo,
+            ErrorKind::InfoNotDictionary,
+            ErrorKind::SignatureNotFound,
+            ErrorKind::InvalidSignatureEntry,
+            ErrorKind::InvalidPieceLength,
+            ErrorKind::InvalidPieces,
+            ErrorKind::InvalidPieceLayers,
+            ErrorKind::UnsupportedMetaVersion,
+            ErrorKind::InvalidFileEntry,
+            ErrorKind::WriteOutOfRange,
+            ErrorKind::InvalidPieceData,
+            ErrorKind::UnsafePath,
+            ErrorKind::InvalidTrackerResponse,
+            ErrorKind::InvalidTorrentField,
+            ErrorKind::InvalidTrackerUrl,
+            ErrorKind::InvalidExtensionMessage,
+            ErrorKind::InvalidKrpcMessage,
+            ErrorKind::InvalidArchive,
+            ErrorKind::DuplicateArchiveKey,
+            ErrorKind::UnsupportedType,
+            ErrorKind::DuplicateKey,
+            ErrorKind::InvalidBoolean,
+            ErrorKind::TooManyEntries,
+            ErrorKind::MissingField,
+            ErrorKind::UnexpectedType,
+            ErrorKind::NoParserFits,
+            ErrorKind::TooManyNodes,
+            ErrorKind::TooDeep,
+            ErrorKind::StringTooLong,
+            ErrorKind::InputTooLarge,
+            ErrorKind::NotCanonical,
+            ErrorKind::OutputLimit,
+            ErrorKind::InvalidPath,
+            ErrorKind::ArenaFull,
+            ErrorKind::NotAnInteger,
+            ErrorKind::NotANumber,
+            ErrorKind::UnparsableString,
+            ErrorKind::SchemaMismatch,
+            ErrorKind::InvalidPatch,
+            ErrorKind::PatchConflict,
+            ErrorKind::InvalidYaml,
+            ErrorKind::InvalidXml,
+            ErrorKind::Timeout,
+        ];
+        for kind in kinds {
+            assert_eq!(ErrorKind::from_message(kind.message().unwrap()), kind);
+        }
+        assert_eq!(ErrorKind::Other.message(), None);
+    }
+
+    #[test]
+    fn classifies_parser_errors() {
+        let kind = |data: &[u8]| parse_bytes(data).unwrap_err().kind();
+        assert_eq!(kind(b""), ErrorKind::EmptyInput);
+        assert_eq!(kind(b"x"), ErrorKind::UnexpectedCharacter);
+        assert_eq!(kind(b"li1e"), ErrorKind::UnterminatedList);
+        assert_eq!(kind(b"i1"), ErrorKind::UnterminatedInteger);
+        assert_eq!(ErrorKind::from_message("permission denied"), ErrorKind::Other);
+    }
+}
@@ -1,24 +1,132 @@
+//! Centralized error messages for the bencode library.
+//! Keep all user-visible error strings and small helpers here to ensure consistency.
+//! Every message maps to an [`ErrorKind`](crate::error::kind::ErrorKind), which callers
+//! should match on rather than comparing the prose.
+
 #[cfg(not(feature = "std"))]
 use alloc::{format, string::String};
 
-/// Centralized error messages for the bencode library.
-/// Keep all user-visible error strings and small helpers here to ensure consistency.
-
 /// Generic IO-related errors
 pub const FILE_NOT_FOUND: &str = "File not found";
 
 /// Parser errors
 pub const ERR_EMPTY_INPUT: &str = "Empty input";
 pub const ERR_INVALID_INTEGER: &str = "Invalid integer";
+pub const ERR_INTEGER_OUT_OF_RANGE: &str = "Integer out of range";
+pub const ERR_INTEGER_OVERFLOW: &str = "Integer too large for 64 bits";
 pub const ERR_UNTERMINATED_INTEGER: &str = "Unterminated integer";
 pub const ERR_INVALID_STRING_LENGTH: &str = "Invalid string length";
+pub const ERR_UNTERMINATED_STRING_LENGTH: &str = "Unterminated string length";
 pub const ERR_STRING_TOO_SHORT: &str = "String too short";
 pub const ERR_UNTERMINATED_LIST: &str = "Unterminated list";
 pub const ERR_UNTERMINATED_DICTIONARY: &str = "Unterminated dictionary";
 pub const ERR_DICT_KEYS_ORDER: &str = "Dictionary keys must be in order";
 pub const ERR_DICT_KEY_MUST_BE_STRING: &str = "Dictionary key must be string";
+pub const ERR_TRAILING_DATA: &str = "Trailing data after bencode structure";
+pub const ERR_UNEXPECTED_CHARACTER: &str = "Unexpected character";
+pub const ERR_NO_PARSER_FITS: &str = "Input too large for the available stack and memory";
+pub const ERR_TOO_MANY_NODES: &str = "Input has more nodes than allowed";
+pub const ERR_TOO_DEEP: &str = "Input is nested deeper than allowed";
+pub const ERR_STRING_TOO_LONG: &str = "String is longer than allowed";
+pub const ERR_INPUT_TOO_LARGE: &str = "Input is larger than allowed";
+pub const ERR_NOT_CANONICAL: &str = "Number has leading zeros or a sign";
+
+/// Bounded stringify errors
+pub const ERR_OUTPUT_LIMIT: &str = "Encoded output exceeds the byte limit";
+
+/// Node path errors
+pub const ERR_INVALID_PATH: &str = "Path runs through a value that cannot hold it";
+
+/// Arena parse errors
+pub const ERR_ARENA_FULL: &str = "Arena has no space left for the tree";
+
+/// Typed node accessor errors
+pub const ERR_NOT_AN_INTEGER: &str = "Value is not an integer";
+pub const ERR_NOT_A_NUMBER: &str = "Value is not a number";
+pub const ERR_UNPARSABLE_STRING: &str = "String does not hold a value of the requested type";
+
+/// Schema errors
+pub const ERR_SCHEMA_MISMATCH: &str = "Value does not match the schema";
+
+/// Patch errors
+pub const ERR_INVALID_PATCH: &str = "Invalid patch document";
+pub const ERR_PATCH_CONFLICT: &str = "Patch does not apply to the document";
+
+/// Text format parsing errors
+pub const ERR_INVALID_YAML: &str = "Invalid YAML document";
+pub const ERR_INVALID_XML: &str = "Invalid XML document";
+
+/// Budgeted parse errors (prefix; the step count follows)
+pub const ERR_TIMEOUT: &str = "Parse budget exceeded";
+
+/// Flat node errors
+pub const ERR_FLAT_INVALID: &str = "Invalid flat node buffer";
+pub const ERR_FLAT_TOO_LARGE: &str = "Flat node field exceeds 4 GiB";
+pub const ERR_UNSUPPORTED_FORMAT_VERSION: &str = "Binary format version is newer than this library reads";
+
+/// Torrent metainfo errors
+pub const ERR_NOT_A_DICTIONARY: &str = "Root value is not a dictionary";
+pub const ERR_MISSING_INFO: &str = "Missing info dictionary";
+pub const ERR_INFO_NOT_DICTIONARY: &str = "Info value is not a dictionary";
+pub const ERR_SIGNATURE_NOT_FOUND: &str = "No signature for signer";
+pub const ERR_INVALID_SIGNATURE_ENTRY: &str = "Invalid signature entry";
+pub const ERR_INVALID_PIECE_LENGTH: &str = "Invalid piece length";
+pub const ERR_INVALID_PIECES: &str = "Pieces field does not match the payload length";
+pub const ERR_INVALID_PIECE_LAYERS: &str = "Piece layers do not match the file tree";
+pub const ERR_UNSUPPORTED_META_VERSION: &str = "Unsupported torrent meta version";
+pub const ERR_INVALID_FILE_ENTRY: &str = "Invalid file entry";
+pub const ERR_WRITE_OUT_OF_RANGE: &str = "Write extends past the end of the torrent";
+pub const ERR_INVALID_PIECE_DATA: &str = "Piece index or length does not match the torrent";
+pub const ERR_UNSAFE_PATH: &str = "Unsafe file path in torrent";
+pub const ERR_INVALID_TRACKER_RESPONSE: &str = "Invalid tracker response";
+pub const ERR_INVALID_TORRENT_FIELD: &str = "Invalid torrent field";
+pub const ERR_INVALID_TRACKER_URL: &str = "Invalid tracker URL";
+pub const ERR_INVALID_EXTENSION_MESSAGE: &str = "Invalid extension message";
+pub const ERR_INVALID_KRPC_MESSAGE: &str = "Invalid KRPC message";
+pub const ERR_INVALID_ARCHIVE: &str = "Invalid archive file";
+pub const ERR_DUPLICATE_ARCHIVE_KEY: &str = "Duplicate key in archive";
+
+/// Serde errors
+pub const ERR_UNSUPPORTED_TYPE: &str = "Type cannot be represented in bencode";
+pub const ERR_DUPLICATE_KEY: &str = "Duplicate dictionary key";
+pub const ERR_INVALID_BOOLEAN: &str = "Boolean must be integer 0 or 1";
+pub const ERR_TOO_MANY_ENTRIES: &str = "Container has more entries than expected";
+
+/// ToBencode / FromBencode errors
+pub const ERR_MISSING_FIELD: &str = "Missing dictionary field";
+pub const ERR_UNEXPECTED_TYPE: &str = "Value has the wrong type";
+
+/// What a parser was looking for when it met an unexpected character
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expecting {
+    /// The root value
+    Value,
+    /// A list element or the list's `e` terminator
+    ListItem,
+    /// The value following a dictionary key
+    DictValue,
+}
+
+impl Expecting {
+    /// Returns a short description for use in error messages
+    pub const fn description(&self) -> &'static str {
+        match self {
+            Expecting::Value => "a value",
+            Expecting::ListItem => "a list item or 'e'",
+            Expecting::DictValue => "a dictionary value",
+        }
+    }
+}
 
 /// Helpers for constructing formatted error messages
-pub fn unexpected_character(c: char) -> String {
-    format!("Unexpected character: {}", c)
+///
+/// `depth` is the number of containers enclosing the unexpected character.
+pub fn unexpected_character(c: char, expecting: Expecting, depth: usize) -> String {
+    format!(
+        "{}: '{}' while expecting {} at depth {}",
+        ERR_UNEXPECTED_CHARACTER,
+        c.escape_default(),
+        expecting.description(),
+        depth
+    )
 }
@@ -1,2 +1,6 @@
+pub mod budget;
 pub mod embedded;
+pub mod kind;
 pub mod messages;
+pub mod parse;
+pub mod warning;
@@ -0,0 +1,195 @@
+//! Parse error carrying the byte offset at which the problem was detected.
+
+#[cfg(all(not(feature = "std"), feature = "json"))]
+use alloc::format;
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
+use core::fmt;
+
+use crate::error::kind::ErrorKind;
+#[cfg(feature = "json")]
+use crate::io::destinations::buffer::Buffer;
+#[cfg(feature = "json")]
+use crate::io::traits::IDestination;
+use crate::parser::extract::path_at;
+#[cfg(feature = "json")]
+use crate::stringify::common::escape_string;
+
+/// Error raised while parsing bencode, with the position of the offending input.
+///
+/// Returned by the `parse*` and `stringify*` entry points; for encoding errors
+/// the offset is into the output. Its `Display` is the plain message, and it
+/// converts into a `String` with `?` for code that still handles errors as text.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_bytes, ErrorKind};
+///
+/// let error = parse_bytes(b"d4:infod6:lengthi1x2eee").unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::InvalidInteger);
+/// assert_eq!(error.offset, 21);
+/// assert_eq!(error.to_string(), "Invalid integer");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// Human readable description (one of the `error::messages` strings)
+    pub message: String,
+    /// Byte offset into the input at which the error was detected
+    pub offset: usize,
+    /// Key path of the value being read, if located (see [`ParseError::locate`])
+    pub path: Option<String>,
+}
+
+impl ParseError {
+    /// Creates a new parse error.
+    ///
+    /// # Arguments
+    /// * `message` - Description of the error
+    /// * `offset` - Byte offset at which the error was detected
+    pub fn new(message: impl Into<String>, offset: usize) -> Self {
+        Self {
+            message: message.into(),
+            offset,
+            path: None,
+        }
+    }
+
+    /// Sets the key path of the value being read when the error occurred.
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    /// Fills in the key path by walking `source` up to the error offset.
+    ///
+    /// Paths are dot separated keys with numeric list indexes, as used by
+    /// [`extract_streaming`](crate::extract_streaming); the root is the empty path.
+    ///
+    /// # Arguments
+    /// * `source` - The input the error was raised for
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{parse_lossy, ErrorKind};
+    ///
+    /// let data = b"d4:infod6:lengthi1x2eee";
+    /// let error = parse_lossy(data).1.remove(0).locate(data);
+    /// assert_eq!((error.kind(), error.path.as_deref()), (ErrorKind::InvalidInteger, Some("info.length")));
+    /// ```
+    pub fn locate(self, source: &[u8]) -> Self {
+        let path = path_at(source, self.offset);
+        self.with_path(path)
+    }
+
+    /// Returns the stable kind of this error, for matching without comparing messages
+    pub fn kind(&self) -> ErrorKind {
+        ErrorKind::from_message(&self.message)
+    }
+
+    /// Returns true if the input ended in the middle of a value, so a reader
+    /// receiving it in parts should wait for more rather than give up; see
+    /// [`ErrorKind::is_incomplete`].
+    pub fn is_incomplete(&self) -> bool {
+        self.kind().is_incomplete()
+    }
+
+    /// Serializes the error as a JSON object for structured error payloads.
+    ///
+    /// The object has `kind` (the [`ErrorKind`] variant name), `message`,
+    /// `offset` and `path` (null unless located) members.
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::ParseError;
+    ///
+    /// let error = ParseError::new("Unterminated list", 4).with_path("files");
+    /// assert_eq!(
+    ///     error.to_json(),
+    ///     r#"{"kind":"UnterminatedList","message":"Unterminated list","offset":4,"path":"files"}"#
+    /// );
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        let mut destination = Buffer::new();
+        destination.add_bytes(&format!("{{\"kind\":\"{:?}\",\"message\":\"", self.kind()));
+        escape_string(&self.message, &mut destination);
+        destination.add_bytes(&format!("\",\"offset\":{},\"path\":", self.offset));
+        match &self.path {
+            Some(path) => {
+                destination.add_byte(b'"');
+                escape_string(path, &mut destination);
+                destination.add_byte(b'"');
+            }
+            None => destination.add_bytes("null"),
+        }
+        destination.add_byte(b'}');
+        String::from_utf8_lossy(&destination.buffer).into_owned()
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for String {
+    fn from(error: ParseError) -> Self {
+        error.message
+    }
+}
+
+impl AsRef<str> for ParseError {
+    fn as_ref(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Compares the message only, so errors can be checked against `error::messages` constants
+impl PartialEq<&str> for ParseError {
+    fn eq(&self, other: &&str) -> bool {
+        self.message == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::messages::{ERR_INVALID_INTEGER, ERR_UNTERMINATED_LIST};
+
+    #[test]
+    fn parse_error_display_is_message() {
+        let error = ParseError::new(ERR_INVALID_INTEGER, 3);
+        assert_eq!(error.to_string(), ERR_INVALID_INTEGER);
+        assert_eq!(error.offset, 3);
+        assert_eq!(error.kind(), ErrorKind::InvalidInteger);
+    }
+
+    #[test]
+    #[cfg(feature = "json")]
+    fn to_json_escapes_and_handles_missing_path() {
+        let error = ParseError::new("Unexpected character: '\\\"'", 0);
+        assert_eq!(
+            error.to_json(),
+            r#"{"kind":"UnexpectedCharacter","message":"Unexpected character: '\\\"'","offset":0,"path":null}"#
+        );
+    }
+
+    #[test]
+    fn parse_error_converts_to_its_message() {
+        let error = ParseError::new(ERR_UNTERMINATED_LIST, 7);
+        assert_eq!(error, ERR_UNTERMINATED_LIST);
+        assert_eq!(String::from(error), ERR_UNTERMINATED_LIST);
+    }
+
+    #[test]
+    fn locate_fills_in_the_path() {
+        let data = b"d1:ali1ei2e";
+        let error = ParseError::new(ERR_UNTERMINATED_LIST, data.len()).locate(data);
+        assert_eq!(error.path.as_deref(), Some("a.2"));
+    }
+}
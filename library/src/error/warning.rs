@@ -0,0 +1,49 @@
+//! Warnings for deviations from bencode that lenient parsing let through.
+//!
+//! Each lenient toggle of [`ParserConfig`](crate::ParserConfig) has its own
+//! [`Deviation`], so a caller accepting input from a non-conforming encoder can
+//! see exactly which liberties the input took, and where.
+
+use core::fmt;
+
+/// Warning for an integer written with a leading '+'
+pub const WARN_PLUS_SIGN: &str = "Integer has a '+' sign";
+/// Warning for whitespace between values
+pub const WARN_WHITESPACE: &str = "Whitespace between values";
+
+/// A deviation from bencode accepted by a lenient parser toggle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Deviation {
+    /// An integer with a leading `+`, such as `i+42e`; allowed by
+    /// [`allow_plus_sign`](crate::ParserConfig::allow_plus_sign)
+    PlusSign,
+    /// Whitespace before a value or the end of a list or dictionary; allowed by
+    /// [`allow_whitespace`](crate::ParserConfig::allow_whitespace)
+    Whitespace,
+}
+
+impl Deviation {
+    /// Returns the warning message for the deviation
+    pub const fn message(&self) -> &'static str {
+        match self {
+            Deviation::PlusSign => WARN_PLUS_SIGN,
+            Deviation::Whitespace => WARN_WHITESPACE,
+        }
+    }
+}
+
+/// A deviation found while parsing, with where it was found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseWarning {
+    /// What the input did
+    pub deviation: Deviation,
+    /// Byte offset of the '+' or of the first whitespace byte of a run
+    pub offset: usize,
+}
+
+impl fmt::Display for ParseWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at offset {}", self.deviation.message(), self.offset)
+    }
+}
@@ -0,0 +1,217 @@
+//! Per-call timing and allocation counts for the parsers (requires `instrumentation` feature).
+//!
+//! Each function here runs one parser and returns its result together with a
+//! [`ParseMetrics`], so the recursive, iterative and borrowed parsers can be
+//! compared on a caller's own data without an external profiler. Unlike the
+//! [`metrics`](crate::metrics) module nothing is aggregated or exported; the
+//! figures belong to the single call that produced them.
+//!
+//! Allocations can only be seen by the global allocator, so they are counted
+//! only once [`CountingAllocator`] has been installed with `#[global_allocator]`;
+//! until then [`ParseMetrics::allocations`] is `None`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use crate::error::parse::ParseError;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::nodes::node::Node;
+
+/// Measurements of one parse.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseMetrics {
+    /// Wall-clock time spent parsing
+    pub duration: Duration,
+    /// Length of the input
+    pub bytes: usize,
+    /// Values in the parsed tree, counting containers and their entries (0 on error)
+    pub nodes: usize,
+    /// Heap allocations made by the calling thread during the parse, or None
+    /// if [`CountingAllocator`] is not the global allocator
+    pub allocations: Option<usize>,
+}
+
+/// Global allocator that counts allocations per thread and otherwise defers to [`System`].
+///
+/// # Example
+/// ```
+/// use bencode_lib::instrumentation::CountingAllocator;
+///
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator;
+/// ```
+#[derive(Debug, Default)]
+pub struct CountingAllocator;
+
+/// Set by the first allocation made through [`CountingAllocator`]
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+thread_local! {
+    // Constant initialised and without a destructor, so reading it never allocates
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Counts one allocation on the current thread
+fn count_allocation() {
+    INSTALLED.store(true, Ordering::Relaxed);
+    // Fails only while the thread is being torn down, when there is nothing to measure
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+// SAFETY: every request is passed unchanged to the system allocator
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        unsafe { System.alloc_zeroed(layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        unsafe { System.realloc(ptr, layout, new_size) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+/// Allocations made so far by the current thread
+fn allocations() -> usize {
+    ALLOCATIONS.try_with(Cell::get).unwrap_or(0)
+}
+
+/// Runs `parse` over `bytes` of input, measuring it; `count` gives the nodes in a successful result
+fn measure<T>(bytes: usize, parse: impl FnOnce() -> Result<T, ParseError>, count: fn(&T) -> usize) -> (Result<T, ParseError>, ParseMetrics) {
+    let allocations_before = allocations();
+    let start = Instant::now();
+    let result = parse();
+    let duration = start.elapsed();
+    let allocations_after = allocations();
+    let metrics = ParseMetrics {
+        duration,
+        bytes,
+        nodes: result.as_ref().map_or(0, count),
+        allocations: INSTALLED.load(Ordering::Relaxed).then(|| allocations_after - allocations_before),
+    };
+    (result, metrics)
+}
+
+/// Counts the values in an owned tree
+fn count_nodes(node: &Node) -> usize {
+    match node {
+        Node::List(items) => 1 + items.iter().map(count_nodes).sum::<usize>(),
+        Node::Dictionary(items) => 1 + items.values().map(count_nodes).sum::<usize>(),
+        Node::None => 0,
+        _ => 1,
+    }
+}
+
+/// Counts the values in a borrowed tree
+fn count_borrowed_nodes(node: &BorrowedNode) -> usize {
+    match node {
+        BorrowedNode::List(items) => 1 + items.iter().map(count_borrowed_nodes).sum::<usize>(),
+        BorrowedNode::Dictionary(items) => 1 + items.values().map(count_borrowed_nodes).sum::<usize>(),
+        _ => 1,
+    }
+}
+
+/// Parses `data` with the recursive parser ([`parse_bytes`](crate::parse_bytes)), measuring the call.
+///
+/// # Example
+/// ```
+/// use bencode_lib::instrumentation::parse_bytes;
+///
+/// let (node, metrics) = parse_bytes(b"li1ei2ee");
+/// assert!(node.is_ok());
+/// assert_eq!((metrics.bytes, metrics.nodes), (8, 3));
+/// ```
+pub fn parse_bytes(data: &[u8]) -> (Result<Node, ParseError>, ParseMetrics) {
+    measure(data.len(), || crate::parser::default::parse_bytes(data), count_nodes)
+}
+
+/// Parses `data` with the iterative parser ([`parse_bytes_iterative`](crate::parse_bytes_iterative)),
+/// measuring the call.
+pub fn parse_bytes_iterative(data: &[u8]) -> (Result<Node, ParseError>, ParseMetrics) {
+    measure(data.len(), || crate::parser::iterative::parse_bytes_iterative(data), count_nodes)
+}
+
+/// Parses `data` with the zero-copy parser ([`parse_borrowed`](crate::parse_borrowed)),
+/// measuring the call.
+pub fn parse_borrowed(data: &[u8]) -> (Result<BorrowedNode<'_>, ParseError>, ParseMetrics) {
+    measure(data.len(), || crate::parser::borrowed::parse_borrowed(data), count_borrowed_nodes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[global_allocator]
+    static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+    const DATA: &[u8] = b"d4:listli1ei2ee4:name4:demoe";
+
+    #[test]
+    fn every_parser_reports_the_same_shape() {
+        let (owned, recursive) = parse_bytes(DATA);
+        let (iterated, iterative) = parse_bytes_iterative(DATA);
+        let (borrowed, zero_copy) = parse_borrowed(DATA);
+
+        assert_eq!(owned.unwrap(), iterated.unwrap());
+        assert_eq!(borrowed.unwrap().as_dictionary().map(|dict| dict.len()), Some(2));
+        for metrics in [recursive, iterative, zero_copy] {
+            assert_eq!((metrics.bytes, metrics.nodes), (DATA.len(), 5));
+            assert!(metrics.allocations.is_some_and(|count| count > 0));
+        }
+    }
+
+    #[test]
+    fn allocations_are_those_of_the_calling_thread() {
+        use std::sync::{Arc, Barrier};
+        use std::thread;
+
+        // Another thread allocates throughout the measurement
+        let started = Arc::new(Barrier::new(2));
+        let stop = Arc::new(AtomicBool::new(false));
+        let allocator = {
+            let (started, stop) = (Arc::clone(&started), Arc::clone(&stop));
+            thread::spawn(move || {
+                started.wait();
+                while !stop.load(Ordering::Relaxed) {
+                    drop(std::hint::black_box(vec![0u8; 64]));
+                }
+            })
+        };
+        started.wait();
+
+        // Integers are parsed without touching the heap; the inner parser is
+        // measured so that the metrics feature's timer is not counted
+        let (result, metrics) = measure(
+            4,
+            || {
+                let node = crate::parser::borrowed::parse_integer(b"i42e", &mut 0).map_err(|message| ParseError::new(message, 0));
+                thread::sleep(Duration::from_millis(10));
+                node
+            },
+            count_borrowed_nodes,
+        );
+        stop.store(true, Ordering::Relaxed);
+        allocator.join().unwrap();
+
+        assert_eq!(result.unwrap().as_integer(), Some(42));
+        assert_eq!(metrics.allocations, Some(0));
+    }
+
+    #[test]
+    fn failed_parses_report_no_nodes() {
+        let (result, metrics) = parse_bytes(b"li1e");
+        assert!(result.is_err());
+        assert_eq!((metrics.bytes, metrics.nodes), (4, 0));
+    }
+}
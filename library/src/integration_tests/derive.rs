@@ -0,0 +1,65 @@
+//! Tests for the ToBencode and FromBencode derive macros (requires `derive` feature).
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use crate::{ErrorKind, FromBencode, Node, ToBencode, parse_bytes, stringify_to_bytes};
+
+    #[derive(ToBencode, FromBencode, Debug, PartialEq, Default)]
+    struct File {
+        length: u64,
+        path: Vec<String>,
+        #[bencode(default)]
+        md5sum: String,
+    }
+
+    #[derive(ToBencode, FromBencode, Debug, PartialEq)]
+    struct Info {
+        files: Vec<File>,
+        name: String,
+        #[bencode(rename = "piece length")]
+        piece_length: u32,
+        pieces: Vec<u8>,
+        private: Option<bool>,
+        #[bencode(skip)]
+        cached_size: usize,
+    }
+
+    #[derive(ToBencode, FromBencode, Debug, PartialEq)]
+    struct Torrent<T> {
+        announce: String,
+        info: T,
+    }
+
+    const TORRENT: &[u8] =
+        b"d8:announce3:url4:infod5:filesld6:lengthi5e4:pathl1:aeee4:name3:dir12:piece lengthi16e6:pieces2:\x01\x02ee";
+
+    #[test]
+    fn structs_round_trip_through_bencode() {
+        let torrent = Torrent::<Info>::from_bencode(&parse_bytes(TORRENT).unwrap()).unwrap();
+        assert_eq!(torrent.info.piece_length, 16);
+        assert_eq!(torrent.info.pieces, [1, 2]);
+        assert_eq!(torrent.info.private, None);
+        assert_eq!(torrent.info.files, [File { length: 5, path: vec!["a".to_string()], md5sum: String::new() }]);
+        assert_eq!(torrent.info.cached_size, 0);
+
+        let mut changed = torrent;
+        changed.info.cached_size = 99;
+        changed.info.files[0].md5sum = "abc".to_string();
+        let encoded = stringify_to_bytes(&changed.to_bencode().unwrap()).unwrap();
+        assert_eq!(
+            encoded,
+            b"d8:announce3:url4:infod5:filesld6:lengthi5e6:md5sum3:abc4:pathl1:aeee4:name3:dir12:piece lengthi16e6:pieces2:\x01\x02ee"
+        );
+    }
+
+    #[test]
+    fn decode_errors_give_the_field_path() {
+        let node = parse_bytes(b"d8:announce3:url4:infod5:filesld4:pathleee4:name3:dir12:piece lengthi16e6:pieces0:ee").unwrap();
+        let error = Torrent::<Info>::from_bencode(&node).unwrap_err();
+        assert_eq!((error.kind(), error.path.as_deref()), (ErrorKind::MissingField, Some("info.files.0.length")));
+
+        let error = Torrent::<Node>::from_bencode(&parse_bytes(b"d8:announcei1e4:infoi2ee").unwrap()).unwrap_err();
+        assert_eq!((error.kind(), error.path.as_deref()), (ErrorKind::UnexpectedType, Some("announce")));
+        assert_eq!(File::from_bencode(&Node::from([1])).unwrap_err().kind(), ErrorKind::UnexpectedType);
+    }
+}
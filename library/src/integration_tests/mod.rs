@@ -5,6 +5,8 @@ mod new_features;
 
 mod parser_dictionaries;
 
+mod parser_differential;
+
 mod parser_errors;
 
 mod parser_integers;
@@ -22,3 +24,7 @@ mod stringify_toml;
 mod stringify_xml;
 
 mod stringify_yaml;
+
+mod wire_compat;
+
+mod derive;
@@ -3,7 +3,7 @@
 #[cfg(test)]
 mod tests {
         use crate::{Node, make_node, parse_bytes, parse_str, stringify_to_bytes, stringify_to_string};
-        use std::collections::HashMap;
+        use crate::HashMap;
     
         #[test]
         fn test_parse_bytes_convenience() {
@@ -122,10 +122,15 @@ mod tests {
     fn test_dictionary_with_empty_string_key() {
         // Single empty string key should work
         let mut source = BufferSource::new(b"d0:i1ee");
-        // This should fail because empty string <= empty string (last_key starts as empty)
-        // Let's test that we can have an empty key if it's in proper order
-        // Actually, since last_key starts as "", an empty key will fail the <= check
-        // So let's test the failure case instead
+        match parse(&mut source) {
+            Ok(Node::Dictionary(dict)) => assert!(matches!(dict.get(""), Some(Node::Integer(1)))),
+            _ => panic!("Expected dictionary with empty key"),
+        }
+    }
+
+    #[test]
+    fn test_dictionary_with_duplicate_empty_string_key() {
+        let mut source = BufferSource::new(b"d0:i1e0:i2ee");
         assert!(matches!(parse(&mut source), Err(s) if s == ERR_DICT_KEYS_ORDER));
     }
     
@@ -194,15 +199,12 @@ mod tests {
     #[test]
     fn test_dictionary_with_all_value_types() {
         let mut source = BufferSource::new(b"d4:dicti42e3:inti1e4:listli1ee6:string5:valuee");
-        match parse(&mut source) {
-            Ok(Dictionary(dict)) => {
-                assert_eq!(dict.len(), 4);
-                assert!(matches!(dict.get("int"), Some(Node::Integer(1))));
-                assert!(matches!(dict.get("string"), Some(Node::Str(_))));
-                assert!(matches!(dict.get("list"), Some(Node::List(_))));
-                // dict key has value i42e which should fail as non-string key
-            }
-            _ => {}
+        if let Ok(Dictionary(dict)) = parse(&mut source) {
+            assert_eq!(dict.len(), 4);
+            assert!(matches!(dict.get("int"), Some(Node::Integer(1))));
+            assert!(matches!(dict.get("string"), Some(Node::Str(_))));
+            assert!(matches!(dict.get("list"), Some(Node::List(_))));
+            // dict key has value i42e which should fail as non-string key
         }
     }
     
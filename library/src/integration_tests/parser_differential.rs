@@ -0,0 +1,120 @@
+//! Integration tests checking that the recursive, iterative and borrowed parsers agree.
+
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::borrowed::validate_bencode;
+    use crate::parser::default::parse_bytes;
+    use crate::parser::differential::parsers_agree;
+    use crate::parser::lossy::parse_lossy;
+    use crate::parser::visitor::{Visitor, visit};
+
+    /// Well-formed and malformed seed inputs that mutations start from
+    const SEEDS: &[&[u8]] = &[
+        b"i42e",
+        b"i-42e",
+        b"4:spam",
+        b"0:",
+        b"le",
+        b"de",
+        b"li1e4:spamli2eee",
+        b"d3:bar4:spam3:fooi42ee",
+        b"d0:i1e1:ad1:bli1ei2eeee",
+        b"d8:announce3:url4:infod6:lengthi1e4:name1:a6:pieces3:\xFF\x00\xFEee",
+        b"i-0e",
+        b"i03e",
+        b"d1:bi1e1:ai2ee",
+    ];
+
+    /// Bytes that are meaningful to bencode and therefore interesting to insert
+    const ALPHABET: &[u8] = b"ilde0123456789:-\xFF";
+
+    /// Minimal xorshift generator so the corpus is deterministic without extra dependencies
+    struct XorShift(u64);
+
+    impl XorShift {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    fn mutate(seed: &[u8], rng: &mut XorShift) -> Vec<u8> {
+        let mut data = seed.to_vec();
+        for _ in 0..=rng.below(3) {
+            let position = rng.below(data.len() + 1);
+            match rng.below(3) {
+                0 if position < data.len() => {
+                    data.remove(position);
+                }
+                1 if position < data.len() => {
+                    data[position] = ALPHABET[rng.below(ALPHABET.len())];
+                }
+                _ => data.insert(position, ALPHABET[rng.below(ALPHABET.len())]),
+            }
+        }
+        data
+    }
+
+    #[test]
+    fn test_parsers_agree_on_seeds() {
+        for seed in SEEDS {
+            if let Err(divergence) = parsers_agree(seed) {
+                panic!("{:?}: {}", seed, divergence);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parsers_agree_on_mutated_inputs() {
+        let mut rng = XorShift(0x2545_F491_4F6C_DD1D);
+        for seed in SEEDS {
+            for _ in 0..500 {
+                let input = mutate(seed, &mut rng);
+                if let Err(divergence) = parsers_agree(&input) {
+                    panic!("{:?}: {}", input, divergence);
+                }
+            }
+        }
+    }
+
+    /// Visitor that ignores every event, used to check acceptance only
+    struct Ignore;
+
+    impl<'de> Visitor<'de> for Ignore {}
+
+    #[test]
+    fn test_visitor_agrees_on_mutated_inputs() {
+        let mut rng = XorShift(0xD1B5_4A32_D192_ED03);
+        for seed in SEEDS {
+            for _ in 0..500 {
+                let input = mutate(seed, &mut rng);
+                assert_eq!(visit(&input, &mut Ignore).is_ok(), parse_bytes(&input).is_ok(), "{:?}", input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_lossy_parser_agrees_on_mutated_inputs() {
+        let mut rng = XorShift(0x9E37_79B9_7F4A_7C15);
+        for seed in SEEDS {
+            for _ in 0..500 {
+                let input = mutate(seed, &mut rng);
+                let (node, errors) = parse_lossy(&input);
+                let strict = parse_bytes(&input)
+                    .map_err(String::from)
+                    .and_then(|node| validate_bencode(&input).map(|_| node));
+                match strict {
+                    Ok(expected) => assert!(errors.is_empty() && node == expected, "{:?}", input),
+                    Err(_) => assert!(!errors.is_empty(), "{:?}", input),
+                }
+            }
+        }
+    }
+}
@@ -16,19 +16,19 @@ mod tests {
     #[test]
     fn test_invalid_character() {
         let mut source = BufferSource::new(b"x123");
-        assert!(matches!(parse(&mut source), Err(s) if s.contains("Unexpected character")));
+        assert!(matches!(parse(&mut source), Err(s) if s.message.contains("Unexpected character")));
     }
     
     #[test]
     fn test_invalid_start_character_z() {
         let mut source = BufferSource::new(b"z");
-        assert!(matches!(parse(&mut source), Err(s) if s.contains("Unexpected character")));
+        assert!(matches!(parse(&mut source), Err(s) if s.message.contains("Unexpected character")));
     }
     
     #[test]
     fn test_invalid_start_character_dash() {
         let mut source = BufferSource::new(b"-42");
-        assert!(matches!(parse(&mut source), Err(s) if s.contains("Unexpected character")));
+        assert!(matches!(parse(&mut source), Err(s) if s.message.contains("Unexpected character")));
     }
     
     #[test]
@@ -42,13 +42,13 @@ mod tests {
     #[test]
     fn test_dictionary_missing_value() {
         let mut source = BufferSource::new(b"d3:keye");
-        assert!(matches!(parse(&mut source), Err(_)));
+        assert!(parse(&mut source).is_err());
     }
     
     #[test]
     fn test_list_with_invalid_element() {
         let mut source = BufferSource::new(b"lxe");
-        assert!(matches!(parse(&mut source), Err(s) if s.contains("Unexpected character")));
+        assert!(matches!(parse(&mut source), Err(s) if s.message.contains("Unexpected character")));
     }
     
     #[test]
@@ -57,5 +57,20 @@ mod tests {
         let mut source = BufferSource::new(b":test");
         assert!(matches!(parse(&mut source), Err(s) if s == ERR_INVALID_STRING_LENGTH));
     }
-    
+
+    #[test]
+    fn test_unexpected_character_context_agrees_across_parsers() {
+        use crate::parser::tokenizer::Tokenizer;
+        use crate::{parse_borrowed, parse_bytes, parse_bytes_iterative, parse_lossy, validate_bencode};
+        let data = b"d1:ali1ed1:bxeee";
+        let expected = unexpected_character('x', Expecting::DictValue, 3);
+        let located = crate::ParseError::new(expected.clone(), 12);
+        assert_eq!(parse_bytes(data).unwrap_err(), located);
+        assert_eq!(parse_bytes_iterative(data).unwrap_err(), located);
+        assert_eq!(parse_borrowed(data).unwrap_err(), located);
+        assert_eq!(validate_bencode(data).unwrap_err(), expected);
+        assert_eq!(parse_lossy(data).1[0].message, expected);
+        let error = Tokenizer::new(data).find_map(Result::err).unwrap();
+        assert_eq!((error.message, error.offset), (expected, 12));
+    }
 }
@@ -6,7 +6,8 @@ mod tests {
     use crate::BufferSource;
     use crate::error::messages::*;
     use crate::nodes::node::Node;
-    use crate::parser::default::parse;
+    use crate::parser::default::{parse, parse_bytes_with_config};
+    use crate::{ParserConfig, stringify_to_bytes, validate_bencode_with_limits};
     
     #[test]
     fn test_invalid_integer_format_fails() {
@@ -44,6 +45,7 @@ mod tests {
         assert!(matches!(parse(&mut source), Err(s) if s == ERR_UNTERMINATED_INTEGER));
     }
     
+    #[cfg(not(feature = "int32"))]
     #[test]
     fn test_large_positive_integer() {
         let mut source = BufferSource::new(b"i9223372036854775807e");
@@ -53,6 +55,7 @@ mod tests {
         ));
     }
     
+    #[cfg(not(feature = "int32"))]
     #[test]
     fn test_large_negative_integer() {
         let mut source = BufferSource::new(b"i-9223372036854775808e");
@@ -91,7 +94,52 @@ mod tests {
     fn test_integer_overflow() {
         // Test a number that's too large for i64
         let mut source = BufferSource::new(b"i99999999999999999999e");
-        assert!(matches!(parse(&mut source), Err(s) if s == ERR_INVALID_INTEGER));
+        assert!(matches!(parse(&mut source), Err(s) if s == ERR_INTEGER_OVERFLOW));
+        let mut source = BufferSource::new(b"i-99999999999999999999e");
+        assert!(matches!(parse(&mut source), Err(s) if s == ERR_INTEGER_OVERFLOW));
+    }
+
+    #[test]
+    fn test_big_integers_are_kept_when_configured() {
+        let config = ParserConfig::new().with_big_integers(true);
+        let node = parse_bytes_with_config(b"li0099999999999999999999ei-99999999999999999999ei7ee", &config).unwrap();
+        assert_eq!(
+            node,
+            Node::List(vec![
+                Node::BigInteger("99999999999999999999".to_string()),
+                Node::BigInteger("-99999999999999999999".to_string()),
+                Node::Integer(7),
+            ])
+        );
+        assert_eq!(stringify_to_bytes(&node).unwrap(), b"li99999999999999999999ei-99999999999999999999ei7ee");
+        assert!(validate_bencode_with_limits(b"i99999999999999999999e", &config).is_ok());
+        assert!(validate_bencode_with_limits(b"i99999999999999999999e", &ParserConfig::new()).is_err());
+        // Malformed integers are still rejected
+        let error = parse_bytes_with_config(b"i99999999999999999999xe", &config).unwrap_err();
+        assert_eq!(error.message, ERR_INVALID_INTEGER);
+    }
+
+    #[cfg(all(feature = "int32", not(feature = "int32-saturate")))]
+    #[test]
+    fn test_int32_big_integers_are_kept_when_configured() {
+        let config = ParserConfig::new().with_big_integers(true);
+        let node = parse_bytes_with_config(b"i2147483648e", &config).unwrap();
+        assert_eq!(node, Node::BigInteger("2147483648".to_string()));
+    }
+
+    #[cfg(all(feature = "int32", not(feature = "int32-saturate")))]
+    #[test]
+    fn test_int32_out_of_range_integer() {
+        let mut source = BufferSource::new(b"i2147483648e");
+        assert!(matches!(parse(&mut source), Err(s) if s == ERR_INTEGER_OUT_OF_RANGE));
+        let mut source = BufferSource::new(b"i-2147483648e");
+        assert!(matches!(parse(&mut source), Ok(Node::Integer(i32::MIN))));
+    }
+
+    #[cfg(feature = "int32-saturate")]
+    #[test]
+    fn test_int32_saturated_integer() {
+        let mut source = BufferSource::new(b"i9223372036854775807e");
+        assert!(matches!(parse(&mut source), Ok(Node::Integer(i32::MAX))));
     }
-    
 }
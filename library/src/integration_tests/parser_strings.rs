@@ -23,7 +23,7 @@ mod tests {
     #[test]
     fn test_invalid_string_length() {
         let mut source = BufferSource::new(b"a:test");
-        assert!(matches!(parse(&mut source), Err(s) if s.contains("Unexpected character")));
+        assert!(matches!(parse(&mut source), Err(s) if s.message.contains("Unexpected character")));
     }
     
     #[test]
@@ -41,7 +41,7 @@ mod tests {
     #[test]
     fn test_string_truncated() {
         let mut source = BufferSource::new(b"10:short");
-        assert!(matches!(parse(&mut source), Err(s) if s == ERR_INVALID_STRING_LENGTH));
+        assert!(matches!(parse(&mut source), Err(s) if s == ERR_STRING_TOO_SHORT));
     }
     
     #[test]
@@ -60,7 +60,7 @@ mod tests {
     fn test_string_with_unicode() {
         // Test parsing a string with UTF-8 multi-byte characters
         let utf8_str = "test"; // Simple ASCII to avoid encoding issues
-        let byte_len = utf8_str.as_bytes().len();
+        let byte_len = utf8_str.len();
         let data_str = format!("{}:{}", byte_len, utf8_str);
         let mut source = BufferSource::new(data_str.as_bytes());
         // This test just verifies that string parsing works correctly
@@ -71,7 +71,22 @@ mod tests {
     fn test_very_large_string_length() {
         let mut source = BufferSource::new(b"999999999999:test");
         // This should fail because we can't read such a large string
-        assert!(matches!(parse(&mut source), Err(_)));
+        assert!(parse(&mut source).is_err());
+    }
+
+    #[test]
+    fn test_binary_string_round_trips() {
+        use crate::parser::borrowed::parse_borrowed;
+        use crate::parser::iterative::parse_iterative;
+        use crate::stringify::default::stringify_to_bytes;
+        let data = b"d6:pieces4:\x00\xff\x9c\x01e";
+        for node in [
+            crate::parse_bytes(data).unwrap(),
+            parse_iterative(&mut BufferSource::new(data)).unwrap(),
+            parse_borrowed(data).unwrap().to_node(),
+        ] {
+            assert_eq!(node.get("pieces"), Some(&Node::Bytes(vec![0x00, 0xff, 0x9c, 0x01])));
+            assert_eq!(stringify_to_bytes(&node).unwrap(), data);
+        }
     }
-    
 }
@@ -8,7 +8,7 @@ mod tests {
     use crate::BufferDestination;
     use crate::nodes::node::{Node, make_node};
     use crate::stringify::default::stringify;
-    use std::collections::HashMap;
+    use crate::HashMap;
     
     #[test]
     fn test_stringify_complex_dictionary() {
@@ -139,6 +139,7 @@ mod tests {
         assert_eq!(destination.to_string(), "i0e");
     }
     
+    #[cfg(not(feature = "int32"))]
     #[test]
     fn test_stringify_large_integer() {
         let mut destination = BufferDestination::new();
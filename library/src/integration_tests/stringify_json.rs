@@ -9,7 +9,7 @@ mod tests {
     use crate::io::traits::IDestination;
     use crate::nodes::node::Node;
     use crate::stringify::json::stringify;
-    use std::collections::HashMap;
+    use crate::HashMap;
     
     #[test]
     fn test_stringify_dictionary_sorting() {
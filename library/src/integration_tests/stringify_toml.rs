@@ -9,7 +9,7 @@ mod tests {
     use crate::BufferSource;
     use crate::nodes::node::{Node, make_node};
     use crate::stringify::toml::stringify;
-    use std::collections::HashMap;
+    use crate::HashMap;
     
     #[test]
     fn test_stringify_nested_dictionary() {
@@ -8,7 +8,7 @@ mod tests {
     use crate::BufferDestination;
     use crate::nodes::node::Node;
     use crate::stringify::xml::stringify;
-    use std::collections::HashMap;
+    use crate::HashMap;
     
     #[test]
     fn test_nested_dictionary() {
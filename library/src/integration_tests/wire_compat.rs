@@ -0,0 +1,115 @@
+//! Wire compatibility tests for the persisted binary formats.
+//!
+//! Each fixture holds the exact bytes a format version is made of. A fixture
+//! must never be edited: if the current writer stops producing it, the format
+//! has changed and needs a new version with a reader for the old one.
+
+#[cfg(test)]
+mod tests {
+    use crate::nodes::flat::FLAT_FORMAT_VERSION;
+    use crate::{ErrorKind, FlatNode, Node, make_node};
+
+    /// `{"a": 1, "b": ["", None]}` in flat format version 1
+    const FLAT_V1: &[u8] = b"\x89BFN\x01\0\0\0\
+        d\x02\0\0\0\x22\0\0\0\
+        \x01\0\0\0a\
+        i\x01\0\0\0\0\0\0\0\
+        \x01\0\0\0b\
+        l\x02\0\0\0\x06\0\0\0\
+        s\0\0\0\0\
+        n";
+
+    /// `{"pieces": b"\xff\xfe"}` in flat format version 1, holding a binary string
+    const FLAT_V1_BINARY: &[u8] = b"\x89BFN\x01\0\0\0\
+        d\x01\0\0\0\x11\0\0\0\
+        \x06\0\0\0pieces\
+        s\x02\0\0\0\xff\xfe";
+
+    /// Length of the version 1 header, absent from version 0 buffers
+    const FLAT_HEADER: usize = 8;
+
+    fn sample() -> Node {
+        make_node([("a", make_node(1)), ("b", Node::List(vec![make_node(""), Node::None]))])
+    }
+
+    fn binary_sample() -> Node {
+        make_node([("pieces", Node::Bytes(vec![0xff, 0xfe]))])
+    }
+
+    #[test]
+    fn flat_writer_still_produces_version_1() {
+        assert_eq!(FLAT_FORMAT_VERSION, 1);
+        assert_eq!(FlatNode::from_node(&sample()).unwrap().as_bytes(), FLAT_V1);
+        assert_eq!(FlatNode::from_node(&binary_sample()).unwrap().as_bytes(), FLAT_V1_BINARY);
+    }
+
+    #[test]
+    fn flat_reader_reads_every_version() {
+        let current = FlatNode::from_bytes(FLAT_V1.to_vec()).unwrap();
+        assert_eq!(current.to_node(), sample());
+        // Version 0 is version 1 without the header
+        let legacy = FlatNode::from_bytes(FLAT_V1[FLAT_HEADER..].to_vec()).unwrap();
+        assert_eq!(legacy, current);
+        assert_eq!(legacy.as_bytes(), FLAT_V1);
+        let binary = FlatNode::from_bytes(FLAT_V1_BINARY.to_vec()).unwrap();
+        assert_eq!(binary.to_node(), binary_sample());
+    }
+
+    #[test]
+    fn flat_reader_rejects_unknown_versions() {
+        let mut newer = FLAT_V1.to_vec();
+        newer[4] = 2;
+        let error = FlatNode::from_bytes(newer).unwrap_err();
+        assert_eq!(ErrorKind::from_message(&error), ErrorKind::UnsupportedFormatVersion);
+    }
+
+    #[cfg(feature = "std")]
+    mod archive {
+        use std::fs;
+        use std::path::PathBuf;
+
+        use crate::ErrorKind;
+        use crate::storage::archive::{ARCHIVE_FORMAT_VERSION, Archive, ArchiveWriter};
+
+        /// Records `a` = `i1e` and `b` = `4:spam` in archive format version 1
+        const ARCHIVE_V1: &[u8] = b"BENCARC1\
+            i1e4:spam\
+            \x01\0\0\0a\x08\0\0\0\0\0\0\0\x03\0\0\0\0\0\0\0\
+            \x01\0\0\0b\x0b\0\0\0\0\0\0\0\x06\0\0\0\0\0\0\0\
+            \x11\0\0\0\0\0\0\0\x02\0\0\0BENCARC1";
+
+        fn temp_path(name: &str) -> PathBuf {
+            std::env::temp_dir().join(format!("bencode_wire_compat_{}_{}.bar", std::process::id(), name))
+        }
+
+        #[test]
+        fn archive_writer_still_produces_version_1() {
+            let path = temp_path("write");
+            let mut writer = ArchiveWriter::create(&path).unwrap();
+            writer.add("a", b"i1e").unwrap();
+            writer.add("b", b"4:spam").unwrap();
+            writer.finish().unwrap();
+            let written = fs::read(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(ARCHIVE_FORMAT_VERSION, 1);
+            assert_eq!(written, ARCHIVE_V1);
+        }
+
+        #[test]
+        fn archive_reader_reads_version_1_and_rejects_newer() {
+            let path = temp_path("read");
+            fs::write(&path, ARCHIVE_V1).unwrap();
+            let records = Archive::open(&path).and_then(|mut archive| Ok((archive.get("a")?, archive.get("b")?)));
+            let mut newer = ARCHIVE_V1.to_vec();
+            newer[7] = b'2';
+            fs::write(&path, &newer).unwrap();
+            let error = Archive::open(&path).err();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(records.unwrap(), (Some(b"i1e".to_vec()), Some(b"4:spam".to_vec())));
+            let kind = error.as_deref().map(ErrorKind::from_message);
+            assert_eq!(kind, Some(ErrorKind::UnsupportedFormatVersion));
+        }
+    }
+}
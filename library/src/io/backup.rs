@@ -0,0 +1,134 @@
+//! Backups of files about to be overwritten, following a [`BackupPolicy`].
+
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::BackupPolicy;
+
+/// Extension added to the name of a backup
+pub const BACKUP_EXTENSION: &str = "bak";
+
+/// Returns `path` with `.<suffix>` appended to its file name
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.file_name().unwrap_or_default());
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// Returns the timestamped backups of `path` with their timestamps, oldest first
+fn timestamped_backups(path: &Path) -> io::Result<Vec<(u128, PathBuf)>> {
+    let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+        return Ok(Vec::new());
+    };
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let suffix = format!(".{}", BACKUP_EXTENSION);
+    let mut backups: Vec<(u128, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let file_name = entry.file_name();
+            let stamp = file_name.to_str()?.strip_prefix(name)?.strip_prefix('.')?.strip_suffix(&suffix)?;
+            if stamp.is_empty() || !stamp.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            Some((stamp.parse().ok()?, path.with_file_name(file_name)))
+        })
+        .collect();
+    backups.sort();
+    Ok(backups)
+}
+
+/// Copies an existing file aside before it is overwritten.
+///
+/// Nothing is done if the file does not exist. Under
+/// [`BackupPolicy::Timestamped`] the oldest backups beyond the limit are removed.
+///
+/// # Returns
+/// The path of the new backup, if one was made
+pub(crate) fn back_up(path: &Path, policy: BackupPolicy) -> io::Result<Option<PathBuf>> {
+    if !path.is_file() {
+        return Ok(None);
+    }
+    match policy {
+        BackupPolicy::None | BackupPolicy::Timestamped(0) => Ok(None),
+        BackupPolicy::Single => {
+            let backup = with_suffix(path, BACKUP_EXTENSION);
+            fs::copy(path, &backup)?;
+            Ok(Some(backup))
+        }
+        BackupPolicy::Timestamped(keep) => {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |elapsed| elapsed.as_millis());
+            let existing = timestamped_backups(path)?;
+            // Keep names in time order even if the clock steps back or two
+            // saves land in the same millisecond
+            let stamp = existing.last().map_or(now, |(last, _)| now.max(last + 1));
+            let backup = with_suffix(path, &format!("{}.{}", stamp, BACKUP_EXTENSION));
+            fs::copy(path, &backup)?;
+            let excess = (existing.len() + 1).saturating_sub(keep);
+            for (_, old) in existing.into_iter().take(excess) {
+                fs::remove_file(old)?;
+            }
+            Ok(Some(backup))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_dir;
+
+    #[test]
+    fn missing_files_and_no_policy_make_no_backup() {
+        let dir = create_test_dir("test_backup_none", &[]);
+        let path = dir.join("state.bencode");
+        let missing = back_up(&path, BackupPolicy::Single).unwrap();
+        fs::write(&path, "i1e").unwrap();
+        let none = back_up(&path, BackupPolicy::None).unwrap();
+        let entries = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!((missing, none, entries), (None, None, 1));
+    }
+
+    #[test]
+    fn single_backups_replace_each_other() {
+        let dir = create_test_dir("test_backup_single", &[]);
+        let path = dir.join("state.bencode");
+        fs::write(&path, "i1e").unwrap();
+        back_up(&path, BackupPolicy::Single).unwrap();
+        fs::write(&path, "i2e").unwrap();
+        let backup = back_up(&path, BackupPolicy::Single).unwrap().unwrap();
+        let content = fs::read(&backup).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(backup, dir.join("state.bencode.bak"));
+        assert_eq!(content, b"i2e");
+    }
+
+    #[test]
+    fn timestamped_backups_are_rotated() {
+        let dir = create_test_dir("test_backup_rotate", &[]);
+        let path = dir.join("state.bencode");
+        fs::write(dir.join("state.bencode.notes.bak"), "unrelated").unwrap();
+        let mut made = Vec::new();
+        for value in 1..=4 {
+            fs::write(&path, format!("i{}e", value)).unwrap();
+            made.push(back_up(&path, BackupPolicy::Timestamped(2)).unwrap().unwrap());
+        }
+        let kept = timestamped_backups(&path).unwrap();
+        let contents: Vec<Vec<u8>> = kept.iter().map(|(_, backup)| fs::read(backup).unwrap()).collect();
+        let unrelated = dir.join("state.bencode.notes.bak").is_file();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(kept.iter().map(|(_, backup)| backup).collect::<Vec<_>>(), vec![&made[2], &made[3]]);
+        assert_eq!(contents, vec![b"i3e".to_vec(), b"i4e".to_vec()]);
+        assert!(unrelated);
+    }
+}
@@ -2,6 +2,7 @@
 use alloc::{string::String, vec, vec::Vec};
 
 use crate::io::traits::IDestination;
+use core::fmt;
 /// A memory buffer implementation for storing encoded bencode data as bytes.
 /// Provides functionality to write and manipulate byte content in memory.
 pub struct Buffer {
@@ -18,12 +19,34 @@ impl Buffer {
         Self { buffer: vec![] }
     }
 
-    /// Converts the buffer content to a String.
-    ///
-    /// # Returns
-    /// A String containing UTF-8 interpretation of the buffer bytes.
-    pub fn to_string(&self) -> String {
-        String::from_utf8_lossy(&self.buffer).into_owned()
+    /// Creates an empty Buffer with room for `capacity` bytes before it reallocates.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Returns the bytes written so far.
+    pub fn as_slice(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    /// Consumes the Buffer, returning the bytes written to it.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.buffer
+    }
+}
+
+impl Default for Buffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Displays the buffer content as a (lossy) UTF-8 interpretation of its bytes.
+impl fmt::Display for Buffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.buffer))
     }
 }
 
@@ -38,6 +61,11 @@ impl IDestination for Buffer {
         self.buffer.extend_from_slice(bytes.as_bytes());
     }
 
+    /// Adds bytes that need not be UTF-8 to the buffer.
+    fn add_raw(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
     /// Clears all content from the buffer.
     fn clear(&mut self) {
         self.buffer.clear();
@@ -93,6 +121,15 @@ mod tests {
         assert_eq!(buffer.last(), None);
     }
     #[test]
+    fn add_raw_keeps_binary_data() {
+        let mut destination = Buffer::with_capacity(4);
+        destination.add_raw(b"\xff\x00");
+        destination.add_bytes("ab");
+        assert_eq!(destination.as_slice(), b"\xff\x00ab");
+        assert_eq!(destination.last(), Some(b'b'));
+        assert_eq!(destination.into_bytes(), b"\xff\x00ab");
+    }
+    #[test]
     fn to_string_handles_non_utf8() {
         let mut buffer = Buffer::new();
         buffer.add_byte(0xFF);
@@ -0,0 +1,81 @@
+use crate::io::traits::IDestination;
+
+/// A destination that keeps no data, only counting the bytes written to it.
+/// Stringifying into it measures an encoding without allocating for the output.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{make_node, stringify, SizeCountingDestination};
+///
+/// let mut destination = SizeCountingDestination::new();
+/// stringify(&make_node(vec![make_node(-42), make_node("spam")]), &mut destination).unwrap();
+/// assert_eq!(destination.count(), 13);
+/// ```
+#[derive(Debug, Default)]
+pub struct Counting {
+    /// Bytes written since creation or the last clear
+    count: usize,
+    /// The last byte written
+    last: Option<u8>,
+}
+
+impl Counting {
+    /// Creates a destination with nothing written.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of bytes written since creation or the last clear.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+}
+
+impl IDestination for Counting {
+    /// Counts a single byte.
+    fn add_byte(&mut self, byte: u8) {
+        self.count += 1;
+        self.last = Some(byte);
+    }
+
+    /// Counts the bytes of a string slice.
+    fn add_bytes(&mut self, bytes: &str) {
+        self.add_raw(bytes.as_bytes());
+    }
+
+    /// Counts bytes that need not be UTF-8.
+    fn add_raw(&mut self, bytes: &[u8]) {
+        self.count += bytes.len();
+        if let Some(&byte) = bytes.last() {
+            self.last = Some(byte);
+        }
+    }
+
+    /// Resets the count.
+    fn clear(&mut self) {
+        self.count = 0;
+        self.last = None;
+    }
+
+    /// Returns the last byte written, if any.
+    fn last(&self) -> Option<u8> {
+        self.last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_bytes_and_tracks_the_last_one() {
+        let mut destination = Counting::new();
+        assert_eq!((destination.count(), destination.last()), (0, None));
+        destination.add_byte(b'i');
+        destination.add_bytes("32");
+        destination.add_bytes("");
+        assert_eq!((destination.count(), destination.last()), (3, Some(b'2')));
+        destination.clear();
+        assert_eq!((destination.count(), destination.last()), (0, None));
+    }
+}
@@ -1,14 +1,24 @@
 use std::fs::File as StdFile;
 use std::io::{Write, Read, Seek};
+use std::path::{Path, PathBuf};
+use crate::config::WriteOptions;
+use crate::io::lock::create_locked;
+use crate::io::path::long_path;
 use crate::io::traits::IDestination;
 
 /// A file-based destination for writing bencode data to disk.
 /// Implements file operations for storing and manipulating encoded data.
+///
+/// With the `fs2` feature the file is locked exclusively from creation until
+/// the destination is dropped, so another process writing the same file waits
+/// rather than interleaving its output.
 pub struct File {
     /// The underlying file handle for I/O operations
     file: StdFile,
-    /// Name/path of the file being operated on
+    /// Name/path of the file being operated on, for display
     file_name: String,
+    /// Path used to reopen the file, in extended-length form where needed
+    path: PathBuf,
     /// Current length of the file in bytes
     file_length: usize,
 }
@@ -17,14 +27,30 @@ impl File {
     /// Creates a new File instance with the specified path.
     ///
     /// # Arguments
-    /// * `path` - The file path where the data will be written
+    /// * `path` - The file path where the data will be written (need not be valid UTF-8)
     ///
     /// # Returns
     /// A Result containing the new File instance or an IO error
-    pub fn new(path: &str) -> std::io::Result<Self> {
+    pub fn new<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Self::with_options(path, &WriteOptions::new())
+    }
+
+    /// Creates a new File instance, first backing up any file at the path as
+    /// `options` asks.
+    ///
+    /// # Arguments
+    /// * `path` - The file path where the data will be written (need not be valid UTF-8)
+    /// * `options` - The backup policy for an existing file
+    ///
+    /// # Returns
+    /// A Result containing the new File instance or an IO error
+    pub fn with_options<P: AsRef<Path>>(path: P, options: &WriteOptions) -> std::io::Result<Self> {
+        let file_name = path.as_ref().to_string_lossy().into_owned();
+        let path = long_path(path.as_ref());
         Ok(Self {
-            file: StdFile::create(path)?,
-            file_name: path.to_string(),
+            file: create_locked(&path, options)?,
+            file_name,
+            path,
             file_length: 0,
         })
     }
@@ -33,9 +59,13 @@ impl File {
     pub fn file_length(&self) -> usize {
         self.file_length
     }
-    /// Returns the name/path of the file.
+    /// Returns the name/path of the file (lossily converted if not valid UTF-8).
     pub fn file_name(&self) -> &str {
-        &self.file_name.as_str()
+        self.file_name.as_str()
+    }
+    /// Returns the exact path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
     }
     /// Closes the file handle.
     pub fn close(&self) -> std::io::Result<()> {
@@ -59,13 +89,22 @@ impl IDestination for File {
     /// # Arguments
     /// * `s` - The string to append as bytes
     fn add_bytes(&mut self, s: &str) {
-        self.file.write_all(s.as_bytes()).unwrap();
-        self.file_length = self.file_length + s.len();
+        self.add_raw(s.as_bytes());
     }
 
-    /// Clears the file content by recreating it.
+    /// Adds bytes that need not be UTF-8 to the end of the file.
+    ///
+    /// # Arguments
+    /// * `bytes` - The bytes to append
+    fn add_raw(&mut self, bytes: &[u8]) {
+        self.file.write_all(bytes).unwrap();
+        self.file_length += bytes.len();
+    }
+
+    /// Clears the file content, keeping the handle and so any lock held on it.
     fn clear(&mut self) {
-        self.file = StdFile::create(&self.file_name).unwrap();
+        self.file.set_len(0).unwrap();
+        self.file.rewind().unwrap();
         self.file_length = 0;
     }
 
@@ -78,7 +117,7 @@ impl IDestination for File {
             None
         } else {
             let mut buf = vec![0];
-            let mut file = StdFile::open(&self.file_name).unwrap();
+            let mut file = StdFile::open(&self.path).unwrap();
             file.seek(std::io::SeekFrom::End(-1)).unwrap();
             file.read_exact(&mut buf).unwrap();
             Some(buf[0])
@@ -194,6 +233,20 @@ mod tests {
         let path = "test_name.txt";
         let file = File::new(path)?;
         assert_eq!(file.file_name(), path);
+        assert_eq!(file.path(), Path::new(path));
+        fs::remove_file(path)?;
+        Ok(())
+    }
+    #[cfg(unix)]
+    #[test]
+    fn non_utf8_path_works() -> std::io::Result<()> {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        let path = Path::new(OsStr::from_bytes(b"test_name_\xff.txt"));
+        let mut file = File::new(path)?;
+        file.add_bytes("i1e");
+        assert_eq!(file.path(), path);
+        assert_eq!(fs::read(path)?, b"i1e");
         fs::remove_file(path)?;
         Ok(())
     }
@@ -225,6 +278,36 @@ mod tests {
         Ok(())
     }
 
+    #[cfg(feature = "fs2")]
+    #[test]
+    fn file_is_locked_until_dropped() -> std::io::Result<()> {
+        let path = "test_locked.txt";
+        let mut file = File::new(path)?;
+        file.add_bytes("test");
+        file.clear();
+        let other = StdFile::open(path)?;
+        assert!(fs2::FileExt::try_lock_shared(&other).is_err());
+        drop(file);
+        assert!(fs2::FileExt::try_lock_shared(&other).is_ok());
+        fs::remove_file(path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn with_options_backs_up_the_replaced_file() -> std::io::Result<()> {
+        use crate::config::BackupPolicy;
+        let path = "test_backup_destination.txt";
+        fs::write(path, "i1e")?;
+        let mut file = File::with_options(path, &WriteOptions::new().with_backup(BackupPolicy::Single))?;
+        file.add_bytes("i2e");
+        drop(file);
+        assert_eq!(fs::read(path)?, b"i2e");
+        assert_eq!(fs::read("test_backup_destination.txt.bak")?, b"i1e");
+        fs::remove_file(path)?;
+        fs::remove_file("test_backup_destination.txt.bak")?;
+        Ok(())
+    }
+
     #[test]
     fn close_works() -> std::io::Result<()> {
         let path = "test_name.txt";
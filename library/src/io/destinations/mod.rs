@@ -1,5 +1,10 @@
 /// Module providing a buffer-based destination for writing bencode data into memory
 pub mod buffer;
+/// Module providing a destination that only counts the bytes written to it
+pub mod counting;
 /// Module providing a file-based destination for writing bencode data to disk
 #[cfg(feature = "std")]
-pub mod file;
\ No newline at end of file
+pub mod file;
+/// Module providing a destination adapter writing bencode data to any std::io::Write stream
+#[cfg(feature = "std")]
+pub mod writer;
@@ -0,0 +1,185 @@
+use crate::io::traits::IDestination;
+use std::io::{BufWriter, Write};
+
+/// A destination writing bencode data to any [`Write`] stream, such as a
+/// `TcpStream`, a compressor or standard output.
+///
+/// Output is buffered, so the encoder's many small writes reach the stream in
+/// large ones. A write error is kept and returned by [`into_inner`](Writer::into_inner)
+/// or [`flush`](Writer::flush), and nothing more is written until it has been
+/// returned. Bytes handed to a stream cannot be taken back, so
+/// [`clear`](IDestination::clear) only forgets the last byte written.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{WriterDestination, make_node, stringify};
+///
+/// let mut destination = WriterDestination::new(Vec::new());
+/// stringify(&make_node([("name", make_node("demo"))]), &mut destination).unwrap();
+/// assert_eq!(destination.into_inner().unwrap(), b"d4:name4:demoe");
+/// ```
+pub struct Writer<W: Write> {
+    /// The buffered stream
+    writer: BufWriter<W>,
+    /// The last byte written, if any
+    last: Option<u8>,
+    /// The first write error, if any
+    error: Option<std::io::Error>,
+}
+
+impl<W: Write> Writer<W> {
+    /// Creates a new Writer with the default buffer size.
+    ///
+    /// # Arguments
+    /// * `writer` - The stream to write to
+    ///
+    /// # Returns
+    /// A new Writer
+    pub fn new(writer: W) -> Self {
+        Self::from_buffered(BufWriter::new(writer))
+    }
+
+    /// Creates a new Writer buffering up to `capacity` bytes before writing.
+    ///
+    /// # Arguments
+    /// * `capacity` - The size of the internal buffer
+    /// * `writer` - The stream to write to
+    ///
+    /// # Returns
+    /// A new Writer
+    pub fn with_capacity(capacity: usize, writer: W) -> Self {
+        Self::from_buffered(BufWriter::with_capacity(capacity, writer))
+    }
+
+    /// Creates a Writer over a buffered stream
+    fn from_buffered(writer: BufWriter<W>) -> Self {
+        Self {
+            writer,
+            last: None,
+            error: None,
+        }
+    }
+
+    /// Writes buffered bytes to the stream.
+    ///
+    /// # Returns
+    /// The first write error, if any
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        if let Some(error) = self.error.take() {
+            return Err(error);
+        }
+        self.writer.flush()
+    }
+
+    /// Writes buffered bytes and returns the stream.
+    ///
+    /// # Returns
+    /// * `Ok(W)` - The stream, with everything written
+    /// * `Err(std::io::Error)` - The first write error
+    pub fn into_inner(mut self) -> std::io::Result<W> {
+        self.flush()?;
+        self.writer.into_inner().map_err(|error| error.into_error())
+    }
+
+    /// Writes bytes unless an earlier write failed
+    fn write(&mut self, bytes: &[u8]) {
+        if self.error.is_none() {
+            match self.writer.write_all(bytes) {
+                Ok(()) => self.last = bytes.last().copied().or(self.last),
+                Err(error) => self.error = Some(error),
+            }
+        }
+    }
+}
+
+impl<W: Write> IDestination for Writer<W> {
+    /// Adds a single byte to the stream.
+    fn add_byte(&mut self, byte: u8) {
+        self.write(&[byte]);
+    }
+
+    /// Adds multiple bytes from a string slice to the stream.
+    fn add_bytes(&mut self, bytes: &str) {
+        self.write(bytes.as_bytes());
+    }
+
+    /// Adds bytes that need not be UTF-8 to the stream.
+    fn add_raw(&mut self, bytes: &[u8]) {
+        self.write(bytes);
+    }
+
+    /// Forgets the last byte; bytes already written stay written.
+    fn clear(&mut self) {
+        self.last = None;
+    }
+
+    /// Returns the last byte written, if any.
+    fn last(&self) -> Option<u8> {
+        self.last
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_node;
+    use crate::stringify::default::stringify;
+    use std::io;
+
+    /// Accepts `limit` bytes and then fails
+    #[derive(Debug)]
+    struct Full {
+        written: Vec<u8>,
+        limit: usize,
+    }
+
+    impl Write for Full {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.written.len() + buf.len() > self.limit {
+                return Err(io::Error::new(io::ErrorKind::StorageFull, "full"));
+            }
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn writes_through_the_buffer() {
+        let node = make_node(vec![make_node(1), make_node("spam")]);
+        for capacity in [0, 1, 3, 8192] {
+            let mut destination = Writer::with_capacity(capacity, Vec::new());
+            stringify(&node, &mut destination).unwrap();
+            assert_eq!(destination.last(), Some(b'e'));
+            assert_eq!(destination.into_inner().unwrap(), b"li1e4:spame");
+        }
+    }
+
+    #[test]
+    fn clear_forgets_the_last_byte() {
+        let mut destination = Writer::new(Vec::new());
+        destination.add_bytes("ab");
+        destination.clear();
+        assert_eq!(destination.last(), None);
+        destination.add_bytes("");
+        assert_eq!(destination.last(), None);
+        destination.add_byte(b'c');
+        assert_eq!(destination.into_inner().unwrap(), b"abc");
+    }
+
+    #[test]
+    fn the_first_write_error_is_returned() {
+        let mut destination = Writer::with_capacity(0, Full { written: Vec::new(), limit: 3 });
+        destination.add_bytes("li1e");
+        destination.add_byte(b'x');
+        assert_eq!(destination.flush().unwrap_err().kind(), io::ErrorKind::StorageFull);
+        assert!(destination.flush().is_ok());
+
+        let mut destination = Writer::new(Full { written: Vec::new(), limit: 3 });
+        destination.add_bytes("li1e");
+        assert_eq!(destination.into_inner().unwrap_err().kind(), io::ErrorKind::StorageFull);
+    }
+}
@@ -0,0 +1,114 @@
+//! Advisory file locking for the file writing helpers.
+//!
+//! With the `fs2` feature, files are locked while they are written or read so
+//! two processes updating the same state file cannot interleave their writes
+//! or read a half written document. The locks are advisory: they only keep out
+//! other processes that lock the file too. Without the feature these helpers
+//! do nothing. A lock is released when its file handle is closed.
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::path::Path;
+
+use crate::config::WriteOptions;
+use crate::io::backup::back_up;
+
+/// Waits for and takes an exclusive lock on a file
+pub(crate) fn lock_exclusive(file: &File) -> io::Result<()> {
+    #[cfg(feature = "fs2")]
+    return fs2::FileExt::lock_exclusive(file);
+    #[cfg(not(feature = "fs2"))]
+    {
+        let _ = file;
+        Ok(())
+    }
+}
+
+/// Waits for and takes a shared lock on a file, which excludes only exclusive locks
+pub(crate) fn lock_shared(file: &File) -> io::Result<()> {
+    #[cfg(feature = "fs2")]
+    return fs2::FileExt::lock_shared(file);
+    #[cfg(not(feature = "fs2"))]
+    {
+        let _ = file;
+        Ok(())
+    }
+}
+
+/// Opens a file for writing without truncating it, then locks it exclusively.
+///
+/// Backing up and truncating only once the lock is held keeps a writer from
+/// copying or emptying a file another process is still writing or reading.
+pub(crate) fn create_locked(path: &Path, options: &WriteOptions) -> io::Result<File> {
+    let existed = path.is_file();
+    let file = OpenOptions::new().write(true).create(true).truncate(false).open(path)?;
+    lock_exclusive(&file)?;
+    if existed {
+        back_up(path, options.backup)?;
+    }
+    file.set_len(0)?;
+    Ok(file)
+}
+
+/// Locks a whole directory by holding an exclusive lock on a marker file inside it.
+///
+/// # Returns
+/// * `Ok(Some(File))` - The locked marker file; drop it to release the lock
+/// * `Ok(None)` - Without the `fs2` feature, where no marker file is created
+pub(crate) fn lock_directory(dir: &Path, marker: &str) -> io::Result<Option<File>> {
+    #[cfg(feature = "fs2")]
+    {
+        let file = OpenOptions::new().write(true).create(true).truncate(false).open(dir.join(marker))?;
+        lock_exclusive(&file)?;
+        Ok(Some(file))
+    }
+    #[cfg(not(feature = "fs2"))]
+    {
+        let _ = (dir, marker);
+        Ok(None)
+    }
+}
+
+#[cfg(all(test, feature = "fs2"))]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn locked_files_exclude_other_handles() {
+        let path = Path::new("test_lock_exclusive.txt");
+        fs::write(path, "old").unwrap();
+        let file = create_locked(path, &WriteOptions::new()).unwrap();
+        assert_eq!(fs::metadata(path).unwrap().len(), 0);
+        let other = File::open(path).unwrap();
+        assert!(fs2::FileExt::try_lock_shared(&other).is_err());
+        drop(file);
+        assert!(fs2::FileExt::try_lock_exclusive(&other).is_ok());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn shared_locks_exclude_only_writers() {
+        let path = Path::new("test_lock_shared.txt");
+        fs::write(path, "data").unwrap();
+        let reader = File::open(path).unwrap();
+        lock_shared(&reader).unwrap();
+        let other = File::open(path).unwrap();
+        assert!(fs2::FileExt::try_lock_shared(&other).is_ok());
+        let writer = File::open(path).unwrap();
+        assert!(fs2::FileExt::try_lock_exclusive(&writer).is_err());
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn directories_are_locked_through_a_marker_file() {
+        let dir = Path::new("test_lock_directory");
+        fs::create_dir_all(dir).unwrap();
+        let guard = lock_directory(dir, ".lock").unwrap();
+        assert!(guard.is_some());
+        let other = File::open(dir.join(".lock")).unwrap();
+        assert!(fs2::FileExt::try_lock_exclusive(&other).is_err());
+        drop(guard);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -3,4 +3,13 @@ pub mod sources;
 /// Module containing destination implementations for writing bencode data to different outputs
 pub mod destinations;
 /// Module containing trait definitions for bencode I/O operations
-pub mod traits;
\ No newline at end of file
+pub mod traits;
+/// Module providing platform path helpers such as Windows long-path support
+#[cfg(feature = "std")]
+pub mod path;
+/// Module providing advisory file locks for the file helpers (effective with `fs2` feature)
+#[cfg(feature = "std")]
+pub(crate) mod lock;
+/// Module keeping backups of files before the file helpers overwrite them
+#[cfg(feature = "std")]
+pub(crate) mod backup;
@@ -0,0 +1,102 @@
+//! Platform path helpers for writing deep directory trees.
+//!
+//! Multi-file torrents can nest files deeply enough to exceed the classic
+//! 260 character `MAX_PATH` limit on Windows. Paths passed through
+//! [`long_path`] use the extended-length (`\\?\`) form there so they can still
+//! be opened; on other platforms paths are returned unchanged.
+
+use std::path::{Path, PathBuf};
+
+/// Length beyond which Windows needs the extended-length form (MAX_PATH less
+/// room for an 8.3 file name, which is the limit for creating directories)
+#[cfg(windows)]
+const MAX_SHORT_PATH: usize = 248;
+
+/// Returns a path that can be opened even if it is longer than the platform's
+/// legacy path limit.
+///
+/// On Windows a path longer than `MAX_PATH` is made absolute, its separators
+/// are normalised to `\` and it is given the `\\?\` (or `\\?\UNC\`) prefix.
+/// Shorter or already prefixed paths, and every path on other platforms, are
+/// returned as they are. Non-UTF-8 components are preserved.
+///
+/// # Arguments
+/// * `path` - The path to open or create
+///
+/// # Example
+/// ```
+/// use bencode_lib::long_path;
+/// use std::path::Path;
+///
+/// let path = long_path(Path::new("files/demo.torrent"));
+/// assert!(path.ends_with("demo.torrent"));
+/// ```
+pub fn long_path(path: &Path) -> PathBuf {
+    #[cfg(windows)]
+    {
+        windows::extend(path)
+    }
+    #[cfg(not(windows))]
+    {
+        path.to_path_buf()
+    }
+}
+
+#[cfg(windows)]
+mod windows {
+    use super::MAX_SHORT_PATH;
+    use std::ffi::OsString;
+    use std::os::windows::ffi::{OsStrExt, OsStringExt};
+    use std::path::{Path, PathBuf};
+
+    const VERBATIM: &str = r"\\?\";
+    const VERBATIM_UNC: &str = r"\\?\UNC\";
+
+    /// Adds the extended-length prefix to a path that needs it
+    pub(super) fn extend(path: &Path) -> PathBuf {
+        let absolute = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+        let wide: Vec<u16> = absolute
+            .as_os_str()
+            .encode_wide()
+            .map(|c| if c == u16::from(b'/') { u16::from(b'\\') } else { c })
+            .collect();
+        if wide.len() < MAX_SHORT_PATH || wide.starts_with(&VERBATIM.encode_utf16().collect::<Vec<_>>()) {
+            return path.to_path_buf();
+        }
+        let (prefix, rest) = if wide.starts_with(&[u16::from(b'\\'), u16::from(b'\\')]) {
+            (VERBATIM_UNC, &wide[2..])
+        } else {
+            (VERBATIM, &wide[..])
+        };
+        let mut extended: Vec<u16> = prefix.encode_utf16().collect();
+        extended.extend_from_slice(rest);
+        PathBuf::from(OsString::from_wide(&extended))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_paths_are_unchanged() {
+        assert_eq!(long_path(Path::new("a/b.torrent")), PathBuf::from("a/b.torrent"));
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn long_paths_are_unchanged_off_windows() {
+        let deep: PathBuf = (0..40).map(|i| format!("directory{}", i)).collect();
+        assert_eq!(long_path(&deep), deep);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn long_paths_get_the_verbatim_prefix() {
+        let deep: PathBuf = (0..40).map(|i| format!("directory{}", i)).collect();
+        let extended = long_path(&deep);
+        assert!(extended.to_string_lossy().starts_with(r"\\?\"));
+        assert!(extended.ends_with("directory39"));
+        assert_eq!(long_path(&extended), extended);
+    }
+}
@@ -2,6 +2,7 @@
 use alloc::{string::String, vec::Vec};
 
 use crate::io::traits::ISource;
+use core::fmt;
 
 /// A memory buffer implementation for reading bencode data from bytes.
 /// Provides functionality to traverse and read byte content from memory.
@@ -26,12 +27,12 @@ impl Buffer {
             position: 0,
         }
     }
-    /// Converts the buffer content to a String.
-    ///
-    /// # Returns
-    /// A String containing UTF-8 interpretation of the buffer bytes.
-    pub fn to_string(&self) -> String {
-        String::from_utf8_lossy(&self.buffer).into_owned()
+}
+
+/// Displays the buffer content as a (lossy) UTF-8 interpretation of its bytes.
+impl fmt::Display for Buffer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&String::from_utf8_lossy(&self.buffer))
     }
 }
 
@@ -68,19 +69,13 @@ mod tests {
     #[test]
     fn read_character_from_source_buffer_works() {
         let mut source = Buffer::new(String::from("i32e").as_bytes());
-        match source.current() {
-            Some('i') => assert!(true),
-            _ => assert!(false),
-        }
+        assert_eq!(source.current(), Some('i'));
     }
     #[test]
     fn move_to_next_character_in_source_buffer_works() {
         let mut source = Buffer::new(String::from("i32e").as_bytes());
         source.next();
-        match source.current() {
-            Some('3') => assert!(true),
-            _ => assert!(false),
-        }
+        assert_eq!(source.current(), Some('3'));
     }
     #[test]
     fn move_to_last_character_in_source_buffer_works() {
@@ -88,10 +83,7 @@ mod tests {
         while source.more() {
             source.next()
         }
-        match source.current() {
-            None => assert!(true),
-            _ => assert!(false),
-        }
+        assert_eq!(source.current(), None);
     }
     #[test]
     fn reset_in_source_buffer_works() {
@@ -100,10 +92,7 @@ mod tests {
             source.next()
         }
         source.reset();
-        match source.current() {
-            Some('i') => assert!(true),
-            _ => assert!(false),
-        }
+        assert_eq!(source.current(), Some('i'));
     }
     #[test]
     fn create_empty_buffer_works() {
@@ -123,13 +112,18 @@ mod tests {
         assert!(!source.more());
     }
     #[test]
+    fn is_eof_is_true_once_input_is_used_up() {
+        let mut source = Buffer::new(b"a");
+        assert!(!source.is_eof());
+        source.next();
+        assert!(source.is_eof());
+        assert!(Buffer::new(b"").is_eof());
+    }
+    #[test]
     fn multiple_next_calls_work() {
         let mut source = Buffer::new(String::from("abc").as_bytes());
         source.next();
         source.next();
-        match source.current() {
-            Some('c') => assert!(true),
-            _ => assert!(false),
-        }
+        assert_eq!(source.current(), Some('c'));
     }
 }
@@ -1,6 +1,8 @@
+use crate::io::path::long_path;
 use crate::io::traits::ISource;
 use std::fs::File as StdFile;
 use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
 
 /// A file-based implementation for reading bencode data from disk.
 /// Provides functionality to read and traverse file content byte by byte.
@@ -15,12 +17,12 @@ impl File {
     /// Creates a new File instance from the specified path.
     ///
     /// # Arguments
-    /// * `path` - The path to the file to read from
+    /// * `path` - The path to the file to read from (need not be valid UTF-8)
     ///
     /// # Returns
     /// A Result containing either the new File instance or an IO error
-    pub fn new(path: &str) -> std::io::Result<Self> {
-        let mut file = StdFile::open(path)?;
+    pub fn new<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let mut file = StdFile::open(long_path(path.as_ref()))?;
         let mut current_byte = [0u8; 1];
         let has_byte = file.read(&mut current_byte)? == 1;
 
@@ -58,7 +60,7 @@ impl ISource for File {
 
     /// Resets the file position to the start
     fn reset(&mut self) {
-        if let Ok(_) = self.file.seek(SeekFrom::Start(0)) {
+        if self.file.seek(SeekFrom::Start(0)).is_ok() {
             let mut byte = [0u8; 1];
             self.current_byte = if self.file.read(&mut byte).unwrap_or(0) == 1 {
                 Some(byte[0])
@@ -0,0 +1,138 @@
+//! A source over a memory-mapped file.
+//!
+//! [`Mmap`] maps a file into memory rather than reading it, so the operating
+//! system pages it in as it is touched. With [`parse_borrowed`] a torrent or
+//! DHT dump of hundreds of megabytes is parsed without ever being copied into
+//! a `Vec`: strings in the returned tree point into the mapping.
+//!
+//! The mapping reads the file as it is on disk, so the file must not be
+//! truncated or rewritten while it is mapped; another process doing so can
+//! change the bytes under the parsed tree or end the program with `SIGBUS`.
+
+use crate::error::parse::ParseError;
+use crate::io::path::long_path;
+use crate::io::traits::ISource;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::parser::borrowed::parse_borrowed;
+use std::fs::File as StdFile;
+use std::path::Path;
+
+/// A memory-mapped file implementation for reading bencode data from disk.
+/// Reads bytes straight from the mapping, and lends the whole file out as a
+/// slice for the zero-copy parser.
+///
+/// # Example
+/// ```no_run
+/// use bencode_lib::MmapSource;
+///
+/// let source = MmapSource::new("dump.torrent").unwrap();
+/// let node = source.parse_borrowed().unwrap();
+/// println!("{} top level entries", node.as_dictionary().map_or(0, |entries| entries.len()));
+/// ```
+pub struct Mmap {
+    /// The mapped file content
+    map: memmap2::Mmap,
+    /// Current reading position in the mapping
+    position: usize,
+}
+
+impl Mmap {
+    /// Maps the file at the specified path into memory.
+    ///
+    /// # Arguments
+    /// * `path` - The path to the file to map (need not be valid UTF-8)
+    ///
+    /// # Returns
+    /// A Result containing either the new Mmap instance or an IO error
+    pub fn new<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        let file = StdFile::open(long_path(path.as_ref()))?;
+        // SAFETY: the mapping is read only; the module documentation notes that
+        // the file must not be changed by others while it is mapped
+        let map = unsafe { memmap2::Mmap::map(&file)? };
+        Ok(Self { map, position: 0 })
+    }
+
+    /// Returns the whole file content
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Parses the file without copying it; see [`parse_borrowed`].
+    ///
+    /// # Returns
+    /// * `Ok(BorrowedNode)` - The parsed tree, borrowing from the mapping
+    /// * `Err(ParseError)` - Description of the parsing error and its offset
+    pub fn parse_borrowed(&self) -> Result<BorrowedNode<'_>, ParseError> {
+        parse_borrowed(&self.map)
+    }
+}
+
+impl ISource for Mmap {
+    /// Moves to the next byte in the mapping
+    fn next(&mut self) {
+        self.position += 1;
+    }
+
+    /// Returns the current byte as a character
+    fn current(&mut self) -> Option<char> {
+        self.map.get(self.position).map(|&b| b as char)
+    }
+
+    /// Checks if there are more bytes to read
+    fn more(&mut self) -> bool {
+        self.position < self.map.len()
+    }
+
+    /// Resets the position to the start of the mapping
+    fn reset(&mut self) {
+        self.position = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::default::parse;
+    use std::fs;
+
+    fn create_test_file(name: &str, content: &[u8]) -> std::path::PathBuf {
+        let path = std::env::temp_dir().join(format!("bencode_mmap_{}_{}", std::process::id(), name));
+        fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn source_reads_the_mapped_file() {
+        let path = create_test_file("source", b"d1:ai1e1:bl1:xee");
+        let mut source = Mmap::new(&path).unwrap();
+        assert_eq!(source.current(), Some('d'));
+        let node = parse(&mut source).unwrap();
+        assert_eq!(node.get("a").unwrap().as_integer(), Some(&1));
+        assert!(source.is_eof());
+        source.reset();
+        assert_eq!(source.current(), Some('d'));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn borrowed_parse_points_into_the_mapping() {
+        let path = create_test_file("borrowed", b"l4:spami7ee");
+        let source = Mmap::new(&path).unwrap();
+        let node = source.parse_borrowed().unwrap();
+        let spam = node.as_list().unwrap()[0].as_bytes().unwrap();
+        assert_eq!(spam, b"spam");
+        assert!(source.as_bytes().as_ptr_range().contains(&spam.as_ptr()));
+        drop(source);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn empty_and_missing_files() {
+        let path = create_test_file("empty", b"");
+        let mut source = Mmap::new(&path).unwrap();
+        assert!(source.as_bytes().is_empty() && !source.more() && source.current().is_none());
+        assert!(source.parse_borrowed().unwrap_err().is_incomplete());
+        fs::remove_file(&path).unwrap();
+        assert!(Mmap::new(&path).is_err());
+    }
+}
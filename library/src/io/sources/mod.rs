@@ -3,3 +3,11 @@ pub mod buffer;
 /// Module providing a file-based source for reading bencode data from disk
 #[cfg(feature = "std")]
 pub mod file;
+/// Module providing a memory-mapped file source for zero-copy parsing of large files
+#[cfg(feature = "mmap")]
+pub mod mmap;
+/// Module providing a source adapter reading bencode data from any std::io::Read stream
+#[cfg(feature = "std")]
+pub mod reader;
+/// Module providing a source adapter that tracks the read position and limits input size
+pub(crate) mod tracked;
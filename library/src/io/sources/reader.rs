@@ -0,0 +1,185 @@
+use crate::io::traits::ISource;
+use std::io::{ErrorKind, Read};
+
+/// Number of bytes read from the stream at a time unless set otherwise
+pub const DEFAULT_CHUNK_SIZE: usize = 8192;
+
+/// A source reading bencode data from any [`Read`] stream, such as a
+/// `TcpStream`, a decompressor or standard input.
+///
+/// Bytes are read a chunk at a time into an internal buffer. A stream cannot
+/// be rewound, so [`reset`](ISource::reset) returns to the start only while
+/// the first chunk is still buffered. A read error ends the input as the end
+/// of the stream would, and is kept for [`error`](Reader::error).
+///
+/// # Example
+/// ```
+/// use bencode_lib::{ReaderSource, parse};
+///
+/// let stream = &b"d4:name4:demoe"[..];
+/// let node = parse(&mut ReaderSource::with_chunk_size(stream, 4)).unwrap();
+/// assert_eq!(node.get("name").unwrap().as_string(), Some("demo"));
+/// ```
+pub struct Reader<R: Read> {
+    /// The stream being read
+    reader: R,
+    /// Bytes read from the stream and not yet discarded
+    chunk: Vec<u8>,
+    /// Number of bytes of `chunk` holding data
+    filled: usize,
+    /// Current reading position in `chunk`
+    position: usize,
+    /// True once a chunk has been replaced, so the start of the stream is gone
+    discarded: bool,
+    /// True once the stream has ended or failed
+    finished: bool,
+    /// The error that ended the stream, if any
+    error: Option<std::io::Error>,
+}
+
+impl<R: Read> Reader<R> {
+    /// Creates a new Reader reading [`DEFAULT_CHUNK_SIZE`] bytes at a time.
+    ///
+    /// # Arguments
+    /// * `reader` - The stream to read from
+    ///
+    /// # Returns
+    /// A new Reader positioned at the start of the stream
+    pub fn new(reader: R) -> Self {
+        Self::with_chunk_size(reader, DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates a new Reader reading up to `chunk_size` bytes at a time.
+    ///
+    /// # Arguments
+    /// * `reader` - The stream to read from
+    /// * `chunk_size` - The size of the internal buffer (0 is treated as 1)
+    ///
+    /// # Returns
+    /// A new Reader positioned at the start of the stream
+    pub fn with_chunk_size(reader: R, chunk_size: usize) -> Self {
+        Self {
+            reader,
+            chunk: vec![0; chunk_size.max(1)],
+            filled: 0,
+            position: 0,
+            discarded: false,
+            finished: false,
+            error: None,
+        }
+    }
+
+    /// Returns the error that ended the stream early, if any
+    pub fn error(&self) -> Option<&std::io::Error> {
+        self.error.as_ref()
+    }
+
+    /// Returns the stream, dropping any bytes read but not yet consumed
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the next chunk once the current one is used up
+    fn fill(&mut self) {
+        while self.position >= self.filled && !self.finished {
+            match self.reader.read(&mut self.chunk) {
+                Ok(0) => self.finished = true,
+                Ok(read) => {
+                    self.discarded |= self.filled > 0;
+                    self.filled = read;
+                    self.position = 0;
+                }
+                Err(error) if error.kind() == ErrorKind::Interrupted => {}
+                Err(error) => {
+                    self.error = Some(error);
+                    self.finished = true;
+                }
+            }
+        }
+    }
+}
+
+impl<R: Read> ISource for Reader<R> {
+    /// Moves to the next byte in the stream
+    fn next(&mut self) {
+        if self.more() {
+            self.position += 1;
+        }
+    }
+
+    /// Returns the current byte as a character
+    fn current(&mut self) -> Option<char> {
+        if self.more() {
+            Some(self.chunk[self.position] as char)
+        } else {
+            None
+        }
+    }
+
+    /// Checks if there are more bytes to read, reading the next chunk if needed
+    fn more(&mut self) -> bool {
+        self.fill();
+        self.position < self.filled
+    }
+
+    /// Returns to the start of the stream if it is still buffered
+    fn reset(&mut self) {
+        if !self.discarded {
+            self.position = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::default::parse;
+    use std::io;
+
+    /// Yields its data and then fails
+    struct Failing<'a>(&'a [u8]);
+
+    impl Read for Failing<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.0.is_empty() {
+                return Err(io::Error::new(ErrorKind::ConnectionReset, "reset"));
+            }
+            self.0.read(buf)
+        }
+    }
+
+    #[test]
+    fn parses_across_chunk_boundaries() {
+        let input = b"d4:listli1ei-22ee3:str5:helloe";
+        for chunk_size in [0, 1, 2, 3, 7, DEFAULT_CHUNK_SIZE] {
+            let mut source = Reader::with_chunk_size(&input[..], chunk_size);
+            let node = parse(&mut source).unwrap();
+            assert_eq!(node.get("str").unwrap().as_string(), Some("hello"), "{}", chunk_size);
+            assert!(source.is_eof() && source.error().is_none());
+        }
+    }
+
+    #[test]
+    fn reset_returns_to_the_start_while_it_is_buffered() {
+        let mut source = Reader::with_chunk_size(&b"i32e"[..], 8);
+        source.next();
+        source.reset();
+        assert_eq!(source.current(), Some('i'));
+
+        let mut source = Reader::with_chunk_size(&b"i32e"[..], 2);
+        source.next();
+        source.next();
+        assert_eq!(source.current(), Some('2'));
+        source.reset();
+        assert_eq!(source.current(), Some('2'));
+    }
+
+    #[test]
+    fn read_errors_end_the_input() {
+        let mut source = Reader::with_chunk_size(Failing(b"li1e"), 2);
+        let error = parse(&mut source).unwrap_err();
+        assert!(error.is_incomplete());
+        assert_eq!(source.error().unwrap().kind(), ErrorKind::ConnectionReset);
+        assert!(Reader::new(&b""[..]).is_eof());
+    }
+}
@@ -0,0 +1,86 @@
+use crate::io::traits::ISource;
+
+/// Source adapter counting the characters consumed, so parsers reading any
+/// [`ISource`] can report the offset of an error.
+///
+/// It can also end the input after a maximum number of characters, recording
+/// whether there was more input past the limit.
+pub(crate) struct Tracked<'a> {
+    /// The wrapped source
+    source: &'a mut dyn ISource,
+    /// Characters consumed so far
+    position: usize,
+    /// Characters that may be consumed in total
+    limit: usize,
+    /// Input continued past the limit
+    exceeded: bool,
+}
+
+impl<'a> Tracked<'a> {
+    /// Wraps `source`, ending its input after `limit` characters.
+    pub(crate) fn new(source: &'a mut dyn ISource, limit: usize) -> Self {
+        Self {
+            source,
+            position: 0,
+            limit,
+            exceeded: false,
+        }
+    }
+
+    /// Returns the number of characters consumed
+    pub(crate) fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns true if the input was cut short by the limit
+    pub(crate) fn exceeded(&self) -> bool {
+        self.exceeded
+    }
+}
+
+impl ISource for Tracked<'_> {
+    fn next(&mut self) {
+        if self.position < self.limit && self.source.more() {
+            self.position += 1;
+            self.source.next();
+        }
+    }
+
+    fn current(&mut self) -> Option<char> {
+        if self.position == self.limit {
+            self.exceeded |= self.source.current().is_some();
+            return None;
+        }
+        self.source.current()
+    }
+
+    fn more(&mut self) -> bool {
+        self.position < self.limit && self.source.more()
+    }
+
+    fn reset(&mut self) {
+        self.source.reset();
+        self.position = 0;
+        self.exceeded = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BufferSource;
+
+    #[test]
+    fn tracks_position_and_limit() {
+        let mut buffer = BufferSource::new(b"abc");
+        let mut source = Tracked::new(&mut buffer, 2);
+        source.next();
+        assert_eq!((source.current(), source.position()), (Some('b'), 1));
+        source.next();
+        source.next();
+        assert_eq!((source.current(), source.position()), (None, 2));
+        assert!(source.exceeded());
+        source.reset();
+        assert_eq!((source.current(), source.position(), source.exceeded()), (Some('a'), 0, false));
+    }
+}
@@ -9,6 +9,11 @@ pub trait ISource {
     fn more(&mut self) -> bool;
     /// Resets the reading position to the beginning of the source.
     fn reset(&mut self);
+    /// Returns true once the input is used up. A parser stopping here has met
+    /// the end of the input in the middle of a value rather than an invalid byte.
+    fn is_eof(&mut self) -> bool {
+        !self.more()
+    }
 }
 
 /// Trait defining the interface for writing bencode data to a destination.
@@ -18,6 +23,13 @@ pub trait IDestination {
     fn add_byte(&mut self, byte: u8);
     /// Adds multiple bytes from a string slice to the destination.
     fn add_bytes(&mut self, bytes: &str);
+    /// Adds bytes that need not be UTF-8, such as a torrent's piece hashes.
+    /// The default writes them one at a time with `add_byte`.
+    fn add_raw(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.add_byte(byte);
+        }
+    }
     /// Clears all content from the destination.
     fn clear(&mut self);
     /// Returns the last byte in the destination, if any.
@@ -12,39 +12,88 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 
+// Lets code generated by the derive macros name this crate from inside it
+extern crate self as bencode_lib;
+
+#[cfg(not(feature = "std"))]
+use alloc::string::String;
+
 #[cfg(not(feature = "std"))]
 pub(crate) use alloc::collections::BTreeMap as HashMap;
 
 #[cfg(feature = "std")]
 pub(crate) use std::collections::HashMap;
 
+/// Map holding the entries of a dictionary node: a `HashMap`, or a `BTreeMap`
+/// under `no_std`
+///
+/// Keys are strings, so the owning parsers decode keys that are not valid UTF-8
+/// lossily (invalid sequences become `U+FFFD`) and such dictionaries do not
+/// round trip; `parse_borrowed` keeps key bytes exactly.
+pub type Dictionary<V = Node> = HashMap<String, V>;
+
+/// Module providing batch processing of bencode files with per-file reports (requires `std` feature)
+#[cfg(feature = "std")]
+pub mod batch;
+/// Module gathering aggregate statistics across a directory of torrents (requires `std` feature)
+#[cfg(feature = "std")]
+pub mod collection;
+/// Module converting user types to and from nodes (derivable with `derive` feature)
+pub mod convert;
 /// Module defining custom error types and error handling functionality
 pub mod error;
 /// Module providing input/output operations for reading and writing bencode data
 pub mod io;
 /// Module containing utility functions and helper methods
 pub mod misc;
+/// Module defining the metrics emitted by the main entry points (recorded with `metrics` feature)
+pub mod metrics;
+/// Module measuring single parses for comparing parsers (requires `instrumentation` feature)
+#[cfg(feature = "instrumentation")]
+pub mod instrumentation;
 
 /// Module containing configuration options for parsing and encoding
 pub mod config;
+/// Module providing specification test vectors to check any parser against
+pub mod conformance;
 /// Module providing memory management utilities for embedded systems
 pub mod memory;
 /// Module defining the core data structures for representing bencode nodes
 pub mod nodes;
 /// Module containing the parsing logic to decode bencode format into data structures
 pub mod parser;
+/// Module repairing common defects in bencode produced by non-conforming encoders
+pub mod repair;
+/// Module implementing serde Serializer and Deserializer for bencode (requires `serde` feature)
+#[cfg(feature = "serde")]
+pub mod serde;
+/// Module providing content addressed torrent storage (requires `std` feature)
+#[cfg(feature = "std")]
+pub mod storage;
 /// Module implementing serialization of data structures back to bencode format
 pub mod stringify;
+/// Module providing BitTorrent metainfo helpers such as info hashing
+pub mod torrent;
+/// Module providing deterministic synthetic inputs for tests and benchmarks (requires `testdata` feature)
+#[cfg(any(test, feature = "testdata"))]
+pub mod testdata;
 
 /// Integration tests module
 mod integration_tests;
+/// Helpers shared by the unit tests
+#[cfg(all(test, feature = "std"))]
+mod test_support;
 
-///
-/// Bencode_lib API
-///
+//
+// Bencode_lib API
+//
 
 /// Returns the current version of the bencode library
 pub use misc::get_version as version;
+/// Formats a byte count with binary units, such as `1.5 KiB`
+pub use misc::human_size;
+/// Formats seconds since the Unix epoch as a UTC date and time
+pub use misc::human_date;
 
 /// Reads and parses a bencode-encoded file from disc (requires `std` feature)
 #[cfg(feature = "std")]
@@ -53,14 +102,28 @@ pub use misc::read_bencode_file as read_file;
 /// Writes bencode-encoded data to a file on disk (requires `std` feature)
 #[cfg(feature = "std")]
 pub use misc::write_bencode_file as write_file;
+/// Writes bencode-encoded data to a file, keeping a backup of the file it replaces (requires `std` feature)
+#[cfg(feature = "std")]
+pub use misc::write_bencode_file_with_options as write_file_with_options;
+
+/// Structured per-file result of batch parsing or conversion (requires `std` feature)
+#[cfg(feature = "std")]
+pub use batch::FileReport;
 
 /// Destination implementation for writing bencode data to a memory buffer
 pub use io::destinations::buffer::Buffer as BufferDestination;
 
+/// Destination implementation counting the bytes written without keeping them
+pub use io::destinations::counting::Counting as SizeCountingDestination;
+
 /// Destination implementation for writing bencode data to a file (requires `std` feature)
 #[cfg(feature = "std")]
 pub use io::destinations::file::File as FileDestination;
 
+/// Destination implementation for writing bencode data to any `std::io::Write` stream (requires `std` feature)
+#[cfg(feature = "std")]
+pub use io::destinations::writer::Writer as WriterDestination;
+
 /// Source implementation for reading bencode data from a memory buffer
 pub use io::sources::buffer::Buffer as BufferSource;
 
@@ -68,17 +131,82 @@ pub use io::sources::buffer::Buffer as BufferSource;
 #[cfg(feature = "std")]
 pub use io::sources::file::File as FileSource;
 
+/// Source implementation over a memory-mapped file, for parsing large files without copying (requires `mmap` feature)
+#[cfg(feature = "mmap")]
+pub use io::sources::mmap::Mmap as MmapSource;
+
+/// Source implementation for reading bencode data from any `std::io::Read` stream (requires `std` feature)
+#[cfg(feature = "std")]
+pub use io::sources::reader::Reader as ReaderSource;
+
+/// Extended-length path conversion for deep trees on Windows (requires `std` feature)
+#[cfg(feature = "std")]
+pub use io::path::long_path;
+
 /// Core data structure representing a bencode node in the parsed tree
 pub use nodes::node::Node;
 pub use nodes::node::make_node;
+/// Which value Node::merge keeps when both trees hold one
+pub use nodes::node::MergeStrategy;
+/// Integer type held by nodes (`i64`, or `i32` with the `int32` feature)
+pub use nodes::integer::Integer;
 
 /// Zero-copy borrowed node for embedded systems (no allocation)
 pub use nodes::borrowed::BorrowedNode;
 
+/// Node tree stored in one contiguous buffer
+pub use nodes::flat::FlatNode;
+/// Borrowed view of a node inside a FlatNode buffer
+pub use nodes::flat::FlatRef;
+
+/// ASCII, UTF-8 or binary classification of a byte string
+pub use nodes::string_kind::StringKind;
+
+/// Immutable, cheaply cloneable node tree for concurrent readers
+pub use nodes::shared::ArcNode;
+/// Parsed tree kept with its input, returned as is when encoded unchanged
+pub use nodes::document::Document;
+
 /// Type alias for fixed-size stack buffers with const generics
 pub use nodes::fixed::FixedSizeBuffer;
 /// Memory bounds calculator using const generics
 pub use nodes::fixed::MemoryBounds;
+/// Node tree with compile-time bounds on size and nesting
+pub use nodes::bounded::BoundedNode;
+/// Stable SHA-256 content hash of a node tree
+pub use nodes::fingerprint::{fingerprint, fingerprint_with};
+/// Node tree with per-path annotations
+pub use nodes::annotated::AnnotatedNode;
+/// Note attached to a value of an AnnotatedNode
+pub use nodes::annotated::Annotation;
+/// Trim low priority values until a tree fits an encoded size budget
+pub use nodes::trim::trim_to_size;
+/// Rules applied by trim_to_size
+pub use nodes::trim::TrimPolicy;
+/// One way of making a tree smaller
+pub use nodes::trim::TrimRule;
+/// Infer the schema shared by sample documents
+pub use nodes::schema::infer_schema;
+/// Types, key presence and value ranges seen at each key path
+pub use nodes::schema::Schema;
+/// Receives the values of a tree from Node::walk
+pub use nodes::walk::NodeVisitor;
+/// Receives and may change the values of a tree from Node::walk_mut
+pub use nodes::walk::NodeVisitorMut;
+/// List the differences between two documents
+pub use nodes::diff::diff;
+/// Format differences one per line
+pub use nodes::diff::format_diff;
+/// One difference between two documents
+pub use nodes::diff::DiffEntry;
+/// Apply a patch to a document
+pub use nodes::patch::apply_patch;
+/// Compute the patch turning one document into another
+pub use nodes::patch::diff_to_patch;
+/// Ordered list of changes to a document
+pub use nodes::patch::Patch;
+/// One add, remove or replace operation of a Patch
+pub use nodes::patch::PatchOp;
 
 /// Parses bencode data into a Node tree structure
 pub use parser::default::parse;
@@ -86,11 +214,63 @@ pub use parser::default::parse;
 pub use parser::default::parse_bytes;
 /// Parses bencode data from a string into a Node tree structure
 pub use parser::default::parse_str;
+/// Parses from a source, enforcing the limits of a ParserConfig
+pub use parser::default::parse_with_config;
+/// Parses a byte slice, enforcing the limits of a ParserConfig
+pub use parser::default::parse_bytes_with_config;
+/// Parses a string, enforcing the limits of a ParserConfig
+pub use parser::default::parse_str_with_config;
+/// Parses from a source with a ParserConfig, returning the deviations its lenient toggles let through
+pub use parser::default::parse_with_warnings;
+/// Parses a byte slice with a ParserConfig, returning the deviations its lenient toggles let through
+pub use parser::default::parse_bytes_with_warnings;
 
 /// Zero-copy parser that returns borrowed nodes (no allocation)
 pub use parser::borrowed::parse_borrowed;
 /// Validates bencode data without building a node tree (minimal allocation)
 pub use parser::borrowed::validate_bencode;
+/// Validates bencode data within the limits of a ParserConfig, reporting the failing offset
+pub use parser::borrowed::validate_bencode_with_limits;
+
+/// Reads one integer from the start of a byte slice
+pub use parser::borrowed::decode_int;
+/// Reads one byte string from the start of a byte slice without copying
+pub use parser::borrowed::decode_bytes;
+
+/// Error recovering parser returning a partial tree and all errors found
+pub use parser::lossy::parse_lossy;
+
+/// Parses bencode data, reporting each value to a Visitor
+pub use parser::visitor::visit;
+/// Receives parse events from visit()
+pub use parser::visitor::Visitor;
+
+/// Extracts selected key paths from bencode data without a full parse
+pub use parser::extract::extract_streaming;
+/// Parser that also records the byte range of every value
+pub use parser::spans::parse_with_spans;
+/// Byte ranges of the values in a document, keyed by path
+pub use parser::spans::SpanMap;
+/// View of an encoded value whose keys are looked up by scanning, without parsing
+pub use parser::lazy::LazyValue;
+/// Type of an encoded value, from its first byte
+pub use parser::lazy::ValueType;
+
+/// Checks dictionary key ordering without full validation
+pub use parser::key_order::check_key_order;
+/// Dictionary key found out of order or duplicated
+pub use parser::key_order::KeyOrderViolation;
+/// Check that input is the canonical encoding of its value
+pub use parser::canonical::validate_canonical;
+/// Search for text inside string values only
+pub use parser::search::find_strings;
+/// String value containing a searched pattern
+pub use parser::search::StringMatch;
+
+/// Incremental decoder fed with chunks of input, e.g. from a socket
+pub use parser::streaming::StreamingDecoder;
+/// Outcome of feeding a chunk to a StreamingDecoder
+pub use parser::streaming::Decoded;
 
 /// Parses bencode data from a byte slice using iterative parser
 pub use parser::iterative::parse_bytes_iterative;
@@ -98,21 +278,74 @@ pub use parser::iterative::parse_bytes_iterative;
 pub use parser::iterative::parse_iterative;
 /// Parses bencode data from a string using iterative parser
 pub use parser::iterative::parse_str_iterative;
+/// Iterative parser limited by a step budget and/or deadline
+pub use parser::iterative::parse_iterative_with_budget;
+/// Parses a byte slice with the iterative parser, limited by a budget
+pub use parser::iterative::parse_bytes_iterative_with_budget;
+
+/// Chooses a parser from the input size, available stack and memory limit
+pub use parser::strategy::choose_parser;
+/// Parses with the parser choose_parser picks
+pub use parser::strategy::parse_with_strategy;
+/// Parser selected by choose_parser
+pub use parser::strategy::ParserKind;
+/// Owned or borrowed tree returned by parse_with_strategy
+pub use parser::strategy::Parsed;
 
 /// Arena allocator for bump allocation from fixed buffers
 pub use memory::Arena;
+/// Node of a tree parsed into an arena
+pub use memory::arena::ArenaNode;
+/// Dictionary keys shared between trees parsed into one arena
+pub use memory::arena::Interner;
+/// Parser copying its tree into an arena
+pub use memory::arena::parse_in;
+/// Parser copying its tree into an arena, storing each distinct key once
+pub use memory::arena::parse_in_interned;
 /// Memory usage tracker for embedded systems
 pub use memory::MemoryTracker;
 /// Stack-based fixed-size buffer
 pub use memory::StackBuffer;
+/// Pool recycling node allocations between parses
+pub use memory::pool::NodePool;
+/// Reusable storage for zero-copy parses
+pub use memory::scratch::Scratch;
+/// View of a node parsed into scratch space
+pub use memory::scratch::ScratchRef;
+/// Zero-copy parser storing its tree in scratch space
+pub use memory::scratch::parse_borrowed_in;
+
+/// Conversion of a user type to a node tree
+pub use convert::ToBencode;
+/// Conversion of a node tree to a user type
+pub use convert::FromBencode;
+/// Derive macros for ToBencode and FromBencode (requires `derive` feature)
+#[cfg(feature = "derive")]
+pub use bencode_derive::{FromBencode, ToBencode};
 
 /// Lightweight error type for embedded systems (no heap allocation)
 pub use error::embedded::BencodeError;
+/// Parse error carrying the byte offset of the problem
+pub use error::parse::ParseError;
+/// Stable error classification to match on instead of message text
+pub use error::kind::ErrorKind;
+/// Error from a budgeted parse: timeout or malformed input
+pub use error::budget::BudgetError;
+/// Deviation from bencode let through by a lenient parser toggle, and where it was found
+pub use error::warning::{Deviation, ParseWarning};
 
 /// Encoder configuration options
 pub use config::EncoderConfig;
+/// How binary strings are written in the text formats
+pub use config::BinaryEncoding;
 /// Parser configuration options
 pub use config::ParserConfig;
+/// Step limit and deadline for parsing untrusted input
+pub use config::ParseBudget;
+/// Options for the file writing helpers, such as backups of overwritten files
+pub use config::WriteOptions;
+/// What is kept of a file before it is overwritten
+pub use config::BackupPolicy;
 
 /// Converts a Node tree back to bencode format
 pub use stringify::default::stringify;
@@ -120,19 +353,83 @@ pub use stringify::default::stringify;
 pub use stringify::default::stringify_to_bytes;
 /// Converts a Node tree to bencode format as a String
 pub use stringify::default::stringify_to_string;
+/// Converts a Node tree to bencode format within a byte limit
+pub use stringify::default::stringify_bounded;
+/// Number of bytes a Node tree encodes to
+pub use stringify::default::estimated_encoded_size;
+/// Converts a Node tree to bencode format following an EncoderConfig
+pub use stringify::default::stringify_with_config;
+/// Writes an integer without building a Node
+pub use stringify::default::encode_int;
+/// Writes a byte string without building a Node
+pub use stringify::default::encode_bytes;
+/// Writes the length prefix of a byte string whose bytes the caller streams
+pub use stringify::default::encode_bytes_header;
+/// Writes the marker opening a list
+pub use stringify::default::encode_list_header;
+/// Writes the marker opening a dictionary
+pub use stringify::default::encode_dict_header;
+/// Writes the marker closing a list or dictionary
+pub use stringify::default::encode_end;
+/// Encodes a Node tree in canonical form, re-encoding pre-encoded values
+pub use stringify::canonical::canonicalize;
+/// Writes an indented listing of encoded data with offsets and lengths
+pub use stringify::tree::print_tree;
+/// Layout options for print_tree
+pub use stringify::tree::TreeOptions;
 
 /// Converts a Node tree to JSON format (requires "json" feature)
 #[cfg(feature = "json")]
 pub use stringify::json::stringify as to_json;
+/// Converts a Node tree to JSON, reporting anything lossy (requires "json" feature)
+#[cfg(feature = "json")]
+pub use stringify::json::stringify_with_report as to_json_with_report;
+/// Converts a Node tree to JSON following an EncoderConfig (requires "json" feature)
+#[cfg(feature = "json")]
+pub use stringify::json::stringify_with_config as to_json_with_config;
+/// Converts a borrowed node tree to JSON without copying its strings (requires "json" feature)
+#[cfg(feature = "json")]
+pub use stringify::json::stringify_borrowed as to_json_borrowed;
 
 /// Converts a Node tree to TOML format (requires "toml" feature)
 #[cfg(feature = "toml")]
 pub use stringify::toml::stringify as to_toml;
+/// Converts a Node tree to TOML, reporting anything lossy (requires "toml" feature)
+#[cfg(feature = "toml")]
+pub use stringify::toml::stringify_with_report as to_toml_with_report;
+/// Converts a Node tree to TOML following an EncoderConfig (requires "toml" feature)
+#[cfg(feature = "toml")]
+pub use stringify::toml::stringify_with_config as to_toml_with_config;
 
 /// Converts a Node tree to XML format (requires "xml" feature)
 #[cfg(feature = "xml")]
 pub use stringify::xml::stringify as to_xml;
+/// Converts a Node tree to XML, reporting anything lossy (requires "xml" feature)
+#[cfg(feature = "xml")]
+pub use stringify::xml::stringify_with_report as to_xml_with_report;
+/// Converts a Node tree to XML following an EncoderConfig (requires "xml" feature)
+#[cfg(feature = "xml")]
+pub use stringify::xml::stringify_with_config as to_xml_with_config;
+/// Parses XML in the to_xml layout into a Node tree (requires "xml" feature)
+#[cfg(feature = "xml")]
+pub use parser::xml::parse as from_xml;
 
 /// Converts a Node tree to YAML format (requires "yaml" feature)
 #[cfg(feature = "yaml")]
 pub use stringify::yaml::stringify as to_yaml;
+/// Converts a Node tree to YAML, reporting anything lossy (requires "yaml" feature)
+#[cfg(feature = "yaml")]
+pub use stringify::yaml::stringify_with_report as to_yaml_with_report;
+/// Converts a Node tree to YAML following an EncoderConfig (requires "yaml" feature)
+#[cfg(feature = "yaml")]
+pub use stringify::yaml::stringify_with_config as to_yaml_with_config;
+/// Parses YAML, such as to_yaml output, into a Node tree (requires "yaml" feature)
+#[cfg(feature = "yaml")]
+pub use parser::yaml::parse as from_yaml;
+
+/// What a conversion to JSON, YAML, XML or TOML could not preserve
+#[cfg(any(feature = "json", feature = "yaml", feature = "xml", feature = "toml"))]
+pub use stringify::report::ConversionReport;
+/// One lossy aspect of a conversion
+#[cfg(any(feature = "json", feature = "yaml", feature = "xml", feature = "toml"))]
+pub use stringify::report::Loss;
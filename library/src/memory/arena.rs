@@ -0,0 +1,427 @@
+//! Parsing into an [`Arena`].
+//!
+//! Indexing thousands of torrents with [`parse_bytes`](crate::parse_bytes)
+//! makes a heap allocation for every string, list and dictionary of every
+//! file. [`parse_in`] instead copies the tree into an arena with bump
+//! allocation, so a whole batch is freed at once, and the tree no longer
+//! borrows the input buffer, which can be reused for the next file.
+//!
+//! Torrents repeat the same dictionary keys (`length`, `path`, `piece length`)
+//! many times over. [`parse_in_interned`] stores each distinct key once in the
+//! arena, however many dictionaries or files it appears in.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::HashMap;
+use crate::error::messages::*;
+use crate::error::parse::ParseError;
+use crate::memory::Arena;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::nodes::integer::Integer;
+use crate::nodes::node::Node;
+use crate::parser::borrowed::{parse_bytes, parse_integer};
+
+/// A node of a tree held in an [`Arena`].
+///
+/// Strings, lists and dictionaries all live in the arena, so nodes are plain
+/// references and copying one is cheap. Dictionary entries are in key order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ArenaNode<'a> {
+    /// A signed integer
+    Integer(Integer),
+    /// A byte string
+    Bytes(&'a [u8]),
+    /// A list of nodes
+    List(&'a [ArenaNode<'a>]),
+    /// A dictionary as (raw key, value) entries in key order
+    Dictionary(&'a [(&'a [u8], ArenaNode<'a>)]),
+}
+
+impl<'a> ArenaNode<'a> {
+    /// Returns the type name of the node ("integer", "string", "list" or "dictionary")
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            ArenaNode::Integer(_) => "integer",
+            ArenaNode::Bytes(_) => "string",
+            ArenaNode::List(_) => "list",
+            ArenaNode::Dictionary(_) => "dictionary",
+        }
+    }
+
+    /// Returns the integer value if this is an integer node
+    pub fn as_integer(&self) -> Option<Integer> {
+        match self {
+            ArenaNode::Integer(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes of a string node
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self {
+            ArenaNode::Bytes(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    /// Returns the string value if this is a string node holding valid UTF-8
+    pub fn as_string(&self) -> Option<&'a str> {
+        self.as_bytes().and_then(|bytes| core::str::from_utf8(bytes).ok())
+    }
+
+    /// Returns the elements if this is a list node
+    pub fn as_list(&self) -> Option<&'a [ArenaNode<'a>]> {
+        match self {
+            ArenaNode::List(items) => Some(items),
+            _ => None,
+        }
+    }
+
+    /// Returns the entries if this is a dictionary node
+    pub fn as_dictionary(&self) -> Option<&'a [(&'a [u8], ArenaNode<'a>)]> {
+        match self {
+            ArenaNode::Dictionary(entries) => Some(entries),
+            _ => None,
+        }
+    }
+
+    /// Returns the dictionary value for `key`
+    pub fn get(&self, key: &str) -> Option<ArenaNode<'a>> {
+        let entries = self.as_dictionary()?;
+        let index = entries.binary_search_by(|(k, _)| (*k).cmp(key.as_bytes())).ok()?;
+        Some(entries[index].1)
+    }
+
+    /// Returns a [`BorrowedNode`] tree borrowing its strings from the arena
+    pub fn to_borrowed(&self) -> BorrowedNode<'a> {
+        match *self {
+            ArenaNode::Integer(value) => BorrowedNode::Integer(value),
+            ArenaNode::Bytes(bytes) => BorrowedNode::Bytes(bytes),
+            ArenaNode::List(items) => BorrowedNode::List(items.iter().map(ArenaNode::to_borrowed).collect()),
+            ArenaNode::Dictionary(entries) => {
+                BorrowedNode::Dictionary(entries.iter().map(|(key, value)| (*key, value.to_borrowed())).collect())
+            }
+        }
+    }
+
+    /// Rebuilds the owned node tree rooted at this node.
+    pub fn to_node(&self) -> Node {
+        match self {
+            ArenaNode::Integer(value) => Node::Integer(*value),
+            ArenaNode::Bytes(bytes) => Node::from_bytes(bytes.to_vec()),
+            ArenaNode::List(items) => Node::List(items.iter().map(ArenaNode::to_node).collect()),
+            ArenaNode::Dictionary(entries) => Node::Dictionary(
+                entries
+                    .iter()
+                    .map(|(k, v)| (String::from_utf8_lossy(k).into_owned(), v.to_node()))
+                    .collect::<HashMap<_, _>>(),
+            ),
+        }
+    }
+}
+
+/// Distinct strings already copied into an [`Arena`], for [`parse_in_interned`].
+///
+/// An interner is tied to the arena its strings live in; keep one for as long
+/// as the arena to share keys across every tree parsed into it.
+#[derive(Debug, Default)]
+pub struct Interner<'a> {
+    /// Strings stored in the arena
+    strings: HashMap<&'a [u8], ()>,
+    /// Number of strings found already stored
+    hits: usize,
+}
+
+impl<'a> Interner<'a> {
+    /// Creates an empty interner
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of distinct strings stored
+    pub fn len(&self) -> usize {
+        self.strings.len()
+    }
+
+    /// Returns true if no strings are stored
+    pub fn is_empty(&self) -> bool {
+        self.strings.is_empty()
+    }
+
+    /// Returns the number of times a string was found already stored rather
+    /// than copied into the arena again
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Returns the stored copy of `bytes`, copying them into `arena` the first time.
+    ///
+    /// # Returns
+    /// The copy in the arena, or None if the arena is full
+    pub fn intern(&mut self, arena: &'a Arena, bytes: &[u8]) -> Option<&'a [u8]> {
+        if let Some((stored, _)) = self.strings.get_key_value(bytes) {
+            self.hits += 1;
+            return Some(stored);
+        }
+        let stored: &'a [u8] = arena.alloc_copy(bytes)?;
+        self.strings.insert(stored, ());
+        Some(stored)
+    }
+}
+
+/// Parses bencode data into `arena`.
+///
+/// Accepts the same input and reports the same errors as
+/// [`parse_borrowed`](crate::parse_borrowed), plus an error if the arena runs
+/// out of space. Space taken by a failed parse is not returned.
+///
+/// # Arguments
+/// * `arena` - Storage for the tree's strings, lists and dictionaries
+/// * `input` - The bencode data, which the tree does not borrow
+///
+/// # Returns
+/// * `Ok(ArenaNode)` - The root node, borrowing `arena`
+/// * `Err(ParseError)` - Description of the parse error and its offset
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_in, Arena};
+///
+/// let arena = Arena::with_capacity(4096);
+/// let mut input = b"d6:lengthi10e4:name1:ae".to_vec();
+/// let first = parse_in(&arena, &input).unwrap();
+/// input.clear();
+/// input.extend_from_slice(b"d6:lengthi20e4:name1:be");
+/// let second = parse_in(&arena, &input).unwrap();
+/// assert_eq!(first.get("name").and_then(|n| n.as_string()), Some("a"));
+/// assert_eq!(second.get("length").and_then(|n| n.as_integer()), Some(20));
+/// ```
+pub fn parse_in<'a>(arena: &'a Arena, input: &[u8]) -> Result<ArenaNode<'a>, ParseError> {
+    ArenaParser::new(arena, None, input).parse()
+}
+
+/// Parses bencode data into `arena`, storing each distinct dictionary key once.
+///
+/// Behaves as [`parse_in`], except that a key already held by `interner` is
+/// shared rather than copied again, across this and earlier parses.
+///
+/// # Arguments
+/// * `arena` - Storage for the tree's strings, lists and dictionaries
+/// * `interner` - The keys already stored in `arena`
+/// * `input` - The bencode data, which the tree does not borrow
+///
+/// # Returns
+/// * `Ok(ArenaNode)` - The root node, borrowing `arena`
+/// * `Err(ParseError)` - Description of the parse error and its offset
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_in_interned, Arena, Interner};
+///
+/// let arena = Arena::with_capacity(4096);
+/// let mut interner = Interner::new();
+/// for input in [&b"ld6:lengthi1eed6:lengthi2eee"[..], b"d6:lengthi3ee"] {
+///     parse_in_interned(&arena, &mut interner, input).unwrap();
+/// }
+/// assert_eq!((interner.len(), interner.hits()), (1, 2));
+/// ```
+pub fn parse_in_interned<'a>(
+    arena: &'a Arena,
+    interner: &mut Interner<'a>,
+    input: &[u8],
+) -> Result<ArenaNode<'a>, ParseError> {
+    ArenaParser::new(arena, Some(interner), input).parse()
+}
+
+/// State of one parse into an arena
+struct ArenaParser<'a, 'p> {
+    arena: &'a Arena,
+    interner: Option<&'p mut Interner<'a>>,
+    input: &'p [u8],
+    position: usize,
+    /// Elements of the lists being parsed, innermost last
+    items: Vec<ArenaNode<'a>>,
+    /// Entries of the dictionaries being parsed, innermost last
+    entries: Vec<(&'a [u8], ArenaNode<'a>)>,
+}
+
+impl<'a, 'p> ArenaParser<'a, 'p> {
+    fn new(arena: &'a Arena, interner: Option<&'p mut Interner<'a>>, input: &'p [u8]) -> Self {
+        Self {
+            arena,
+            interner,
+            input,
+            position: 0,
+            items: Vec::new(),
+            entries: Vec::new(),
+        }
+    }
+
+    fn parse(mut self) -> Result<ArenaNode<'a>, ParseError> {
+        self.parse_node(Expecting::Value, 0)
+            .map_err(|message| ParseError::new(message, self.position))
+    }
+
+    /// Parses the value at the current position
+    fn parse_node(&mut self, expecting: Expecting, depth: usize) -> Result<ArenaNode<'a>, String> {
+        match self.input.get(self.position) {
+            Some(b'i') => match parse_integer(self.input, &mut self.position)? {
+                BorrowedNode::Integer(value) => Ok(ArenaNode::Integer(value)),
+                _ => Err(ERR_INVALID_INTEGER.to_string()),
+            },
+            Some(b'l') => self.parse_list(depth + 1),
+            Some(b'd') => self.parse_dictionary(depth + 1),
+            Some(b'0'..=b'9') => {
+                let bytes = self.parse_string()?;
+                Ok(ArenaNode::Bytes(copy(self.arena, bytes)?))
+            }
+            Some(&c) => Err(unexpected_character(c as char, expecting, depth)),
+            None => Err(ERR_EMPTY_INPUT.to_string()),
+        }
+    }
+
+    /// Parses a byte string, returning it borrowed from the input
+    fn parse_string(&mut self) -> Result<&'p [u8], String> {
+        Ok(parse_bytes(self.input, &mut self.position)?.as_bytes().unwrap_or_default())
+    }
+
+    fn parse_list(&mut self, depth: usize) -> Result<ArenaNode<'a>, String> {
+        self.position += 1; // skip 'l'
+        let start = self.items.len();
+        while let Some(&c) = self.input.get(self.position) {
+            if c == b'e' {
+                self.position += 1;
+                let items = copy(self.arena, &self.items[start..])?;
+                self.items.truncate(start);
+                return Ok(ArenaNode::List(items));
+            }
+            let item = self.parse_node(Expecting::ListItem, depth)?;
+            self.items.push(item);
+        }
+        Err(ERR_UNTERMINATED_LIST.to_string())
+    }
+
+    fn parse_dictionary(&mut self, depth: usize) -> Result<ArenaNode<'a>, String> {
+        self.position += 1; // skip 'd'
+        let start = self.entries.len();
+        while let Some(&c) = self.input.get(self.position) {
+            if c == b'e' {
+                self.position += 1;
+                let entries = copy(self.arena, &self.entries[start..])?;
+                self.entries.truncate(start);
+                return Ok(ArenaNode::Dictionary(entries));
+            }
+            if !c.is_ascii_digit() {
+                return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string());
+            }
+            let key = self.parse_string()?;
+            if self.entries.len() > start
+                && let Some((last, _)) = self.entries.last()
+                && key <= *last
+            {
+                return Err(ERR_DICT_KEYS_ORDER.to_string());
+            }
+            let key = match self.interner.as_deref_mut() {
+                Some(interner) => interner.intern(self.arena, key).ok_or_else(|| ERR_ARENA_FULL.to_string())?,
+                None => copy(self.arena, key)?,
+            };
+            let value = self.parse_node(Expecting::DictValue, depth)?;
+            self.entries.push((key, value));
+        }
+        Err(ERR_UNTERMINATED_DICTIONARY.to_string())
+    }
+}
+
+/// Copies values into the arena
+fn copy<'a, T: Copy>(arena: &'a Arena, items: &[T]) -> Result<&'a [T], String> {
+    match arena.alloc_copy(items) {
+        Some(copied) => Ok(copied),
+        None => Err(ERR_ARENA_FULL.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+    use crate::parser::borrowed::parse_borrowed;
+
+    const TORRENTS: [&[u8]; 3] = [
+        b"d8:announce9:udp://one4:infod5:filesld6:lengthi3e4:pathl1:aeed6:lengthi4e4:pathl1:beee4:name3:dir\
+12:piece lengthi4e6:pieces20:aaaaaaaaaaaaaaaaaaaaee",
+        b"d4:infod6:lengthi10e4:name3:one12:piece lengthi16e6:pieces20:bbbbbbbbbbbbbbbbbbbbee",
+        b"li-1e0:led1:xdeee",
+    ];
+
+    #[test]
+    fn trees_match_parse_borrowed() {
+        let arena = Arena::with_capacity(4096);
+        let mut interner = Interner::new();
+        for input in TORRENTS {
+            let expected = parse_borrowed(input).unwrap();
+            assert_eq!(parse_in(&arena, input).unwrap().to_borrowed(), expected);
+            let root = parse_in_interned(&arena, &mut interner, input).unwrap();
+            assert_eq!(root.to_borrowed(), expected);
+            assert_eq!(root.to_node(), expected.to_node());
+        }
+    }
+
+    #[test]
+    fn navigation() {
+        let arena = Arena::with_capacity(1024);
+        let root = parse_in(&arena, TORRENTS[0]).unwrap();
+        let info = root.get("info").unwrap();
+        assert_eq!((root.type_name(), info.as_dictionary().map(<[_]>::len)), ("dictionary", Some(4)));
+        let files = info.get("files").and_then(|n| n.as_list()).unwrap();
+        assert_eq!(files[1].get("length").and_then(|n| n.as_integer()), Some(4));
+        assert_eq!(files[0].get("path").and_then(|n| n.as_list()).unwrap()[0].as_string(), Some("a"));
+        assert_eq!(info.get("pieces").and_then(|n| n.as_bytes()).map(<[u8]>::len), Some(20));
+        assert!(info.get("missing").is_none() && files[0].get("length").unwrap().as_list().is_none());
+    }
+
+    #[test]
+    fn interning_shares_repeated_keys() {
+        let plain = Arena::with_capacity(4096);
+        let interned = Arena::with_capacity(4096);
+        let mut interner = Interner::new();
+        for input in TORRENTS {
+            parse_in(&plain, input).unwrap();
+            parse_in_interned(&interned, &mut interner, input).unwrap();
+        }
+        // announce files info length path name piece length pieces x
+        assert_eq!(interner.len(), 9);
+        assert_eq!(interner.hits(), 7);
+        assert!(interned.used() < plain.used());
+
+        let first = parse_in_interned(&interned, &mut interner, TORRENTS[1]).unwrap();
+        let second = parse_in_interned(&interned, &mut interner, TORRENTS[1]).unwrap();
+        let key = |root: ArenaNode| root.as_dictionary().unwrap()[0].0.as_ptr();
+        assert_eq!(key(first), key(second));
+    }
+
+    #[test]
+    fn errors_match_parse_borrowed() {
+        let arena = Arena::with_capacity(1024);
+        for data in [&b""[..], b"i1", b"i-0e", b"5:abc", b"li1e", b"d1:bi1e1:ai2ee", b"di1ei2ee", b"d1:ai1e", b"x", b"lx"] {
+            let expected = parse_borrowed(data).unwrap_err();
+            assert_eq!(parse_in(&arena, data).unwrap_err(), expected, "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn full_arena_is_an_error() {
+        let arena = Arena::with_capacity(8);
+        let error = parse_in(&arena, b"l9:abcdefghie").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ArenaFull);
+        assert_eq!(error.offset, 12);
+        let empty = Arena::with_capacity(0);
+        let mut interner = Interner::new();
+        let error = parse_in_interned(&empty, &mut interner, b"d1:ai1ee").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ArenaFull);
+        assert!(interner.is_empty());
+    }
+}
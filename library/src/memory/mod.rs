@@ -11,6 +11,15 @@ use alloc::vec::Vec;
 
 use core::cell::Cell;
 
+/// Parsing into an arena, with optional interning of dictionary keys
+pub mod arena;
+
+/// Recycling of node allocations between parses
+pub mod pool;
+
+/// Zero-copy parsing into reusable scratch space
+pub mod scratch;
+
 /// A simple memory usage tracker that can be queried during parsing.
 ///
 /// This helps embedded systems monitor and limit memory consumption.
@@ -103,23 +112,58 @@ impl Arena {
         }
     }
 
-    /// Allocates a byte slice from the arena.
+    /// Reserves `size` bytes aligned to `align` and returns their address.
     /// Returns None if there's insufficient space.
-    pub fn alloc_bytes(&self, size: usize) -> Option<&mut [u8]> {
-        let pos = self.position.get();
-        let new_pos = pos + size;
+    fn alloc_raw(&self, size: usize, align: usize) -> Option<*mut u8> {
+        let base = self.buffer.as_ptr() as usize;
+        let start = (base + self.position.get()).checked_next_multiple_of(align)? - base;
+        let end = start.checked_add(size)?;
 
-        if new_pos > self.buffer.capacity() {
+        if end > self.buffer.capacity() {
             return None;
         }
 
-        self.position.set(new_pos);
+        self.position.set(end);
+
+        // Safety: start is within the buffer's capacity, checked above
+        Some(unsafe { self.buffer.as_ptr().add(start) as *mut u8 })
+    }
+
+    /// Allocates a byte slice from the arena.
+    /// Returns None if there's insufficient space.
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_bytes(&self, size: usize) -> Option<&mut [u8]> {
+        let ptr = self.alloc_raw(size, 1)?;
 
         // Safety: We've checked the bounds and we own the buffer
         // This is safe because we're returning a unique mutable reference
+        unsafe { Some(core::slice::from_raw_parts_mut(ptr, size)) }
+    }
+
+    /// Copies a slice of plain values into the arena, aligned for their type.
+    /// Returns None if there's insufficient space.
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::Arena;
+    ///
+    /// let arena = Arena::with_capacity(64);
+    /// let copy = arena.alloc_copy(&[1u32, 2, 3]).unwrap();
+    /// assert_eq!(copy, [1, 2, 3]);
+    /// assert!(arena.alloc_copy(&[0u64; 8]).is_none());
+    /// ```
+    #[allow(clippy::mut_from_ref)]
+    pub fn alloc_copy<T: Copy>(&self, items: &[T]) -> Option<&mut [T]> {
+        if items.is_empty() {
+            return Some(&mut []);
+        }
+        let ptr = self.alloc_raw(core::mem::size_of_val(items), core::mem::align_of::<T>())? as *mut T;
+
+        // Safety: the reserved space is aligned for T, large enough for the
+        // items and handed out only once; T is Copy, so has no drop to skip
         unsafe {
-            let ptr = self.buffer.as_ptr().add(pos) as *mut u8;
-            Some(core::slice::from_raw_parts_mut(ptr, size))
+            ptr.copy_from_nonoverlapping(items.as_ptr(), items.len());
+            Some(core::slice::from_raw_parts_mut(ptr, items.len()))
         }
     }
 
@@ -249,6 +293,18 @@ mod tests {
             assert_eq!(tracker.current(), 0);
         }
 
+        #[test]
+        fn arena_copies_are_aligned() {
+            let arena = Arena::with_capacity(64);
+            arena.alloc_bytes(1).unwrap();
+            let copy = arena.alloc_copy(&[7u64, 8]).unwrap();
+            assert_eq!(copy, [7, 8]);
+            assert_eq!(copy.as_ptr() as usize % core::mem::align_of::<u64>(), 0);
+            assert!(arena.used() >= 17);
+            assert_eq!(arena.alloc_copy::<u64>(&[]).unwrap(), []);
+            assert!(arena.alloc_copy(&[0u8; 64]).is_none());
+        }
+
         #[test]
         fn arena_allocation_errors() {
             let arena = Arena::with_capacity(8);
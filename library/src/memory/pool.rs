@@ -0,0 +1,364 @@
+//! Recycling of node allocations between parses.
+//!
+//! Parsing a small tracker message allocates a buffer for every string and a
+//! container for every list and dictionary, only for most of them to be freed
+//! moments later. A [`NodePool`] keeps those allocations when a tree is handed
+//! back with [`NodePool::recycle`] and reuses them for the next parse, so a
+//! service parsing many similar messages stops hitting the allocator once the
+//! pool has warmed up.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::HashMap;
+use crate::error::messages::*;
+use crate::nodes::integer::parse_integer_text;
+use crate::nodes::node::Node;
+use crate::parser::common::{unterminated_integer, unterminated_string_length};
+
+/// Default number of free allocations of each kind a pool keeps
+pub const DEFAULT_MAX_RETAINED: usize = 1024;
+
+/// Pool of string, list and dictionary allocations reused across parses.
+///
+/// # Example
+/// ```
+/// use bencode_lib::NodePool;
+///
+/// let mut pool = NodePool::new();
+/// for message in [&b"d8:intervali1800ee"[..], b"d8:intervali900ee"] {
+///     let node = pool.parse(message).unwrap();
+///     assert!(node.is_dictionary());
+///     pool.recycle(node);
+/// }
+/// assert!(pool.reused() > 0);
+/// ```
+pub struct NodePool {
+    buffers: Vec<Vec<u8>>,
+    lists: Vec<Vec<Node>>,
+    dictionaries: Vec<HashMap<String, Node>>,
+    max_retained: usize,
+    reused: usize,
+    allocated: usize,
+}
+
+impl Default for NodePool {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NodePool {
+    /// Creates an empty pool.
+    pub const fn new() -> Self {
+        Self {
+            buffers: Vec::new(),
+            lists: Vec::new(),
+            dictionaries: Vec::new(),
+            max_retained: DEFAULT_MAX_RETAINED,
+            reused: 0,
+            allocated: 0,
+        }
+    }
+
+    /// Sets how many free allocations of each kind the pool keeps; the rest
+    /// are freed as usual.
+    pub const fn with_max_retained(mut self, max_retained: usize) -> Self {
+        self.max_retained = max_retained;
+        self
+    }
+
+    /// Returns the number of free allocations held by the pool
+    pub fn retained(&self) -> usize {
+        self.buffers.len() + self.lists.len() + self.dictionaries.len()
+    }
+
+    /// Returns how many allocations have been served from the pool
+    pub fn reused(&self) -> usize {
+        self.reused
+    }
+
+    /// Returns how many allocations the pool could not serve and had to make
+    pub fn allocated(&self) -> usize {
+        self.allocated
+    }
+
+    /// Takes a free allocation of one kind, or makes a new one
+    fn take<T: Default>(free: &mut Vec<T>, reused: &mut usize, allocated: &mut usize) -> T {
+        match free.pop() {
+            Some(value) => {
+                *reused += 1;
+                value
+            }
+            None => {
+                *allocated += 1;
+                T::default()
+            }
+        }
+    }
+
+    fn buffer(&mut self) -> Vec<u8> {
+        Self::take(&mut self.buffers, &mut self.reused, &mut self.allocated)
+    }
+
+    fn list(&mut self) -> Vec<Node> {
+        Self::take(&mut self.lists, &mut self.reused, &mut self.allocated)
+    }
+
+    fn dictionary(&mut self) -> HashMap<String, Node> {
+        Self::take(&mut self.dictionaries, &mut self.reused, &mut self.allocated)
+    }
+
+    /// Hands a tree back so its allocations can be reused.
+    ///
+    /// # Arguments
+    /// * `node` - A tree that is no longer needed (from this pool or anywhere else)
+    pub fn recycle(&mut self, node: Node) {
+        match node {
+            Node::Str(text) | Node::BigInteger(text) => self.recycle_buffer(text.into_bytes()),
+            Node::Bytes(bytes) | Node::Raw(bytes) => self.recycle_buffer(bytes),
+            Node::List(mut items) => {
+                for item in items.drain(..) {
+                    self.recycle(item);
+                }
+                if self.lists.len() < self.max_retained {
+                    self.lists.push(items);
+                }
+            }
+            Node::Dictionary(mut entries) => {
+                // Draining keeps the map's capacity for the next parse
+                #[cfg(feature = "std")]
+                let drained = entries.drain();
+                #[cfg(not(feature = "std"))]
+                let drained = core::iter::from_fn(|| entries.pop_first());
+                for (key, value) in drained {
+                    self.recycle_buffer(key.into_bytes());
+                    self.recycle(value);
+                }
+                if self.dictionaries.len() < self.max_retained {
+                    self.dictionaries.push(entries);
+                }
+            }
+            Node::Integer(_) | Node::None => {}
+        }
+    }
+
+    fn recycle_buffer(&mut self, mut buffer: Vec<u8>) {
+        if self.buffers.len() < self.max_retained {
+            buffer.clear();
+            self.buffers.push(buffer);
+        }
+    }
+
+    /// Parses bencode data, drawing allocations from the pool.
+    ///
+    /// Accepts the same input and reports the same errors as
+    /// [`parse_bytes`](crate::parse_bytes).
+    ///
+    /// # Arguments
+    /// * `data` - The bencode data
+    ///
+    /// # Returns
+    /// * `Ok(Node)` - The parsed tree, to be passed to [`recycle`](Self::recycle) when done
+    /// * `Err(String)` - Description of the parse error
+    pub fn parse(&mut self, data: &[u8]) -> Result<Node, String> {
+        let mut position = 0;
+        self.parse_value(data, &mut position, Expecting::Value, 0)
+    }
+
+    fn parse_value(
+        &mut self,
+        data: &[u8],
+        position: &mut usize,
+        expecting: Expecting,
+        depth: usize,
+    ) -> Result<Node, String> {
+        match data.get(*position) {
+            Some(b'i') => parse_integer(data, position),
+            Some(b'l') => self.parse_list(data, position, depth + 1),
+            Some(b'd') => self.parse_dictionary(data, position, depth + 1),
+            Some(b'0'..=b'9') => Ok(Node::from_bytes(self.parse_string(data, position)?)),
+            Some(b':') => Err(ERR_INVALID_STRING_LENGTH.to_string()),
+            Some(&c) => Err(unexpected_character(c as char, expecting, depth)),
+            None => Err(ERR_EMPTY_INPUT.to_string()),
+        }
+    }
+
+    /// Reads `<length>:<bytes>` into a pooled buffer
+    fn parse_string(&mut self, data: &[u8], position: &mut usize) -> Result<Vec<u8>, String> {
+        let rest = &data[*position..];
+        let separator = rest
+            .iter()
+            .position(|&b| b == b':')
+            .ok_or_else(|| unterminated_string_length(rest).to_string())?;
+        let length = core::str::from_utf8(&rest[..separator])
+            .ok()
+            .and_then(|text| text.parse::<usize>().ok())
+            .ok_or_else(|| ERR_INVALID_STRING_LENGTH.to_string())?;
+        let start = *position + separator + 1;
+        let content = start
+            .checked_add(length)
+            .and_then(|end| data.get(start..end))
+            .ok_or_else(|| ERR_STRING_TOO_SHORT.to_string())?;
+        let mut buffer = self.buffer();
+        buffer.extend_from_slice(content);
+        *position = start + length;
+        Ok(buffer)
+    }
+
+    fn parse_list(&mut self, data: &[u8], position: &mut usize, depth: usize) -> Result<Node, String> {
+        *position += 1; // skip 'l'
+        let mut items = self.list();
+        while let Some(&c) = data.get(*position) {
+            if c == b'e' {
+                *position += 1;
+                return Ok(Node::List(items));
+            }
+            match self.parse_value(data, position, Expecting::ListItem, depth) {
+                Ok(item) => items.push(item),
+                Err(error) => {
+                    self.recycle(Node::List(items));
+                    return Err(error);
+                }
+            }
+        }
+        self.recycle(Node::List(items));
+        Err(ERR_UNTERMINATED_LIST.to_string())
+    }
+
+    fn parse_dictionary(&mut self, data: &[u8], position: &mut usize, depth: usize) -> Result<Node, String> {
+        *position += 1; // skip 'd'
+        let mut entries = self.dictionary();
+        // Copy of the previous key for the order check, empty before the first
+        let mut last_key = self.buffer();
+        let mut first = true;
+        let result = loop {
+            let Some(&c) = data.get(*position) else {
+                break Err(ERR_UNTERMINATED_DICTIONARY.to_string());
+            };
+            if c == b'e' {
+                *position += 1;
+                break Ok(());
+            }
+            if !c.is_ascii_digit() {
+                break Err(ERR_DICT_KEY_MUST_BE_STRING.to_string());
+            }
+            let key = match self.parse_string(data, position) {
+                Ok(key) => key,
+                Err(error) => break Err(error),
+            };
+            if !first && key <= last_key {
+                self.recycle_buffer(key);
+                break Err(ERR_DICT_KEYS_ORDER.to_string());
+            }
+            first = false;
+            last_key.clear();
+            last_key.extend_from_slice(&key);
+            let value = match self.parse_value(data, position, Expecting::DictValue, depth) {
+                Ok(value) => value,
+                Err(error) => {
+                    self.recycle_buffer(key);
+                    break Err(error);
+                }
+            };
+            let name = String::from_utf8(key).unwrap_or_else(|binary| {
+                let name = String::from_utf8_lossy(binary.as_bytes()).into_owned();
+                self.recycle_buffer(binary.into_bytes());
+                name
+            });
+            if let Some(replaced) = entries.insert(name, value) {
+                self.recycle(replaced);
+            }
+        };
+        self.recycle_buffer(last_key);
+        match result {
+            Ok(()) => Ok(Node::Dictionary(entries)),
+            Err(error) => {
+                self.recycle(Node::Dictionary(entries));
+                Err(error)
+            }
+        }
+    }
+}
+
+/// Parses `i<number>e`
+fn parse_integer(data: &[u8], position: &mut usize) -> Result<Node, String> {
+    let rest = &data[*position + 1..];
+    let end = rest
+        .iter()
+        .position(|&b| b == b'e')
+        .ok_or_else(|| unterminated_integer(rest).to_string())?;
+    let text = core::str::from_utf8(&rest[..end]).map_err(|_| ERR_INVALID_INTEGER.to_string())?;
+    let value = parse_integer_text(text).map_err(|e| e.to_string())?;
+    *position += end + 2;
+    Ok(Node::Integer(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_bytes;
+
+    const MESSAGES: [&[u8]; 3] = [
+        b"d8:completei5e10:incompletei2e8:intervali1800e5:peersl20:aaaaaaaaaaaaaaaaaaaaee",
+        b"d8:intervali900e5:peers6:\x0a\x00\x00\x01\x1a\xe1e",
+        b"d14:failure reason7:refusede",
+    ];
+
+    #[test]
+    fn pooled_parses_match_parse_bytes() {
+        let mut pool = NodePool::new();
+        for _ in 0..3 {
+            for message in MESSAGES {
+                let node = pool.parse(message).unwrap();
+                assert_eq!(node, parse_bytes(message).unwrap());
+                pool.recycle(node);
+            }
+        }
+        // After the first round every allocation comes from the pool
+        let warm = pool.allocated();
+        for message in MESSAGES {
+            let node = pool.parse(message).unwrap();
+            pool.recycle(node);
+        }
+        assert_eq!(pool.allocated(), warm);
+        assert!(pool.reused() > 0);
+    }
+
+    #[test]
+    fn errors_match_parse_bytes() {
+        let mut pool = NodePool::new();
+        for data in [
+            &b""[..],
+            b"i1",
+            b"i-0e",
+            b"5:abc",
+            b":",
+            b"li1e",
+            b"d1:bi1e1:ai2ee",
+            b"di1ei2ee",
+            b"d1:ai1e",
+            b"x",
+            b"lx",
+            b"d1:a",
+            b"d4",
+            b"d5:ab",
+            b"12",
+            b"i1x",
+        ] {
+            assert_eq!(pool.parse(data).unwrap_err(), parse_bytes(data).unwrap_err().message, "{:?}", data);
+        }
+        // Allocations made by failed parses are kept for reuse
+        assert!(pool.retained() > 0);
+    }
+
+    #[test]
+    fn retention_is_bounded() {
+        let mut pool = NodePool::new().with_max_retained(2);
+        pool.recycle(parse_bytes(b"l1:a1:b1:cll1:deee").unwrap());
+        assert_eq!(pool.retained(), 4);
+    }
+}
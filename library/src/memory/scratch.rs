@@ -0,0 +1,400 @@
+//! Zero-copy parsing into reusable scratch space.
+//!
+//! [`parse_borrowed`](crate::parse_borrowed) borrows strings from the input but
+//! still allocates a vector or map for every list and dictionary. A [`Scratch`]
+//! instead records the whole tree in one flat table of slots that is cleared,
+//! not freed, between parses, so once it has grown to fit the largest message a
+//! device handles, parsing no longer touches the heap.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::HashMap;
+use crate::error::messages::*;
+use crate::error::parse::ParseError;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::nodes::integer::Integer;
+use crate::nodes::node::Node;
+use crate::parser::borrowed::{parse_bytes, parse_integer};
+
+/// One node of a parsed tree, stored in pre-order.
+///
+/// Containers record their item count and the index of the first slot after
+/// their subtree; a dictionary's items alternate between key and value slots.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Slot {
+    Integer(Integer),
+    Bytes { start: usize, end: usize },
+    List { len: usize, next: usize },
+    Dictionary { len: usize, next: usize },
+}
+
+/// Reusable storage for [`parse_borrowed_in`].
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_borrowed_in, Scratch};
+///
+/// let mut scratch = Scratch::new();
+/// for message in [&b"d8:intervali1800e5:peersl2:p1ee"[..], b"d8:intervali900e5:peerslee"] {
+///     let root = parse_borrowed_in(&mut scratch, message).unwrap();
+///     assert!(root.get("interval").and_then(|n| n.as_integer()).is_some());
+/// }
+/// ```
+#[derive(Debug, Default)]
+pub struct Scratch {
+    slots: Vec<Slot>,
+}
+
+impl Scratch {
+    /// Creates empty scratch space, which grows on first use.
+    pub const fn new() -> Self {
+        Self { slots: Vec::new() }
+    }
+
+    /// Creates scratch space for trees of up to `nodes` nodes (dictionary keys
+    /// count as nodes) without growing.
+    pub fn with_capacity(nodes: usize) -> Self {
+        Self {
+            slots: Vec::with_capacity(nodes),
+        }
+    }
+
+    /// Returns the number of nodes the scratch space holds without growing
+    pub fn capacity(&self) -> usize {
+        self.slots.capacity()
+    }
+
+    /// Returns the number of nodes (including dictionary keys) of the last parse
+    pub fn len(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns true if no tree is held
+    pub fn is_empty(&self) -> bool {
+        self.slots.is_empty()
+    }
+}
+
+/// Parses bencode data without copying strings, storing the tree in `scratch`.
+///
+/// Accepts the same input and reports the same errors as
+/// [`parse_borrowed`](crate::parse_borrowed); the previous tree in `scratch` is
+/// discarded. Once `scratch` has the capacity for a tree, parsing it makes no
+/// heap allocations.
+///
+/// # Arguments
+/// * `scratch` - Storage for the tree, reused across calls
+/// * `input` - The bencode data
+///
+/// # Returns
+/// * `Ok(ScratchRef)` - View of the root node, borrowing `scratch` and `input`
+/// * `Err(ParseError)` - Description of the parse error and its offset
+pub fn parse_borrowed_in<'a>(scratch: &'a mut Scratch, input: &'a [u8]) -> Result<ScratchRef<'a>, ParseError> {
+    scratch.slots.clear();
+    let mut position = 0;
+    parse_slot(&mut scratch.slots, input, &mut position, Expecting::Value, 0)
+        .map_err(|message| ParseError::new(message, position))?;
+    Ok(ScratchRef::new(input, &scratch.slots))
+}
+
+/// Appends the slots of the value at `position`
+fn parse_slot(
+    slots: &mut Vec<Slot>,
+    input: &[u8],
+    position: &mut usize,
+    expecting: Expecting,
+    depth: usize,
+) -> Result<(), String> {
+    match input.get(*position) {
+        Some(b'i') => {
+            if let BorrowedNode::Integer(value) = parse_integer(input, position)? {
+                slots.push(Slot::Integer(value));
+            }
+            Ok(())
+        }
+        Some(b'l') => parse_container(slots, input, position, false, depth + 1),
+        Some(b'd') => parse_container(slots, input, position, true, depth + 1),
+        Some(b'0'..=b'9') => push_bytes(slots, input, position),
+        Some(&c) => Err(unexpected_character(c as char, expecting, depth)),
+        None => Err(ERR_EMPTY_INPUT.to_string()),
+    }
+}
+
+/// Appends a byte string slot
+fn push_bytes(slots: &mut Vec<Slot>, input: &[u8], position: &mut usize) -> Result<(), String> {
+    let length = parse_bytes(input, position)?.as_bytes().map_or(0, <[u8]>::len);
+    slots.push(Slot::Bytes {
+        start: *position - length,
+        end: *position,
+    });
+    Ok(())
+}
+
+/// Appends a list or dictionary slot followed by its items
+fn parse_container(
+    slots: &mut Vec<Slot>,
+    input: &[u8],
+    position: &mut usize,
+    dictionary: bool,
+    depth: usize,
+) -> Result<(), String> {
+    *position += 1; // skip 'l' or 'd'
+    let header = slots.len();
+    slots.push(Slot::List { len: 0, next: 0 });
+    let mut len = 0;
+    let mut last_key: Option<(usize, usize)> = None;
+    while let Some(&c) = input.get(*position) {
+        if c == b'e' {
+            *position += 1;
+            let next = slots.len();
+            slots[header] = if dictionary {
+                Slot::Dictionary { len, next }
+            } else {
+                Slot::List { len, next }
+            };
+            return Ok(());
+        }
+        if dictionary {
+            if !c.is_ascii_digit() {
+                return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string());
+            }
+            push_bytes(slots, input, position)?;
+            let Some(&Slot::Bytes { start, end }) = slots.last() else {
+                return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string());
+            };
+            if let Some((last_start, last_end)) = last_key
+                && input[start..end] <= input[last_start..last_end]
+            {
+                return Err(ERR_DICT_KEYS_ORDER.to_string());
+            }
+            last_key = Some((start, end));
+            parse_slot(slots, input, position, Expecting::DictValue, depth)?;
+        } else {
+            parse_slot(slots, input, position, Expecting::ListItem, depth)?;
+        }
+        len += 1;
+    }
+    Err(if dictionary {
+        ERR_UNTERMINATED_DICTIONARY
+    } else {
+        ERR_UNTERMINATED_LIST
+    }
+    .to_string())
+}
+
+/// A view of one node of a tree held in a [`Scratch`] or a
+/// [`BoundedNode`](crate::BoundedNode).
+#[derive(Clone, Copy, Debug)]
+pub struct ScratchRef<'a> {
+    input: &'a [u8],
+    slots: &'a [Slot],
+    index: usize,
+}
+
+impl<'a> ScratchRef<'a> {
+    /// Returns a view of the root of a slot table
+    pub(crate) fn new(input: &'a [u8], slots: &'a [Slot]) -> Self {
+        Self { input, slots, index: 0 }
+    }
+
+    /// Returns the type name of the node ("integer", "string", "list" or "dictionary")
+    pub fn type_name(&self) -> &'static str {
+        match self.slot() {
+            Slot::Integer(_) => "integer",
+            Slot::Bytes { .. } => "string",
+            Slot::List { .. } => "list",
+            Slot::Dictionary { .. } => "dictionary",
+        }
+    }
+
+    /// Returns the integer value if this is an integer node
+    pub fn as_integer(&self) -> Option<Integer> {
+        match self.slot() {
+            Slot::Integer(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns the string value if this is a string node holding valid UTF-8
+    pub fn as_string(&self) -> Option<&'a str> {
+        self.as_bytes().and_then(|bytes| core::str::from_utf8(bytes).ok())
+    }
+
+    /// Returns the bytes of a string node, borrowed from the input
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        match self.slot() {
+            Slot::Bytes { start, end } => Some(&self.input[start..end]),
+            _ => None,
+        }
+    }
+
+    /// Returns the number of items in a list or dictionary (0 for other nodes)
+    pub fn len(&self) -> usize {
+        match self.slot() {
+            Slot::List { len, .. } | Slot::Dictionary { len, .. } => len,
+            _ => 0,
+        }
+    }
+
+    /// Returns true if this node has no items
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the list element at `index`
+    pub fn index(&self, index: usize) -> Option<ScratchRef<'a>> {
+        self.items().nth(index)
+    }
+
+    /// Returns the dictionary value for `key`
+    pub fn get(&self, key: &str) -> Option<ScratchRef<'a>> {
+        self.entries()
+            .find(|(k, _)| *k == key.as_bytes())
+            .map(|(_, value)| value)
+    }
+
+    /// Iterates over list elements
+    pub fn items(&self) -> impl Iterator<Item = ScratchRef<'a>> + 'a {
+        let this = *self;
+        let count = if matches!(this.slot(), Slot::List { .. }) { this.len() } else { 0 };
+        let mut index = this.index + 1;
+        (0..count).map(move |_| {
+            let item = this.at(index);
+            index = item.next();
+            item
+        })
+    }
+
+    /// Iterates over dictionary entries in key order as (raw key, value)
+    pub fn entries(&self) -> impl Iterator<Item = (&'a [u8], ScratchRef<'a>)> + 'a {
+        let this = *self;
+        let count = if matches!(this.slot(), Slot::Dictionary { .. }) { this.len() } else { 0 };
+        let mut index = this.index + 1;
+        (0..count).map(move |_| {
+            let key = this.at(index).as_bytes().unwrap_or_default();
+            let value = this.at(index + 1);
+            index = value.next();
+            (key, value)
+        })
+    }
+
+    /// Copies the tree rooted at this view into a [`BorrowedNode`]
+    pub fn to_borrowed(&self) -> BorrowedNode<'a> {
+        match self.slot() {
+            Slot::Integer(value) => BorrowedNode::Integer(value),
+            Slot::Bytes { start, end } => BorrowedNode::Bytes(&self.input[start..end]),
+            Slot::List { .. } => BorrowedNode::List(self.items().map(|item| item.to_borrowed()).collect()),
+            Slot::Dictionary { .. } => BorrowedNode::Dictionary(
+                self.entries()
+                    .map(|(key, value)| (key, value.to_borrowed()))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Rebuilds the owned node tree rooted at this view.
+    pub fn to_node(&self) -> Node {
+        match self.slot() {
+            Slot::Integer(value) => Node::Integer(value),
+            Slot::Bytes { start, end } => Node::from_bytes(self.input[start..end].to_vec()),
+            Slot::List { .. } => Node::List(self.items().map(|item| item.to_node()).collect()),
+            Slot::Dictionary { .. } => Node::Dictionary(
+                self.entries()
+                    .map(|(k, v)| (String::from_utf8_lossy(k).into_owned(), v.to_node()))
+                    .collect::<HashMap<_, _>>(),
+            ),
+        }
+    }
+
+    /// Returns a view of another slot of the same tree
+    fn at(&self, index: usize) -> ScratchRef<'a> {
+        ScratchRef { index, ..*self }
+    }
+
+    fn slot(&self) -> Slot {
+        self.slots[self.index]
+    }
+
+    /// Returns the index of the first slot after this node's subtree
+    fn next(&self) -> usize {
+        match self.slot() {
+            Slot::List { next, .. } | Slot::Dictionary { next, .. } => next,
+            _ => self.index + 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse_borrowed;
+
+    const MESSAGES: [&[u8]; 3] = [
+        b"d8:completei5e10:incompletei2e8:intervali1800e5:peersl20:aaaaaaaaaaaaaaaaaaaaee",
+        b"d8:intervali900e5:peers6:\x0a\x00\x00\x01\x1a\xe1e",
+        b"li1eli2eli3eee4:spamd1:ad1:blleeeee",
+    ];
+
+    #[test]
+    fn trees_match_parse_borrowed() {
+        let mut scratch = Scratch::new();
+        for message in MESSAGES {
+            let root = parse_borrowed_in(&mut scratch, message).unwrap();
+            assert_eq!(root.to_borrowed(), parse_borrowed(message).unwrap());
+            assert_eq!(root.to_node(), parse_borrowed(message).unwrap().to_node());
+        }
+    }
+
+    #[test]
+    fn navigation() {
+        let mut scratch = Scratch::new();
+        let root = parse_borrowed_in(&mut scratch, b"d4:listli1el1:xee3:numi-7e3:str3:abce").unwrap();
+        assert_eq!((root.type_name(), root.len()), ("dictionary", 3));
+        let list = root.get("list").unwrap();
+        assert_eq!(list.index(0).and_then(|n| n.as_integer()), Some(1));
+        assert_eq!(list.index(1).and_then(|n| n.index(0)).and_then(|n| n.as_string()), Some("x"));
+        assert!(list.index(2).is_none());
+        assert_eq!(root.get("num").and_then(|n| n.as_integer()), Some(-7));
+        assert_eq!(root.get("str").and_then(|n| n.as_bytes()), Some(&b"abc"[..]));
+        assert!(root.get("missing").is_none());
+        assert_eq!(root.entries().map(|(k, _)| k).collect::<Vec<_>>(), vec![&b"list"[..], b"num", b"str"]);
+        assert_eq!(scratch.len(), 10);
+    }
+
+    #[test]
+    fn warm_scratch_does_not_grow() {
+        let mut scratch = Scratch::with_capacity(16);
+        let capacity = scratch.capacity();
+        for _ in 0..3 {
+            for message in MESSAGES {
+                parse_borrowed_in(&mut scratch, message).unwrap();
+            }
+        }
+        assert_eq!(scratch.capacity(), capacity);
+    }
+
+    #[test]
+    fn errors_match_parse_borrowed() {
+        let mut scratch = Scratch::new();
+        for data in [
+            &b""[..],
+            b"i1",
+            b"i-0e",
+            b"5:abc",
+            b"li1e",
+            b"d1:bi1e1:ai2ee",
+            b"di1ei2ee",
+            b"d1:ai1e",
+            b"x",
+            b"lx",
+            b"d1:a",
+        ] {
+            let expected = parse_borrowed(data).unwrap_err();
+            assert_eq!(parse_borrowed_in(&mut scratch, data).unwrap_err(), expected, "{:?}", data);
+        }
+    }
+}
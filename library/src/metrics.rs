@@ -0,0 +1,129 @@
+//! Metrics emitted by the main entry points (requires `metrics` feature).
+//!
+//! With the feature enabled, [`parse`](crate::parse), [`parse_bytes`](crate::parse_bytes),
+//! [`parse_borrowed`](crate::parse_borrowed), [`validate_bencode`](crate::validate_bencode),
+//! [`stringify`](crate::stringify) and [`stringify_to_bytes`](crate::stringify_to_bytes)
+//! report through the [`metrics`](https://docs.rs/metrics) facade, so whichever
+//! recorder the service installs (Prometheus, StatsD, ...) picks them up. Every
+//! metric has an `operation` label naming the entry point; errors also have a
+//! `kind` label with the [`ErrorKind`](crate::ErrorKind) variant name.
+//!
+//! Without the feature the hooks compile to nothing.
+
+/// Counter of calls to an entry point
+pub const CALLS: &str = "bencode_calls_total";
+/// Histogram of call durations in seconds
+pub const DURATION: &str = "bencode_duration_seconds";
+/// Counter of bytes parsed or produced (entry points working on byte slices)
+pub const BYTES: &str = "bencode_bytes_total";
+/// Counter of failed calls, labelled by error kind
+pub const ERRORS: &str = "bencode_errors_total";
+
+/// Times one call to an entry point and records its outcome.
+pub(crate) struct Timer {
+    #[cfg(feature = "metrics")]
+    operation: &'static str,
+    #[cfg(feature = "metrics")]
+    start: std::time::Instant,
+}
+
+#[cfg(feature = "metrics")]
+impl Timer {
+    /// Starts timing a call
+    pub(crate) fn start(operation: &'static str) -> Self {
+        Self {
+            operation,
+            start: std::time::Instant::now(),
+        }
+    }
+
+    /// Records the call's duration, size (if known) and any error
+    pub(crate) fn finish<T, E: AsRef<str>>(self, bytes: Option<usize>, result: &Result<T, E>) {
+        let operation = self.operation;
+        ::metrics::counter!(CALLS, "operation" => operation).increment(1);
+        ::metrics::histogram!(DURATION, "operation" => operation).record(self.start.elapsed().as_secs_f64());
+        if let Some(bytes) = bytes {
+            ::metrics::counter!(BYTES, "operation" => operation).increment(bytes as u64);
+        }
+        if let Err(message) = result {
+            let kind = format!("{:?}", crate::error::kind::ErrorKind::from_message(message.as_ref()));
+            ::metrics::counter!(ERRORS, "operation" => operation, "kind" => kind).increment(1);
+        }
+    }
+}
+
+#[cfg(not(feature = "metrics"))]
+impl Timer {
+    /// Does nothing without the `metrics` feature
+    #[inline(always)]
+    pub(crate) fn start(_operation: &'static str) -> Self {
+        Self {}
+    }
+
+    /// Does nothing without the `metrics` feature
+    #[inline(always)]
+    pub(crate) fn finish<T, E>(self, _bytes: Option<usize>, _result: &Result<T, E>) {}
+}
+
+#[cfg(all(test, feature = "metrics"))]
+mod tests {
+    use ::metrics::{Counter, Gauge, Histogram, Key, KeyName, Metadata, Recorder, SharedString, Unit};
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    /// Recorder keeping counters in memory, keyed by name and labels
+    #[derive(Default)]
+    struct Counters(Mutex<Vec<(String, Arc<AtomicU64>)>>);
+
+    impl Counters {
+        fn get(&self, key: &str) -> u64 {
+            let counters = self.0.lock().unwrap();
+            counters
+                .iter()
+                .filter(|(name, _)| name == key)
+                .map(|(_, value)| value.load(Ordering::Relaxed))
+                .sum()
+        }
+    }
+
+    impl Recorder for Counters {
+        fn describe_counter(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+        fn describe_gauge(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+        fn describe_histogram(&self, _key: KeyName, _unit: Option<Unit>, _description: SharedString) {}
+
+        fn register_counter(&self, key: &Key, _metadata: &Metadata<'_>) -> Counter {
+            let mut name = key.name().to_string();
+            for label in key.labels() {
+                name.push_str(&format!(",{}={}", label.key(), label.value()));
+            }
+            let value = Arc::new(AtomicU64::new(0));
+            self.0.lock().unwrap().push((name, value.clone()));
+            Counter::from_arc(value)
+        }
+
+        fn register_gauge(&self, _key: &Key, _metadata: &Metadata<'_>) -> Gauge {
+            Gauge::noop()
+        }
+
+        fn register_histogram(&self, _key: &Key, _metadata: &Metadata<'_>) -> Histogram {
+            Histogram::noop()
+        }
+    }
+
+    #[test]
+    fn entry_points_record_calls_bytes_and_errors() {
+        let counters = Counters::default();
+        ::metrics::with_local_recorder(&counters, || {
+            crate::parse_bytes(b"li1ee").unwrap();
+            crate::parse_bytes(b"li1e").unwrap_err();
+            crate::validate_bencode(b"i1e").unwrap();
+        });
+        assert_eq!(counters.get("bencode_calls_total,operation=parse_bytes"), 2);
+        assert_eq!(counters.get("bencode_bytes_total,operation=parse_bytes"), 9);
+        assert_eq!(
+            counters.get("bencode_errors_total,operation=parse_bytes,kind=UnterminatedList"),
+            1
+        );
+        assert_eq!(counters.get("bencode_calls_total,operation=validate_bencode"), 1);
+    }
+}
@@ -0,0 +1,117 @@
+//! Locale-neutral formatting of sizes and dates for reports and tool output.
+//!
+//! The output does not depend on the user's locale: sizes use binary units
+//! with a `.` decimal point and dates are UTC in year-month-day order, so logs
+//! and listings compare the same on every machine.
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::String};
+
+/// Units of [`human_size`], each 1024 times the one before
+const SIZE_UNITS: [&str; 7] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB", "EiB"];
+
+/// Seconds in a day
+const SECONDS_PER_DAY: i64 = 86_400;
+
+/// Formats a byte count with binary units and one decimal place.
+///
+/// # Arguments
+/// * `bytes` - The number of bytes
+///
+/// # Returns
+/// The size, such as `512 B`, `1.5 KiB` or `4.0 GiB`
+///
+/// # Example
+/// ```
+/// use bencode_lib::human_size;
+///
+/// assert_eq!(human_size(1023), "1023 B");
+/// assert_eq!(human_size(1536), "1.5 KiB");
+/// assert_eq!(human_size(734_003_200), "700.0 MiB");
+/// ```
+pub fn human_size(bytes: u64) -> String {
+    let mut unit = 0;
+    while unit + 1 < SIZE_UNITS.len() && bytes >= 1u64 << (10 * (unit + 1)) {
+        unit += 1;
+    }
+    if unit == 0 {
+        return format!("{} B", bytes);
+    }
+    let scale = 1u128 << (10 * unit);
+    let mut tenths = (u128::from(bytes) * 10 + scale / 2) / scale;
+    // Rounding may carry into the next unit (1023.96 KiB is 1.0 MiB)
+    if tenths >= 10_240 && unit + 1 < SIZE_UNITS.len() {
+        unit += 1;
+        tenths = (tenths + 512) / 1024;
+    }
+    format!("{}.{} {}", tenths / 10, tenths % 10, SIZE_UNITS[unit])
+}
+
+/// Returns the year, month and day of a count of days since 1970-01-01 in
+/// the proleptic Gregorian calendar.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    // Days are counted from 0000-03-01 so leap days fall at the end of a year
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Formats seconds since the Unix epoch as a UTC date and time.
+///
+/// Times before 1970 are negative. Leap seconds are not counted, as in Unix time.
+///
+/// # Arguments
+/// * `seconds` - Seconds since 1970-01-01 00:00:00 UTC, such as a torrent's `creation date`
+///
+/// # Returns
+/// The time as `YYYY-MM-DD HH:MM:SS UTC`
+///
+/// # Example
+/// ```
+/// use bencode_lib::human_date;
+///
+/// assert_eq!(human_date(0), "1970-01-01 00:00:00 UTC");
+/// assert_eq!(human_date(1_700_000_000), "2023-11-14 22:13:20 UTC");
+/// ```
+pub fn human_date(seconds: i64) -> String {
+    let (year, month, day) = civil_from_days(seconds.div_euclid(SECONDS_PER_DAY));
+    let time = seconds.rem_euclid(SECONDS_PER_DAY);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        year,
+        month,
+        day,
+        time / 3600,
+        time / 60 % 60,
+        time % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_use_binary_units() {
+        let sizes: Vec<String> = [0, 1, 1024, 10_240 + 51, 1_048_575, 1 << 30, u64::MAX].into_iter().map(human_size).collect();
+        assert_eq!(sizes, ["0 B", "1 B", "1.0 KiB", "10.0 KiB", "1.0 MiB", "1.0 GiB", "16.0 EiB"]);
+    }
+
+    #[test]
+    fn dates_are_utc_calendar_dates() {
+        assert_eq!(human_date(951_782_400), "2000-02-29 00:00:00 UTC");
+        assert_eq!(human_date(4_107_542_399), "2100-02-28 23:59:59 UTC");
+        assert_eq!(human_date(-1), "1969-12-31 23:59:59 UTC");
+        assert_eq!(human_date(-62_135_596_800), "0001-01-01 00:00:00 UTC");
+        // The extremes do not overflow
+        assert!(human_date(i64::MIN).ends_with(" UTC"));
+        assert!(human_date(i64::MAX).ends_with(" UTC"));
+    }
+}
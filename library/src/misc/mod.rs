@@ -5,23 +5,63 @@ pub fn get_version() -> &'static str {
     env!("CARGO_PKG_VERSION")
 }
 
+/// Locale-neutral formatting of sizes and dates
+mod human;
+pub use human::{human_date, human_size};
+
 // File I/O functions are only available with std feature
 #[cfg(feature = "std")]
 mod file_io {
-    use std::fs;
+    use std::fs::File;
+    use std::io::{Read, Write};
     use std::path::Path;
 
+    use crate::config::WriteOptions;
+    use crate::io::lock::{create_locked, lock_shared};
+
     /// Writes bencode string to a file
     ///
+    /// With the `fs2` feature the file is locked exclusively while it is
+    /// written, so concurrent writers and readers using these helpers never
+    /// see it half written.
+    ///
     /// # Arguments
     /// * `path` - The file path where the content will be written
     /// * `content` - The bencode string content to write to the file
     pub fn write_bencode_file(path: &str, content: &str) -> Result<(), std::io::Error> {
-        fs::write(Path::new(path), content)
+        write_bencode_file_with_options(path, content, &WriteOptions::new())
+    }
+
+    /// Writes bencode string to a file, backing up any file it replaces as
+    /// `options` asks; see [`write_bencode_file`].
+    ///
+    /// # Arguments
+    /// * `path` - The file path where the content will be written
+    /// * `content` - The bencode string content to write to the file
+    /// * `options` - The backup policy for an existing file
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{write_file_with_options, BackupPolicy, WriteOptions};
+    ///
+    /// let options = WriteOptions::new().with_backup(BackupPolicy::Single);
+    /// let path = std::env::temp_dir().join("doc_state.bencode");
+    /// let path = path.to_str().unwrap();
+    /// write_file_with_options(path, "i1e", &options).unwrap();
+    /// write_file_with_options(path, "i2e", &options).unwrap();
+    /// assert_eq!(std::fs::read(format!("{}.bak", path)).unwrap(), b"i1e");
+    /// std::fs::remove_file(path).unwrap();
+    /// std::fs::remove_file(format!("{}.bak", path)).unwrap();
+    /// ```
+    pub fn write_bencode_file_with_options(path: &str, content: &str, options: &WriteOptions) -> Result<(), std::io::Error> {
+        create_locked(Path::new(path), options)?.write_all(content.as_bytes())
     }
 
     /// Reads bencode string from a file
     ///
+    /// With the `fs2` feature a shared lock is held while reading, waiting for
+    /// a writer using [`write_bencode_file`] to finish.
+    ///
     /// # Arguments
     /// * `path` - The file path to read from
     ///
@@ -29,7 +69,11 @@ mod file_io {
     /// * `Ok(String)` - The content of the file as a string if successful
     /// * `Err(std::io::Error)` - The error if reading fails
     pub fn read_bencode_file(path: &str) -> Result<String, std::io::Error> {
-        fs::read_to_string(Path::new(path))
+        let mut file = File::open(Path::new(path))?;
+        lock_shared(&file)?;
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        Ok(content)
     }
 }
 
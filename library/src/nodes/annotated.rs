@@ -0,0 +1,195 @@
+//! Node tree carrying annotations alongside its values.
+//!
+//! An [`AnnotatedNode`] pairs a [`Node`] with notes keyed by the path of the
+//! value they describe: where the value was read from, comments added by a
+//! user and findings reported by a linter. Editors and linters can keep their
+//! notes with the tree instead of in a structure of their own. Paths use the
+//! same syntax as [`Node::query`].
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    collections::BTreeMap,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use core::ops::Range;
+
+use crate::error::parse::ParseError;
+use crate::nodes::node::Node;
+use crate::parser::default::parse_bytes;
+use crate::parser::extract::TokenPaths;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+
+/// A note attached to a value in an [`AnnotatedNode`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Annotation {
+    /// Byte span of the value's encoding in the input it was parsed from
+    Span(Range<usize>),
+    /// A note written by a user
+    Comment(String),
+    /// A problem reported by a linter or validator
+    Finding(String),
+}
+
+/// A node tree with annotations keyed by value path.
+///
+/// Annotations stay with their path when the tree is edited; call
+/// [`prune`](Self::prune) after removing values to drop the notes on them.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{AnnotatedNode, Annotation};
+///
+/// let mut document = AnnotatedNode::parse(b"d8:announce3:url4:infod4:name1:xee").unwrap();
+/// assert_eq!(document.span("info.name"), Some(29..32));
+///
+/// document.annotate("announce", Annotation::Comment("main tracker".to_string()));
+/// let notes: Vec<_> = document.iter().filter(|(_, note)| !matches!(note, Annotation::Span(_))).collect();
+/// assert_eq!(notes, [("announce", &Annotation::Comment("main tracker".to_string()))]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotatedNode {
+    node: Node,
+    annotations: BTreeMap<String, Vec<Annotation>>,
+}
+
+impl AnnotatedNode {
+    /// Wraps a tree with no annotations.
+    pub fn new(node: Node) -> Self {
+        Self {
+            node,
+            annotations: BTreeMap::new(),
+        }
+    }
+
+    /// Parses bencode data, annotating every value with its [`Annotation::Span`].
+    ///
+    /// # Arguments
+    /// * `source` - The bencode data
+    ///
+    /// # Returns
+    /// * `Ok(AnnotatedNode)` - The tree with the span of each value
+    /// * `Err(ParseError)` - If the input is malformed
+    pub fn parse(source: &[u8]) -> Result<Self, ParseError> {
+        let mut annotated = Self::new(parse_bytes(source)?);
+        let mut paths = TokenPaths::new();
+        // Path and start offset of each open container
+        let mut open: Vec<(String, usize)> = Vec::new();
+        for token in Tokenizer::new(source) {
+            let token = token?;
+            match (paths.next_path(&token.kind), token.kind) {
+                (Some(path), TokenKind::ListStart | TokenKind::DictStart) => open.push((path, token.offset)),
+                (Some(path), _) => annotated.annotate(&path, Annotation::Span(token.offset..token.end)),
+                (None, TokenKind::ListEnd | TokenKind::DictEnd) => {
+                    if let Some((path, start)) = open.pop() {
+                        annotated.annotate(&path, Annotation::Span(start..token.end));
+                    }
+                }
+                (None, _) => {}
+            }
+        }
+        Ok(annotated)
+    }
+
+    /// Returns the tree.
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    /// Returns the tree for modification; annotations are left as they are.
+    pub fn node_mut(&mut self) -> &mut Node {
+        &mut self.node
+    }
+
+    /// Returns the tree, dropping the annotations.
+    pub fn into_node(self) -> Node {
+        self.node
+    }
+
+    /// Attaches an annotation to the value at a path.
+    ///
+    /// The path need not exist yet, so notes can be added before the value is.
+    pub fn annotate(&mut self, path: &str, annotation: Annotation) {
+        self.annotations.entry(path.to_string()).or_default().push(annotation);
+    }
+
+    /// Returns the annotations on the value at a path, oldest first.
+    pub fn annotations(&self, path: &str) -> &[Annotation] {
+        self.annotations.get(path).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the span the value at a path was parsed from, if it was parsed.
+    pub fn span(&self, path: &str) -> Option<Range<usize>> {
+        self.annotations(path).iter().find_map(|annotation| match annotation {
+            Annotation::Span(span) => Some(span.clone()),
+            _ => None,
+        })
+    }
+
+    /// Removes and returns the annotations on the value at a path.
+    pub fn clear(&mut self, path: &str) -> Vec<Annotation> {
+        self.annotations.remove(path).unwrap_or_default()
+    }
+
+    /// Drops the annotations on paths no longer in the tree.
+    pub fn prune(&mut self) {
+        let node = &self.node;
+        self.annotations.retain(|path, _| node.query(path).is_some());
+    }
+
+    /// Returns every annotation with its path, in path order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Annotation)> {
+        self.annotations
+            .iter()
+            .flat_map(|(path, annotations)| annotations.iter().map(move |annotation| (path.as_str(), annotation)))
+    }
+}
+
+impl From<Node> for AnnotatedNode {
+    fn from(node: Node) -> Self {
+        Self::new(node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_records_the_span_of_every_value() {
+        let data = b"d1:ali1eli2eee1:bd1:c1:xee";
+        let document = AnnotatedNode::parse(data).unwrap();
+        let spans: Vec<_> = document.iter().map(|(path, annotation)| (path, annotation.clone())).collect();
+        assert_eq!(spans.len(), 7);
+        for (path, span) in [("", 0..26), ("a", 4..14), ("a.0", 5..8), ("a.1", 8..13), ("a.1.0", 9..12), ("b", 17..25), ("b.c", 21..24)] {
+            assert_eq!(document.span(path), Some(span.clone()), "{}", path);
+            assert_eq!(parse_bytes(&data[span]).ok().as_ref(), document.node().query(path), "{}", path);
+        }
+        assert_eq!(AnnotatedNode::parse(b"4:spam").unwrap().span(""), Some(0..6));
+        assert!(AnnotatedNode::parse(b"li1e").is_err());
+    }
+
+    #[test]
+    fn annotations_follow_paths_until_pruned() {
+        let mut document = AnnotatedNode::parse(b"d1:ai1e1:bi2ee").unwrap();
+        document.annotate("a", Annotation::Finding("odd value".to_string()));
+        document.annotate("a", Annotation::Comment("checked".to_string()));
+        document.annotate("c", Annotation::Comment("to add".to_string()));
+        assert_eq!(
+            document.annotations("a")[1..],
+            [Annotation::Finding("odd value".to_string()), Annotation::Comment("checked".to_string())]
+        );
+        assert_eq!(document.annotations("missing"), []);
+
+        document.node_mut().extract("a");
+        document.prune();
+        assert!(document.annotations("a").is_empty());
+        assert!(document.annotations("c").is_empty());
+        assert_eq!(document.span("b"), Some(10..13));
+        assert_eq!(document.clear("b"), [Annotation::Span(10..13)]);
+        assert_eq!(document.into_node(), Node::from([("b", Node::from(2))]));
+    }
+}
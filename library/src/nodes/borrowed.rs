@@ -1,13 +1,12 @@
+use crate::HashMap;
+use crate::nodes::integer::Integer;
 use crate::nodes::node::Node;
+use crate::nodes::string_kind::StringKind;
+use crate::parser::extract::{split_path, value_span};
 /// Borrowed/zero-copy node implementation for embedded systems.
 /// This module provides a Node variant that holds references to the input buffer
 /// instead of allocating and copying data, reducing memory usage.
 
-#[cfg(not(feature = "std"))]
-use alloc::collections::BTreeMap as HashMap;
-#[cfg(feature = "std")]
-use std::collections::HashMap;
-
 #[cfg(not(feature = "std"))]
 use alloc::vec::Vec;
 
@@ -19,8 +18,8 @@ use core::fmt;
 /// The lifetime parameter 'a represents the lifetime of the borrowed input data.
 #[derive(Clone, Debug, PartialEq)]
 pub enum BorrowedNode<'a> {
-    /// Represents a 64-bit signed integer value
-    Integer(i64),
+    /// Represents a signed integer value (64-bit, or 32-bit with the `int32` feature)
+    Integer(Integer),
     /// Represents a string value as a borrowed byte slice
     /// Note: bencode strings are byte strings, not necessarily UTF-8
     Bytes(&'a [u8]),
@@ -31,22 +30,27 @@ pub enum BorrowedNode<'a> {
 }
 
 impl<'a> BorrowedNode<'a> {
-    /// Recursively convert a BorrowedNode to an owned Node
+    /// Recursively convert a BorrowedNode to an owned Node, which no longer
+    /// borrows the input; see [`materialize`](Self::materialize) to copy only part of it
     pub fn to_node(&self) -> Node {
         match self {
             BorrowedNode::Integer(i) => Node::Integer(*i),
-            BorrowedNode::Bytes(b) => Node::Str(String::from_utf8_lossy(b).into_owned()),
+            BorrowedNode::Bytes(b) => Node::from_bytes(b.to_vec()),
             BorrowedNode::List(list) => {
                 Node::List(list.iter().map(|item| item.to_node()).collect())
             }
-            BorrowedNode::Dictionary(dict) => Node::Dictionary(
-                dict.iter()
-                    .map(|(k, v)| {
-                        let key = String::from_utf8_lossy(k).into_owned();
-                        (key, v.to_node())
-                    })
-                    .collect(),
-            ),
+            BorrowedNode::Dictionary(dict) => {
+                // Insert in key order so keys that decode to the same lossy string
+                // resolve the same way as in the owning parsers (last key wins).
+                let mut entries: Vec<_> = dict.iter().collect();
+                entries.sort_by_key(|(k, _)| **k);
+                Node::Dictionary(
+                    entries
+                        .into_iter()
+                        .map(|(k, v)| (String::from_utf8_lossy(k).into_owned(), v.to_node()))
+                        .collect(),
+                )
+            }
         }
     }
     /// Returns true if the node is an Integer variant
@@ -70,7 +74,7 @@ impl<'a> BorrowedNode<'a> {
     }
 
     /// Returns the integer value if this is an Integer node
-    pub fn as_integer(&self) -> Option<i64> {
+    pub fn as_integer(&self) -> Option<Integer> {
         match self {
             BorrowedNode::Integer(i) => Some(*i),
             _ => None,
@@ -85,6 +89,11 @@ impl<'a> BorrowedNode<'a> {
         }
     }
 
+    /// Classifies the bytes as ASCII, UTF-8 or binary if this is a Bytes node
+    pub fn string_kind(&self) -> Option<StringKind> {
+        self.as_bytes().map(StringKind::of_bytes)
+    }
+
     /// Returns the list reference if this is a List node
     pub fn as_list(&self) -> Option<&Vec<BorrowedNode<'a>>> {
         match self {
@@ -100,6 +109,71 @@ impl<'a> BorrowedNode<'a> {
             _ => None,
         }
     }
+
+    /// Looks up the value at a key path.
+    ///
+    /// Paths use the same syntax as [`Node::query`]: dot separated dictionary
+    /// keys with numeric list indexes, and the empty path for the node itself.
+    ///
+    /// # Returns
+    /// The value, or None if the path does not exist
+    pub fn query(&self, path: &str) -> Option<&BorrowedNode<'a>> {
+        split_path(path).into_iter().try_fold(self, |node, segment| match node {
+            BorrowedNode::Dictionary(dict) => dict.get(segment.as_bytes()),
+            BorrowedNode::List(list) => list.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    /// Copies the value at a key path into an owned Node, leaving the rest of
+    /// the tree uncopied.
+    ///
+    /// Lets code parse a large buffer zero-copy and keep only the fields it
+    /// needs once the buffer is gone.
+    ///
+    /// # Returns
+    /// The owned subtree, or None if the path does not exist
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{Node, parse_borrowed};
+    ///
+    /// let name = {
+    ///     let data = b"d4:infod4:name4:demo6:pieces3:\x01\x02\x03ee".to_vec();
+    ///     parse_borrowed(&data).unwrap().materialize("info.name")
+    /// };
+    /// assert_eq!(name, Some(Node::from("demo")));
+    /// ```
+    pub fn materialize(&self, path: &str) -> Option<Node> {
+        self.query(path).map(BorrowedNode::to_node)
+    }
+
+    /// Returns the raw encoded bytes of the value at a key path in `source`.
+    ///
+    /// Borrowed nodes do not record their position, so this works on the input
+    /// itself; only the values before the target are scanned. Paths are dot
+    /// separated keys with numeric list indexes (e.g. `"info"`, `"info.files.0"`).
+    ///
+    /// # Returns
+    /// The encoded value, or None if the path does not exist or the input is
+    /// malformed before reaching it
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::BorrowedNode;
+    ///
+    /// let data = b"d8:announce3:url4:infod4:name1:xee";
+    /// assert_eq!(BorrowedNode::slice(data, "info"), Some(&b"d4:name1:xe"[..]));
+    /// ```
+    pub fn slice<'s>(source: &'s [u8], path: &str) -> Option<&'s [u8]> {
+        value_span(source, path).ok().flatten().map(|span| &source[span])
+    }
+}
+
+impl From<&BorrowedNode<'_>> for Node {
+    fn from(node: &BorrowedNode<'_>) -> Self {
+        node.to_node()
+    }
 }
 
 impl<'a> fmt::Display for BorrowedNode<'a> {
@@ -188,14 +262,8 @@ mod tests {
         let b = b"3:\xFF\x00\xFE";
         let n = parse_borrowed(b).unwrap();
         assert_eq!(n.as_bytes(), Some(&b"\xFF\x00\xFE"[..]));
-        let node = n.to_node();
-        match node {
-            Node::Str(ref s) => {
-                // Should not panic, but will contain replacement chars
-                assert!(s.contains("\u{FFFD}") || s.contains("\x00") || s.contains("\u{FFFD}"));
-            }
-            _ => panic!("Expected Node::Str"),
-        }
+        // Binary strings are kept exactly rather than decoded lossily
+        assert_eq!(n.to_node(), Node::Bytes(b"\xFF\x00\xFE".to_vec()));
     }
     #[test]
     fn round_trip_parse_borrowed_to_node() {
@@ -261,6 +329,21 @@ mod tests {
         assert_eq!(bytes_node.as_integer(), None);
     }
 
+    #[test]
+    fn materialize_copies_only_the_requested_subtree() {
+        use crate::nodes::node::Node;
+        use crate::parser::borrowed::parse_borrowed;
+        let data = b"d4:infod5:filesld6:lengthi5e4:pathl1:aeeee4:name1:xe";
+        let node = parse_borrowed(data).unwrap();
+        assert_eq!(node.materialize("info.files.0.length"), Some(Node::Integer(5)));
+        assert_eq!(node.materialize("info.files.0.path"), Some(Node::List(vec![Node::from("a")])));
+        assert_eq!(node.materialize(""), Some(node.to_node()));
+        assert_eq!(node.materialize("info.files.1"), None);
+        assert_eq!(node.materialize("name.0"), None);
+        assert_eq!(node.query("name"), Some(&BorrowedNode::Bytes(b"x")));
+        assert_eq!(Node::from(&node), node.to_node());
+    }
+
     #[test]
     fn borrowed_node_display() {
         let int_node = BorrowedNode::Integer(42);
@@ -296,7 +379,7 @@ mod tests {
         }
 
         // Dictionary
-        let mut dict = std::collections::HashMap::new();
+        let mut dict = crate::HashMap::new();
         dict.insert(b"foo".as_ref(), BorrowedNode::Integer(7));
         dict.insert(b"bar".as_ref(), BorrowedNode::Bytes(b"baz"));
         let b_dict = BorrowedNode::Dictionary(dict);
@@ -313,7 +396,7 @@ mod tests {
     #[test]
     fn to_node_nested_structures() {
         use crate::nodes::node::Node;
-        let mut inner_dict = std::collections::HashMap::new();
+        let mut inner_dict = crate::HashMap::new();
         inner_dict.insert(b"k".as_ref(), BorrowedNode::Bytes(b"v"));
         let b = BorrowedNode::List(vec![
             BorrowedNode::Dictionary(inner_dict),
@@ -334,4 +417,26 @@ mod tests {
             _ => panic!("Expected Node::List"),
         }
     }
+
+    #[test]
+    fn slice_returns_raw_span() {
+        use super::BorrowedNode;
+        let data = b"d4:infod5:filesli1ei2ee4:name1:xe3:numi7ee";
+        assert_eq!(BorrowedNode::slice(data, "info.files"), Some(&b"li1ei2ee"[..]));
+        assert_eq!(BorrowedNode::slice(data, "info.files.1"), Some(&b"i2e"[..]));
+        assert_eq!(BorrowedNode::slice(data, "info.missing"), None);
+        assert_eq!(BorrowedNode::slice(b"d1:ai", "b"), None);
+    }
+
+    #[test]
+    fn string_kind_classifies_raw_bytes() {
+        use crate::nodes::string_kind::StringKind;
+        use crate::parser::borrowed::parse_borrowed;
+        let node = parse_borrowed(b"l1:a2:\xc3\xa91:\xffi1ee").unwrap();
+        let kinds: Vec<_> = node.as_list().unwrap().iter().map(|n| n.string_kind()).collect();
+        assert_eq!(
+            kinds,
+            vec![Some(StringKind::Ascii), Some(StringKind::Utf8), Some(StringKind::Binary), None]
+        );
+    }
 }
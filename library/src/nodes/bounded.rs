@@ -0,0 +1,274 @@
+//! Node tree with compile-time bounds on its size and nesting.
+//!
+//! A [`BoundedNode`] holds a parsed tree in a fixed array of `MAX_NODES` slots
+//! and parses without recursion using a fixed stack of `MAX_DEPTH` frames, so
+//! both its own size and the stack its parser uses are known at compile time.
+//! Input that needs more nodes or deeper nesting is rejected with
+//! [`ErrorKind::TooManyNodes`](crate::ErrorKind::TooManyNodes) or
+//! [`ErrorKind::TooDeep`](crate::ErrorKind::TooDeep) at the point the bound is
+//! crossed, whatever the rest of the input holds. Strings are borrowed from the
+//! input.
+
+#[cfg(not(feature = "std"))]
+use alloc::string::{String, ToString};
+
+use crate::error::messages::*;
+use crate::memory::scratch::{ScratchRef, Slot};
+use crate::nodes::borrowed::BorrowedNode;
+use crate::nodes::node::Node;
+use crate::parser::borrowed::{parse_bytes, parse_integer};
+
+/// An open list or dictionary during parsing
+#[derive(Clone, Copy)]
+struct Frame {
+    /// Slot of the container
+    header: usize,
+    /// Items read so far (entries for a dictionary)
+    len: usize,
+    dictionary: bool,
+    /// A dictionary key has been read and its value has not
+    awaiting_value: bool,
+    /// Span of the previous dictionary key, for the order check
+    last_key: Option<(usize, usize)>,
+}
+
+const EMPTY_FRAME: Frame = Frame {
+    header: 0,
+    len: 0,
+    dictionary: false,
+    awaiting_value: false,
+    last_key: None,
+};
+
+/// A parsed tree of at most `MAX_NODES` nodes (dictionary keys count as
+/// nodes) nested at most `MAX_DEPTH` containers deep.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{BoundedNode, ErrorKind};
+///
+/// type Message<'a> = BoundedNode<'a, 16, 2>;
+/// // Worst case memory is a compile-time constant
+/// const _: () = assert!(Message::SIZE < 1024);
+///
+/// let tree = Message::parse(b"d8:intervali1800e5:peersl2:p1ee").unwrap();
+/// assert_eq!(tree.root().get("interval").and_then(|n| n.as_integer()), Some(1800));
+///
+/// let error = Message::parse(b"llli1eee").unwrap_err();
+/// assert_eq!(ErrorKind::from_message(&error), ErrorKind::TooDeep);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BoundedNode<'a, const MAX_NODES: usize, const MAX_DEPTH: usize> {
+    input: &'a [u8],
+    slots: [Slot; MAX_NODES],
+    len: usize,
+}
+
+impl<'a, const MAX_NODES: usize, const MAX_DEPTH: usize> BoundedNode<'a, MAX_NODES, MAX_DEPTH> {
+    /// Size in bytes of a tree of this type, whatever it holds
+    pub const SIZE: usize = core::mem::size_of::<Self>();
+
+    /// Stack bytes [`parse`](Self::parse) uses for open containers
+    pub const PARSE_STACK_BYTES: usize = MAX_DEPTH * core::mem::size_of::<Frame>();
+
+    /// Parses bencode data into a bounded tree.
+    ///
+    /// Malformed input is reported with the same errors as
+    /// [`parse_borrowed`](crate::parse_borrowed).
+    ///
+    /// # Arguments
+    /// * `input` - The bencode data
+    ///
+    /// # Returns
+    /// * `Ok(BoundedNode)` - The parsed tree
+    /// * `Err(String)` - If the input is malformed, has more than `MAX_NODES`
+    ///   nodes or nests more than `MAX_DEPTH` containers
+    pub fn parse(input: &'a [u8]) -> Result<Self, String> {
+        let mut tree = Self {
+            input,
+            slots: [Slot::Integer(0); MAX_NODES],
+            len: 0,
+        };
+        let mut frames = [EMPTY_FRAME; MAX_DEPTH];
+        let mut depth: usize = 0;
+        let mut position = 0;
+        loop {
+            if let Some(frame) = depth.checked_sub(1).map(|top| &mut frames[top]) {
+                match input.get(position) {
+                    _ if frame.awaiting_value => {
+                        frame.len += 1;
+                        frame.awaiting_value = false;
+                    }
+                    None if frame.dictionary => return Err(ERR_UNTERMINATED_DICTIONARY.to_string()),
+                    None => return Err(ERR_UNTERMINATED_LIST.to_string()),
+                    Some(b'e') => {
+                        position += 1;
+                        tree.slots[frame.header] = if frame.dictionary {
+                            Slot::Dictionary { len: frame.len, next: tree.len }
+                        } else {
+                            Slot::List { len: frame.len, next: tree.len }
+                        };
+                        depth -= 1;
+                        if depth == 0 {
+                            break;
+                        }
+                        continue;
+                    }
+                    Some(&c) if frame.dictionary => {
+                        if !c.is_ascii_digit() {
+                            return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string());
+                        }
+                        let (start, end) = tree.push_bytes(&mut position)?;
+                        if let Some((last_start, last_end)) = frame.last_key
+                            && input[start..end] <= input[last_start..last_end]
+                        {
+                            return Err(ERR_DICT_KEYS_ORDER.to_string());
+                        }
+                        frame.last_key = Some((start, end));
+                        frame.awaiting_value = true;
+                        continue;
+                    }
+                    Some(_) => frame.len += 1,
+                }
+            }
+            let expecting = match depth.checked_sub(1).map(|top| frames[top].dictionary) {
+                None => Expecting::Value,
+                Some(true) => Expecting::DictValue,
+                Some(false) => Expecting::ListItem,
+            };
+            match input.get(position) {
+                Some(b'i') => {
+                    if let BorrowedNode::Integer(value) = parse_integer(input, &mut position)? {
+                        tree.push(Slot::Integer(value))?;
+                    }
+                }
+                Some(b'0'..=b'9') => {
+                    tree.push_bytes(&mut position)?;
+                }
+                Some(&c @ (b'l' | b'd')) => {
+                    if depth == MAX_DEPTH {
+                        return Err(ERR_TOO_DEEP.to_string());
+                    }
+                    frames[depth] = Frame {
+                        header: tree.len,
+                        dictionary: c == b'd',
+                        ..EMPTY_FRAME
+                    };
+                    tree.push(Slot::List { len: 0, next: 0 })?;
+                    position += 1;
+                    depth += 1;
+                    continue;
+                }
+                Some(&c) => return Err(unexpected_character(c as char, expecting, depth)),
+                None => return Err(ERR_EMPTY_INPUT.to_string()),
+            }
+            if depth == 0 {
+                break;
+            }
+        }
+        Ok(tree)
+    }
+
+    /// Appends a slot, failing once the tree is full
+    fn push(&mut self, slot: Slot) -> Result<(), String> {
+        let free = self.slots.get_mut(self.len).ok_or_else(|| ERR_TOO_MANY_NODES.to_string())?;
+        *free = slot;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Appends the byte string at `position`, returning its span in the input
+    fn push_bytes(&mut self, position: &mut usize) -> Result<(usize, usize), String> {
+        let length = parse_bytes(self.input, position)?.as_bytes().map_or(0, <[u8]>::len);
+        let span = (*position - length, *position);
+        self.push(Slot::Bytes { start: span.0, end: span.1 })?;
+        Ok(span)
+    }
+
+    /// Returns a view of the root node
+    pub fn root(&self) -> ScratchRef<'_> {
+        ScratchRef::new(self.input, &self.slots[..self.len])
+    }
+
+    /// Returns the number of slots used (dictionary keys count as nodes)
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no slots are used (never the case for a parsed tree)
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Rebuilds the tree as an owned [`Node`]
+    pub fn to_node(&self) -> Node {
+        self.root().to_node()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+    use crate::parse_borrowed;
+
+    type Small<'a> = BoundedNode<'a, 8, 2>;
+
+    #[test]
+    fn trees_within_bounds_match_parse_borrowed() {
+        for data in [
+            &b"i-42e"[..],
+            b"4:spam",
+            b"le",
+            b"de",
+            b"li1e1:ae",
+            b"d1:ali1ei2ee1:bdee",
+            b"lli1eeli2eee",
+        ] {
+            let tree = Small::parse(data).unwrap();
+            assert_eq!(tree.root().to_borrowed(), parse_borrowed(data).unwrap(), "{:?}", data);
+        }
+        assert_eq!(Small::parse(b"d1:ali1ei2ee1:bdee").unwrap().len(), 7);
+    }
+
+    #[test]
+    fn bounds_are_enforced_where_they_are_crossed() {
+        let kind = |result: Result<Small, String>| ErrorKind::from_message(&result.unwrap_err());
+        assert_eq!(kind(Small::parse(b"li1ei2ei3ei4ei5ei6ei7ei8ee")), ErrorKind::TooManyNodes);
+        assert_eq!(kind(Small::parse(b"llleee")), ErrorKind::TooDeep);
+        // The bound is reported before later malformed input is reached
+        assert_eq!(kind(Small::parse(b"lllxxx")), ErrorKind::TooDeep);
+        assert_eq!(
+            ErrorKind::from_message(&BoundedNode::<1, 0>::parse(b"le").unwrap_err()),
+            ErrorKind::TooDeep
+        );
+        assert!(BoundedNode::<1, 0>::parse(b"i1e").is_ok());
+    }
+
+    #[test]
+    fn errors_match_parse_borrowed() {
+        for data in [
+            &b""[..],
+            b"i1",
+            b"i-0e",
+            b"5:abc",
+            b"li1e",
+            b"d1:bi1e1:ai2ee",
+            b"di1ei2ee",
+            b"d1:ai1e",
+            b"d1:ae",
+            b"x",
+            b"lx",
+            b"d1:a",
+        ] {
+            let expected = parse_borrowed(data).unwrap_err().message;
+            assert_eq!(Small::parse(data).unwrap_err(), expected, "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn size_is_fixed_by_the_bounds() {
+        const { assert!(BoundedNode::<64, 4>::SIZE > BoundedNode::<8, 4>::SIZE) };
+        assert_eq!(BoundedNode::<8, 4>::PARSE_STACK_BYTES, 2 * BoundedNode::<8, 2>::PARSE_STACK_BYTES);
+    }
+}
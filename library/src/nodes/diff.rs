@@ -0,0 +1,183 @@
+//! Structural comparison of two node trees.
+//!
+//! [`diff`] lists where two documents differ as added, removed and changed
+//! values, each with its key path in the syntax of [`Node::query`] and the
+//! values on both sides, for showing a reader what changed between versions
+//! of a torrent or why an encoder's output no longer matches. Unlike
+//! [`diff_to_patch`](crate::diff_to_patch), the entries are meant to be read
+//! rather than replayed: removed values are kept and entries are in document
+//! order.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use core::fmt;
+
+use crate::nodes::node::Node;
+use crate::nodes::patch::{addressable, child_path};
+
+/// One difference between two documents.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DiffEntry {
+    /// A key or list element only in the new document
+    Added { path: String, value: Node },
+    /// A key or list element only in the old document
+    Removed { path: String, value: Node },
+    /// A value that differs between the documents
+    Changed { path: String, old: Node, new: Node },
+}
+
+impl DiffEntry {
+    /// Returns the key path of the value that differs
+    pub fn path(&self) -> &str {
+        match self {
+            DiffEntry::Added { path, .. } | DiffEntry::Removed { path, .. } | DiffEntry::Changed { path, .. } => path,
+        }
+    }
+}
+
+/// Formats the entry as one line: `+ path: value`, `- path: value` or `~ path: old -> new`;
+/// the root is shown as `(root)`.
+impl fmt::Display for DiffEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = match self.path() {
+            "" => "(root)",
+            path => path,
+        };
+        match self {
+            DiffEntry::Added { value, .. } => write!(f, "+ {}: {}", path, value),
+            DiffEntry::Removed { value, .. } => write!(f, "- {}: {}", path, value),
+            DiffEntry::Changed { old, new, .. } => write!(f, "~ {}: {} -> {}", path, old, new),
+        }
+    }
+}
+
+/// Appends the differences between `old` and `new`, both found at `path`
+fn diff_into(old: &Node, new: &Node, path: &str, entries: &mut Vec<DiffEntry>) {
+    match (old, new) {
+        (Node::Dictionary(old_dict), Node::Dictionary(new_dict))
+            if old_dict.keys().chain(new_dict.keys()).all(|key| addressable(path, key)) =>
+        {
+            let mut keys: Vec<&String> = old_dict.keys().chain(new_dict.keys().filter(|key| !old_dict.contains_key(*key))).collect();
+            keys.sort();
+            for key in keys {
+                let path = child_path(path, key);
+                match (old_dict.get(key), new_dict.get(key)) {
+                    (Some(previous), Some(value)) => diff_into(previous, value, &path, entries),
+                    (Some(value), None) => entries.push(DiffEntry::Removed { path, value: value.clone() }),
+                    (None, Some(value)) => entries.push(DiffEntry::Added { path, value: value.clone() }),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Node::List(old_list), Node::List(new_list)) => {
+            for index in 0..old_list.len().max(new_list.len()) {
+                let path = child_path(path, &index.to_string());
+                match (old_list.get(index), new_list.get(index)) {
+                    (Some(previous), Some(value)) => diff_into(previous, value, &path, entries),
+                    (Some(value), None) => entries.push(DiffEntry::Removed { path, value: value.clone() }),
+                    (None, Some(value)) => entries.push(DiffEntry::Added { path, value: value.clone() }),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if old != new => entries.push(DiffEntry::Changed {
+            path: path.to_string(),
+            old: old.clone(),
+            new: new.clone(),
+        }),
+        _ => {}
+    }
+}
+
+/// Lists the differences between two documents.
+///
+/// Dictionaries are compared key by key and lists index by index, so a value
+/// changed deep inside a tree yields a single entry, and an element inserted
+/// in the middle of a list shows up as changes to every later element. A
+/// dictionary with a key holding the path separator is reported changed as a
+/// whole, as no path can name its entries.
+///
+/// # Arguments
+/// * `old` - The earlier document
+/// * `new` - The later document
+///
+/// # Returns
+/// The differences in document order, with dictionary keys sorted; empty when
+/// the documents are equal
+///
+/// # Example
+/// ```
+/// use bencode_lib::{diff, format_diff, parse_bytes};
+///
+/// let old = parse_bytes(b"d8:announce5:url-14:infod4:name1:xe4:tagsl1:aee").unwrap();
+/// let new = parse_bytes(b"d8:announce5:url-24:infod4:name1:xe4:tagsl1:a1:bee").unwrap();
+/// assert_eq!(format_diff(&diff(&old, &new)), "~ announce: \"url-1\" -> \"url-2\"\n+ tags.1: \"b\"\n");
+/// ```
+pub fn diff(old: &Node, new: &Node) -> Vec<DiffEntry> {
+    let mut entries = Vec::new();
+    diff_into(old, new, "", &mut entries);
+    entries
+}
+
+/// Formats differences one per line, as [`DiffEntry`]'s `Display` does.
+pub fn format_diff(entries: &[DiffEntry]) -> String {
+    entries.iter().map(|entry| format!("{}\n", entry)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> Node {
+        Node::from([
+            ("announce", Node::from("url")),
+            ("info", Node::from([("files", Node::from([1, 2, 3])), ("name", Node::from("x"))])),
+        ])
+    }
+
+    #[test]
+    fn equal_documents_have_no_differences() {
+        assert!(diff(&document(), &document()).is_empty());
+        assert_eq!(format_diff(&[]), "");
+    }
+
+    #[test]
+    fn differences_are_reported_with_paths_in_document_order() {
+        let mut new = document();
+        new.extract("announce");
+        *new.query_mut("info.name").unwrap() = Node::from("y");
+        new.query_mut("info.files").unwrap().as_list_mut().unwrap().truncate(1);
+        new.insert_path("private", 1).unwrap();
+
+        assert_eq!(
+            diff(&document(), &new),
+            vec![
+                DiffEntry::Removed { path: "announce".to_string(), value: Node::from("url") },
+                DiffEntry::Removed { path: "info.files.1".to_string(), value: Node::from(2) },
+                DiffEntry::Removed { path: "info.files.2".to_string(), value: Node::from(3) },
+                DiffEntry::Changed { path: "info.name".to_string(), old: Node::from("x"), new: Node::from("y") },
+                DiffEntry::Added { path: "private".to_string(), value: Node::from(1) },
+            ]
+        );
+    }
+
+    #[test]
+    fn values_of_different_types_are_changed_whole() {
+        let entries = diff(&document(), &Node::from(1));
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path(), "");
+        assert_eq!(format_diff(&diff(&Node::from(1), &Node::from("1"))), "~ (root): 1 -> \"1\"\n");
+    }
+
+    #[test]
+    fn unaddressable_keys_change_their_dictionary() {
+        let old = Node::from([("a.b", Node::from(1))]);
+        let new = Node::from([("a.b", Node::from(2))]);
+        assert_eq!(diff(&old, &new), vec![DiffEntry::Changed { path: String::new(), old, new }]);
+    }
+}
@@ -0,0 +1,193 @@
+//! Parsed documents that remember the bytes they were parsed from.
+//!
+//! A [`Document`] keeps its input behind an `Arc` alongside the parsed tree.
+//! Until the tree is borrowed mutably, encoding the document hands back the
+//! original bytes rather than walking the tree, so a proxy that reads, inspects
+//! and forwards documents pays only for the parse. The fast path is taken only
+//! when the input is the canonical encoding of its value, as re-encoding
+//! canonical input gives the same bytes; other input is re-encoded.
+
+#[cfg(not(feature = "std"))]
+use alloc::{borrow::Cow, sync::Arc};
+#[cfg(feature = "std")]
+use std::{borrow::Cow, sync::Arc};
+
+use crate::config::ParserConfig;
+use crate::error::parse::ParseError;
+use crate::nodes::node::Node;
+use crate::parser::canonical::validate_canonical;
+use crate::parser::default::{parse_bytes, parse_bytes_with_config};
+use crate::stringify::default::stringify_to_bytes;
+
+/// A parsed tree and the input it was parsed from.
+///
+/// # Example
+/// ```
+/// use bencode_lib::Document;
+///
+/// let mut document = Document::parse(b"d8:announce9:udp://old4:infod4:name4:demoee".to_vec()).unwrap();
+/// assert_eq!(document.node().get("info").unwrap().get("name").unwrap().as_string(), Some("demo"));
+/// // Unchanged, so the input itself is returned
+/// assert!(matches!(document.as_bytes().unwrap(), std::borrow::Cow::Borrowed(_)));
+///
+/// document.node_mut().insert_path("announce", "udp://new").unwrap();
+/// assert!(document.is_modified());
+/// assert_eq!(document.as_bytes().unwrap().as_ref(), b"d8:announce9:udp://new4:infod4:name4:demoee");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Document {
+    /// The bytes the document was parsed from
+    source: Arc<[u8]>,
+    /// The parsed tree
+    node: Node,
+    /// True if `source` is the canonical encoding of `node` as parsed
+    canonical: bool,
+    /// True once the tree has been borrowed mutably
+    modified: bool,
+}
+
+impl Document {
+    /// Parses a document, keeping its input.
+    ///
+    /// # Arguments
+    /// * `data` - The bencode data; a `Vec<u8>` or `Arc<[u8]>` is kept without copying
+    ///
+    /// # Returns
+    /// * `Ok(Document)` - The parsed document
+    /// * `Err(ParseError)` - The input is not valid bencode
+    pub fn parse(data: impl Into<Arc<[u8]>>) -> Result<Document, ParseError> {
+        let source = data.into();
+        let node = parse_bytes(&source)?;
+        Ok(Self::new(source, node))
+    }
+
+    /// Parses a document within the limits of a [`ParserConfig`], keeping its input.
+    ///
+    /// # Arguments
+    /// * `data` - The bencode data; a `Vec<u8>` or `Arc<[u8]>` is kept without copying
+    /// * `config` - The limits on depth, string length, value count and input size
+    ///
+    /// # Returns
+    /// * `Ok(Document)` - The parsed document
+    /// * `Err(ParseError)` - The input is not valid bencode or breaks a limit
+    pub fn parse_with_config(data: impl Into<Arc<[u8]>>, config: &ParserConfig) -> Result<Document, ParseError> {
+        let source = data.into();
+        let node = parse_bytes_with_config(&source, config)?;
+        Ok(Self::new(source, node))
+    }
+
+    /// Creates a document from its input and the tree parsed from it
+    fn new(source: Arc<[u8]>, node: Node) -> Document {
+        let canonical = validate_canonical(&source).is_ok();
+        Document {
+            source,
+            node,
+            canonical,
+            modified: false,
+        }
+    }
+
+    /// Returns the parsed tree
+    pub fn node(&self) -> &Node {
+        &self.node
+    }
+
+    /// Returns the parsed tree for editing. The document counts as modified from
+    /// then on, even if the tree is left unchanged.
+    pub fn node_mut(&mut self) -> &mut Node {
+        self.modified = true;
+        &mut self.node
+    }
+
+    /// Returns the parsed tree, dropping the input
+    pub fn into_node(self) -> Node {
+        self.node
+    }
+
+    /// Returns the bytes the document was parsed from
+    pub fn source(&self) -> &Arc<[u8]> {
+        &self.source
+    }
+
+    /// Returns true if the tree has been borrowed mutably
+    pub fn is_modified(&self) -> bool {
+        self.modified
+    }
+
+    /// Returns true if encoding the document returns its input without re-encoding
+    pub fn is_unchanged(&self) -> bool {
+        self.canonical && !self.modified
+    }
+
+    /// Encodes the document.
+    ///
+    /// # Returns
+    /// * `Ok(Cow::Borrowed)` - The input, if the document [is unchanged](Document::is_unchanged)
+    /// * `Ok(Cow::Owned)` - The tree re-encoded
+    /// * `Err(ParseError)` - The edited tree cannot be encoded
+    pub fn as_bytes(&self) -> Result<Cow<'_, [u8]>, ParseError> {
+        if self.is_unchanged() {
+            return Ok(Cow::Borrowed(&self.source));
+        }
+        stringify_to_bytes(&self.node).map(Cow::Owned)
+    }
+
+    /// Encodes the document into a shared buffer; an unchanged document returns
+    /// a clone of its input's `Arc` without copying.
+    ///
+    /// # Returns
+    /// * `Ok(Arc<[u8]>)` - The encoded document
+    /// * `Err(ParseError)` - The edited tree cannot be encoded
+    pub fn to_shared_bytes(&self) -> Result<Arc<[u8]>, ParseError> {
+        if self.is_unchanged() {
+            return Ok(Arc::clone(&self.source));
+        }
+        stringify_to_bytes(&self.node).map(Arc::from)
+    }
+}
+
+impl PartialEq for Document {
+    /// Documents are equal if their trees are, whatever input they came from
+    fn eq(&self, other: &Self) -> bool {
+        self.node == other.node
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::make_node;
+
+    #[test]
+    fn unchanged_canonical_documents_return_their_input() {
+        let input: Arc<[u8]> = Arc::from(&b"d1:ai1e1:bl1:xee"[..]);
+        let document = Document::parse(Arc::clone(&input)).unwrap();
+        assert!(document.is_unchanged());
+        assert!(matches!(document.as_bytes().unwrap(), Cow::Borrowed(bytes) if bytes.as_ptr() == input.as_ptr()));
+        assert!(Arc::ptr_eq(&document.to_shared_bytes().unwrap(), &input));
+        assert_eq!(document.node(), &make_node([("a", make_node(1)), ("b", make_node(vec![make_node("x")]))]));
+    }
+
+    #[test]
+    fn edited_documents_are_re_encoded() {
+        let mut document = Document::parse(b"d1:ai1ee".to_vec()).unwrap();
+        let _ = document.node_mut();
+        assert!(document.is_modified() && !document.is_unchanged());
+        assert!(matches!(document.as_bytes().unwrap(), Cow::Owned(_)));
+
+        document.node_mut().insert_path("a", 2).unwrap();
+        assert_eq!(document.as_bytes().unwrap().as_ref(), b"d1:ai2ee");
+        assert_eq!(document.to_shared_bytes().unwrap().as_ref(), b"d1:ai2ee");
+        assert_eq!(document.source().as_ref(), b"d1:ai1ee");
+    }
+
+    #[test]
+    fn non_canonical_input_is_re_encoded() {
+        let document = Document::parse(b"i1ejunk".to_vec()).unwrap();
+        assert!(!document.is_modified() && !document.is_unchanged());
+        assert_eq!(document.as_bytes().unwrap().as_ref(), b"i1e");
+        assert!(Document::parse(b"i1".to_vec()).is_err());
+        let config = ParserConfig::new().with_max_depth(1);
+        assert!(Document::parse_with_config(b"lli1eee".to_vec(), &config).is_err());
+    }
+}
@@ -0,0 +1,93 @@
+//! Stable content hash of a node tree.
+//!
+//! A fingerprint is the SHA-256 of a node's canonical encoding, so it depends
+//! only on the values held: dictionary insertion order, whether a string is
+//! held as `Str` or `Bytes`, and whether a value is still `Raw` make no
+//! difference. It serves as a cache key or deduplication identity for any
+//! bencode document, such as DHT records.
+
+use crate::io::destinations::buffer::Buffer;
+use crate::nodes::node::Node;
+use crate::stringify::default::{estimated_encoded_size, stringify_node};
+use crate::torrent::hasher::{DefaultHasher, Hasher};
+use crate::torrent::sha256::SHA256_LENGTH;
+
+/// Computes the fingerprint of a node: the SHA-256 of its canonical encoding.
+///
+/// Hashes with the [`DefaultHasher`]. `None` nodes contribute nothing, as when
+/// stringified.
+///
+/// # Arguments
+/// * `node` - The node to fingerprint
+///
+/// # Returns
+/// The 32 byte fingerprint
+///
+/// # Example
+/// ```
+/// use bencode_lib::{fingerprint, parse_bytes, make_node, torrent::sha256::sha256};
+///
+/// let record = parse_bytes(b"d1:ki42e1:v4:spame").unwrap();
+/// assert_eq!(fingerprint(&record), sha256(b"d1:ki42e1:v4:spame"));
+/// assert_eq!(fingerprint(&make_node([("v", make_node("spam")), ("k", make_node(42))])), fingerprint(&record));
+/// ```
+pub fn fingerprint(node: &Node) -> [u8; SHA256_LENGTH] {
+    fingerprint_with(node, &DefaultHasher)
+}
+
+/// Computes the fingerprint of a node as [`fingerprint`] does, hashing with `hasher`.
+///
+/// # Arguments
+/// * `node` - The node to fingerprint
+/// * `hasher` - The SHA-256 backend
+///
+/// # Returns
+/// The 32 byte fingerprint
+pub fn fingerprint_with(node: &Node, hasher: &dyn Hasher) -> [u8; SHA256_LENGTH] {
+    let mut destination = Buffer::with_capacity(estimated_encoded_size(node));
+    // Encoding into a buffer cannot fail
+    let _ = stringify_node(node, &mut destination);
+    hasher.sha256(&destination.into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{make_node, stringify_to_bytes};
+    use crate::torrent::sha256::sha256;
+    use crate::torrent::BuiltinHasher;
+
+    #[test]
+    fn fingerprint_is_the_hash_of_the_encoding() {
+        let node = make_node(vec![make_node(-7), make_node("abc"), Node::Bytes(vec![0xff, 0])]);
+        assert_eq!(fingerprint(&node), sha256(&stringify_to_bytes(&node).unwrap()));
+    }
+
+    #[test]
+    fn equivalent_representations_share_a_fingerprint() {
+        assert_eq!(fingerprint(&make_node("abc")), fingerprint(&Node::Bytes(b"abc".to_vec())));
+        let raw = Node::raw(b"d1:ai1e1:bli2eee".to_vec()).unwrap();
+        assert_eq!(fingerprint(&raw), fingerprint(&raw.expand_raw().unwrap()));
+        assert_ne!(fingerprint(&make_node(1)), fingerprint(&make_node("1")));
+    }
+
+    #[test]
+    fn fingerprints_are_hashed_with_the_given_backend() {
+        #[derive(Debug)]
+        struct Lengths;
+
+        impl Hasher for Lengths {
+            fn sha1(&self, data: &[u8]) -> [u8; 20] {
+                [data.len() as u8; 20]
+            }
+
+            fn sha256(&self, data: &[u8]) -> [u8; SHA256_LENGTH] {
+                [data.len() as u8; SHA256_LENGTH]
+            }
+        }
+
+        let node = make_node(vec![make_node(1), make_node("ab")]);
+        assert_eq!(fingerprint_with(&node, &Lengths), [9; SHA256_LENGTH]);
+        assert_eq!(fingerprint_with(&node, &BuiltinHasher), fingerprint(&node));
+    }
+}
@@ -3,6 +3,8 @@
 //! This module provides compile-time memory calculations to help developers
 //! understand and control memory usage when parsing bencode data.
 
+use crate::nodes::node::Node;
+
 /// Memory requirements calculator for bencode structures using const generics.
 ///
 /// Since Rust's type system requires heap allocation for recursive types like
@@ -68,6 +70,58 @@ impl MemoryBounds {
         // Reserve 50% for safety margin
         (stack_bytes / 2) / bytes_per_frame
     }
+
+    /// Calculates the number of bytes [`stringify`](crate::stringify) writes for a node.
+    ///
+    /// The count is exact, so it can size an output buffer or be checked
+    /// against a link's frame size before anything is encoded.
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{MemoryBounds, make_node, stringify_to_bytes};
+    ///
+    /// let node = make_node(vec![make_node(-42), make_node("spam")]);
+    /// assert_eq!(MemoryBounds::stringify_estimate(&node), stringify_to_bytes(&node).unwrap().len());
+    /// ```
+    ///
+    /// # Arguments
+    /// * `node` - The node to be encoded
+    ///
+    /// # Returns
+    /// Encoded size in bytes
+    pub fn stringify_estimate(node: &Node) -> usize {
+        match node {
+            Node::Integer(value) => {
+                let sign = usize::from(*value < 0);
+                // Widened so `int32` builds share the digit count
+                #[allow(clippy::useless_conversion)]
+                let magnitude = u64::from(value.unsigned_abs());
+                2 + sign + decimal_length(magnitude)
+            }
+            Node::BigInteger(digits) => 2 + digits.len(),
+            Node::Str(value) => string_length(value.len()),
+            Node::Bytes(bytes) => string_length(bytes.len()),
+            Node::List(items) => 2 + items.iter().map(Self::stringify_estimate).sum::<usize>(),
+            Node::Dictionary(items) => {
+                2 + items
+                    .iter()
+                    .map(|(key, value)| string_length(key.len()) + Self::stringify_estimate(value))
+                    .sum::<usize>()
+            }
+            Node::Raw(encoded) => encoded.len(),
+            Node::None => 0,
+        }
+    }
+}
+
+/// Number of decimal digits in `value`
+fn decimal_length(value: u64) -> usize {
+    value.checked_ilog10().map_or(1, |log| log as usize + 1)
+}
+
+/// Encoded size of a byte string of `length` bytes (length prefix, colon and data)
+fn string_length(length: usize) -> usize {
+    decimal_length(length as u64) + 1 + length
 }
 
 /// Type alias demonstrating const generic buffer sizing.
@@ -134,6 +188,25 @@ mod tests {
         assert!(depth <= 64);
     }
 
+    #[test]
+    fn stringify_estimate_is_exact() {
+        use crate::{make_node, stringify_to_bytes};
+        let mut dictionary = crate::HashMap::new();
+        dictionary.insert("key".to_string(), Node::Bytes(vec![0; 10]));
+        dictionary.insert("n".to_string(), make_node(crate::Integer::MIN));
+        for node in [
+            make_node(0),
+            make_node(-9),
+            make_node(1234567890),
+            make_node("x".repeat(100)),
+            make_node(vec![make_node(1), make_node(vec![] as Vec<Node>)]),
+            make_node(dictionary),
+            Node::raw(b"li1ee".to_vec()).unwrap(),
+        ] {
+            assert_eq!(MemoryBounds::stringify_estimate(&node), stringify_to_bytes(&node).unwrap().len());
+        }
+    }
+
     #[test]
     fn fixed_size_buffer_type() {
         let _buf: FixedSizeBuffer<256> = FixedSizeBuffer::new();
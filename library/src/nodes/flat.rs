@@ -0,0 +1,545 @@
+//! Compact single-buffer representation of a bencode node tree.
+//!
+//! A [`FlatNode`] stores a whole tree in one contiguous `Vec<u8>`, using lengths and
+//! offsets instead of pointers. It costs a single allocation, can be cached or shared
+//! with a plain byte copy, and can be navigated in place through [`FlatRef`] without
+//! rebuilding a [`Node`].
+//!
+//! A buffer starts with an 8-byte header: the magic bytes `\x89BFN` and the
+//! format version as a `u32`. The root node follows. Layout (all integers
+//! little-endian, lengths and counts are `u32`):
+//!
+//! | Node       | Encoding                                                        |
+//! |------------|-----------------------------------------------------------------|
+//! | Integer    | `'i'` `i64`                                                     |
+//! | Str, Bytes | `'s'` `len` `bytes`                                             |
+//! | List       | `'l'` `count` `payload_len` `child...`                          |
+//! | Dictionary | `'d'` `count` `payload_len` (`key_len` `key` `child`)... sorted |
+//! | None       | `'n'`                                                           |
+//!
+//! # Compatibility
+//!
+//! Buffers are meant to be persisted, so the format is versioned and
+//! [`FlatNode::from_bytes`] keeps reading every version written by an earlier
+//! release: version 0, the headerless layout written before the header was
+//! added, is read and upgraded to the current version. A buffer from a newer
+//! release is rejected with `ERR_UNSUPPORTED_FORMAT_VERSION` rather than
+//! misread. Any change to the layout must bump [`FLAT_FORMAT_VERSION`] and
+//! keep a reader for the previous one; the fixtures in the wire compatibility
+//! tests pin the bytes of each version.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::HashMap;
+use crate::error::messages::{ERR_FLAT_INVALID, ERR_FLAT_TOO_LARGE, ERR_INTEGER_OVERFLOW, ERR_UNSUPPORTED_FORMAT_VERSION};
+use crate::nodes::integer::Integer;
+use crate::nodes::node::Node;
+
+/// Version of the layout written by [`FlatNode::from_node`]
+pub const FLAT_FORMAT_VERSION: u32 = 1;
+/// Bytes starting a versioned buffer; the first is not a valid tag, which sets
+/// versioned buffers apart from headerless version 0 ones
+const FLAT_MAGIC: [u8; 4] = *b"\x89BFN";
+/// Size of the magic and version
+const HEADER_LENGTH: usize = FLAT_MAGIC.len() + U32_SIZE;
+
+const TAG_INTEGER: u8 = b'i';
+const TAG_STRING: u8 = b's';
+const TAG_LIST: u8 = b'l';
+const TAG_DICTIONARY: u8 = b'd';
+const TAG_NONE: u8 = b'n';
+
+/// Size of a length/count field
+const U32_SIZE: usize = 4;
+/// Size of a stored integer
+const I64_SIZE: usize = 8;
+/// Size of a container header after the tag (count + payload length)
+const CONTAINER_HEADER: usize = 2 * U32_SIZE;
+
+/// A bencode tree stored in one contiguous buffer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FlatNode {
+    data: Vec<u8>,
+}
+
+impl FlatNode {
+    /// Flattens a node tree into a single buffer.
+    ///
+    /// # Arguments
+    /// * `node` - The tree to flatten
+    ///
+    /// # Returns
+    /// * `Ok(FlatNode)` - The flattened tree
+    /// * `Err(String)` - If a string or container exceeds the 4 GiB `u32` limit
+    pub fn from_node(node: &Node) -> Result<Self, String> {
+        let mut data = header();
+        write_node(node, &mut data)?;
+        Ok(Self { data })
+    }
+
+    /// Wraps a buffer previously obtained from [`FlatNode::as_bytes`], by this
+    /// or any earlier release, validating it.
+    ///
+    /// # Arguments
+    /// * `data` - The flat encoded bytes
+    ///
+    /// # Returns
+    /// * `Ok(FlatNode)` - The buffer is a well-formed flat tree, upgraded to
+    ///   the current format version if it was older
+    /// * `Err(String)` - The buffer is truncated or malformed, or was written
+    ///   in a newer format version
+    pub fn from_bytes(data: Vec<u8>) -> Result<Self, String> {
+        let data = match Self::format_version(&data) {
+            Some(FLAT_FORMAT_VERSION) => data,
+            // Version 0 nodes are laid out as in version 1, only the header is missing
+            Some(0) => [header(), data].concat(),
+            Some(_) => return Err(ERR_UNSUPPORTED_FORMAT_VERSION.to_string()),
+            None => return Err(ERR_FLAT_INVALID.to_string()),
+        };
+        match validate(&data, HEADER_LENGTH) {
+            Some(end) if end == data.len() => Ok(Self { data }),
+            _ => Err(ERR_FLAT_INVALID.to_string()),
+        }
+    }
+
+    /// Returns the format version of a buffer without validating its nodes.
+    ///
+    /// # Returns
+    /// The version in the buffer's header, 0 for a headerless buffer written
+    /// before versioning, or None if the buffer is neither
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{FlatNode, make_node};
+    /// use bencode_lib::nodes::flat::FLAT_FORMAT_VERSION;
+    ///
+    /// let flat = FlatNode::from_node(&make_node(42)).unwrap();
+    /// assert_eq!(FlatNode::format_version(flat.as_bytes()), Some(FLAT_FORMAT_VERSION));
+    /// assert_eq!(FlatNode::format_version(b"n"), Some(0));
+    /// assert_eq!(FlatNode::format_version(b""), None);
+    /// ```
+    pub fn format_version(data: &[u8]) -> Option<u32> {
+        match *data.first()? {
+            TAG_INTEGER | TAG_STRING | TAG_LIST | TAG_DICTIONARY | TAG_NONE => Some(0),
+            _ if data.starts_with(&FLAT_MAGIC) => read_u32(data, FLAT_MAGIC.len()).map(|version| version as u32),
+            _ => None,
+        }
+    }
+
+    /// Returns the underlying buffer, header included, suitable for caching or
+    /// copying between processes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Consumes the flat node and returns its buffer.
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.data
+    }
+
+    /// Returns a view of the root node.
+    pub fn root(&self) -> FlatRef<'_> {
+        FlatRef {
+            data: &self.data,
+            offset: HEADER_LENGTH,
+        }
+    }
+
+    /// Rebuilds the owned node tree.
+    pub fn to_node(&self) -> Node {
+        self.root().to_node()
+    }
+}
+
+impl TryFrom<&Node> for FlatNode {
+    type Error = String;
+
+    fn try_from(node: &Node) -> Result<Self, Self::Error> {
+        FlatNode::from_node(node)
+    }
+}
+
+impl From<&FlatNode> for Node {
+    fn from(flat: &FlatNode) -> Self {
+        flat.to_node()
+    }
+}
+
+/// A borrowed view of one node inside a [`FlatNode`] buffer.
+#[derive(Clone, Copy, Debug)]
+pub struct FlatRef<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> FlatRef<'a> {
+    /// Returns the type name of the node ("integer", "string", "list", "dictionary" or "none")
+    pub fn type_name(&self) -> &'static str {
+        match self.tag() {
+            TAG_INTEGER => "integer",
+            TAG_STRING => "string",
+            TAG_LIST => "list",
+            TAG_DICTIONARY => "dictionary",
+            _ => "none",
+        }
+    }
+
+    /// Returns the integer value if this is an integer node
+    pub fn as_integer(&self) -> Option<Integer> {
+        if self.tag() != TAG_INTEGER {
+            return None;
+        }
+        let mut bytes = [0u8; I64_SIZE];
+        bytes.copy_from_slice(&self.data[self.offset + 1..self.offset + 1 + I64_SIZE]);
+        Integer::try_from(i64::from_le_bytes(bytes)).ok()
+    }
+
+    /// Returns the string value if this is a string node holding valid UTF-8
+    pub fn as_string(&self) -> Option<&'a str> {
+        self.as_bytes().and_then(|bytes| core::str::from_utf8(bytes).ok())
+    }
+
+    /// Returns the bytes of a string node, whether or not they are UTF-8
+    pub fn as_bytes(&self) -> Option<&'a [u8]> {
+        if self.tag() != TAG_STRING {
+            return None;
+        }
+        read_bytes(self.data, self.offset + 1).map(|(bytes, _)| bytes)
+    }
+
+    /// Returns the number of items in a list or dictionary (0 for other nodes)
+    pub fn len(&self) -> usize {
+        match self.tag() {
+            TAG_LIST | TAG_DICTIONARY => read_u32(self.data, self.offset + 1).unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    /// Returns true if this node has no items
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the list element at `index`
+    pub fn index(&self, index: usize) -> Option<FlatRef<'a>> {
+        if self.tag() != TAG_LIST {
+            return None;
+        }
+        let mut offset = self.offset + 1 + CONTAINER_HEADER;
+        for _ in 0..index.min(self.len()) {
+            offset = self.at(offset).end();
+        }
+        (index < self.len()).then(|| self.at(offset))
+    }
+
+    /// Returns the dictionary value for `key`
+    pub fn get(&self, key: &str) -> Option<FlatRef<'a>> {
+        self.entries()
+            .find(|(k, _)| *k == key.as_bytes())
+            .map(|(_, value)| value)
+    }
+
+    /// Iterates over list elements
+    pub fn items(&self) -> impl Iterator<Item = FlatRef<'a>> + 'a {
+        let this = *self;
+        let count = if this.tag() == TAG_LIST { this.len() } else { 0 };
+        let mut offset = this.offset + 1 + CONTAINER_HEADER;
+        (0..count).map(move |_| {
+            let item = this.at(offset);
+            offset = item.end();
+            item
+        })
+    }
+
+    /// Iterates over dictionary entries in key order as (raw key, value)
+    pub fn entries(&self) -> impl Iterator<Item = (&'a [u8], FlatRef<'a>)> + 'a {
+        let this = *self;
+        let count = if this.tag() == TAG_DICTIONARY { this.len() } else { 0 };
+        let mut offset = this.offset + 1 + CONTAINER_HEADER;
+        (0..count).map(move |_| {
+            let (key, value_offset) = read_bytes(this.data, offset).unwrap_or((&[], this.data.len()));
+            let value = this.at(value_offset);
+            offset = value.end();
+            (key, value)
+        })
+    }
+
+    /// Rebuilds the owned node tree rooted at this view.
+    pub fn to_node(&self) -> Node {
+        match self.tag() {
+            TAG_INTEGER => self.as_integer().map_or(Node::None, Node::Integer),
+            TAG_STRING => self.as_bytes().map_or(Node::None, |bytes| Node::from_bytes(bytes.to_vec())),
+            TAG_LIST => Node::List(self.items().map(|item| item.to_node()).collect()),
+            TAG_DICTIONARY => Node::Dictionary(
+                self.entries()
+                    .map(|(k, v)| (String::from_utf8_lossy(k).into_owned(), v.to_node()))
+                    .collect::<HashMap<_, _>>(),
+            ),
+            _ => Node::None,
+        }
+    }
+
+    /// Returns a view of the node at `offset` in the same buffer
+    fn at(&self, offset: usize) -> FlatRef<'a> {
+        FlatRef {
+            data: self.data,
+            offset,
+        }
+    }
+
+    /// Returns the tag byte of this node
+    fn tag(&self) -> u8 {
+        self.data.get(self.offset).copied().unwrap_or(TAG_NONE)
+    }
+
+    /// Returns the offset just past this node
+    fn end(&self) -> usize {
+        let body = self.offset + 1;
+        match self.tag() {
+            TAG_INTEGER => body + I64_SIZE,
+            TAG_STRING => read_u32(self.data, body).map_or(self.data.len(), |len| body + U32_SIZE + len),
+            TAG_LIST | TAG_DICTIONARY => read_u32(self.data, body + U32_SIZE)
+                .map_or(self.data.len(), |len| body + CONTAINER_HEADER + len),
+            _ => body,
+        }
+    }
+}
+
+/// Returns the header of a buffer in the current format version
+fn header() -> Vec<u8> {
+    let mut data = Vec::with_capacity(HEADER_LENGTH);
+    data.extend_from_slice(&FLAT_MAGIC);
+    data.extend_from_slice(&FLAT_FORMAT_VERSION.to_le_bytes());
+    data
+}
+
+/// Appends a `u32` length field, failing if the value does not fit
+fn write_u32(value: usize, data: &mut Vec<u8>) -> Result<(), String> {
+    let value = u32::try_from(value).map_err(|_| ERR_FLAT_TOO_LARGE.to_string())?;
+    data.extend_from_slice(&value.to_le_bytes());
+    Ok(())
+}
+
+/// Appends a length prefixed byte string
+fn write_bytes(bytes: &[u8], data: &mut Vec<u8>) -> Result<(), String> {
+    write_u32(bytes.len(), data)?;
+    data.extend_from_slice(bytes);
+    Ok(())
+}
+
+/// Appends the flat encoding of `node`
+fn write_node(node: &Node, data: &mut Vec<u8>) -> Result<(), String> {
+    match node {
+        Node::Integer(value) => {
+            data.push(TAG_INTEGER);
+            // Always stored as i64 so buffers are portable across `int32` builds
+            #[allow(clippy::useless_conversion)]
+            let value = i64::from(*value);
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        // Integers are stored in 64 bits, which big integers do not fit
+        Node::BigInteger(_) => return Err(ERR_INTEGER_OVERFLOW.to_string()),
+        Node::Str(value) => {
+            data.push(TAG_STRING);
+            write_bytes(value.as_bytes(), data)?;
+        }
+        Node::Bytes(bytes) => {
+            data.push(TAG_STRING);
+            write_bytes(bytes, data)?;
+        }
+        Node::List(items) => {
+            let header = start_container(TAG_LIST, items.len(), data)?;
+            for item in items {
+                write_node(item, data)?;
+            }
+            finish_container(header, data)?;
+        }
+        Node::Dictionary(entries) => {
+            let header = start_container(TAG_DICTIONARY, entries.len(), data)?;
+            let mut sorted: Vec<_> = entries.iter().collect();
+            sorted.sort_by_key(|(k, _)| *k);
+            for (key, value) in sorted {
+                write_bytes(key.as_bytes(), data)?;
+                write_node(value, data)?;
+            }
+            finish_container(header, data)?;
+        }
+        Node::Raw(_) => write_node(&node.decode_raw()?, data)?,
+        Node::None => data.push(TAG_NONE),
+    }
+    Ok(())
+}
+
+/// Writes a container tag and count, reserving the payload length; returns its offset
+fn start_container(tag: u8, count: usize, data: &mut Vec<u8>) -> Result<usize, String> {
+    data.push(tag);
+    write_u32(count, data)?;
+    let header = data.len();
+    data.extend_from_slice(&[0; U32_SIZE]);
+    Ok(header)
+}
+
+/// Fills in the payload length reserved by `start_container`
+fn finish_container(header: usize, data: &mut [u8]) -> Result<(), String> {
+    let payload = data.len() - header - U32_SIZE;
+    let payload = u32::try_from(payload).map_err(|_| ERR_FLAT_TOO_LARGE.to_string())?;
+    data[header..header + U32_SIZE].copy_from_slice(&payload.to_le_bytes());
+    Ok(())
+}
+
+/// Reads a `u32` field at `offset`
+fn read_u32(data: &[u8], offset: usize) -> Option<usize> {
+    let bytes = data.get(offset..offset + U32_SIZE)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as usize)
+}
+
+/// Reads a length prefixed byte string at `offset`, returning it and the offset after it
+fn read_bytes(data: &[u8], offset: usize) -> Option<(&[u8], usize)> {
+    let len = read_u32(data, offset)?;
+    let start = offset + U32_SIZE;
+    let bytes = data.get(start..start.checked_add(len)?)?;
+    Some((bytes, start + len))
+}
+
+/// Checks the node at `offset` is well formed, returning the offset after it
+fn validate(data: &[u8], offset: usize) -> Option<usize> {
+    let body = offset + 1;
+    match *data.get(offset)? {
+        TAG_INTEGER => {
+            let bytes = data.get(body..body + I64_SIZE)?;
+            let value = i64::from_le_bytes(bytes.try_into().ok()?);
+            Integer::try_from(value).ok()?;
+            Some(body + I64_SIZE)
+        }
+        // String values may hold any bytes; only dictionary keys must be UTF-8
+        TAG_STRING => read_bytes(data, body).map(|(_, end)| end),
+        tag @ (TAG_LIST | TAG_DICTIONARY) => {
+            let count = read_u32(data, body)?;
+            let payload = read_u32(data, body + U32_SIZE)?;
+            let mut position = body + CONTAINER_HEADER;
+            let end = position.checked_add(payload)?;
+            let mut last_key: Option<&[u8]> = None;
+            for _ in 0..count {
+                if tag == TAG_DICTIONARY {
+                    let (key, after) = read_bytes(data, position)?;
+                    core::str::from_utf8(key).ok()?;
+                    if last_key.is_some_and(|last| key <= last) {
+                        return None;
+                    }
+                    last_key = Some(key);
+                    position = after;
+                }
+                position = validate(data, position)?;
+            }
+            (position == end).then_some(end)
+        }
+        TAG_NONE => Some(body),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::default::parse_bytes;
+
+    fn sample() -> Node {
+        parse_bytes(b"d4:infod6:lengthi1024e4:name8:file.txte4:listli1ei-2e0:lee3:numi42ee").unwrap()
+    }
+
+    #[test]
+    fn flat_node_round_trips() {
+        let node = sample();
+        let flat = FlatNode::from_node(&node).unwrap();
+        assert_eq!(flat.to_node(), node);
+        assert_eq!(Node::from(&flat), node);
+    }
+
+    #[test]
+    fn flat_node_round_trips_none() {
+        let node = Node::List(vec![Node::None, Node::Integer(1)]);
+        assert_eq!(FlatNode::from_node(&node).unwrap().to_node(), node);
+    }
+
+    #[test]
+    fn flat_node_navigates_in_place() {
+        let flat = FlatNode::from_node(&sample()).unwrap();
+        let root = flat.root();
+        assert_eq!(root.type_name(), "dictionary");
+        assert_eq!(root.len(), 3);
+        assert_eq!(root.get("num").and_then(|n| n.as_integer()), Some(42));
+        let info = root.get("info").unwrap();
+        assert_eq!(info.get("name").and_then(|n| n.as_string()), Some("file.txt"));
+        let list = root.get("list").unwrap();
+        assert_eq!(list.index(1).and_then(|n| n.as_integer()), Some(-2));
+        assert_eq!(list.index(2).and_then(|n| n.as_string()), Some(""));
+        assert!(list.index(3).unwrap().is_empty());
+        assert!(list.index(4).is_none());
+        assert!(root.get("missing").is_none());
+    }
+
+    #[test]
+    fn flat_node_entries_are_sorted() {
+        let flat = FlatNode::from_node(&sample()).unwrap();
+        let keys: Vec<&[u8]> = flat.root().entries().map(|(k, _)| k).collect();
+        assert_eq!(keys, vec![&b"info"[..], b"list", b"num"]);
+    }
+
+    #[test]
+    fn flat_node_is_deterministic() {
+        let a = FlatNode::from_node(&sample()).unwrap();
+        let b = FlatNode::from_node(&sample()).unwrap();
+        assert_eq!(a.as_bytes(), b.as_bytes());
+    }
+
+    #[test]
+    fn flat_node_from_bytes_round_trips() {
+        let flat = FlatNode::from_node(&sample()).unwrap();
+        let copy = FlatNode::from_bytes(flat.as_bytes().to_vec()).unwrap();
+        assert_eq!(copy, flat);
+    }
+
+    #[test]
+    fn flat_node_from_bytes_round_trips_binary_strings() {
+        let node = parse_bytes(b"d6:pieces2:\xff\xfee").unwrap();
+        let flat = FlatNode::from_node(&node).unwrap();
+        let copy = FlatNode::from_bytes(flat.as_bytes().to_vec()).unwrap();
+        assert_eq!(copy.root().get("pieces").and_then(|pieces| pieces.as_bytes()), Some(&b"\xff\xfe"[..]));
+        assert_eq!(copy.to_node(), node);
+
+        let mut bad_key = FlatNode::from_node(&parse_bytes(b"d1:ai1ee").unwrap()).unwrap().into_bytes();
+        let key = bad_key.iter().rposition(|&byte| byte == b'a').unwrap();
+        bad_key[key] = 0xff;
+        assert_eq!(FlatNode::from_bytes(bad_key), Err(ERR_FLAT_INVALID.to_string()));
+    }
+
+    #[test]
+    fn flat_node_from_bytes_rejects_malformed() {
+        let bytes = FlatNode::from_node(&sample()).unwrap().into_bytes();
+        for len in 0..bytes.len() {
+            assert_eq!(
+                FlatNode::from_bytes(bytes[..len].to_vec()),
+                Err(ERR_FLAT_INVALID.to_string())
+            );
+        }
+        assert!(FlatNode::from_bytes(vec![b'x']).is_err());
+        assert!(FlatNode::from_bytes(vec![TAG_NONE, TAG_NONE]).is_err());
+    }
+
+    #[test]
+    fn flat_node_reads_headerless_buffers_and_rejects_newer_versions() {
+        let flat = FlatNode::from_node(&sample()).unwrap();
+        let legacy = flat.as_bytes()[HEADER_LENGTH..].to_vec();
+        assert_eq!(FlatNode::format_version(&legacy), Some(0));
+        assert_eq!(FlatNode::from_bytes(legacy).unwrap(), flat);
+
+        let mut newer = flat.into_bytes();
+        newer[FLAT_MAGIC.len()] += 1;
+        assert_eq!(FlatNode::format_version(&newer), Some(FLAT_FORMAT_VERSION + 1));
+        assert_eq!(FlatNode::from_bytes(newer), Err(ERR_UNSUPPORTED_FORMAT_VERSION.to_string()));
+    }
+}
@@ -0,0 +1,118 @@
+//! Integer type stored in bencode nodes.
+//!
+//! Nodes hold `i64` by default. Enabling the `int32` feature stores `i32` instead,
+//! shrinking nodes and avoiding 64-bit arithmetic on 32-bit microcontrollers.
+//! Parsed values that do not fit are rejected with `ERR_INTEGER_OUT_OF_RANGE`, or
+//! clamped to the nearest bound when the `int32-saturate` feature is enabled.
+//! Values that do not even fit 64 bits are rejected with `ERR_INTEGER_OVERFLOW`,
+//! unless the parser is configured to keep them as `Node::BigInteger`.
+
+use core::num::IntErrorKind;
+
+use crate::error::messages::{ERR_INTEGER_OVERFLOW, ERR_INVALID_INTEGER};
+#[cfg(all(feature = "int32", not(feature = "int32-saturate")))]
+use crate::error::messages::ERR_INTEGER_OUT_OF_RANGE;
+
+/// Integer type held by `Node::Integer` and `BorrowedNode::Integer`
+#[cfg(not(feature = "int32"))]
+pub type Integer = i64;
+
+/// Integer type held by `Node::Integer` and `BorrowedNode::Integer`
+#[cfg(feature = "int32")]
+pub type Integer = i32;
+
+/// Parses the text between the 'i' and 'e' markers of a bencode integer,
+/// applying the configured overflow policy. A leading '+' is rejected, as
+/// bencode does not allow one.
+///
+/// # Arguments
+/// * `text` - The integer digits (with optional leading '-')
+///
+/// # Returns
+/// * `Result<Integer, &'static str>` - Parsed value or error message
+pub(crate) fn parse_integer_text(text: &str) -> Result<Integer, &'static str> {
+    narrow(parse_i64_text(text)?)
+}
+
+/// Parses the text of a bencode integer as an `i64`, whatever the node integer type.
+pub(crate) fn parse_i64_text(text: &str) -> Result<i64, &'static str> {
+    if !is_integer_text(text) {
+        return Err(ERR_INVALID_INTEGER);
+    }
+    text.parse::<i64>().map_err(|error| match error.kind() {
+        IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => ERR_INTEGER_OVERFLOW,
+        _ => ERR_INVALID_INTEGER,
+    })
+}
+
+/// Returns true if `text` is an optional '-' followed by decimal digits, other than `-0`
+pub(crate) fn is_integer_text(text: &str) -> bool {
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    text != "-0" && !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Converts a parsed value to the node integer type (no-op for `i64`)
+#[cfg(not(feature = "int32"))]
+fn narrow(value: i64) -> Result<Integer, &'static str> {
+    Ok(value)
+}
+
+/// Converts a parsed value to the node integer type, rejecting values out of range
+#[cfg(all(feature = "int32", not(feature = "int32-saturate")))]
+fn narrow(value: i64) -> Result<Integer, &'static str> {
+    Integer::try_from(value).map_err(|_| ERR_INTEGER_OUT_OF_RANGE)
+}
+
+/// Converts a parsed value to the node integer type, clamping values out of range
+#[cfg(feature = "int32-saturate")]
+fn narrow(value: i64) -> Result<Integer, &'static str> {
+    Ok(value.clamp(Integer::MIN as i64, Integer::MAX as i64) as Integer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_integer_text_works() {
+        assert_eq!(parse_integer_text("42"), Ok(42));
+        assert_eq!(parse_integer_text("-42"), Ok(-42));
+        assert_eq!(parse_integer_text("0"), Ok(0));
+    }
+
+    #[test]
+    fn parse_integer_text_rejects_invalid() {
+        assert_eq!(parse_integer_text("-0"), Err(ERR_INVALID_INTEGER));
+        assert_eq!(parse_integer_text(""), Err(ERR_INVALID_INTEGER));
+        assert_eq!(parse_integer_text("1x"), Err(ERR_INVALID_INTEGER));
+        assert_eq!(parse_integer_text("+1"), Err(ERR_INVALID_INTEGER));
+    }
+
+    #[cfg(not(feature = "int32"))]
+    #[test]
+    fn parse_integer_text_accepts_64_bit_values() {
+        assert_eq!(parse_integer_text("9223372036854775807"), Ok(i64::MAX));
+    }
+
+    #[test]
+    fn parse_i64_text_reports_overflow() {
+        assert_eq!(parse_i64_text("-9223372036854775808"), Ok(i64::MIN));
+        assert_eq!(parse_i64_text("9223372036854775808"), Err(ERR_INTEGER_OVERFLOW));
+        assert_eq!(parse_i64_text("-9223372036854775809"), Err(ERR_INTEGER_OVERFLOW));
+        assert_eq!(parse_i64_text("99999999999999999999x"), Err(ERR_INVALID_INTEGER));
+    }
+
+    #[cfg(all(feature = "int32", not(feature = "int32-saturate")))]
+    #[test]
+    fn parse_integer_text_rejects_out_of_range() {
+        assert_eq!(parse_integer_text("2147483647"), Ok(i32::MAX));
+        assert_eq!(parse_integer_text("2147483648"), Err(ERR_INTEGER_OUT_OF_RANGE));
+    }
+
+    #[cfg(feature = "int32-saturate")]
+    #[test]
+    fn parse_integer_text_saturates_out_of_range() {
+        assert_eq!(parse_integer_text("2147483648"), Ok(i32::MAX));
+        assert_eq!(parse_integer_text("-2147483649"), Ok(i32::MIN));
+    }
+}
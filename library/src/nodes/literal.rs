@@ -0,0 +1,116 @@
+//! The [`bencode!`](crate::bencode) macro for writing node trees as literals.
+
+/// Builds a [`Node`](crate::Node) from a JSON-like literal.
+///
+/// `[...]` makes a list and `{ key: value, ... }` a dictionary, nested to any
+/// depth; anything else is an expression converted with `Node::from`, so
+/// integers, strings, byte strings (`b"..."` or a `&[u8]`) and existing nodes
+/// can all be used. A dictionary key is a single token: a string literal, a
+/// variable, or a parenthesized expression, converted `Into<String>`.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{bencode, stringify_to_bytes};
+///
+/// let length = 5;
+/// let name = String::from("demo");
+/// let torrent = bencode!({
+///     "announce": "http://tracker/announce",
+///     "info": {
+///         "files": [{ "length": length * 2, "path": ["a", "b"] }],
+///         "name": name,
+///         "pieces": b"\x01\x02",
+///     },
+///     ("private".to_string()): 1,
+/// });
+/// assert_eq!(
+///     stringify_to_bytes(&torrent).unwrap(),
+///     b"d8:announce23:http://tracker/announce4:infod5:filesld6:lengthi10e4:pathl1:a1:beee4:name4:demo6:pieces2:\x01\x02e7:privatei1ee"
+/// );
+/// ```
+#[macro_export]
+macro_rules! bencode {
+    // Lists: the elements built so far, then the tokens still to read
+    (@list [$($done:expr,)*]) => {
+        $crate::Node::from([$($done,)*])
+    };
+    (@list [$($done:expr,)*] [$($inner:tt)*] $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($done,)* $crate::bencode!([$($inner)*]),] $($($rest)*)?)
+    };
+    (@list [$($done:expr,)*] {$($inner:tt)*} $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($done,)* $crate::bencode!({$($inner)*}),] $($($rest)*)?)
+    };
+    (@list [$($done:expr,)*] $value:expr $(, $($rest:tt)*)?) => {
+        $crate::bencode!(@list [$($done,)* $crate::bencode!($value),] $($($rest)*)?)
+    };
+
+    // Dictionaries: one insert into `$dict` per entry
+    (@dict $dict:ident) => {};
+    (@dict $dict:ident $key:tt : [$($inner:tt)*] $(, $($rest:tt)*)?) => {
+        $dict.insert(::core::convert::Into::into($key), $crate::bencode!([$($inner)*]));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+    (@dict $dict:ident $key:tt : {$($inner:tt)*} $(, $($rest:tt)*)?) => {
+        $dict.insert(::core::convert::Into::into($key), $crate::bencode!({$($inner)*}));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+    (@dict $dict:ident $key:tt : $value:expr $(, $($rest:tt)*)?) => {
+        $dict.insert(::core::convert::Into::into($key), $crate::bencode!($value));
+        $crate::bencode!(@dict $dict $($($rest)*)?);
+    };
+
+    ([]) => {
+        $crate::Node::List(::core::default::Default::default())
+    };
+    ([$($elements:tt)+]) => {
+        $crate::bencode!(@list [] $($elements)+)
+    };
+    ({}) => {
+        $crate::Node::Dictionary(::core::default::Default::default())
+    };
+    ({$($entries:tt)+}) => {{
+        let mut dict: $crate::Dictionary = ::core::default::Default::default();
+        $crate::bencode!(@dict dict $($entries)+);
+        $crate::Node::Dictionary(dict)
+    }};
+    ($value:expr) => {
+        $crate::Node::from($value)
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nodes::node::Node;
+
+    #[test]
+    fn scalars_convert_with_from() {
+        assert_eq!(bencode!(42), Node::Integer(42));
+        assert_eq!(bencode!(-1 - 1), Node::Integer(-2));
+        assert_eq!(bencode!("text"), Node::Str("text".to_string()));
+        assert_eq!(bencode!(b"\xff\x00"), Node::Bytes(vec![0xff, 0]));
+        assert_eq!(bencode!(&[1u8, 2][..]), Node::Bytes(vec![1, 2]));
+        assert_eq!(bencode!(Node::from(1)), Node::Integer(1));
+    }
+
+    #[test]
+    fn containers_nest_and_accept_trailing_commas() {
+        let key = "b";
+        let values = [1, 2];
+        let node = bencode!({
+            "a": [],
+            key: [[1, 2,], {}, values[0] + 1],
+            ("c".to_string() + "d"): { "e": { "f": "g" } },
+        });
+        let expected = Node::from([
+            ("a", Node::List(vec![])),
+            ("b", Node::List(vec![Node::from([1, 2]), Node::Dictionary(Default::default()), Node::from(2)])),
+            ("cd", Node::from([("e", Node::from([("f", "g")]))])),
+        ]);
+        assert_eq!(node, expected);
+    }
+
+    #[test]
+    fn later_duplicate_keys_win() {
+        assert_eq!(bencode!({ "a": 1, "a": 2 }), Node::from([("a", 2)]));
+    }
+}
@@ -12,8 +12,50 @@
 /// * Dictionaries (key-value pairs)
 pub mod node;
 
+/// The bencode! macro for building node trees from literals
+pub mod literal;
+
+/// Integer type stored in nodes (selectable with the `int32` feature)
+pub mod integer;
+
 /// Zero-copy borrowed node implementation for embedded systems
 pub mod borrowed;
 
 /// Fixed-capacity node implementation using const generics
 pub mod fixed;
+
+/// Node tree with compile-time bounds on size and nesting
+pub mod bounded;
+
+/// Compact single-buffer node tree for caching and sharing
+pub mod flat;
+
+/// Reference counted, copy-on-write node tree for sharing between threads
+pub mod shared;
+
+/// Parsed tree kept with its input, so unchanged documents encode without copying
+pub mod document;
+
+/// Classification of byte strings as ASCII, UTF-8 or binary
+pub mod string_kind;
+
+/// Stable content hash of a node tree
+pub mod fingerprint;
+
+/// Node tree with per-path annotations such as source spans and comments
+pub mod annotated;
+
+/// Trimming of low priority values to fit an encoded size budget
+pub mod trim;
+
+/// Schema inference from sample documents
+pub mod schema;
+
+/// Patch documents computed from and applied to node trees
+pub mod patch;
+
+/// Readable list of the differences between two node trees
+pub mod diff;
+
+/// Depth-first traversal of node trees with visitor callbacks
+pub mod walk;
@@ -1,7 +1,4 @@
-#[cfg(not(feature = "std"))]
-use alloc::collections::BTreeMap as HashMap;
-#[cfg(feature = "std")]
-use std::collections::HashMap;
+use crate::HashMap;
 
 #[cfg(not(feature = "std"))]
 use alloc::{
@@ -11,21 +8,55 @@ use alloc::{
 
 use core::fmt;
 
+use crate::error::messages::{
+    ERR_INTEGER_OUT_OF_RANGE, ERR_INVALID_BOOLEAN, ERR_INVALID_PATH, ERR_NOT_AN_INTEGER, ERR_NOT_A_NUMBER, ERR_UNPARSABLE_STRING,
+};
+use crate::nodes::integer::Integer;
+use crate::nodes::string_kind::StringKind;
+use crate::nodes::walk::{self, NodeVisitor, NodeVisitorMut};
+use crate::parser::borrowed::validate_bencode;
+use crate::parser::default::parse_bytes;
+use crate::parser::extract::split_path;
+use crate::stringify::tree::{TreeOptions, write_pretty};
+
 /// A node in the bencode data structure that can represent different types of values.
 #[derive(Clone, Debug, PartialEq)]
 pub enum Node {
-    /// Represents a 64-bit signed integer value
-    Integer(i64),
+    /// Represents a signed integer value (64-bit, or 32-bit with the `int32` feature)
+    Integer(Integer),
+    /// An integer too large for [`Integer`], kept as its decimal digits (with
+    /// any leading '-'); parsed only when
+    /// [`ParserConfig::big_integers`](crate::ParserConfig::big_integers) is set
+    BigInteger(String),
     /// Represents a string value
     Str(String),
+    /// Represents a byte string that is not valid UTF-8 (such as the SHA-1
+    /// hashes in a torrent's `pieces`), kept exactly so it round trips
+    Bytes(Vec<u8>),
     /// Represents a list of other nodes
     List(Vec<Node>),
-    /// Represents a dictionary/map of string keys to node values
+    /// Represents a dictionary/map of string keys to node values; keys that
+    /// are not valid UTF-8 are decoded lossily (see [`Dictionary`](crate::Dictionary))
     Dictionary(HashMap<String, Node>),
+    /// A pre-encoded bencode value written verbatim when encoding; build it with
+    /// [`Node::raw`] so the bytes are checked to hold exactly one valid value
+    Raw(Vec<u8>),
     /// Represents an empty or uninitialized node
     None,
 }
 
+/// How [`Node::merge`] resolves a value present in both trees.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergeStrategy {
+    /// The incoming value replaces the existing one
+    Replace,
+    /// The existing value is kept
+    Keep,
+    /// Lists are extended with the incoming elements they do not already
+    /// hold; other values are replaced
+    Append,
+}
+
 impl Node {
     pub(crate) fn add_to_list(&mut self, p0: Node) -> Result<(), &'static str> {
         match self {
@@ -57,6 +88,11 @@ impl Node {
         matches!(self, Node::Str(_))
     }
 
+    /// Returns true if the node is a Bytes variant
+    pub fn is_bytes(&self) -> bool {
+        matches!(self, Node::Bytes(_))
+    }
+
     /// Returns true if the node is a List variant
     pub fn is_list(&self) -> bool {
         matches!(self, Node::List(_))
@@ -72,14 +108,105 @@ impl Node {
         matches!(self, Node::None)
     }
 
+    /// Returns true if the node is a Raw variant
+    pub fn is_raw(&self) -> bool {
+        matches!(self, Node::Raw(_))
+    }
+
+    /// Creates a string node from raw bytes: a Str node if they are valid UTF-8,
+    /// otherwise a Bytes node. This is how the parsers build string values.
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::Node;
+    ///
+    /// assert_eq!(Node::from_bytes(b"text".to_vec()), Node::from("text"));
+    /// assert_eq!(Node::from_bytes(vec![0xff, 0x00]), Node::Bytes(vec![0xff, 0x00]));
+    /// ```
+    pub fn from_bytes(bytes: Vec<u8>) -> Node {
+        match String::from_utf8(bytes) {
+            Ok(text) => Node::Str(text),
+            Err(e) => Node::Bytes(e.into_bytes()),
+        }
+    }
+
+    /// Creates a Raw node from pre-encoded bencode, which the encoder copies verbatim.
+    ///
+    /// This lets already encoded values (such as an info dictionary that must keep
+    /// its exact bytes) be spliced into a newly built tree.
+    ///
+    /// # Arguments
+    /// * `encoded` - Exactly one complete bencode value
+    ///
+    /// # Returns
+    /// * `Ok(Node::Raw)` - The bytes hold one valid value
+    /// * `Err(String)` - The bytes are malformed or followed by trailing data
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{stringify_to_bytes, Node};
+    ///
+    /// let info = Node::raw(b"d5:extrai1e4:name1:xe".to_vec()).unwrap();
+    /// let torrent = Node::from([("announce", Node::from("url")), ("info", info)]);
+    /// assert_eq!(stringify_to_bytes(&torrent).unwrap(), b"d8:announce3:url4:infod5:extrai1e4:name1:xee");
+    /// assert!(Node::raw(b"i1e2:xx".to_vec()).is_err());
+    /// ```
+    pub fn raw(encoded: Vec<u8>) -> Result<Node, String> {
+        validate_bencode(&encoded)?;
+        Ok(Node::Raw(encoded))
+    }
+
+    /// Decodes a Raw node into an ordinary tree; other nodes are returned as copies.
+    ///
+    /// Used by the text converters, which cannot copy bencode verbatim.
+    pub fn decode_raw(&self) -> Result<Node, String> {
+        match self {
+            Node::Raw(encoded) => Ok(parse_bytes(encoded)?),
+            _ => Ok(self.clone()),
+        }
+    }
+
+    /// Returns a copy of the tree with every Raw node decoded.
+    pub fn expand_raw(&self) -> Result<Node, String> {
+        match self {
+            Node::Raw(_) => self.decode_raw(),
+            Node::List(list) => list.iter().map(Node::expand_raw).collect::<Result<_, _>>().map(Node::List),
+            Node::Dictionary(dict) => dict
+                .iter()
+                .map(|(k, v)| v.expand_raw().map(|v| (k.clone(), v)))
+                .collect::<Result<_, _>>()
+                .map(Node::Dictionary),
+            _ => Ok(self.clone()),
+        }
+    }
+
+    /// Returns true if the tree contains a Raw node
+    #[cfg(any(feature = "toml", test))]
+    pub(crate) fn contains_raw(&self) -> bool {
+        match self {
+            Node::Raw(_) => true,
+            Node::List(list) => list.iter().any(Node::contains_raw),
+            Node::Dictionary(dict) => dict.values().any(Node::contains_raw),
+            _ => false,
+        }
+    }
+
     /// Returns a reference to the inner integer value if this is an Integer node
-    pub fn as_integer(&self) -> Option<&i64> {
+    pub fn as_integer(&self) -> Option<&Integer> {
         match self {
             Node::Integer(i) => Some(i),
             _ => None,
         }
     }
 
+    /// Returns the decimal digits of a BigInteger node
+    pub fn as_big_integer(&self) -> Option<&str> {
+        match self {
+            Node::BigInteger(digits) => Some(digits),
+            _ => None,
+        }
+    }
+
     /// Returns a reference to the inner string value if this is a Str node
     pub fn as_string(&self) -> Option<&str> {
         match self {
@@ -88,6 +215,30 @@ impl Node {
         }
     }
 
+    /// Returns the bytes of a Str or Bytes node.
+    ///
+    /// Unlike `as_string` this gives access to binary strings such as `pieces`.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Node::Str(s) => Some(s.as_bytes()),
+            Node::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Classifies the string as ASCII, UTF-8 or binary if this is a Str or
+    /// Bytes node.
+    ///
+    /// Str nodes are always text; the parsers keep strings that are not valid
+    /// UTF-8 as Bytes nodes, which are reported as binary.
+    pub fn string_kind(&self) -> Option<StringKind> {
+        match self {
+            Node::Str(s) => Some(StringKind::of_str(s)),
+            Node::Bytes(b) => Some(StringKind::of_bytes(b)),
+            _ => None,
+        }
+    }
+
     /// Returns a reference to the inner list if this is a List node
     pub fn as_list(&self) -> Option<&Vec<Node>> {
         match self {
@@ -120,6 +271,142 @@ impl Node {
         }
     }
 
+    /// Iterates over the entries of a Dictionary node in bencode canonical order.
+    ///
+    /// Keys are ordered by their raw bytes whichever map backs [`Dictionary`](crate::Dictionary),
+    /// so the order is the same with or without `std`.
+    /// Other nodes have no entries.
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::Node;
+    ///
+    /// let node = Node::from([("b", 1), ("a", 2), ("B", 3)]);
+    /// let keys: Vec<&str> = node.iter_sorted().map(|(key, _)| key).collect();
+    /// assert_eq!(keys, ["B", "a", "b"]);
+    /// assert_eq!(Node::from(1).iter_sorted().count(), 0);
+    /// ```
+    pub fn iter_sorted(&self) -> impl Iterator<Item = (&str, &Node)> {
+        self.as_dictionary()
+            .map(sorted_entries)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(key, value)| (key.as_str(), value))
+    }
+
+    /// Converts an Integer node to another integer type, failing rather than
+    /// wrapping when the value does not fit
+    fn checked_integer<T: TryFrom<Integer>>(&self) -> Result<T, String> {
+        let value = *self.as_integer().ok_or_else(|| ERR_NOT_AN_INTEGER.to_string())?;
+        T::try_from(value).map_err(|_| ERR_INTEGER_OUT_OF_RANGE.to_string())
+    }
+
+    /// Returns the value of an Integer node as a `u64`.
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - The value
+    /// * `Err(String)` - If the node is not an integer, or the value is negative
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::make_node;
+    ///
+    /// assert_eq!(make_node(16384).as_u64(), Ok(16384));
+    /// assert!(make_node(-1).as_u64().is_err());
+    /// ```
+    pub fn as_u64(&self) -> Result<u64, String> {
+        self.checked_integer()
+    }
+
+    /// Returns the value of an Integer node as a `u32`.
+    ///
+    /// # Returns
+    /// * `Ok(u32)` - The value
+    /// * `Err(String)` - If the node is not an integer, or the value is negative
+    ///   or above `u32::MAX`
+    pub fn as_u32(&self) -> Result<u32, String> {
+        self.checked_integer()
+    }
+
+    /// Returns the value of an Integer node as a `usize`.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - The value
+    /// * `Err(String)` - If the node is not an integer, or the value is negative
+    ///   or above `usize::MAX` on this platform
+    pub fn as_usize(&self) -> Result<usize, String> {
+        self.checked_integer()
+    }
+
+    /// Returns the value of an Integer node holding a flag, such as a
+    /// torrent's `private` field, where 1 is true and 0 is false.
+    ///
+    /// # Returns
+    /// * `Ok(bool)` - The flag
+    /// * `Err(String)` - If the node is not an integer, or is neither 0 nor 1
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::make_node;
+    ///
+    /// assert_eq!(make_node(1).as_bool(), Ok(true));
+    /// assert!(make_node(2).as_bool().is_err());
+    /// ```
+    pub fn as_bool(&self) -> Result<bool, String> {
+        match self.as_integer().ok_or_else(|| ERR_NOT_AN_INTEGER.to_string())? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(ERR_INVALID_BOOLEAN.to_string()),
+        }
+    }
+
+    /// Returns the number held by an Integer node, or by a string holding a
+    /// decimal number, such as the `"1.5"` some clients use for ratios.
+    ///
+    /// # Returns
+    /// * `Ok(f64)` - The number (integers beyond 2^53 are rounded)
+    /// * `Err(String)` - If the node is neither an integer nor a string holding a number
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::make_node;
+    ///
+    /// assert_eq!(make_node(2).as_f64(), Ok(2.0));
+    /// assert_eq!(make_node("1.5").as_f64(), Ok(1.5));
+    /// assert!(make_node("fast").as_f64().is_err());
+    /// ```
+    pub fn as_f64(&self) -> Result<f64, String> {
+        match self {
+            Node::Integer(value) => Ok(*value as f64),
+            _ => self.parse_string().map_err(|_| ERR_NOT_A_NUMBER.to_string()),
+        }
+    }
+
+    /// Parses the text of a Str node, or of a Bytes node holding UTF-8, as any
+    /// type implementing [`FromStr`](core::str::FromStr), for values such as
+    /// ports or versions that some encoders write as strings.
+    ///
+    /// # Returns
+    /// * `Ok(T)` - The parsed value
+    /// * `Err(String)` - If the node is not a string, or its text does not parse as `T`
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::make_node;
+    ///
+    /// assert_eq!(make_node("6881").parse_string::<u16>(), Ok(6881));
+    /// assert!(make_node("-1").parse_string::<u16>().is_err());
+    /// assert!(make_node(6881).parse_string::<u16>().is_err());
+    /// ```
+    pub fn parse_string<T: core::str::FromStr>(&self) -> Result<T, String> {
+        let text = match self {
+            Node::Str(text) => text.as_str(),
+            Node::Bytes(bytes) => core::str::from_utf8(bytes).map_err(|_| ERR_UNPARSABLE_STRING.to_string())?,
+            _ => return Err(ERR_UNPARSABLE_STRING.to_string()),
+        };
+        text.parse().map_err(|_| ERR_UNPARSABLE_STRING.to_string())
+    }
+
     /// Gets a value from a Dictionary node by key
     pub fn get(&self, key: &str) -> Option<&Node> {
         match self {
@@ -136,12 +423,219 @@ impl Node {
         }
     }
 
+    /// Removes and returns the subtree at a key path, leaving the rest of the tree in place.
+    ///
+    /// Paths are dot separated dictionary keys with numeric list indexes
+    /// (e.g. `"info"` or `"info.files.0"`); removing a list element shifts the
+    /// ones after it. The empty path takes the whole tree, leaving `Node::None`.
+    ///
+    /// # Returns
+    /// The removed subtree, or None if the path does not exist
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{parse_bytes, Node};
+    ///
+    /// let mut torrent = parse_bytes(b"d8:announce3:url4:infod4:name1:xee").unwrap();
+    /// let info = torrent.extract("info").unwrap();
+    /// assert_eq!(info.get("name"), Some(&Node::from("x")));
+    /// assert!(torrent.get("info").is_none());
+    /// ```
+    pub fn extract(&mut self, path: &str) -> Option<Node> {
+        let mut segments = split_path(path);
+        let Some(last) = segments.pop() else {
+            return Some(core::mem::replace(self, Node::None));
+        };
+        let mut parent = self;
+        for segment in segments {
+            parent = match parent {
+                Node::Dictionary(dict) => dict.get_mut(segment)?,
+                Node::List(list) => list.get_mut(segment.parse::<usize>().ok()?)?,
+                _ => return None,
+            };
+        }
+        match parent {
+            Node::Dictionary(dict) => dict.remove(last),
+            Node::List(list) => {
+                let index = last.parse::<usize>().ok()?;
+                (index < list.len()).then(|| list.remove(index))
+            }
+            _ => None,
+        }
+    }
+
+    /// Looks up the value at a key path.
+    ///
+    /// Paths use the same syntax as [`extract`](Self::extract): dot separated
+    /// dictionary keys with numeric list indexes, and the empty path for the
+    /// node itself. Raw nodes are not looked into.
+    ///
+    /// # Returns
+    /// The value, or None if the path does not exist
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{parse_bytes, Node};
+    ///
+    /// let torrent = parse_bytes(b"d4:infod5:filesld6:lengthi5e4:pathl1:aeeeee").unwrap();
+    /// assert_eq!(torrent.query("info.files.0.path.0"), Some(&Node::from("a")));
+    /// assert_eq!(torrent.query("info.files.1"), None);
+    /// ```
+    pub fn query(&self, path: &str) -> Option<&Node> {
+        split_path(path).into_iter().try_fold(self, |node, segment| match node {
+            Node::Dictionary(dict) => dict.get(segment),
+            Node::List(list) => list.get(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    /// Looks up the value at a key path for modification; see [`query`](Self::query).
+    pub fn query_mut(&mut self, path: &str) -> Option<&mut Node> {
+        split_path(path).into_iter().try_fold(self, |node, segment| match node {
+            Node::Dictionary(dict) => dict.get_mut(segment),
+            Node::List(list) => list.get_mut(segment.parse::<usize>().ok()?),
+            _ => None,
+        })
+    }
+
+    /// Visits the node and everything below it, depth first.
+    ///
+    /// Lists and dictionaries are entered before and left after their children,
+    /// dictionary entries are visited in key order, and every callback is given
+    /// the path of its value in the syntax of [`query`](Self::query). See
+    /// [`NodeVisitor`](crate::NodeVisitor).
+    pub fn walk<V: NodeVisitor + ?Sized>(&self, visitor: &mut V) {
+        walk::walk(self, "", visitor)
+    }
+
+    /// Visits the node and everything below it, allowing each value to be changed
+    /// in place; see [`walk`](Self::walk) and [`NodeVisitorMut`](crate::NodeVisitorMut).
+    pub fn walk_mut<V: NodeVisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        walk::walk_mut(self, "", visitor)
+    }
+
+    /// Returns the value at a key path, creating it and any missing containers on the way.
+    ///
+    /// Missing values are created as `Node::None` for the caller to fill in, and
+    /// a `Node::None` met on the path becomes an empty dictionary. The index one
+    /// past the end of a list appends to it. The tree is unchanged on error.
+    ///
+    /// # Returns
+    /// * `Ok(&mut Node)` - The value at the path
+    /// * `Err(String)` - If the path runs through a value other than a list,
+    ///   dictionary or `Node::None`, or indexes a list past its end
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{stringify_to_bytes, Node};
+    ///
+    /// let mut torrent = Node::None;
+    /// *torrent.query_or_create("info.name").unwrap() = Node::from("x");
+    /// *torrent.query_or_create("announce").unwrap() = Node::from("url");
+    /// assert_eq!(stringify_to_bytes(&torrent).unwrap(), b"d8:announce3:url4:infod4:name1:xee");
+    /// assert!(torrent.query_or_create("info.name.x").is_err());
+    /// ```
+    pub fn query_or_create(&mut self, path: &str) -> Result<&mut Node, String> {
+        // Every segment after the first created one is created too, so a path
+        // can only fail while it still runs through existing values
+        split_path(path).into_iter().try_fold(self, |node, segment| {
+            if node.is_none() {
+                *node = Node::Dictionary(HashMap::new());
+            }
+            match node {
+                Node::Dictionary(dict) => Ok(dict.entry(segment.to_string()).or_insert(Node::None)),
+                Node::List(list) => match segment.parse::<usize>() {
+                    Ok(index) if index == list.len() => {
+                        list.push(Node::None);
+                        Ok(&mut list[index])
+                    }
+                    Ok(index) if index < list.len() => Ok(&mut list[index]),
+                    _ => Err(ERR_INVALID_PATH.to_string()),
+                },
+                _ => Err(ERR_INVALID_PATH.to_string()),
+            }
+        })
+    }
+
+    /// Sets the value at a key path, creating missing containers as
+    /// [`query_or_create`](Self::query_or_create) does.
+    ///
+    /// # Returns
+    /// * `Ok(Option<Node>)` - The value that was replaced, if any
+    /// * `Err(String)` - If the path cannot be created; the tree is unchanged
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{parse_bytes, Node};
+    ///
+    /// let mut torrent = parse_bytes(b"d4:infod4:name1:xee").unwrap();
+    /// assert_eq!(torrent.insert_path("info.private", 1), Ok(None));
+    /// assert_eq!(torrent.insert_path("info.name", "y"), Ok(Some(Node::from("x"))));
+    /// assert_eq!(torrent, parse_bytes(b"d4:infod4:name1:y7:privatei1eee").unwrap());
+    /// ```
+    pub fn insert_path<T: Into<Node>>(&mut self, path: &str, value: T) -> Result<Option<Node>, String> {
+        let previous = core::mem::replace(self.query_or_create(path)?, value.into());
+        Ok((!previous.is_none()).then_some(previous))
+    }
+
+    /// Removes the value at a key path; the counterpart of
+    /// [`insert_path`](Self::insert_path), with the behaviour of [`extract`](Self::extract).
+    pub fn remove_path(&mut self, path: &str) -> Option<Node> {
+        self.extract(path)
+    }
+
+    /// Combines another tree into this one.
+    ///
+    /// Dictionaries are merged key by key, recursing into values that are
+    /// dictionaries on both sides. Other values present in both trees are
+    /// resolved by the strategy; `Node::None` on either side gives way to the other.
+    ///
+    /// # Arguments
+    /// * `other` - The tree to merge in
+    /// * `strategy` - Which value wins when both trees hold one
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{parse_bytes, MergeStrategy, Node};
+    ///
+    /// let mut torrent = parse_bytes(b"d13:announce-listll1:aee7:comment3:olde").unwrap();
+    /// let extra = parse_bytes(b"d13:announce-listll1:ael1:bee7:comment3:newe").unwrap();
+    /// torrent.merge(&extra, MergeStrategy::Append);
+    /// assert_eq!(torrent, parse_bytes(b"d13:announce-listll1:ael1:bee7:comment3:newe").unwrap());
+    /// ```
+    pub fn merge(&mut self, other: &Node, strategy: MergeStrategy) {
+        match (&mut *self, other) {
+            (_, Node::None) => {}
+            (Node::Dictionary(dict), Node::Dictionary(entries)) => {
+                for (key, value) in entries {
+                    match dict.get_mut(key) {
+                        Some(existing) => existing.merge(value, strategy),
+                        None => {
+                            dict.insert(key.clone(), value.clone());
+                        }
+                    }
+                }
+            }
+            (Node::List(list), Node::List(items)) if strategy == MergeStrategy::Append => {
+                for item in items {
+                    if !list.contains(item) {
+                        list.push(item.clone());
+                    }
+                }
+            }
+            (Node::None, _) => *self = other.clone(),
+            (_, _) if strategy != MergeStrategy::Keep => *self = other.clone(),
+            _ => {}
+        }
+    }
+
     /// Returns the number of elements in a List or Dictionary, or 0 for other types
     pub fn len(&self) -> usize {
         match self {
             Node::List(list) => list.len(),
             Node::Dictionary(dict) => dict.len(),
             Node::Str(s) => s.len(),
+            Node::Bytes(b) => b.len(),
             _ => 0,
         }
     }
@@ -152,18 +646,48 @@ impl Node {
             Node::List(list) => list.is_empty(),
             Node::Dictionary(dict) => dict.is_empty(),
             Node::Str(s) => s.is_empty(),
+            Node::Bytes(b) => b.is_empty(),
             Node::None => true,
             _ => false,
         }
     }
 
+    /// Lays the tree out one value per line, for debugging.
+    ///
+    /// Values are indented by depth under their dictionary key, with keys in
+    /// sorted order. Lists and dictionaries show their size, long strings are
+    /// cut short with their length in bytes, and strings that are not UTF-8
+    /// are previewed in hex, so fields such as a torrent's `pieces` stay
+    /// readable. [`print_tree`](crate::print_tree) gives the same listing, with
+    /// byte offsets, for encoded input.
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::{parse_bytes, TreeOptions};
+    ///
+    /// let torrent = parse_bytes(b"d4:infod4:name1:x6:pieces4:\xff\xfe\x00\x01ee").unwrap();
+    /// assert_eq!(torrent.to_pretty_string(&TreeOptions::new()), concat!(
+    ///     "dictionary (1 entry)\n",
+    ///     "  info: dictionary (2 entries)\n",
+    ///     "    name: \"x\"\n",
+    ///     "    pieces: <4 bytes: fffe0001>\n",
+    /// ));
+    /// ```
+    pub fn to_pretty_string(&self, options: &TreeOptions) -> String {
+        let mut out = String::new();
+        write_pretty(self, "", 0, options, &mut out);
+        out
+    }
+
     /// Returns the type name as a string
     pub fn type_name(&self) -> &'static str {
         match self {
-            Node::Integer(_) => "integer",
+            Node::Integer(_) | Node::BigInteger(_) => "integer",
             Node::Str(_) => "string",
+            Node::Bytes(_) => "bytes",
             Node::List(_) => "list",
             Node::Dictionary(_) => "dictionary",
+            Node::Raw(_) => "raw",
             Node::None => "none",
         }
     }
@@ -177,7 +701,7 @@ impl Node {
     }
 
     /// Get a required integer field from a dictionary
-    pub fn get_int_required(&self, key: &str) -> Result<i64, String> {
+    pub fn get_int_required(&self, key: &str) -> Result<Integer, String> {
         self.get_required(key)?
             .as_integer()
             .copied()
@@ -191,6 +715,13 @@ impl Node {
             .ok_or_else(|| format!("Field '{}' must be a string", key))
     }
 
+    /// Get a required byte string field (text or binary) from a dictionary
+    pub fn get_bytes_required(&self, key: &str) -> Result<&[u8], String> {
+        self.get_required(key)?
+            .as_bytes()
+            .ok_or_else(|| format!("Field '{}' must be a string", key))
+    }
+
     /// Get a required list field from a dictionary
     pub fn get_list_required(&self, key: &str) -> Result<&Vec<Node>, String> {
         self.get_required(key)?
@@ -206,7 +737,7 @@ impl Node {
     }
 
     /// Get an optional integer field, returning None if not found or not an integer
-    pub fn get_int_optional(&self, key: &str) -> Option<i64> {
+    pub fn get_int_optional(&self, key: &str) -> Option<Integer> {
         self.get(key).and_then(|n| n.as_integer()).copied()
     }
 
@@ -226,6 +757,14 @@ impl Node {
     }
 }
 
+/// Returns the entries of a dictionary in bencode canonical order, that is
+/// sorted by the bytes of their keys.
+pub(crate) fn sorted_entries<V>(dict: &HashMap<String, V>) -> Vec<(&String, &V)> {
+    let mut entries: Vec<_> = dict.iter().collect();
+    entries.sort_unstable_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+    entries
+}
+
 /// Converts a vector of values into a List node
 impl<T: Into<Node>> From<Vec<T>> for Node {
     fn from(value: Vec<T>) -> Self {
@@ -234,8 +773,8 @@ impl<T: Into<Node>> From<Vec<T>> for Node {
 }
 
 /// Converts an integer into an Integer node
-impl From<i64> for Node {
-    fn from(value: i64) -> Self {
+impl From<Integer> for Node {
+    fn from(value: Integer) -> Self {
         Node::Integer(value)
     }
 }
@@ -254,6 +793,20 @@ impl From<String> for Node {
     }
 }
 
+/// Converts a byte slice into a Bytes node
+impl From<&[u8]> for Node {
+    fn from(value: &[u8]) -> Self {
+        Node::Bytes(value.to_vec())
+    }
+}
+
+/// Converts a byte string literal (e.g. `b"abc"`) into a Bytes node
+impl<const N: usize> From<&[u8; N]> for Node {
+    fn from(value: &[u8; N]) -> Self {
+        Node::Bytes(value.to_vec())
+    }
+}
+
 /// Converts a HashMap into a Dictionary node
 impl From<HashMap<String, Node>> for Node {
     fn from(value: HashMap<String, Node>) -> Self {
@@ -300,7 +853,9 @@ impl fmt::Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Node::Integer(i) => write!(f, "{}", i),
+            Node::BigInteger(digits) => write!(f, "{}", digits),
             Node::Str(s) => write!(f, "\"{}\"", s),
+            Node::Bytes(b) => write!(f, "{:?}", b),
             Node::List(list) => {
                 write!(f, "[")?;
                 for (i, item) in list.iter().enumerate() {
@@ -323,6 +878,10 @@ impl fmt::Display for Node {
                 }
                 write!(f, "}}")
             }
+            Node::Raw(_) => match self.decode_raw() {
+                Ok(decoded) => write!(f, "{}", decoded),
+                Err(_) => write!(f, "<invalid raw>"),
+            },
             Node::None => write!(f, "null"),
         }
     }
@@ -330,8 +889,9 @@ impl fmt::Display for Node {
 
 #[cfg(test)]
 mod tests {
-    use super::{Node, make_node};
-    use std::collections::HashMap;
+    use super::{Integer, MergeStrategy, Node, make_node};
+    use crate::HashMap;
+    use crate::error::kind::ErrorKind;
 
     #[test]
     fn create_integer_works() {
@@ -362,7 +922,7 @@ mod tests {
         let variant = Node::List(Vec::<Node>::new());
         match variant {
             Node::List(list) => {
-                assert_eq!(list.is_empty(), true);
+                assert!(list.is_empty());
             }
             _ => {
                 assert_eq!(false, true);
@@ -420,7 +980,7 @@ mod tests {
         let variant = Node::Dictionary(HashMap::new());
         match variant {
             Node::Dictionary(dictionary) => {
-                assert_eq!(dictionary.is_empty(), true);
+                assert!(dictionary.is_empty());
             }
             _ => {
                 assert_eq!(false, true);
@@ -502,7 +1062,7 @@ mod tests {
         let node = make_node(Vec::<Node>::new());
         match node {
             Node::List(list) => {
-                assert_eq!(list.is_empty(), true);
+                assert!(list.is_empty());
             }
             _ => {
                 assert_eq!(false, true);
@@ -514,7 +1074,7 @@ mod tests {
         let node = make_node(HashMap::<String, Node>::new());
         match node {
             Node::Dictionary(dictionary) => {
-                assert_eq!(dictionary.is_empty(), true);
+                assert!(dictionary.is_empty());
             }
             _ => {
                 assert_eq!(false, true);
@@ -598,7 +1158,7 @@ mod tests {
 
     #[test]
     fn empty_array_to_list_works() {
-        let node = Node::from([] as [i64; 0]);
+        let node = Node::from([] as [Integer; 0]);
         match node {
             Node::List(list) => assert_eq!(list.len(), 0),
             _ => assert_eq!(false, true),
@@ -651,8 +1211,8 @@ mod tests {
     }
 
     #[test]
-    fn test_from_i64() {
-        let value: i64 = 42;
+    fn test_from_integer() {
+        let value: Integer = 42;
         let node = Node::from(value);
         assert_eq!(node, Node::Integer(42));
     }
@@ -744,6 +1304,18 @@ mod tests {
         assert_eq!(node.as_dictionary().unwrap().len(), 1);
     }
 
+    #[test]
+    fn iter_sorted_orders_keys_by_bytes() {
+        let keys: Vec<String> = (0..50).rev().map(|i| format!("k{}", i)).chain(["\u{e9}".to_string(), "Z".to_string()]).collect();
+        let node = Node::Dictionary(keys.iter().map(|key| (key.clone(), Node::from(key.as_str()))).collect());
+        assert!(node.iter_sorted().all(|(key, value)| value.as_string() == Some(key)));
+        let sorted: Vec<&str> = node.iter_sorted().map(|(key, _)| key).collect();
+        let mut expected: Vec<&str> = keys.iter().map(String::as_str).collect();
+        expected.sort_by(|a, b| a.as_bytes().cmp(b.as_bytes()));
+        assert_eq!(sorted, expected);
+        assert_eq!(Node::from([1, 2]).iter_sorted().count(), 0);
+    }
+
     #[test]
     fn test_get() {
         let mut dict = HashMap::new();
@@ -845,6 +1417,37 @@ mod tests {
         assert!(node.get_required("missing").is_err());
     }
 
+    #[test]
+    fn typed_accessors_check_type_and_range() {
+        use crate::error::kind::ErrorKind;
+        fn kind<T: core::fmt::Debug>(result: Result<T, String>) -> ErrorKind {
+            ErrorKind::from_message(&result.unwrap_err())
+        }
+
+        assert_eq!(Node::Integer(7).as_u64(), Ok(7));
+        assert_eq!(Node::Integer(7).as_u32(), Ok(7));
+        assert_eq!(Node::Integer(7).as_usize(), Ok(7));
+        assert_eq!(kind(Node::Integer(-1).as_u64()), ErrorKind::IntegerOutOfRange);
+        assert_eq!(kind(Node::Integer(-1).as_usize()), ErrorKind::IntegerOutOfRange);
+        #[cfg(not(feature = "int32"))]
+        assert_eq!(kind(Node::Integer(1 << 32).as_u32()), ErrorKind::IntegerOutOfRange);
+        assert_eq!(kind(Node::from("7").as_u64()), ErrorKind::NotAnInteger);
+
+        assert_eq!(Node::Integer(0).as_bool(), Ok(false));
+        assert_eq!(Node::Integer(1).as_bool(), Ok(true));
+        assert_eq!(kind(Node::Integer(-1).as_bool()), ErrorKind::InvalidBoolean);
+        assert_eq!(kind(Node::None.as_bool()), ErrorKind::NotAnInteger);
+
+        assert_eq!(Node::Integer(-3).as_f64(), Ok(-3.0));
+        assert_eq!(Node::Bytes(b"0.25".to_vec()).as_f64(), Ok(0.25));
+        assert_eq!(kind(Node::List(vec![]).as_f64()), ErrorKind::NotANumber);
+        assert_eq!(kind(Node::from("1,5").as_f64()), ErrorKind::NotANumber);
+
+        assert_eq!(Node::from("true").parse_string::<bool>(), Ok(true));
+        assert_eq!(kind(Node::Bytes(vec![0xff]).parse_string::<u8>()), ErrorKind::UnparsableString);
+        assert_eq!(kind(Node::from(" 1").parse_string::<u8>()), ErrorKind::UnparsableString);
+    }
+
     #[test]
     fn test_get_int_required() {
         let mut dict = HashMap::new();
@@ -930,4 +1533,142 @@ mod tests {
 
         assert_eq!(format!("{}", node), "[1, {\"x\": 10}]");
     }
+
+    #[test]
+    fn extract_removes_subtree() {
+        let mut node = Node::from([
+            ("info", Node::from([("files", Node::from(vec![Node::from(1), Node::from(2)])), ("name", Node::from("x"))])),
+            ("num", Node::from(7)),
+        ]);
+        assert_eq!(node.extract("info.files.0"), Some(Node::from(1)));
+        assert_eq!(node.get("info").unwrap().get("files"), Some(&Node::from(vec![Node::from(2)])));
+        assert_eq!(node.extract("info.name"), Some(Node::from("x")));
+        assert_eq!(node.extract("num"), Some(Node::from(7)));
+        assert_eq!(node.len(), 1);
+    }
+
+    #[test]
+    fn extract_missing_paths() {
+        let mut node = Node::from([("list", Node::from(vec![Node::from(1)])), ("num", Node::from(7))]);
+        for path in ["missing", "list.1", "list.x", "num.x", "list.0.x"] {
+            assert_eq!(node.extract(path), None, "{}", path);
+        }
+        assert_eq!(node.len(), 2);
+    }
+
+    #[test]
+    fn extract_root_leaves_none() {
+        let mut node = Node::from(5);
+        assert_eq!(node.extract(""), Some(Node::from(5)));
+        assert!(node.is_none());
+    }
+
+    #[test]
+    fn string_kind_classifies_str_nodes() {
+        use crate::nodes::string_kind::StringKind;
+        assert_eq!(Node::from("abc").string_kind(), Some(StringKind::Ascii));
+        assert_eq!(Node::from("\u{e9}").string_kind(), Some(StringKind::Utf8));
+        let binary = crate::parser::default::parse_bytes(b"2:\xff\xfe").unwrap();
+        assert_eq!(binary.string_kind(), Some(StringKind::Binary));
+        assert_eq!(Node::Integer(1).string_kind(), None);
+    }
+
+    #[test]
+    fn raw_nodes_are_validated_and_decodable() {
+        let raw = Node::raw(b"li1e1:ae".to_vec()).unwrap();
+        assert!(raw.is_raw());
+        assert_eq!(raw.type_name(), "raw");
+        assert_eq!(raw.decode_raw().unwrap(), Node::from([Node::Integer(1), Node::from("a")]));
+        assert_eq!(raw.to_string(), "[1, \"a\"]");
+        assert!(Node::raw(b"li1e".to_vec()).is_err());
+        assert!(Node::raw(Vec::new()).is_err());
+
+        let tree = Node::from([("x", raw)]);
+        assert!(tree.contains_raw());
+        let expanded = tree.expand_raw().unwrap();
+        assert!(!expanded.contains_raw());
+        assert_eq!(expanded.get("x").and_then(Node::as_list).map(Vec::len), Some(2));
+    }
+
+    #[test]
+    fn query_follows_keys_and_indexes() {
+        let mut node = Node::from([
+            ("info", Node::from([("files", Node::from(vec![Node::from(1), Node::from(2)]))])),
+            ("num", Node::from(7)),
+        ]);
+        assert_eq!(node.query(""), Some(&node));
+        assert_eq!(node.query("info.files.1"), Some(&Node::from(2)));
+        for path in ["missing", "info.files.2", "info.files.x", "num.0", "info.files.0.x"] {
+            assert_eq!(node.query(path), None, "{}", path);
+        }
+        *node.query_mut("info.files.0").unwrap() = Node::from("a");
+        assert_eq!(node.query("info.files.0"), Some(&Node::from("a")));
+        assert!(node.query_mut("info.files.2").is_none());
+    }
+
+    #[test]
+    fn query_or_create_builds_missing_containers() {
+        let mut node = Node::from([("list", Node::from(vec![Node::from(1)])), ("num", Node::from(7))]);
+        *node.query_or_create("list.1").unwrap() = Node::from(2);
+        *node.query_or_create("a.b.0").unwrap() = Node::from(3);
+        assert_eq!(node.query("list"), Some(&Node::from(vec![Node::from(1), Node::from(2)])));
+        // Numeric segments key newly created dictionaries
+        assert_eq!(node.query("a.b.0"), Some(&Node::from(3)));
+        assert_eq!(node.query_or_create("num").unwrap(), &Node::from(7));
+
+        let before = node.clone();
+        for path in ["num.x", "list.3", "list.x", "list.0.x"] {
+            let error = node.query_or_create(path).unwrap_err();
+            assert_eq!(ErrorKind::from_message(&error), ErrorKind::InvalidPath, "{}", path);
+        }
+        assert_eq!(node, before);
+    }
+
+    #[test]
+    fn insert_and_remove_path_edit_nested_values() {
+        let mut node = Node::from([("info", Node::from([("name", "x")]))]);
+        assert_eq!(node.insert_path("info.private", 1), Ok(None));
+        assert_eq!(node.insert_path("info.files.0", "a"), Ok(None));
+        assert_eq!(node.insert_path("info.name", "y"), Ok(Some(Node::from("x"))));
+        assert_eq!(node.query("info.files.0"), Some(&Node::from("a")));
+        assert!(node.insert_path("info.name.x", 1).is_err());
+        assert_eq!(node.remove_path("info.private"), Some(Node::from(1)));
+        assert_eq!(node.remove_path("info.private"), None);
+        assert_eq!(node, Node::from([("info", Node::from([("files", Node::from([("0", "a")])), ("name", Node::from("y"))]))]));
+    }
+
+    #[test]
+    fn merge_resolves_conflicts_by_strategy() {
+        let base = Node::from([
+            ("info", Node::from([("name", Node::from("x"))])),
+            ("trackers", Node::from(["a", "b"])),
+            ("comment", Node::from("old")),
+        ]);
+        let other = Node::from([
+            ("info", Node::from([("private", Node::from(1))])),
+            ("trackers", Node::from(["b", "c"])),
+            ("comment", Node::from("new")),
+        ]);
+        let merged = |strategy| {
+            let mut node = base.clone();
+            node.merge(&other, strategy);
+            node
+        };
+        let replaced = merged(MergeStrategy::Replace);
+        assert_eq!(replaced.query("info.name"), Some(&Node::from("x")));
+        assert_eq!(replaced.query("info.private"), Some(&Node::from(1)));
+        assert_eq!((replaced.get("trackers"), replaced.get("comment")), (Some(&Node::from(["b", "c"])), Some(&Node::from("new"))));
+        let kept = merged(MergeStrategy::Keep);
+        assert_eq!(kept.query("info.private"), Some(&Node::from(1)));
+        assert_eq!((kept.get("trackers"), kept.get("comment")), (Some(&Node::from(["a", "b"])), Some(&Node::from("old"))));
+        let appended = merged(MergeStrategy::Append);
+        assert_eq!(appended.get("trackers"), Some(&Node::from(["a", "b", "c"])));
+        assert_eq!(appended.get("comment"), Some(&Node::from("new")));
+
+        let mut empty = Node::None;
+        empty.merge(&base, MergeStrategy::Keep);
+        assert_eq!(empty, base);
+        empty.merge(&Node::None, MergeStrategy::Replace);
+        assert_eq!(empty, base);
+    }
 }
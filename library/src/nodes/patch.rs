@@ -0,0 +1,411 @@
+//! Patch documents describing the changes between two node trees.
+//!
+//! A [`Patch`] is a list of add, remove and replace operations, each naming a
+//! key path in the syntax of [`Node::query`]. [`diff_to_patch`] computes one
+//! from two versions of a document and [`apply_patch`] replays it, so services
+//! sharing metadata can send only what changed. A patch is itself bencoded: a
+//! list with one dictionary per operation holding `op`, `path` and, for add
+//! and replace, `value`.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::HashMap;
+use crate::error::messages::{ERR_INVALID_PATCH, ERR_PATCH_CONFLICT};
+use crate::error::parse::ParseError;
+use crate::nodes::node::Node;
+use crate::parser::default::parse_bytes;
+use crate::parser::extract::PATH_SEPARATOR;
+use crate::stringify::default::stringify_to_bytes;
+
+/// One change to a document.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PatchOp {
+    /// Insert a value at a path that does not exist yet; a list index
+    /// inserts before the element there, or appends when one past the end
+    Add { path: String, value: Node },
+    /// Remove the value at a path; later list elements shift down
+    Remove { path: String },
+    /// Replace the value at an existing path
+    Replace { path: String, value: Node },
+}
+
+impl PatchOp {
+    /// Returns the name used for the operation in an encoded patch
+    pub fn name(&self) -> &'static str {
+        match self {
+            PatchOp::Add { .. } => "add",
+            PatchOp::Remove { .. } => "remove",
+            PatchOp::Replace { .. } => "replace",
+        }
+    }
+
+    /// Returns the key path the operation applies to
+    pub fn path(&self) -> &str {
+        match self {
+            PatchOp::Add { path, .. } | PatchOp::Remove { path } | PatchOp::Replace { path, .. } => path,
+        }
+    }
+}
+
+/// Ordered list of changes, applied first to last.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Patch {
+    /// The operations in the order they are applied
+    pub ops: Vec<PatchOp>,
+}
+
+impl Patch {
+    /// Creates an empty patch
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the patch changes nothing
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Encodes the patch as a list of operation dictionaries
+    pub fn to_node(&self) -> Node {
+        Node::List(
+            self.ops
+                .iter()
+                .map(|op| {
+                    let mut entry = HashMap::new();
+                    entry.insert("op".to_string(), Node::from(op.name()));
+                    entry.insert("path".to_string(), Node::from(op.path()));
+                    if let PatchOp::Add { value, .. } | PatchOp::Replace { value, .. } = op {
+                        entry.insert("value".to_string(), value.clone());
+                    }
+                    Node::Dictionary(entry)
+                })
+                .collect(),
+        )
+    }
+
+    /// Encodes the patch as bencode
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ParseError> {
+        stringify_to_bytes(&self.to_node())
+    }
+
+    /// Reads a patch from its node form.
+    ///
+    /// # Returns
+    /// * `Ok(Patch)` - The operations
+    /// * `Err(ParseError)` - If the node is not a list of operation
+    ///   dictionaries; the path is the index of the first bad operation
+    pub fn from_node(node: &Node) -> Result<Self, ParseError> {
+        let invalid = |index: Option<usize>| {
+            let error = ParseError::new(ERR_INVALID_PATCH, 0);
+            match index {
+                Some(index) => error.with_path(index.to_string()),
+                None => error,
+            }
+        };
+        let entries = node.as_list().ok_or_else(|| invalid(None))?;
+        let ops = entries
+            .iter()
+            .enumerate()
+            .map(|(index, entry)| {
+                let path = entry.get_string_optional("path").ok_or_else(|| invalid(Some(index)))?.to_string();
+                let value = || entry.get("value").cloned().ok_or_else(|| invalid(Some(index)));
+                match entry.get_string_optional("op") {
+                    Some("add") => Ok(PatchOp::Add { path, value: value()? }),
+                    Some("remove") => Ok(PatchOp::Remove { path }),
+                    Some("replace") => Ok(PatchOp::Replace { path, value: value()? }),
+                    _ => Err(invalid(Some(index))),
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { ops })
+    }
+
+    /// Decodes a bencoded patch; see [`from_node`](Self::from_node).
+    pub fn from_bytes(data: &[u8]) -> Result<Self, ParseError> {
+        Self::from_node(&parse_bytes(data)?)
+    }
+}
+
+/// Splits a path into the path of its parent and the last segment
+fn split_last(path: &str) -> (&str, &str) {
+    path.rsplit_once(PATH_SEPARATOR).unwrap_or(("", path))
+}
+
+/// Applies one operation, returning false if the document does not allow it
+fn apply_op(node: &mut Node, op: &PatchOp) -> bool {
+    match op {
+        PatchOp::Add { path, value } if path.is_empty() => {
+            let vacant = node.is_none();
+            if vacant {
+                *node = value.clone();
+            }
+            vacant
+        }
+        PatchOp::Add { path, value } => {
+            let (parent, last) = split_last(path);
+            match node.query_mut(parent) {
+                Some(Node::Dictionary(dict)) if !dict.contains_key(last) => {
+                    dict.insert(last.to_string(), value.clone());
+                    true
+                }
+                Some(Node::List(list)) => match last.parse::<usize>() {
+                    Ok(index) if index <= list.len() => {
+                        list.insert(index, value.clone());
+                        true
+                    }
+                    _ => false,
+                },
+                _ => false,
+            }
+        }
+        PatchOp::Remove { path } => node.extract(path).is_some(),
+        PatchOp::Replace { path, value } => match node.query_mut(path) {
+            Some(target) => {
+                *target = value.clone();
+                true
+            }
+            None => false,
+        },
+    }
+}
+
+/// Applies a patch to a document.
+///
+/// Operations run in order, each seeing the changes of those before it. If
+/// one cannot be applied (an add whose path already exists or whose parent is
+/// missing, or a remove or replace of a missing path) the document is left
+/// unchanged.
+///
+/// # Arguments
+/// * `node` - The document to change
+/// * `patch` - The changes to make
+///
+/// # Returns
+/// * `Ok(())` - Every operation was applied
+/// * `Err(ParseError)` - The first operation that could not be applied, with
+///   its key path in `path`
+///
+/// # Example
+/// ```
+/// use bencode_lib::{apply_patch, parse_bytes, Patch};
+///
+/// let mut torrent = parse_bytes(b"d8:announce3:url4:infod4:name1:xee").unwrap();
+/// let patch = Patch::from_bytes(b"ld2:op7:replace4:path9:info.name5:value1:yed2:op6:remove4:path8:announceee").unwrap();
+/// apply_patch(&mut torrent, &patch).unwrap();
+/// assert_eq!(torrent, parse_bytes(b"d4:infod4:name1:yee").unwrap());
+/// assert!(apply_patch(&mut torrent, &patch).is_err());
+/// ```
+pub fn apply_patch(node: &mut Node, patch: &Patch) -> Result<(), ParseError> {
+    let mut patched = node.clone();
+    for op in &patch.ops {
+        if !apply_op(&mut patched, op) {
+            return Err(ParseError::new(ERR_PATCH_CONFLICT, 0).with_path(op.path()));
+        }
+    }
+    *node = patched;
+    Ok(())
+}
+
+/// Appends a segment to a key path
+pub(crate) fn child_path(parent: &str, segment: &str) -> String {
+    if parent.is_empty() {
+        segment.to_string()
+    } else {
+        let mut path = String::with_capacity(parent.len() + 1 + segment.len());
+        path.push_str(parent);
+        path.push(PATH_SEPARATOR);
+        path.push_str(segment);
+        path
+    }
+}
+
+/// Returns true if a path can name the value under `key` of the dictionary at `parent`
+pub(crate) fn addressable(parent: &str, key: &str) -> bool {
+    !(key.contains(PATH_SEPARATOR) || parent.is_empty() && key.is_empty())
+}
+
+/// Appends the operations turning `old` into `new`, both found at `path`
+fn diff_into(old: &Node, new: &Node, path: &str, ops: &mut Vec<PatchOp>) {
+    match (old, new) {
+        (Node::Dictionary(old_dict), Node::Dictionary(new_dict))
+            if old_dict.keys().chain(new_dict.keys()).all(|key| addressable(path, key)) =>
+        {
+            let mut removed: Vec<&String> = old_dict.keys().filter(|key| !new_dict.contains_key(*key)).collect();
+            removed.sort();
+            for key in removed {
+                ops.push(PatchOp::Remove { path: child_path(path, key) });
+            }
+            let mut keys: Vec<&String> = new_dict.keys().collect();
+            keys.sort();
+            for key in keys {
+                let value = &new_dict[key];
+                match old_dict.get(key) {
+                    Some(previous) => diff_into(previous, value, &child_path(path, key), ops),
+                    None => ops.push(PatchOp::Add { path: child_path(path, key), value: value.clone() }),
+                }
+            }
+        }
+        (Node::List(old_list), Node::List(new_list)) => {
+            let common = old_list.len().min(new_list.len());
+            for (index, (previous, value)) in old_list.iter().zip(new_list).enumerate() {
+                diff_into(previous, value, &child_path(path, &index.to_string()), ops);
+            }
+            // Remove from the end so earlier indexes stay valid
+            for index in (common..old_list.len()).rev() {
+                ops.push(PatchOp::Remove { path: child_path(path, &index.to_string()) });
+            }
+            for (index, value) in new_list.iter().enumerate().skip(common) {
+                ops.push(PatchOp::Add { path: child_path(path, &index.to_string()), value: value.clone() });
+            }
+        }
+        _ if old != new => ops.push(PatchOp::Replace { path: path.to_string(), value: new.clone() }),
+        _ => {}
+    }
+}
+
+/// Computes the patch turning one document into another.
+///
+/// Dictionaries are compared key by key and lists index by index, so a value
+/// changed deep inside a tree yields a single replace. Elements inserted in
+/// the middle of a list show up as changes to every later element. A
+/// dictionary with a key holding the path separator is replaced whole, as no
+/// path can name its entries.
+///
+/// # Arguments
+/// * `old` - The document as it was
+/// * `new` - The document as it should be
+///
+/// # Returns
+/// A patch that [`apply_patch`] turns `old` into `new` with; empty when they are equal
+///
+/// # Example
+/// ```
+/// use bencode_lib::{apply_patch, diff_to_patch, parse_bytes};
+///
+/// let old = parse_bytes(b"d8:announce3:url4:infod6:lengthi5e4:name1:xee").unwrap();
+/// let new = parse_bytes(b"d4:infod6:lengthi5e4:name1:y7:privatei1eee").unwrap();
+/// let patch = diff_to_patch(&old, &new);
+/// assert_eq!(patch.ops.len(), 3);
+/// let mut synced = old.clone();
+/// apply_patch(&mut synced, &patch).unwrap();
+/// assert_eq!(synced, new);
+/// ```
+pub fn diff_to_patch(old: &Node, new: &Node) -> Patch {
+    let mut ops = Vec::new();
+    diff_into(old, new, "", &mut ops);
+    Patch { ops }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    fn document() -> Node {
+        Node::from([
+            ("announce", Node::from("url")),
+            ("info", Node::from([("files", Node::from([1, 2, 3])), ("name", Node::from("x"))])),
+        ])
+    }
+
+    #[test]
+    fn patches_round_trip_through_bencode() {
+        let patch = Patch {
+            ops: vec![
+                PatchOp::Add { path: "info.private".to_string(), value: Node::from(1) },
+                PatchOp::Remove { path: "announce".to_string() },
+                PatchOp::Replace { path: "".to_string(), value: Node::from("x") },
+            ],
+        };
+        let encoded = patch.to_bytes().unwrap();
+        assert_eq!(Patch::from_bytes(&encoded), Ok(patch));
+        assert!(Patch::new().is_empty());
+    }
+
+    #[test]
+    fn malformed_patches_are_rejected() {
+        let error = Patch::from_bytes(b"d2:op3:adde").unwrap_err();
+        assert_eq!((error.kind(), error.path), (ErrorKind::InvalidPatch, None));
+        for data in [&b"ld2:op3:add4:path1:aee"[..], b"ld2:op4:move4:path1:aee", b"ld2:op6:removeee"] {
+            assert_eq!(Patch::from_bytes(data).unwrap_err().path.as_deref(), Some("0"));
+        }
+    }
+
+    #[test]
+    fn operations_follow_paths_into_lists_and_dictionaries() {
+        let mut node = document();
+        let patch = Patch {
+            ops: vec![
+                PatchOp::Add { path: "info.files.0".to_string(), value: Node::from(0) },
+                PatchOp::Add { path: "info.files.4".to_string(), value: Node::from(4) },
+                PatchOp::Remove { path: "info.files.1".to_string() },
+                PatchOp::Replace { path: "announce".to_string(), value: Node::from("new") },
+            ],
+        };
+        apply_patch(&mut node, &patch).unwrap();
+        assert_eq!(node.query("info.files"), Some(&Node::from([0, 2, 3, 4])));
+        assert_eq!(node.get("announce"), Some(&Node::from("new")));
+
+        let mut empty = Node::None;
+        apply_patch(&mut empty, &Patch { ops: vec![PatchOp::Add { path: "".to_string(), value: Node::from(1) }] })
+            .unwrap();
+        assert_eq!(empty, Node::from(1));
+    }
+
+    #[test]
+    fn failed_patches_leave_the_document_unchanged() {
+        let conflicts = [
+            PatchOp::Add { path: "announce".to_string(), value: Node::from(1) },
+            PatchOp::Add { path: "info.files.9".to_string(), value: Node::from(1) },
+            PatchOp::Add { path: "missing.key".to_string(), value: Node::from(1) },
+            PatchOp::Add { path: "".to_string(), value: Node::from(1) },
+            PatchOp::Remove { path: "info.size".to_string() },
+            PatchOp::Replace { path: "info.files.3".to_string(), value: Node::from(1) },
+        ];
+        for conflict in conflicts {
+            let mut node = document();
+            let path = conflict.path().to_string();
+            let patch = Patch { ops: vec![PatchOp::Remove { path: "info.name".to_string() }, conflict] };
+            let error = apply_patch(&mut node, &patch).unwrap_err();
+            assert_eq!((error.kind(), error.path), (ErrorKind::PatchConflict, Some(path)));
+            assert_eq!(node, document());
+        }
+    }
+
+    #[test]
+    fn diffs_turn_one_document_into_the_other() {
+        let old = document();
+        let changes = [
+            Node::from([
+                ("announce", Node::from("url")),
+                ("info", Node::from([("files", Node::from([1, 5])), ("length", Node::from(2))])),
+            ]),
+            Node::from([("info", Node::from([("files", Node::from([1, 2, 3, 4, 5])), ("name", Node::from("x"))]))]),
+            Node::from([("a.b", 1)]),
+            Node::from(["x"]),
+            old.clone(),
+        ];
+        for new in changes {
+            let patch = diff_to_patch(&old, &new);
+            let decoded = Patch::from_bytes(&patch.to_bytes().unwrap()).unwrap();
+            let mut synced = old.clone();
+            apply_patch(&mut synced, &decoded).unwrap();
+            assert_eq!(synced, new);
+        }
+        assert!(diff_to_patch(&old, &old).is_empty());
+        let replaced = diff_to_patch(&Node::from([("a.b", 1)]), &Node::from([("a.b", 2)]));
+        assert_eq!(replaced.ops, vec![PatchOp::Replace { path: "".to_string(), value: Node::from([("a.b", 2)]) }]);
+    }
+
+    #[test]
+    fn diffs_are_minimal_for_nested_changes() {
+        let mut new = document();
+        *new.query_mut("info.files.1").unwrap() = Node::from(9);
+        let patch = diff_to_patch(&document(), &new);
+        assert_eq!(patch.ops, vec![PatchOp::Replace { path: "info.files.1".to_string(), value: Node::from(9) }]);
+    }
+}
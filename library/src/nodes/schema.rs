@@ -0,0 +1,332 @@
+//! Schema inference from a corpus of documents.
+//!
+//! Bencoded formats met in the wild (tracker extensions, client resume files,
+//! DHT messages) are rarely documented. [`infer_schema`] walks a set of sample
+//! documents and records, for every key path, which types occur, how often a
+//! dictionary key is present, and the range of integers and lengths seen. The
+//! resulting [`Schema`] prints as a one line per path summary, and
+//! [`Schema::check`] tests further documents against it.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use core::fmt;
+
+use crate::error::messages::ERR_SCHEMA_MISMATCH;
+use crate::error::parse::ParseError;
+use crate::nodes::integer::Integer;
+use crate::nodes::node::Node;
+use crate::parser::extract::PATH_SEPARATOR;
+
+/// Path segment standing for every element of a list in printed schemas
+const ANY_ITEM: &str = "*";
+
+/// What was seen at one key path across a corpus.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    /// Number of values seen at this path
+    pub count: usize,
+    /// How many of them were integers
+    pub integers: usize,
+    /// How many were UTF-8 text strings
+    pub strings: usize,
+    /// How many were binary strings
+    pub bytes: usize,
+    /// How many were lists
+    pub lists: usize,
+    /// How many were dictionaries
+    pub dictionaries: usize,
+    /// Smallest and largest integer seen
+    pub integer_range: Option<(Integer, Integer)>,
+    /// Shortest and longest string seen, in bytes
+    pub string_lengths: Option<(usize, usize)>,
+    /// Fewest and most elements in a list
+    pub list_lengths: Option<(usize, usize)>,
+    /// Schema of list elements, merged over every index
+    pub items: Option<Box<Schema>>,
+    /// Schema of the value under each dictionary key; its `count` is the
+    /// number of dictionaries holding the key
+    pub keys: BTreeMap<String, Schema>,
+}
+
+/// Extends a range to include `value`
+fn widen<T: Ord + Copy>(range: &mut Option<(T, T)>, value: T) {
+    *range = Some(match *range {
+        Some((low, high)) => (low.min(value), high.max(value)),
+        None => (value, value),
+    });
+}
+
+/// Formats a labelled range, or a single value when both ends agree
+fn describe_range<T: fmt::Display + PartialEq>(label: &str, (low, high): (T, T)) -> String {
+    if low == high {
+        format!("{} {}", label, low)
+    } else {
+        format!("{} {}..{}", label, low, high)
+    }
+}
+
+/// Appends a segment to a key path
+fn child_path(parent: &str, segment: &str) -> String {
+    if parent.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{}{}{}", parent, PATH_SEPARATOR, segment)
+    }
+}
+
+impl Schema {
+    /// Adds one value to the statistics
+    fn add(&mut self, node: &Node) {
+        if let Node::Raw(_) = node
+            && let Ok(expanded) = node.expand_raw()
+        {
+            return self.add(&expanded);
+        }
+        if let Node::None = node {
+            return;
+        }
+        self.count += 1;
+        match node {
+            Node::Integer(value) => {
+                self.integers += 1;
+                widen(&mut self.integer_range, *value);
+            }
+            // Counted as integers, though the range cannot hold them
+            Node::BigInteger(_) => self.integers += 1,
+            Node::Str(text) => {
+                self.strings += 1;
+                widen(&mut self.string_lengths, text.len());
+            }
+            // Raw values that do not decode are counted as binary strings
+            Node::Bytes(bytes) | Node::Raw(bytes) => {
+                self.bytes += 1;
+                widen(&mut self.string_lengths, bytes.len());
+            }
+            Node::List(items) => {
+                self.lists += 1;
+                widen(&mut self.list_lengths, items.len());
+                if !items.is_empty() {
+                    let schema = self.items.get_or_insert_with(Default::default);
+                    items.iter().for_each(|item| schema.add(item));
+                }
+            }
+            Node::Dictionary(entries) => {
+                self.dictionaries += 1;
+                for (key, value) in entries {
+                    self.keys.entry(key.clone()).or_default().add(value);
+                }
+            }
+            Node::None => {}
+        }
+    }
+
+    /// Returns the fraction of dictionaries at this path holding `key`, from 0.0 to 1.0
+    pub fn presence(&self, key: &str) -> f64 {
+        match self.keys.get(key) {
+            Some(schema) if self.dictionaries > 0 => schema.count as f64 / self.dictionaries as f64,
+            _ => 0.0,
+        }
+    }
+
+    /// Returns true if every dictionary at this path held `key`
+    pub fn is_required(&self, key: &str) -> bool {
+        self.keys.get(key).is_some_and(|schema| schema.count == self.dictionaries)
+    }
+
+    /// Checks that a document has the structure of the corpus.
+    ///
+    /// Every value must have a type seen at its path (text and binary strings
+    /// count as one type) and every dictionary must hold the keys present in
+    /// all sampled dictionaries at its path. Keys never seen, and values
+    /// outside the recorded ranges, are accepted: a sample rarely shows the
+    /// full range of a field.
+    ///
+    /// # Arguments
+    /// * `node` - The document to check
+    ///
+    /// # Returns
+    /// * `Ok(())` - The document matches
+    /// * `Err(ParseError)` - The first mismatch, with the key path of the value
+    ///   or missing key in `path`
+    pub fn check(&self, node: &Node) -> Result<(), ParseError> {
+        self.check_at(node, "")
+    }
+
+    fn check_at(&self, node: &Node, path: &str) -> Result<(), ParseError> {
+        let mismatch = |path: &str| Err(ParseError::new(ERR_SCHEMA_MISMATCH, 0).with_path(path));
+        let seen = match node {
+            Node::Integer(_) | Node::BigInteger(_) => self.integers,
+            Node::Str(_) | Node::Bytes(_) => self.strings + self.bytes,
+            Node::List(_) => self.lists,
+            Node::Dictionary(_) => self.dictionaries,
+            Node::Raw(_) => {
+                return match node.expand_raw() {
+                    Ok(expanded) => self.check_at(&expanded, path),
+                    Err(_) if self.strings + self.bytes > 0 => Ok(()),
+                    Err(_) => mismatch(path),
+                };
+            }
+            Node::None => return Ok(()),
+        };
+        if seen == 0 {
+            return mismatch(path);
+        }
+        match node {
+            Node::List(items) => {
+                if let Some(schema) = &self.items {
+                    for (index, item) in items.iter().enumerate() {
+                        schema.check_at(item, &child_path(path, &index.to_string()))?;
+                    }
+                }
+            }
+            Node::Dictionary(entries) => {
+                for (key, schema) in &self.keys {
+                    match entries.get(key) {
+                        Some(value) => schema.check_at(value, &child_path(path, key))?,
+                        None if schema.count == self.dictionaries => return mismatch(&child_path(path, key)),
+                        None => {}
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Writes the line for this path and then those below it
+    fn write_lines(&self, f: &mut fmt::Formatter<'_>, path: &str, parents: Option<usize>) -> fmt::Result {
+        let kinds = [
+            (self.integers, "integer"),
+            (self.strings, "string"),
+            (self.bytes, "binary string"),
+            (self.lists, "list"),
+            (self.dictionaries, "dictionary"),
+        ];
+        let kinds: Vec<&str> = kinds.iter().filter(|(count, _)| *count > 0).map(|(_, name)| *name).collect();
+        let mut details = vec![kinds.join(" or ")];
+        details.extend(self.integer_range.map(|range| describe_range("values", range)));
+        details.extend(self.string_lengths.map(|range| describe_range("length", range)));
+        details.extend(self.list_lengths.map(|range| describe_range("items", range)));
+        if let Some(parents) = parents
+            && self.count < parents
+        {
+            details.push(format!("in {} of {}", self.count, parents));
+        }
+        writeln!(f, "{}: {}", if path.is_empty() { "(root)" } else { path }, details.join(", "))?;
+
+        if let Some(items) = &self.items {
+            items.write_lines(f, &child_path(path, ANY_ITEM), None)?;
+        }
+        for (key, schema) in &self.keys {
+            schema.write_lines(f, &child_path(path, key), Some(self.dictionaries))?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Schema {
+    /// Lists each key path with its types, ranges and, for keys not in every
+    /// dictionary, how often it is present. List elements appear under a `*` segment.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_lines(f, "", None)
+    }
+}
+
+/// Infers the schema shared by a corpus of documents.
+///
+/// # Arguments
+/// * `corpus` - Sample documents of one format
+///
+/// # Returns
+/// The types, key presence and value ranges seen at each key path
+///
+/// # Example
+/// ```
+/// use bencode_lib::{infer_schema, parse_bytes};
+///
+/// let corpus = [
+///     parse_bytes(b"d8:intervali900e5:peersli1ei2eee").unwrap(),
+///     parse_bytes(b"d8:intervali1800e5:peersle7:warning4:busye").unwrap(),
+/// ];
+/// let schema = infer_schema(&corpus);
+/// assert!(schema.is_required("interval"));
+/// assert_eq!(schema.presence("warning"), 0.5);
+/// assert_eq!(schema.to_string(), concat!(
+///     "(root): dictionary\n",
+///     "interval: integer, values 900..1800\n",
+///     "peers: list, items 0..2\n",
+///     "peers.*: integer, values 1..2\n",
+///     "warning: string, length 4, in 1 of 2\n",
+/// ));
+/// assert!(schema.check(&parse_bytes(b"d8:intervali60e5:peersl3:abcee").unwrap()).is_err());
+/// ```
+pub fn infer_schema(corpus: &[Node]) -> Schema {
+    let mut schema = Schema::default();
+    corpus.iter().for_each(|document| schema.add(document));
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    fn corpus() -> Vec<Node> {
+        vec![
+            Node::from([("info", Node::from([("length", 10), ("name", 1)])), ("announce", Node::from(1))]),
+            Node::from([
+                ("info", Node::from([("name", Node::Bytes(vec![0xff, 0x00]))])),
+                ("announce", Node::from("url")),
+            ]),
+        ]
+    }
+
+    #[test]
+    fn statistics_are_gathered_per_path() {
+        let schema = infer_schema(&corpus());
+        let info = &schema.keys["info"];
+        assert_eq!((schema.count, info.dictionaries), (2, 2));
+        assert_eq!(info.presence("length"), 0.5);
+        assert!(info.is_required("name") && !info.is_required("length") && !info.is_required("missing"));
+        let name = &info.keys["name"];
+        assert_eq!((name.integers, name.bytes, name.strings), (1, 1, 0));
+        assert_eq!((name.integer_range, name.string_lengths), (Some((1, 1)), Some((2, 2))));
+        assert_eq!(infer_schema(&[]), Schema::default());
+    }
+
+    #[test]
+    fn raw_values_are_decoded_and_none_is_skipped() {
+        let raw = Node::raw(b"li5ee".to_vec()).unwrap();
+        let schema = infer_schema(&[raw, Node::None, Node::Raw(b"i1".to_vec())]);
+        assert_eq!((schema.count, schema.lists, schema.bytes), (2, 1, 1));
+        assert_eq!(schema.items.as_ref().unwrap().integer_range, Some((5, 5)));
+    }
+
+    #[test]
+    fn checks_report_the_path_of_the_mismatch() {
+        let schema = infer_schema(&corpus());
+        for document in corpus() {
+            assert_eq!(schema.check(&document), Ok(()));
+        }
+        // Unseen keys and out of range values are accepted; text may be binary
+        let extra = Node::from([("info", Node::from([("name", "x"), ("x", "y")])), ("announce", Node::from(99))]);
+        assert_eq!(schema.check(&extra), Ok(()));
+
+        let missing = Node::from([("announce", Node::from(1)), ("info", Node::from([("length", 1)]))]);
+        let error = schema.check(&missing).unwrap_err();
+        assert_eq!((error.kind(), error.path.as_deref()), (ErrorKind::SchemaMismatch, Some("info.name")));
+        let wrong_type = Node::from([("announce", Node::from(["a"])), ("info", Node::from([("name", 1)]))]);
+        assert_eq!(schema.check(&wrong_type).unwrap_err().path.as_deref(), Some("announce"));
+        assert_eq!(schema.check(&Node::from(1)).unwrap_err().path.as_deref(), Some(""));
+    }
+}
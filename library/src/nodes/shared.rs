@@ -0,0 +1,322 @@
+//! Cheaply cloneable, copy-on-write node tree for sharing between threads.
+//!
+//! [`ArcNode`] mirrors [`Node`] but keeps strings, lists and dictionaries behind
+//! `Arc`, so cloning a whole parsed torrent (or any subtree of it) only bumps a
+//! reference count. Trees are `Send + Sync` and can be handed to many reader
+//! threads without deep copies.
+//!
+//! Trees can still be edited: the `_mut` accessors are copy-on-write, cloning only
+//! the containers on the path to the change (and only if they are shared), so an
+//! edited copy keeps sharing every untouched subtree with the original.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, sync::Arc, vec::Vec};
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+use crate::HashMap;
+use crate::nodes::integer::Integer;
+use crate::nodes::node::Node;
+
+/// A node whose subtrees are reference counted and copied on write.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_bytes, ArcNode};
+///
+/// let tree = ArcNode::from(parse_bytes(b"d4:infod4:name4:demoee").unwrap());
+/// let info = tree.get("info").unwrap().clone(); // O(1)
+/// let reader = std::thread::spawn(move || info.get("name").and_then(|n| n.as_string().map(String::from)));
+/// assert_eq!(reader.join().unwrap().as_deref(), Some("demo"));
+///
+/// // Editing a clone copies only the path to the change
+/// let mut edited = tree.clone();
+/// *edited.get_mut("info").unwrap().get_mut("name").unwrap() = ArcNode::Str("new".into());
+/// assert_eq!(tree.get("info").unwrap().get("name").unwrap().as_string(), Some("demo"));
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum ArcNode {
+    /// A signed integer value
+    Integer(Integer),
+    /// The decimal digits of an integer too large for [`Integer`]
+    BigInteger(Arc<str>),
+    /// A shared string value
+    Str(Arc<str>),
+    /// A shared byte string that is not valid UTF-8
+    Bytes(Arc<[u8]>),
+    /// A shared list of nodes
+    List(Arc<Vec<ArcNode>>),
+    /// A shared dictionary of string keys to nodes
+    Dictionary(Arc<HashMap<String, ArcNode>>),
+    /// An empty or uninitialized node
+    None,
+}
+
+impl ArcNode {
+    /// Returns true if the node is an Integer variant
+    pub fn is_integer(&self) -> bool {
+        matches!(self, ArcNode::Integer(_))
+    }
+
+    /// Returns true if the node is a String variant
+    pub fn is_string(&self) -> bool {
+        matches!(self, ArcNode::Str(_))
+    }
+
+    /// Returns true if the node is a Bytes variant
+    pub fn is_bytes(&self) -> bool {
+        matches!(self, ArcNode::Bytes(_))
+    }
+
+    /// Returns true if the node is a List variant
+    pub fn is_list(&self) -> bool {
+        matches!(self, ArcNode::List(_))
+    }
+
+    /// Returns true if the node is a Dictionary variant
+    pub fn is_dictionary(&self) -> bool {
+        matches!(self, ArcNode::Dictionary(_))
+    }
+
+    /// Returns true if the node is a None variant
+    pub fn is_none(&self) -> bool {
+        matches!(self, ArcNode::None)
+    }
+
+    /// Returns a reference to the inner integer value if this is an Integer node
+    pub fn as_integer(&self) -> Option<&Integer> {
+        match self {
+            ArcNode::Integer(i) => Some(i),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner string if this is a Str node
+    pub fn as_string(&self) -> Option<&str> {
+        match self {
+            ArcNode::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes of a Str or Bytes node
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            ArcNode::Str(s) => Some(s.as_bytes()),
+            ArcNode::Bytes(b) => Some(b),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner list if this is a List node
+    pub fn as_list(&self) -> Option<&[ArcNode]> {
+        match self {
+            ArcNode::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner dictionary if this is a Dictionary node
+    pub fn as_dictionary(&self) -> Option<&HashMap<String, ArcNode>> {
+        match self {
+            ArcNode::Dictionary(dict) => Some(dict),
+            _ => None,
+        }
+    }
+
+    /// Gets a value from a Dictionary node by key
+    pub fn get(&self, key: &str) -> Option<&ArcNode> {
+        self.as_dictionary().and_then(|dict| dict.get(key))
+    }
+
+    /// Gets an element of a List node by index
+    pub fn index(&self, index: usize) -> Option<&ArcNode> {
+        self.as_list().and_then(|list| list.get(index))
+    }
+
+    /// Returns the inner list for modification if this is a List node.
+    ///
+    /// If the list is shared with other clones it is first copied (shallowly, so
+    /// its elements stay shared); other clones never observe the change.
+    pub fn as_list_mut(&mut self) -> Option<&mut Vec<ArcNode>> {
+        match self {
+            ArcNode::List(list) => Some(Arc::make_mut(list)),
+            _ => None,
+        }
+    }
+
+    /// Returns the inner dictionary for modification if this is a Dictionary node.
+    ///
+    /// If the dictionary is shared with other clones it is first copied (shallowly,
+    /// so its values stay shared); other clones never observe the change.
+    pub fn as_dictionary_mut(&mut self) -> Option<&mut HashMap<String, ArcNode>> {
+        match self {
+            ArcNode::Dictionary(dict) => Some(Arc::make_mut(dict)),
+            _ => None,
+        }
+    }
+
+    /// Gets a value from a Dictionary node by key for modification (copy-on-write)
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut ArcNode> {
+        self.as_dictionary_mut().and_then(|dict| dict.get_mut(key))
+    }
+
+    /// Gets an element of a List node by index for modification (copy-on-write)
+    pub fn index_mut(&mut self, index: usize) -> Option<&mut ArcNode> {
+        self.as_list_mut().and_then(|list| list.get_mut(index))
+    }
+
+    /// Returns true if both nodes share the same allocation (clones of one another).
+    ///
+    /// Integers and None have no allocation and are never considered shared.
+    pub fn ptr_eq(&self, other: &ArcNode) -> bool {
+        match (self, other) {
+            (ArcNode::Str(a), ArcNode::Str(b)) => Arc::ptr_eq(a, b),
+            (ArcNode::Bytes(a), ArcNode::Bytes(b)) => Arc::ptr_eq(a, b),
+            (ArcNode::List(a), ArcNode::List(b)) => Arc::ptr_eq(a, b),
+            (ArcNode::Dictionary(a), ArcNode::Dictionary(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Converts back to an owned, mutable `Node` tree (a deep copy).
+    pub fn to_node(&self) -> Node {
+        match self {
+            ArcNode::Integer(i) => Node::Integer(*i),
+            ArcNode::BigInteger(digits) => Node::BigInteger(String::from(&**digits)),
+            ArcNode::Str(s) => Node::Str(String::from(&**s)),
+            ArcNode::Bytes(b) => Node::Bytes(b.to_vec()),
+            ArcNode::List(list) => Node::List(list.iter().map(ArcNode::to_node).collect()),
+            ArcNode::Dictionary(dict) => {
+                Node::Dictionary(dict.iter().map(|(k, v)| (k.clone(), v.to_node())).collect())
+            }
+            ArcNode::None => Node::None,
+        }
+    }
+}
+
+impl From<&Node> for ArcNode {
+    fn from(node: &Node) -> Self {
+        match node {
+            Node::Integer(i) => ArcNode::Integer(*i),
+            Node::BigInteger(digits) => ArcNode::BigInteger(Arc::from(digits.as_str())),
+            Node::Str(s) => ArcNode::Str(Arc::from(s.as_str())),
+            Node::Bytes(b) => ArcNode::Bytes(Arc::from(b.as_slice())),
+            Node::List(list) => ArcNode::List(Arc::new(list.iter().map(ArcNode::from).collect())),
+            Node::Dictionary(dict) => {
+                ArcNode::Dictionary(Arc::new(dict.iter().map(|(k, v)| (k.clone(), ArcNode::from(v))).collect()))
+            }
+            Node::Raw(_) => node.decode_raw().map_or(ArcNode::None, ArcNode::from),
+            Node::None => ArcNode::None,
+        }
+    }
+}
+
+impl From<Node> for ArcNode {
+    fn from(node: Node) -> Self {
+        match node {
+            Node::Integer(i) => ArcNode::Integer(i),
+            Node::BigInteger(digits) => ArcNode::BigInteger(Arc::from(digits)),
+            Node::Str(s) => ArcNode::Str(Arc::from(s)),
+            Node::Bytes(b) => ArcNode::Bytes(Arc::from(b)),
+            Node::List(list) => ArcNode::List(Arc::new(list.into_iter().map(ArcNode::from).collect())),
+            Node::Dictionary(dict) => {
+                ArcNode::Dictionary(Arc::new(dict.into_iter().map(|(k, v)| (k, ArcNode::from(v))).collect()))
+            }
+            Node::Raw(_) => node.decode_raw().map_or(ArcNode::None, ArcNode::from),
+            Node::None => ArcNode::None,
+        }
+    }
+}
+
+impl From<&ArcNode> for Node {
+    fn from(node: &ArcNode) -> Self {
+        node.to_node()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::default::parse_bytes;
+
+    const DATA: &[u8] = b"d4:infod6:lengthi5e4:name4:demoe4:listli1e3:twoee";
+
+    #[test]
+    fn round_trips_through_node() {
+        let node = parse_bytes(DATA).unwrap();
+        let shared = ArcNode::from(&node);
+        assert_eq!(shared.to_node(), node);
+        assert_eq!(Node::from(&ArcNode::from(node.clone())), node);
+    }
+
+    #[test]
+    fn accessors_mirror_node() {
+        let shared = ArcNode::from(parse_bytes(DATA).unwrap());
+        assert!(shared.is_dictionary());
+        let info = shared.get("info").unwrap();
+        assert_eq!(info.get("length").and_then(ArcNode::as_integer), Some(&5));
+        assert_eq!(info.get("name").and_then(ArcNode::as_string), Some("demo"));
+        let list = shared.get("list").unwrap();
+        assert_eq!(list.as_list().map(<[ArcNode]>::len), Some(2));
+        assert_eq!(list.index(1).and_then(ArcNode::as_string), Some("two"));
+        assert!(list.index(2).is_none());
+        assert!(shared.get("missing").is_none());
+        assert!(ArcNode::None.is_none());
+    }
+
+    #[test]
+    fn clone_shares_subtrees() {
+        let shared = ArcNode::from(parse_bytes(DATA).unwrap());
+        let copy = shared.clone();
+        assert!(copy.ptr_eq(&shared));
+        assert!(copy.get("info").unwrap().ptr_eq(shared.get("info").unwrap()));
+        let rebuilt = ArcNode::from(shared.to_node());
+        assert_eq!(rebuilt, shared);
+        assert!(!rebuilt.ptr_eq(&shared));
+    }
+
+    #[test]
+    fn readers_on_many_threads() {
+        let shared = ArcNode::from(parse_bytes(DATA).unwrap());
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                let tree = shared.clone();
+                std::thread::spawn(move || tree.get("info").and_then(|i| i.get("length")).cloned())
+            })
+            .collect();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), Some(ArcNode::Integer(5)));
+        }
+    }
+
+    #[test]
+    fn mutation_copies_only_the_edited_path() {
+        let original = ArcNode::from(parse_bytes(DATA).unwrap());
+        let mut edited = original.clone();
+        *edited.get_mut("info").unwrap().get_mut("length").unwrap() = ArcNode::Integer(9);
+        edited.get_mut("list").unwrap().as_list_mut().unwrap().push(ArcNode::None);
+
+        assert_eq!(original.to_node(), parse_bytes(DATA).unwrap());
+        assert_eq!(edited.get("info").unwrap().get("length"), Some(&ArcNode::Integer(9)));
+        assert_eq!(edited.get("list").unwrap().as_list().unwrap().len(), 3);
+        assert!(!edited.ptr_eq(&original));
+        assert!(!edited.get("info").unwrap().ptr_eq(original.get("info").unwrap()));
+        // Untouched siblings are still shared
+        let name = |tree: &ArcNode| tree.get("info").unwrap().get("name").unwrap().clone();
+        assert!(name(&edited).ptr_eq(&name(&original)));
+        let two = |tree: &ArcNode| tree.get("list").unwrap().index(1).unwrap().clone();
+        assert!(two(&edited).ptr_eq(&two(&original)));
+    }
+
+    #[test]
+    fn unshared_mutation_does_not_copy() {
+        let mut tree = ArcNode::from(parse_bytes(DATA).unwrap());
+        let list = tree.get_mut("list").unwrap();
+        let address = list.as_list().unwrap().as_ptr();
+        *list.index_mut(0).unwrap() = ArcNode::Integer(7);
+        assert_eq!(list.as_list().unwrap().as_ptr(), address);
+        assert!(tree.index_mut(0).is_none());
+        assert!(ArcNode::Integer(1).get_mut("x").is_none());
+    }
+}
@@ -0,0 +1,85 @@
+//! Classification of bencode byte strings as ASCII, UTF-8 or binary.
+//!
+//! Bencode strings are raw bytes. Most hold text, but fields such as `pieces` hold
+//! binary hashes and badly encoded torrents put legacy code page text in `name`.
+//! [`StringKind`] lets converters pick a representation and validators flag
+//! fields that should be text but are not.
+
+/// What a byte string holds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StringKind {
+    /// Every byte is 7-bit ASCII
+    Ascii,
+    /// Valid UTF-8 containing at least one non-ASCII character
+    Utf8,
+    /// Not valid UTF-8
+    Binary,
+}
+
+impl StringKind {
+    /// Classifies raw bytes.
+    ///
+    /// # Arguments
+    /// * `bytes` - The byte string to classify
+    ///
+    /// # Example
+    /// ```
+    /// use bencode_lib::StringKind;
+    ///
+    /// assert_eq!(StringKind::of_bytes(b"name"), StringKind::Ascii);
+    /// assert_eq!(StringKind::of_bytes("caf\u{e9}".as_bytes()), StringKind::Utf8);
+    /// assert_eq!(StringKind::of_bytes(b"\xff\xfe"), StringKind::Binary);
+    /// ```
+    pub fn of_bytes(bytes: &[u8]) -> StringKind {
+        if bytes.is_ascii() {
+            StringKind::Ascii
+        } else if core::str::from_utf8(bytes).is_ok() {
+            StringKind::Utf8
+        } else {
+            StringKind::Binary
+        }
+    }
+
+    /// Classifies a string, which being valid UTF-8 is never binary.
+    ///
+    /// The owning parsers keep strings that are not UTF-8 as `Node::Bytes`, so
+    /// a `U+FFFD` replacement character in a string was in the input.
+    ///
+    /// # Arguments
+    /// * `value` - The string to classify
+    pub fn of_str(value: &str) -> StringKind {
+        if value.is_ascii() {
+            StringKind::Ascii
+        } else {
+            StringKind::Utf8
+        }
+    }
+
+    /// Returns true for ASCII and UTF-8 strings
+    pub fn is_text(&self) -> bool {
+        !matches!(self, StringKind::Binary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_bytes() {
+        assert_eq!(StringKind::of_bytes(b""), StringKind::Ascii);
+        assert_eq!(StringKind::of_bytes(b"hello\n"), StringKind::Ascii);
+        assert_eq!(StringKind::of_bytes(b"\xc3\xa9"), StringKind::Utf8);
+        assert_eq!(StringKind::of_bytes(b"\xc3"), StringKind::Binary);
+        assert_eq!(StringKind::of_bytes(b"ok\xe9"), StringKind::Binary);
+    }
+
+    #[test]
+    fn strings_are_text() {
+        assert_eq!(StringKind::of_str("plain"), StringKind::Ascii);
+        assert_eq!(StringKind::of_str("\u{e9}t\u{e9}"), StringKind::Utf8);
+        assert_eq!(StringKind::of_str("ok\u{fffd}"), StringKind::Utf8);
+        assert!(!StringKind::Binary.is_text());
+        assert!(StringKind::Utf8.is_text());
+    }
+}
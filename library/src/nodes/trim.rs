@@ -0,0 +1,180 @@
+//! Trimming of low priority values until a tree fits an encoded size budget.
+//!
+//! Messages with a hard size limit, such as a DHT value or a peer extension
+//! message, can carry a torrent's metadata only if it is small enough.
+//! [`trim_to_size`] applies the rules of a [`TrimPolicy`] in order, each
+//! removing or cutting back one value, and stops as soon as the encoding fits.
+
+use crate::error::messages::ERR_OUTPUT_LIMIT;
+use crate::error::parse::ParseError;
+use crate::nodes::fixed::MemoryBounds;
+use crate::nodes::node::Node;
+
+/// One way of making a tree smaller, applied to the value at a key path.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimRule<'a> {
+    /// Remove the value and its dictionary key
+    Remove(&'a str),
+    /// Drop elements from the end of a list, one at a time, until the tree fits
+    Truncate(&'a str),
+    /// Cut a string short, at a character boundary for text, until the tree fits
+    Shorten(&'a str),
+}
+
+/// Rules applied by [`trim_to_size`], lowest priority value first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrimPolicy<'a> {
+    /// Rules in the order they are tried (default: none)
+    pub rules: &'a [TrimRule<'a>],
+}
+
+impl Default for TrimPolicy<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> TrimPolicy<'a> {
+    /// Rules for torrent metadata: the comment goes first, then web seeds, then
+    /// backup tracker tiers, leaving the main tracker and the info dictionary
+    pub const TORRENT: TrimPolicy<'static> = TrimPolicy::new().with_rules(&[
+        TrimRule::Remove("comment"),
+        TrimRule::Truncate("url-list"),
+        TrimRule::Remove("url-list"),
+        TrimRule::Truncate("announce-list"),
+    ]);
+
+    /// Create a policy with no rules
+    pub const fn new() -> Self {
+        Self { rules: &[] }
+    }
+
+    /// Set the rules, lowest priority value first
+    pub const fn with_rules(mut self, rules: &'a [TrimRule<'a>]) -> Self {
+        self.rules = rules;
+        self
+    }
+}
+
+/// Trims a tree until its encoding is at most `max_encoded_len` bytes.
+///
+/// Rules whose path does not exist, or holds a value of another type, are
+/// skipped. When every rule has been applied and the tree is still too large,
+/// the changes made so far are kept.
+///
+/// # Arguments
+/// * `node` - The tree to trim
+/// * `max_encoded_len` - Budget for the encoded tree in bytes
+/// * `policy` - What may be removed or cut, in order
+///
+/// # Returns
+/// * `Ok(usize)` - The encoded size of the trimmed tree
+/// * `Err(ParseError)` - If the tree does not fit once every rule is applied;
+///   the offset is the budget
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_bytes, stringify_to_bytes, trim_to_size, TrimPolicy};
+///
+/// let mut torrent = parse_bytes(b"d8:announce1:a13:announce-listll1:aell1:bee7:comment4:textee").unwrap();
+/// assert_eq!(trim_to_size(&mut torrent, 40, &TrimPolicy::TORRENT), Ok(38));
+/// assert_eq!(stringify_to_bytes(&torrent).unwrap(), b"d8:announce1:a13:announce-listll1:aeee");
+/// ```
+pub fn trim_to_size(node: &mut Node, max_encoded_len: usize, policy: &TrimPolicy) -> Result<usize, ParseError> {
+    let mut size = MemoryBounds::stringify_estimate(node);
+    for rule in policy.rules {
+        if size <= max_encoded_len {
+            break;
+        }
+        match *rule {
+            TrimRule::Remove(path) => {
+                if node.extract(path).is_some() {
+                    size = MemoryBounds::stringify_estimate(node);
+                }
+            }
+            TrimRule::Truncate(path) => {
+                if let Some(Node::List(items)) = node.query_mut(path) {
+                    // List framing does not depend on its length
+                    while size > max_encoded_len
+                        && let Some(item) = items.pop()
+                    {
+                        size -= MemoryBounds::stringify_estimate(&item);
+                    }
+                }
+            }
+            TrimRule::Shorten(path) => {
+                let excess = size - max_encoded_len;
+                match node.query_mut(path) {
+                    Some(Node::Str(text)) => {
+                        let mut keep = text.len().saturating_sub(excess);
+                        while !text.is_char_boundary(keep) {
+                            keep -= 1;
+                        }
+                        text.truncate(keep);
+                    }
+                    Some(Node::Bytes(bytes)) => bytes.truncate(bytes.len().saturating_sub(excess)),
+                    _ => continue,
+                }
+                size = MemoryBounds::stringify_estimate(node);
+            }
+        }
+    }
+    if size > max_encoded_len {
+        return Err(ParseError::new(ERR_OUTPUT_LIMIT, max_encoded_len));
+    }
+    Ok(size)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+    use crate::stringify::default::stringify_to_bytes;
+
+    fn torrent() -> Node {
+        Node::from([
+            ("announce", Node::from("a")),
+            ("announce-list", Node::from(vec![Node::from(["a"]), Node::from(["b"]), Node::from(["c"])])),
+            ("comment", Node::from("text")),
+            ("info", Node::from([("name", Node::from("x"))])),
+            ("url-list", Node::from(["w1", "w2"])),
+        ])
+    }
+
+    #[test]
+    fn rules_stop_as_soon_as_the_tree_fits() {
+        let full = stringify_to_bytes(&torrent()).unwrap().len();
+        let mut node = torrent();
+        assert_eq!(trim_to_size(&mut node, full, &TrimPolicy::TORRENT), Ok(full));
+        assert_eq!(node, torrent());
+
+        // Removing the comment (15 bytes) and one web seed (4 bytes) is enough
+        let mut node = torrent();
+        assert_eq!(trim_to_size(&mut node, full - 17, &TrimPolicy::TORRENT), Ok(full - 19));
+        assert_eq!(node.query("url-list"), Some(&Node::from(["w1"])));
+        assert!(node.query("comment").is_none());
+        assert_eq!(stringify_to_bytes(&node).unwrap().len(), full - 19);
+    }
+
+    #[test]
+    fn trees_that_cannot_fit_keep_the_changes_made() {
+        let mut node = torrent();
+        let error = trim_to_size(&mut node, 10, &TrimPolicy::TORRENT).unwrap_err();
+        assert_eq!((error.kind(), error.offset), (ErrorKind::OutputLimit, 10));
+        assert_eq!(node.query("announce-list"), Some(&Node::from(Vec::<Node>::new())));
+        assert!(node.query("info.name").is_some());
+    }
+
+    #[test]
+    fn strings_are_shortened_at_character_boundaries() {
+        let rules = [TrimRule::Shorten("text"), TrimRule::Shorten("missing")];
+        let policy = TrimPolicy::new().with_rules(&rules);
+        let mut node = Node::from([("text", Node::from("caf\u{e9}s"))]);
+        // Cutting two bytes would split the two byte 'é'
+        assert_eq!(trim_to_size(&mut node, 14, &policy), Ok(13));
+        assert_eq!(node.query("text"), Some(&Node::from("caf")));
+        let mut node = Node::from([("text", Node::Bytes(vec![0xff; 12]))]);
+        assert_eq!(trim_to_size(&mut node, 10, &policy), Ok(10));
+        assert_eq!(node.query("text"), Some(&Node::Bytes(Vec::new())));
+    }
+}
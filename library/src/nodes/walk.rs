@@ -0,0 +1,249 @@
+//! Depth-first traversal of node trees.
+//!
+//! [`Node::walk`] calls a [`NodeVisitor`] for every value of a tree, entering
+//! and leaving each list and dictionary around its children, and
+//! [`Node::walk_mut`] does the same with a [`NodeVisitorMut`] that may change
+//! values in place. Each callback gets the key path of its value in the
+//! syntax of [`Node::query`], so consumers no longer need their own
+//! recursion. Dictionary entries are visited in key order.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::Dictionary;
+use crate::nodes::node::{Node, sorted_entries};
+use crate::nodes::patch::child_path;
+
+/// Receives the values of a tree from [`Node::walk`].
+///
+/// All methods default to doing nothing (and to descending into containers),
+/// so implementations only override the callbacks they need.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_bytes, Node, NodeVisitor};
+///
+/// /// Collects the paths of every string in a tree
+/// struct Strings(Vec<String>);
+///
+/// impl NodeVisitor for Strings {
+///     fn visit_value(&mut self, path: &str, node: &Node) {
+///         if let Node::Str(_) = node {
+///             self.0.push(path.to_string());
+///         }
+///     }
+/// }
+///
+/// let torrent = parse_bytes(b"d4:infod5:filesld4:pathl1:a1:beee4:name1:xee").unwrap();
+/// let mut strings = Strings(Vec::new());
+/// torrent.walk(&mut strings);
+/// assert_eq!(strings.0, ["info.files.0.path.0", "info.files.0.path.1", "info.name"]);
+/// ```
+pub trait NodeVisitor {
+    /// Called for every value that is not a list or dictionary
+    fn visit_value(&mut self, _path: &str, _node: &Node) {}
+
+    /// Called before the elements of a list; returning false skips them and `leave_list`
+    fn enter_list(&mut self, _path: &str, _list: &[Node]) -> bool {
+        true
+    }
+
+    /// Called after the elements of a list
+    fn leave_list(&mut self, _path: &str, _list: &[Node]) {}
+
+    /// Called before the entries of a dictionary; returning false skips them and `leave_dict`
+    fn enter_dict(&mut self, _path: &str, _dict: &Dictionary) -> bool {
+        true
+    }
+
+    /// Called after the entries of a dictionary
+    fn leave_dict(&mut self, _path: &str, _dict: &Dictionary) {}
+}
+
+/// Receives the values of a tree from [`Node::walk_mut`], which it may change in place.
+///
+/// Containers are entered before their children are visited, so changes made
+/// in `enter_*` decide which children are walked, and left afterwards, so
+/// `leave_*` sees the children as already changed.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_bytes, Dictionary, Node, NodeVisitorMut};
+///
+/// /// Removes private keys and doubles every integer
+/// struct Scrub;
+///
+/// impl NodeVisitorMut for Scrub {
+///     fn visit_value(&mut self, _path: &str, node: &mut Node) {
+///         if let Node::Integer(value) = node {
+///             *value *= 2;
+///         }
+///     }
+///
+///     fn enter_dict(&mut self, _path: &str, dict: &mut Dictionary) -> bool {
+///         dict.retain(|key, _| !key.starts_with('_'));
+///         true
+///     }
+/// }
+///
+/// let mut node = parse_bytes(b"d2:_bi2e1:ai1e1:cli3eee").unwrap();
+/// node.walk_mut(&mut Scrub);
+/// assert_eq!(node, parse_bytes(b"d1:ai2e1:cli6eee").unwrap());
+/// ```
+pub trait NodeVisitorMut {
+    /// Called for every value that is not a list or dictionary
+    fn visit_value(&mut self, _path: &str, _node: &mut Node) {}
+
+    /// Called before the elements of a list; returning false skips them and `leave_list`
+    fn enter_list(&mut self, _path: &str, _list: &mut Vec<Node>) -> bool {
+        true
+    }
+
+    /// Called after the elements of a list
+    fn leave_list(&mut self, _path: &str, _list: &mut Vec<Node>) {}
+
+    /// Called before the entries of a dictionary; returning false skips them and `leave_dict`
+    fn enter_dict(&mut self, _path: &str, _dict: &mut Dictionary) -> bool {
+        true
+    }
+
+    /// Called after the entries of a dictionary
+    fn leave_dict(&mut self, _path: &str, _dict: &mut Dictionary) {}
+}
+
+/// Visits `node`, found at `path`, and everything below it
+pub(crate) fn walk<V: NodeVisitor + ?Sized>(node: &Node, path: &str, visitor: &mut V) {
+    match node {
+        Node::List(list) => {
+            if visitor.enter_list(path, list) {
+                for (index, item) in list.iter().enumerate() {
+                    walk(item, &child_path(path, &index.to_string()), visitor);
+                }
+                visitor.leave_list(path, list);
+            }
+        }
+        Node::Dictionary(dict) => {
+            if visitor.enter_dict(path, dict) {
+                for (key, value) in node.iter_sorted() {
+                    walk(value, &child_path(path, key), visitor);
+                }
+                visitor.leave_dict(path, dict);
+            }
+        }
+        _ => visitor.visit_value(path, node),
+    }
+}
+
+/// Visits `node`, found at `path`, and everything below it, allowing changes
+pub(crate) fn walk_mut<V: NodeVisitorMut + ?Sized>(node: &mut Node, path: &str, visitor: &mut V) {
+    match node {
+        Node::List(list) => {
+            if visitor.enter_list(path, list) {
+                for (index, item) in list.iter_mut().enumerate() {
+                    walk_mut(item, &child_path(path, &index.to_string()), visitor);
+                }
+                visitor.leave_list(path, list);
+            }
+        }
+        Node::Dictionary(dict) => {
+            if visitor.enter_dict(path, dict) {
+                let keys: Vec<String> = sorted_entries(dict).into_iter().map(|(key, _)| key.clone()).collect();
+                for key in keys {
+                    if let Some(value) = dict.get_mut(&key) {
+                        walk_mut(value, &child_path(path, &key), visitor);
+                    }
+                }
+                visitor.leave_dict(path, dict);
+            }
+        }
+        _ => visitor.visit_value(path, node),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::default::parse_bytes;
+
+    /// Records every callback as a line
+    #[derive(Default)]
+    struct Trace(Vec<String>);
+
+    impl NodeVisitor for Trace {
+        fn visit_value(&mut self, path: &str, node: &Node) {
+            self.0.push(format!("value {} {}", path, node));
+        }
+
+        fn enter_list(&mut self, path: &str, list: &[Node]) -> bool {
+            self.0.push(format!("enter list {} {}", path, list.len()));
+            path != "skip"
+        }
+
+        fn leave_list(&mut self, path: &str, _list: &[Node]) {
+            self.0.push(format!("leave list {}", path));
+        }
+
+        fn enter_dict(&mut self, path: &str, _dict: &Dictionary) -> bool {
+            self.0.push(format!("enter dict {}", path));
+            true
+        }
+
+        fn leave_dict(&mut self, path: &str, _dict: &Dictionary) {
+            self.0.push(format!("leave dict {}", path));
+        }
+    }
+
+    #[test]
+    fn walk_visits_every_value_with_its_path() {
+        let node = parse_bytes(b"d1:a1:x1:bli1ed1:ci2eee4:skipli3eee").unwrap();
+        let mut trace = Trace::default();
+        node.walk(&mut trace);
+        assert_eq!(
+            trace.0,
+            [
+                "enter dict ",
+                "value a \"x\"",
+                "enter list b 2",
+                "value b.0 1",
+                "enter dict b.1",
+                "value b.1.c 2",
+                "leave dict b.1",
+                "leave list b",
+                "enter list skip 1",
+                "leave dict ",
+            ]
+        );
+    }
+
+    #[test]
+    fn walk_mut_changes_values_in_place() {
+        /// Replaces integers by their paths, drops `drop` keys and counts list elements
+        struct Rewrite(usize);
+
+        impl NodeVisitorMut for Rewrite {
+            fn visit_value(&mut self, path: &str, node: &mut Node) {
+                if let Node::Integer(_) = node {
+                    *node = Node::from(path);
+                }
+            }
+
+            fn leave_list(&mut self, _path: &str, list: &mut Vec<Node>) {
+                self.0 += list.len();
+            }
+
+            fn enter_dict(&mut self, _path: &str, dict: &mut Dictionary) -> bool {
+                dict.remove("drop");
+                true
+            }
+        }
+
+        let mut node = parse_bytes(b"d1:ali1ei2ee1:bd1:ci4ee4:dropli3eee").unwrap();
+        let mut rewrite = Rewrite(0);
+        node.walk_mut(&mut rewrite);
+        assert_eq!(node, parse_bytes(b"d1:al3:a.03:a.1e1:bd1:c3:b.cee").unwrap());
+        assert_eq!(rewrite.0, 2);
+    }
+}
@@ -2,10 +2,7 @@
 //! This parser creates BorrowedNode structures that reference the input buffer
 //! without allocating or copying data, making it suitable for embedded systems.
 
-#[cfg(not(feature = "std"))]
-use alloc::collections::BTreeMap as HashMap;
-#[cfg(feature = "std")]
-use std::collections::HashMap;
+use crate::HashMap;
 
 #[cfg(not(feature = "std"))]
 use alloc::{
@@ -13,8 +10,13 @@ use alloc::{
     vec::Vec,
 };
 
+use crate::config::ParserConfig;
 use crate::error::messages::*;
+use crate::error::parse::ParseError;
+use crate::metrics::Timer;
 use crate::nodes::borrowed::BorrowedNode;
+use crate::nodes::integer::{parse_i64_text, parse_integer_text};
+use crate::parser::common::{Limits, unterminated_integer, unterminated_string_length};
 
 const BENCODE_INTEGER_START: u8 = b'i';
 const BENCODE_LIST_START: u8 = b'l';
@@ -33,7 +35,7 @@ const BENCODE_STRING_DELIMITER: u8 = b':';
 ///
 /// # Returns
 /// * `Ok(BorrowedNode)` - The parsed node tree with borrowed data
-/// * `Err(String)` - Description of the parsing error
+/// * `Err(ParseError)` - Description of the parsing error and its offset
 ///
 /// # Example
 /// ```
@@ -43,78 +45,145 @@ const BENCODE_STRING_DELIMITER: u8 = b':';
 /// let node = parse_borrowed(data).unwrap();
 /// assert_eq!(node.as_integer(), Some(42));
 /// ```
-pub fn parse_borrowed(input: &[u8]) -> Result<BorrowedNode, String> {
+pub fn parse_borrowed(input: &[u8]) -> Result<BorrowedNode<'_>, ParseError> {
+    let timer = Timer::start("parse_borrowed");
+    let result = parse_borrowed_prefix(input).map(|(node, _)| node);
+    timer.finish(Some(input.len()), &result);
+    result
+}
+
+/// Parses the value at the start of `input`, returning it with the offset
+/// just past it; any bytes from there on are left unread
+pub(crate) fn parse_borrowed_prefix(input: &[u8]) -> Result<(BorrowedNode<'_>, usize), ParseError> {
     let mut position = 0;
-    parse_node(input, &mut position)
+    let node = parse_node(input, &mut position, Expecting::Value, 0).map_err(|message| ParseError::new(message, position))?;
+    Ok((node, position))
 }
 
-/// Internal function to parse a single bencode node from the input
-fn parse_node<'a>(input: &'a [u8], position: &mut usize) -> Result<BorrowedNode<'a>, String> {
+/// Internal function to parse a single bencode node from the input; `expecting`
+/// and `depth` describe where the node appears, for error messages
+fn parse_node<'a>(
+    input: &'a [u8],
+    position: &mut usize,
+    expecting: Expecting,
+    depth: usize,
+) -> Result<BorrowedNode<'a>, String> {
     if *position >= input.len() {
         return Err(ERR_EMPTY_INPUT.to_string());
     }
 
     match input[*position] {
         BENCODE_INTEGER_START => parse_integer(input, position),
-        BENCODE_LIST_START => parse_list(input, position),
-        BENCODE_DICTIONARY_START => parse_dictionary(input, position),
+        BENCODE_LIST_START => parse_list(input, position, depth + 1),
+        BENCODE_DICTIONARY_START => parse_dictionary(input, position, depth + 1),
         b'0'..=b'9' => parse_bytes(input, position),
-        c => Err(unexpected_character(c as char)),
+        c => Err(unexpected_character(c as char, expecting, depth)),
     }
 }
 
-/// Parses a bencode integer (i<number>e)
-fn parse_integer<'a>(input: &'a [u8], position: &mut usize) -> Result<BorrowedNode<'a>, String> {
-    *position += 1; // Skip 'i'
+/// Decodes the integer at the start of `input`.
+///
+/// This and [`decode_bytes`] read single values with the same checks as the
+/// parsers, for protocol code that walks a known message layout by hand. The
+/// value is always read as an `i64`, even with the `int32` feature.
+///
+/// # Arguments
+/// * `input` - Bytes starting with the integer's `i`
+///
+/// # Returns
+/// * `Ok((i64, usize))` - The value and the number of bytes it took up
+/// * `Err(ParseError)` - If the input does not start with a valid integer
+///
+/// # Example
+/// ```
+/// use bencode_lib::decode_int;
+///
+/// assert_eq!(decode_int(b"i-42e4:rest").unwrap(), (-42, 5));
+/// assert!(decode_int(b"i-0e").is_err());
+/// ```
+pub fn decode_int(input: &[u8]) -> Result<(i64, usize), ParseError> {
+    match input.first() {
+        Some(&BENCODE_INTEGER_START) => {}
+        Some(_) => return Err(ParseError::new(ERR_INVALID_INTEGER, 0)),
+        None => return Err(ParseError::new(ERR_EMPTY_INPUT, 0)),
+    }
+    let (text, end) = integer_text(input, 1).map_err(|message| ParseError::new(message, 1))?;
+    let value = parse_i64_text(text).map_err(|message| ParseError::new(message, 1))?;
+    Ok((value, end + 1))
+}
 
-    let start = *position;
-    let mut end = start;
+/// Decodes the byte string at the start of `input` without copying it.
+///
+/// # Arguments
+/// * `input` - Bytes starting with the string's length
+///
+/// # Returns
+/// * `Ok((&[u8], usize))` - The string and the number of bytes it took up,
+///   length prefix included
+/// * `Err(ParseError)` - If the input does not start with a valid byte string
+///
+/// # Example
+/// ```
+/// use bencode_lib::decode_bytes;
+///
+/// assert_eq!(decode_bytes(b"4:spami1e").unwrap(), (&b"spam"[..], 6));
+/// assert!(decode_bytes(b"5:spam").is_err());
+/// ```
+pub fn decode_bytes(input: &[u8]) -> Result<(&[u8], usize), ParseError> {
+    if input.is_empty() {
+        return Err(ParseError::new(ERR_EMPTY_INPUT, 0));
+    }
+    let (length, colon) = string_length(input, 0).map_err(|message| ParseError::new(message, 0))?;
+    let end = (colon + 1)
+        .checked_add(length)
+        .filter(|&end| end <= input.len())
+        .ok_or_else(|| ParseError::new(ERR_STRING_TOO_SHORT, colon + 1))?;
+    Ok((&input[colon + 1..end], end))
+}
 
-    // Find the end marker
-    while end < input.len() && input[end] != BENCODE_END {
-        end += 1;
-    }
+/// Returns the text of the integer whose digits start at `start` and the index
+/// of the `e` ending it
+fn integer_text(input: &[u8], start: usize) -> Result<(&str, usize), &'static str> {
+    let end = input[start..]
+        .iter()
+        .position(|&byte| byte == BENCODE_END)
+        .map(|offset| start + offset)
+        .ok_or_else(|| unterminated_integer(&input[start..]))?;
+    let text = core::str::from_utf8(&input[start..end]).map_err(|_| ERR_INVALID_INTEGER)?;
+    Ok((text, end))
+}
 
-    if end >= input.len() {
-        return Err(ERR_UNTERMINATED_INTEGER.to_string());
-    }
+/// Returns the length of the byte string whose prefix starts at `start` and
+/// the index of the `:` ending the prefix
+fn string_length(input: &[u8], start: usize) -> Result<(usize, usize), &'static str> {
+    let colon = input[start..]
+        .iter()
+        .position(|&byte| byte == BENCODE_STRING_DELIMITER)
+        .map(|offset| start + offset)
+        .ok_or_else(|| unterminated_string_length(&input[start..]))?;
+    let length = core::str::from_utf8(&input[start..colon])
+        .ok()
+        .and_then(|text| text.parse::<usize>().ok())
+        .ok_or(ERR_INVALID_STRING_LENGTH)?;
+    Ok((length, colon))
+}
 
-    // Parse the integer from the slice
-    let int_slice = &input[start..end];
-    let int_str = core::str::from_utf8(int_slice).map_err(|_| ERR_INVALID_INTEGER.to_string())?;
+/// Parses a bencode integer (i<number>e)
+pub(crate) fn parse_integer<'a>(input: &'a [u8], position: &mut usize) -> Result<BorrowedNode<'a>, String> {
+    *position += 1; // Skip 'i'
 
-    let value = int_str
-        .parse::<i64>()
-        .map_err(|_| ERR_INVALID_INTEGER.to_string())?;
+    let (text, end) = integer_text(input, *position)?;
+    let value = parse_integer_text(text)?;
 
     *position = end + 1; // Skip 'e'
     Ok(BorrowedNode::Integer(value))
 }
 
 /// Parses a bencode byte string (<length>:<bytes>)
-fn parse_bytes<'a>(input: &'a [u8], position: &mut usize) -> Result<BorrowedNode<'a>, String> {
-    let start = *position;
-    let mut end = start;
+pub(crate) fn parse_bytes<'a>(input: &'a [u8], position: &mut usize) -> Result<BorrowedNode<'a>, String> {
+    let (length, colon) = string_length(input, *position)?;
 
-    // Find the colon delimiter
-    while end < input.len() && input[end] != BENCODE_STRING_DELIMITER {
-        end += 1;
-    }
-
-    if end >= input.len() {
-        return Err(ERR_INVALID_STRING_LENGTH.to_string());
-    }
-
-    // Parse the length
-    let length_slice = &input[start..end];
-    let length_str =
-        core::str::from_utf8(length_slice).map_err(|_| ERR_INVALID_STRING_LENGTH.to_string())?;
-
-    let length = length_str
-        .parse::<usize>()
-        .map_err(|_| ERR_INVALID_STRING_LENGTH.to_string())?;
-
-    *position = end + 1; // Skip ':'
+    *position = colon + 1; // Skip ':'
 
     // Check if we have enough bytes
     if *position + length > input.len() {
@@ -129,13 +198,13 @@ fn parse_bytes<'a>(input: &'a [u8], position: &mut usize) -> Result<BorrowedNode
 }
 
 /// Parses a bencode list (l<items>e)
-fn parse_list<'a>(input: &'a [u8], position: &mut usize) -> Result<BorrowedNode<'a>, String> {
+fn parse_list<'a>(input: &'a [u8], position: &mut usize, depth: usize) -> Result<BorrowedNode<'a>, String> {
     *position += 1; // Skip 'l'
 
     let mut list = Vec::new();
 
     while *position < input.len() && input[*position] != BENCODE_END {
-        let node = parse_node(input, position)?;
+        let node = parse_node(input, position, Expecting::ListItem, depth)?;
         list.push(node);
     }
 
@@ -148,7 +217,7 @@ fn parse_list<'a>(input: &'a [u8], position: &mut usize) -> Result<BorrowedNode<
 }
 
 /// Parses a bencode dictionary (d<key-value pairs>e)
-fn parse_dictionary<'a>(input: &'a [u8], position: &mut usize) -> Result<BorrowedNode<'a>, String> {
+fn parse_dictionary<'a>(input: &'a [u8], position: &mut usize, depth: usize) -> Result<BorrowedNode<'a>, String> {
     *position += 1; // Skip 'd'
 
     let mut dict = HashMap::new();
@@ -156,22 +225,24 @@ fn parse_dictionary<'a>(input: &'a [u8], position: &mut usize) -> Result<Borrowe
 
     while *position < input.len() && input[*position] != BENCODE_END {
         // Parse key (must be a byte string)
-        let key_node = parse_node(input, position)?;
-        let key = match key_node {
+        if !input[*position].is_ascii_digit() {
+            return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string());
+        }
+        let key = match parse_bytes(input, position)? {
             BorrowedNode::Bytes(b) => b,
             _ => return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string()),
         };
 
         // Check key ordering (bencode requires sorted keys)
-        if let Some(prev_key) = last_key {
-            if key <= prev_key {
-                return Err(ERR_DICT_KEYS_ORDER.to_string());
-            }
+        if let Some(prev_key) = last_key
+            && key <= prev_key
+        {
+            return Err(ERR_DICT_KEYS_ORDER.to_string());
         }
         last_key = Some(key);
 
         // Parse value
-        let value = parse_node(input, position)?;
+        let value = parse_node(input, position, Expecting::DictValue, depth)?;
         dict.insert(key, value);
     }
 
@@ -204,78 +275,106 @@ fn parse_dictionary<'a>(input: &'a [u8], position: &mut usize) -> Result<Borrowe
 /// assert!(validate_bencode(b"invalid").is_err());
 /// ```
 pub fn validate_bencode(input: &[u8]) -> Result<(), String> {
+    let timer = Timer::start("validate_bencode");
     let mut position = 0;
-    validate_node(input, &mut position)?;
+    let result = validate_root(input, &mut position, &mut Limits::none());
+    timer.finish(Some(input.len()), &result);
+    result
+}
 
+/// Validates bencode data without building a node tree, enforcing the limits
+/// of a [`ParserConfig`] so hostile input cannot exhaust the stack or run on
+/// without bound. Input over the size limit is rejected before it is read.
+///
+/// # Arguments
+/// * `input` - The byte slice to validate
+/// * `config` - The limits on depth, string length, value count and input size
+///
+/// # Returns
+/// * `Ok(())` - The input is valid bencode within the limits
+/// * `Err(ParseError)` - The problem and the offset at which it was found; a
+///   crossed limit is reported as [`ErrorKind::TooDeep`](crate::ErrorKind::TooDeep),
+///   [`StringTooLong`](crate::ErrorKind::StringTooLong),
+///   [`TooManyNodes`](crate::ErrorKind::TooManyNodes) or
+///   [`InputTooLarge`](crate::ErrorKind::InputTooLarge)
+///
+/// # Example
+/// ```
+/// use bencode_lib::{validate_bencode_with_limits, ErrorKind, ParserConfig};
+///
+/// let config = ParserConfig::new().with_max_depth(2).with_max_string_length(4);
+/// assert!(validate_bencode_with_limits(b"ld4:spami1eee", &config).is_ok());
+///
+/// let error = validate_bencode_with_limits(b"llleee", &config).unwrap_err();
+/// assert_eq!((error.kind(), error.offset), (ErrorKind::TooDeep, 2));
+/// let error = validate_bencode_with_limits(b"l4:spam5:eggs!e", &config).unwrap_err();
+/// assert_eq!((error.kind(), error.offset), (ErrorKind::StringTooLong, 7));
+/// ```
+pub fn validate_bencode_with_limits(input: &[u8], config: &ParserConfig) -> Result<(), ParseError> {
+    let timer = Timer::start("validate_bencode_with_limits");
+    let mut position = 0;
+    let result = if input.len() > config.max_input_size {
+        Err(ParseError::new(ERR_INPUT_TOO_LARGE, config.max_input_size))
+    } else {
+        validate_root(input, &mut position, &mut Limits::new(*config))
+            .map_err(|message| ParseError::new(message, position))
+    };
+    timer.finish(Some(input.len()), &result);
+    result
+}
+
+/// Validates the root value, which must span the whole input
+fn validate_root(input: &[u8], position: &mut usize, limits: &mut Limits) -> Result<(), String> {
+    validate_node(input, position, Expecting::Value, 0, limits)?;
     // Ensure we consumed all input
-    if position != input.len() {
-        return Err("Trailing data after bencode structure".to_string());
+    if *position != input.len() {
+        return Err(ERR_TRAILING_DATA.to_string());
     }
-
     Ok(())
 }
 
 /// Internal validation function that doesn't allocate nodes
-fn validate_node(input: &[u8], position: &mut usize) -> Result<(), String> {
+fn validate_node(input: &[u8], position: &mut usize, expecting: Expecting, depth: usize, limits: &mut Limits) -> Result<(), String> {
     if *position >= input.len() {
         return Err(ERR_EMPTY_INPUT.to_string());
     }
 
     match input[*position] {
-        BENCODE_INTEGER_START => validate_integer(input, position),
-        BENCODE_LIST_START => validate_list(input, position),
-        BENCODE_DICTIONARY_START => validate_dictionary(input, position),
-        b'0'..=b'9' => validate_bytes(input, position),
-        c => Err(unexpected_character(c as char)),
+        BENCODE_INTEGER_START => {
+            limits.add_element()?;
+            validate_integer(input, position, limits)
+        }
+        BENCODE_LIST_START => {
+            limits.open_container(depth + 1)?;
+            validate_list(input, position, depth + 1, limits)
+        }
+        BENCODE_DICTIONARY_START => {
+            limits.open_container(depth + 1)?;
+            validate_dictionary(input, position, depth + 1, limits)
+        }
+        b'0'..=b'9' => {
+            limits.add_element()?;
+            validate_bytes(input, position, limits)
+        }
+        c => Err(unexpected_character(c as char, expecting, depth)),
     }
 }
 
-fn validate_integer(input: &[u8], position: &mut usize) -> Result<(), String> {
+fn validate_integer(input: &[u8], position: &mut usize, limits: &Limits) -> Result<(), String> {
     *position += 1; // Skip 'i'
 
-    let start = *position;
-    let mut end = start;
-
-    while end < input.len() && input[end] != BENCODE_END {
-        end += 1;
-    }
-
-    if end >= input.len() {
-        return Err(ERR_UNTERMINATED_INTEGER.to_string());
-    }
-
-    let int_slice = &input[start..end];
-    let int_str = core::str::from_utf8(int_slice).map_err(|_| ERR_INVALID_INTEGER.to_string())?;
-
-    int_str
-        .parse::<i64>()
-        .map_err(|_| ERR_INVALID_INTEGER.to_string())?;
+    let (text, end) = integer_text(input, *position)?;
+    limits.integer(text)?;
 
     *position = end + 1;
     Ok(())
 }
 
-fn validate_bytes(input: &[u8], position: &mut usize) -> Result<(), String> {
-    let start = *position;
-    let mut end = start;
-
-    while end < input.len() && input[end] != BENCODE_STRING_DELIMITER {
-        end += 1;
-    }
+fn validate_bytes(input: &[u8], position: &mut usize, limits: &Limits) -> Result<(), String> {
+    let (length, colon) = string_length(input, *position)?;
+    limits.check_string_length(length)?;
 
-    if end >= input.len() {
-        return Err(ERR_INVALID_STRING_LENGTH.to_string());
-    }
-
-    let length_slice = &input[start..end];
-    let length_str =
-        core::str::from_utf8(length_slice).map_err(|_| ERR_INVALID_STRING_LENGTH.to_string())?;
-
-    let length = length_str
-        .parse::<usize>()
-        .map_err(|_| ERR_INVALID_STRING_LENGTH.to_string())?;
-
-    *position = end + 1;
+    *position = colon + 1;
 
     if *position + length > input.len() {
         return Err(ERR_STRING_TOO_SHORT.to_string());
@@ -285,11 +384,11 @@ fn validate_bytes(input: &[u8], position: &mut usize) -> Result<(), String> {
     Ok(())
 }
 
-fn validate_list(input: &[u8], position: &mut usize) -> Result<(), String> {
+fn validate_list(input: &[u8], position: &mut usize, depth: usize, limits: &mut Limits) -> Result<(), String> {
     *position += 1; // Skip 'l'
 
     while *position < input.len() && input[*position] != BENCODE_END {
-        validate_node(input, position)?;
+        validate_node(input, position, Expecting::ListItem, depth, limits)?;
     }
 
     if *position >= input.len() {
@@ -300,7 +399,7 @@ fn validate_list(input: &[u8], position: &mut usize) -> Result<(), String> {
     Ok(())
 }
 
-fn validate_dictionary(input: &[u8], position: &mut usize) -> Result<(), String> {
+fn validate_dictionary(input: &[u8], position: &mut usize, depth: usize, limits: &mut Limits) -> Result<(), String> {
     *position += 1; // Skip 'd'
 
     let mut last_key_start = 0;
@@ -309,7 +408,7 @@ fn validate_dictionary(input: &[u8], position: &mut usize) -> Result<(), String>
 
     while *position < input.len() && input[*position] != BENCODE_END {
         // Validate key is a byte string
-        if !matches!(input[*position], b'0'..=b'9') {
+        if !input[*position].is_ascii_digit() {
             return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string());
         }
 
@@ -321,7 +420,7 @@ fn validate_dictionary(input: &[u8], position: &mut usize) -> Result<(), String>
         }
 
         if len_end >= input.len() {
-            return Err(ERR_INVALID_STRING_LENGTH.to_string());
+            return Err(unterminated_string_length(&input[len_start..]).to_string());
         }
 
         let length_str = core::str::from_utf8(&input[len_start..len_end])
@@ -329,6 +428,7 @@ fn validate_dictionary(input: &[u8], position: &mut usize) -> Result<(), String>
         let length = length_str
             .parse::<usize>()
             .map_err(|_| ERR_INVALID_STRING_LENGTH.to_string())?;
+        limits.check_string_length(length)?;
 
         let key_bytes_start = len_end + 1; // Skip ':'
         let key_bytes_end = key_bytes_start + length;
@@ -353,7 +453,7 @@ fn validate_dictionary(input: &[u8], position: &mut usize) -> Result<(), String>
         *position = key_bytes_end;
 
         // Validate value
-        validate_node(input, position)?;
+        validate_node(input, position, Expecting::DictValue, depth, limits)?;
     }
 
     if *position >= input.len() {
@@ -368,6 +468,28 @@ fn validate_dictionary(input: &[u8], position: &mut usize) -> Result<(), String>
 mod tests {
     use super::*;
 
+    #[test]
+    fn decode_primitives_report_value_and_length() {
+        assert_eq!(decode_int(b"i0e").unwrap(), (0, 3));
+        assert_eq!(decode_int(b"i-9223372036854775808ee").unwrap(), (i64::MIN, 22));
+        assert_eq!(decode_bytes(b"0:").unwrap(), (&b""[..], 2));
+        assert_eq!(decode_bytes(b"2:\xff\x00le").unwrap(), (&b"\xff\x00"[..], 4));
+
+        let error = |result: Result<(), ParseError>| {
+            let error = result.unwrap_err();
+            (error.message, error.offset)
+        };
+        assert_eq!(error(decode_int(b"").map(drop)), (ERR_EMPTY_INPUT.to_string(), 0));
+        assert_eq!(error(decode_int(b"4:spam").map(drop)), (ERR_INVALID_INTEGER.to_string(), 0));
+        assert_eq!(error(decode_int(b"i-0e").map(drop)), (ERR_INVALID_INTEGER.to_string(), 1));
+        assert_eq!(error(decode_int(b"i12").map(drop)), (ERR_UNTERMINATED_INTEGER.to_string(), 1));
+        assert_eq!(error(decode_bytes(b"i1e").map(drop)), (ERR_INVALID_STRING_LENGTH.to_string(), 0));
+        assert_eq!(error(decode_bytes(b"12").map(drop)), (ERR_UNTERMINATED_STRING_LENGTH.to_string(), 0));
+        assert_eq!(error(decode_bytes(b"3:ab").map(drop)), (ERR_STRING_TOO_SHORT.to_string(), 2));
+        let huge = b"18446744073709551615:a";
+        assert_eq!(error(decode_bytes(huge).map(drop)), (ERR_STRING_TOO_SHORT.to_string(), 21));
+    }
+
     #[test]
     fn parse_borrowed_integer() {
         let result = parse_borrowed(b"i42e").unwrap();
@@ -433,6 +555,43 @@ mod tests {
         assert!(validate_bencode(large_data).is_ok());
     }
 
+    #[test]
+    fn validate_bencode_with_limits_enforces_each_limit() {
+        use crate::ErrorKind;
+
+        let failure = |data: &[u8], config: ParserConfig| {
+            let error = validate_bencode_with_limits(data, &config).unwrap_err();
+            (error.kind(), error.offset)
+        };
+        let depth = ParserConfig::new().with_max_depth(2);
+        assert!(validate_bencode_with_limits(b"lli1eee", &depth).is_ok());
+        assert_eq!(failure(b"ld1:alleee", depth), (ErrorKind::TooDeep, 5));
+        // Deep nesting is refused before it can exhaust the stack
+        let deep = [b"l".repeat(100_000), b"e".repeat(100_000)].concat();
+        assert_eq!(failure(&deep, ParserConfig::untrusted()), (ErrorKind::TooDeep, 32));
+
+        let strings = ParserConfig::new().with_max_string_length(3);
+        assert!(validate_bencode_with_limits(b"d3:key3:vale", &strings).is_ok());
+        assert_eq!(failure(b"d4:keysi1ee", strings), (ErrorKind::StringTooLong, 1));
+        assert_eq!(failure(b"99999999999:", strings), (ErrorKind::StringTooLong, 0));
+
+        let elements = ParserConfig::new().with_max_elements(3);
+        assert!(validate_bencode_with_limits(b"d1:ai1e1:bi2ee", &elements).is_ok());
+        assert_eq!(failure(b"li1ei2ei3ee", elements), (ErrorKind::TooManyNodes, 7));
+
+        let size = ParserConfig::new().with_max_input_size(5);
+        assert!(validate_bencode_with_limits(b"i123e", &size).is_ok());
+        assert_eq!(failure(b"i1234e", size), (ErrorKind::InputTooLarge, 5));
+
+        // Other errors keep their kind, with an offset
+        assert_eq!(failure(b"li1ei2e", ParserConfig::new()), (ErrorKind::UnterminatedList, 7));
+        assert_eq!(failure(b"i1ei2e", ParserConfig::new()), (ErrorKind::TrailingData, 3));
+        let unlimited = ParserConfig::unlimited();
+        for data in [&b"d1:ai1e1:bl0:ee"[..], b"i-0e", b"d1:bi1e1:ai2ee", b"4:abc", b""] {
+            assert_eq!(validate_bencode_with_limits(data, &unlimited).is_ok(), validate_bencode(data).is_ok());
+        }
+    }
+
     #[test]
     fn borrowed_node_lifetime() {
         let data = b"5:hello";
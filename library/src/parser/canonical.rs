@@ -0,0 +1,120 @@
+//! Check that input is the canonical encoding of its value.
+//!
+//! Every bencode value has exactly one canonical encoding: dictionary keys in
+//! strictly increasing byte order, integers and string lengths without leading
+//! zeros or a `+` sign, and nothing after the root value. Info hashes are taken
+//! over the encoded bytes, so only canonical input re-encodes to the same hash.
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::error::messages::{ERR_DICT_KEYS_ORDER, ERR_NOT_CANONICAL, ERR_TRAILING_DATA};
+use crate::error::parse::ParseError;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+
+/// Returns true if `digits` is a decimal number with no sign and no leading zeros
+fn is_canonical_number(digits: &[u8]) -> bool {
+    match digits {
+        [b'0'] => true,
+        [b'1'..=b'9', rest @ ..] => rest.iter().all(u8::is_ascii_digit),
+        _ => false,
+    }
+}
+
+/// Checks that the input is the canonical encoding of a single value.
+///
+/// # Arguments
+/// * `source` - The bencode data
+///
+/// # Returns
+/// * `Ok(())` - The input is well formed and canonical
+/// * `Err(ParseError)` - The first problem found, at the offset of the value
+///   or key it was found in; non-canonical integers and string lengths are
+///   reported with [`ErrorKind::NotCanonical`](crate::ErrorKind::NotCanonical)
+///
+/// # Example
+/// ```
+/// use bencode_lib::{validate_canonical, ErrorKind};
+///
+/// assert!(validate_canonical(b"d1:ai1e1:bi2ee").is_ok());
+/// assert_eq!(validate_canonical(b"d1:bi1e1:ai2ee").unwrap_err().kind(), ErrorKind::DictKeysOrder);
+/// assert_eq!(validate_canonical(b"li1ei02ee").unwrap_err().offset, 4);
+/// assert_eq!(validate_canonical(b"04:spam").unwrap_err().kind(), ErrorKind::NotCanonical);
+/// ```
+pub fn validate_canonical(source: &[u8]) -> Result<(), ParseError> {
+    // A '+' is read so that it is reported as not canonical rather than invalid
+    let mut tokenizer = Tokenizer::new(source).with_allow_plus_sign(true);
+    // Previous key of each open dictionary; None for lists and before the first key
+    let mut last_keys: Vec<Option<&[u8]>> = Vec::new();
+    for token in tokenizer.by_ref() {
+        let token = token?;
+        let canonical = match token.kind {
+            TokenKind::Integer(_) => {
+                let digits = &source[token.offset + 1..token.end - 1];
+                is_canonical_number(digits.strip_prefix(b"-").unwrap_or(digits))
+            }
+            TokenKind::Bytes(bytes) | TokenKind::Key(bytes) => {
+                is_canonical_number(&source[token.offset..token.end - bytes.len() - 1])
+            }
+            _ => true,
+        };
+        if !canonical {
+            return Err(ParseError::new(ERR_NOT_CANONICAL, token.offset));
+        }
+        match token.kind {
+            TokenKind::ListStart | TokenKind::DictStart => last_keys.push(None),
+            TokenKind::ListEnd | TokenKind::DictEnd => {
+                last_keys.pop();
+            }
+            TokenKind::Key(key) => {
+                if let Some(last) = last_keys.last_mut() {
+                    if last.is_some_and(|last| key <= last) {
+                        return Err(ParseError::new(ERR_DICT_KEYS_ORDER, token.offset));
+                    }
+                    *last = Some(key);
+                }
+            }
+            _ => {}
+        }
+    }
+    if tokenizer.position() != source.len() {
+        return Err(ParseError::new(ERR_TRAILING_DATA, tokenizer.position()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    #[test]
+    fn canonical_input_is_accepted() {
+        for data in [&b"i0e"[..], b"i-7e", b"0:", b"10:0123456789", b"le", b"de", b"d1:ad1:ai1ee1:bl0:ee", b"ldedee"] {
+            assert_eq!(validate_canonical(data), Ok(()), "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn non_canonical_forms_are_located() {
+        for (data, kind, offset) in [
+            (&b"i00e"[..], ErrorKind::NotCanonical, 0),
+            (b"i-01e", ErrorKind::NotCanonical, 0),
+            (b"i+1e", ErrorKind::NotCanonical, 0),
+            (b"d01:ai1ee", ErrorKind::NotCanonical, 1),
+            (b"d1:ai1e1:ai2ee", ErrorKind::DictKeysOrder, 7),
+            (b"ld1:bi1e1:ai1eee", ErrorKind::DictKeysOrder, 8),
+            (b"i1ei2e", ErrorKind::TrailingData, 3),
+            (b"i-0e", ErrorKind::InvalidInteger, 0),
+        ] {
+            let error = validate_canonical(data).unwrap_err();
+            assert_eq!((error.kind(), error.offset), (kind, offset), "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn key_order_is_checked_per_dictionary() {
+        // Keys of a nested dictionary do not take part in the outer order
+        assert!(validate_canonical(b"d1:bd1:ci1ee1:ci2ee").is_ok());
+    }
+}
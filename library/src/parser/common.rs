@@ -0,0 +1,218 @@
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::config::ParserConfig;
+use crate::error::messages::{
+    ERR_INTEGER_OUT_OF_RANGE, ERR_INTEGER_OVERFLOW, ERR_INVALID_INTEGER, ERR_INVALID_STRING_LENGTH, ERR_STRING_TOO_LONG,
+    ERR_TOO_DEEP, ERR_TOO_MANY_NODES, ERR_UNTERMINATED_INTEGER, ERR_UNTERMINATED_STRING_LENGTH,
+};
+use crate::error::warning::{Deviation, ParseWarning};
+use crate::nodes::integer::parse_integer_text;
+use crate::nodes::node::Node;
+
+/// Limits checked while parsing, with the count of values seen so far and
+/// the deviations the lenient toggles let through
+pub(crate) struct Limits {
+    pub(crate) config: ParserConfig,
+    elements: usize,
+    pub(crate) warnings: Vec<ParseWarning>,
+}
+
+impl Limits {
+    pub(crate) const fn new(config: ParserConfig) -> Self {
+        Self {
+            config,
+            elements: 0,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// No limits, for the unconfigured parse and validate functions
+    pub(crate) const fn none() -> Self {
+        Self::new(ParserConfig::unlimited())
+    }
+
+    /// Counts one more value in the document
+    pub(crate) fn add_element(&mut self) -> Result<(), String> {
+        self.elements += 1;
+        if self.elements > self.config.max_elements {
+            return Err(ERR_TOO_MANY_NODES.to_string());
+        }
+        Ok(())
+    }
+
+    /// Counts a list or dictionary whose values sit at `depth`
+    pub(crate) fn open_container(&mut self, depth: usize) -> Result<(), String> {
+        if depth > self.config.max_depth {
+            return Err(ERR_TOO_DEEP.to_string());
+        }
+        self.add_element()
+    }
+
+    /// Checks the length prefix of a string or dictionary key
+    pub(crate) fn check_string_length(&self, length: usize) -> Result<(), String> {
+        if length > self.config.max_string_length {
+            return Err(ERR_STRING_TOO_LONG.to_string());
+        }
+        Ok(())
+    }
+
+    /// Records a deviation found at `offset`
+    pub(crate) fn warn(&mut self, deviation: Deviation, offset: usize) {
+        self.warnings.push(ParseWarning { deviation, offset });
+    }
+
+    /// Parses the text of an integer, keeping values too large for the node
+    /// integer type as BigInteger nodes when the configuration allows. Their
+    /// digits lose any leading zeros, as other integers do.
+    pub(crate) fn integer(&self, text: &str) -> Result<Node, &'static str> {
+        match parse_integer_text(text) {
+            Ok(value) => Ok(Node::Integer(value)),
+            Err(ERR_INTEGER_OVERFLOW | ERR_INTEGER_OUT_OF_RANGE) if self.config.big_integers => {
+                let (sign, digits) = text.split_at(usize::from(text.starts_with('-')));
+                Ok(Node::BigInteger(format!("{}{}", sign, digits.trim_start_matches('0'))))
+            }
+            Err(error) => Err(error),
+        }
+    }
+}
+
+/// Returns the error for an integer whose text was cut off by the end of the
+/// input: unterminated if more input could still complete it, otherwise the
+/// error the text already has, so a reader waiting for more data gives up on
+/// input no continuation can fix.
+///
+/// # Arguments
+/// * `text` - The text read after the 'i'
+///
+/// # Returns
+/// The error message
+pub(crate) fn unterminated_integer(text: &[u8]) -> &'static str {
+    match text {
+        b"" | b"-" => ERR_UNTERMINATED_INTEGER,
+        _ => match core::str::from_utf8(text) {
+            Ok(text) => parse_integer_text(text).err().unwrap_or(ERR_UNTERMINATED_INTEGER),
+            Err(_) => ERR_INVALID_INTEGER,
+        },
+    }
+}
+
+/// Returns true if integer text starts with a '+' followed by a digit, the
+/// form non-conforming encoders write that lenient parsing accepts.
+///
+/// # Arguments
+/// * `text` - The text read after the 'i'
+///
+/// # Returns
+/// True if dropping the first byte leaves text that may be a valid integer
+pub(crate) fn has_plus_sign(text: &[u8]) -> bool {
+    matches!(text, [b'+', b'0'..=b'9', ..])
+}
+
+/// Returns the error for a string length prefix cut off by the end of the
+/// input before its ':'; see [`unterminated_integer`].
+///
+/// # Arguments
+/// * `digits` - The length prefix read so far
+///
+/// # Returns
+/// The error message
+pub(crate) fn unterminated_string_length(digits: &[u8]) -> &'static str {
+    let complete = digits.iter().all(u8::is_ascii_digit)
+        && core::str::from_utf8(digits).is_ok_and(|digits| digits.parse::<usize>().is_ok());
+    if complete {
+        ERR_UNTERMINATED_STRING_LENGTH
+    } else {
+        ERR_INVALID_STRING_LENGTH
+    }
+}
+
+/// Decodes the backslash escapes the text format encoders write, returning the
+/// string's bytes.
+///
+/// `\uXXXX` escapes below 0x100 stand for a single byte, as the encoders write
+/// every byte outside printable ASCII that way; higher values are encoded as
+/// UTF-8. `\n`, `\t`, `\r`, `\0`, `\/`, `\"`, `\\` and `\'` are also accepted
+/// for hand-written text.
+///
+/// # Arguments
+/// * `text` - The escaped text, without its quotes
+///
+/// # Returns
+/// The decoded bytes, or None if an escape is malformed
+#[cfg(any(feature = "yaml", feature = "xml"))]
+pub(crate) fn unescape(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next()? {
+            'u' => {
+                let digits: Vec<char> = chars.by_ref().take(4).collect();
+                if digits.len() != 4 {
+                    return None;
+                }
+                let value = digits.iter().try_fold(0u32, |value, digit| Some(value << 4 | digit.to_digit(16)?))?;
+                if value < 0x100 {
+                    bytes.push(value as u8);
+                } else {
+                    let mut buffer = [0; 4];
+                    bytes.extend_from_slice(char::from_u32(value)?.encode_utf8(&mut buffer).as_bytes());
+                }
+            }
+            'n' => bytes.push(b'\n'),
+            't' => bytes.push(b'\t'),
+            'r' => bytes.push(b'\r'),
+            '0' => bytes.push(0),
+            c @ ('"' | '\\' | '/' | '\'') => bytes.push(c as u8),
+            _ => return None,
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cut_off_numbers_are_unterminated_only_if_they_can_be_completed() {
+        for text in [&b""[..], b"-", b"0", b"-12", b"42"] {
+            assert_eq!(unterminated_integer(text), ERR_UNTERMINATED_INTEGER);
+        }
+        for text in [&b"-0"[..], b"1x", b"--", b"\xff"] {
+            assert_eq!(unterminated_integer(text), ERR_INVALID_INTEGER);
+        }
+        let too_large = if cfg!(feature = "int32") { "99999999999" } else { "99999999999999999999" };
+        let expected = if cfg!(feature = "int32") { ERR_INTEGER_OUT_OF_RANGE } else { ERR_INTEGER_OVERFLOW };
+        if !cfg!(feature = "int32-saturate") {
+            assert_eq!(unterminated_integer(too_large.as_bytes()), expected);
+        }
+        assert!(has_plus_sign(b"+5") && has_plus_sign(b"+0"));
+        assert!(!has_plus_sign(b"+") && !has_plus_sign(b"+-5") && !has_plus_sign(b"5"));
+        assert_eq!(unterminated_string_length(b"12"), ERR_UNTERMINATED_STRING_LENGTH);
+        assert_eq!(unterminated_string_length(b"1x"), ERR_INVALID_STRING_LENGTH);
+        assert_eq!(unterminated_string_length(b"+1"), ERR_INVALID_STRING_LENGTH);
+        assert_eq!(unterminated_string_length(b"99999999999999999999999"), ERR_INVALID_STRING_LENGTH);
+    }
+
+    #[cfg(any(feature = "yaml", feature = "xml"))]
+    #[test]
+    fn escapes_written_by_the_encoders_are_decoded() {
+        assert_eq!(unescape(r#"caf\u00c3\u00a9 \"q\" \\"#).unwrap(), "café \"q\" \\".as_bytes());
+        assert_eq!(unescape(r"\u00ff\u0000").unwrap(), vec![0xff, 0x00]);
+        assert_eq!(unescape(r"\u20ac\n\t").unwrap(), "€\n\t".as_bytes());
+        assert_eq!(unescape("plain é").unwrap(), "plain é".as_bytes());
+        for bad in [r"\", r"\q", r"\u00f", r"\u00fg", r"\ud800"] {
+            assert_eq!(unescape(bad), None, "{}", bad);
+        }
+    }
+}
@@ -5,13 +5,20 @@
 use alloc::{
     string::{String, ToString},
     vec,
+    vec::Vec,
 };
 
 use crate::HashMap;
 use crate::Node::Dictionary;
+use crate::config::ParserConfig;
 use crate::error::messages::*;
+use crate::error::parse::ParseError;
+use crate::error::warning::{Deviation, ParseWarning};
+use crate::io::sources::tracked::Tracked;
 use crate::io::traits::ISource;
+use crate::metrics::Timer;
 use crate::nodes::node::Node;
+use crate::parser::common::{Limits, has_plus_sign, unterminated_integer, unterminated_string_length};
 
 /// Start marker for bencode integer values ('i')
 /// Format: i<digits>e
@@ -42,6 +49,21 @@ const DICT_END: char = 'e';
 /// Examples: 4:test, 0:, 5:hello
 const STRING_SEPARATOR: char = ':';
 
+/// Skips a run of whitespace if the configuration allows it, recording a warning.
+///
+/// # Arguments
+/// * `source` - The source positioned before a value or container end
+/// * `limits` - The configuration and the warnings found so far
+fn skip_whitespace(source: &mut Tracked, limits: &mut Limits) {
+    if !limits.config.allow_whitespace || !source.current().is_some_and(|c| c.is_ascii_whitespace()) {
+        return;
+    }
+    limits.warn(Deviation::Whitespace, source.position());
+    while source.current().is_some_and(|c| c.is_ascii_whitespace()) {
+        source.next();
+    }
+}
+
 /// Parses the length prefix of a bencode string, expecting digits followed by ':'.
 /// Reads characters until ':' is found and converts them to a numeric length.
 ///
@@ -53,17 +75,53 @@ const STRING_SEPARATOR: char = ':';
 fn parse_string_length(source: &mut dyn ISource) -> Result<usize, String> {
     let mut length = String::new();
     while let Some(c) = source.current() {
+        source.next();
         if c == STRING_SEPARATOR {
-            source.next();
-            break;
+            return length
+                .parse::<usize>()
+                .map_err(|_| ERR_INVALID_STRING_LENGTH.to_string());
         }
         length.push(c);
-        source.next();
     }
+    Err(unterminated_string_length(length.as_bytes()).to_string())
+}
 
-    length
-        .parse::<usize>()
-        .map_err(|_| ERR_INVALID_STRING_LENGTH.to_string())
+/// Reads the raw bytes of a bencode string, expecting format '<length>:<bytes>'.
+///
+/// # Arguments
+/// * `source` - The source containing the string to read
+///
+/// # Returns
+/// * `Result<Vec<u8>, String>` - String content or error message
+#[cfg(feature = "serde")]
+pub(crate) fn parse_string_bytes(source: &mut dyn ISource) -> Result<Vec<u8>, String> {
+    parse_limited_string_bytes(source, usize::MAX)
+}
+
+/// Reads the raw bytes of a bencode string, rejecting a length prefix over
+/// `max_length` before any content is read.
+///
+/// # Arguments
+/// * `source` - The source containing the string to read
+/// * `max_length` - The longest string accepted
+///
+/// # Returns
+/// * `Result<Vec<u8>, String>` - String content or error message
+fn parse_limited_string_bytes(source: &mut dyn ISource, max_length: usize) -> Result<Vec<u8>, String> {
+    let length = parse_string_length(source)?;
+    if length > max_length {
+        return Err(ERR_STRING_TOO_LONG.to_string());
+    }
+    let mut bytes = Vec::new();
+    for _ in 0..length {
+        if let Some(c) = source.current() {
+            bytes.push(c as u8);
+            source.next();
+        } else {
+            return Err(ERR_STRING_TOO_SHORT.to_string());
+        }
+    }
+    Ok(bytes)
 }
 
 /// Parses bencode data from the given source into a Node structure.
@@ -73,15 +131,99 @@ fn parse_string_length(source: &mut dyn ISource) -> Result<usize, String> {
 /// * `source` - The source containing bencode-encoded data
 ///
 /// # Returns
+/// * `Result<Node, ParseError>` - Parsed Node or the error and its offset
+pub fn parse(source: &mut dyn ISource) -> Result<Node, ParseError> {
+    let timer = Timer::start("parse");
+    let result = parse_tracked(&mut Tracked::new(source, usize::MAX), Limits::none());
+    timer.finish(None, &result);
+    result
+}
+
+/// Parses the root value, reporting errors at the position reached in `source`.
+fn parse_tracked(source: &mut Tracked, limits: Limits) -> Result<Node, ParseError> {
+    parse_tracked_with_warnings(source, limits).map(|(node, _)| node)
+}
+
+/// Parses the root value, returning it with the deviations found.
+fn parse_tracked_with_warnings(source: &mut Tracked, mut limits: Limits) -> Result<(Node, Vec<ParseWarning>), ParseError> {
+    match parse_value(source, Expecting::Value, 0, &mut limits) {
+        Ok(node) => Ok((node, limits.warnings)),
+        Err(message) => {
+            // Running out of allowed input shows up as truncated data; report the real cause
+            let message = if source.exceeded() { ERR_INPUT_TOO_LARGE.to_string() } else { message };
+            Err(ParseError::new(message, source.position()))
+        }
+    }
+}
+
+/// Parses bencode data from the given source, enforcing the limits of a
+/// [`ParserConfig`] so untrusted input cannot exhaust the stack or memory.
+///
+/// # Arguments
+/// * `source` - The source containing bencode-encoded data
+/// * `config` - The limits on depth, string length, value count and input size
+///
+/// # Returns
+/// * `Result<Node, ParseError>` - Parsed Node or the error and its offset; a crossed limit is
+///   reported as [`ErrorKind::TooDeep`](crate::ErrorKind::TooDeep),
+///   [`StringTooLong`](crate::ErrorKind::StringTooLong),
+///   [`TooManyNodes`](crate::ErrorKind::TooManyNodes) or
+///   [`InputTooLarge`](crate::ErrorKind::InputTooLarge)
+pub fn parse_with_config(source: &mut dyn ISource, config: &ParserConfig) -> Result<Node, ParseError> {
+    let timer = Timer::start("parse_with_config");
+    let result = parse_tracked(&mut Tracked::new(source, config.max_input_size), Limits::new(*config));
+    timer.finish(None, &result);
+    result
+}
+
+/// Parses bencode data from the given source within the limits of a
+/// [`ParserConfig`], also returning the deviations its lenient toggles let through.
+///
+/// # Arguments
+/// * `source` - The source containing bencode-encoded data
+/// * `config` - The limits to enforce and the deviations to accept
+///
+/// # Returns
+/// * `Ok((Node, Vec<ParseWarning>))` - Parsed Node and the deviations found, in input order
+/// * `Err(ParseError)` - The error and its offset
+pub fn parse_with_warnings(source: &mut dyn ISource, config: &ParserConfig) -> Result<(Node, Vec<ParseWarning>), ParseError> {
+    let timer = Timer::start("parse_with_warnings");
+    let result = parse_tracked_with_warnings(&mut Tracked::new(source, config.max_input_size), Limits::new(*config));
+    timer.finish(None, &result);
+    result
+}
+
+/// Parses the value at the current position of the source.
+///
+/// # Arguments
+/// * `source` - The source containing bencode-encoded data
+/// * `expecting` - Where the value appears, for unexpected character errors
+/// * `depth` - Number of containers enclosing the value
+/// * `limits` - The limits to enforce
+///
+/// # Returns
 /// * `Result<Node, String>` - Parsed Node or error message
-pub fn parse(source: &mut dyn ISource) -> Result<Node, String> {
+fn parse_value(source: &mut Tracked, expecting: Expecting, depth: usize, limits: &mut Limits) -> Result<Node, String> {
+    skip_whitespace(source, limits);
     match source.current() {
-        Some(INTEGER_START) => parse_integer(source),
-        Some(LIST_START) => parse_list(source),
-        Some(DICT_START) => parse_dictionary(source),
-        Some('0'..='9') => parse_string(source),
+        Some(INTEGER_START) => {
+            limits.add_element()?;
+            parse_integer(source, limits)
+        }
+        Some(LIST_START) => {
+            limits.open_container(depth + 1)?;
+            parse_list(source, depth + 1, limits)
+        }
+        Some(DICT_START) => {
+            limits.open_container(depth + 1)?;
+            parse_dictionary(source, depth + 1, limits)
+        }
+        Some('0'..='9') => {
+            limits.add_element()?;
+            parse_string(source, limits)
+        }
         Some(STRING_SEPARATOR) => Err(ERR_INVALID_STRING_LENGTH.to_string()),
-        Some(c) => Err(unexpected_character(c)),
+        Some(c) => Err(unexpected_character(c, expecting, depth)),
         None => Err(ERR_EMPTY_INPUT.to_string()),
     }
 }
@@ -93,11 +235,89 @@ pub fn parse(source: &mut dyn ISource) -> Result<Node, String> {
 /// * `data` - The byte slice containing bencode-encoded data
 ///
 /// # Returns
-/// * `Result<Node, String>` - Parsed Node or error message
-pub fn parse_bytes(data: &[u8]) -> Result<Node, String> {
+/// * `Result<Node, ParseError>` - Parsed Node or the error and its offset
+pub fn parse_bytes(data: &[u8]) -> Result<Node, ParseError> {
     use crate::io::sources::buffer::Buffer;
+    let timer = Timer::start("parse_bytes");
     let mut source = Buffer::new(data);
-    parse(&mut source)
+    let result = parse_tracked(&mut Tracked::new(&mut source, usize::MAX), Limits::none());
+    timer.finish(Some(data.len()), &result);
+    result
+}
+
+/// Parses bencode data from a byte slice, enforcing the limits of a
+/// [`ParserConfig`]. Input over the size limit is rejected before parsing starts.
+///
+/// # Arguments
+/// * `data` - The byte slice containing bencode-encoded data
+/// * `config` - The limits on depth, string length, value count and input size
+///
+/// # Returns
+/// * `Result<Node, ParseError>` - Parsed Node or the error and its offset
+///
+/// # Example
+/// ```
+/// use bencode_lib::{ErrorKind, ParserConfig, parse_bytes_with_config};
+///
+/// let config = ParserConfig::new().with_max_depth(2).with_max_string_length(8);
+/// assert!(parse_bytes_with_config(b"d4:spamli1eee", &config).is_ok());
+///
+/// let error = parse_bytes_with_config(b"lllee", &config).unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::TooDeep);
+/// let error = parse_bytes_with_config(b"9:too long!", &config).unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::StringTooLong);
+/// ```
+pub fn parse_bytes_with_config(data: &[u8], config: &ParserConfig) -> Result<Node, ParseError> {
+    use crate::io::sources::buffer::Buffer;
+    let timer = Timer::start("parse_bytes_with_config");
+    let result = if data.len() > config.max_input_size {
+        Err(ParseError::new(ERR_INPUT_TOO_LARGE, config.max_input_size))
+    } else {
+        let mut source = Buffer::new(data);
+        parse_tracked(&mut Tracked::new(&mut source, usize::MAX), Limits::new(*config))
+    };
+    timer.finish(Some(data.len()), &result);
+    result
+}
+
+/// Parses bencode data from a byte slice within the limits of a [`ParserConfig`],
+/// also returning the deviations its lenient toggles let through.
+///
+/// # Arguments
+/// * `data` - The byte slice containing bencode-encoded data
+/// * `config` - The limits to enforce and the deviations to accept
+///
+/// # Returns
+/// * `Ok((Node, Vec<ParseWarning>))` - Parsed Node and the deviations found, in input order
+/// * `Err(ParseError)` - The error and its offset
+///
+/// # Example
+/// ```
+/// use bencode_lib::{Deviation, ErrorKind, ParserConfig, make_node, parse_bytes_with_config, parse_bytes_with_warnings};
+///
+/// let data = b"d4:porti+6881e 4:spaml i1e ee";
+/// let error = parse_bytes_with_config(data, &ParserConfig::new()).unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::InvalidInteger);
+///
+/// // Each toggle allows one deviation and reports where it was found
+/// let config = ParserConfig::new().with_allow_plus_sign(true).with_allow_whitespace(true);
+/// let (node, warnings) = parse_bytes_with_warnings(data, &config).unwrap();
+/// assert_eq!(node.get("port"), Some(&make_node(6881)));
+/// let found: Vec<_> = warnings.iter().map(|warning| (warning.deviation, warning.offset)).collect();
+/// assert_eq!(found, [(Deviation::PlusSign, 8), (Deviation::Whitespace, 14), (Deviation::Whitespace, 22), (Deviation::Whitespace, 26)]);
+/// assert!(parse_bytes_with_warnings(data, &ParserConfig::new().with_allow_plus_sign(true)).is_err());
+/// ```
+pub fn parse_bytes_with_warnings(data: &[u8], config: &ParserConfig) -> Result<(Node, Vec<ParseWarning>), ParseError> {
+    use crate::io::sources::buffer::Buffer;
+    let timer = Timer::start("parse_bytes_with_warnings");
+    let result = if data.len() > config.max_input_size {
+        Err(ParseError::new(ERR_INPUT_TOO_LARGE, config.max_input_size))
+    } else {
+        let mut source = Buffer::new(data);
+        parse_tracked_with_warnings(&mut Tracked::new(&mut source, usize::MAX), Limits::new(*config))
+    };
+    timer.finish(Some(data.len()), &result);
+    result
 }
 
 /// Parses bencode data from a string into a Node structure.
@@ -107,58 +327,68 @@ pub fn parse_bytes(data: &[u8]) -> Result<Node, String> {
 /// * `data` - The string containing bencode-encoded data
 ///
 /// # Returns
-/// * `Result<Node, String>` - Parsed Node or error message
-pub fn parse_str(data: &str) -> Result<Node, String> {
+/// * `Result<Node, ParseError>` - Parsed Node or the error and its offset
+pub fn parse_str(data: &str) -> Result<Node, ParseError> {
     parse_bytes(data.as_bytes())
 }
 
+/// Parses bencode data from a string, enforcing the limits of a [`ParserConfig`].
+///
+/// # Arguments
+/// * `data` - The string containing bencode-encoded data
+/// * `config` - The limits on depth, string length, value count and input size
+///
+/// # Returns
+/// * `Result<Node, ParseError>` - Parsed Node or the error and its offset
+pub fn parse_str_with_config(data: &str, config: &ParserConfig) -> Result<Node, ParseError> {
+    parse_bytes_with_config(data.as_bytes(), config)
+}
+
 /// Parses an integer value from the source, expecting format 'i<number>e'.
-/// Handles both positive and negative integers, rejecting invalid formats like '-0'.
+/// Handles both positive and negative integers, rejecting invalid formats like '-0'
+/// and values that do not fit the node integer type. A leading '+' is accepted
+/// only if the configuration allows it.
 ///
 /// # Arguments
 /// * `source` - The source containing the integer to parse
+/// * `limits` - The configuration and the warnings found so far
 ///
 /// # Returns
 /// * `Result<Node, String>` - Integer Node or error message
-fn parse_integer(source: &mut dyn ISource) -> Result<Node, String> {
+fn parse_integer(source: &mut Tracked, limits: &mut Limits) -> Result<Node, String> {
     source.next(); // skip 'i'
+    let start = source.position();
     let mut number = String::new();
     while let Some(c) = source.current() {
         if c == INTEGER_END {
             source.next();
-            if number == "-0" {
-                return Err(ERR_INVALID_INTEGER.to_string());
-            }
-            return number
-                .parse::<i64>()
-                .map(Node::Integer)
-                .map_err(|_| ERR_INVALID_INTEGER.to_string());
+            let digits = if limits.config.allow_plus_sign && has_plus_sign(number.as_bytes()) {
+                limits.warn(Deviation::PlusSign, start);
+                &number[1..]
+            } else {
+                &number
+            };
+            return limits.integer(digits).map_err(|e| e.to_string());
         }
         number.push(c);
         source.next();
     }
-    Err(ERR_UNTERMINATED_INTEGER.to_string())
+    let allowed_plus = limits.config.allow_plus_sign && has_plus_sign(number.as_bytes());
+    Err(unterminated_integer(&number.as_bytes()[usize::from(allowed_plus)..]).to_string())
 }
 
 /// Parses a string value from the source, expecting format '<length>:<string>'.
 /// Validates the string length and ensures the full string content is available.
+/// Content that is not valid UTF-8 is kept as a Bytes node.
 ///
 /// # Arguments
 /// * `source` - The source containing the string to parse
+/// * `limits` - The limits to enforce
 ///
 /// # Returns
-/// * `Result<Node, String>` - String Node or error message
-fn parse_string(source: &mut dyn ISource) -> Result<Node, String> {
-    let mut string = String::new();
-    for _ in 0..parse_string_length(source)? {
-        if let Some(c) = source.current() {
-            string.push(c);
-            source.next();
-        } else {
-            return Err(ERR_INVALID_STRING_LENGTH.to_string());
-        }
-    }
-    Ok(Node::Str(string))
+/// * `Result<Node, String>` - Str or Bytes Node or error message
+fn parse_string(source: &mut Tracked, limits: &Limits) -> Result<Node, String> {
+    Ok(Node::from_bytes(parse_limited_string_bytes(source, limits.config.max_string_length)?))
 }
 
 /// Parses a list from the source, expecting format 'l<elements>e'.
@@ -166,52 +396,60 @@ fn parse_string(source: &mut dyn ISource) -> Result<Node, String> {
 ///
 /// # Arguments
 /// * `source` - The source containing the list to parse
+/// * `depth` - Nesting depth of the list's elements
+/// * `limits` - The limits to enforce
 ///
 /// # Returns
 /// * `Result<Node, String>` - List Node or error message
-fn parse_list(source: &mut dyn ISource) -> Result<Node, String> {
+fn parse_list(source: &mut Tracked, depth: usize, limits: &mut Limits) -> Result<Node, String> {
     source.next(); // skip 'l'
     let mut list = Node::List(vec![]);
+    skip_whitespace(source, limits);
     while let Some(c) = source.current() {
         if c == LIST_END {
             source.next();
             return Ok(list);
         }
-        list.add_to_list(parse(source)?)
+        list.add_to_list(parse_value(source, Expecting::ListItem, depth, limits)?)
             .map_err(|e| e.to_string())?;
+        skip_whitespace(source, limits);
     }
     Err(ERR_UNTERMINATED_LIST.to_string())
 }
 
 /// Parses a dictionary from the source, expecting format 'd<key><value>...e'.
-/// Ensures keys are strings and are in sorted order.
+/// Ensures keys are strings and are in sorted (raw byte) order.
 ///
 /// # Arguments
 /// * `source` - The source containing the dictionary to parse
+/// * `depth` - Nesting depth of the dictionary's values
+/// * `limits` - The limits to enforce
 ///
 /// # Returns
 /// * `Result<Node, String>` - Dictionary Node or error message
-fn parse_dictionary(source: &mut dyn ISource) -> Result<Node, String> {
+fn parse_dictionary(source: &mut Tracked, depth: usize, limits: &mut Limits) -> Result<Node, String> {
     source.next(); // skip 'd'
     let mut dict = Dictionary(HashMap::new());
-    let mut last_key = String::new();
+    let mut last_key: Option<Vec<u8>> = None;
+    skip_whitespace(source, limits);
     while let Some(c) = source.current() {
         if c == DICT_END {
             source.next();
             return Ok(dict);
         }
-        match parse_string(source) {
-            Ok(Node::Str(key)) => {
-                if key <= last_key {
-                    return Err(ERR_DICT_KEYS_ORDER.to_string());
-                }
-                last_key = key.clone();
-                let value = parse(source)?;
-                dict.add_to_dictionary(&key, value)
-                    .map_err(|e| e.to_string())?;
-            }
-            _ => return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string()),
+        if !c.is_ascii_digit() {
+            return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string());
+        }
+        let key = parse_limited_string_bytes(source, limits.config.max_string_length)?;
+        if last_key.as_ref().is_some_and(|last| key <= *last) {
+            return Err(ERR_DICT_KEYS_ORDER.to_string());
         }
+        let value = parse_value(source, Expecting::DictValue, depth, limits)?;
+        // Keys are strings, so binary keys are decoded lossily (see `Dictionary`)
+        dict.add_to_dictionary(&String::from_utf8_lossy(&key), value)
+            .map_err(|e| e.to_string())?;
+        last_key = Some(key);
+        skip_whitespace(source, limits);
     }
     Err(ERR_UNTERMINATED_DICTIONARY.to_string())
 }
@@ -270,7 +508,7 @@ mod tests {
     #[test]
     fn parse_string_with_error() {
         let mut source = BufferSource::new(b"4:tes");
-        assert!(matches!(parse(&mut source), Err(s) if s == ERR_INVALID_STRING_LENGTH));
+        assert!(matches!(parse(&mut source), Err(s) if s == ERR_STRING_TOO_SHORT));
     }
 
     #[test]
@@ -302,4 +540,88 @@ mod tests {
         let mut source = BufferSource::new(b"d3:bbci32e3:abci42ee");
         assert!(matches!(parse(&mut source), Err(s) if s == ERR_DICT_KEYS_ORDER));
     }
+
+    #[test]
+    fn unexpected_character_reports_context() {
+        let mut source = BufferSource::new(b"d1:ali1exee");
+        assert_eq!(
+            parse(&mut source).unwrap_err(),
+            "Unexpected character: 'x' while expecting a list item or 'e' at depth 2"
+        );
+        let mut source = BufferSource::new(b"d1:aee");
+        assert_eq!(
+            parse(&mut source).unwrap_err(),
+            "Unexpected character: 'e' while expecting a dictionary value at depth 1"
+        );
+    }
+
+    #[test]
+    fn config_limits_are_enforced() {
+        let kind = |data: &[u8], config: ParserConfig| {
+            parse_bytes_with_config(data, &config).unwrap_err().kind()
+        };
+        let depth = ParserConfig::new().with_max_depth(2);
+        assert!(parse_bytes_with_config(b"lli1eee", &depth).is_ok());
+        assert_eq!(kind(b"llli1eeee", depth), crate::ErrorKind::TooDeep);
+        assert_eq!(kind(b"ld1:ai1eee", ParserConfig::new().with_max_depth(1)), crate::ErrorKind::TooDeep);
+
+        let strings = ParserConfig::new().with_max_string_length(3);
+        assert!(parse_bytes_with_config(b"d3:key3:vale", &strings).is_ok());
+        assert_eq!(kind(b"l4:spame", strings), crate::ErrorKind::StringTooLong);
+        assert_eq!(kind(b"d4:keysi1ee", strings), crate::ErrorKind::StringTooLong);
+        // The length prefix alone is enough to reject a huge string
+        assert_eq!(kind(b"99999999999:", strings), crate::ErrorKind::StringTooLong);
+
+        let elements = ParserConfig::new().with_max_elements(3);
+        assert!(parse_bytes_with_config(b"d1:ai1e1:bi2ee", &elements).is_ok());
+        assert_eq!(kind(b"li1ei2ei3ee", elements), crate::ErrorKind::TooManyNodes);
+
+        let size = ParserConfig::new().with_max_input_size(5);
+        assert!(parse_bytes_with_config(b"i123e", &size).is_ok());
+        assert_eq!(kind(b"i1234e", size), crate::ErrorKind::InputTooLarge);
+    }
+
+    #[test]
+    fn config_limits_apply_to_sources() {
+        let config = ParserConfig::new().with_max_input_size(6);
+        let mut source = BufferSource::new(b"li1ei2ee");
+        assert_eq!(parse_with_config(&mut source, &config).unwrap_err(), ERR_INPUT_TOO_LARGE);
+        // Only the parsed value counts, not data after it
+        let mut source = BufferSource::new(b"i1234etrailing");
+        assert!(matches!(parse_with_config(&mut source, &config), Ok(Node::Integer(1234))));
+        // Malformed input within the limit keeps its own error
+        let mut source = BufferSource::new(b"i12");
+        assert_eq!(parse_with_config(&mut source, &config).unwrap_err(), ERR_UNTERMINATED_INTEGER);
+        let mut source = BufferSource::new(b"lli1eee");
+        let config = ParserConfig::new().with_max_depth(1);
+        assert_eq!(parse_with_config(&mut source, &config).unwrap_err(), ERR_TOO_DEEP);
+    }
+
+    #[test]
+    fn lenient_toggles_are_independent() {
+        let plus = ParserConfig::new().with_allow_plus_sign(true);
+        let whitespace = ParserConfig::new().with_allow_whitespace(true);
+        let deviations = |data: &[u8], config: &ParserConfig| {
+            let (_, warnings) = parse_bytes_with_warnings(data, config).unwrap();
+            warnings.iter().map(|warning| (warning.deviation, warning.offset)).collect::<Vec<_>>()
+        };
+        assert_eq!(deviations(b"li+1ei-2ee", &plus), [(Deviation::PlusSign, 2)]);
+        assert_eq!(deviations(b" \r\nl i1e\td1:a i2e e e", &whitespace), [
+            (Deviation::Whitespace, 0),
+            (Deviation::Whitespace, 4),
+            (Deviation::Whitespace, 8),
+            (Deviation::Whitespace, 13),
+            (Deviation::Whitespace, 17),
+            (Deviation::Whitespace, 19),
+        ]);
+        assert!(parse_bytes_with_config(b"i+1e", &whitespace).is_err());
+        assert!(parse_bytes_with_config(b"l i1ee", &plus).is_err());
+        // Whitespace is not allowed inside a value, nor more than a '+' sign
+        for data in [&b"i 1e"[..], b"1 :a", b"d1:ai1e 1 :bi2ee", b"i+-1e", b"i++1e", b"i+e"] {
+            assert!(parse_bytes_with_config(data, &plus.with_allow_whitespace(true)).is_err(), "{:?}", data);
+        }
+        // A cut off integer with an allowed '+' can still be completed
+        assert!(parse_bytes_with_config(b"i+1", &plus).unwrap_err().is_incomplete());
+        assert_eq!(parse_bytes_with_warnings(b"  ", &whitespace).unwrap_err().kind(), crate::ErrorKind::EmptyInput);
+    }
 }
@@ -0,0 +1,177 @@
+//! Differential testing of the recursive, iterative and borrowed parsers.
+//!
+//! All three parsers are expected to accept and reject exactly the same inputs and,
+//! when they accept, to produce equivalent trees. [`parsers_agree`] runs every parser
+//! over the same bytes and reports the first disagreement, making it a convenient
+//! entry point for fuzz targets and consistency tests.
+
+use core::fmt;
+
+use crate::error::parse::ParseError;
+use crate::nodes::node::Node;
+use crate::parser::borrowed::parse_borrowed;
+use crate::parser::default::parse_bytes;
+use crate::parser::iterative::parse_bytes_iterative;
+
+/// Name reported for the recursive (reference) parser
+pub const RECURSIVE_PARSER: &str = "recursive";
+/// Name reported for the iterative parser
+pub const ITERATIVE_PARSER: &str = "iterative";
+/// Name reported for the borrowed (zero-copy) parser
+pub const BORROWED_PARSER: &str = "borrowed";
+
+/// A disagreement between the recursive parser and one of the other parsers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Divergence {
+    /// Name of the parser whose outcome differs from the recursive parser
+    pub parser: &'static str,
+    /// Outcome of the recursive (reference) parser
+    pub expected: Result<Node, ParseError>,
+    /// Outcome of the diverging parser
+    pub actual: Result<Node, ParseError>,
+}
+
+impl fmt::Display for Divergence {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} parser diverged from {} parser: expected ", self.parser, RECURSIVE_PARSER)?;
+        write_outcome(f, &self.expected)?;
+        write!(f, ", got ")?;
+        write_outcome(f, &self.actual)
+    }
+}
+
+/// Writes a parse outcome for a divergence report.
+fn write_outcome(f: &mut fmt::Formatter<'_>, outcome: &Result<Node, ParseError>) -> fmt::Result {
+    match outcome {
+        Ok(node) => write!(f, "Ok({})", node),
+        Err(e) => write!(f, "Err({})", e),
+    }
+}
+
+/// Runs the recursive, iterative and borrowed parsers over `data` and checks that
+/// they agree.
+///
+/// Parsers agree when they all accept the input and produce equal trees
+/// (borrowed results are converted with `BorrowedNode::to_node`), or all reject
+/// it and agree on whether it [ended mid-value](ParseError::is_incomplete).
+/// Error messages are not otherwise compared.
+///
+/// # Arguments
+/// * `data` - The bytes to parse
+///
+/// # Returns
+/// * `Ok(())` - All parsers agree
+/// * `Err(Divergence)` - The first parser found to disagree with the recursive parser
+///
+/// # Example
+/// ```
+/// use bencode_lib::parser::differential::parsers_agree;
+///
+/// assert!(parsers_agree(b"d3:agei25e4:name4:Johne").is_ok());
+/// assert!(parsers_agree(b"i-0e").is_ok());
+/// ```
+#[allow(clippy::result_large_err)]
+pub fn parsers_agree(data: &[u8]) -> Result<(), Divergence> {
+    let expected = parse_bytes(data);
+    let others = [
+        (ITERATIVE_PARSER, parse_bytes_iterative(data)),
+        (BORROWED_PARSER, parse_borrowed(data).map(|node| node.to_node())),
+    ];
+    for (parser, actual) in others {
+        let agree = match (&expected, &actual) {
+            (Ok(a), Ok(b)) => a == b,
+            (Err(a), Err(b)) => a.is_incomplete() == b.is_incomplete(),
+            _ => false,
+        };
+        if !agree {
+            return Err(Divergence {
+                parser,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsers_agree_on_valid_input() {
+        assert!(parsers_agree(b"d4:listli1ei2ee3:numi-42e3:str5:helloe").is_ok());
+    }
+
+    #[test]
+    fn parsers_agree_on_invalid_input() {
+        for input in [&b""[..], b"i1", b"li1e", b"d3:abci1e", b"5:abc", b"x", b":", b"di1ei2ee"] {
+            assert!(parsers_agree(input).is_ok(), "{:?}", input);
+        }
+    }
+
+    #[test]
+    fn cut_off_input_is_incomplete_for_every_parser() {
+        use crate::{parse_lossy, validate_bencode, validate_canonical, ErrorKind};
+
+        let document = b"d4:listli1ei-22ee3:str5:hello3:subd0:0:ee";
+        let kinds = |data: &[u8]| {
+            [
+                parse_bytes(data).map(|_| ()).unwrap_err().kind(),
+                parse_bytes_iterative(data).map(|_| ()).unwrap_err().kind(),
+                parse_borrowed(data).map(|_| ()).unwrap_err().kind(),
+                ErrorKind::from_message(&validate_bencode(data).unwrap_err()),
+                validate_canonical(data).unwrap_err().kind(),
+                parse_lossy(data).1[0].kind(),
+            ]
+        };
+        for end in 0..document.len() {
+            let cut = &document[..end];
+            assert!(kinds(cut).iter().all(ErrorKind::is_incomplete), "{:?}", kinds(cut));
+        }
+        for invalid in [&b"i1x"[..], b"i-0", b"i--", b"4x", b"d4x", b"dx", b"lx", b"l:", b"d1:ax"] {
+            assert!(!kinds(invalid).iter().any(ErrorKind::is_incomplete), "{:?}", invalid);
+            assert!(parsers_agree(invalid).is_ok());
+        }
+    }
+
+    #[test]
+    fn parsers_agree_on_negative_zero() {
+        assert!(parsers_agree(b"i-0e").is_ok());
+        assert!(parse_borrowed(b"i-0e").is_err());
+    }
+
+    #[test]
+    fn parsers_agree_on_empty_first_key() {
+        assert!(parsers_agree(b"d0:i1ee").is_ok());
+        assert!(parse_bytes(b"d0:i1ee").is_ok());
+    }
+
+    #[test]
+    fn parsers_agree_on_utf8_and_binary_strings() {
+        assert!(parsers_agree("6:héllo".as_bytes()).is_ok());
+        assert_eq!(parse_bytes("6:héllo".as_bytes()), Ok(Node::Str("héllo".to_string())));
+        assert!(parsers_agree(b"3:\xFF\x00\xFE").is_ok());
+    }
+
+    #[test]
+    fn parsers_agree_on_binary_key_order() {
+        // Ordered by raw bytes even though both keys decode to the replacement character
+        assert!(parsers_agree(b"d1:\xFEi1e1:\xFFi2ee").is_ok());
+        assert!(parsers_agree(b"d1:\xFFi1e1:\xFEi2ee").is_ok());
+        assert!(parse_bytes(b"d1:\xFFi1e1:\xFEi2ee").is_err());
+    }
+
+    #[test]
+    fn divergence_display_names_parser() {
+        let divergence = Divergence {
+            parser: BORROWED_PARSER,
+            expected: Err(ParseError::new("Invalid integer", 2)),
+            actual: Ok(Node::Integer(0)),
+        };
+        assert_eq!(
+            divergence.to_string(),
+            "borrowed parser diverged from recursive parser: expected Err(Invalid integer), got Ok(0)"
+        );
+    }
+}
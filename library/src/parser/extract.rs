@@ -0,0 +1,493 @@
+//! Streaming extraction of selected key paths, built on the tokenizer.
+//!
+//! Rather than parsing a whole document, [`extract_streaming`] walks the tokens,
+//! descends only into containers that lie on a subscribed path, skips everything
+//! else and stops as soon as every path has been delivered.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use core::ops::Range;
+
+use crate::error::messages::ERR_EMPTY_INPUT;
+use crate::error::parse::ParseError;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::parser::borrowed::parse_borrowed;
+use crate::parser::tokenizer::{Token, TokenKind, Tokenizer};
+
+/// Separator between the segments of a key path
+pub const PATH_SEPARATOR: char = '.';
+
+/// Position inside an open container while walking the tokens
+enum Frame<'a> {
+    /// Inside a list; holds the number of elements started so far
+    List(usize),
+    /// Inside a dictionary; holds the key of the current value
+    Dict(&'a [u8]),
+}
+
+/// Splits a key path into its segments; the empty path has none.
+pub(crate) fn split_path(path: &str) -> Vec<&str> {
+    if path.is_empty() {
+        Vec::new()
+    } else {
+        path.split(PATH_SEPARATOR).collect()
+    }
+}
+
+/// Tracks the key path of each value while walking tokens.
+pub(crate) struct TokenPaths {
+    /// Path of each open container, with the next index of a list
+    open: Vec<(String, Option<usize>)>,
+    /// Last key read in the innermost dictionary, decoded lossily
+    key: String,
+}
+
+impl TokenPaths {
+    pub(crate) fn new() -> Self {
+        Self {
+            open: Vec::new(),
+            key: String::new(),
+        }
+    }
+
+    /// Feeds the next token, returning the path of the value it starts, or None
+    /// for keys and end markers
+    pub(crate) fn next_path(&mut self, kind: &TokenKind) -> Option<String> {
+        let path = match kind {
+            TokenKind::Key(key) => {
+                self.key = String::from_utf8_lossy(key).into_owned();
+                return None;
+            }
+            TokenKind::ListEnd | TokenKind::DictEnd => {
+                self.open.pop();
+                return None;
+            }
+            _ => match self.open.last_mut() {
+                None => String::new(),
+                Some((parent, next_index)) => {
+                    let segment = match next_index {
+                        Some(index) => {
+                            *index += 1;
+                            (*index - 1).to_string()
+                        }
+                        None => core::mem::take(&mut self.key),
+                    };
+                    if parent.is_empty() {
+                        segment
+                    } else {
+                        format!("{}{}{}", parent, PATH_SEPARATOR, segment)
+                    }
+                }
+            },
+        };
+        match kind {
+            TokenKind::ListStart => self.open.push((path.clone(), Some(0))),
+            TokenKind::DictStart => self.open.push((path.clone(), None)),
+            _ => {}
+        }
+        Some(path)
+    }
+}
+
+/// Returns true if `segment` addresses the value at `frame`.
+fn segment_matches(segment: &str, frame: &Frame) -> bool {
+    match frame {
+        Frame::List(count) => segment.parse::<usize>().is_ok_and(|index| index + 1 == *count),
+        Frame::Dict(key) => segment.as_bytes() == *key,
+    }
+}
+
+/// Returns true if `path` starts with the segments of the current position.
+fn path_has_prefix(path: &[&str], frames: &[Frame]) -> bool {
+    path.len() >= frames.len() && path.iter().zip(frames).all(|(segment, frame)| segment_matches(segment, frame))
+}
+
+/// Extracts the values at the given key paths, invoking `callback` for each as soon
+/// as it is read.
+///
+/// Paths are dot separated dictionary keys, with numeric segments indexing lists
+/// (e.g. `"info.files.0.length"`); the empty path `""` selects the root. Containers
+/// that cannot contain a subscribed path are skipped without being decoded, and
+/// extraction stops once all paths have been seen, so input after that point is not
+/// validated. Paths that do not occur in the input are silently never reported.
+/// A container that is subscribed while also containing other subscribed paths is
+/// reported when it closes, after those nested paths.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded input
+/// * `paths` - Key paths to extract
+/// * `callback` - Called with each subscribed path and its borrowed value
+///
+/// # Returns
+/// * `Ok(())` - Extraction finished (all paths seen or input exhausted)
+/// * `Err(ParseError)` - The input read before finishing is malformed
+///
+/// # Example
+/// ```
+/// use bencode_lib::extract_streaming;
+///
+/// let data = b"d8:announce3:url4:infod4:name4:filee5:otheri1ee";
+/// let mut found = Vec::new();
+/// extract_streaming(data, &["info.name", "announce"], |path, value| {
+///     found.push((path.to_string(), value.as_bytes().unwrap().to_vec()));
+/// })
+/// .unwrap();
+/// assert_eq!(found, vec![
+///     ("announce".to_string(), b"url".to_vec()),
+///     ("info.name".to_string(), b"file".to_vec()),
+/// ]);
+/// ```
+pub fn extract_streaming<'a, F>(source: &'a [u8], paths: &[&str], mut callback: F) -> Result<(), ParseError>
+where
+    F: FnMut(&str, BorrowedNode<'a>),
+{
+    let split: Vec<Vec<&str>> = paths.iter().map(|path| split_path(path)).collect();
+    let mut pending: Vec<bool> = split.iter().map(|_| true).collect();
+    let mut remaining = pending.len();
+    let mut frames: Vec<Frame<'a>> = Vec::new();
+    // Subscribed containers that are also descended into: (depth, offset, path index)
+    let mut open_matches: Vec<(usize, usize, usize)> = Vec::new();
+    let mut tokenizer = Tokenizer::new(source);
+
+    while remaining > 0 {
+        let Some(token) = tokenizer.next() else {
+            break;
+        };
+        let token = token?;
+        let is_container = match token.kind {
+            TokenKind::Key(key) => {
+                if let Some(Frame::Dict(current)) = frames.last_mut() {
+                    *current = key;
+                }
+                continue;
+            }
+            TokenKind::ListEnd | TokenKind::DictEnd => {
+                frames.pop();
+                while let Some(&(depth, offset, index)) = open_matches.last() {
+                    if depth != frames.len() {
+                        break;
+                    }
+                    open_matches.pop();
+                    let value = parse_borrowed(&source[offset..token.end])
+                        .map_err(|e| ParseError::new(e, offset))?;
+                    callback(paths[index], value);
+                    remaining -= 1;
+                }
+                continue;
+            }
+            TokenKind::ListStart | TokenKind::DictStart => true,
+            TokenKind::Integer(_) | TokenKind::Bytes(_) => false,
+        };
+
+        // The value just started sits at the path described by `frames`
+        if let Some(Frame::List(count)) = frames.last_mut() {
+            *count += 1;
+        }
+        let mut matches = Vec::new();
+        let mut descend = false;
+        for (index, path) in split.iter().enumerate() {
+            if pending[index] && path_has_prefix(path, &frames) {
+                if path.len() == frames.len() {
+                    pending[index] = false;
+                    matches.push(index);
+                } else {
+                    descend = true;
+                }
+            }
+        }
+
+        if is_container && descend {
+            // Matches on this container are delivered when it closes
+            let depth = frames.len();
+            open_matches.extend(matches.iter().map(|&index| (depth, token.offset, index)));
+            frames.push(match token.kind {
+                TokenKind::ListStart => Frame::List(0),
+                _ => Frame::Dict(&[]),
+            });
+            continue;
+        }
+        let end = if is_container {
+            tokenizer.skip_container()?
+        } else {
+            token.end
+        };
+        for index in matches {
+            let value = parse_borrowed(&source[token.offset..end])
+                .map_err(|e| ParseError::new(e, token.offset))?;
+            callback(paths[index], value);
+            remaining -= 1;
+        }
+    }
+    Ok(())
+}
+
+/// Returns the next token, treating the end of input as truncation (the tokenizer
+/// reports truncated input itself, so this only guards against misuse).
+pub(crate) fn next_token<'a>(tokenizer: &mut Tokenizer<'a>) -> Result<Token<'a>, ParseError> {
+    match tokenizer.next() {
+        Some(token) => token,
+        None => Err(ParseError::new(ERR_EMPTY_INPUT, Tokenizer::position(tokenizer))),
+    }
+}
+
+/// Returns the end offset of the value starting with `token`, skipping its content.
+pub(crate) fn value_end(tokenizer: &mut Tokenizer, token: &Token) -> Result<usize, ParseError> {
+    match token.kind {
+        TokenKind::ListStart | TokenKind::DictStart => tokenizer.skip_container(),
+        _ => Ok(token.end),
+    }
+}
+
+/// Finds the raw byte span of the value at a key path without decoding anything
+/// else.
+///
+/// Paths use the same syntax as [`extract_streaming`]. Values before the target are
+/// skipped and nothing after it is read.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded input
+/// * `path` - Key path of the value to find
+///
+/// # Returns
+/// * `Ok(Some(Range<usize>))` - Span of the encoded value
+/// * `Ok(None)` - The path does not exist
+/// * `Err(ParseError)` - The input read before finding the value is malformed
+///
+/// # Example
+/// ```
+/// use bencode_lib::parser::extract::value_span;
+///
+/// let data = b"d8:announce3:url4:infod4:name1:xee";
+/// let span = value_span(data, "info").unwrap().unwrap();
+/// assert_eq!(&data[span], b"d4:name1:xe");
+/// ```
+pub fn value_span(source: &[u8], path: &str) -> Result<Option<Range<usize>>, ParseError> {
+    let mut tokenizer = Tokenizer::new(source);
+    let mut token = next_token(&mut tokenizer)?;
+    for segment in split_path(path) {
+        match token.kind {
+            TokenKind::DictStart => loop {
+                let key = next_token(&mut tokenizer)?;
+                let TokenKind::Key(key) = key.kind else {
+                    return Ok(None);
+                };
+                let value = next_token(&mut tokenizer)?;
+                if key == segment.as_bytes() {
+                    token = value;
+                    break;
+                }
+                value_end(&mut tokenizer, &value)?;
+            },
+            TokenKind::ListStart => {
+                let Ok(index) = segment.parse::<usize>() else {
+                    return Ok(None);
+                };
+                let mut element = next_token(&mut tokenizer)?;
+                for _ in 0..index {
+                    if element.kind == TokenKind::ListEnd {
+                        return Ok(None);
+                    }
+                    value_end(&mut tokenizer, &element)?;
+                    element = next_token(&mut tokenizer)?;
+                }
+                if element.kind == TokenKind::ListEnd {
+                    return Ok(None);
+                }
+                token = element;
+            }
+            _ => return Ok(None),
+        }
+    }
+    let end = value_end(&mut tokenizer, &token)?;
+    Ok(Some(token.offset..end))
+}
+
+/// Position inside an open container while looking for the path of an offset
+enum Cursor<'a> {
+    /// Inside a list; holds the number of elements started so far
+    List(usize),
+    /// Inside a dictionary; holds the key of the value being read, if any
+    Dict(Option<&'a [u8]>),
+}
+
+/// Returns the key path of the value being read at byte `offset` of `source`.
+///
+/// Used to locate errors: the path names the innermost value that was being
+/// read (or was about to be read) when the error at `offset` was detected.
+/// Stops early, keeping the path reached so far, if the input is malformed
+/// before `offset`.
+pub(crate) fn path_at(source: &[u8], offset: usize) -> String {
+    let mut cursors: Vec<Cursor> = Vec::new();
+    for token in Tokenizer::new(source) {
+        let Ok(token) = token else {
+            break;
+        };
+        if token.offset >= offset {
+            break;
+        }
+        match token.kind {
+            TokenKind::Key(key) => {
+                if let Some(Cursor::Dict(current)) = cursors.last_mut() {
+                    *current = Some(key);
+                }
+                continue;
+            }
+            TokenKind::ListEnd | TokenKind::DictEnd => {
+                cursors.pop();
+            }
+            TokenKind::ListStart | TokenKind::DictStart => {
+                if let Some(Cursor::List(count)) = cursors.last_mut() {
+                    *count += 1;
+                }
+                cursors.push(match token.kind {
+                    TokenKind::ListStart => Cursor::List(0),
+                    _ => Cursor::Dict(None),
+                });
+                continue;
+            }
+            TokenKind::Integer(_) | TokenKind::Bytes(_) => {
+                if let Some(Cursor::List(count)) = cursors.last_mut() {
+                    *count += 1;
+                }
+            }
+        }
+        // A value was completed, so its dictionary now expects a key
+        if let Some(Cursor::Dict(current)) = cursors.last_mut() {
+            *current = None;
+        }
+    }
+
+    let last = cursors.len().saturating_sub(1);
+    let mut segments: Vec<String> = Vec::new();
+    for (depth, cursor) in cursors.iter().enumerate() {
+        match cursor {
+            // Enclosing lists hold the element being read; the innermost one
+            // failed before its next element started
+            Cursor::List(count) if depth == last => segments.push(count.to_string()),
+            Cursor::List(count) => segments.push(count.saturating_sub(1).to_string()),
+            Cursor::Dict(Some(key)) => segments.push(String::from_utf8_lossy(key).into_owned()),
+            Cursor::Dict(None) => break,
+        }
+    }
+    segments.join(&PATH_SEPARATOR.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::messages::*;
+
+    const TORRENT: &[u8] =
+        b"d8:announce3:url4:infod5:filesld6:lengthi1e4:pathl1:aeed6:lengthi2e4:pathl1:beee4:name3:dir12:piece lengthi16ee3:numi7ee";
+
+    fn extract<'a>(data: &'a [u8], paths: &[&str]) -> Result<Vec<(String, BorrowedNode<'a>)>, ParseError> {
+        let mut found = Vec::new();
+        extract_streaming(data, paths, |path, value| found.push((path.to_string(), value)))?;
+        Ok(found)
+    }
+
+    #[test]
+    fn extracts_scalars_in_document_order() {
+        let found = extract(TORRENT, &["num", "info.name", "announce"]).unwrap();
+        assert_eq!(
+            found,
+            vec![
+                ("announce".to_string(), BorrowedNode::Bytes(b"url")),
+                ("info.name".to_string(), BorrowedNode::Bytes(b"dir")),
+                ("num".to_string(), BorrowedNode::Integer(7)),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_list_elements_by_index() {
+        let found = extract(TORRENT, &["info.files.1.length", "info.files.0.path.0"]).unwrap();
+        assert_eq!(
+            found,
+            vec![
+                ("info.files.0.path.0".to_string(), BorrowedNode::Bytes(b"a")),
+                ("info.files.1.length".to_string(), BorrowedNode::Integer(2)),
+            ]
+        );
+    }
+
+    #[test]
+    fn extracts_containers_and_nested_paths_together() {
+        let found = extract(TORRENT, &["info", "info.name"]).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0], ("info.name".to_string(), BorrowedNode::Bytes(b"dir")));
+        assert_eq!(found[1].0, "info");
+        match &found[1].1 {
+            BorrowedNode::Dictionary(info) => assert_eq!(info.len(), 3),
+            _ => panic!("Expected info dictionary"),
+        }
+    }
+
+    #[test]
+    fn extracts_root_with_empty_path() {
+        let found = extract(b"li1ee", &[""]).unwrap();
+        assert_eq!(found, vec![("".to_string(), BorrowedNode::List(vec![BorrowedNode::Integer(1)]))]);
+    }
+
+    #[test]
+    fn stops_once_all_paths_are_seen() {
+        // Everything after "announce" is garbage and is never read
+        let found = extract(b"d8:announce3:urlXXXX", &["announce"]).unwrap();
+        assert_eq!(found, vec![("announce".to_string(), BorrowedNode::Bytes(b"url"))]);
+    }
+
+    #[test]
+    fn missing_paths_are_not_reported() {
+        assert!(extract(TORRENT, &["info.missing", "num.x", "info.files.9"]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn reports_errors_before_completion() {
+        assert_eq!(
+            extract(b"d1:ai1e1:bi", &["b"]),
+            Err(ParseError::new(ERR_UNTERMINATED_INTEGER, 10))
+        );
+    }
+
+    #[test]
+    fn value_span_finds_raw_bytes() {
+        let span = |path| value_span(TORRENT, path).unwrap().map(|span| &TORRENT[span]);
+        assert_eq!(span(""), Some(TORRENT));
+        assert_eq!(span("announce"), Some(&b"3:url"[..]));
+        assert_eq!(span("info.files.1"), Some(&b"d6:lengthi2e4:pathl1:bee"[..]));
+        assert_eq!(span("info.files.0.path.0"), Some(&b"1:a"[..]));
+        assert_eq!(span("num"), Some(&b"i7e"[..]));
+    }
+
+    #[test]
+    fn value_span_missing_paths() {
+        for path in ["missing", "info.files.2", "info.files.x", "num.x", "announce.0", "info.zzz"] {
+            assert_eq!(value_span(TORRENT, path), Ok(None), "{}", path);
+        }
+    }
+
+    #[test]
+    fn value_span_errors_only_before_target() {
+        assert_eq!(value_span(b"d1:ai1e1:bi", "a"), Ok(Some(4..7)));
+        assert_eq!(
+            value_span(b"d1:ai1e1:bi", "b"),
+            Err(ParseError::new(ERR_UNTERMINATED_INTEGER, 10))
+        );
+        assert_eq!(value_span(b"", ""), Err(ParseError::new(ERR_EMPTY_INPUT, 0)));
+    }
+
+    #[test]
+    fn path_at_names_the_value_being_read() {
+        let data = b"d4:infod5:filesld6:lengthi1eed6:lengthxeeee";
+        let offset = data.iter().position(|&b| b == b'x').unwrap();
+        assert_eq!(path_at(data, offset), "info.files.1.length");
+        assert_eq!(path_at(b"li1ei2ex", 7), "2");
+        assert_eq!(path_at(b"d1:ai1ex", 7), "");
+        assert_eq!(path_at(b"x", 0), "");
+    }
+}
@@ -12,9 +12,15 @@ use alloc::{
 
 use crate::HashMap;
 use crate::Node::Dictionary;
+use crate::config::ParseBudget;
+use crate::error::budget::BudgetError;
 use crate::error::messages::*;
+use crate::error::parse::ParseError;
+use crate::io::sources::tracked::Tracked;
 use crate::io::traits::ISource;
+use crate::nodes::integer::parse_integer_text;
 use crate::nodes::node::Node;
+use crate::parser::common::{unterminated_integer, unterminated_string_length};
 
 /// Parser state machine states
 enum ParseState {
@@ -22,16 +28,16 @@ enum ParseState {
     ParseValue,
     /// Inside a list, collecting elements
     InList { elements: Vec<Node> },
-    /// Inside a dictionary, expecting a key
+    /// Inside a dictionary, expecting a key (raw bytes of the previous key kept for ordering)
     InDictKey {
         entries: HashMap<String, Node>,
-        last_key: String,
+        last_key: Option<Vec<u8>>,
     },
     /// Inside a dictionary, expecting a value for the given key
     InDictValue {
         entries: HashMap<String, Node>,
         key: String,
-        last_key: String,
+        last_key: Option<Vec<u8>>,
     },
 }
 
@@ -45,22 +51,37 @@ const LIST_START: char = 'l';
 const DICT_START: char = 'd';
 /// Separator between string length and content (':')
 const STRING_SEPARATOR: char = ':';
+/// Number of steps between checks of a budget deadline
+#[cfg(feature = "std")]
+const DEADLINE_CHECK_INTERVAL: usize = 256;
 
 /// Parses the length prefix of a bencode string.
 fn parse_string_length(source: &mut dyn ISource) -> Result<usize, String> {
     let mut length = String::new();
     while let Some(c) = source.current() {
+        source.next();
         if c == STRING_SEPARATOR {
-            source.next();
-            break;
+            return length
+                .parse::<usize>()
+                .map_err(|_| ERR_INVALID_STRING_LENGTH.to_string());
         }
         length.push(c);
-        source.next();
     }
+    Err(unterminated_string_length(length.as_bytes()).to_string())
+}
 
-    length
-        .parse::<usize>()
-        .map_err(|_| ERR_INVALID_STRING_LENGTH.to_string())
+/// Reads the raw bytes of a bencode string.
+fn parse_string_bytes(source: &mut dyn ISource) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for _ in 0..parse_string_length(source)? {
+        if let Some(c) = source.current() {
+            bytes.push(c as u8);
+            source.next();
+        } else {
+            return Err(ERR_STRING_TOO_SHORT.to_string());
+        }
+    }
+    Ok(bytes)
 }
 
 /// Parses an integer value from the source.
@@ -70,32 +91,19 @@ fn parse_integer(source: &mut dyn ISource) -> Result<Node, String> {
     while let Some(c) = source.current() {
         if c == END_MARKER {
             source.next();
-            if number == "-0" {
-                return Err(ERR_INVALID_INTEGER.to_string());
-            }
-            return number
-                .parse::<i64>()
+            return parse_integer_text(&number)
                 .map(Node::Integer)
-                .map_err(|_| ERR_INVALID_INTEGER.to_string());
+                .map_err(|e| e.to_string());
         }
         number.push(c);
         source.next();
     }
-    Err(ERR_UNTERMINATED_INTEGER.to_string())
+    Err(unterminated_integer(number.as_bytes()).to_string())
 }
 
-/// Parses a string value from the source.
+/// Parses a string value from the source, keeping non UTF-8 content as a Bytes node.
 fn parse_string(source: &mut dyn ISource) -> Result<Node, String> {
-    let mut string = String::new();
-    for _ in 0..parse_string_length(source)? {
-        if let Some(c) = source.current() {
-            string.push(c);
-            source.next();
-        } else {
-            return Err(ERR_INVALID_STRING_LENGTH.to_string());
-        }
-    }
-    Ok(Node::Str(string))
+    Ok(Node::from_bytes(parse_string_bytes(source)?))
 }
 
 /// Iterative parser that uses an explicit stack instead of recursion.
@@ -105,7 +113,7 @@ fn parse_string(source: &mut dyn ISource) -> Result<Node, String> {
 /// * `source` - The source containing bencode-encoded data
 ///
 /// # Returns
-/// * `Result<Node, String>` - Parsed Node or error message
+/// * `Result<Node, ParseError>` - Parsed Node or the error and its offset
 ///
 /// # Example
 /// ```
@@ -116,11 +124,79 @@ fn parse_string(source: &mut dyn ISource) -> Result<Node, String> {
 /// let mut source = BufferSource::new(data);
 /// let node = parse_iterative(&mut source).unwrap();
 /// ```
-pub fn parse_iterative(source: &mut dyn ISource) -> Result<Node, String> {
+pub fn parse_iterative(source: &mut dyn ISource) -> Result<Node, ParseError> {
+    let mut tracked = Tracked::new(source, usize::MAX);
+    run_iterative(&mut tracked, &ParseBudget::new()).map_err(|stop| {
+        let message = match stop {
+            Stop::Invalid(message) => message,
+            // An unlimited budget never runs out
+            Stop::Timeout { .. } => ERR_TIMEOUT.to_string(),
+        };
+        ParseError::new(message, tracked.position())
+    })
+}
+
+/// Iterative parser that gives up once `budget` is exhausted, so adversarial input
+/// cannot stall the calling thread indefinitely.
+///
+/// # Arguments
+/// * `source` - The source containing bencode-encoded data
+/// * `budget` - Step limit and/or deadline for the parse
+///
+/// # Returns
+/// * `Ok(Node)` - The parsed tree
+/// * `Err(BudgetError::Timeout)` - The budget ran out first
+/// * `Err(BudgetError::Parse)` - The input is malformed
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_iterative_with_budget, BudgetError, BufferSource, ParseBudget};
+///
+/// let budget = ParseBudget::new().with_max_steps(3);
+/// let mut source = BufferSource::new(b"li1ei2ei3ei4ee");
+/// let result = parse_iterative_with_budget(&mut source, &budget);
+/// assert_eq!(result, Err(BudgetError::Timeout { steps: 3 }));
+/// ```
+pub fn parse_iterative_with_budget(source: &mut dyn ISource, budget: &ParseBudget) -> Result<Node, BudgetError> {
+    let mut tracked = Tracked::new(source, usize::MAX);
+    run_iterative(&mut tracked, budget).map_err(|stop| match stop {
+        Stop::Timeout { steps } => BudgetError::Timeout { steps },
+        Stop::Invalid(message) => BudgetError::Parse(ParseError::new(message, tracked.position())),
+    })
+}
+
+/// Why the state machine stopped before producing a value
+enum Stop {
+    /// The budget ran out after this many steps
+    Timeout { steps: usize },
+    /// The input is malformed (one of the `error::messages` strings)
+    Invalid(String),
+}
+
+impl From<String> for Stop {
+    fn from(message: String) -> Self {
+        Stop::Invalid(message)
+    }
+}
+
+/// Parses the state machine, checking `budget` before every step.
+fn run_iterative(source: &mut dyn ISource, budget: &ParseBudget) -> Result<Node, Stop> {
     let mut stack: Vec<ParseState> = vec![ParseState::ParseValue];
     let mut value_stack: Vec<Node> = vec![];
+    let mut steps: usize = 0;
 
     while let Some(state) = stack.pop() {
+        steps += 1;
+        if budget.max_steps.is_some_and(|max_steps| steps > max_steps) {
+            return Err(Stop::Timeout { steps: steps - 1 });
+        }
+        // Reading the clock is comparatively slow, so only do it periodically
+        #[cfg(feature = "std")]
+        if steps.is_multiple_of(DEADLINE_CHECK_INTERVAL)
+            && budget.deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline)
+        {
+            return Err(Stop::Timeout { steps });
+        }
         match state {
             ParseState::ParseValue => {
                 match source.current() {
@@ -132,6 +208,8 @@ pub fn parse_iterative(source: &mut dyn ISource) -> Result<Node, String> {
                         if source.current() == Some(END_MARKER) {
                             source.next();
                             value_stack.push(Node::List(vec![]));
+                        } else if source.is_eof() {
+                            return Err(ERR_UNTERMINATED_LIST.to_string().into());
                         } else {
                             stack.push(ParseState::InList { elements: vec![] });
                             stack.push(ParseState::ParseValue);
@@ -145,22 +223,27 @@ pub fn parse_iterative(source: &mut dyn ISource) -> Result<Node, String> {
                         } else {
                             stack.push(ParseState::InDictKey {
                                 entries: HashMap::new(),
-                                last_key: String::new(),
+                                last_key: None,
                             });
-                            stack.push(ParseState::ParseValue);
                         }
                     }
                     Some('0'..='9') => {
                         value_stack.push(parse_string(source)?);
                     }
                     Some(STRING_SEPARATOR) => {
-                        return Err(ERR_INVALID_STRING_LENGTH.to_string());
+                        return Err(ERR_INVALID_STRING_LENGTH.to_string().into());
                     }
                     Some(c) => {
-                        return Err(unexpected_character(c));
+                        // Only open containers remain below a pending value
+                        let expecting = match stack.last() {
+                            None => Expecting::Value,
+                            Some(ParseState::InList { .. }) => Expecting::ListItem,
+                            Some(_) => Expecting::DictValue,
+                        };
+                        return Err(unexpected_character(c, expecting, stack.len()).into());
                     }
                     None => {
-                        return Err(ERR_EMPTY_INPUT.to_string());
+                        return Err(ERR_EMPTY_INPUT.to_string().into());
                     }
                 }
             }
@@ -183,27 +266,31 @@ pub fn parse_iterative(source: &mut dyn ISource) -> Result<Node, String> {
                         stack.push(ParseState::ParseValue);
                     }
                     None => {
-                        return Err(ERR_UNTERMINATED_LIST.to_string());
+                        return Err(ERR_UNTERMINATED_LIST.to_string().into());
                     }
                 }
             }
 
             ParseState::InDictKey { entries, last_key } => {
-                // Pop the parsed key from value_stack
-                if let Some(Node::Str(key)) = value_stack.pop() {
-                    if key <= last_key {
-                        return Err(ERR_DICT_KEYS_ORDER.to_string());
-                    }
-                    // Now parse the value for this key
-                    stack.push(ParseState::InDictValue {
-                        entries,
-                        key: key.clone(),
-                        last_key: key,
-                    });
-                    stack.push(ParseState::ParseValue);
-                } else {
-                    return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string());
+                if source.is_eof() {
+                    return Err(ERR_UNTERMINATED_DICTIONARY.to_string().into());
                 }
+                // Keys are read directly so ordering is checked on the raw bytes
+                if !matches!(source.current(), Some('0'..='9')) {
+                    return Err(ERR_DICT_KEY_MUST_BE_STRING.to_string().into());
+                }
+                let key = parse_string_bytes(source)?;
+                if last_key.is_some_and(|last| key <= last) {
+                    return Err(ERR_DICT_KEYS_ORDER.to_string().into());
+                }
+                // Now parse the value for this key; keys are strings, so
+                // binary keys are decoded lossily (see `Dictionary`)
+                stack.push(ParseState::InDictValue {
+                    entries,
+                    key: String::from_utf8_lossy(&key).into_owned(),
+                    last_key: Some(key),
+                });
+                stack.push(ParseState::ParseValue);
             }
 
             ParseState::InDictValue {
@@ -225,10 +312,9 @@ pub fn parse_iterative(source: &mut dyn ISource) -> Result<Node, String> {
                     Some(_) => {
                         // Continue parsing next key-value pair
                         stack.push(ParseState::InDictKey { entries, last_key });
-                        stack.push(ParseState::ParseValue);
                     }
                     None => {
-                        return Err(ERR_UNTERMINATED_DICTIONARY.to_string());
+                        return Err(ERR_UNTERMINATED_DICTIONARY.to_string().into());
                     }
                 }
             }
@@ -239,7 +325,7 @@ pub fn parse_iterative(source: &mut dyn ISource) -> Result<Node, String> {
     if value_stack.len() == 1 {
         Ok(value_stack.pop().unwrap())
     } else {
-        Err("Parser error: unexpected state".to_string())
+        Err("Parser error: unexpected state".to_string().into())
     }
 }
 
@@ -249,21 +335,35 @@ pub fn parse_iterative(source: &mut dyn ISource) -> Result<Node, String> {
 /// * `data` - The byte slice containing bencode-encoded data
 ///
 /// # Returns
-/// * `Result<Node, String>` - Parsed Node or error message
-pub fn parse_bytes_iterative(data: &[u8]) -> Result<Node, String> {
+/// * `Result<Node, ParseError>` - Parsed Node or the error and its offset
+pub fn parse_bytes_iterative(data: &[u8]) -> Result<Node, ParseError> {
     use crate::io::sources::buffer::Buffer;
     let mut source = Buffer::new(data);
     parse_iterative(&mut source)
 }
 
+/// Parses bencode data from a byte slice using the iterative parser, limited by `budget`.
+///
+/// # Arguments
+/// * `data` - The byte slice containing bencode-encoded data
+/// * `budget` - Step limit and/or deadline for the parse
+///
+/// # Returns
+/// * `Result<Node, BudgetError>` - Parsed Node, or a timeout or parse error
+pub fn parse_bytes_iterative_with_budget(data: &[u8], budget: &ParseBudget) -> Result<Node, BudgetError> {
+    use crate::io::sources::buffer::Buffer;
+    let mut source = Buffer::new(data);
+    parse_iterative_with_budget(&mut source, budget)
+}
+
 /// Parses bencode data from a string using iterative parser.
 ///
 /// # Arguments
 /// * `data` - The string containing bencode-encoded data
 ///
 /// # Returns
-/// * `Result<Node, String>` - Parsed Node or error message
-pub fn parse_str_iterative(data: &str) -> Result<Node, String> {
+/// * `Result<Node, ParseError>` - Parsed Node or the error and its offset
+pub fn parse_str_iterative(data: &str) -> Result<Node, ParseError> {
     parse_bytes_iterative(data.as_bytes())
 }
 
@@ -371,4 +471,40 @@ mod tests {
         let mut source = BufferSource::new(b"d3:bbci32e3:abci42ee");
         assert!(matches!(parse_iterative(&mut source), Err(s) if s == ERR_DICT_KEYS_ORDER));
     }
+
+    #[test]
+    fn budget_allows_parse_within_limit() {
+        let budget = ParseBudget::new().with_max_steps(100);
+        assert_eq!(
+            parse_bytes_iterative_with_budget(b"d1:ali1ei2eee", &budget),
+            parse_bytes_iterative(b"d1:ali1ei2eee").map_err(BudgetError::Parse)
+        );
+    }
+
+    #[test]
+    fn budget_step_limit_times_out() {
+        let data = crate::testdata::gen_deeply_nested(1000);
+        let budget = ParseBudget::new().with_max_steps(500);
+        assert_eq!(
+            parse_bytes_iterative_with_budget(&data, &budget),
+            Err(BudgetError::Timeout { steps: 500 })
+        );
+    }
+
+    #[test]
+    fn budget_expired_deadline_times_out() {
+        let data = crate::testdata::gen_deeply_nested(1000);
+        let budget = ParseBudget::new().with_deadline(std::time::Instant::now());
+        let result = parse_bytes_iterative_with_budget(&data, &budget);
+        assert!(matches!(result, Err(BudgetError::Timeout { steps }) if steps == DEADLINE_CHECK_INTERVAL));
+    }
+
+    #[test]
+    fn budget_reports_parse_errors() {
+        let budget = ParseBudget::new().with_max_steps(100);
+        assert_eq!(
+            parse_bytes_iterative_with_budget(b"i1x2e", &budget),
+            Err(BudgetError::Parse(ParseError::new(ERR_INVALID_INTEGER, 5)))
+        );
+    }
 }
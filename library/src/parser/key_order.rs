@@ -0,0 +1,189 @@
+//! Standalone dictionary key ordering checks, built on the tokenizer.
+//!
+//! Bencode requires dictionary keys to be unique and sorted by their raw bytes.
+//! Many legacy torrents break this rule while being otherwise well formed, so these
+//! functions report exactly where the ordering is wrong without doing a full parse.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use core::fmt;
+
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+
+/// A dictionary key that is not strictly greater than the key before it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyOrderViolation {
+    /// Byte offset of the offending key
+    pub offset: usize,
+    /// Byte offset of the dictionary containing the key (its `d` marker)
+    pub dict_offset: usize,
+    /// The preceding key in the same dictionary
+    pub prev: Vec<u8>,
+    /// The offending key, less than or equal to `prev`
+    pub curr: Vec<u8>,
+}
+
+impl fmt::Display for KeyOrderViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.curr == self.prev {
+            "duplicate"
+        } else {
+            "out of order"
+        };
+        write!(
+            f,
+            "{} key {:?} after {:?} at offset {}",
+            kind,
+            String::from_utf8_lossy(&self.curr),
+            String::from_utf8_lossy(&self.prev),
+            self.offset
+        )
+    }
+}
+
+/// An open dictionary: offset of its `d` marker and the last key seen so far
+type OpenDict<'a> = (usize, Option<&'a [u8]>);
+
+/// Walks the dictionaries in `source`, calling `report` for each violation until
+/// it returns false or the input ends (or becomes malformed).
+fn walk_key_order(source: &[u8], mut report: impl FnMut(KeyOrderViolation) -> bool) {
+    // Open containers, None for lists
+    let mut open: Vec<Option<OpenDict>> = Vec::new();
+    for token in Tokenizer::new(source) {
+        let Ok(token) = token else {
+            return;
+        };
+        match token.kind {
+            TokenKind::ListStart => open.push(None),
+            TokenKind::DictStart => open.push(Some((token.offset, None))),
+            TokenKind::ListEnd | TokenKind::DictEnd => {
+                open.pop();
+            }
+            TokenKind::Key(key) => {
+                if let Some(Some((dict_offset, last_key))) = open.last_mut() {
+                    if let Some(prev) = last_key.filter(|prev| key <= *prev) {
+                        let violation = KeyOrderViolation {
+                            offset: token.offset,
+                            dict_offset: *dict_offset,
+                            prev: prev.to_vec(),
+                            curr: key.to_vec(),
+                        };
+                        if !report(violation) {
+                            return;
+                        }
+                    }
+                    *last_key = Some(key);
+                }
+            }
+            TokenKind::Integer(_) | TokenKind::Bytes(_) => {}
+        }
+    }
+}
+
+/// Checks that every dictionary in `source` has unique keys in ascending byte order.
+///
+/// Only key ordering is checked: other structural problems are not reported and
+/// checking simply stops at the first malformed token (use
+/// [`validate_bencode`](crate::validate_bencode) for full validation).
+///
+/// # Arguments
+/// * `source` - The bencode-encoded input
+///
+/// # Returns
+/// * `Ok(())` - No ordering violation was found
+/// * `Err(KeyOrderViolation)` - The first misplaced or duplicate key
+///
+/// # Example
+/// ```
+/// use bencode_lib::check_key_order;
+///
+/// assert!(check_key_order(b"d1:ai1e1:bi2ee").is_ok());
+/// let violation = check_key_order(b"d1:bi1e1:ai2ee").unwrap_err();
+/// assert_eq!((violation.offset, violation.prev, violation.curr), (7, b"b".to_vec(), b"a".to_vec()));
+/// ```
+pub fn check_key_order(source: &[u8]) -> Result<(), KeyOrderViolation> {
+    let mut first = None;
+    walk_key_order(source, |violation| {
+        first = Some(violation);
+        false
+    });
+    first.map_or(Ok(()), Err)
+}
+
+/// Returns every key ordering violation in `source`, in input order.
+///
+/// Unlike [`check_key_order`] this keeps going after the first violation, so
+/// callers can find all offending dictionaries (via `dict_offset`) in one pass.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded input
+///
+/// # Returns
+/// All violations found before the end of the input or the first malformed token
+pub fn key_order_violations(source: &[u8]) -> Vec<KeyOrderViolation> {
+    let mut violations = Vec::new();
+    walk_key_order(source, |violation| {
+        violations.push(violation);
+        true
+    });
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sorted_dictionaries_pass() {
+        assert_eq!(check_key_order(b"d1:ad1:xi1e1:yi2ee1:bli1eee"), Ok(()));
+        assert_eq!(check_key_order(b"li1e3:abce"), Ok(()));
+    }
+
+    #[test]
+    fn reports_out_of_order_key() {
+        assert_eq!(
+            check_key_order(b"d1:bi1e1:ai2ee"),
+            Err(KeyOrderViolation {
+                offset: 7,
+                dict_offset: 0,
+                prev: b"b".to_vec(),
+                curr: b"a".to_vec(),
+            })
+        );
+    }
+
+    #[test]
+    fn reports_duplicate_key() {
+        let violation = check_key_order(b"d1:ai1e1:ai2ee").unwrap_err();
+        assert_eq!(violation.curr, violation.prev);
+        assert_eq!(violation.to_string(), "duplicate key \"a\" after \"a\" at offset 7");
+    }
+
+    #[test]
+    fn order_is_checked_per_dictionary() {
+        // The nested dictionary's keys do not affect its parent
+        assert_eq!(check_key_order(b"d1:bd1:ai1ee1:ci2ee"), Ok(()));
+        let violation = check_key_order(b"d1:ad1:yi1e1:xi2eee").unwrap_err();
+        assert_eq!((violation.offset, violation.dict_offset), (11, 4));
+    }
+
+    #[test]
+    fn keys_compare_as_raw_bytes() {
+        assert_eq!(check_key_order(b"d1:Zi1e1:ai2ee"), Ok(()));
+        assert!(check_key_order(b"d2:abi1e1:bi2e1:ai3ee").is_err());
+    }
+
+    #[test]
+    fn malformed_input_stops_checking() {
+        assert_eq!(check_key_order(b"d1:bi1ex1:ai2ee"), Ok(()));
+        assert!(check_key_order(b"d1:bi1e1:ai2e").is_err());
+    }
+
+    #[test]
+    fn collects_all_violations() {
+        let violations = key_order_violations(b"d1:bi1e1:ai2e1:cd1:zi1e1:yi2eee");
+        let offsets: Vec<(usize, usize)> = violations.iter().map(|v| (v.offset, v.dict_offset)).collect();
+        assert_eq!(offsets, vec![(7, 0), (23, 16)]);
+    }
+}
@@ -0,0 +1,204 @@
+//! Lazy access to values inside encoded bencode, without building a tree.
+//!
+//! A [`LazyValue`] is a view of one encoded value in a buffer. Looking up a
+//! key scans the dictionary's entries, stepping over the values of other keys
+//! without decoding them, and returns a view of the value found: its byte
+//! span, which can be hashed or copied as is, and its type. Nothing after the
+//! value found is read.
+
+use core::ops::Range;
+
+use crate::error::parse::ParseError;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::parser::borrowed::parse_borrowed;
+use crate::parser::extract::{next_token, value_end, value_span};
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+
+/// The type of an encoded value, as given by its first byte.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueType {
+    /// `i<digits>e`
+    Integer,
+    /// `<length>:<bytes>`
+    Bytes,
+    /// `l...e`
+    List,
+    /// `d...e`
+    Dictionary,
+}
+
+impl ValueType {
+    /// Returns the type of the value starting with `byte`, or None if no value starts with it
+    pub fn from_first_byte(byte: u8) -> Option<Self> {
+        match byte {
+            b'i' => Some(ValueType::Integer),
+            b'0'..=b'9' => Some(ValueType::Bytes),
+            b'l' => Some(ValueType::List),
+            b'd' => Some(ValueType::Dictionary),
+            _ => None,
+        }
+    }
+}
+
+/// View of one encoded value inside a buffer.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{LazyValue, ValueType};
+///
+/// let data = b"d8:announce3:url4:infod4:name1:xee";
+/// let info = LazyValue::new(data).get("info").unwrap().unwrap();
+/// assert_eq!(info.value_type(), Some(ValueType::Dictionary));
+/// assert_eq!(info.as_bytes(), b"d4:name1:xe");
+/// assert_eq!(info.span(), 22..33);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LazyValue<'a> {
+    /// The whole buffer, so spans stay relative to it
+    source: &'a [u8],
+    /// Span of this value in `source`
+    start: usize,
+    end: usize,
+}
+
+impl<'a> LazyValue<'a> {
+    /// Creates a view of the value held in `source`, which is not read until a
+    /// lookup. The view spans the whole buffer, so `source` should hold exactly
+    /// one value.
+    pub fn new(source: &'a [u8]) -> Self {
+        Self {
+            source,
+            start: 0,
+            end: source.len(),
+        }
+    }
+
+    /// Returns the type of the value, or None if it does not start like a bencode value
+    pub fn value_type(&self) -> Option<ValueType> {
+        self.as_bytes().first().copied().and_then(ValueType::from_first_byte)
+    }
+
+    /// Returns the span of the value in the buffer passed to [`LazyValue::new`]
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns the encoded value
+    pub fn as_bytes(&self) -> &'a [u8] {
+        &self.source[self.start..self.end]
+    }
+
+    /// Looks up a key in the value, if it is a dictionary.
+    ///
+    /// # Arguments
+    /// * `key` - The dictionary key
+    ///
+    /// # Returns
+    /// * `Ok(Some(LazyValue))` - The value stored under the key
+    /// * `Ok(None)` - The value is not a dictionary or has no such key
+    /// * `Err(ParseError)` - The entries read before finding the key are malformed;
+    ///   the offset is into the whole buffer
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Result<Option<LazyValue<'a>>, ParseError> {
+        let mut tokenizer = Tokenizer::new(self.as_bytes());
+        let found = self.find(&mut tokenizer, key.as_ref());
+        found.map_err(|e| self.shift(e))
+    }
+
+    /// Scans the dictionary entries read by `tokenizer` for `key`
+    fn find(&self, tokenizer: &mut Tokenizer<'a>, key: &[u8]) -> Result<Option<LazyValue<'a>>, ParseError> {
+        if next_token(tokenizer)?.kind != TokenKind::DictStart {
+            return Ok(None);
+        }
+        loop {
+            let TokenKind::Key(found) = next_token(tokenizer)?.kind else {
+                return Ok(None);
+            };
+            let value = next_token(tokenizer)?;
+            let end = value_end(tokenizer, &value)?;
+            if found == key {
+                return Ok(Some(self.child(value.offset..end)));
+            }
+        }
+    }
+
+    /// Looks up a key path in the value.
+    ///
+    /// Paths use the same syntax as [`extract_streaming`](crate::extract_streaming):
+    /// dot separated dictionary keys with numeric list indexes.
+    ///
+    /// # Returns
+    /// * `Ok(Some(LazyValue))` - The value at the path
+    /// * `Ok(None)` - The path does not exist
+    /// * `Err(ParseError)` - The input read before finding the value is malformed;
+    ///   the offset is into the whole buffer
+    pub fn path(&self, path: &str) -> Result<Option<LazyValue<'a>>, ParseError> {
+        let span = value_span(self.as_bytes(), path).map_err(|e| self.shift(e))?;
+        Ok(span.map(|span| self.child(span)))
+    }
+
+    /// Decodes the value into a tree borrowing from the buffer.
+    pub fn decode(&self) -> Result<BorrowedNode<'a>, ParseError> {
+        parse_borrowed(self.as_bytes()).map_err(|e| self.shift(e))
+    }
+
+    /// Returns a view of the value at `span` within this one
+    fn child(&self, span: Range<usize>) -> LazyValue<'a> {
+        LazyValue {
+            source: self.source,
+            start: self.start + span.start,
+            end: self.start + span.end,
+        }
+    }
+
+    /// Makes the offset of an error found within this value relative to the whole buffer
+    fn shift(&self, error: ParseError) -> ParseError {
+        ParseError::new(error.message, self.start + error.offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::messages::ERR_UNTERMINATED_INTEGER;
+
+    const TORRENT: &[u8] = b"d8:announce3:url4:infod5:filesld6:lengthi5eee4:name1:xe5:otheri1ee";
+
+    #[test]
+    fn get_returns_the_span_and_type_of_a_value() {
+        let root = LazyValue::new(TORRENT);
+        let announce = root.get("announce").unwrap().unwrap();
+        let info = root.get(b"info").unwrap().unwrap();
+
+        assert_eq!((announce.value_type(), announce.as_bytes()), (Some(ValueType::Bytes), &b"3:url"[..]));
+        assert_eq!(info.span(), crate::torrent::info_span(TORRENT).unwrap());
+        assert_eq!(info.get("name").unwrap().unwrap().span(), 51..54);
+        assert_eq!(root.get("other").unwrap().unwrap().value_type(), Some(ValueType::Integer));
+    }
+
+    #[test]
+    fn missing_keys_and_other_types_are_none() {
+        let root = LazyValue::new(TORRENT);
+        assert_eq!(root.get("missing").unwrap(), None);
+        assert_eq!(root.get("announce").unwrap().unwrap().get("url").unwrap(), None);
+        assert_eq!(LazyValue::new(b"li1ee").get("0").unwrap(), None);
+    }
+
+    #[test]
+    fn paths_and_decoding_stay_relative_to_the_buffer() {
+        let info = LazyValue::new(TORRENT).get("info").unwrap().unwrap();
+        let files = info.path("files.0").unwrap().unwrap();
+
+        assert_eq!(files.as_bytes(), b"d6:lengthi5ee");
+        assert_eq!(files.get("length").unwrap().unwrap().decode().unwrap(), BorrowedNode::Integer(5));
+        assert_eq!(&TORRENT[files.span()], files.as_bytes());
+    }
+
+    #[test]
+    fn only_the_entries_before_the_key_are_read() {
+        let data = b"d1:ai1e1:bi2e1:ci";
+        let root = LazyValue::new(data);
+
+        assert_eq!(root.get("b").unwrap().unwrap().as_bytes(), b"i2e");
+        assert_eq!(root.get("c"), Err(ParseError::new(ERR_UNTERMINATED_INTEGER, 16)));
+    }
+}
@@ -0,0 +1,308 @@
+//! Error recovering parser for bencode format.
+//!
+//! Instead of stopping at the first problem, this parser records a [`ParseError`]
+//! and salvages as much of the tree as it can:
+//!
+//! - A value that is malformed but whose extent is known (e.g. `i1x2e`) is replaced
+//!   by `Node::None` and parsing continues after it.
+//! - Out of order keys and non-string keys are reported, the rest of the dictionary
+//!   is still parsed.
+//! - When the structure can no longer be followed (bad string length, unexpected
+//!   character, missing terminator) the enclosing containers are truncated at that
+//!   point and returned with whatever they already hold.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use crate::HashMap;
+use crate::error::messages::*;
+use crate::error::parse::ParseError;
+use crate::nodes::integer::parse_integer_text;
+use crate::nodes::node::Node;
+use crate::parser::common::{unterminated_integer, unterminated_string_length};
+
+const INTEGER_START: u8 = b'i';
+const LIST_START: u8 = b'l';
+const DICT_START: u8 = b'd';
+const END_MARKER: u8 = b'e';
+const STRING_SEPARATOR: u8 = b':';
+
+/// Parses bencode data, recovering from errors to produce a partial tree.
+///
+/// # Arguments
+/// * `data` - The byte slice containing (possibly corrupt) bencode data
+///
+/// # Returns
+/// * `(Node, Vec<ParseError>)` - The salvaged tree and every error encountered, in
+///   input order. An empty error list means the input was valid bencode.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_lossy, Node};
+///
+/// let (node, errors) = parse_lossy(b"li1ei1x2ei3e");
+/// assert_eq!(node, Node::List(vec![Node::Integer(1), Node::None, Node::Integer(3)]));
+/// assert_eq!(errors.len(), 2); // invalid integer and unterminated list
+/// ```
+pub fn parse_lossy(data: &[u8]) -> (Node, Vec<ParseError>) {
+    let mut parser = LossyParser {
+        input: data,
+        position: 0,
+        errors: Vec::new(),
+        in_sync: true,
+        depth: 0,
+    };
+    let root = parser.parse_value(Expecting::Value);
+    if parser.in_sync && parser.position < data.len() {
+        parser.error(ERR_TRAILING_DATA, parser.position);
+    }
+    (root, parser.errors)
+}
+
+/// Recursive descent parser state for lossy parsing
+struct LossyParser<'a> {
+    /// Input being parsed
+    input: &'a [u8],
+    /// Current byte offset into the input
+    position: usize,
+    /// Errors recorded so far
+    errors: Vec<ParseError>,
+    /// False once the parser can no longer tell where the next value starts
+    in_sync: bool,
+    /// Number of containers enclosing the current position
+    depth: usize,
+}
+
+impl<'a> LossyParser<'a> {
+    /// Records a recoverable error.
+    fn error(&mut self, message: impl Into<String>, offset: usize) {
+        self.errors.push(ParseError::new(message, offset));
+    }
+
+    /// Records an error after which the structure can no longer be followed.
+    fn lose_sync(&mut self, message: impl Into<String>, offset: usize) -> Node {
+        self.error(message, offset);
+        self.in_sync = false;
+        self.position = self.input.len();
+        Node::None
+    }
+
+    /// Parses the value starting at the current position; `expecting` describes
+    /// where it appears, for unexpected character errors.
+    fn parse_value(&mut self, expecting: Expecting) -> Node {
+        let start = self.position;
+        match self.input.get(start) {
+            Some(&INTEGER_START) => self.parse_integer(),
+            Some(&LIST_START) => self.parse_list(),
+            Some(&DICT_START) => self.parse_dictionary(),
+            Some(b'0'..=b'9') => self.parse_string(),
+            Some(&STRING_SEPARATOR) => self.lose_sync(ERR_INVALID_STRING_LENGTH, start),
+            Some(&c) => self.lose_sync(unexpected_character(c as char, expecting, self.depth), start),
+            None => self.lose_sync(ERR_EMPTY_INPUT, start),
+        }
+    }
+
+    /// Parses an integer; invalid digits yield `Node::None` without losing sync.
+    fn parse_integer(&mut self) -> Node {
+        let start = self.position;
+        let Some(length) = self.input[start + 1..].iter().position(|&b| b == END_MARKER) else {
+            return self.lose_sync(unterminated_integer(&self.input[start + 1..]), start);
+        };
+        let end = start + 1 + length;
+        self.position = end + 1;
+        let number = core::str::from_utf8(&self.input[start + 1..end]).unwrap_or_default();
+        match parse_integer_text(number) {
+            Ok(value) => Node::Integer(value),
+            Err(e) => {
+                self.error(e, start);
+                Node::None
+            }
+        }
+    }
+
+    /// Reads the raw bytes of a string, or returns None after losing sync.
+    fn parse_string_bytes(&mut self) -> Option<&'a [u8]> {
+        let start = self.position;
+        let Some(length) = self.input[start..]
+            .iter()
+            .position(|&b| b == STRING_SEPARATOR)
+        else {
+            self.lose_sync(unterminated_string_length(&self.input[start..]), start);
+            return None;
+        };
+        let separator = start + length;
+        let Some(size) = core::str::from_utf8(&self.input[start..separator])
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+        else {
+            self.lose_sync(ERR_INVALID_STRING_LENGTH, start);
+            return None;
+        };
+        let begin = separator + 1;
+        if size > self.input.len() - begin {
+            self.lose_sync(ERR_STRING_TOO_SHORT, start);
+            return None;
+        }
+        self.position = begin + size;
+        Some(&self.input[begin..begin + size])
+    }
+
+    /// Parses a string, keeping non UTF-8 content as a Bytes node.
+    fn parse_string(&mut self) -> Node {
+        match self.parse_string_bytes() {
+            Some(bytes) => Node::from_bytes(bytes.to_vec()),
+            None => Node::None,
+        }
+    }
+
+    /// Parses a list, truncating it if an element cannot be followed.
+    fn parse_list(&mut self) -> Node {
+        let start = self.position;
+        self.position += 1; // skip 'l'
+        self.depth += 1;
+        let mut items = Vec::new();
+        while self.in_sync {
+            match self.input.get(self.position) {
+                Some(&END_MARKER) => {
+                    self.position += 1;
+                    break;
+                }
+                Some(_) => items.push(self.parse_value(Expecting::ListItem)),
+                None => {
+                    self.lose_sync(ERR_UNTERMINATED_LIST, start);
+                }
+            }
+        }
+        self.depth -= 1;
+        Node::List(items)
+    }
+
+    /// Parses a dictionary, keeping out of order keys and skipping non-string keys.
+    fn parse_dictionary(&mut self) -> Node {
+        let start = self.position;
+        self.position += 1; // skip 'd'
+        self.depth += 1;
+        let mut entries = HashMap::new();
+        let mut last_key: Option<&[u8]> = None;
+        while self.in_sync {
+            let key_start = self.position;
+            match self.input.get(key_start) {
+                Some(&END_MARKER) => {
+                    self.position += 1;
+                    break;
+                }
+                Some(b'0'..=b'9') => {
+                    let Some(key) = self.parse_string_bytes() else {
+                        break;
+                    };
+                    if last_key.is_some_and(|last| key <= last) {
+                        self.error(ERR_DICT_KEYS_ORDER, key_start);
+                    }
+                    last_key = Some(key);
+                    let value = if self.input.get(self.position) == Some(&END_MARKER) {
+                        let message = unexpected_character(END_MARKER as char, Expecting::DictValue, self.depth);
+                        self.error(message, self.position);
+                        Node::None
+                    } else {
+                        self.parse_value(Expecting::DictValue)
+                    };
+                    entries.insert(String::from_utf8_lossy(key).into_owned(), value);
+                }
+                Some(&INTEGER_START) | Some(&LIST_START) | Some(&DICT_START) => {
+                    // Skip the whole key/value pair as its key cannot be represented
+                    self.error(ERR_DICT_KEY_MUST_BE_STRING, key_start);
+                    self.parse_value(Expecting::DictValue);
+                    if self.in_sync && self.input.get(self.position) != Some(&END_MARKER) {
+                        self.parse_value(Expecting::DictValue);
+                    }
+                }
+                Some(_) => {
+                    self.lose_sync(ERR_DICT_KEY_MUST_BE_STRING, key_start);
+                }
+                None => {
+                    self.lose_sync(ERR_UNTERMINATED_DICTIONARY, start);
+                }
+            }
+        }
+        self.depth -= 1;
+        Node::Dictionary(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::default::parse_bytes;
+
+    #[test]
+    fn parse_lossy_valid_input_has_no_errors() {
+        let data = b"d4:listli1ei2ee3:numi-42e3:str5:helloe";
+        let (node, errors) = parse_lossy(data);
+        assert!(errors.is_empty());
+        assert_eq!(Ok(node), parse_bytes(data));
+    }
+
+    #[test]
+    fn parse_lossy_empty_input() {
+        let (node, errors) = parse_lossy(b"");
+        assert_eq!(node, Node::None);
+        assert_eq!(errors, vec![ParseError::new(ERR_EMPTY_INPUT, 0)]);
+    }
+
+    #[test]
+    fn parse_lossy_replaces_invalid_integer_and_continues() {
+        let (node, errors) = parse_lossy(b"li1ei-0ei3ee");
+        assert_eq!(node, Node::List(vec![Node::Integer(1), Node::None, Node::Integer(3)]));
+        assert_eq!(errors, vec![ParseError::new(ERR_INVALID_INTEGER, 4)]);
+    }
+
+    #[test]
+    fn parse_lossy_truncates_unterminated_containers() {
+        let (node, errors) = parse_lossy(b"d1:ali1ei2e");
+        let expected = Node::from([("a", Node::List(vec![Node::Integer(1), Node::Integer(2)]))]);
+        assert_eq!(node, expected);
+        assert_eq!(errors, vec![ParseError::new(ERR_UNTERMINATED_LIST, 4)]);
+    }
+
+    #[test]
+    fn parse_lossy_truncates_at_short_string() {
+        let (node, errors) = parse_lossy(b"l3:abc10:defe");
+        assert_eq!(node, Node::List(vec![Node::from("abc"), Node::None]));
+        assert_eq!(errors, vec![ParseError::new(ERR_STRING_TOO_SHORT, 6)]);
+    }
+
+    #[test]
+    fn parse_lossy_keeps_out_of_order_keys() {
+        let (node, errors) = parse_lossy(b"d1:bi1e1:ai2ee");
+        assert_eq!(node, Node::from([("a", Node::Integer(2)), ("b", Node::Integer(1))]));
+        assert_eq!(errors, vec![ParseError::new(ERR_DICT_KEYS_ORDER, 7)]);
+    }
+
+    #[test]
+    fn parse_lossy_skips_non_string_keys() {
+        let (node, errors) = parse_lossy(b"di1ei2e1:ai3ee");
+        assert_eq!(node, Node::from([("a", Node::Integer(3))]));
+        assert_eq!(errors, vec![ParseError::new(ERR_DICT_KEY_MUST_BE_STRING, 1)]);
+    }
+
+    #[test]
+    fn parse_lossy_key_without_value() {
+        let (node, errors) = parse_lossy(b"d1:ae");
+        assert_eq!(node, Node::from([("a", Node::None)]));
+        assert_eq!(errors, vec![ParseError::new(unexpected_character('e', Expecting::DictValue, 1), 4)]);
+    }
+
+    #[test]
+    fn parse_lossy_reports_trailing_data() {
+        let (node, errors) = parse_lossy(b"i1eXX");
+        assert_eq!(node, Node::Integer(1));
+        assert_eq!(errors, vec![ParseError::new(ERR_TRAILING_DATA, 3)]);
+    }
+
+    #[test]
+    fn parse_lossy_unexpected_character_stops_parent() {
+        let (node, errors) = parse_lossy(b"li1exi2ee");
+        assert_eq!(node, Node::List(vec![Node::Integer(1), Node::None]));
+        assert_eq!(errors, vec![ParseError::new(unexpected_character('x', Expecting::ListItem, 1), 4)]);
+    }
+}
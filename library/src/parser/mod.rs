@@ -8,3 +8,50 @@ pub mod borrowed;
 /// Iterative (stack-based) parser for deeply nested structures
 /// Avoids recursion to prevent stack overflow on embedded systems
 pub mod iterative;
+
+/// Zero-copy tokenizer yielding tokens with byte spans
+pub mod tokenizer;
+
+/// Push based decoder fed with input chunks as they arrive
+pub mod streaming;
+
+/// Visitor based parsing without intermediate Node allocation
+pub mod visitor;
+
+/// Streaming extraction of selected key paths
+pub mod extract;
+
+/// Parsing that records the byte range of every value
+pub mod spans;
+
+/// Lazy key lookup on encoded values without building a tree
+pub mod lazy;
+
+/// Standalone dictionary key ordering checks
+pub mod key_order;
+
+/// Checks that input is the canonical encoding of its value
+pub mod canonical;
+
+/// Text search inside string values
+pub mod search;
+
+/// Error recovering parser producing partial trees
+pub mod lossy;
+
+/// Parser selection from input size and available resources
+pub mod strategy;
+
+/// Differential testing harness checking that all parsers agree
+pub mod differential;
+
+/// Parsing of YAML written by the YAML encoder or edited by hand
+#[cfg(feature = "yaml")]
+pub mod yaml;
+
+/// Parsing of XML in the layout the XML encoder writes
+#[cfg(feature = "xml")]
+pub mod xml;
+
+/// Helpers shared by the parsers
+pub(crate) mod common;
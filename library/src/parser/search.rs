@@ -0,0 +1,109 @@
+//! Text search confined to the string values of a document.
+//!
+//! A plain byte search over encoded bencode also matches length prefixes,
+//! dictionary keys and the binary contents of fields such as `pieces`, whose
+//! random bytes can spell anything. [`find_strings`] walks the tokens and only
+//! looks inside string values that are UTF-8 text, reporting each hit with the
+//! key path of the value it was found in.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use crate::error::parse::ParseError;
+use crate::parser::extract::TokenPaths;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+
+/// A string value containing the searched pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StringMatch {
+    /// Key path of the string value, in the syntax of [`Node::query`](crate::Node::query)
+    pub path: String,
+    /// Offset in the input of the first byte of the match
+    pub offset: usize,
+}
+
+/// Finds every occurrence of `pattern` inside the text string values of a document.
+///
+/// Dictionary keys and strings that are not valid UTF-8 (binary data such as
+/// piece hashes) are not searched. Overlapping occurrences are all reported;
+/// an empty pattern matches nothing.
+///
+/// # Arguments
+/// * `source` - The bencode data
+/// * `pattern` - The bytes to look for
+///
+/// # Returns
+/// * `Ok(Vec<StringMatch>)` - The matches, in input order
+/// * `Err(ParseError)` - If the input is malformed
+///
+/// # Example
+/// ```
+/// use bencode_lib::find_strings;
+///
+/// let torrent = b"d8:announce18:udp://tracker.test4:infod4:name7:tracker6:pieces9:\xfftracker\x00ee";
+/// let matches = find_strings(torrent, b"tracker").unwrap();
+/// let paths: Vec<_> = matches.iter().map(|m| m.path.as_str()).collect();
+/// assert_eq!(paths, ["announce", "info.name"]);
+/// assert_eq!(matches[0].offset, 20);
+/// ```
+pub fn find_strings(source: &[u8], pattern: &[u8]) -> Result<Vec<StringMatch>, ParseError> {
+    let mut matches = Vec::new();
+    let mut paths = TokenPaths::new();
+    for token in Tokenizer::new(source) {
+        let token = token?;
+        let Some(path) = paths.next_path(&token.kind) else {
+            continue;
+        };
+        let TokenKind::Bytes(bytes) = token.kind else {
+            continue;
+        };
+        if pattern.is_empty() || core::str::from_utf8(bytes).is_err() {
+            continue;
+        }
+        let start = token.end - bytes.len();
+        for (index, window) in bytes.windows(pattern.len()).enumerate() {
+            if window == pattern {
+                matches.push(StringMatch {
+                    path: path.clone(),
+                    offset: start + index,
+                });
+            }
+        }
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    fn found(source: &[u8], pattern: &[u8]) -> Vec<(String, usize)> {
+        find_strings(source, pattern)
+            .unwrap()
+            .into_iter()
+            .map(|m| (m.path, m.offset))
+            .collect()
+    }
+
+    #[test]
+    fn only_string_values_are_searched() {
+        // "4" also appears in length prefixes, an integer and a key, which are not searched
+        let data = b"d1:4i4e1:al2:x41:4ee";
+        assert_eq!(found(data, b"4"), [("a.0".to_string(), 14), ("a.1".to_string(), 17)]);
+        assert!(found(data, b"a").is_empty());
+        assert!(found(data, b"").is_empty());
+    }
+
+    #[test]
+    fn overlapping_matches_are_all_reported() {
+        assert_eq!(found(b"4:aaaa", b"aa"), [(String::new(), 2), (String::new(), 3), (String::new(), 4)]);
+    }
+
+    #[test]
+    fn binary_strings_are_not_searched() {
+        assert_eq!(found(b"d1:a4:\xffabc1:b3:abce", b"abc"), [("b".to_string(), 15)]);
+        let error = find_strings(b"l3:abc", b"abc").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnterminatedList);
+    }
+}
@@ -0,0 +1,143 @@
+//! Parsing that also records where in the input each value was read from.
+//!
+//! [`parse_with_spans`] returns the tree together with a [`SpanMap`] from the
+//! key path of every value to its byte range in the input, so a value can be
+//! located in, or hashed straight from, the original bytes (the info hash of a
+//! torrent is the SHA-1 of the span at `"info"`).
+
+#[cfg(not(feature = "std"))]
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+#[cfg(feature = "std")]
+use std::collections::BTreeMap;
+
+use core::ops::Range;
+
+use crate::error::parse::ParseError;
+use crate::nodes::node::Node;
+use crate::parser::default::parse_bytes;
+use crate::parser::extract::TokenPaths;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+
+/// Byte ranges of the values in a document, keyed by path.
+///
+/// Paths are dot separated dictionary keys with numeric list indexes, as used
+/// by [`Node::query`]; the root is the empty path. Keys that are not UTF-8 are
+/// decoded lossily.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpanMap {
+    spans: BTreeMap<String, Range<usize>>,
+}
+
+impl SpanMap {
+    /// Returns the byte range of the value at `path`, or None if there is no such value
+    pub fn get(&self, path: &str) -> Option<Range<usize>> {
+        self.spans.get(path).cloned()
+    }
+
+    /// Returns the number of values recorded
+    pub fn len(&self) -> usize {
+        self.spans.len()
+    }
+
+    /// Returns true if no values were recorded
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Iterates over the paths and their byte ranges, in path order
+    pub fn iter(&self) -> impl Iterator<Item = (&str, Range<usize>)> {
+        self.spans.iter().map(|(path, span)| (path.as_str(), span.clone()))
+    }
+
+    /// Returns the path of the innermost value whose range contains `offset`, if any
+    pub fn path_at(&self, offset: usize) -> Option<&str> {
+        self.iter()
+            .filter(|(_, span)| span.contains(&offset))
+            .min_by_key(|(_, span)| span.len())
+            .map(|(path, _)| path)
+    }
+}
+
+/// Parses bencode data from a byte slice, recording the byte range of every value.
+///
+/// # Arguments
+/// * `data` - The byte slice containing bencode-encoded data
+///
+/// # Returns
+/// * `Ok((Node, SpanMap))` - The parsed tree and the range of each of its values
+/// * `Err(ParseError)` - Description of the parsing error and its offset
+///
+/// # Example
+/// ```
+/// use bencode_lib::parse_with_spans;
+///
+/// let data = b"d8:announce3:url4:infod4:name1:xee";
+/// let (node, spans) = parse_with_spans(data).unwrap();
+/// assert_eq!(&data[spans.get("info").unwrap()], b"d4:name1:xe");
+/// assert_eq!(spans.get("info.name"), Some(29..32));
+/// assert_eq!(spans.path_at(30), Some("info.name"));
+/// assert!(node.query("info.name").is_some());
+/// ```
+pub fn parse_with_spans(data: &[u8]) -> Result<(Node, SpanMap), ParseError> {
+    let node = parse_bytes(data)?;
+    let mut spans = BTreeMap::new();
+    let mut paths = TokenPaths::new();
+    // Path and start of each open container
+    let mut open: Vec<(String, usize)> = Vec::new();
+    for token in Tokenizer::new(data) {
+        let token = token?;
+        let path = paths.next_path(&token.kind);
+        match (token.kind, path) {
+            (TokenKind::ListStart | TokenKind::DictStart, Some(path)) => open.push((path, token.offset)),
+            (TokenKind::ListEnd | TokenKind::DictEnd, _) => {
+                if let Some((path, start)) = open.pop() {
+                    spans.insert(path, start..token.end);
+                }
+            }
+            (_, Some(path)) => {
+                spans.insert(path, token.offset..token.end);
+            }
+            (_, None) => {}
+        }
+    }
+    Ok((node, SpanMap { spans }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TORRENT: &[u8] = b"d8:announce3:url4:infod5:filesld6:lengthi5eee4:name1:xee";
+
+    #[test]
+    fn every_value_has_its_span() {
+        let (node, spans) = parse_with_spans(TORRENT).unwrap();
+        let span = |path| spans.get(path).map(|span| &TORRENT[span]);
+
+        assert_eq!(spans.len(), 7);
+        assert_eq!(span(""), Some(TORRENT));
+        assert_eq!(span("announce"), Some(&b"3:url"[..]));
+        assert_eq!(span("info.files"), Some(&b"ld6:lengthi5eee"[..]));
+        assert_eq!(span("info.files.0.length"), Some(&b"i5e"[..]));
+        assert_eq!(spans.get("info"), Some(crate::torrent::info_span(TORRENT).unwrap()));
+        assert_eq!(spans.get("info.missing"), None);
+        for (path, range) in spans.iter() {
+            assert_eq!(parse_bytes(&TORRENT[range]).ok().as_ref(), node.query(path));
+        }
+    }
+
+    #[test]
+    fn offsets_map_back_to_the_innermost_value() {
+        let (_, spans) = parse_with_spans(TORRENT).unwrap();
+        assert_eq!(spans.path_at(0), Some(""));
+        assert_eq!(spans.path_at(41), Some("info.files.0.length"));
+        assert_eq!(spans.path_at(43), Some("info.files.0"));
+        assert_eq!(spans.path_at(TORRENT.len()), None);
+    }
+
+    #[test]
+    fn malformed_input_is_an_error() {
+        assert!(parse_with_spans(b"li1e").is_err());
+        assert!(parse_with_spans(b"").is_err());
+    }
+}
@@ -0,0 +1,178 @@
+//! Choice of parser from the input size and the resources available.
+//!
+//! The parsers trade speed for resource use: the recursive parser is fastest but
+//! uses a stack frame per nesting level, the iterative parser keeps its stack on
+//! the heap, and the borrowed parser avoids copying strings. [`choose_parser`]
+//! picks between them from worst case bounds that hold for any input of a given
+//! length, so the choice is safe without looking at the data first.
+
+use crate::error::messages::ERR_NO_PARSER_FITS;
+use crate::error::parse::ParseError;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::nodes::fixed::MemoryBounds;
+use crate::nodes::node::Node;
+
+use super::borrowed::parse_borrowed;
+use super::default::parse_bytes;
+use super::iterative::parse_bytes_iterative;
+
+/// Stack bytes assumed per nesting level of the recursive parsers
+pub const RECURSIVE_FRAME_BYTES: usize = 256;
+
+/// Parser selected by [`choose_parser`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParserKind {
+    /// The default recursive parser ([`parse_bytes`](crate::parse_bytes))
+    Recursive,
+    /// The iterative parser, which cannot exhaust the call stack
+    /// ([`parse_bytes_iterative`](crate::parse_bytes_iterative))
+    Iterative,
+    /// The zero-copy parser, for when an owned tree would not fit in memory
+    /// ([`parse_borrowed`](crate::parse_borrowed))
+    Borrowed,
+    /// No tree fits; use [`visit`](crate::visit) or
+    /// [`extract_streaming`](crate::extract_streaming) instead
+    Unavailable,
+}
+
+/// Worst case nesting depth of valid input: every container takes at least two bytes
+const fn max_depth(input_len: usize) -> usize {
+    input_len / 2
+}
+
+/// Worst case number of nodes in valid input: every value takes at least two bytes
+const fn max_nodes(input_len: usize) -> usize {
+    input_len / 2
+}
+
+/// Worst case heap use of an owned node tree: every node plus a copy of every string
+const fn owned_estimate(input_len: usize) -> usize {
+    max_nodes(input_len)
+        .saturating_mul(2 * core::mem::size_of::<Node>())
+        .saturating_add(input_len)
+}
+
+/// Worst case heap use of a borrowed node tree, whose strings stay in the input
+const fn borrowed_estimate(input_len: usize) -> usize {
+    max_nodes(input_len).saturating_mul(2 * core::mem::size_of::<BorrowedNode<'static>>())
+}
+
+/// Chooses the parser to use for an input.
+///
+/// | stack deep enough | owned tree fits | borrowed tree fits | parser        |
+/// |-------------------|-----------------|--------------------|---------------|
+/// | yes               | yes             | -                  | `Recursive`   |
+/// | no                | yes             | -                  | `Iterative`   |
+/// | yes               | no              | yes                | `Borrowed`    |
+/// | otherwise         |                 |                    | `Unavailable` |
+///
+/// The stack is deep enough when the worst case nesting of the input fits
+/// [`MemoryBounds::max_safe_depth`] with [`RECURSIVE_FRAME_BYTES`] per level.
+///
+/// # Arguments
+/// * `input_len` - Length of the input in bytes
+/// * `available_stack` - Stack bytes available to the parser
+/// * `memory_limit` - Heap bytes the parsed tree may use
+///
+/// # Example
+/// ```
+/// use bencode_lib::{choose_parser, ParserKind};
+///
+/// assert_eq!(choose_parser(100, 64 * 1024, 1 << 20), ParserKind::Recursive);
+/// assert_eq!(choose_parser(100, 1024, 1 << 20), ParserKind::Iterative);
+/// assert_eq!(choose_parser(1 << 20, 1024, 1 << 10), ParserKind::Unavailable);
+/// ```
+pub const fn choose_parser(input_len: usize, available_stack: usize, memory_limit: usize) -> ParserKind {
+    let depth_ok = max_depth(input_len) <= MemoryBounds::max_safe_depth(available_stack, RECURSIVE_FRAME_BYTES);
+    if owned_estimate(input_len) <= memory_limit && depth_ok {
+        ParserKind::Recursive
+    } else if owned_estimate(input_len) <= memory_limit {
+        ParserKind::Iterative
+    } else if depth_ok && borrowed_estimate(input_len) <= memory_limit {
+        ParserKind::Borrowed
+    } else {
+        ParserKind::Unavailable
+    }
+}
+
+/// Tree produced by [`parse_with_strategy`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Parsed<'a> {
+    /// Owned tree from the recursive or iterative parser
+    Owned(Node),
+    /// Tree borrowing its strings from the input
+    Borrowed(BorrowedNode<'a>),
+}
+
+impl Parsed<'_> {
+    /// Converts the tree to an owned [`Node`], copying borrowed strings
+    pub fn into_node(self) -> Node {
+        match self {
+            Parsed::Owned(node) => node,
+            Parsed::Borrowed(node) => node.to_node(),
+        }
+    }
+}
+
+/// Parses an input with the parser [`choose_parser`] picks for it.
+///
+/// # Arguments
+/// * `input` - The bencode data
+/// * `available_stack` - Stack bytes available to the parser
+/// * `memory_limit` - Heap bytes the parsed tree may use
+///
+/// # Returns
+/// * `Ok(Parsed)` - The parsed tree
+/// * `Err(ParseError)` - If the input is malformed or no parser fits the limits
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_with_strategy, Parsed};
+///
+/// let parsed = parse_with_strategy(b"li1ei2ee", 1024, 1 << 20).unwrap();
+/// assert!(matches!(parsed, Parsed::Owned(_)));
+/// ```
+pub fn parse_with_strategy(input: &[u8], available_stack: usize, memory_limit: usize) -> Result<Parsed<'_>, ParseError> {
+    match choose_parser(input.len(), available_stack, memory_limit) {
+        ParserKind::Recursive => parse_bytes(input).map(Parsed::Owned),
+        ParserKind::Iterative => parse_bytes_iterative(input).map(Parsed::Owned),
+        ParserKind::Borrowed => parse_borrowed(input).map(Parsed::Borrowed),
+        ParserKind::Unavailable => Err(ParseError::new(ERR_NO_PARSER_FITS, 0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recursion_needs_stack_for_the_worst_case_depth() {
+        // 1000 bytes can nest 500 deep, needing 500 * 256 bytes with a 50% margin
+        assert_eq!(choose_parser(1000, 256_000, usize::MAX), ParserKind::Recursive);
+        assert_eq!(choose_parser(1000, 255_999, usize::MAX), ParserKind::Iterative);
+    }
+
+    #[test]
+    fn borrowed_parser_is_used_when_owned_trees_do_not_fit() {
+        let owned = owned_estimate(1000);
+        let borrowed = borrowed_estimate(1000);
+        assert!(borrowed < owned);
+        assert_eq!(choose_parser(1000, usize::MAX, owned), ParserKind::Recursive);
+        assert_eq!(choose_parser(1000, usize::MAX, owned - 1), ParserKind::Borrowed);
+        assert_eq!(choose_parser(1000, usize::MAX, borrowed - 1), ParserKind::Unavailable);
+        // The borrowed parser recurses, so it also needs the stack
+        assert_eq!(choose_parser(1000, 0, owned - 1), ParserKind::Unavailable);
+    }
+
+    #[test]
+    fn parse_with_strategy_applies_the_choice() {
+        let data = b"d4:spaml1:a1:bee";
+        let expected = parse_bytes(data).unwrap();
+        let deep = parse_with_strategy(data, 0, usize::MAX).unwrap();
+        assert_eq!(deep, Parsed::Owned(expected.clone()));
+        let lean = parse_with_strategy(data, usize::MAX, borrowed_estimate(data.len())).unwrap();
+        assert!(matches!(lean, Parsed::Borrowed(_)));
+        assert_eq!(lean.into_node(), expected);
+        assert_eq!(parse_with_strategy(data, 0, 0).unwrap_err(), ERR_NO_PARSER_FITS);
+    }
+}
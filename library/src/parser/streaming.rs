@@ -0,0 +1,416 @@
+//! Push based decoding of bencode arriving in chunks.
+//!
+//! Tracker responses and peer extension messages come off a socket a few
+//! bytes at a time. A [`StreamingDecoder`] is fed each chunk as it arrives
+//! and builds the value as it goes, keeping only the part of the tree decoded
+//! so far and the string or number it is in the middle of, so the payload
+//! never has to be buffered in full. Bytes following a complete value are kept
+//! for the next one, so back to back messages on one connection decode in turn.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use crate::HashMap;
+use crate::config::ParserConfig;
+use crate::error::messages::*;
+use crate::error::parse::ParseError;
+use crate::nodes::integer::parse_integer_text;
+use crate::nodes::node::Node;
+use crate::parser::common::unterminated_integer;
+
+/// Longest integer text accepted between 'i' and 'e', the length of "-9223372036854775808"
+const MAX_INTEGER_LENGTH: usize = 20;
+/// Most digits accepted in a string length prefix
+const MAX_LENGTH_DIGITS: usize = 20;
+
+/// Outcome of feeding bytes to a [`StreamingDecoder`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum Decoded {
+    /// The value is not complete yet; feed more bytes
+    NeedMoreData,
+    /// A whole value was decoded; any bytes after it are kept for the next value
+    Complete(Node),
+}
+
+/// A container still being decoded
+enum Frame {
+    List(Vec<Node>),
+    Dictionary {
+        entries: HashMap<String, Node>,
+        /// Key read whose value has not arrived yet
+        key: Option<Vec<u8>>,
+        /// Previous key, for the ordering check
+        last_key: Option<Vec<u8>>,
+    },
+}
+
+/// The scalar being decoded when a chunk ends
+enum Pending {
+    /// Between values
+    Nothing,
+    /// Text of an integer after its 'i'
+    Integer(Vec<u8>),
+    /// Digits of a string length prefix
+    Length(Vec<u8>),
+    /// Contents of a string still arriving
+    Content { bytes: Vec<u8>, length: usize },
+}
+
+/// Incremental bencode decoder fed with chunks of input as they arrive.
+///
+/// Limits of a [`ParserConfig`] apply to each value decoded; a string longer
+/// than allowed is rejected from its length prefix, before any of it arrives.
+/// Once an error is returned the decoder keeps returning it until [`reset`](Self::reset).
+///
+/// # Example
+/// ```
+/// use bencode_lib::{Decoded, Node, StreamingDecoder};
+///
+/// let mut decoder = StreamingDecoder::new();
+/// assert_eq!(decoder.feed(b"d8:intervali18").unwrap(), Decoded::NeedMoreData);
+/// let Decoded::Complete(response) = decoder.feed(b"00e5:peers0:ei1e").unwrap() else {
+///     panic!("response is complete");
+/// };
+/// assert_eq!(response.query("interval"), Some(&Node::from(1800)));
+/// // The next message started in the same chunk
+/// assert_eq!(decoder.buffered(), 3);
+/// assert_eq!(decoder.feed(b"").unwrap(), Decoded::Complete(Node::from(1)));
+/// ```
+pub struct StreamingDecoder {
+    config: ParserConfig,
+    stack: Vec<Frame>,
+    pending: Pending,
+    elements: usize,
+    /// Bytes of the current value consumed so far
+    position: usize,
+    /// Bytes received after the last complete value
+    leftover: Vec<u8>,
+    error: Option<ParseError>,
+}
+
+impl Default for StreamingDecoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StreamingDecoder {
+    /// Create a decoder with the default [`ParserConfig`] limits
+    pub fn new() -> Self {
+        Self::with_config(ParserConfig::new())
+    }
+
+    /// Create a decoder enforcing the limits of `config` on each value
+    pub fn with_config(config: ParserConfig) -> Self {
+        Self {
+            config,
+            stack: Vec::new(),
+            pending: Pending::Nothing,
+            elements: 0,
+            position: 0,
+            leftover: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Decodes the next chunk of input.
+    ///
+    /// Bytes left over from an earlier call are decoded first, so when
+    /// [`buffered`](Self::buffered) is non-zero an empty chunk may complete
+    /// another value.
+    ///
+    /// # Arguments
+    /// * `chunk` - The bytes that arrived, possibly empty
+    ///
+    /// # Returns
+    /// * `Ok(Decoded)` - Whether a complete value is available
+    /// * `Err(ParseError)` - If the input is malformed or crosses a limit; the
+    ///   offset counts from the first byte of the value being decoded
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Decoded, ParseError> {
+        if let Some(error) = &self.error {
+            return Err(error.clone());
+        }
+        let mut queued = core::mem::take(&mut self.leftover);
+        let input = if queued.is_empty() {
+            chunk
+        } else {
+            queued.extend_from_slice(chunk);
+            &queued[..]
+        };
+        match self.decode(input) {
+            Ok(Some((node, used))) => {
+                self.leftover = input[used..].to_vec();
+                self.position = 0;
+                self.elements = 0;
+                Ok(Decoded::Complete(node))
+            }
+            Ok(None) => Ok(Decoded::NeedMoreData),
+            Err(message) => {
+                let error = ParseError::new(message, self.position);
+                self.error = Some(error.clone());
+                Err(error)
+            }
+        }
+    }
+
+    /// Number of bytes received after the last complete value and not yet decoded
+    pub fn buffered(&self) -> usize {
+        self.leftover.len()
+    }
+
+    /// Discards any partly decoded value, buffered bytes and error
+    pub fn reset(&mut self) {
+        *self = Self::with_config(self.config);
+    }
+
+    /// Decodes `input` until a value completes, returning it with the bytes used
+    fn decode(&mut self, input: &[u8]) -> Result<Option<(Node, usize)>, String> {
+        let mut index = 0;
+        while index < input.len() {
+            let allowed = self.config.max_input_size.saturating_sub(self.position);
+            if allowed == 0 {
+                return Err(ERR_INPUT_TOO_LARGE.to_string());
+            }
+            let completed = if let Pending::Content { bytes, length } = &mut self.pending {
+                let count = (*length - bytes.len()).min(input.len() - index).min(allowed);
+                bytes.extend_from_slice(&input[index..index + count]);
+                index += count;
+                self.position += count;
+                if bytes.len() < *length {
+                    continue;
+                }
+                let bytes = core::mem::take(bytes);
+                self.pending = Pending::Nothing;
+                self.string(bytes)?
+            } else {
+                let completed = self.step(input[index])?;
+                index += 1;
+                self.position += 1;
+                completed
+            };
+            if let Some(node) = completed {
+                return Ok(Some((node, index)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Decodes one byte outside string contents
+    fn step(&mut self, byte: u8) -> Result<Option<Node>, String> {
+        match &mut self.pending {
+            Pending::Integer(text) => {
+                if byte != b'e' {
+                    if text.len() == MAX_INTEGER_LENGTH {
+                        return Err(ERR_INVALID_INTEGER.to_string());
+                    }
+                    text.push(byte);
+                    // Fail as soon as no further bytes could complete the integer
+                    return match unterminated_integer(text) {
+                        ERR_UNTERMINATED_INTEGER => Ok(None),
+                        error => Err(error.to_string()),
+                    };
+                }
+                let value = parse_integer_text(&String::from_utf8_lossy(text)).map_err(|e| e.to_string())?;
+                self.pending = Pending::Nothing;
+                return self.complete(Node::Integer(value));
+            }
+            Pending::Length(digits) => {
+                if byte != b':' {
+                    if !byte.is_ascii_digit() || digits.len() == MAX_LENGTH_DIGITS {
+                        return Err(ERR_INVALID_STRING_LENGTH.to_string());
+                    }
+                    digits.push(byte);
+                    return Ok(None);
+                }
+                let length = core::str::from_utf8(digits)
+                    .ok()
+                    .and_then(|digits| digits.parse::<usize>().ok())
+                    .ok_or_else(|| ERR_INVALID_STRING_LENGTH.to_string())?;
+                if length > self.config.max_string_length {
+                    return Err(ERR_STRING_TOO_LONG.to_string());
+                }
+                if length == 0 {
+                    self.pending = Pending::Nothing;
+                    return self.string(Vec::new());
+                }
+                self.pending = Pending::Content {
+                    bytes: Vec::new(),
+                    length,
+                };
+                return Ok(None);
+            }
+            Pending::Nothing | Pending::Content { .. } => {}
+        }
+
+        let depth = self.stack.len();
+        let expecting = match self.stack.last() {
+            None => Expecting::Value,
+            Some(Frame::List(_)) => Expecting::ListItem,
+            Some(Frame::Dictionary { key: None, .. }) => {
+                return match byte {
+                    b'e' => self.close(),
+                    b'0'..=b'9' => {
+                        self.pending = Pending::Length(vec![byte]);
+                        Ok(None)
+                    }
+                    _ => Err(ERR_DICT_KEY_MUST_BE_STRING.to_string()),
+                };
+            }
+            Some(Frame::Dictionary { .. }) => Expecting::DictValue,
+        };
+        match byte {
+            b'e' if expecting == Expecting::ListItem => return self.close(),
+            b'i' => {
+                self.add_element()?;
+                self.pending = Pending::Integer(Vec::new());
+            }
+            b'l' | b'd' => {
+                if depth + 1 > self.config.max_depth {
+                    return Err(ERR_TOO_DEEP.to_string());
+                }
+                self.add_element()?;
+                self.stack.push(if byte == b'l' {
+                    Frame::List(Vec::new())
+                } else {
+                    Frame::Dictionary {
+                        entries: HashMap::new(),
+                        key: None,
+                        last_key: None,
+                    }
+                });
+            }
+            b'0'..=b'9' => {
+                self.add_element()?;
+                self.pending = Pending::Length(vec![byte]);
+            }
+            b':' => return Err(ERR_INVALID_STRING_LENGTH.to_string()),
+            _ => return Err(unexpected_character(byte as char, expecting, depth)),
+        }
+        Ok(None)
+    }
+
+    /// Counts one more value, containers included
+    fn add_element(&mut self) -> Result<(), String> {
+        self.elements += 1;
+        if self.elements > self.config.max_elements {
+            return Err(ERR_TOO_MANY_NODES.to_string());
+        }
+        Ok(())
+    }
+
+    /// Handles a complete string, which is a key when a dictionary is waiting for one
+    fn string(&mut self, bytes: Vec<u8>) -> Result<Option<Node>, String> {
+        if let Some(Frame::Dictionary { key: key @ None, last_key, .. }) = self.stack.last_mut() {
+            if last_key.as_ref().is_some_and(|last| bytes <= *last) {
+                return Err(ERR_DICT_KEYS_ORDER.to_string());
+            }
+            *key = Some(bytes);
+            return Ok(None);
+        }
+        self.complete(Node::from_bytes(bytes))
+    }
+
+    /// Ends the innermost container
+    fn close(&mut self) -> Result<Option<Node>, String> {
+        match self.stack.pop() {
+            Some(Frame::List(items)) => self.complete(Node::List(items)),
+            Some(Frame::Dictionary { entries, .. }) => self.complete(Node::Dictionary(entries)),
+            None => Ok(None),
+        }
+    }
+
+    /// Adds a complete value to its container, returning it if it is the root
+    fn complete(&mut self, node: Node) -> Result<Option<Node>, String> {
+        match self.stack.last_mut() {
+            None => return Ok(Some(node)),
+            Some(Frame::List(items)) => items.push(node),
+            Some(Frame::Dictionary { entries, key, last_key }) => {
+                if let Some(key) = key.take() {
+                    entries.insert(String::from_utf8_lossy(&key).into_owned(), node);
+                    *last_key = Some(key);
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+    use crate::parser::default::parse_bytes;
+
+    const TRACKER_RESPONSE: &[u8] = b"d8:completei5e8:intervali1800e5:peersld2:ip8:10.0.0.14:porti6881eee5:\xff\x00key4:\x01\x02\x03\x04e";
+
+    #[test]
+    fn any_chunking_gives_the_parsed_value() {
+        let expected = parse_bytes(TRACKER_RESPONSE).unwrap();
+        for size in 1..=TRACKER_RESPONSE.len() {
+            let mut decoder = StreamingDecoder::new();
+            let mut decoded = Vec::new();
+            for chunk in TRACKER_RESPONSE.chunks(size) {
+                if let Decoded::Complete(node) = decoder.feed(chunk).unwrap() {
+                    decoded.push(node);
+                }
+            }
+            assert_eq!(decoded, core::slice::from_ref(&expected), "chunk size {}", size);
+            assert_eq!(decoder.buffered(), 0);
+        }
+    }
+
+    #[test]
+    fn back_to_back_values_decode_in_turn() {
+        let mut decoder = StreamingDecoder::new();
+        assert_eq!(decoder.feed(b"i1e0:le").unwrap(), Decoded::Complete(Node::from(1)));
+        assert_eq!(decoder.buffered(), 4);
+        assert_eq!(decoder.feed(b"4:sp").unwrap(), Decoded::Complete(Node::from("")));
+        assert_eq!(decoder.feed(b"").unwrap(), Decoded::Complete(Node::List(Vec::new())));
+        assert_eq!(decoder.feed(b"").unwrap(), Decoded::NeedMoreData);
+        assert_eq!(decoder.feed(b"am").unwrap(), Decoded::Complete(Node::from("spam")));
+    }
+
+    #[test]
+    fn errors_are_located_and_kept_until_reset() {
+        for (data, kind, offset) in [
+            (&b"i1x"[..], ErrorKind::InvalidInteger, 2),
+            (b"d1:bi1e1:ai2ee", ErrorKind::DictKeysOrder, 10),
+            (b"di1ei2ee", ErrorKind::DictKeyMustBeString, 1),
+            (b"l1xe", ErrorKind::InvalidStringLength, 2),
+            (b"lie", ErrorKind::InvalidInteger, 2),
+        ] {
+            let mut decoder = StreamingDecoder::new();
+            let error = decoder.feed(data).unwrap_err();
+            assert_eq!((error.kind(), error.offset), (kind, offset), "{:?}", data);
+            assert_eq!(decoder.feed(b"i1e"), Err(error));
+            decoder.reset();
+            assert_eq!(decoder.feed(b"i1e"), Ok(Decoded::Complete(Node::from(1))));
+        }
+        let error = StreamingDecoder::new().feed(b"li1exe").unwrap_err();
+        assert_eq!(error.message, "Unexpected character: 'x' while expecting a list item or 'e' at depth 1");
+    }
+
+    #[test]
+    fn limits_apply_to_each_value() {
+        let config = ParserConfig::new().with_max_string_length(3).with_max_depth(1).with_max_input_size(8);
+        // The length prefix alone is enough to reject a huge string
+        let error = StreamingDecoder::with_config(ParserConfig::new().with_max_string_length(3))
+            .feed(b"99999999999:")
+            .unwrap_err();
+        assert_eq!((error.kind(), error.offset), (ErrorKind::StringTooLong, 11));
+
+        let mut decoder = StreamingDecoder::with_config(config);
+        assert_eq!(decoder.feed(b"l3:abce").unwrap(), Decoded::Complete(Node::from(["abc"])));
+        assert_eq!(decoder.feed(b"ll").unwrap_err().kind(), ErrorKind::TooDeep);
+
+        let mut decoder = StreamingDecoder::with_config(config);
+        assert_eq!(decoder.feed(b"i1234567e").unwrap_err().kind(), ErrorKind::InputTooLarge);
+        let mut decoder = StreamingDecoder::with_config(ParserConfig::new().with_max_elements(2));
+        assert!(decoder.feed(b"li1ee").is_ok());
+        assert_eq!(decoder.feed(b"li1ei2ee").unwrap_err().kind(), ErrorKind::TooManyNodes);
+    }
+}
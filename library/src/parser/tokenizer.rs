@@ -0,0 +1,389 @@
+//! Zero-copy tokenizer for bencode data.
+//!
+//! The tokenizer walks a byte slice and yields one [`Token`] per structural element,
+//! each carrying its byte span in the input. It tracks container nesting so it can
+//! tell dictionary keys from values and report unterminated containers, but it does
+//! not enforce key ordering, leaving that to its consumers (parsers, validators and
+//! repair tools that must read unsorted input).
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use crate::error::messages::*;
+use crate::error::parse::ParseError;
+use crate::nodes::integer::{Integer, parse_integer_text};
+use crate::parser::common::{has_plus_sign, unterminated_integer, unterminated_string_length};
+
+const INTEGER_START: u8 = b'i';
+const LIST_START: u8 = b'l';
+const DICT_START: u8 = b'd';
+const END_MARKER: u8 = b'e';
+const STRING_SEPARATOR: u8 = b':';
+
+/// The kind of a token, borrowing string content from the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind<'a> {
+    /// An integer value (`i<digits>e`)
+    Integer(Integer),
+    /// A byte string value (`<length>:<bytes>`)
+    Bytes(&'a [u8]),
+    /// A dictionary key (always a byte string)
+    Key(&'a [u8]),
+    /// Start of a list (`l`)
+    ListStart,
+    /// End of a list (`e`)
+    ListEnd,
+    /// Start of a dictionary (`d`)
+    DictStart,
+    /// End of a dictionary (`e`)
+    DictEnd,
+}
+
+/// A token together with its byte span in the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// What was read
+    pub kind: TokenKind<'a>,
+    /// Offset of the first byte of the token
+    pub offset: usize,
+    /// Offset just past the last byte of the token
+    pub end: usize,
+}
+
+/// Open container tracked by the tokenizer
+#[derive(Clone, Copy, Debug)]
+enum Container {
+    /// A list started at the given offset
+    List(usize),
+    /// A dictionary started at the given offset; `expect_key` is true between entries
+    Dict { offset: usize, expect_key: bool },
+}
+
+/// Iterator over the tokens of a single bencode value.
+///
+/// Yields `Err` once on malformed input and then stops. Bytes following the root
+/// value are not read; use [`Tokenizer::position`] to detect trailing data.
+///
+/// # Example
+/// ```
+/// use bencode_lib::parser::tokenizer::{TokenKind, Tokenizer};
+///
+/// let kinds: Vec<_> = Tokenizer::new(b"d1:ai1ee").map(|t| t.unwrap().kind).collect();
+/// assert_eq!(kinds, vec![
+///     TokenKind::DictStart,
+///     TokenKind::Key(b"a"),
+///     TokenKind::Integer(1),
+///     TokenKind::DictEnd,
+/// ]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Tokenizer<'a> {
+    input: &'a [u8],
+    position: usize,
+    stack: Vec<Container>,
+    started: bool,
+    failed: bool,
+    allow_plus_sign: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Creates a tokenizer over the given input.
+    pub fn new(input: &'a [u8]) -> Self {
+        Self {
+            input,
+            position: 0,
+            stack: Vec::new(),
+            started: false,
+            failed: false,
+            allow_plus_sign: false,
+        }
+    }
+
+    /// Sets whether integers written with a leading '+', such as `i+5e`, are
+    /// read rather than rejected (default: false). The token spans still cover
+    /// the '+', so consumers can tell the integer was not canonical.
+    pub const fn with_allow_plus_sign(mut self, allow: bool) -> Self {
+        self.allow_plus_sign = allow;
+        self
+    }
+
+    /// Returns the text of an integer, without a '+' sign if one is allowed
+    fn integer_digits(&self, text: &'a [u8]) -> &'a [u8] {
+        if self.allow_plus_sign && has_plus_sign(text) { &text[1..] } else { text }
+    }
+
+    /// Returns the input being tokenized.
+    pub fn input(&self) -> &'a [u8] {
+        self.input
+    }
+
+    /// Returns the offset of the next unread byte.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of currently open containers.
+    pub fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    /// Returns true once the root value has been completely read.
+    pub fn is_finished(&self) -> bool {
+        self.started && self.stack.is_empty()
+    }
+
+    /// Skips the remainder of the container opened by the last `ListStart` or
+    /// `DictStart` token, returning the offset just past its end marker.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - End offset of the skipped container
+    /// * `Err(ParseError)` - If the skipped content is malformed
+    pub fn skip_container(&mut self) -> Result<usize, ParseError> {
+        let depth = self.depth();
+        while self.depth() >= depth && depth > 0 {
+            match self.next() {
+                Some(Ok(token)) if self.depth() < depth => return Ok(token.end),
+                Some(Ok(_)) => {}
+                Some(Err(e)) => return Err(e),
+                None => break,
+            }
+        }
+        Ok(self.position)
+    }
+
+    /// Records a failure so iteration stops after it is reported.
+    fn fail(&mut self, message: impl Into<String>, offset: usize) -> ParseError {
+        self.failed = true;
+        ParseError::new(message, offset)
+    }
+
+    /// Builds a token spanning from `offset` to the current position.
+    fn token(&self, kind: TokenKind<'a>, offset: usize) -> Token<'a> {
+        Token {
+            kind,
+            offset,
+            end: self.position,
+        }
+    }
+
+    /// Reads a length prefixed byte string at the current position.
+    fn read_bytes(&mut self) -> Result<&'a [u8], ParseError> {
+        let start = self.position;
+        let Some(length) = self.input[start..]
+            .iter()
+            .position(|&b| b == STRING_SEPARATOR)
+        else {
+            return Err(self.fail(unterminated_string_length(&self.input[start..]), start));
+        };
+        let separator = start + length;
+        let Some(size) = core::str::from_utf8(&self.input[start..separator])
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+        else {
+            return Err(self.fail(ERR_INVALID_STRING_LENGTH, start));
+        };
+        let begin = separator + 1;
+        if size > self.input.len() - begin {
+            return Err(self.fail(ERR_STRING_TOO_SHORT, start));
+        }
+        self.position = begin + size;
+        Ok(&self.input[begin..begin + size])
+    }
+
+    /// Reads the value starting at the current position.
+    fn read_value(&mut self) -> Result<Token<'a>, ParseError> {
+        let start = self.position;
+        match self.input.get(start) {
+            Some(&INTEGER_START) => {
+                let Some(length) = self.input[start + 1..].iter().position(|&b| b == END_MARKER) else {
+                    return Err(self.fail(unterminated_integer(self.integer_digits(&self.input[start + 1..])), start));
+                };
+                let end = start + 1 + length;
+                let text = core::str::from_utf8(self.integer_digits(&self.input[start + 1..end])).unwrap_or_default();
+                let value = parse_integer_text(text).map_err(|e| self.fail(e, start))?;
+                self.position = end + 1;
+                Ok(self.token(TokenKind::Integer(value), start))
+            }
+            Some(&LIST_START) => {
+                self.position += 1;
+                self.stack.push(Container::List(start));
+                Ok(self.token(TokenKind::ListStart, start))
+            }
+            Some(&DICT_START) => {
+                self.position += 1;
+                self.stack.push(Container::Dict {
+                    offset: start,
+                    expect_key: true,
+                });
+                Ok(self.token(TokenKind::DictStart, start))
+            }
+            Some(b'0'..=b'9') => {
+                let bytes = self.read_bytes()?;
+                Ok(self.token(TokenKind::Bytes(bytes), start))
+            }
+            Some(&STRING_SEPARATOR) => Err(self.fail(ERR_INVALID_STRING_LENGTH, start)),
+            Some(&c) => {
+                let expecting = match self.stack.last() {
+                    None => Expecting::Value,
+                    Some(Container::List(_)) => Expecting::ListItem,
+                    Some(Container::Dict { .. }) => Expecting::DictValue,
+                };
+                Err(self.fail(unexpected_character(c as char, expecting, self.depth()), start))
+            }
+            None => Err(self.fail(ERR_EMPTY_INPUT, start)),
+        }
+    }
+
+    /// Reads the next token inside the innermost open container.
+    fn read_in_container(&mut self, container: Container) -> Result<Token<'a>, ParseError> {
+        let start = self.position;
+        let current = self.input.get(start).copied();
+        match container {
+            Container::List(offset) => match current {
+                Some(END_MARKER) => {
+                    self.position += 1;
+                    self.stack.pop();
+                    Ok(self.token(TokenKind::ListEnd, start))
+                }
+                Some(_) => self.read_value(),
+                None => Err(self.fail(ERR_UNTERMINATED_LIST, offset)),
+            },
+            Container::Dict { offset, expect_key } => {
+                if current.is_none() {
+                    return Err(self.fail(ERR_UNTERMINATED_DICTIONARY, offset));
+                }
+                let index = self.stack.len() - 1;
+                if expect_key {
+                    match current {
+                        Some(END_MARKER) => {
+                            self.position += 1;
+                            self.stack.pop();
+                            Ok(self.token(TokenKind::DictEnd, start))
+                        }
+                        Some(b'0'..=b'9') => {
+                            let key = self.read_bytes()?;
+                            self.stack[index] = Container::Dict {
+                                offset,
+                                expect_key: false,
+                            };
+                            Ok(self.token(TokenKind::Key(key), start))
+                        }
+                        _ => Err(self.fail(ERR_DICT_KEY_MUST_BE_STRING, start)),
+                    }
+                } else {
+                    self.stack[index] = Container::Dict {
+                        offset,
+                        expect_key: true,
+                    };
+                    self.read_value()
+                }
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Result<Token<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed || self.is_finished() {
+            return None;
+        }
+        let result = match self.stack.last().copied() {
+            Some(container) => self.read_in_container(container),
+            None => {
+                self.started = true;
+                self.read_value()
+            }
+        };
+        Some(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(input: &[u8]) -> Vec<Result<TokenKind<'_>, ParseError>> {
+        Tokenizer::new(input).map(|t| t.map(|t| t.kind)).collect()
+    }
+
+    #[test]
+    fn tokenizes_scalars() {
+        assert_eq!(kinds(b"i-42e"), vec![Ok(TokenKind::Integer(-42))]);
+        assert_eq!(kinds(b"4:spam"), vec![Ok(TokenKind::Bytes(b"spam"))]);
+        assert_eq!(kinds(b"0:"), vec![Ok(TokenKind::Bytes(b""))]);
+    }
+
+    #[test]
+    fn tokenizes_nested_containers() {
+        assert_eq!(
+            kinds(b"d1:ali1ee1:bdee"),
+            vec![
+                Ok(TokenKind::DictStart),
+                Ok(TokenKind::Key(b"a")),
+                Ok(TokenKind::ListStart),
+                Ok(TokenKind::Integer(1)),
+                Ok(TokenKind::ListEnd),
+                Ok(TokenKind::Key(b"b")),
+                Ok(TokenKind::DictStart),
+                Ok(TokenKind::DictEnd),
+                Ok(TokenKind::DictEnd),
+            ]
+        );
+    }
+
+    #[test]
+    fn tokens_carry_spans() {
+        let tokens: Vec<Token> = Tokenizer::new(b"l4:spami7ee").map(|t| t.unwrap()).collect();
+        let spans: Vec<(usize, usize)> = tokens.iter().map(|t| (t.offset, t.end)).collect();
+        assert_eq!(spans, vec![(0, 1), (1, 7), (7, 10), (10, 11)]);
+    }
+
+    #[test]
+    fn does_not_enforce_key_order() {
+        assert!(Tokenizer::new(b"d1:bi1e1:ai2ee").all(|t| t.is_ok()));
+    }
+
+    #[test]
+    fn stops_after_root_value() {
+        let mut tokenizer = Tokenizer::new(b"i1eXX");
+        assert!(tokenizer.next().unwrap().is_ok());
+        assert!(tokenizer.next().is_none());
+        assert!(tokenizer.is_finished());
+        assert_eq!(tokenizer.position(), 3);
+    }
+
+    #[test]
+    fn reports_errors_once() {
+        assert_eq!(kinds(b""), vec![Err(ParseError::new(ERR_EMPTY_INPUT, 0))]);
+        assert_eq!(
+            kinds(b"li1e"),
+            vec![
+                Ok(TokenKind::ListStart),
+                Ok(TokenKind::Integer(1)),
+                Err(ParseError::new(ERR_UNTERMINATED_LIST, 0)),
+            ]
+        );
+        assert_eq!(
+            kinds(b"di1ei2ee"),
+            vec![
+                Ok(TokenKind::DictStart),
+                Err(ParseError::new(ERR_DICT_KEY_MUST_BE_STRING, 1)),
+            ]
+        );
+        assert_eq!(kinds(b"5:abc"), vec![Err(ParseError::new(ERR_STRING_TOO_SHORT, 0))]);
+        assert_eq!(kinds(b"i-0e"), vec![Err(ParseError::new(ERR_INVALID_INTEGER, 0))]);
+        assert_eq!(kinds(b"x"), vec![Err(ParseError::new(unexpected_character('x', Expecting::Value, 0), 0))]);
+    }
+
+    #[test]
+    fn skip_container_returns_end_offset() {
+        let mut tokenizer = Tokenizer::new(b"d1:ald1:xi1eee1:bi2ee");
+        tokenizer.next(); // DictStart
+        tokenizer.next(); // Key a
+        assert_eq!(tokenizer.next().unwrap().unwrap().kind, TokenKind::ListStart);
+        assert_eq!(tokenizer.skip_container(), Ok(14));
+        assert_eq!(tokenizer.next().unwrap().unwrap().kind, TokenKind::Key(b"b"));
+    }
+}
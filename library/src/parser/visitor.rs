@@ -0,0 +1,241 @@
+//! Visitor based (SAX style) parsing driven by the tokenizer.
+//!
+//! [`visit`] walks the input once and reports each value to a user supplied
+//! [`Visitor`], so callers can build their own data structures directly from the
+//! borrowed input without allocating an intermediate `Node` tree.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use crate::error::messages::ERR_DICT_KEYS_ORDER;
+use crate::error::parse::ParseError;
+use crate::nodes::integer::Integer;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+
+/// Receives parse events from [`visit`].
+///
+/// All methods default to doing nothing, so implementations only override the
+/// events they care about. Returning `Err` from any method stops parsing and the
+/// message is reported as a `ParseError` at the offset of the current token.
+/// Byte strings and keys borrow from the input (`'de`), so visitors may keep them.
+pub trait Visitor<'de> {
+    /// Called for an integer value
+    fn on_integer(&mut self, _value: Integer) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called for a byte string value
+    fn on_bytes(&mut self, _value: &'de [u8]) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called when a list starts
+    fn on_list_begin(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called when a list ends
+    fn on_list_end(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called when a dictionary starts
+    fn on_dict_begin(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called for each dictionary key, before its value
+    fn on_dict_key(&mut self, _key: &'de [u8]) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Called when a dictionary ends
+    fn on_dict_end(&mut self) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Parses `input`, reporting every value to `visitor` in document order.
+///
+/// Validation matches the default parser: dictionary keys must be strings in
+/// strictly ascending order and bytes after the root value are ignored.
+///
+/// # Arguments
+/// * `input` - The byte slice containing bencode-encoded data
+/// * `visitor` - Receives the parse events
+///
+/// # Returns
+/// * `Ok(())` - The whole value was visited
+/// * `Err(ParseError)` - The input is malformed or the visitor returned an error
+///
+/// # Example
+/// ```
+/// use bencode_lib::parser::visitor::{visit, Visitor};
+/// use bencode_lib::Integer;
+///
+/// struct Sum(Integer);
+///
+/// impl<'de> Visitor<'de> for Sum {
+///     fn on_integer(&mut self, value: Integer) -> Result<(), String> {
+///         self.0 += value;
+///         Ok(())
+///     }
+/// }
+///
+/// let mut sum = Sum(0);
+/// visit(b"li1ei2ed1:ai3eee", &mut sum).unwrap();
+/// assert_eq!(sum.0, 6);
+/// ```
+pub fn visit<'de, V: Visitor<'de> + ?Sized>(input: &'de [u8], visitor: &mut V) -> Result<(), ParseError> {
+    // Last key seen in each open dictionary (None for lists and new dictionaries)
+    let mut last_keys: Vec<Option<&'de [u8]>> = Vec::new();
+    for token in Tokenizer::new(input) {
+        let token = token?;
+        let result = match token.kind {
+            TokenKind::Integer(value) => visitor.on_integer(value),
+            TokenKind::Bytes(value) => visitor.on_bytes(value),
+            TokenKind::ListStart => {
+                last_keys.push(None);
+                visitor.on_list_begin()
+            }
+            TokenKind::ListEnd => {
+                last_keys.pop();
+                visitor.on_list_end()
+            }
+            TokenKind::DictStart => {
+                last_keys.push(None);
+                visitor.on_dict_begin()
+            }
+            TokenKind::Key(key) => {
+                if let Some(last_key) = last_keys.last_mut() {
+                    if last_key.is_some_and(|last| key <= last) {
+                        return Err(ParseError::new(ERR_DICT_KEYS_ORDER, token.offset));
+                    }
+                    *last_key = Some(key);
+                }
+                visitor.on_dict_key(key)
+            }
+            TokenKind::DictEnd => {
+                last_keys.pop();
+                visitor.on_dict_end()
+            }
+        };
+        result.map_err(|e| ParseError::new(e, token.offset))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::HashMap;
+    use crate::nodes::node::Node;
+    use crate::parser::default::parse_bytes;
+
+    /// Rebuilds a Node tree from events, mirroring the default parser
+    #[derive(Default)]
+    struct NodeBuilder {
+        stack: Vec<(Node, Option<String>)>,
+        key: Option<String>,
+        root: Option<Node>,
+    }
+
+    impl NodeBuilder {
+        fn add(&mut self, node: Node) {
+            match self.stack.last_mut() {
+                Some((Node::List(items), _)) => items.push(node),
+                Some((Node::Dictionary(entries), _)) => {
+                    entries.insert(self.key.take().unwrap(), node);
+                }
+                _ => self.root = Some(node),
+            }
+        }
+    }
+
+    impl<'de> Visitor<'de> for NodeBuilder {
+        fn on_integer(&mut self, value: Integer) -> Result<(), String> {
+            self.add(Node::Integer(value));
+            Ok(())
+        }
+        fn on_bytes(&mut self, value: &'de [u8]) -> Result<(), String> {
+            self.add(Node::from_bytes(value.to_vec()));
+            Ok(())
+        }
+        fn on_list_begin(&mut self) -> Result<(), String> {
+            self.stack.push((Node::List(Vec::new()), self.key.take()));
+            Ok(())
+        }
+        fn on_dict_begin(&mut self) -> Result<(), String> {
+            self.stack.push((Node::Dictionary(HashMap::new()), self.key.take()));
+            Ok(())
+        }
+        fn on_dict_key(&mut self, key: &'de [u8]) -> Result<(), String> {
+            self.key = Some(String::from_utf8_lossy(key).into_owned());
+            Ok(())
+        }
+        fn on_list_end(&mut self) -> Result<(), String> {
+            let (node, key) = self.stack.pop().unwrap();
+            self.key = key;
+            self.add(node);
+            Ok(())
+        }
+        fn on_dict_end(&mut self) -> Result<(), String> {
+            self.on_list_end()
+        }
+    }
+
+    /// Collects borrowed keys, showing visitors can keep references into the input
+    struct KeyCollector<'de>(Vec<&'de [u8]>);
+
+    impl<'de> Visitor<'de> for KeyCollector<'de> {
+        fn on_dict_key(&mut self, key: &'de [u8]) -> Result<(), String> {
+            self.0.push(key);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn visit_builds_same_tree_as_parser() {
+        let data = b"d4:infod6:lengthi1024e4:name8:file.txte4:listli1ei-2e0:lee3:numi42ee";
+        let mut builder = NodeBuilder::default();
+        visit(data, &mut builder).unwrap();
+        assert_eq!(builder.root, parse_bytes(data).ok());
+    }
+
+    #[test]
+    fn visit_borrows_keys_from_input() {
+        let data = b"d1:ad1:bi1ee1:ci2ee".to_vec();
+        let mut collector = KeyCollector(Vec::new());
+        visit(&data, &mut collector).unwrap();
+        assert_eq!(collector.0, vec![&b"a"[..], b"b", b"c"]);
+    }
+
+    #[test]
+    fn visit_rejects_unordered_keys() {
+        let mut collector = KeyCollector(Vec::new());
+        assert_eq!(
+            visit(b"d1:bi1e1:ai2ee", &mut collector),
+            Err(ParseError::new(ERR_DICT_KEYS_ORDER, 7))
+        );
+    }
+
+    #[test]
+    fn visit_checks_order_per_dictionary() {
+        let mut collector = KeyCollector(Vec::new());
+        assert!(visit(b"d1:bd1:ai1ee1:ci2ee", &mut collector).is_ok());
+    }
+
+    #[test]
+    fn visit_stops_on_visitor_error() {
+        struct Reject;
+        impl<'de> Visitor<'de> for Reject {
+            fn on_bytes(&mut self, _value: &'de [u8]) -> Result<(), String> {
+                Err("no strings".to_string())
+            }
+        }
+        assert_eq!(
+            visit(b"li1e3:abce", &mut Reject),
+            Err(ParseError::new("no strings", 4))
+        );
+    }
+}
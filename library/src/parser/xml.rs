@@ -0,0 +1,277 @@
+//! Parsing of XML back into nodes.
+//!
+//! Reads the element layout [`to_xml`](crate::to_xml) writes: `<integer>`,
+//! `<string>`, `<list>` and `<dictionary>` of `<item><key>..</key><value>..</value></item>`.
+//! Whitespace between elements, comments and an `<?xml ..?>` declaration are
+//! skipped, so pretty printed or annotated files are read too, and empty
+//! elements may be written as `<list/>`. An empty `<value>` gives [`Node::None`].
+//!
+//! String contents use the backslash escapes the encoder writes, where
+//! `\u00XX` stands for one byte, rather than XML entities: the encoder writes
+//! `&` and `<` as they are, so entities are not decoded. Keys are taken as
+//! written.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::HashMap;
+use crate::error::messages::{ERR_DUPLICATE_KEY, ERR_EMPTY_INPUT, ERR_INVALID_XML};
+use crate::error::parse::ParseError;
+use crate::nodes::integer::parse_integer_text;
+use crate::nodes::node::Node;
+use crate::parser::common::unescape;
+
+/// Recursive descent over the XML text
+struct Parser<'a> {
+    text: &'a str,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Returns an invalid document error at `offset`
+    fn invalid(offset: usize) -> ParseError {
+        ParseError::new(ERR_INVALID_XML, offset)
+    }
+
+    /// Returns the unread text
+    fn rest(&self) -> &'a str {
+        &self.text[self.position..]
+    }
+
+    /// Skips whitespace, comments and processing instructions.
+    fn skip_misc(&mut self) -> Result<(), ParseError> {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.position += rest.len() - trimmed.len();
+            let terminator = if trimmed.starts_with("<!--") {
+                "-->"
+            } else if trimmed.starts_with("<?") {
+                "?>"
+            } else {
+                return Ok(());
+            };
+            let end = trimmed.find(terminator).ok_or_else(|| Self::invalid(self.position))?;
+            self.position += end + terminator.len();
+        }
+    }
+
+    /// Reads an opening tag, returning its name and whether it is self-closing.
+    fn open_tag(&mut self) -> Result<(&'a str, bool), ParseError> {
+        self.skip_misc()?;
+        let start = self.position;
+        let rest = self.rest().strip_prefix('<').ok_or_else(|| Self::invalid(start))?;
+        let end = rest.find('>').ok_or_else(|| Self::invalid(start))?;
+        let (tag, empty) = match rest[..end].strip_suffix('/') {
+            Some(tag) => (tag.trim_end(), true),
+            None => (rest[..end].trim_end(), false),
+        };
+        if tag.is_empty() || !tag.bytes().all(|b| b.is_ascii_alphanumeric()) {
+            return Err(Self::invalid(start));
+        }
+        self.position += end + 2;
+        Ok((tag, empty))
+    }
+
+    /// Reads a closing tag, which must be for `name`.
+    fn close_tag(&mut self, name: &str) -> Result<(), ParseError> {
+        self.skip_misc()?;
+        let start = self.position;
+        let rest = self.rest().strip_prefix("</").ok_or_else(|| Self::invalid(start))?;
+        let rest = rest.strip_prefix(name).ok_or_else(|| Self::invalid(start))?;
+        let trimmed = rest.trim_start();
+        if !trimmed.starts_with('>') {
+            return Err(Self::invalid(start));
+        }
+        self.position += 2 + name.len() + (rest.len() - trimmed.len()) + 1;
+        Ok(())
+    }
+
+    /// Returns true if the next tag closes an element
+    fn at_close(&mut self) -> Result<bool, ParseError> {
+        self.skip_misc()?;
+        Ok(self.rest().starts_with("</"))
+    }
+
+    /// Reads the text of an element up to its closing tag.
+    fn content(&mut self, name: &str, empty: bool) -> Result<&'a str, ParseError> {
+        if empty {
+            return Ok("");
+        }
+        let start = self.position;
+        let end = self.rest().find("</").ok_or_else(|| Self::invalid(start))?;
+        self.position += end;
+        self.close_tag(name)?;
+        Ok(&self.text[start..start + end])
+    }
+
+    /// Reads one element and its children.
+    fn parse_value(&mut self) -> Result<Node, ParseError> {
+        self.skip_misc()?;
+        let tag_start = self.position;
+        let (tag, empty) = self.open_tag()?;
+        let start = self.position;
+        match tag {
+            "integer" => {
+                let text = self.content(tag, empty)?.trim();
+                parse_integer_text(text)
+                    .map(Node::Integer)
+                    .map_err(|message| ParseError::new(message, start))
+            }
+            "string" => {
+                let text = self.content(tag, empty)?;
+                Ok(Node::from_bytes(unescape(text).ok_or_else(|| Self::invalid(start))?))
+            }
+            "list" => {
+                let mut items = Vec::new();
+                if !empty {
+                    while !self.at_close()? {
+                        items.push(self.parse_value()?);
+                    }
+                    self.close_tag(tag)?;
+                }
+                Ok(Node::List(items))
+            }
+            "dictionary" => {
+                let mut entries = HashMap::new();
+                if !empty {
+                    while !self.at_close()? {
+                        let (key, value) = self.parse_item()?;
+                        if entries.contains_key(&key) {
+                            return Err(ParseError::new(ERR_DUPLICATE_KEY, start).with_path(key));
+                        }
+                        entries.insert(key, value);
+                    }
+                    self.close_tag(tag)?;
+                }
+                Ok(Node::Dictionary(entries))
+            }
+            _ => Err(Self::invalid(tag_start)),
+        }
+    }
+
+    /// Reads one `<item>` of a dictionary.
+    fn parse_item(&mut self) -> Result<(String, Node), ParseError> {
+        let start = self.position;
+        let expect = |parser: &mut Self, name: &str| match parser.open_tag()? {
+            (tag, empty) if tag == name => Ok(empty),
+            _ => Err(Self::invalid(start)),
+        };
+        if expect(self, "item")? {
+            return Err(Self::invalid(start));
+        }
+        let empty = expect(self, "key")?;
+        let key = self.content("key", empty)?.to_string();
+        let value = if expect(self, "value")? {
+            Node::None
+        } else if self.at_close()? {
+            self.close_tag("value")?;
+            Node::None
+        } else {
+            let value = self.parse_value()?;
+            self.close_tag("value")?;
+            value
+        };
+        self.close_tag("item")?;
+        Ok((key, value))
+    }
+}
+
+/// Parses XML in the layout [`to_xml`](crate::to_xml) writes into a Node tree.
+///
+/// # Arguments
+/// * `text` - The XML document
+///
+/// # Returns
+/// * `Ok(Node)` - The parsed tree
+/// * `Err(ParseError)` - The document is empty, has an unknown or unclosed
+///   element, an invalid integer or escape, or repeats a key; the offset is
+///   where the problem was found
+///
+/// # Example
+/// ```
+/// use bencode_lib::{from_xml, stringify_to_bytes};
+///
+/// let text = "<?xml version=\"1.0\"?>
+/// <dictionary>
+///   <item><key>announce</key><value><string>udp://new</string></value></item>
+///   <item><key>private</key><value><integer>1</integer></value></item>
+/// </dictionary>";
+/// let node = from_xml(text).unwrap();
+/// assert_eq!(stringify_to_bytes(&node).unwrap(), b"d8:announce9:udp://new7:privatei1ee");
+/// ```
+pub fn parse(text: &str) -> Result<Node, ParseError> {
+    let mut parser = Parser { text, position: 0 };
+    parser.skip_misc()?;
+    if parser.rest().is_empty() {
+        return Err(ParseError::new(ERR_EMPTY_INPUT, 0));
+    }
+    let node = parser.parse_value()?;
+    parser.skip_misc()?;
+    if !parser.rest().is_empty() {
+        return Err(Parser::invalid(parser.position));
+    }
+    Ok(node)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+    use crate::io::destinations::buffer::Buffer;
+    use crate::make_node;
+    use crate::stringify::xml::stringify;
+
+    fn round_trip(node: &Node) -> Node {
+        let mut destination = Buffer::new();
+        stringify(node, &mut destination).unwrap();
+        parse(&destination.to_string()).unwrap()
+    }
+
+    #[test]
+    fn encoder_output_round_trips() {
+        let node = make_node([
+            ("announce", make_node("udp://tracker:6969/announce?a=1&b=<2>")),
+            ("empty", make_node(vec![] as Vec<Node>)),
+            ("hashes", Node::List(vec![Node::Bytes(vec![0xff, 0x00, b'"']), make_node("caf\u{e9}")])),
+            ("info", make_node([("length", make_node(-5)), ("name", make_node(""))])),
+            ("table", Node::Dictionary(HashMap::new())),
+        ]);
+        assert_eq!(round_trip(&node), node);
+        assert_eq!(round_trip(&make_node(42)), make_node(42));
+    }
+
+    #[test]
+    fn formatted_xml_is_read() {
+        let text = "<!-- edited -->\n<list>\n  <list/>\n  <string/>\n  <dictionary>\n    <item>\n      <key>k</key>\n      <value></value>\n    </item>\n  </dictionary >\n  <integer> 7 </integer>\n</list>\n";
+        let mut entries = HashMap::new();
+        entries.insert("k".to_string(), Node::None);
+        let expected = make_node(vec![
+            make_node(vec![] as Vec<Node>),
+            make_node(""),
+            Node::Dictionary(entries),
+            make_node(7),
+        ]);
+        assert_eq!(parse(text).unwrap(), expected);
+    }
+
+    #[test]
+    fn malformed_xml_is_rejected() {
+        let kind = |text: &str| parse(text).unwrap_err().kind();
+        assert_eq!(kind(" <!-- nothing --> "), ErrorKind::EmptyInput);
+        assert_eq!(kind("<float>1.5</float>"), ErrorKind::InvalidXml);
+        assert_eq!(kind("<list><integer>1</integer>"), ErrorKind::InvalidXml);
+        assert_eq!(kind("<string>a</list>"), ErrorKind::InvalidXml);
+        assert_eq!(kind("<string>\\q</string>"), ErrorKind::InvalidXml);
+        assert_eq!(kind("<integer>x</integer>"), ErrorKind::InvalidInteger);
+        assert_eq!(kind("<dictionary><key>a</key></dictionary>"), ErrorKind::InvalidXml);
+        assert_eq!(kind("<integer>1</integer><integer>2</integer>"), ErrorKind::InvalidXml);
+        let duplicate = "<dictionary><item><key>a</key><value/></item><item><key>a</key><value/></item></dictionary>";
+        assert_eq!(parse(duplicate).unwrap_err().kind(), ErrorKind::DuplicateKey);
+        let error = parse("<list>\n  <map/>\n</list>").unwrap_err();
+        assert_eq!((error.kind(), error.offset), (ErrorKind::InvalidXml, 9));
+    }
+}
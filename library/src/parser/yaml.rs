@@ -0,0 +1,350 @@
+//! Parsing of YAML back into nodes.
+//!
+//! Reads what [`to_yaml`](crate::to_yaml) writes, and the block style YAML a
+//! person editing that output is likely to produce: `key: value` mappings,
+//! `- item` sequences (including `- key: value` items), comments, plain,
+//! single quoted and double quoted strings, and `[]` / `{}` for empty
+//! containers. Plain scalars made of digits are integers and other plain
+//! scalars are strings; `null` and `~` give [`Node::None`]. Flow collections
+//! with items, anchors, tags and block scalars are rejected.
+//!
+//! Double quoted strings use the escapes the encoder writes, where `\u00XX`
+//! stands for one byte, so binary strings survive the round trip.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::HashMap;
+use crate::error::messages::{ERR_DUPLICATE_KEY, ERR_EMPTY_INPUT, ERR_INVALID_YAML};
+use crate::error::parse::ParseError;
+use crate::nodes::integer::parse_integer_text;
+use crate::nodes::node::Node;
+use crate::parser::common::unescape;
+
+/// One line holding content, with comments and blank lines already dropped
+#[derive(Debug, Clone, Copy)]
+struct Line<'a> {
+    /// Byte offset of the content in the input
+    offset: usize,
+    /// Column the content starts at
+    indent: usize,
+    /// The content, without indentation or trailing whitespace
+    text: &'a str,
+}
+
+/// Splits the input into content lines.
+fn content_lines(text: &str) -> Result<Vec<Line<'_>>, ParseError> {
+    let mut lines = Vec::new();
+    let mut offset = 0;
+    for raw in text.split('\n') {
+        let start = offset;
+        offset += raw.len() + 1;
+        let content = raw.trim_start_matches(' ');
+        let indent = raw.len() - content.len();
+        let content = content.trim_end();
+        if content.starts_with('\t') {
+            return Err(ParseError::new(ERR_INVALID_YAML, start + indent));
+        }
+        if content.is_empty() || content.starts_with('#') || content == "---" || content == "..." {
+            continue;
+        }
+        lines.push(Line {
+            offset: start + indent,
+            indent,
+            text: content,
+        });
+    }
+    Ok(lines)
+}
+
+/// Returns true if a line starts a sequence item
+fn is_item(text: &str) -> bool {
+    text == "-" || text.starts_with("- ")
+}
+
+/// Returns the length of a quoted scalar at the start of `text`, including
+/// both quotes, or None if it is not closed.
+fn quoted_length(text: &str) -> Option<usize> {
+    let quote = text.chars().next()?;
+    let mut chars = text.char_indices().skip(1);
+    while let Some((index, c)) = chars.next() {
+        if quote == '"' && c == '\\' {
+            chars.next();
+        } else if c == quote {
+            // A doubled quote is an escaped quote in single quoted scalars
+            if quote == '\'' && text[index + 1..].starts_with('\'') {
+                chars.next();
+            } else {
+                return Some(index + 1);
+            }
+        }
+    }
+    None
+}
+
+/// Returns the text of a quoted scalar, with its quotes and escapes removed
+fn unquote(quoted: &str) -> Option<Vec<u8>> {
+    let inner = &quoted[1..quoted.len() - 1];
+    if quoted.starts_with('"') {
+        unescape(inner)
+    } else {
+        Some(inner.replace("''", "'").into_bytes())
+    }
+}
+
+/// Returns true if nothing but a comment follows a scalar
+fn only_comment(rest: &str) -> bool {
+    let trimmed = rest.trim_start();
+    trimmed.is_empty() || (trimmed.len() < rest.len() && trimmed.starts_with('#'))
+}
+
+/// Splits a mapping entry into its key and the text after the colon.
+fn split_entry(text: &str) -> Option<(String, &str)> {
+    let (key, rest) = if text.starts_with('"') || text.starts_with('\'') {
+        let length = quoted_length(text)?;
+        let key = String::from_utf8(unquote(&text[..length])?).ok()?;
+        (key, text[length..].trim_start().strip_prefix(':')?)
+    } else {
+        let colon = text.match_indices(':').map(|(index, _)| index).find(|&index| {
+            let after = &text[index + 1..];
+            after.is_empty() || after.starts_with(' ')
+        })?;
+        (text[..colon].trim_end().to_string(), &text[colon + 1..])
+    };
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((key, rest.trim_start()))
+}
+
+/// Reads a scalar value.
+///
+/// # Arguments
+/// * `text` - The scalar and any trailing comment
+/// * `offset` - Byte offset of the scalar, for errors
+fn parse_scalar(text: &str, offset: usize) -> Result<Node, ParseError> {
+    let invalid = || ParseError::new(ERR_INVALID_YAML, offset);
+    if text.starts_with('"') || text.starts_with('\'') {
+        let length = quoted_length(text).ok_or_else(invalid)?;
+        if !only_comment(&text[length..]) {
+            return Err(invalid());
+        }
+        return Ok(Node::from_bytes(unquote(&text[..length]).ok_or_else(invalid)?));
+    }
+    let text = match text.find(" #") {
+        Some(comment) => text[..comment].trim_end(),
+        None => text,
+    };
+    match text {
+        "[]" => return Ok(Node::List(Vec::new())),
+        "{}" => return Ok(Node::Dictionary(HashMap::new())),
+        "null" | "~" => return Ok(Node::None),
+        _ => {}
+    }
+    if text.starts_with(['[', '{', '&', '*', '!', '|', '>', '%', '@', '`']) {
+        return Err(invalid());
+    }
+    let digits = text.strip_prefix('-').unwrap_or(text);
+    if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+        return parse_integer_text(text)
+            .map(Node::Integer)
+            .map_err(|message| ParseError::new(message, offset));
+    }
+    Ok(Node::Str(text.to_string()))
+}
+
+/// Recursive descent over the content lines
+struct Parser<'a> {
+    lines: Vec<Line<'a>>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Returns the next line if it is indented at least `indent` columns
+    fn peek(&self, indent: usize) -> Option<Line<'a>> {
+        self.lines.get(self.position).copied().filter(|line| line.indent >= indent)
+    }
+
+    /// Reads the block starting at the current line.
+    fn parse_block(&mut self) -> Result<Node, ParseError> {
+        let line = self.lines[self.position];
+        if is_item(line.text) {
+            self.parse_sequence(line.indent)
+        } else if split_entry(line.text).is_some() {
+            self.parse_mapping(line.indent)
+        } else {
+            self.position += 1;
+            parse_scalar(line.text, line.offset)
+        }
+    }
+
+    /// Reads the value nested under a line with nothing after its `-` or `:`;
+    /// with no deeper lines the value is empty.
+    fn parse_nested(&mut self, parent_indent: usize) -> Result<Node, ParseError> {
+        match self.peek(parent_indent + 1) {
+            Some(_) => self.parse_block(),
+            None => Ok(Node::None),
+        }
+    }
+
+    /// Reads the items of a sequence whose dashes are at `indent`.
+    fn parse_sequence(&mut self, indent: usize) -> Result<Node, ParseError> {
+        let mut items = Vec::new();
+        while let Some(line) = self.peek(indent).filter(|line| line.indent == indent && is_item(line.text)) {
+            let rest = line.text[1..].trim_start();
+            if rest.is_empty() {
+                self.position += 1;
+                items.push(self.parse_nested(indent)?);
+            } else {
+                // The item's content starts a block at its own column
+                let shift = line.text.len() - rest.len();
+                self.lines[self.position] = Line {
+                    offset: line.offset + shift,
+                    indent: line.indent + shift,
+                    text: rest,
+                };
+                items.push(self.parse_block()?);
+            }
+        }
+        Ok(Node::List(items))
+    }
+
+    /// Reads the entries of a mapping whose keys are at `indent`.
+    fn parse_mapping(&mut self, indent: usize) -> Result<Node, ParseError> {
+        let mut entries = HashMap::new();
+        while let Some(line) = self.peek(indent).filter(|line| line.indent == indent && !is_item(line.text)) {
+            let (key, value) = split_entry(line.text).ok_or_else(|| ParseError::new(ERR_INVALID_YAML, line.offset))?;
+            self.position += 1;
+            let value = if !value.is_empty() {
+                parse_scalar(value, line.offset + (line.text.len() - value.len()))?
+            } else if self.peek(indent).is_some_and(|next| next.indent == indent && is_item(next.text)) {
+                // Sequences may sit at the same indent as their key
+                self.parse_sequence(indent)?
+            } else {
+                self.parse_nested(indent)?
+            };
+            if entries.insert(key.clone(), value).is_some() {
+                return Err(ParseError::new(ERR_DUPLICATE_KEY, line.offset).with_path(key));
+            }
+        }
+        Ok(Node::Dictionary(entries))
+    }
+}
+
+/// Parses YAML into a Node tree.
+///
+/// # Arguments
+/// * `text` - The YAML document
+///
+/// # Returns
+/// * `Ok(Node)` - The parsed tree
+/// * `Err(ParseError)` - The document is empty, uses YAML this parser does not
+///   read, is badly indented or repeats a key; the offset is where the
+///   problem was found
+///
+/// # Example
+/// ```
+/// use bencode_lib::{BufferDestination, from_yaml, parse_bytes, stringify_to_bytes, to_yaml};
+///
+/// let torrent = parse_bytes(b"d8:announce9:udp://old4:infod6:lengthi5e4:name5:helloee").unwrap();
+/// let mut destination = BufferDestination::new();
+/// to_yaml(&torrent, &mut destination).unwrap();
+/// let edited = destination.to_string().replace("udp://old", "udp://new");
+/// let node = from_yaml(&edited).unwrap();
+/// assert_eq!(stringify_to_bytes(&node).unwrap(), b"d8:announce9:udp://new4:infod6:lengthi5e4:name5:helloee");
+/// ```
+pub fn parse(text: &str) -> Result<Node, ParseError> {
+    let lines = content_lines(text)?;
+    if lines.is_empty() {
+        return Err(ParseError::new(ERR_EMPTY_INPUT, 0));
+    }
+    let mut parser = Parser { lines, position: 0 };
+    let node = parser.parse_block()?;
+    match parser.lines.get(parser.position) {
+        Some(line) => Err(ParseError::new(ERR_INVALID_YAML, line.offset)),
+        None => Ok(node),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+    use crate::io::destinations::buffer::Buffer;
+    use crate::make_node;
+    use crate::stringify::yaml::stringify;
+
+    fn round_trip(node: &Node) -> Node {
+        let mut destination = Buffer::new();
+        stringify(node, &mut destination).unwrap();
+        parse(&destination.to_string()).unwrap()
+    }
+
+    #[test]
+    fn encoder_output_round_trips() {
+        let node = make_node([
+            ("announce", make_node("udp://tracker:6969/announce")),
+            ("empty", make_node(vec![] as Vec<Node>)),
+            ("hashes", Node::List(vec![Node::Bytes(vec![0xff, 0x00, b'"']), make_node("caf\u{e9}")])),
+            ("info", make_node([("length", make_node(-5)), ("name", make_node("a: b #c"))])),
+            ("nested", make_node(vec![make_node(vec![make_node(1)]), make_node([("k", make_node("v"))])])),
+            ("table", Node::Dictionary(HashMap::new())),
+        ]);
+        assert_eq!(round_trip(&node), node);
+        for scalar in [make_node(42), make_node("text"), make_node(vec![make_node(1), make_node(2)])] {
+            assert_eq!(round_trip(&scalar), scalar);
+        }
+    }
+
+    #[test]
+    fn keys_needing_quotes_round_trip() {
+        let torrent = crate::parser::default::parse_bytes(b"d4:infod4:nam 1:xee").unwrap();
+        assert_eq!(round_trip(&torrent), torrent);
+        let keys = [" lead", "trail ", "", "a: b", "a #b", "end:", "-dash", "#hash", "\"q", "caf\u{e9}", "tab\t"];
+        let node = make_node(keys.map(|key| (key, make_node(1))));
+        assert_eq!(round_trip(&node), node);
+        let mut destination = Buffer::new();
+        stringify(&make_node([("plain key", make_node(1)), ("trail ", make_node(2))]), &mut destination).unwrap();
+        assert_eq!(destination.to_string(), "\nplain key: 1\n\"trail \": 2\n");
+    }
+
+    #[test]
+    fn hand_written_yaml_is_read() {
+        let text = "# trackers to try\n---\nannounce: 'it''s' # main\nlist:\n- udp://a\n- - 1\n  - \"two\"\ntiers:\n  - url: x\n    weight: 2\n  -\n    url: y\nnothing:\n";
+        let node = parse(text).unwrap();
+        let expected = make_node([
+            ("announce", make_node("it's")),
+            ("list", make_node(vec![make_node("udp://a"), make_node(vec![make_node(1), make_node("two")])])),
+            (
+                "tiers",
+                make_node(vec![
+                    make_node([("url", make_node("x")), ("weight", make_node(2))]),
+                    make_node([("url", make_node("y"))]),
+                ]),
+            ),
+            ("nothing", Node::None),
+        ]);
+        assert_eq!(node, expected);
+    }
+
+    #[test]
+    fn unsupported_or_malformed_yaml_is_rejected() {
+        let kind = |text: &str| parse(text).unwrap_err().kind();
+        assert_eq!(kind(""), ErrorKind::EmptyInput);
+        assert_eq!(kind("# only a comment\n"), ErrorKind::EmptyInput);
+        assert_eq!(kind("a: [1, 2]"), ErrorKind::InvalidYaml);
+        assert_eq!(kind("a: &anchor 1"), ErrorKind::InvalidYaml);
+        assert_eq!(kind("a: |\n  text"), ErrorKind::InvalidYaml);
+        assert_eq!(kind("a: \"open"), ErrorKind::InvalidYaml);
+        assert_eq!(kind("a: \"bad \\q\""), ErrorKind::InvalidYaml);
+        assert_eq!(kind("a: 99999999999999999999"), ErrorKind::IntegerOverflow);
+        assert_eq!(kind("a:\n\t- 1"), ErrorKind::InvalidYaml);
+        let error = parse("a: 1\n  b: 2\n").unwrap_err();
+        assert_eq!((error.kind(), error.offset), (ErrorKind::InvalidYaml, 7));
+        let error = parse("a: 1\nb: 2\na: 3\n").unwrap_err();
+        assert_eq!((error.kind(), error.offset, error.path.as_deref()), (ErrorKind::DuplicateKey, 10, Some("a")));
+    }
+}
@@ -0,0 +1,638 @@
+//! Tools for repairing bencode produced by non-conforming encoders.
+//!
+//! Repairs work on the raw input rather than a `Node` tree so everything that is
+//! not being fixed (string contents, integer text, unknown fields) is re-emitted
+//! byte for byte.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use core::fmt;
+use core::ops::Range;
+
+use crate::HashMap;
+use crate::error::messages::ERR_EMPTY_INPUT;
+use crate::error::parse::ParseError;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+
+const INTEGER_START: u8 = b'i';
+const LIST_START: u8 = b'l';
+const DICT_START: u8 = b'd';
+const END_MARKER: u8 = b'e';
+
+/// Which occurrence of a duplicated dictionary key to keep
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    /// Keep the first occurrence, as most torrent clients do
+    KeepFirst,
+    /// Keep the last occurrence, as building a map by insertion does
+    KeepLast,
+}
+
+/// Selects which fixes [`repair`] applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RepairConfig {
+    /// Drop bytes following the root value (default: true)
+    pub strip_trailing_data: bool,
+    /// Sort dictionary keys into canonical order (default: true)
+    pub sort_keys: bool,
+    /// Remove duplicate dictionary keys using this policy; None keeps them (default: KeepFirst)
+    pub duplicate_keys: Option<DuplicateKeyPolicy>,
+    /// Rewrite integers in canonical form, e.g. `i007e` as `i7e` (default: true)
+    pub normalize_integers: bool,
+}
+
+impl Default for RepairConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RepairConfig {
+    /// Create a repair configuration with every fix enabled
+    pub const fn new() -> Self {
+        Self {
+            strip_trailing_data: true,
+            sort_keys: true,
+            duplicate_keys: Some(DuplicateKeyPolicy::KeepFirst),
+            normalize_integers: true,
+        }
+    }
+
+    /// Create a repair configuration with every fix disabled
+    pub const fn none() -> Self {
+        Self {
+            strip_trailing_data: false,
+            sort_keys: false,
+            duplicate_keys: None,
+            normalize_integers: false,
+        }
+    }
+
+    /// Set whether to drop bytes following the root value
+    pub const fn with_strip_trailing_data(mut self, strip: bool) -> Self {
+        self.strip_trailing_data = strip;
+        self
+    }
+
+    /// Set whether to sort dictionary keys
+    pub const fn with_sort_keys(mut self, sort: bool) -> Self {
+        self.sort_keys = sort;
+        self
+    }
+
+    /// Set the duplicate key policy (None keeps duplicates)
+    pub const fn with_duplicate_keys(mut self, policy: Option<DuplicateKeyPolicy>) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Set whether to rewrite integers in canonical form
+    pub const fn with_normalize_integers(mut self, normalize: bool) -> Self {
+        self.normalize_integers = normalize;
+        self
+    }
+}
+
+/// Selects how [`normalize`] resolves duplicates and which keys it drops
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Profile<'a> {
+    /// Occurrence kept when a dictionary key is repeated (default: KeepFirst)
+    pub duplicate_keys: DuplicateKeyPolicy,
+    /// Keys removed, with their values, from every dictionary at any depth (default: none)
+    pub excluded_keys: &'a [&'a [u8]],
+}
+
+impl Default for Profile<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Profile<'a> {
+    /// Create a profile keeping the first of duplicated keys and dropping nothing
+    pub const fn new() -> Self {
+        Self {
+            duplicate_keys: DuplicateKeyPolicy::KeepFirst,
+            excluded_keys: &[],
+        }
+    }
+
+    /// Set which occurrence of a duplicated key is kept
+    pub const fn with_duplicate_keys(mut self, policy: DuplicateKeyPolicy) -> Self {
+        self.duplicate_keys = policy;
+        self
+    }
+
+    /// Set the keys dropped from every dictionary, e.g. volatile transaction ids
+    pub const fn with_excluded_keys(mut self, keys: &'a [&'a [u8]]) -> Self {
+        self.excluded_keys = keys;
+        self
+    }
+}
+
+/// A single alteration made by [`repair`]; offsets refer to the original input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepairChange {
+    /// Bytes after the root value were removed
+    TrailingDataRemoved {
+        /// Offset of the first removed byte
+        offset: usize,
+        /// Number of bytes removed
+        length: usize,
+    },
+    /// The keys of a dictionary were reordered
+    KeysSorted {
+        /// Offset of the dictionary
+        offset: usize,
+    },
+    /// A duplicate key and its value were removed
+    DuplicateKeyRemoved {
+        /// Offset of the removed key
+        offset: usize,
+        /// The duplicated key
+        key: Vec<u8>,
+    },
+    /// An integer was rewritten in canonical form
+    IntegerNormalized {
+        /// Offset of the integer
+        offset: usize,
+        /// Original digits
+        from: String,
+        /// Canonical digits
+        to: String,
+    },
+}
+
+impl fmt::Display for RepairChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RepairChange::TrailingDataRemoved { offset, length } => {
+                write!(f, "removed {} bytes of trailing data at offset {}", length, offset)
+            }
+            RepairChange::KeysSorted { offset } => {
+                write!(f, "sorted dictionary keys at offset {}", offset)
+            }
+            RepairChange::DuplicateKeyRemoved { offset, key } => write!(
+                f,
+                "removed duplicate key {:?} at offset {}",
+                String::from_utf8_lossy(key),
+                offset
+            ),
+            RepairChange::IntegerNormalized { offset, from, to } => {
+                write!(f, "normalized integer {} to {} at offset {}", from, to, offset)
+            }
+        }
+    }
+}
+
+/// Result of [`repair`]: the re-encoded document and what was changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepairReport {
+    /// The repaired bencode
+    pub output: Vec<u8>,
+    /// Every change made, in input order (empty if the input needed no repair)
+    pub changes: Vec<RepairChange>,
+}
+
+/// A value of the input, described by byte spans rather than decoded content
+enum Item<'a> {
+    /// An integer, spanning `i...e`
+    Integer(Range<usize>),
+    /// A string, copied verbatim
+    Bytes(Range<usize>),
+    /// A list of items
+    List(Vec<Item<'a>>),
+    /// A dictionary starting at the given offset, entries in input order
+    Dict(usize, Vec<Entry<'a>>),
+}
+
+/// A dictionary entry
+struct Entry<'a> {
+    /// Key content, used for ordering
+    key: &'a [u8],
+    /// Span of the encoded key (length prefix included)
+    key_span: Range<usize>,
+    /// The associated value
+    value: Item<'a>,
+}
+
+/// Container being built while reading tokens
+enum Open<'a> {
+    List(Vec<Item<'a>>),
+    Dict(usize, Vec<Entry<'a>>, Option<(&'a [u8], Range<usize>)>),
+}
+
+/// Reads the root value of `source` into a span tree.
+///
+/// # Returns
+/// * `Ok((Item, usize))` - The root item and the offset just past it
+/// * `Err(ParseError)` - If the input is malformed
+fn read_tree(source: &[u8]) -> Result<(Item<'_>, usize), ParseError> {
+    let mut tokenizer = Tokenizer::new(source).with_allow_plus_sign(true);
+    let mut stack: Vec<Open> = Vec::new();
+    while let Some(token) = tokenizer.next() {
+        let token = token?;
+        let item = match token.kind {
+            TokenKind::Integer(_) => Item::Integer(token.offset..token.end),
+            TokenKind::Bytes(_) => Item::Bytes(token.offset..token.end),
+            TokenKind::Key(key) => {
+                if let Some(Open::Dict(_, _, pending)) = stack.last_mut() {
+                    *pending = Some((key, token.offset..token.end));
+                }
+                continue;
+            }
+            TokenKind::ListStart => {
+                stack.push(Open::List(Vec::new()));
+                continue;
+            }
+            TokenKind::DictStart => {
+                stack.push(Open::Dict(token.offset, Vec::new(), None));
+                continue;
+            }
+            TokenKind::ListEnd | TokenKind::DictEnd => match stack.pop() {
+                Some(Open::List(items)) => Item::List(items),
+                Some(Open::Dict(offset, entries, _)) => Item::Dict(offset, entries),
+                None => unreachable!("tokenizer only closes open containers"),
+            },
+        };
+        match stack.last_mut() {
+            Some(Open::List(items)) => items.push(item),
+            Some(Open::Dict(_, entries, pending)) => {
+                if let Some((key, key_span)) = pending.take() {
+                    entries.push(Entry {
+                        key,
+                        key_span,
+                        value: item,
+                    });
+                }
+            }
+            None => return Ok((item, tokenizer.position())),
+        }
+    }
+    // The tokenizer reports empty and truncated input, so this is not reached
+    Err(ParseError::new(ERR_EMPTY_INPUT, source.len()))
+}
+
+/// Re-encodes a span tree, applying the configured fixes
+struct Encoder<'a> {
+    source: &'a [u8],
+    config: &'a RepairConfig,
+    /// Keys dropped from every dictionary
+    excluded_keys: &'a [&'a [u8]],
+    out: Vec<u8>,
+    changes: Vec<RepairChange>,
+}
+
+impl<'a> Encoder<'a> {
+    /// Writes `item` to the output.
+    fn emit(&mut self, item: &Item) {
+        match item {
+            Item::Integer(span) => self.emit_integer(span.clone()),
+            Item::Bytes(span) => self.out.extend_from_slice(&self.source[span.clone()]),
+            Item::List(items) => {
+                self.out.push(LIST_START);
+                for item in items {
+                    self.emit(item);
+                }
+                self.out.push(END_MARKER);
+            }
+            Item::Dict(offset, entries) => self.emit_dictionary(*offset, entries),
+        }
+    }
+
+    /// Writes an integer, rewriting it in canonical form if configured.
+    fn emit_integer(&mut self, span: Range<usize>) {
+        let digits = &self.source[span.start + 1..span.end - 1];
+        if self.config.normalize_integers {
+            // The tokenizer has already checked the digits parse
+            let text = core::str::from_utf8(digits).unwrap_or_default();
+            let canonical = text.parse::<i64>().unwrap_or_default().to_string();
+            if canonical != text {
+                self.out.push(INTEGER_START);
+                self.out.extend_from_slice(canonical.as_bytes());
+                self.out.push(END_MARKER);
+                self.changes.push(RepairChange::IntegerNormalized {
+                    offset: span.start,
+                    from: text.to_string(),
+                    to: canonical,
+                });
+                return;
+            }
+        }
+        self.out.extend_from_slice(&self.source[span]);
+    }
+
+    /// Writes a dictionary, removing duplicates and sorting keys if configured.
+    fn emit_dictionary(&mut self, offset: usize, entries: &[Entry]) {
+        let excluded = self.excluded_keys;
+        let filtered: Vec<&Entry> = entries
+            .iter()
+            .filter(|entry| !excluded.contains(&entry.key))
+            .collect();
+        let mut kept: Vec<&Entry> = match self.config.duplicate_keys {
+            Some(policy) => {
+                // Index of the occurrence to keep for each key
+                let mut keep: HashMap<&[u8], usize> = HashMap::new();
+                for (index, entry) in filtered.iter().enumerate() {
+                    match policy {
+                        DuplicateKeyPolicy::KeepFirst => {
+                            keep.entry(entry.key).or_insert(index);
+                        }
+                        DuplicateKeyPolicy::KeepLast => {
+                            keep.insert(entry.key, index);
+                        }
+                    }
+                }
+                let mut kept = Vec::with_capacity(keep.len());
+                for (index, entry) in filtered.into_iter().enumerate() {
+                    if keep[entry.key] == index {
+                        kept.push(entry);
+                    } else {
+                        self.changes.push(RepairChange::DuplicateKeyRemoved {
+                            offset: entry.key_span.start,
+                            key: entry.key.to_vec(),
+                        });
+                    }
+                }
+                kept
+            }
+            None => filtered,
+        };
+        if self.config.sort_keys && kept.windows(2).any(|pair| pair[1].key < pair[0].key) {
+            kept.sort_by_key(|entry| entry.key);
+            self.changes.push(RepairChange::KeysSorted { offset });
+        }
+        self.out.push(DICT_START);
+        for entry in kept {
+            self.out.extend_from_slice(&self.source[entry.key_span.clone()]);
+            self.emit(&entry.value);
+        }
+        self.out.push(END_MARKER);
+    }
+}
+
+/// Repairs common defects in `source` and re-encodes it, reporting every change.
+///
+/// The fixes applied are selected by `config`: stripping trailing data, removing
+/// duplicate keys, sorting dictionary keys and rewriting integers canonically.
+/// Anything not being fixed is copied byte for byte. Input that is malformed in
+/// other ways (truncated, bad string lengths) cannot be repaired and is rejected.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded input
+/// * `config` - The fixes to apply
+///
+/// # Returns
+/// * `Ok(RepairReport)` - The repaired document and the list of changes
+/// * `Err(ParseError)` - If the input cannot be read
+///
+/// # Example
+/// ```
+/// use bencode_lib::repair::{repair, RepairChange, RepairConfig};
+///
+/// let report = repair(b"d1:bi1e1:ai07eeXX", &RepairConfig::new()).unwrap();
+/// assert_eq!(report.output, b"d1:ai7e1:bi1ee");
+/// assert_eq!(report.changes.len(), 3);
+/// assert_eq!(report.changes[0], RepairChange::KeysSorted { offset: 0 });
+/// assert!(report.changes.contains(&RepairChange::IntegerNormalized {
+///     offset: 10,
+///     from: "07".to_string(),
+///     to: "7".to_string(),
+/// }));
+/// ```
+pub fn repair(source: &[u8], config: &RepairConfig) -> Result<RepairReport, ParseError> {
+    let (root, end) = read_tree(source)?;
+    let mut encoder = Encoder {
+        source,
+        config,
+        excluded_keys: &[],
+        out: Vec::with_capacity(source.len()),
+        changes: Vec::new(),
+    };
+    encoder.emit(&root);
+    if end < source.len() {
+        if config.strip_trailing_data {
+            encoder.changes.push(RepairChange::TrailingDataRemoved {
+                offset: end,
+                length: source.len() - end,
+            });
+        } else {
+            encoder.out.extend_from_slice(&source[end..]);
+        }
+    }
+    Ok(RepairReport {
+        output: encoder.out,
+        changes: encoder.changes,
+    })
+}
+
+/// Re-emits `source` with the keys of every dictionary sorted into canonical order.
+///
+/// Keys and values are copied byte for byte, only their order changes, so a
+/// document whose sole defect is unsorted keys becomes valid bencode. The sort is
+/// stable: duplicate keys are kept, in input order. Bytes after the root value are
+/// copied unchanged.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded input, possibly with unsorted keys
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The re-sorted document
+/// * `Err(ParseError)` - If the input is malformed in some other way
+///
+/// # Example
+/// ```
+/// use bencode_lib::repair::sort_keys;
+///
+/// assert_eq!(sort_keys(b"d1:bi1e1:ai2ee").unwrap(), b"d1:ai2e1:bi1ee");
+/// ```
+pub fn sort_keys(source: &[u8]) -> Result<Vec<u8>, ParseError> {
+    repair(source, &RepairConfig::none().with_sort_keys(true)).map(|report| report.output)
+}
+
+/// Re-encodes `source` in a normalized form: keys sorted, duplicates resolved,
+/// integers canonical, excluded keys dropped and trailing data removed.
+///
+/// Two documents that decode to the same values under `profile` normalize to
+/// the same bytes, so the output is a stable form to hash or compare. String
+/// contents are copied byte for byte, and normalizing the output again leaves
+/// it unchanged.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded input
+/// * `profile` - How duplicates are resolved and which keys are dropped
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The normalized document
+/// * `Err(ParseError)` - If the input is malformed
+///
+/// # Example
+/// ```
+/// use bencode_lib::repair::{normalize, Profile};
+///
+/// let a = normalize(b"d1:yi02e1:t2:aa1:xi1ee", &Profile::new().with_excluded_keys(&[b"t"])).unwrap();
+/// let b = normalize(b"d1:t2:zz1:xi1e1:yi2ee", &Profile::new().with_excluded_keys(&[b"t"])).unwrap();
+/// assert_eq!(a, b"d1:xi1e1:yi2ee");
+/// assert_eq!(a, b);
+/// ```
+pub fn normalize(source: &[u8], profile: &Profile) -> Result<Vec<u8>, ParseError> {
+    let (root, _) = read_tree(source)?;
+    let config = RepairConfig::new().with_duplicate_keys(Some(profile.duplicate_keys));
+    let mut encoder = Encoder {
+        source,
+        config: &config,
+        excluded_keys: profile.excluded_keys,
+        out: Vec::with_capacity(source.len()),
+        changes: Vec::new(),
+    };
+    encoder.emit(&root);
+    Ok(encoder.out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::borrowed::validate_bencode;
+    use crate::parser::key_order::check_key_order;
+
+    #[test]
+    fn sorted_input_is_unchanged() {
+        let data = b"d4:infod6:lengthi1e4:name1:xe4:listli2eee";
+        assert_eq!(sort_keys(data).unwrap(), data);
+        assert_eq!(sort_keys(b"i-5e").unwrap(), b"i-5e");
+    }
+
+    #[test]
+    fn sorts_nested_dictionaries() {
+        let data = b"d1:bld1:zi1e1:yi2eee1:ad1:di1e1:ci2eee";
+        let sorted = sort_keys(data).unwrap();
+        assert_eq!(sorted, b"d1:ad1:ci2e1:di1ee1:bld1:yi2e1:zi1eeee");
+        assert_eq!(check_key_order(&sorted), Ok(()));
+        assert!(validate_bencode(&sorted).is_ok());
+    }
+
+    #[test]
+    fn values_are_byte_identical() {
+        // Binary string content and integer text are copied, not re-encoded
+        let data = b"d1:b3:\xff\x00\x011:ai007ee";
+        assert_eq!(sort_keys(data).unwrap(), b"d1:ai007e1:b3:\xff\x00\x01e");
+    }
+
+    #[test]
+    fn duplicate_keys_keep_input_order() {
+        assert_eq!(sort_keys(b"d1:bi1e1:ai2e1:ai3ee").unwrap(), b"d1:ai2e1:ai3e1:bi1ee");
+    }
+
+    #[test]
+    fn trailing_data_is_kept() {
+        assert_eq!(sort_keys(b"d1:bi1e1:ai2eeXY").unwrap(), b"d1:ai2e1:bi1eeXY");
+    }
+
+    #[test]
+    fn malformed_input_is_rejected() {
+        assert_eq!(sort_keys(b""), Err(ParseError::new(ERR_EMPTY_INPUT, 0)));
+        assert!(sort_keys(b"d1:bi1e1:ai2e").is_err());
+    }
+
+    #[test]
+    fn repair_valid_input_makes_no_changes() {
+        let data = b"d4:infod6:lengthi1e4:name1:xe4:listli-2eee";
+        let report = repair(data, &RepairConfig::new()).unwrap();
+        assert_eq!(report.output, data);
+        assert!(report.changes.is_empty());
+    }
+
+    #[test]
+    fn repair_strips_trailing_data() {
+        let report = repair(b"li1eeGARBAGE", &RepairConfig::new()).unwrap();
+        assert_eq!(report.output, b"li1ee");
+        assert_eq!(
+            report.changes,
+            vec![RepairChange::TrailingDataRemoved { offset: 5, length: 7 }]
+        );
+    }
+
+    #[test]
+    fn repair_removes_duplicates_keeping_first() {
+        let report = repair(b"d1:ai1e1:bi2e1:ai3ee", &RepairConfig::new()).unwrap();
+        assert_eq!(report.output, b"d1:ai1e1:bi2ee");
+        assert_eq!(
+            report.changes,
+            vec![RepairChange::DuplicateKeyRemoved {
+                offset: 13,
+                key: b"a".to_vec()
+            }]
+        );
+    }
+
+    #[test]
+    fn repair_removes_duplicates_keeping_last() {
+        let config = RepairConfig::new().with_duplicate_keys(Some(DuplicateKeyPolicy::KeepLast));
+        let report = repair(b"d1:ai1e1:bi2e1:ai3ee", &config).unwrap();
+        assert_eq!(report.output, b"d1:ai3e1:bi2ee");
+        assert_eq!(report.changes[0], RepairChange::DuplicateKeyRemoved { offset: 1, key: b"a".to_vec() });
+        assert_eq!(report.changes[1], RepairChange::KeysSorted { offset: 0 });
+    }
+
+    #[test]
+    fn repair_normalizes_integers() {
+        let report = repair(b"li007ei-01ei+5ei0ee", &RepairConfig::new()).unwrap();
+        assert_eq!(report.output, b"li7ei-1ei5ei0ee");
+        let offsets: Vec<usize> = report
+            .changes
+            .iter()
+            .map(|change| match change {
+                RepairChange::IntegerNormalized { offset, .. } => *offset,
+                _ => panic!("Unexpected change {}", change),
+            })
+            .collect();
+        assert_eq!(offsets, vec![1, 6, 11]);
+    }
+
+    #[test]
+    fn repair_fixes_can_be_disabled() {
+        let data = b"d1:bi01e1:ai2e1:ai3eeXX";
+        let report = repair(data, &RepairConfig::none()).unwrap();
+        assert_eq!(report.output, data);
+        assert!(report.changes.is_empty());
+    }
+
+    #[test]
+    fn repair_output_is_valid_bencode() {
+        let data = b"d1:zd1:yi01e1:xi2e1:xi3ee1:ali1ei02eeeTRAIL";
+        let report = repair(data, &RepairConfig::new()).unwrap();
+        assert_eq!(check_key_order(&report.output), Ok(()));
+        assert!(validate_bencode(&report.output).is_ok());
+        assert_eq!(report.changes.last().unwrap().to_string(), "removed 5 bytes of trailing data at offset 38");
+    }
+
+    #[test]
+    fn normalize_combines_every_fix() {
+        let data = b"d1:bli02ee1:ad1:zi1e1:yi2e1:zi3ee1:bi9eeJUNK";
+        assert_eq!(normalize(data, &Profile::new()).unwrap(), b"d1:ad1:yi2e1:zi1ee1:bli2eee");
+        let last = Profile::new().with_duplicate_keys(DuplicateKeyPolicy::KeepLast);
+        assert_eq!(normalize(data, &last).unwrap(), b"d1:ad1:yi2e1:zi3ee1:bi9ee");
+    }
+
+    #[test]
+    fn normalize_drops_excluded_keys_at_any_depth() {
+        let profile = Profile::new().with_excluded_keys(&[b"t", b"v"]);
+        let data = b"d1:ad1:t1:x1:yi1ee1:t2:ab1:v3:1.0e";
+        assert_eq!(normalize(data, &profile).unwrap(), b"d1:ad1:yi1eee");
+        // Duplicates are always resolved, so the output has unique keys
+        assert_eq!(normalize(b"d1:ai1e1:ai2ee", &Profile::new()).unwrap(), b"d1:ai1ee");
+    }
+
+    #[test]
+    fn normalize_is_idempotent() {
+        let profile = Profile::new().with_excluded_keys(&[b"t"]);
+        let once = normalize(b"d1:zi01e1:t0:1:ali1ed1:ci1e1:bi2eeee", &profile).unwrap();
+        assert_eq!(normalize(&once, &profile).unwrap(), once);
+        assert_eq!(check_key_order(&once), Ok(()));
+        assert!(normalize(b"li1e", &profile).is_err());
+    }
+}
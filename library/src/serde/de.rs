@@ -0,0 +1,392 @@
+//! Serde deserializer reading bencode from an [`ISource`].
+//!
+//! The mapping mirrors the serializer: integers also decode as booleans (`0` or
+//! `1`), byte strings as strings or byte arrays, and a missing dictionary entry
+//! as `None`. Dictionary keys must be in order, as the parsers require.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+use crate::error::messages::*;
+use crate::error::parse::ParseError;
+use crate::io::traits::ISource;
+use crate::parser::default::parse_string_bytes;
+
+/// Creates an error; the offset is filled in when it reaches the caller
+fn error(message: &str) -> ParseError {
+    ParseError::new(message, 0)
+}
+
+/// Source wrapper counting the bytes consumed, for error offsets
+struct Counted<'a> {
+    source: &'a mut dyn ISource,
+    position: usize,
+}
+
+impl ISource for Counted<'_> {
+    fn next(&mut self) {
+        self.source.next();
+        self.position += 1;
+    }
+
+    fn current(&mut self) -> Option<char> {
+        self.source.current()
+    }
+
+    fn more(&mut self) -> bool {
+        self.source.more()
+    }
+
+    fn reset(&mut self) {
+        self.source.reset();
+        self.position = 0;
+    }
+}
+
+/// Deserializes values from bencode read from a source.
+pub struct Deserializer<'a> {
+    source: Counted<'a>,
+    /// Number of containers enclosing the current value
+    depth: usize,
+    /// Where the current value appears, for unexpected character errors
+    expecting: Expecting,
+}
+
+impl<'a> Deserializer<'a> {
+    /// Creates a deserializer reading from `source`.
+    pub fn new(source: &'a mut dyn ISource) -> Self {
+        Self {
+            source: Counted { source, position: 0 },
+            depth: 0,
+            expecting: Expecting::Value,
+        }
+    }
+
+    /// Returns the number of bytes consumed so far
+    pub fn position(&self) -> usize {
+        self.source.position
+    }
+
+    /// Returns true if the source has input left after the values read so far
+    pub fn more(&mut self) -> bool {
+        self.source.more()
+    }
+
+    /// Returns the first character of the next value, failing at the end of input
+    fn peek(&mut self) -> Result<char, ParseError> {
+        self.source.current().ok_or_else(|| error(ERR_EMPTY_INPUT))
+    }
+
+    /// Builds the error for a character that cannot start a value
+    fn unexpected(&self, c: char) -> ParseError {
+        ParseError::new(unexpected_character(c, self.expecting, self.depth), 0)
+    }
+
+    /// Reads the text of an integer, `i<digits>e`
+    fn integer_text(&mut self) -> Result<String, ParseError> {
+        self.source.next(); // skip 'i'
+        let mut text = String::new();
+        while let Some(c) = self.source.current() {
+            self.source.next();
+            if c == 'e' {
+                if text == "-0" || text.starts_with('+') {
+                    return Err(error(ERR_INVALID_INTEGER));
+                }
+                return Ok(text);
+            }
+            text.push(c);
+        }
+        // Cut off: only text more digits could complete is unterminated
+        let viable = matches!(text.as_str(), "" | "-")
+            || (text != "-0" && !text.starts_with('+') && (text.parse::<i64>().is_ok() || text.parse::<u64>().is_ok()));
+        Err(error(if viable { ERR_UNTERMINATED_INTEGER } else { ERR_INVALID_INTEGER }))
+    }
+
+    /// Reads a byte string, `<length>:<bytes>`
+    fn bytes(&mut self) -> Result<Vec<u8>, ParseError> {
+        parse_string_bytes(&mut self.source).map_err(|message| error(&message))
+    }
+
+    /// Consumes the `e` closing a container once its visitor has finished
+    fn close(&mut self, unterminated: &str) -> Result<(), ParseError> {
+        match self.source.current() {
+            Some('e') => {
+                self.source.next();
+                self.depth -= 1;
+                Ok(())
+            }
+            Some(_) => Err(error(ERR_TOO_MANY_ENTRIES)),
+            None => Err(error(unterminated)),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'_> {
+    type Error = ParseError;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.peek()? {
+            'i' => {
+                let text = self.integer_text()?;
+                if let Ok(value) = text.parse::<i64>() {
+                    visitor.visit_i64(value)
+                } else if let Ok(value) = text.parse::<u64>() {
+                    visitor.visit_u64(value)
+                } else {
+                    Err(error(ERR_INVALID_INTEGER))
+                }
+            }
+            '0'..='9' => match String::from_utf8(self.bytes()?) {
+                Ok(text) => visitor.visit_string(text),
+                Err(binary) => visitor.visit_byte_buf(binary.into_bytes()),
+            },
+            ':' => Err(error(ERR_INVALID_STRING_LENGTH)),
+            'l' => {
+                self.source.next();
+                self.depth += 1;
+                let value = visitor.visit_seq(List { de: &mut *self })?;
+                self.close(ERR_UNTERMINATED_LIST)?;
+                Ok(value)
+            }
+            'd' => {
+                self.source.next();
+                self.depth += 1;
+                let value = visitor.visit_map(Dictionary { de: &mut *self, last_key: None })?;
+                self.close(ERR_UNTERMINATED_DICTIONARY)?;
+                Ok(value)
+            }
+            c => Err(self.unexpected(c)),
+        }
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        if self.peek()? != 'i' {
+            return self.deserialize_any(visitor);
+        }
+        match self.integer_text()?.as_str() {
+            "0" => visitor.visit_bool(false),
+            "1" => visitor.visit_bool(true),
+            _ => Err(error(ERR_INVALID_BOOLEAN)),
+        }
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, ParseError> {
+        Err(error(ERR_UNSUPPORTED_TYPE))
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, _visitor: V) -> Result<V::Value, ParseError> {
+        Err(error(ERR_UNSUPPORTED_TYPE))
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        self.deserialize_byte_buf(visitor)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match self.peek()? {
+            '0'..='9' => visitor.visit_byte_buf(self.bytes()?),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        // Bencode has no null; absent values are missing dictionary entries
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _name: &'static str, visitor: V) -> Result<V::Value, ParseError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParseError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParseError> {
+        match self.peek()? {
+            '0'..='9' => {
+                let name = String::from_utf8_lossy(&self.bytes()?).into_owned();
+                visitor.visit_enum(name.into_deserializer())
+            }
+            'd' => {
+                self.source.next();
+                self.depth += 1;
+                let value = visitor.visit_enum(Variant { de: &mut *self })?;
+                self.close(ERR_UNTERMINATED_DICTIONARY)?;
+                Ok(value)
+            }
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 char str string
+        seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+/// Reads the elements of a list up to (not including) its `e`
+struct List<'b, 'a> {
+    de: &'b mut Deserializer<'a>,
+}
+
+impl<'de> de::SeqAccess<'de> for List<'_, '_> {
+    type Error = ParseError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>, ParseError> {
+        match self.de.source.current() {
+            Some('e') => Ok(None),
+            Some(_) => {
+                self.de.expecting = Expecting::ListItem;
+                seed.deserialize(&mut *self.de).map(Some)
+            }
+            None => Err(error(ERR_UNTERMINATED_LIST)),
+        }
+    }
+}
+
+/// Reads the entries of a dictionary up to (not including) its `e`
+struct Dictionary<'b, 'a> {
+    de: &'b mut Deserializer<'a>,
+    last_key: Option<Vec<u8>>,
+}
+
+/// Reads a dictionary key, checking it follows the previous key
+fn read_key(de: &mut Deserializer<'_>, last_key: &mut Option<Vec<u8>>) -> Result<Vec<u8>, ParseError> {
+    if !de.peek()?.is_ascii_digit() {
+        return Err(error(ERR_DICT_KEY_MUST_BE_STRING));
+    }
+    let key = de.bytes()?;
+    if last_key.as_ref().is_some_and(|last| key <= *last) {
+        return Err(error(ERR_DICT_KEYS_ORDER));
+    }
+    *last_key = Some(key.clone());
+    Ok(key)
+}
+
+impl<'de> de::MapAccess<'de> for Dictionary<'_, '_> {
+    type Error = ParseError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>, ParseError> {
+        match self.de.source.current() {
+            Some('e') => Ok(None),
+            Some(_) => {
+                let key = read_key(self.de, &mut self.last_key)?;
+                seed.deserialize(Key(key)).map(Some)
+            }
+            None => Err(error(ERR_UNTERMINATED_DICTIONARY)),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, ParseError> {
+        self.de.expecting = Expecting::DictValue;
+        seed.deserialize(&mut *self.de)
+    }
+}
+
+/// Reads an enum variant encoded as a single entry dictionary
+struct Variant<'b, 'a> {
+    de: &'b mut Deserializer<'a>,
+}
+
+impl<'de> de::EnumAccess<'de> for Variant<'_, '_> {
+    type Error = ParseError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), ParseError> {
+        let key = read_key(self.de, &mut None)?;
+        let value = seed.deserialize(Key(key))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de> de::VariantAccess<'de> for Variant<'_, '_> {
+    type Error = ParseError;
+
+    fn unit_variant(self) -> Result<(), ParseError> {
+        Err(de::Error::invalid_type(de::Unexpected::Map, &"unit variant"))
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, ParseError> {
+        self.de.expecting = Expecting::DictValue;
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, ParseError> {
+        de::Deserializer::deserialize_seq(&mut *self.de, visitor)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParseError> {
+        de::Deserializer::deserialize_map(&mut *self.de, visitor)
+    }
+}
+
+/// Deserializer for a dictionary key: a string if valid UTF-8, otherwise bytes
+struct Key(Vec<u8>);
+
+impl<'de> de::Deserializer<'de> for Key {
+    type Error = ParseError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, ParseError> {
+        match String::from_utf8(self.0) {
+            Ok(text) => visitor.visit_string(text),
+            Err(binary) => visitor.visit_byte_buf(binary.into_bytes()),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, ParseError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, ParseError> {
+        visitor.visit_enum(String::from_utf8_lossy(&self.0).into_owned().into_deserializer())
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl de::Error for ParseError {
+    fn custom<T: core::fmt::Display>(message: T) -> Self {
+        ParseError::new(message.to_string(), 0)
+    }
+}
@@ -0,0 +1,271 @@
+//! Serde support: encode and decode user types directly as bencode.
+//!
+//! [`Serializer`] writes to any [`IDestination`] and [`Deserializer`] reads from
+//! any [`ISource`], so derived types go straight to and from bencode without
+//! building a [`Node`](crate::Node) tree. Errors are [`ParseError`]s whose offset
+//! is the position in the input (decoding) or output (encoding) at which the
+//! problem was found.
+//!
+//! Bencode has no floating point or null values: floats are rejected, and `None`
+//! and `()` encode as nothing, so an `Option` struct field that is `None` is left
+//! out of its dictionary (and read back as `None` when missing).
+//!
+//! # Example
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! struct Peer {
+//!     ip: String,
+//!     port: u16,
+//!     id: Option<String>,
+//! }
+//!
+//! let peer = Peer { ip: "10.0.0.1".into(), port: 6881, id: None };
+//! let encoded = bencode_lib::serde::to_bytes(&peer).unwrap();
+//! assert_eq!(encoded, b"d2:ip8:10.0.0.14:porti6881ee");
+//! assert_eq!(bencode_lib::serde::from_bytes::<Peer>(&encoded).unwrap(), peer);
+//! ```
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use ::serde::Serialize;
+use ::serde::de::DeserializeOwned;
+
+use crate::error::messages::{ERR_TRAILING_DATA, ERR_UNSUPPORTED_TYPE};
+use crate::error::parse::ParseError;
+use crate::io::destinations::buffer::Buffer as BufferDestination;
+use crate::io::sources::buffer::Buffer as BufferSource;
+use crate::io::traits::{IDestination, ISource};
+
+/// Serde deserializer over a source
+pub mod de;
+/// Serde serializer over a destination
+pub mod ser;
+
+pub use de::Deserializer;
+pub use ser::Serializer;
+
+#[cfg(not(feature = "std"))]
+impl ::serde::ser::StdError for ParseError {}
+
+/// Encodes a value as bencode, writing it to a destination.
+///
+/// # Arguments
+/// * `value` - The value to encode
+/// * `destination` - Where to write the encoding
+///
+/// # Returns
+/// * `Ok(())` - The value was written
+/// * `Err(ParseError)` - If the value holds a type bencode cannot represent, a
+///   map key that is not a string, or a top level value that encodes to nothing
+pub fn to_destination<T: ?Sized + Serialize>(value: &T, destination: &mut dyn IDestination) -> Result<(), ParseError> {
+    let mut serializer = Serializer::new(destination);
+    let result = value.serialize(&mut serializer);
+    let written = serializer.written();
+    match result {
+        Ok(()) if written == 0 => Err(ParseError::new(ERR_UNSUPPORTED_TYPE, 0)),
+        Ok(()) => Ok(()),
+        Err(error) => Err(ParseError { offset: written, ..error }),
+    }
+}
+
+/// Encodes a value as bencode bytes.
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The encoding
+/// * `Err(ParseError)` - As for [`to_destination`]
+pub fn to_bytes<T: ?Sized + Serialize>(value: &T) -> Result<Vec<u8>, ParseError> {
+    let mut destination = BufferDestination::new();
+    to_destination(value, &mut destination)?;
+    Ok(destination.into_bytes())
+}
+
+/// Decodes one value from a source, leaving any input after it unread.
+///
+/// # Arguments
+/// * `source` - The source positioned at the start of the value
+///
+/// # Returns
+/// * `Ok(T)` - The decoded value
+/// * `Err(ParseError)` - If the input is malformed or does not match `T`
+pub fn from_source<T: DeserializeOwned>(source: &mut dyn ISource) -> Result<T, ParseError> {
+    let mut deserializer = Deserializer::new(source);
+    T::deserialize(&mut deserializer).map_err(|error| ParseError {
+        offset: deserializer.position(),
+        ..error
+    })
+}
+
+/// Decodes a value from bencode bytes, which must hold exactly one value.
+///
+/// # Returns
+/// * `Ok(T)` - The decoded value
+/// * `Err(ParseError)` - If the input is malformed, does not match `T` or has
+///   trailing data
+pub fn from_bytes<T: DeserializeOwned>(data: &[u8]) -> Result<T, ParseError> {
+    let mut source = BufferSource::new(data);
+    let mut deserializer = Deserializer::new(&mut source);
+    let value = T::deserialize(&mut deserializer).map_err(|error| ParseError {
+        offset: deserializer.position(),
+        ..error
+    })?;
+    if deserializer.more() {
+        return Err(ParseError::new(ERR_TRAILING_DATA, deserializer.position()));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+    use crate::HashMap;
+    use ::serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct File {
+        length: u64,
+        path: Vec<String>,
+        md5sum: Option<String>,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Info {
+        name: String,
+        #[serde(rename = "piece length")]
+        piece_length: u32,
+        #[serde(with = "bytes")]
+        pieces: Vec<u8>,
+        private: bool,
+        files: Vec<File>,
+    }
+
+    /// Encodes a byte vector as a byte string rather than a list of integers
+    mod bytes {
+        use ::serde::{Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.serialize_bytes(bytes)
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+            struct Bytes;
+            impl ::serde::de::Visitor<'_> for Bytes {
+                type Value = Vec<u8>;
+                fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                    f.write_str("a byte string")
+                }
+                fn visit_byte_buf<E>(self, bytes: Vec<u8>) -> Result<Vec<u8>, E> {
+                    Ok(bytes)
+                }
+                fn visit_str<E>(self, text: &str) -> Result<Vec<u8>, E> {
+                    Ok(text.as_bytes().to_vec())
+                }
+            }
+            deserializer.deserialize_byte_buf(Bytes)
+        }
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    enum Message {
+        Choke,
+        Have(u32),
+        Request(u32, u32, u32),
+        Piece { index: u32, begin: u32 },
+    }
+
+    fn info() -> Info {
+        Info {
+            name: "demo".into(),
+            piece_length: 16384,
+            pieces: vec![0xff, 0x00, b':', 0x80],
+            private: true,
+            files: vec![
+                File { length: 3, path: vec!["a".into(), "b".into()], md5sum: None },
+                File { length: 5, path: vec!["c".into()], md5sum: Some("x".into()) },
+            ],
+        }
+    }
+
+    #[test]
+    fn structs_encode_as_sorted_dictionaries() {
+        let encoded = to_bytes(&info()).unwrap();
+        let mut expected = b"d5:filesld6:lengthi3e4:pathl1:a1:beed6:lengthi5e6:md5sum1:x4:pathl1:cee".to_vec();
+        expected.extend(b"e4:name4:demo12:piece lengthi16384e6:pieces4:\xff\x00:\x807:privatei1ee");
+        assert_eq!(encoded, expected);
+        assert_eq!(from_bytes::<Info>(&encoded).unwrap(), info());
+    }
+
+    #[test]
+    fn encoding_matches_the_node_parser() {
+        let encoded = to_bytes(&info()).unwrap();
+        let node = crate::parse_bytes(&encoded).unwrap();
+        let mut destination = BufferDestination::new();
+        crate::stringify(&node, &mut destination).unwrap();
+        assert_eq!(destination.buffer, encoded);
+    }
+
+    #[test]
+    fn enums_are_externally_tagged() {
+        let messages = vec![
+            Message::Choke,
+            Message::Have(7),
+            Message::Request(1, 2, 3),
+            Message::Piece { index: 4, begin: 5 },
+        ];
+        let encoded = to_bytes(&messages).unwrap();
+        assert_eq!(
+            encoded,
+            b"l5:Choked4:Havei7eed7:Requestli1ei2ei3eeed5:Pieced5:begini5e5:indexi4eeee".to_vec()
+        );
+        assert_eq!(from_bytes::<Vec<Message>>(&encoded).unwrap(), messages);
+    }
+
+    #[test]
+    fn maps_and_tuples_round_trip() {
+        let mut map = HashMap::new();
+        map.insert("zeta".to_string(), (1i64, "one".to_string()));
+        map.insert("alpha".to_string(), (-2i64, "two".to_string()));
+        let encoded = to_bytes(&map).unwrap();
+        assert_eq!(encoded, b"d5:alphali-2e3:twoe4:zetali1e3:oneee".to_vec());
+        assert_eq!(from_bytes::<HashMap<String, (i64, String)>>(&encoded).unwrap(), map);
+        assert_eq!(from_bytes::<u64>(b"i18446744073709551615e").unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn unsupported_values_are_rejected() {
+        assert_eq!(to_bytes(&1.5f64).unwrap_err().kind(), ErrorKind::UnsupportedType);
+        assert_eq!(to_bytes(&None::<u8>).unwrap_err().kind(), ErrorKind::UnsupportedType);
+        let error = to_bytes(&vec![Some(1), None]).unwrap_err();
+        assert_eq!((error.kind(), error.offset), (ErrorKind::UnsupportedType, 4));
+        let mut map = HashMap::new();
+        map.insert(1, 2);
+        assert_eq!(to_bytes(&map).unwrap_err().kind(), ErrorKind::DictKeyMustBeString);
+    }
+
+    #[test]
+    fn malformed_input_reports_the_offset() {
+        let error = from_bytes::<Vec<u32>>(b"li1ei2x").unwrap_err();
+        assert_eq!((error.kind(), error.offset), (ErrorKind::InvalidInteger, 7));
+        let error = from_bytes::<Vec<u32>>(b"li1ei2").unwrap_err();
+        assert!(error.is_incomplete());
+        assert_eq!((error.kind(), error.offset), (ErrorKind::UnterminatedInteger, 6));
+        let error = from_bytes::<HashMap<String, u8>>(b"d1:bi1e1:ai2ee").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::DictKeysOrder);
+        assert_eq!(from_bytes::<bool>(b"i2e").unwrap_err().kind(), ErrorKind::InvalidBoolean);
+        assert_eq!(from_bytes::<u8>(b"i+1e").unwrap_err().kind(), ErrorKind::InvalidInteger);
+        assert_eq!(from_bytes::<(u8,)>(b"li1ei2ee").unwrap_err().kind(), ErrorKind::TooManyEntries);
+        assert_eq!(from_bytes::<u8>(b"i1ei2e").unwrap_err().kind(), ErrorKind::TrailingData);
+        let error = from_bytes::<u8>(b"3:abc").unwrap_err();
+        assert_eq!((error.kind(), error.offset), (ErrorKind::Other, 5));
+    }
+
+    #[test]
+    fn from_source_reads_one_value_at_a_time() {
+        let mut source = BufferSource::new(b"i1e4:spam");
+        assert_eq!(from_source::<u8>(&mut source).unwrap(), 1);
+        assert_eq!(from_source::<String>(&mut source).unwrap(), "spam");
+    }
+}
@@ -0,0 +1,427 @@
+//! Serde serializer writing bencode to an [`IDestination`].
+//!
+//! Integers and booleans become bencode integers, strings, chars and byte
+//! arrays become byte strings, sequences and tuples become lists, and maps and
+//! structs become dictionaries with their keys sorted. Enum variants follow the
+//! externally tagged convention: a unit variant is its name, any other variant a
+//! single entry dictionary from the name to its content. `None` and `()` produce
+//! nothing, so dictionary entries holding them are omitted.
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, string::ToString, vec::Vec};
+
+use serde::ser::{self, Serialize};
+
+use crate::error::messages::{ERR_DICT_KEY_MUST_BE_STRING, ERR_DUPLICATE_KEY, ERR_UNSUPPORTED_TYPE};
+use crate::error::parse::ParseError;
+use crate::io::destinations::buffer::Buffer;
+use crate::io::traits::IDestination;
+
+/// Creates an error; the offset is filled in when it reaches the caller
+fn error(message: &str) -> ParseError {
+    ParseError::new(message, 0)
+}
+
+/// Serializes values as bencode to a destination.
+pub struct Serializer<'a> {
+    destination: &'a mut dyn IDestination,
+    written: usize,
+}
+
+impl<'a> Serializer<'a> {
+    /// Creates a serializer writing to `destination`.
+    pub fn new(destination: &'a mut dyn IDestination) -> Self {
+        Self { destination, written: 0 }
+    }
+
+    /// Returns the number of bytes written so far
+    pub fn written(&self) -> usize {
+        self.written
+    }
+
+    /// Writes bytes verbatim
+    fn raw(&mut self, bytes: &[u8]) {
+        self.destination.add_raw(bytes);
+        self.written += bytes.len();
+    }
+
+    /// Writes a byte string as `<length>:<bytes>`
+    fn string(&mut self, bytes: &[u8]) {
+        self.raw(format!("{}:", bytes.len()).as_bytes());
+        self.raw(bytes);
+    }
+
+    /// Writes an integer as `i<value>e`
+    fn integer(&mut self, value: impl core::fmt::Display) {
+        self.raw(format!("i{}e", value).as_bytes());
+    }
+
+    /// Writes the opening of a variant with content: `d<name>`
+    fn variant(&mut self, name: &str) {
+        self.raw(b"d");
+        self.string(name.as_bytes());
+    }
+}
+
+/// Serializes a value on its own, returning its encoding
+fn encode<T: ?Sized + Serialize>(value: &T) -> Result<Vec<u8>, ParseError> {
+    let mut buffer = Buffer::new();
+    value.serialize(&mut Serializer::new(&mut buffer))?;
+    Ok(buffer.into_bytes())
+}
+
+impl<'b, 'a> ser::Serializer for &'b mut Serializer<'a> {
+    type Ok = ();
+    type Error = ParseError;
+    type SerializeSeq = ListSerializer<'b, 'a>;
+    type SerializeTuple = ListSerializer<'b, 'a>;
+    type SerializeTupleStruct = ListSerializer<'b, 'a>;
+    type SerializeTupleVariant = ListSerializer<'b, 'a>;
+    type SerializeMap = DictionarySerializer<'b, 'a>;
+    type SerializeStruct = DictionarySerializer<'b, 'a>;
+    type SerializeStructVariant = DictionarySerializer<'b, 'a>;
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+
+    fn serialize_bool(self, value: bool) -> Result<(), ParseError> {
+        self.integer(value as u8);
+        Ok(())
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<(), ParseError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<(), ParseError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<(), ParseError> {
+        self.serialize_i64(value as i64)
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<(), ParseError> {
+        self.integer(value);
+        Ok(())
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<(), ParseError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<(), ParseError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<(), ParseError> {
+        self.serialize_u64(value as u64)
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<(), ParseError> {
+        self.integer(value);
+        Ok(())
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result<(), ParseError> {
+        Err(error(ERR_UNSUPPORTED_TYPE))
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result<(), ParseError> {
+        Err(error(ERR_UNSUPPORTED_TYPE))
+    }
+
+    fn serialize_char(self, value: char) -> Result<(), ParseError> {
+        self.string(value.encode_utf8(&mut [0; 4]).as_bytes());
+        Ok(())
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), ParseError> {
+        self.string(value.as_bytes());
+        Ok(())
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<(), ParseError> {
+        self.string(value);
+        Ok(())
+    }
+
+    fn serialize_none(self) -> Result<(), ParseError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), ParseError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), ParseError> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), ParseError> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), ParseError> {
+        self.serialize_str(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _name: &'static str, value: &T) -> Result<(), ParseError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), ParseError> {
+        self.variant(variant);
+        let start = self.written;
+        value.serialize(&mut *self)?;
+        if self.written == start {
+            return Err(error(ERR_UNSUPPORTED_TYPE));
+        }
+        self.raw(b"e");
+        Ok(())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<ListSerializer<'b, 'a>, ParseError> {
+        self.raw(b"l");
+        Ok(ListSerializer { serializer: self, variant: false })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<ListSerializer<'b, 'a>, ParseError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<ListSerializer<'b, 'a>, ParseError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<ListSerializer<'b, 'a>, ParseError> {
+        self.variant(variant);
+        self.raw(b"l");
+        Ok(ListSerializer { serializer: self, variant: true })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<DictionarySerializer<'b, 'a>, ParseError> {
+        Ok(DictionarySerializer {
+            serializer: self,
+            entries: Vec::new(),
+            key: None,
+            variant: false,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<DictionarySerializer<'b, 'a>, ParseError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<DictionarySerializer<'b, 'a>, ParseError> {
+        self.variant(variant);
+        Ok(DictionarySerializer {
+            serializer: self,
+            entries: Vec::new(),
+            key: None,
+            variant: true,
+        })
+    }
+}
+
+/// Writes list elements straight to the destination.
+pub struct ListSerializer<'b, 'a> {
+    serializer: &'b mut Serializer<'a>,
+    /// The list is the content of an enum variant, so a second `e` closes it
+    variant: bool,
+}
+
+impl ListSerializer<'_, '_> {
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), ParseError> {
+        // An element that writes nothing (None or unit) cannot be held in a list
+        let start = self.serializer.written;
+        value.serialize(&mut *self.serializer)?;
+        if self.serializer.written == start {
+            return Err(error(ERR_UNSUPPORTED_TYPE));
+        }
+        Ok(())
+    }
+
+    fn close(self) -> Result<(), ParseError> {
+        self.serializer.raw(if self.variant { b"ee" } else { b"e" });
+        Ok(())
+    }
+}
+
+impl ser::SerializeSeq for ListSerializer<'_, '_> {
+    type Ok = ();
+    type Error = ParseError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), ParseError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), ParseError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTuple for ListSerializer<'_, '_> {
+    type Ok = ();
+    type Error = ParseError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), ParseError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), ParseError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTupleStruct for ListSerializer<'_, '_> {
+    type Ok = ();
+    type Error = ParseError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), ParseError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), ParseError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeTupleVariant for ListSerializer<'_, '_> {
+    type Ok = ();
+    type Error = ParseError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), ParseError> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), ParseError> {
+        self.close()
+    }
+}
+
+/// Collects dictionary entries so they can be written in key order.
+pub struct DictionarySerializer<'b, 'a> {
+    serializer: &'b mut Serializer<'a>,
+    /// Raw key and encoded value of each entry
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+    /// Key waiting for its value (maps serialize the two separately)
+    key: Option<Vec<u8>>,
+    /// The dictionary is the content of an enum variant, so a second `e` closes it
+    variant: bool,
+}
+
+impl DictionarySerializer<'_, '_> {
+    /// Adds an entry, dropping it if the value encodes to nothing (None or unit)
+    fn entry(&mut self, key: Vec<u8>, value: Vec<u8>) {
+        if !value.is_empty() {
+            self.entries.push((key, value));
+        }
+    }
+
+    fn close(mut self) -> Result<(), ParseError> {
+        self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+        if self.entries.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return Err(error(ERR_DUPLICATE_KEY));
+        }
+        self.serializer.raw(b"d");
+        for (key, value) in &self.entries {
+            self.serializer.string(key);
+            self.serializer.raw(value);
+        }
+        self.serializer.raw(if self.variant { b"ee" } else { b"e" });
+        Ok(())
+    }
+}
+
+/// Serializes a map key, which must encode to a bencode string, returning its bytes
+fn key_bytes<T: ?Sized + Serialize>(key: &T) -> Result<Vec<u8>, ParseError> {
+    let encoded = encode(key)?;
+    let separator = encoded.iter().position(|&b| b == b':');
+    match (encoded.first(), separator) {
+        (Some(b'0'..=b'9'), Some(separator)) => Ok(encoded[separator + 1..].to_vec()),
+        _ => Err(error(ERR_DICT_KEY_MUST_BE_STRING)),
+    }
+}
+
+impl ser::SerializeMap for DictionarySerializer<'_, '_> {
+    type Ok = ();
+    type Error = ParseError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), ParseError> {
+        self.key = Some(key_bytes(key)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), ParseError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| <ParseError as ser::Error>::custom("serialize_value called before serialize_key"))?;
+        let value = encode(value)?;
+        self.entry(key, value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), ParseError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeStruct for DictionarySerializer<'_, '_> {
+    type Ok = ();
+    type Error = ParseError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), ParseError> {
+        let value = encode(value)?;
+        self.entry(key.as_bytes().to_vec(), value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), ParseError> {
+        self.close()
+    }
+}
+
+impl ser::SerializeStructVariant for DictionarySerializer<'_, '_> {
+    type Ok = ();
+    type Error = ParseError;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), ParseError> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<(), ParseError> {
+        self.close()
+    }
+}
+
+impl ser::Error for ParseError {
+    fn custom<T: core::fmt::Display>(message: T) -> Self {
+        ParseError::new(message.to_string(), 0)
+    }
+}
@@ -0,0 +1,416 @@
+//! Single-file archives of keyed bencode records.
+//!
+//! Storing millions of small records (such as DHT entries) one per file costs
+//! far more in filesystem overhead than the records themselves. An archive packs
+//! them into one file: the values concatenated, followed by a sorted index of
+//! keys and offsets, so any record can be read with one seek.
+//!
+//! Layout (integers little endian):
+//!
+//! ```text
+//! "BENCARC1"                                   magic
+//! value 0 | value 1 | ...                      encoded records
+//! count x (u32 key length, key, u64 offset, u64 length)   index, sorted by key
+//! u64 index offset | u32 count | "BENCARC1"    footer
+//! ```
+//!
+//! The digit ending the magic is the format version. Archives are meant to be
+//! kept across upgrades: [`Archive::open`] reads every version an earlier release
+//! wrote and rejects a newer one with `ERR_UNSUPPORTED_FORMAT_VERSION` instead
+//! of misreading it. Only version 1 exists so far.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::messages::{ERR_DUPLICATE_ARCHIVE_KEY, ERR_INVALID_ARCHIVE, ERR_UNSUPPORTED_FORMAT_VERSION};
+use crate::parser::borrowed::validate_bencode;
+use crate::torrent::is_unsafe_segment;
+
+/// Format version written by [`ArchiveWriter`]
+pub const ARCHIVE_FORMAT_VERSION: u8 = 1;
+/// Marks the start and end of an archive file: the prefix and the version digit
+const MAGIC: &[u8; 8] = b"BENCARC1";
+/// Part of the magic shared by every format version
+const MAGIC_PREFIX: &[u8; 7] = b"BENCARC";
+/// Size of the footer: index offset, record count and magic
+const FOOTER_LENGTH: u64 = 8 + 4 + 8;
+
+/// Location of one record in an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Entry {
+    key: String,
+    offset: u64,
+    length: u64,
+}
+
+/// Writes records to a new archive file.
+///
+/// # Example
+/// ```
+/// use bencode_lib::storage::archive::{Archive, ArchiveWriter};
+///
+/// let path = std::env::temp_dir().join("doc_archive_example.bar");
+/// let mut writer = ArchiveWriter::create(&path).unwrap();
+/// writer.add("b", b"i2e").unwrap();
+/// writer.add("a", b"4:spam").unwrap();
+/// writer.finish().unwrap();
+///
+/// let mut archive = Archive::open(&path).unwrap();
+/// assert_eq!(archive.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+/// assert_eq!(archive.get("a").unwrap(), Some(b"4:spam".to_vec()));
+/// std::fs::remove_file(&path).unwrap();
+/// ```
+pub struct ArchiveWriter {
+    file: BufWriter<File>,
+    index: Vec<Entry>,
+    offset: u64,
+}
+
+impl ArchiveWriter {
+    /// Creates (or truncates) an archive file.
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let mut file = BufWriter::new(File::create(path).map_err(|e| e.to_string())?);
+        file.write_all(MAGIC).map_err(|e| e.to_string())?;
+        Ok(Self {
+            file,
+            index: Vec::new(),
+            offset: MAGIC.len() as u64,
+        })
+    }
+
+    /// Appends a record.
+    ///
+    /// # Arguments
+    /// * `key` - Name of the record (must be unique within the archive)
+    /// * `value` - Exactly one complete bencode value
+    ///
+    /// # Returns
+    /// * `Ok(())` - The record was written
+    /// * `Err(String)` - If the value is not valid bencode or on an I/O error
+    pub fn add(&mut self, key: &str, value: &[u8]) -> Result<(), String> {
+        validate_bencode(value)?;
+        self.file.write_all(value).map_err(|e| e.to_string())?;
+        self.index.push(Entry {
+            key: key.to_string(),
+            offset: self.offset,
+            length: value.len() as u64,
+        });
+        self.offset += value.len() as u64;
+        Ok(())
+    }
+
+    /// Returns the number of records added so far
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if no records have been added
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Writes the index and footer, completing the archive.
+    ///
+    /// # Returns
+    /// * `Ok(usize)` - The number of records in the archive
+    /// * `Err(String)` - If two records share a key or on an I/O error
+    pub fn finish(mut self) -> Result<usize, String> {
+        self.index.sort_by(|a, b| a.key.cmp(&b.key));
+        if self.index.windows(2).any(|pair| pair[0].key == pair[1].key) {
+            return Err(ERR_DUPLICATE_ARCHIVE_KEY.to_string());
+        }
+        let count = u32::try_from(self.index.len()).map_err(|_| ERR_INVALID_ARCHIVE.to_string())?;
+        let write = |file: &mut BufWriter<File>, bytes: &[u8]| file.write_all(bytes).map_err(|e| e.to_string());
+        for entry in &self.index {
+            write(&mut self.file, &(entry.key.len() as u32).to_le_bytes())?;
+            write(&mut self.file, entry.key.as_bytes())?;
+            write(&mut self.file, &entry.offset.to_le_bytes())?;
+            write(&mut self.file, &entry.length.to_le_bytes())?;
+        }
+        write(&mut self.file, &self.offset.to_le_bytes())?;
+        write(&mut self.file, &count.to_le_bytes())?;
+        write(&mut self.file, MAGIC)?;
+        self.file.flush().map_err(|e| e.to_string())?;
+        Ok(self.index.len())
+    }
+}
+
+/// Reads fixed size little endian fields from the index
+fn read_array<const N: usize>(reader: &mut impl Read) -> Result<[u8; N], String> {
+    let mut bytes = [0; N];
+    reader.read_exact(&mut bytes).map_err(|_| ERR_INVALID_ARCHIVE.to_string())?;
+    Ok(bytes)
+}
+
+/// Checks the magic at either end of an archive is that of a version this library reads
+fn check_magic(magic: &[u8; 8]) -> Result<(), String> {
+    let (version, prefix) = magic.split_last().unwrap_or((&0, &[]));
+    if prefix != MAGIC_PREFIX || !version.is_ascii_digit() {
+        return Err(ERR_INVALID_ARCHIVE.to_string());
+    }
+    match version - b'0' {
+        ARCHIVE_FORMAT_VERSION => Ok(()),
+        _ => Err(ERR_UNSUPPORTED_FORMAT_VERSION.to_string()),
+    }
+}
+
+/// An open archive; only the index is held in memory.
+pub struct Archive {
+    file: File,
+    index: Vec<Entry>,
+}
+
+impl Archive {
+    /// Opens an archive and reads its index.
+    ///
+    /// # Returns
+    /// * `Ok(Archive)` - The archive
+    /// * `Err(String)` - If the file cannot be read or is not a well formed archive
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let invalid = || ERR_INVALID_ARCHIVE.to_string();
+        let mut file = File::open(path).map_err(|e| e.to_string())?;
+        let size = file.metadata().map_err(|e| e.to_string())?.len();
+        if size < MAGIC.len() as u64 + FOOTER_LENGTH {
+            return Err(invalid());
+        }
+        check_magic(&read_array(&mut file)?)?;
+        file.seek(SeekFrom::End(-(FOOTER_LENGTH as i64))).map_err(|e| e.to_string())?;
+        let index_offset = u64::from_le_bytes(read_array(&mut file)?);
+        let count = u32::from_le_bytes(read_array(&mut file)?);
+        if read_array::<8>(&mut file)? != *MAGIC || index_offset > size - FOOTER_LENGTH {
+            return Err(invalid());
+        }
+
+        file.seek(SeekFrom::Start(index_offset)).map_err(|e| e.to_string())?;
+        let mut reader = BufReader::new(&mut file).take(size - FOOTER_LENGTH - index_offset);
+        let mut index = Vec::with_capacity(count.min(1 << 20) as usize);
+        for _ in 0..count {
+            let key_length = u32::from_le_bytes(read_array(&mut reader)?) as u64;
+            let mut key = Vec::new();
+            (&mut reader).take(key_length).read_to_end(&mut key).map_err(|e| e.to_string())?;
+            let key = String::from_utf8(key).map_err(|_| invalid())?;
+            let offset = u64::from_le_bytes(read_array(&mut reader)?);
+            let length = u64::from_le_bytes(read_array(&mut reader)?);
+            if offset < MAGIC.len() as u64 || offset.checked_add(length).is_none_or(|end| end > index_offset) {
+                return Err(invalid());
+            }
+            index.push(Entry { key, offset, length });
+        }
+        if index.windows(2).any(|pair| pair[0].key >= pair[1].key) {
+            return Err(invalid());
+        }
+        Ok(Self { file, index })
+    }
+
+    /// Returns the number of records
+    pub fn len(&self) -> usize {
+        self.index.len()
+    }
+
+    /// Returns true if the archive holds no records
+    pub fn is_empty(&self) -> bool {
+        self.index.is_empty()
+    }
+
+    /// Returns the record keys in sorted order
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.index.iter().map(|entry| entry.key.as_str())
+    }
+
+    /// Returns true if a record with the key exists
+    pub fn contains(&self, key: &str) -> bool {
+        self.find(key).is_some()
+    }
+
+    /// Finds the index entry for a key
+    fn find(&self, key: &str) -> Option<&Entry> {
+        self.index
+            .binary_search_by(|entry| entry.key.as_str().cmp(key))
+            .ok()
+            .map(|position| &self.index[position])
+    }
+
+    /// Reads the record stored under a key.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Vec<u8>))` - The encoded record
+    /// * `Ok(None)` - No record has that key
+    /// * `Err(String)` - On an I/O error
+    pub fn get(&mut self, key: &str) -> Result<Option<Vec<u8>>, String> {
+        let Some(entry) = self.find(key).cloned() else {
+            return Ok(None);
+        };
+        self.read(&entry).map(Some)
+    }
+
+    /// Reads a record's bytes
+    fn read(&mut self, entry: &Entry) -> Result<Vec<u8>, String> {
+        let mut value = vec![0; entry.length as usize];
+        self.file.seek(SeekFrom::Start(entry.offset)).map_err(|e| e.to_string())?;
+        self.file.read_exact(&mut value).map_err(|e| e.to_string())?;
+        Ok(value)
+    }
+
+    /// Calls `handler` with every record in key order, stopping at the first error.
+    pub fn for_each<F>(&mut self, mut handler: F) -> Result<(), String>
+    where
+        F: FnMut(&str, Vec<u8>) -> Result<(), String>,
+    {
+        for position in 0..self.index.len() {
+            let entry = self.index[position].clone();
+            let value = self.read(&entry)?;
+            handler(&entry.key, value)?;
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of exporting a directory into an archive
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExportSummary {
+    /// Number of records written
+    pub records: usize,
+    /// Files that were not archived, with the reason
+    pub skipped: Vec<(PathBuf, String)>,
+}
+
+/// Packs every file with the given extension in `dir` into a new archive.
+///
+/// Each record is keyed by its file name without the extension. Files that are
+/// not valid bencode, or whose names are not UTF-8, are skipped and reported.
+///
+/// # Arguments
+/// * `dir` - Directory of record files (not searched recursively)
+/// * `extension` - Extension of the record files, without the dot
+/// * `archive` - Path of the archive to create
+///
+/// # Returns
+/// * `Ok(ExportSummary)` - How many records were written and which files were skipped
+/// * `Err(String)` - If the directory or archive cannot be accessed
+pub fn export_directory<P: AsRef<Path>, Q: AsRef<Path>>(
+    dir: P,
+    extension: &str,
+    archive: Q,
+) -> Result<ExportSummary, String> {
+    let mut paths: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| e.to_string())?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == extension))
+        .collect();
+    paths.sort();
+
+    let mut writer = ArchiveWriter::create(archive)?;
+    let mut skipped = Vec::new();
+    for path in paths {
+        let Some(key) = path.file_stem().and_then(|stem| stem.to_str()).map(String::from) else {
+            skipped.push((path, ERR_INVALID_ARCHIVE.to_string()));
+            continue;
+        };
+        let outcome = fs::read(&path).map_err(|e| e.to_string()).and_then(|value| writer.add(&key, &value));
+        if let Err(error) = outcome {
+            skipped.push((path, error));
+        }
+    }
+    let records = writer.finish()?;
+    Ok(ExportSummary { records, skipped })
+}
+
+/// Unpacks an archive into `dir`, one `<key>.<extension>` file per record.
+///
+/// # Arguments
+/// * `archive` - Path of the archive
+/// * `dir` - Directory to write the files to (created if missing)
+/// * `extension` - Extension to give the files, without the dot
+///
+/// # Returns
+/// * `Ok(usize)` - The number of files written
+/// * `Err(String)` - If the archive is invalid, a key is not a plain file name,
+///   or a file cannot be written
+pub fn import_archive<P: AsRef<Path>, Q: AsRef<Path>>(archive: P, dir: Q, extension: &str) -> Result<usize, String> {
+    let mut archive = Archive::open(archive)?;
+    let dir = dir.as_ref();
+    fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    let mut written = 0;
+    archive.for_each(|key, value| {
+        // Keys become file names, so they must not reach outside `dir`
+        if is_unsafe_segment(key.as_bytes()) {
+            return Err(ERR_INVALID_ARCHIVE.to_string());
+        }
+        fs::write(dir.join(format!("{}.{}", key, extension)), value).map_err(|e| e.to_string())?;
+        written += 1;
+        Ok(())
+    })?;
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directory_round_trips_through_an_archive() {
+        let source = PathBuf::from("test_archive_source");
+        let target = PathBuf::from("test_archive_target");
+        let archive = PathBuf::from("test_archive.bar");
+        let _ = fs::remove_dir_all(&source);
+        let _ = fs::remove_dir_all(&target);
+        fs::create_dir_all(&source).unwrap();
+        fs::write(source.join("node1.bencode"), b"d2:ip7:1.2.3.4e").unwrap();
+        fs::write(source.join("node2.bencode"), b"li1ei2ee").unwrap();
+        fs::write(source.join("broken.bencode"), b"li1e").unwrap();
+        fs::write(source.join("other.txt"), b"i1e").unwrap();
+
+        let summary = export_directory(&source, "bencode", &archive).unwrap();
+        let imported = import_archive(&archive, &target, "bencode").unwrap();
+        let node2 = fs::read(target.join("node2.bencode")).unwrap();
+        let broken_exists = target.join("broken.bencode").exists();
+        fs::remove_dir_all(&source).unwrap();
+        fs::remove_dir_all(&target).unwrap();
+        fs::remove_file(&archive).unwrap();
+
+        assert_eq!(summary.records, 2);
+        assert_eq!(summary.skipped.len(), 1);
+        assert!(summary.skipped[0].0.ends_with("broken.bencode"));
+        assert_eq!(imported, 2);
+        assert_eq!(node2, b"li1ei2ee");
+        assert!(!broken_exists);
+    }
+
+    #[test]
+    fn lookups_read_single_records() {
+        let path = "test_archive_lookup.bar";
+        let mut writer = ArchiveWriter::create(path).unwrap();
+        for index in 0..100 {
+            writer.add(&format!("key{:03}", index), format!("i{}e", index).as_bytes()).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), 100);
+        let mut archive = Archive::open(path).unwrap();
+        let found = archive.get("key042").unwrap();
+        let missing = archive.get("key100").unwrap();
+        let len = archive.len();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(found, Some(b"i42e".to_vec()));
+        assert_eq!(missing, None);
+        assert_eq!(len, 100);
+    }
+
+    #[test]
+    fn invalid_records_and_archives_are_rejected() {
+        let path = "test_archive_invalid.bar";
+        let mut writer = ArchiveWriter::create(path).unwrap();
+        assert!(writer.add("bad", b"i1").is_err());
+        writer.add("a", b"i1e").unwrap();
+        writer.add("a", b"i2e").unwrap();
+        assert_eq!(writer.finish().unwrap_err(), ERR_DUPLICATE_ARCHIVE_KEY);
+
+        fs::write(path, b"BENCARC1 truncated").unwrap();
+        let truncated = Archive::open(path).err();
+        fs::write(path, b"BENCARC2 from a newer release").unwrap();
+        let newer = Archive::open(path).err();
+        fs::remove_file(path).unwrap();
+        assert_eq!(truncated.as_deref(), Some(ERR_INVALID_ARCHIVE));
+        assert_eq!(newer.as_deref(), Some(ERR_UNSUPPORTED_FORMAT_VERSION));
+    }
+}
@@ -0,0 +1,441 @@
+//! Content addressed storage of torrent files.
+//!
+//! A [`TorrentStore`] keeps each torrent in a single directory under the name
+//! `<infohash>.torrent`, so identical torrents are stored once however they were
+//! named on import, and files renamed or copied into the directory by hand can be
+//! put back in place with [`TorrentStore::reconcile`].
+
+/// Single-file archives of keyed bencode records
+pub mod archive;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::batch::scan_directory;
+use crate::io::lock::lock_directory;
+use crate::torrent::sha1::SHA1_LENGTH;
+use crate::torrent::{hash_from_hex, info_hash, to_hex};
+
+/// Extension of stored torrent files
+pub const TORRENT_EXTENSION: &str = "torrent";
+
+/// Marker file locked while the store is changed (with the `fs2` feature)
+pub const LOCK_FILE_NAME: &str = ".lock";
+
+/// A v1 info hash
+pub type InfoHash = [u8; SHA1_LENGTH];
+
+/// Path of an imported file and the outcome of adding it
+pub type ImportOutcome = (PathBuf, Result<AddResult, String>);
+
+/// Outcome of adding a torrent to the store
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddResult {
+    /// The torrent was not in the store and has been written
+    Added(InfoHash),
+    /// A torrent with the same info hash was already stored; nothing was written
+    AlreadyStored(InfoHash),
+}
+
+impl AddResult {
+    /// Returns the info hash of the torrent
+    pub fn hash(&self) -> InfoHash {
+        match self {
+            AddResult::Added(hash) | AddResult::AlreadyStored(hash) => *hash,
+        }
+    }
+}
+
+/// A change made (or problem found) while reconciling the store directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreEvent {
+    /// A torrent stored under the wrong name was moved to its canonical name
+    Renamed {
+        /// Previous path
+        from: PathBuf,
+        /// Canonical path
+        to: PathBuf,
+    },
+    /// A torrent already stored under its canonical name was found again and removed
+    DuplicateRemoved {
+        /// Path of the removed copy
+        path: PathBuf,
+        /// Info hash of the torrent
+        hash: InfoHash,
+    },
+    /// A file could not be read or is not a valid torrent; it was left untouched
+    Unreadable {
+        /// Path of the file
+        path: PathBuf,
+        /// Why it could not be stored
+        error: String,
+    },
+}
+
+/// Directory of torrents addressed by info hash.
+///
+/// With the `fs2` feature, [`add`](Self::add), [`remove`](Self::remove) and
+/// [`reconcile`](Self::reconcile) hold an exclusive lock on a marker file in
+/// the directory, so processes sharing a store make their changes one at a time.
+///
+/// # Example
+/// ```
+/// use bencode_lib::storage::{AddResult, TorrentStore};
+///
+/// let root = std::env::temp_dir().join("doc_store_example");
+/// let store = TorrentStore::open(&root).unwrap();
+/// let torrent = b"d4:infod4:name4:demoee";
+/// let hash = store.add(torrent).unwrap().hash();
+/// assert_eq!(store.add(torrent).unwrap(), AddResult::AlreadyStored(hash));
+/// assert_eq!(store.get(&hash).unwrap(), Some(torrent.to_vec()));
+/// std::fs::remove_dir_all(&root).unwrap();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TorrentStore {
+    root: PathBuf,
+}
+
+impl TorrentStore {
+    /// Opens the store in `root`, creating the directory if needed.
+    ///
+    /// # Arguments
+    /// * `root` - Directory holding the stored torrents
+    ///
+    /// # Returns
+    /// * `Ok(TorrentStore)` - The opened store
+    /// * `Err(String)` - If the directory could not be created
+    pub fn open<P: AsRef<Path>>(root: P) -> Result<Self, String> {
+        let root = root.as_ref().to_path_buf();
+        fs::create_dir_all(&root).map_err(|e| e.to_string())?;
+        Ok(Self { root })
+    }
+
+    /// Returns the store directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the path a torrent with the given info hash is stored under.
+    pub fn path_for(&self, hash: &InfoHash) -> PathBuf {
+        self.root.join(format!("{}.{}", to_hex(hash), TORRENT_EXTENSION))
+    }
+
+    /// Returns true if a torrent with the given info hash is stored.
+    pub fn contains(&self, hash: &InfoHash) -> bool {
+        self.path_for(hash).is_file()
+    }
+
+    /// Stores a torrent unless one with the same info hash is already present.
+    ///
+    /// # Arguments
+    /// * `data` - The bencode-encoded torrent
+    ///
+    /// # Returns
+    /// * `Ok(AddResult)` - Whether the torrent was written, with its info hash
+    /// * `Err(String)` - If the torrent has no valid `info` dictionary or could not be written
+    pub fn add(&self, data: &[u8]) -> Result<AddResult, String> {
+        let hash = info_hash(data).map_err(|e| e.to_string())?;
+        let _lock = self.lock()?;
+        let path = self.path_for(&hash);
+        if path.is_file() {
+            return Ok(AddResult::AlreadyStored(hash));
+        }
+        // Write then rename so a crash never leaves a partial file under the final name
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, data).map_err(|e| e.to_string())?;
+        fs::rename(&temporary, &path).map_err(|e| e.to_string())?;
+        Ok(AddResult::Added(hash))
+    }
+
+    /// Reads a torrent file and stores it.
+    ///
+    /// # Arguments
+    /// * `path` - The torrent file to import
+    ///
+    /// # Returns
+    /// * `Ok(AddResult)` - Whether the torrent was written, with its info hash
+    /// * `Err(String)` - If the file could not be read or is not a valid torrent
+    pub fn add_file<P: AsRef<Path>>(&self, path: P) -> Result<AddResult, String> {
+        let data = fs::read(path).map_err(|e| e.to_string())?;
+        self.add(&data)
+    }
+
+    /// Imports every `.torrent` file in `dir` (non-recursively).
+    ///
+    /// Files that fail to parse are reported without being hashed.
+    ///
+    /// # Arguments
+    /// * `dir` - The directory to import from
+    ///
+    /// # Returns
+    /// * `Ok(Vec<ImportOutcome>)` - The outcome for each file, sorted by path
+    /// * `Err(String)` - If the directory could not be read
+    pub fn import_directory<P: AsRef<Path>>(&self, dir: P) -> Result<Vec<ImportOutcome>, String> {
+        let reports = scan_directory(dir.as_ref(), TORRENT_EXTENSION).map_err(|e| e.to_string())?;
+        Ok(reports
+            .into_iter()
+            .map(|report| {
+                let outcome = match report.error {
+                    Some(error) => Err(error),
+                    None => self.add_file(&report.path),
+                };
+                (report.path, outcome)
+            })
+            .collect())
+    }
+
+    /// Returns the stored torrent with the given info hash, if any.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Vec<u8>))` - The stored torrent bytes
+    /// * `Ok(None)` - No torrent with that hash is stored
+    /// * `Err(String)` - If the stored file could not be read
+    pub fn get(&self, hash: &InfoHash) -> Result<Option<Vec<u8>>, String> {
+        let path = self.path_for(hash);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        fs::read(path).map(Some).map_err(|e| e.to_string())
+    }
+
+    /// Looks up a torrent by its hexadecimal info hash.
+    ///
+    /// # Returns
+    /// * `Ok(Some(Vec<u8>))` - The stored torrent bytes
+    /// * `Ok(None)` - The hash is malformed or not stored
+    /// * `Err(String)` - If the stored file could not be read
+    pub fn get_hex(&self, hex: &str) -> Result<Option<Vec<u8>>, String> {
+        match hash_from_hex(hex) {
+            Some(hash) => self.get(&hash),
+            None => Ok(None),
+        }
+    }
+
+    /// Removes the torrent with the given info hash.
+    ///
+    /// # Returns
+    /// * `Ok(bool)` - True if a torrent was removed
+    /// * `Err(String)` - If the file could not be removed
+    pub fn remove(&self, hash: &InfoHash) -> Result<bool, String> {
+        let _lock = self.lock()?;
+        let path = self.path_for(hash);
+        if !path.is_file() {
+            return Ok(false);
+        }
+        fs::remove_file(path).map(|_| true).map_err(|e| e.to_string())
+    }
+
+    /// Lists the info hashes of all torrents stored under their canonical name.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<InfoHash>)` - The stored hashes, sorted
+    /// * `Err(String)` - If the directory could not be read
+    pub fn hashes(&self) -> Result<Vec<InfoHash>, String> {
+        let mut hashes: Vec<InfoHash> = self
+            .torrent_files()?
+            .iter()
+            .filter_map(|path| path.file_stem()?.to_str().and_then(hash_from_hex))
+            .collect();
+        hashes.sort();
+        Ok(hashes)
+    }
+
+    /// Brings the directory back to one canonically named file per torrent.
+    ///
+    /// Every `.torrent` file is hashed: files whose name does not match their info
+    /// hash (renamed, or moved in from elsewhere) are renamed to it, or removed if
+    /// that torrent is already stored. Files that are not valid torrents are
+    /// reported and left in place.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<StoreEvent>)` - Every change made or problem found, in path order
+    /// * `Err(String)` - If the directory could not be read or a file could not be moved
+    pub fn reconcile(&self) -> Result<Vec<StoreEvent>, String> {
+        let _lock = self.lock()?;
+        let mut events = Vec::new();
+        for path in self.torrent_files()? {
+            let hash = match fs::read(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|data| info_hash(&data).map_err(|e| e.to_string()))
+            {
+                Ok(hash) => hash,
+                Err(error) => {
+                    events.push(StoreEvent::Unreadable { path, error });
+                    continue;
+                }
+            };
+            let canonical = self.path_for(&hash);
+            if path == canonical {
+                continue;
+            }
+            if canonical.is_file() {
+                fs::remove_file(&path).map_err(|e| e.to_string())?;
+                events.push(StoreEvent::DuplicateRemoved { path, hash });
+            } else {
+                fs::rename(&path, &canonical).map_err(|e| e.to_string())?;
+                events.push(StoreEvent::Renamed {
+                    from: path,
+                    to: canonical,
+                });
+            }
+        }
+        Ok(events)
+    }
+
+    /// Locks the store until the returned guard is dropped.
+    fn lock(&self) -> Result<Option<fs::File>, String> {
+        lock_directory(&self.root, LOCK_FILE_NAME).map_err(|e| e.to_string())
+    }
+
+    /// Returns the `.torrent` files in the store directory, sorted by path.
+    fn torrent_files(&self) -> Result<Vec<PathBuf>, String> {
+        let mut paths: Vec<PathBuf> = fs::read_dir(&self.root)
+            .map_err(|e| e.to_string())?
+            .filter_map(|entry| entry.ok().map(|e| e.path()))
+            .filter(|path| path.is_file() && path.extension().is_some_and(|e| e == TORRENT_EXTENSION))
+            .collect();
+        paths.sort();
+        Ok(paths)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::create_test_dir;
+
+    const TORRENT_A: &[u8] = b"d8:announce3:url4:infod4:name1:aee";
+    const TORRENT_A_OTHER_TRACKER: &[u8] = b"d8:announce5:other4:infod4:name1:aee";
+    const TORRENT_B: &[u8] = b"d4:infod4:name1:bee";
+
+    #[test]
+    fn add_stores_under_info_hash_and_dedupes() {
+        let dir = create_test_dir("test_store_add", &[]);
+        let store = TorrentStore::open(&dir).unwrap();
+        let added = store.add(TORRENT_A).unwrap();
+        let again = store.add(TORRENT_A_OTHER_TRACKER).unwrap();
+        let stored = store.get(&added.hash()).unwrap();
+        let path = store.path_for(&added.hash());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(added, AddResult::Added(info_hash(TORRENT_A).unwrap()));
+        // Only the info dictionary identifies a torrent
+        assert_eq!(again, AddResult::AlreadyStored(added.hash()));
+        assert_eq!(stored, Some(TORRENT_A.to_vec()));
+        assert_eq!(
+            path.file_name().unwrap().to_str().unwrap(),
+            format!("{}.torrent", to_hex(&added.hash()))
+        );
+    }
+
+    #[cfg(feature = "fs2")]
+    #[test]
+    fn add_waits_for_the_store_lock() {
+        let dir = create_test_dir("test_store_lock", &[]);
+        let store = TorrentStore::open(&dir).unwrap();
+        let guard = store.lock().unwrap();
+        let writer = {
+            let store = store.clone();
+            std::thread::spawn(move || store.add(TORRENT_A).unwrap().hash())
+        };
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        let hash = info_hash(TORRENT_A).unwrap();
+        let stored_while_locked = store.contains(&hash);
+        drop(guard);
+        let added = writer.join().unwrap();
+        let stored = store.contains(&hash);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(!stored_while_locked);
+        assert!(stored && added == hash);
+    }
+
+    #[test]
+    fn add_rejects_non_torrents() {
+        let dir = create_test_dir("test_store_reject", &[]);
+        let store = TorrentStore::open(&dir).unwrap();
+        let result = store.add(b"li1ee");
+        let hashes = store.hashes().unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(result.is_err());
+        assert!(hashes.is_empty());
+    }
+
+    #[test]
+    fn lookup_remove_and_list() {
+        let dir = create_test_dir("test_store_lookup", &[]);
+        let store = TorrentStore::open(&dir).unwrap();
+        let a = store.add(TORRENT_A).unwrap().hash();
+        let b = store.add(TORRENT_B).unwrap().hash();
+        let mut expected = vec![a, b];
+        expected.sort();
+        let hashes = store.hashes().unwrap();
+        let by_hex = store.get_hex(&to_hex(&b).to_uppercase()).unwrap();
+        let removed = store.remove(&a).unwrap();
+        let removed_again = store.remove(&a).unwrap();
+        let contains = store.contains(&a);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(hashes, expected);
+        assert_eq!(by_hex, Some(TORRENT_B.to_vec()));
+        assert!(removed);
+        assert!(!removed_again);
+        assert!(!contains);
+    }
+
+    #[test]
+    fn reconcile_detects_renames_and_duplicates() {
+        let dir = create_test_dir("test_store_reconcile", &[]);
+        let store = TorrentStore::open(&dir).unwrap();
+        let a = store.add(TORRENT_A).unwrap().hash();
+        let b = info_hash(TORRENT_B).unwrap();
+        fs::write(dir.join("copy_of_a.torrent"), TORRENT_A_OTHER_TRACKER).unwrap();
+        fs::write(dir.join("renamed_b.torrent"), TORRENT_B).unwrap();
+        fs::write(dir.join("broken.torrent"), b"d4:info").unwrap();
+        let events = store.reconcile().unwrap();
+        let second = store.reconcile().unwrap();
+        let contains_b = store.contains(&b);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(events.len(), 3);
+        assert!(matches!(&events[0], StoreEvent::Unreadable { path, .. } if path.ends_with("broken.torrent")));
+        assert_eq!(
+            events[1],
+            StoreEvent::DuplicateRemoved {
+                path: dir.join("copy_of_a.torrent"),
+                hash: a
+            }
+        );
+        assert_eq!(
+            events[2],
+            StoreEvent::Renamed {
+                from: dir.join("renamed_b.torrent"),
+                to: store.path_for(&b)
+            }
+        );
+        assert!(contains_b);
+        // Only the unreadable file remains out of place
+        assert_eq!(second.len(), 1);
+    }
+
+    #[test]
+    fn import_directory_hashes_valid_files() {
+        let source = create_test_dir(
+            "test_store_import_source",
+            &[("a.torrent", TORRENT_A), ("b.torrent", TORRENT_B), ("bad.torrent", b"i1"), ("notes.txt", b"ignored")],
+        );
+        let dir = create_test_dir("test_store_import", &[]);
+        let store = TorrentStore::open(&dir).unwrap();
+        let outcomes = store.import_directory(&source).unwrap();
+        let hashes = store.hashes().unwrap();
+        fs::remove_dir_all(&source).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(outcomes.len(), 3);
+        assert!(matches!(outcomes[0].1, Ok(AddResult::Added(_))));
+        assert!(matches!(outcomes[1].1, Ok(AddResult::Added(_))));
+        assert!(outcomes[2].1.is_err());
+        assert_eq!(hashes.len(), 2);
+    }
+}
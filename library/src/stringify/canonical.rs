@@ -0,0 +1,129 @@
+//! Encoding of node trees in canonical form.
+//!
+//! [`stringify`](crate::stringify) copies Raw nodes verbatim, so a tree holding
+//! pre-encoded values is only as canonical as they are. [`canonicalize`]
+//! re-encodes Raw values as well, so its output always passes
+//! [`validate_canonical`](crate::validate_canonical).
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+use crate::error::parse::ParseError;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::nodes::node::Node;
+use crate::parser::borrowed::parse_borrowed;
+use crate::parser::canonical::validate_canonical;
+
+/// Appends a length prefixed byte string
+fn encode_bytes(bytes: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(format!("{}:", bytes.len()).as_bytes());
+    out.extend_from_slice(bytes);
+}
+
+/// Appends a borrowed tree, keeping its keys as the exact bytes read
+fn encode_borrowed(node: &BorrowedNode, out: &mut Vec<u8>) {
+    match node {
+        BorrowedNode::Integer(value) => out.extend_from_slice(format!("i{}e", value).as_bytes()),
+        BorrowedNode::Bytes(bytes) => encode_bytes(bytes, out),
+        BorrowedNode::List(items) => {
+            out.push(b'l');
+            items.iter().for_each(|item| encode_borrowed(item, out));
+            out.push(b'e');
+        }
+        BorrowedNode::Dictionary(entries) => {
+            let mut sorted: Vec<_> = entries.iter().collect();
+            sorted.sort_by_key(|(key, _)| **key);
+            out.push(b'd');
+            for (key, value) in sorted {
+                encode_bytes(key, out);
+                encode_borrowed(value, out);
+            }
+            out.push(b'e');
+        }
+    }
+}
+
+/// Appends a node tree, re-encoding Raw values that are not canonical
+fn encode(node: &Node, out: &mut Vec<u8>) -> Result<(), ParseError> {
+    match node {
+        Node::Integer(value) => out.extend_from_slice(format!("i{}e", value).as_bytes()),
+        Node::BigInteger(digits) => out.extend_from_slice(format!("i{}e", digits).as_bytes()),
+        Node::Str(value) => encode_bytes(value.as_bytes(), out),
+        Node::Bytes(bytes) => encode_bytes(bytes, out),
+        Node::List(items) => {
+            out.push(b'l');
+            for item in items {
+                encode(item, out)?;
+            }
+            out.push(b'e');
+        }
+        Node::Dictionary(_) => {
+            out.push(b'd');
+            for (key, value) in node.iter_sorted() {
+                encode_bytes(key.as_bytes(), out);
+                encode(value, out)?;
+            }
+            out.push(b'e');
+        }
+        Node::Raw(encoded) if validate_canonical(encoded).is_ok() => out.extend_from_slice(encoded),
+        Node::Raw(encoded) => encode_borrowed(&parse_borrowed(encoded)?, out),
+        // As with stringify, None has no encoding
+        Node::None => {}
+    }
+    Ok(())
+}
+
+/// Encodes a node tree in canonical form.
+///
+/// The output matches [`stringify_to_bytes`](crate::stringify_to_bytes)
+/// except that Raw values are re-encoded when they are not already canonical,
+/// keeping their dictionary keys as the exact bytes they hold.
+///
+/// # Arguments
+/// * `node` - The tree to encode
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The canonical encoding
+/// * `Err(ParseError)` - If a Raw node does not hold valid bencode
+///
+/// # Example
+/// ```
+/// use bencode_lib::{canonicalize, stringify_to_bytes, validate_canonical, Node};
+///
+/// let info = Node::raw(b"d6:lengthi010e4:name01:xe".to_vec()).unwrap();
+/// let torrent = Node::from([("info", info)]);
+/// assert!(validate_canonical(&stringify_to_bytes(&torrent).unwrap()).is_err());
+/// let encoded = canonicalize(&torrent).unwrap();
+/// assert_eq!(encoded, b"d4:infod6:lengthi10e4:name1:xee");
+/// assert!(validate_canonical(&encoded).is_ok());
+/// ```
+pub fn canonicalize(node: &Node) -> Result<Vec<u8>, ParseError> {
+    let mut out = Vec::new();
+    encode(node, &mut out)?;
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stringify::default::stringify_to_bytes;
+
+    #[test]
+    fn canonical_trees_encode_as_stringify_does() {
+        let node = Node::from([
+            ("b", Node::from(vec![Node::from(-3), Node::Bytes(vec![0xff])])),
+            ("a", Node::raw(b"d1:xi1ee".to_vec()).unwrap()),
+        ]);
+        assert_eq!(canonicalize(&node).unwrap(), stringify_to_bytes(&node).unwrap());
+    }
+
+    #[test]
+    fn raw_values_are_made_canonical() {
+        // Keys that are not UTF-8 keep their bytes
+        let raw = Node::Raw(b"d1:ai02e2:\xff\x00i01ee".to_vec());
+        let encoded = canonicalize(&Node::from(vec![raw])).unwrap();
+        assert_eq!(encoded, b"ld1:ai2e2:\xff\x00i1eee");
+        assert_eq!(validate_canonical(&encoded), Ok(()));
+        assert!(canonicalize(&Node::Raw(b"i1".to_vec())).is_err());
+    }
+}
@@ -1,8 +1,12 @@
 #[cfg(not(feature = "std"))]
 use alloc::format;
 
+use crate::config::BinaryEncoding;
 use crate::io::traits::IDestination;
 
+/// Alphabet of standard base64
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
 /// Escapes and writes a string value to the destination, handling special characters
 /// and converting unprintable characters to \u escape sequences.
 ///
@@ -10,7 +14,17 @@ use crate::io::traits::IDestination;
 /// * `value` - The string value to escape and write
 /// * `destination` - The destination to write the escaped string to
 pub(crate) fn escape_string(value: &str, destination: &mut dyn IDestination) {
-    for &byte in value.as_bytes() {
+    escape_bytes(value.as_bytes(), destination);
+}
+
+/// Escapes and writes a byte string, as `escape_string` does for text. Bytes
+/// outside printable ASCII are written as `\u00XX` escapes of the byte value.
+///
+/// # Arguments
+/// * `value` - The bytes to escape and write
+/// * `destination` - The destination to write the escaped bytes to
+pub(crate) fn escape_bytes(value: &[u8], destination: &mut dyn IDestination) {
+    for &byte in value {
         if byte == b'"' || byte == b'\\' {
             destination.add_byte(b'\\');
             destination.add_byte(byte);
@@ -24,6 +38,35 @@ pub(crate) fn escape_string(value: &str, destination: &mut dyn IDestination) {
     }
 }
 
+/// Writes a binary string in the given encoding, without surrounding quotes.
+///
+/// # Arguments
+/// * `value` - The bytes to write
+/// * `encoding` - Whether to escape, hex encode or base64 encode them
+/// * `destination` - The destination to write to
+pub(crate) fn write_binary(value: &[u8], encoding: BinaryEncoding, destination: &mut dyn IDestination) {
+    match encoding {
+        BinaryEncoding::Escaped => escape_bytes(value, destination),
+        BinaryEncoding::Hex => {
+            for &byte in value {
+                destination.add_bytes(&format!("{:02x}", byte));
+            }
+        }
+        BinaryEncoding::Base64 => {
+            for chunk in value.chunks(3) {
+                let group = chunk.iter().enumerate().fold(0u32, |group, (index, &byte)| group | (byte as u32) << (16 - 8 * index));
+                for index in 0..4 {
+                    if index <= chunk.len() {
+                        destination.add_byte(BASE64_ALPHABET[(group >> (18 - 6 * index) & 0x3f) as usize]);
+                    } else {
+                        destination.add_byte(b'=');
+                    }
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -115,4 +158,21 @@ mod tests {
         escape_string("abc123!@#$%^&*()", &mut destination);
         assert_eq!(destination.to_string(), "abc123!@#$%^&*()");
     }
+
+    #[test]
+    fn test_write_binary_encodings() {
+        let encode = |value: &[u8], encoding| {
+            let mut destination = Buffer::new();
+            write_binary(value, encoding, &mut destination);
+            destination.to_string()
+        };
+        assert_eq!(encode(b"\x00\xff\"", BinaryEncoding::Escaped), "\\u0000\\u00ff\\\"");
+        assert_eq!(encode(b"\x00\xffA", BinaryEncoding::Hex), "00ff41");
+        let base64: Vec<_> = ["", "f", "fo", "foo", "foob", "fooba", "foobar"]
+            .iter()
+            .map(|value| encode(value.as_bytes(), BinaryEncoding::Base64))
+            .collect();
+        assert_eq!(base64, ["", "Zg==", "Zm8=", "Zm9v", "Zm9vYg==", "Zm9vYmE=", "Zm9vYmFy"]);
+        assert_eq!(encode(b"\xfb\xff", BinaryEncoding::Base64), "+/8=");
+    }
 }
@@ -2,46 +2,147 @@
 //! Implements the bencode encoding rules for different node types.
 
 #[cfg(not(feature = "std"))]
-use alloc::{format, string::String, vec::Vec};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
+use crate::config::EncoderConfig;
+use crate::error::messages::{ERR_DICT_KEYS_ORDER, ERR_OUTPUT_LIMIT};
+use crate::error::parse::ParseError;
 use crate::io::traits::IDestination;
+use crate::metrics::Timer;
+use crate::nodes::fixed::MemoryBounds;
 use crate::nodes::node::*;
+use crate::parser::key_order::check_key_order;
 
 /// Converts a bencode Node into its string representation and writes it to the destination.
 ///
 /// # Arguments
 /// * `node` - The bencode node to stringify
 /// * `destination` - The destination to write the string representation to
-pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(), String> {
+pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(), ParseError> {
+    let timer = Timer::start("stringify");
+    let result = stringify_node(node, destination).map_err(|message| ParseError::new(message, 0));
+    timer.finish(None, &result);
+    result
+}
+
+/// Converts a bencode Node into its string representation, refusing to write
+/// more than `max_bytes` bytes.
+///
+/// The encoded size is worked out with [`MemoryBounds::stringify_estimate`]
+/// first, so a node that does not fit is rejected before any byte reaches the
+/// destination rather than leaving a truncated message behind.
+///
+/// # Arguments
+/// * `node` - The bencode node to stringify
+/// * `destination` - The destination to write the string representation to
+/// * `max_bytes` - The most bytes the destination may receive
+///
+/// # Returns
+/// * `Ok(usize)` - The number of bytes written
+/// * `Err(ParseError)` - If the encoding would be longer than `max_bytes`; the
+///   offset is the limit
+///
+/// # Example
+/// ```
+/// use bencode_lib::{BufferDestination, ErrorKind, make_node, stringify_bounded};
+///
+/// let mut destination = BufferDestination::new();
+/// assert_eq!(stringify_bounded(&make_node("spam"), &mut destination, 6), Ok(6));
+///
+/// let error = stringify_bounded(&make_node("spam"), &mut destination, 5).unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::OutputLimit);
+/// assert_eq!(destination.to_string(), "4:spam");
+/// ```
+pub fn stringify_bounded(node: &Node, destination: &mut dyn IDestination, max_bytes: usize) -> Result<usize, ParseError> {
+    let length = MemoryBounds::stringify_estimate(node);
+    if length > max_bytes {
+        return Err(ParseError::new(ERR_OUTPUT_LIMIT, max_bytes));
+    }
+    stringify(node, destination)?;
+    Ok(length)
+}
+
+/// Converts a bencode Node into its string representation following an
+/// [`EncoderConfig`].
+///
+/// Dictionary keys are always written in canonical order, whichever map backs
+/// the tree. With `verify_dict_order` set, pre-encoded values are checked for dictionary keys out of order before
+/// they are copied. The text format options have no effect here.
+///
+/// # Arguments
+/// * `node` - The bencode node to stringify
+/// * `destination` - The destination to write the string representation to
+/// * `config` - How the node is encoded
+///
+/// # Returns
+/// * `Ok(())` - The node was written
+/// * `Err(ParseError)` - A pre-encoded value has keys out of order; the offset
+///   is within that value
+///
+/// # Example
+/// ```
+/// use bencode_lib::{BufferDestination, EncoderConfig, ErrorKind, Node, stringify_with_config};
+///
+/// let raw = Node::Raw(b"d1:bi1e1:ai2ee".to_vec());
+/// let mut destination = BufferDestination::new();
+/// let error = stringify_with_config(&raw, &mut destination, &EncoderConfig::new()).unwrap_err();
+/// assert_eq!(error.kind(), ErrorKind::DictKeysOrder);
+///
+/// let unchecked = EncoderConfig::new().with_dict_order_verification(false);
+/// stringify_with_config(&raw, &mut destination, &unchecked).unwrap();
+/// assert_eq!(destination.to_string(), "d1:bi1e1:ai2ee");
+/// ```
+pub fn stringify_with_config(node: &Node, destination: &mut dyn IDestination, config: &EncoderConfig) -> Result<(), ParseError> {
+    let timer = Timer::start("stringify");
+    let result = encode_node(node, destination, config);
+    timer.finish(None, &result);
+    result
+}
+
+/// Writes a node and its children to the destination.
+pub(crate) fn stringify_node(node: &Node, destination: &mut dyn IDestination) -> Result<(), String> {
+    const UNCHECKED: EncoderConfig = EncoderConfig::new().with_dict_order_verification(false);
+    encode_node(node, destination, &UNCHECKED).map_err(|error| error.message)
+}
+
+/// Writes a node and its children to the destination following `config`.
+fn encode_node(node: &Node, destination: &mut dyn IDestination, config: &EncoderConfig) -> Result<(), ParseError> {
     match node {
-        // Handle integer nodes by formatting as "i<value>e"
-        Node::Integer(value) => {
-            let s = format!("i{}e", value);
-            destination.add_bytes(s.as_str());
-        }
-        // Handle string nodes by formatting as "<length>:<value>"
-        Node::Str(value) => {
-            let s = format!("{}:{}", value.len(), value);
-            destination.add_bytes(s.as_str());
-        }
+        // Widened for `int32` builds, where Integer is i32
+        #[allow(clippy::useless_conversion)]
+        Node::Integer(value) => encode_int(i64::from(*value), destination),
+        Node::BigInteger(digits) => destination.add_bytes(&format!("i{}e", digits)),
+        Node::Str(value) => encode_bytes(value.as_bytes(), destination),
+        Node::Bytes(bytes) => encode_bytes(bytes, destination),
         // Handle list nodes by wrapping items with 'l' and 'e' markers
         Node::List(items) => {
-            destination.add_byte(b'l');
+            encode_list_header(destination);
             for item in items {
-                stringify(item, destination)?;
+                encode_node(item, destination, config)?;
             }
-            destination.add_byte(b'e');
+            encode_end(destination);
         }
         // Handle dictionary nodes by wrapping sorted key-value pairs with 'd' and 'e' markers
-        Node::Dictionary(items) => {
-            destination.add_byte(b'd');
-            let mut sorted: Vec<_> = items.iter().collect();
-            sorted.sort_by(|a, b| a.0.cmp(b.0));
-            for (key, value) in sorted {
-                stringify(&Node::Str(key.clone()), destination)?;
-                stringify(value, destination)?;
+        Node::Dictionary(_) => {
+            encode_dict_header(destination);
+            for (key, value) in node.iter_sorted() {
+                encode_bytes(key.as_bytes(), destination);
+                encode_node(value, destination, config)?;
             }
-            destination.add_byte(b'e');
+            encode_end(destination);
+        }
+        // Copy pre-encoded values verbatim
+        Node::Raw(encoded) => {
+            if config.verify_dict_order
+                && let Err(violation) = check_key_order(encoded)
+            {
+                return Err(ParseError::new(ERR_DICT_KEYS_ORDER, violation.offset));
+            }
+            destination.add_raw(encoded);
         }
         // Skip None nodes as they don't have a string representation
         Node::None => {
@@ -51,6 +152,87 @@ pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(),
     Ok(())
 }
 
+/// Writes an integer as `i<value>e`.
+///
+/// The primitives below emit bencode piece by piece without building a
+/// [`Node`], for protocol code streaming values too large to hold in a tree.
+/// The caller is responsible for the structure: every header needs a matching
+/// [`encode_end`] and dictionary keys must be byte strings written in sorted
+/// order.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{BufferDestination, encode_bytes, encode_dict_header, encode_end, encode_int};
+///
+/// let mut destination = BufferDestination::new();
+/// encode_dict_header(&mut destination);
+/// encode_bytes(b"length", &mut destination);
+/// encode_int(-42, &mut destination);
+/// encode_end(&mut destination);
+/// assert_eq!(destination.to_string(), "d6:lengthi-42ee");
+/// ```
+pub fn encode_int(value: i64, destination: &mut dyn IDestination) {
+    destination.add_bytes(&format!("i{}e", value));
+}
+
+/// Writes a byte string as `<length>:<bytes>`.
+pub fn encode_bytes(bytes: &[u8], destination: &mut dyn IDestination) {
+    encode_bytes_header(bytes.len(), destination);
+    destination.add_raw(bytes);
+}
+
+/// Writes the `<length>:` prefix of a byte string, leaving the caller to write
+/// exactly `length` bytes after it.
+///
+/// # Example
+/// ```
+/// use bencode_lib::io::traits::IDestination;
+/// use bencode_lib::{BufferDestination, encode_bytes_header};
+///
+/// // Stream a long string in chunks without holding it in memory
+/// let mut destination = BufferDestination::new();
+/// encode_bytes_header(6, &mut destination);
+/// for chunk in [b"abc", b"\xff\x00\x01"] {
+///     destination.add_raw(chunk);
+/// }
+/// assert_eq!(destination.into_bytes(), b"6:abc\xff\x00\x01");
+/// ```
+pub fn encode_bytes_header(length: usize, destination: &mut dyn IDestination) {
+    destination.add_bytes(&format!("{}:", length));
+}
+
+/// Writes the `l` that opens a list.
+pub fn encode_list_header(destination: &mut dyn IDestination) {
+    destination.add_byte(b'l');
+}
+
+/// Writes the `d` that opens a dictionary.
+pub fn encode_dict_header(destination: &mut dyn IDestination) {
+    destination.add_byte(b'd');
+}
+
+/// Writes the `e` that closes a list or dictionary.
+pub fn encode_end(destination: &mut dyn IDestination) {
+    destination.add_byte(b'e');
+}
+
+/// Returns the number of bytes [`stringify`] writes for a node, without encoding it.
+///
+/// The size is worked out from the tree alone, as [`MemoryBounds::stringify_estimate`]
+/// does, so it can size a buffer before encoding. It is exact for trees whose
+/// Raw values hold a single valid value, as [`Node::raw`] ensures.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{estimated_encoded_size, parse_bytes};
+///
+/// let node = parse_bytes(b"d4:infod6:lengthi1024e4:name4:demoee").unwrap();
+/// assert_eq!(estimated_encoded_size(&node), 36);
+/// ```
+pub fn estimated_encoded_size(node: &Node) -> usize {
+    MemoryBounds::stringify_estimate(node)
+}
+
 /// Converts a bencode Node into its string representation and returns it as a String.
 /// This is a convenience function that creates a BufferDestination internally.
 ///
@@ -58,32 +240,39 @@ pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(),
 /// * `node` - The bencode node to stringify
 ///
 /// # Returns
-/// * `Result<String, String>` - The bencode string representation or error message
-pub fn stringify_to_string(node: &Node) -> Result<String, String> {
+/// * `Result<String, ParseError>` - The bencode string representation or error
+pub fn stringify_to_string(node: &Node) -> Result<String, ParseError> {
     use crate::io::destinations::buffer::Buffer;
-    let mut destination = Buffer::new();
+    let mut destination = Buffer::with_capacity(estimated_encoded_size(node));
     stringify(node, &mut destination)?;
     Ok(destination.to_string())
 }
 
 /// Converts a bencode Node into its byte representation and returns it as a Vec<u8>.
-/// This is a convenience function that creates a BufferDestination internally.
+/// This is a convenience function that creates a BufferDestination internally,
+/// sized up front with [`estimated_encoded_size`] so the output is allocated once.
 ///
 /// # Arguments
 /// * `node` - The bencode node to stringify
 ///
 /// # Returns
-/// * `Result<Vec<u8>, String>` - The bencode byte representation or error message
-pub fn stringify_to_bytes(node: &Node) -> Result<Vec<u8>, String> {
-    let s = stringify_to_string(node)?;
-    Ok(s.into_bytes())
+/// * `Result<Vec<u8>, ParseError>` - The bencode byte representation or error
+pub fn stringify_to_bytes(node: &Node) -> Result<Vec<u8>, ParseError> {
+    use crate::io::destinations::buffer::Buffer;
+    let timer = Timer::start("stringify_to_bytes");
+    let mut destination = Buffer::with_capacity(estimated_encoded_size(node));
+    let result = stringify_node(node, &mut destination)
+        .map(|()| destination.into_bytes())
+        .map_err(|message| ParseError::new(message, 0));
+    timer.finish(result.as_ref().ok().map(Vec::len), &result);
+    result
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::BufferDestination;
-    use std::collections::HashMap;
+    use crate::HashMap;
 
     #[test]
     fn stringify_integer_works() {
@@ -136,4 +325,74 @@ mod tests {
         stringify(&Node::None, &mut destination).unwrap();
         assert_eq!(destination.to_string(), "");
     }
+
+    #[test]
+    fn stringify_copies_raw_bytes_verbatim() {
+        // Binary data survives because the bytes are not decoded and re-encoded
+        let raw = Node::raw(b"d1:a1:\xff1:bi1ee".to_vec()).unwrap();
+        let node = Node::List(vec![Node::Integer(1), raw]);
+        assert_eq!(stringify_to_bytes(&node).unwrap(), b"li1ed1:a1:\xff1:bi1eee");
+    }
+
+    #[test]
+    fn stringify_with_config_sorts_keys_and_checks_raw_values() {
+        let node = make_node([("b", make_node(1)), ("a", make_node(2))]);
+        let mut destination = BufferDestination::new();
+        stringify_with_config(&node, &mut destination, &EncoderConfig::new()).unwrap();
+        assert_eq!(destination.to_string(), "d1:ai2e1:bi1ee");
+
+        let raw = make_node(vec![Node::Raw(b"d1:bi1e1:ai2ee".to_vec())]);
+        let error = stringify_with_config(&raw, &mut BufferDestination::new(), &EncoderConfig::new()).unwrap_err();
+        assert_eq!((error.message.as_str(), error.offset), (ERR_DICT_KEYS_ORDER, 7));
+        assert_eq!(stringify_to_bytes(&raw).unwrap(), b"ld1:bi1e1:ai2eee");
+    }
+
+    #[test]
+    fn output_is_preallocated_to_the_estimated_size() {
+        let raw = Node::raw(b"l1:ae".to_vec()).unwrap();
+        let node = make_node([("a", make_node(vec![make_node(-7), raw])), ("b", make_node(b"\xff\x00".as_slice()))]);
+        let bytes = stringify_to_bytes(&node).unwrap();
+        assert_eq!(estimated_encoded_size(&node), bytes.len());
+        assert!(bytes.capacity() >= bytes.len());
+
+        let mut counting = crate::SizeCountingDestination::new();
+        stringify(&node, &mut counting).unwrap();
+        assert_eq!(counting.count(), bytes.len());
+    }
+
+    #[test]
+    fn encode_primitives_match_stringify() {
+        let mut destination = BufferDestination::new();
+        encode_list_header(&mut destination);
+        encode_int(i64::MIN, &mut destination);
+        encode_bytes(b"\xff\x00", &mut destination);
+        encode_dict_header(&mut destination);
+        encode_bytes(b"a", &mut destination);
+        encode_bytes_header(0, &mut destination);
+        encode_end(&mut destination);
+        encode_end(&mut destination);
+        let expected = b"li-9223372036854775808e2:\xff\x00d1:a0:ee";
+        assert_eq!(destination.buffer, expected);
+
+        #[cfg(not(feature = "int32"))]
+        {
+            let node = make_node(vec![
+                make_node(i64::MIN),
+                Node::Bytes(vec![0xff, 0]),
+                make_node([("a", make_node(""))]),
+            ]);
+            assert_eq!(stringify_to_bytes(&node).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn stringify_bounded_writes_nothing_past_the_limit() {
+        let node = make_node(vec![make_node(1), make_node("ab")]);
+        let mut destination = BufferDestination::new();
+        assert_eq!(stringify_bounded(&node, &mut destination, 9), Ok(9));
+        assert_eq!(destination.to_string(), "li1e2:abe");
+        let mut destination = BufferDestination::new();
+        assert!(stringify_bounded(&node, &mut destination, 8).is_err());
+        assert_eq!(destination.to_string(), "");
+    }
 }
@@ -4,25 +4,87 @@ use alloc::{
     vec::Vec,
 };
 
+use crate::config::EncoderConfig;
 use crate::io::traits::IDestination;
+use crate::nodes::borrowed::BorrowedNode;
 use crate::nodes::node::*;
-use crate::stringify::common::escape_string;
+use crate::stringify::common::{escape_bytes, escape_string, write_binary};
+use crate::stringify::report::{ConversionReport, Format, analyze};
 
-/// Converts a Node structure into a JSON string representation and writes it to the given destination.
+/// Converts a Node structure into a JSON string representation and writes it to the destination.
 /// Handles different node types (Integer, String, List, Dictionary) according to JSON format rules.
 ///
 /// # Arguments
 /// * `node` - The Node structure to convert
 /// * `destination` - The destination to write the JSON output to
 pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(), String> {
+    stringify_with_config(node, destination, &EncoderConfig::new())
+}
+
+/// Converts a Node structure to JSON like [`stringify`], laid out as `config` asks.
+///
+/// Keys are sorted, `pretty` puts each item on its own line indented by
+/// `indent` spaces per level, and `binary` chooses how byte strings that are
+/// not UTF-8 are written.
+///
+/// # Arguments
+/// * `node` - The Node structure to convert
+/// * `destination` - The destination to write the JSON output to
+/// * `config` - Layout of the output
+///
+/// # Example
+/// ```
+/// use bencode_lib::{BinaryEncoding, BufferDestination, EncoderConfig, parse_bytes, to_json_with_config};
+///
+/// let node = parse_bytes(b"d4:hash2:\xab\xcd4:listli1eee").unwrap();
+/// let config = EncoderConfig::new().with_pretty(true).with_binary(BinaryEncoding::Hex);
+/// let mut destination = BufferDestination::new();
+/// to_json_with_config(&node, &mut destination, &config).unwrap();
+/// assert_eq!(destination.to_string(), "{\n  \"hash\": \"abcd\",\n  \"list\": [\n    1\n  ]\n}");
+/// ```
+pub fn stringify_with_config(node: &Node, destination: &mut dyn IDestination, config: &EncoderConfig) -> Result<(), String> {
+    write_node(node, 1, destination, config)
+}
+
+/// Starts a new line indented for `level` when writing pretty JSON.
+///
+/// # Arguments
+/// * `level` - The nesting level of the item that follows
+/// * `destination` - The destination to write to
+/// * `config` - Whether the output is pretty and how wide an indent is
+fn write_break(level: usize, destination: &mut dyn IDestination, config: &EncoderConfig) {
+    if config.pretty {
+        destination.add_byte(b'\n');
+        for _ in 0..level * config.indent {
+            destination.add_byte(b' ');
+        }
+    }
+}
+
+/// Writes a node at the given nesting level, following `config`.
+///
+/// # Arguments
+/// * `node` - The node to write
+/// * `level` - Nesting level of the node's items
+/// * `destination` - The destination to write to
+/// * `config` - Layout of the output
+fn write_node(node: &Node, level: usize, destination: &mut dyn IDestination, config: &EncoderConfig) -> Result<(), String> {
     match node {
         Node::Integer(value) => {
             destination.add_bytes(&value.to_string());
         }
+        // JSON numbers have no size limit
+        Node::BigInteger(digits) => destination.add_bytes(digits),
         // Format a string value as JSON by wrapping it in double quotes
         Node::Str(value) => {
             destination.add_byte(b'"');
-            escape_string(&value, destination);
+            escape_string(value, destination);
+            destination.add_byte(b'"');
+        }
+        // Binary strings are written in the configured encoding
+        Node::Bytes(bytes) => {
+            destination.add_byte(b'"');
+            write_binary(bytes, config.binary, destination);
             destination.add_byte(b'"');
         }
         Node::List(items) => {
@@ -31,25 +93,32 @@ pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(),
                 if index > 0 {
                     destination.add_byte(b',');
                 }
-                stringify(item, destination)?;
+                write_break(level, destination, config);
+                write_node(item, level + 1, destination, config)?;
+            }
+            if !items.is_empty() {
+                write_break(level - 1, destination, config);
             }
             destination.add_byte(b']');
         }
         Node::Dictionary(items) => {
             destination.add_byte(b'{');
-            let mut sorted: Vec<_> = items.iter().collect();
-            sorted.sort_by(|a, b| a.0.cmp(b.0));
-            for (index, (key, value)) in sorted.iter().enumerate() {
+            for (index, (key, value)) in node.iter_sorted().enumerate() {
                 if index > 0 {
                     destination.add_byte(b',');
                 }
+                write_break(level, destination, config);
                 destination.add_bytes("\"");
                 destination.add_bytes(key);
-                destination.add_bytes("\":");
-                stringify(value, destination)?;
+                destination.add_bytes(if config.pretty { "\": " } else { "\":" });
+                write_node(value, level + 1, destination, config)?;
+            }
+            if !items.is_empty() {
+                write_break(level - 1, destination, config);
             }
             destination.add_byte(b'}');
         }
+        Node::Raw(_) => write_node(&node.decode_raw()?, level, destination, config)?,
         Node::None => {
             destination.add_bytes("null");
         }
@@ -57,9 +126,84 @@ pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(),
     Ok(())
 }
 
+/// Converts a borrowed node tree to JSON without building an owned [`Node`] first.
+///
+/// The output is the same as [`stringify`] gives for `node.to_node()`, so
+/// zero-copy pipelines can convert large documents while holding little more
+/// than the input in memory.
+///
+/// # Arguments
+/// * `node` - The borrowed tree to convert
+/// * `destination` - The destination to write the JSON output to
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_borrowed, to_json_borrowed, BufferDestination};
+///
+/// let tree = parse_borrowed(b"d4:spaml1:ai1eee").unwrap();
+/// let mut destination = BufferDestination::new();
+/// to_json_borrowed(&tree, &mut destination);
+/// assert_eq!(destination.to_string(), r#"{"spam":["a",1]}"#);
+/// ```
+pub fn stringify_borrowed(node: &BorrowedNode, destination: &mut dyn IDestination) {
+    match node {
+        BorrowedNode::Integer(value) => {
+            destination.add_bytes(&value.to_string());
+        }
+        BorrowedNode::Bytes(bytes) => {
+            destination.add_byte(b'"');
+            escape_bytes(bytes, destination);
+            destination.add_byte(b'"');
+        }
+        BorrowedNode::List(items) => {
+            destination.add_byte(b'[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    destination.add_byte(b',');
+                }
+                stringify_borrowed(item, destination);
+            }
+            destination.add_byte(b']');
+        }
+        BorrowedNode::Dictionary(items) => {
+            destination.add_byte(b'{');
+            let mut sorted: Vec<_> = items.iter().collect();
+            sorted.sort_by_key(|(key, _)| **key);
+            for (index, (key, value)) in sorted.iter().enumerate() {
+                if index > 0 {
+                    destination.add_byte(b',');
+                }
+                // Keys are written as the owned tree holds them, decoded lossily
+                destination.add_bytes("\"");
+                destination.add_bytes(&String::from_utf8_lossy(key));
+                destination.add_bytes("\":");
+                stringify_borrowed(value, destination);
+            }
+            destination.add_byte(b'}');
+        }
+    }
+}
+
+/// Converts a Node structure to JSON like [`stringify`], also reporting anything
+/// the JSON output could not preserve.
+///
+/// # Arguments
+/// * `node` - The Node structure to convert
+/// * `destination` - The destination to write the JSON output to
+///
+/// # Returns
+/// * `Ok(ConversionReport)` - What was lost; check `is_lossless()` to refuse lossy output
+/// * `Err(String)` - The node could not be converted at all
+pub fn stringify_with_report(node: &Node, destination: &mut dyn IDestination) -> Result<ConversionReport, String> {
+    let report = analyze(node, Format::Json);
+    stringify(node, destination)?;
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::BinaryEncoding;
     use crate::io::destinations::buffer::Buffer;
 
     #[test]
@@ -94,7 +238,7 @@ mod tests {
     #[test]
     fn stringify_dictionary_works() {
         let mut destination = Buffer::new();
-        let mut dict = std::collections::HashMap::new();
+        let mut dict = crate::HashMap::new();
         dict.insert("key1".to_string(), Node::Integer(1));
         dict.insert("key2".to_string(), Node::Str("value".to_string()));
         stringify(&Node::Dictionary(dict), &mut destination).unwrap();
@@ -107,4 +251,63 @@ mod tests {
         stringify(&Node::None, &mut destination).unwrap();
         assert_eq!(destination.to_string(), "null");
     }
+
+    #[test]
+    fn stringify_with_report_flags_non_ascii_strings() {
+        let mut destination = Buffer::new();
+        let node = Node::List(vec![Node::Str("caf\u{e9}".to_string()), Node::None]);
+        let report = stringify_with_report(&node, &mut destination).unwrap();
+        assert_eq!(destination.to_string(), "[\"caf\\u00c3\\u00a9\",null]");
+        assert!(!report.is_lossless());
+        assert_eq!(report.losses[0].to_string(), "binary string mangled at '0'");
+        assert_eq!(report.losses.len(), 1);
+    }
+
+    #[test]
+    fn stringify_borrowed_matches_owned_conversion() {
+        use crate::parser::borrowed::parse_borrowed;
+        for data in [
+            &b"i-42e"[..],
+            b"4:spam",
+            b"3:\xff\x00\"",
+            b"le",
+            b"de",
+            b"d1:ali1e5:helloe1:bi2ee",
+            b"ld2:\xc3\xa9i1eeli1ei2eee",
+        ] {
+            let tree = parse_borrowed(data).unwrap();
+            let mut borrowed = Buffer::new();
+            stringify_borrowed(&tree, &mut borrowed);
+            let mut owned = Buffer::new();
+            stringify(&tree.to_node(), &mut owned).unwrap();
+            assert_eq!(borrowed.to_string(), owned.to_string(), "{:?}", data);
+        }
+    }
+
+    #[test]
+    fn stringify_decodes_raw_nodes() {
+        let mut destination = Buffer::new();
+        let node = Node::List(vec![Node::raw(b"d1:ai1ee".to_vec()).unwrap()]);
+        stringify(&node, &mut destination).unwrap();
+        assert_eq!(destination.to_string(), "[{\"a\":1}]");
+    }
+
+    #[test]
+    fn stringify_with_config_writes_pretty_json() {
+        let node = Node::List(vec![
+            Node::List(vec![]),
+            Node::Dictionary(crate::HashMap::new()),
+            Node::Bytes(b"\xff\x00".to_vec()),
+            Node::List(vec![Node::Integer(1)]),
+        ]);
+        let config = EncoderConfig::new().with_pretty(true).with_indent(4).with_binary(BinaryEncoding::Base64);
+        let mut destination = Buffer::new();
+        stringify_with_config(&node, &mut destination, &config).unwrap();
+        assert_eq!(destination.to_string(), "[\n    [],\n    {},\n    \"/wA=\",\n    [\n        1\n    ]\n]");
+
+        // Without pretty output the indent has no effect
+        let mut destination = Buffer::new();
+        stringify_with_config(&node, &mut destination, &config.with_pretty(false)).unwrap();
+        assert_eq!(destination.to_string(), "[[],{},\"/wA=\",[1]]");
+    }
 }
@@ -2,6 +2,12 @@
 /// Provides different formatting options for serializing bencode data.
 pub mod default;
 
+/// Module encoding node trees in canonical form, including pre-encoded values.
+pub mod canonical;
+
+/// Module writing an indented listing of encoded data with byte offsets.
+pub mod tree;
+
 /// Module for converting bencode data structures into JSON format.
 /// Enables interoperability with JSON-based systems and tools.
 #[cfg(feature = "json")]
@@ -22,4 +28,9 @@ pub mod xml;
 #[cfg(feature = "toml")]
 pub mod toml;
 
-mod common;
+/// Module reporting what a conversion to a text format could not preserve.
+#[cfg(any(feature = "json", feature = "yaml", feature = "xml", feature = "toml"))]
+pub mod report;
+
+#[cfg(any(feature = "json", feature = "yaml", feature = "xml", feature = "toml"))]
+pub(crate) mod common;
@@ -0,0 +1,237 @@
+//! Fidelity reports for the text format converters.
+//!
+//! None of JSON, YAML, XML or TOML can represent every bencode tree exactly. The
+//! `stringify_with_report` variant of each converter returns a [`ConversionReport`]
+//! listing every place where the output does not faithfully round trip, so
+//! automated pipelines can refuse lossy conversions instead of silently
+//! producing damaged output.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use core::fmt;
+
+use crate::HashMap;
+use crate::nodes::node::Node;
+use crate::nodes::string_kind::StringKind;
+use crate::parser::extract::PATH_SEPARATOR;
+
+/// Target format of a conversion, each with its own fidelity rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    /// JSON: keys are sorted, None becomes `null`
+    Json,
+    /// YAML: keys are sorted, None has no representation
+    Yaml,
+    /// XML: keys are sorted, None is omitted
+    Xml,
+    /// TOML: plain values are written before tables, None has no representation
+    Toml,
+}
+
+/// One way in which a conversion did not preserve the source tree.
+///
+/// Each loss carries the key path of the affected node (segments joined by `.`,
+/// list elements by index, the root being the empty path).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Loss {
+    /// A string holding non-ASCII (possibly binary) data was escaped byte by
+    /// byte and will not read back as the same bytes
+    BinaryMangled {
+        /// Path of the string
+        path: String,
+    },
+    /// A dictionary's entries were not written in key order
+    OrderLost {
+        /// Path of the dictionary
+        path: String,
+    },
+    /// A None node has no representation in the target format and was skipped
+    /// or replaced by a placeholder
+    NoneSkipped {
+        /// Path of the None node
+        path: String,
+    },
+}
+
+impl Loss {
+    /// Returns the key path of the node affected by this loss
+    pub fn path(&self) -> &str {
+        match self {
+            Loss::BinaryMangled { path } | Loss::OrderLost { path } | Loss::NoneSkipped { path } => path,
+        }
+    }
+}
+
+impl fmt::Display for Loss {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self {
+            Loss::BinaryMangled { .. } => "binary string mangled",
+            Loss::OrderLost { .. } => "dictionary order lost",
+            Loss::NoneSkipped { .. } => "none value skipped",
+        };
+        write!(f, "{} at '{}'", what, self.path())
+    }
+}
+
+/// Everything that was lossy about one conversion.
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_bytes, to_json_with_report, to_toml_with_report, BufferDestination};
+///
+/// let node = parse_bytes(b"d1:ad1:xi1ee1:bi2ee").unwrap();
+/// let report = to_json_with_report(&node, &mut BufferDestination::new()).unwrap();
+/// assert!(report.is_lossless());
+///
+/// let report = to_toml_with_report(&node, &mut BufferDestination::new()).unwrap();
+/// assert_eq!(report.losses[0].to_string(), "dictionary order lost at ''");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConversionReport {
+    /// Each loss found, in depth-first order
+    pub losses: Vec<Loss>,
+}
+
+impl ConversionReport {
+    /// Returns true if the output represents the source tree exactly
+    pub fn is_lossless(&self) -> bool {
+        self.losses.is_empty()
+    }
+}
+
+/// Returns true if a string cannot be written faithfully by `escape_string`,
+/// which escapes non-ASCII text byte by byte
+fn is_mangled(value: &str) -> bool {
+    StringKind::of_str(value) != StringKind::Ascii
+}
+
+/// Returns true if a TOML dictionary entry is written as a table or array of tables
+fn is_toml_table(value: &Node) -> bool {
+    match value {
+        Node::Dictionary(_) => true,
+        Node::List(items) => items.iter().all(|item| matches!(item, Node::Dictionary(_))),
+        _ => false,
+    }
+}
+
+/// Returns true if `format` writes the entries of `dict` out of key order
+fn order_lost(dict: &HashMap<String, Node>, format: Format) -> bool {
+    match format {
+        Format::Json | Format::Yaml | Format::Xml => false,
+        Format::Toml => {
+            // Tables come after plain values, so order survives only if every
+            // table key sorts after every plain key
+            let last_plain = dict.iter().filter(|(_, v)| !is_toml_table(v)).map(|(k, _)| k).max();
+            let first_table = dict.iter().filter(|(_, v)| is_toml_table(v)).map(|(k, _)| k).min();
+            matches!((last_plain, first_table), (Some(plain), Some(table)) if table < plain)
+        }
+    }
+}
+
+/// Appends a segment to a key path
+fn child_path(path: &str, segment: &str) -> String {
+    if path.is_empty() {
+        segment.to_string()
+    } else {
+        format!("{}{}{}", path, PATH_SEPARATOR, segment)
+    }
+}
+
+/// Records the losses for `node` and its children, depth first
+fn collect(node: &Node, path: &str, format: Format, losses: &mut Vec<Loss>) {
+    match node {
+        Node::Integer(_) | Node::BigInteger(_) => {}
+        Node::Str(value) => {
+            if is_mangled(value) {
+                losses.push(Loss::BinaryMangled { path: path.to_string() });
+            }
+        }
+        Node::Bytes(_) => losses.push(Loss::BinaryMangled { path: path.to_string() }),
+        Node::List(items) => {
+            for (index, item) in items.iter().enumerate() {
+                collect(item, &child_path(path, &index.to_string()), format, losses);
+            }
+        }
+        Node::Dictionary(dict) => {
+            if order_lost(dict, format) {
+                losses.push(Loss::OrderLost { path: path.to_string() });
+            }
+            for (key, value) in node.iter_sorted() {
+                collect(value, &child_path(path, key), format, losses);
+            }
+        }
+        Node::Raw(_) => {
+            if let Ok(decoded) = node.decode_raw() {
+                collect(&decoded, path, format, losses);
+            }
+        }
+        Node::None => {
+            if format != Format::Json {
+                losses.push(Loss::NoneSkipped { path: path.to_string() });
+            }
+        }
+    }
+}
+
+/// Works out what converting `node` to `format` will lose.
+pub(crate) fn analyze(node: &Node, format: Format) -> ConversionReport {
+    let mut losses = Vec::new();
+    collect(node, "", format, &mut losses);
+    ConversionReport { losses }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::default::parse_bytes;
+
+    #[test]
+    fn plain_ascii_tree_is_lossless_in_json_and_yaml() {
+        let node = parse_bytes(b"d1:ali1e2:abe1:bd1:ci3eee").unwrap();
+        assert!(analyze(&node, Format::Json).is_lossless());
+        assert!(analyze(&node, Format::Yaml).is_lossless());
+    }
+
+    #[test]
+    fn non_ascii_strings_are_reported_with_their_path() {
+        let node = parse_bytes(b"d4:infod4:name2:\xc3\xa9e6:piecesl1:a1:\xffee").unwrap();
+        let report = analyze(&node, Format::Json);
+        let paths: Vec<&str> = report.losses.iter().map(Loss::path).collect();
+        assert_eq!(paths, vec!["info.name", "pieces.1"]);
+        assert!(matches!(report.losses[0], Loss::BinaryMangled { .. }));
+    }
+
+    #[test]
+    fn xml_keeps_order_of_dictionaries() {
+        let node = parse_bytes(b"d1:ad1:xi1ee1:bi2ee").unwrap();
+        assert!(analyze(&node, Format::Xml).is_lossless());
+    }
+
+    #[test]
+    fn toml_loses_order_when_tables_sort_before_values() {
+        let lost = parse_bytes(b"d1:ad1:xi1ee1:bi2ee").unwrap();
+        assert_eq!(analyze(&lost, Format::Toml).losses, vec![Loss::OrderLost { path: String::new() }]);
+        let kept = parse_bytes(b"d1:ai2e1:bd1:xi1eee").unwrap();
+        assert!(analyze(&kept, Format::Toml).is_lossless());
+    }
+
+    #[test]
+    fn none_is_only_representable_in_json() {
+        let node = Node::List(vec![Node::Integer(1), Node::None]);
+        assert!(analyze(&node, Format::Json).is_lossless());
+        for format in [Format::Yaml, Format::Xml, Format::Toml] {
+            assert_eq!(analyze(&node, format).losses, vec![Loss::NoneSkipped { path: "1".to_string() }]);
+        }
+    }
+
+    #[test]
+    fn losses_display_their_kind_and_path() {
+        let loss = Loss::NoneSkipped { path: "a.0".to_string() };
+        assert_eq!(loss.to_string(), "none value skipped at 'a.0'");
+    }
+}
@@ -17,8 +17,6 @@
 #[cfg(not(feature = "std"))]
 use alloc::collections::BTreeMap;
 #[cfg(not(feature = "std"))]
-use alloc::collections::BTreeMap as HashMap;
-#[cfg(not(feature = "std"))]
 use alloc::{
     format,
     string::{String, ToString},
@@ -26,11 +24,15 @@ use alloc::{
 };
 
 #[cfg(feature = "std")]
-use std::collections::{BTreeMap, HashMap};
+use std::collections::BTreeMap;
 
+use crate::HashMap;
 use crate::Node;
+use crate::config::EncoderConfig;
+use crate::nodes::integer::Integer;
 use crate::io::traits::IDestination;
-use crate::stringify::common::escape_string;
+use crate::stringify::common::{escape_string, write_binary};
+use crate::stringify::report::{ConversionReport, Format, analyze};
 
 /// Converts a Node structure to a TOML formatted string
 ///
@@ -41,8 +43,42 @@ use crate::stringify::common::escape_string;
 /// * `Ok(())` if successful
 /// * `Err(String)` if the root node is not an Object
 pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(), String> {
+    stringify_with_config(node, destination, &EncoderConfig::new())
+}
+
+/// Converts a Node structure to TOML like [`stringify`], writing binary
+/// strings as `config.binary` asks.
+///
+/// TOML tables are always written with their keys sorted and the layout
+/// options only apply to JSON and YAML, so the binary encoding is the one
+/// setting used here.
+///
+/// # Arguments
+/// * `node` - The root Node to convert
+/// * `destination` - The destination to write the TOML string to
+/// * `config` - Encoder configuration giving the binary encoding
+///
+/// # Returns
+/// * `Ok(())` if successful
+/// * `Err(String)` if the root node is not an Object
+///
+/// # Example
+/// ```
+/// use bencode_lib::{BinaryEncoding, BufferDestination, EncoderConfig, parse_bytes, to_toml_with_config};
+///
+/// let node = parse_bytes(b"d6:pieces2:\x01\xfee").unwrap();
+/// let config = EncoderConfig::new().with_binary(BinaryEncoding::Hex);
+/// let mut destination = BufferDestination::new();
+/// to_toml_with_config(&node, &mut destination, &config).unwrap();
+/// assert_eq!(destination.to_string(), "pieces = \"01fe\"\n");
+/// ```
+pub fn stringify_with_config(node: &Node, destination: &mut dyn IDestination, config: &EncoderConfig) -> Result<(), String> {
+    // Raw values must be decoded up front so nested dictionaries become tables
+    if node.contains_raw() {
+        return stringify_with_config(&node.expand_raw()?, destination, config);
+    }
     match node {
-        Node::Dictionary(dict) => stringify_object(dict, "", destination),
+        Node::Dictionary(dict) => stringify_object(dict, "", destination, config),
         _ => Err("TOML format requires a dictionary at the root level".to_string()),
     }
 }
@@ -53,6 +89,7 @@ pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(),
 /// * `value` - The Node to convert
 /// * `add_cr` - Whether to add a carriage return after the value
 /// * `destination` - The destination to write to
+/// * `config` - Encoder configuration giving the binary encoding
 ///
 /// # Returns
 /// * `Ok(())` if successful
@@ -61,12 +98,21 @@ fn stringify_value(
     value: &Node,
     add_cr: bool,
     destination: &mut dyn IDestination,
+    config: &EncoderConfig,
 ) -> Result<(), String> {
     match value {
         Node::Str(s) => stringify_str(s, destination),
+        Node::Bytes(bytes) => {
+            destination.add_bytes("\"");
+            write_binary(bytes, config.binary, destination);
+            destination.add_bytes("\"");
+        }
         Node::Integer(value) => stringify_number(value, destination),
-        Node::List(items) => stringify_array(items, destination)?,
+        // TOML integers are limited to 64 bits, so the digits are written as a string
+        Node::BigInteger(digits) => stringify_str(digits, destination),
+        Node::List(items) => stringify_array(items, destination, config)?,
         Node::None => destination.add_bytes("null"),
+        Node::Raw(_) => return stringify_value(&value.decode_raw()?, add_cr, destination, config),
         Node::Dictionary(_) => return Ok(()), // Handled separately for table syntax
     }
     if add_cr {
@@ -91,7 +137,7 @@ fn stringify_str(s: &str, destination: &mut dyn IDestination) {
 /// # Arguments
 /// * `value` - The numeric value to convert
 /// * `destination` - The destination to write to
-fn stringify_number(value: &i64, destination: &mut dyn IDestination) {
+fn stringify_number(value: &Integer, destination: &mut dyn IDestination) {
     destination.add_bytes(&value.to_string())
 }
 
@@ -101,11 +147,12 @@ fn stringify_number(value: &i64, destination: &mut dyn IDestination) {
 /// # Arguments
 /// * `items` - The vector of Nodes to convert
 /// * `destination` - The destination to write to
+/// * `config` - Encoder configuration giving the binary encoding
 ///
 /// # Returns
 /// * `Ok(())` if successful
 /// * `Err(String)` if the array contains mixed types
-fn stringify_array(items: &Vec<Node>, destination: &mut dyn IDestination) -> Result<(), String> {
+fn stringify_array(items: &Vec<Node>, destination: &mut dyn IDestination, config: &EncoderConfig) -> Result<(), String> {
     let first_type = get_node_type(&items[0]);
 
     for item in items {
@@ -119,7 +166,7 @@ fn stringify_array(items: &Vec<Node>, destination: &mut dyn IDestination) -> Res
         if i > 0 {
             destination.add_bytes(", ");
         }
-        stringify_value(item, false, destination)?;
+        stringify_value(item, false, destination, config)?;
     }
     destination.add_bytes("]");
     Ok(())
@@ -135,10 +182,11 @@ fn stringify_array(items: &Vec<Node>, destination: &mut dyn IDestination) -> Res
 /// A string representing the Node type
 fn get_node_type(node: &Node) -> &'static str {
     match node {
-        Node::Str(_) => "string",
+        Node::Str(_) | Node::Bytes(_) | Node::BigInteger(_) => "string",
         Node::Integer(_) => "integer",
         Node::List(_) => "list",
         Node::Dictionary(_) => "object",
+        Node::Raw(_) => "raw",
         Node::None => "null",
     }
 }
@@ -148,6 +196,7 @@ fn get_node_type(node: &Node) -> &'static str {
 /// # Arguments
 /// * `prefix` - The current table path prefix
 /// * `destination` - The destination to write to
+/// * `config` - Encoder configuration giving the binary encoding
 /// * `is_first` - Whether this is the first entry in a table
 /// * `key` - The key of the pair
 /// * `value` - The value Node
@@ -157,8 +206,9 @@ fn get_node_type(node: &Node) -> &'static str {
 fn stringify_key_value_pair(
     prefix: &str,
     destination: &mut dyn IDestination,
+    config: &EncoderConfig,
     is_first: &mut bool,
-    key: &String,
+    key: &str,
     value: &Node,
 ) -> Result<(), String> {
     if !prefix.is_empty() && *is_first {
@@ -170,7 +220,7 @@ fn stringify_key_value_pair(
 
     destination.add_bytes(key);
     destination.add_bytes(" = ");
-    stringify_value(value, true, destination)?;
+    stringify_value(value, true, destination, config)?;
 
     Ok(())
 }
@@ -187,6 +237,7 @@ fn stringify_key_value_pair(
 /// * `dict` - The HashMap to convert containing key-value pairs
 /// * `prefix` - The current table path prefix for nested structures
 /// * `destination` - The destination to write the formatted TOML output
+/// * `config` - Encoder configuration giving the binary encoding
 ///
 /// # Returns
 /// * `Ok(())` if conversion was successful
@@ -195,6 +246,7 @@ fn stringify_object(
     dict: &HashMap<String, Node>,
     prefix: &str,
     destination: &mut dyn IDestination,
+    config: &EncoderConfig,
 ) -> Result<(), String> {
     if dict.is_empty() {
         return Ok(());
@@ -205,12 +257,17 @@ fn stringify_object(
     let tables: BTreeMap<_, _> = tables_dict.iter().map(|(k, v)| (k, *v)).collect();
     let array_tables: BTreeMap<_, _> = array_tables_dict.iter().map(|(k, v)| (k, *v)).collect();
 
-    process_key_value_pairs(&dict_sorted, prefix, destination)?;
-    process_nested_tables(&tables, prefix, destination)?;
-    process_array_tables(&array_tables, prefix, destination)?;
+    process_key_value_pairs(&dict_sorted, prefix, destination, config)?;
+    process_nested_tables(&tables, prefix, destination, config)?;
+    process_array_tables(&array_tables, prefix, destination, config)?;
 
     Ok(())
 }
+/// Regular (nested) tables found in a dictionary, keyed by table name
+type TablesInDict<'a> = HashMap<String, &'a HashMap<String, Node>>;
+/// Array tables found in a dictionary, keyed by table name
+type ArrayTablesInDict<'a> = HashMap<String, &'a Vec<Node>>;
+
 /// Gets tables and array tables from a dictionary by categorizing its entries
 /// This helper function processes a dictionary and identifies table entries,
 /// separating them into regular tables and array tables.
@@ -222,12 +279,7 @@ fn stringify_object(
 /// A tuple containing:
 /// * HashMap of regular tables
 /// * HashMap of array tables
-fn get_tables_in_dict(
-    dict: &HashMap<String, Node>,
-) -> (
-    HashMap<String, &HashMap<String, Node>>,
-    HashMap<String, &Vec<Node>>,
-) {
+fn get_tables_in_dict(dict: &HashMap<String, Node>) -> (TablesInDict<'_>, ArrayTablesInDict<'_>) {
     let mut tables = HashMap::new();
     let mut array_tables = HashMap::new();
 
@@ -254,6 +306,7 @@ fn get_tables_in_dict(
 /// * `dict_sorted` - BTreeMap containing sorted key-value pairs to process
 /// * `prefix` - Current table path prefix for nested structures
 /// * `destination` - Destination to write the formatted TOML output
+/// * `config` - Encoder configuration giving the binary encoding
 /// * `is_first` - Mutable flag indicating if this is the first entry in current table
 ///
 /// # Returns
@@ -263,21 +316,20 @@ fn process_key_value_pairs<'a>(
     dict_sorted: &BTreeMap<&'a String, &'a Node>,
     prefix: &str,
     destination: &mut dyn IDestination,
+    config: &EncoderConfig,
 ) -> Result<(), String> {
     for (key, value) in dict_sorted {
         match value {
             Node::Dictionary(_) => {
                 continue;
             }
-            Node::List(items) => {
-                if items.iter().all(|item| matches!(item, Node::Dictionary(_))) {
-                    continue;
-                }
+            Node::List(items) if items.iter().all(|item| matches!(item, Node::Dictionary(_))) => {
+                continue;
             }
             _ => {}
         }
         let mut is_first = true;
-        stringify_key_value_pair(prefix, destination, &mut is_first, key, value)?;
+        stringify_key_value_pair(prefix, destination, config, &mut is_first, key, value)?;
     }
     Ok(())
 }
@@ -290,6 +342,7 @@ fn process_key_value_pairs<'a>(
 /// * `tables` - BTreeMap containing the nested table structures to process
 /// * `prefix` - Current table path prefix for nested structures
 /// * `destination` - Destination to write the formatted TOML output
+/// * `config` - Encoder configuration giving the binary encoding
 ///
 /// # Returns
 /// * `Ok(())` if successful
@@ -298,10 +351,11 @@ fn process_nested_tables(
     tables: &BTreeMap<&String, &HashMap<String, Node>>,
     prefix: &str,
     destination: &mut dyn IDestination,
+    config: &EncoderConfig,
 ) -> Result<(), String> {
     for (key, nested) in tables {
         let new_prefix = calculate_prefix(prefix, key);
-        stringify_object(nested, &new_prefix, destination)?;
+        stringify_object(nested, &new_prefix, destination, config)?;
     }
     Ok(())
 }
@@ -314,6 +368,7 @@ fn process_nested_tables(
 /// * `array_tables` - BTreeMap containing the array table structures to process
 /// * `prefix` - Current table path prefix for nested structures
 /// * `destination` - Destination to write the formatted TOML output
+/// * `config` - Encoder configuration giving the binary encoding
 ///
 /// # Returns
 /// * `Ok(())` if successful
@@ -322,6 +377,7 @@ fn process_array_tables(
     array_tables: &BTreeMap<&String, &Vec<Node>>,
     prefix: &str,
     destination: &mut dyn IDestination,
+    config: &EncoderConfig,
 ) -> Result<(), String> {
     for (key, items) in array_tables {
         for item in &**items {
@@ -330,7 +386,7 @@ fn process_array_tables(
                 destination.add_bytes("[[");
                 destination.add_bytes(&new_prefix);
                 destination.add_bytes("]]\n");
-                process_nested_array_table(nested, &new_prefix, destination)?;
+                process_nested_array_table(nested, &new_prefix, destination, config)?;
             }
         }
     }
@@ -346,6 +402,7 @@ fn process_array_tables(
 /// * `nested` - HashMap containing the nested array table key-value pairs
 /// * `new_prefix` - Current table path prefix for the nested structure
 /// * `destination` - Destination to write the formatted TOML output
+/// * `config` - Encoder configuration giving the binary encoding
 ///
 /// # Returns
 /// * `Ok(())` if successful
@@ -354,10 +411,11 @@ fn process_nested_array_table(
     nested: &HashMap<String, Node>,
     new_prefix: &str,
     destination: &mut dyn IDestination,
+    config: &EncoderConfig,
 ) -> Result<(), String> {
     let nested_sorted: BTreeMap<_, _> = nested.iter().collect();
-    process_simple_values(&nested_sorted, destination)?;
-    process_nested_objects(&nested_sorted, new_prefix, destination)?;
+    process_simple_values(&nested_sorted, destination, config)?;
+    process_nested_objects(&nested_sorted, new_prefix, destination, config)?;
     Ok(())
 }
 
@@ -367,6 +425,7 @@ fn process_nested_array_table(
 /// # Arguments
 /// * `nested_sorted` - BTreeMap containing sorted key-value pairs to process
 /// * `destination` - Destination to write the formatted TOML output
+/// * `config` - Encoder configuration giving the binary encoding
 ///
 /// # Returns
 /// * `Ok(())` if successful
@@ -374,29 +433,23 @@ fn process_nested_array_table(
 fn process_simple_values(
     nested_sorted: &BTreeMap<&String, &Node>,
     destination: &mut dyn IDestination,
+    config: &EncoderConfig,
 ) -> Result<(), String> {
     for (inner_key, inner_value) in nested_sorted {
         match inner_value {
             Node::Dictionary(_) => {}
 
-            Node::Integer(_) | Node::Str(_) => {
+            Node::Integer(_) | Node::Str(_) | Node::Bytes(_) => {
                 let mut is_first = true;
-                stringify_key_value_pair("", destination, &mut is_first, inner_key, inner_value)?;
+                stringify_key_value_pair("", destination, config, &mut is_first, inner_key, inner_value)?;
             }
-            Node::List(items) => {
+            Node::List(items)
                 if items
                     .iter()
-                    .all(|item| matches!(item, Node::Integer(_) | Node::Str(_)))
-                {
-                    let mut is_first = true;
-                    stringify_key_value_pair(
-                        "",
-                        destination,
-                        &mut is_first,
-                        inner_key,
-                        inner_value,
-                    )?;
-                }
+                    .all(|item| matches!(item, Node::Integer(_) | Node::Str(_) | Node::Bytes(_))) =>
+            {
+                let mut is_first = true;
+                stringify_key_value_pair("", destination, config, &mut is_first, inner_key, inner_value)?;
             }
             _ => {}
         }
@@ -411,6 +464,7 @@ fn process_simple_values(
 /// * `nested_sorted` - BTreeMap containing sorted key-value pairs with nested structures
 /// * `new_prefix` - Current table path prefix for the nested structure
 /// * `destination` - Destination to write the formatted TOML output
+/// * `config` - Encoder configuration giving the binary encoding
 ///
 /// # Returns
 /// * `Ok(())` if successful
@@ -419,12 +473,13 @@ fn process_nested_objects(
     nested_sorted: &BTreeMap<&String, &Node>,
     new_prefix: &str,
     destination: &mut dyn IDestination,
+    config: &EncoderConfig,
 ) -> Result<(), String> {
     for (inner_key, inner_value) in nested_sorted {
         match inner_value {
             Node::Dictionary(inner_nested) => {
                 let inner_prefix = format!("{}.{}", new_prefix, inner_key);
-                stringify_object(inner_nested, &inner_prefix, destination)?;
+                stringify_object(inner_nested, &inner_prefix, destination, config)?;
             }
             Node::List(inner_items)
                 if inner_items
@@ -434,7 +489,7 @@ fn process_nested_objects(
                 for inner_item in inner_items {
                     if let Node::Dictionary(deepest) = inner_item {
                         let inner_prefix = format!("{}.{}", new_prefix, inner_key);
-                        stringify_object(&deepest, &inner_prefix, destination)?;
+                        stringify_object(deepest, &inner_prefix, destination, config)?;
                     }
                 }
             }
@@ -454,20 +509,36 @@ fn process_nested_objects(
 /// A new string containing the combined prefix path:
 /// - If the prefix is empty, returns the key as-is
 /// - If the prefix exists, returns "prefix.key"
-fn calculate_prefix(prefix: &str, key: &String) -> String {
-    let new_prefix = if prefix.is_empty() {
+fn calculate_prefix(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
         key.to_string()
     } else {
         format!("{}.{}", prefix, key)
-    };
-    new_prefix
+    }
+}
+
+/// Converts a Node structure to TOML like [`stringify`], also reporting anything
+/// the TOML output could not preserve.
+///
+/// # Arguments
+/// * `node` - The Node structure to convert
+/// * `destination` - The destination to write the TOML output to
+///
+/// # Returns
+/// * `Ok(ConversionReport)` - What was lost; check `is_lossless()` to refuse lossy output
+/// * `Err(String)` - The node could not be converted at all
+pub fn stringify_with_report(node: &Node, destination: &mut dyn IDestination) -> Result<ConversionReport, String> {
+    let report = analyze(node, Format::Toml);
+    stringify(node, destination)?;
+    Ok(report)
 }
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::BufferDestination;
     use crate::nodes::node::make_node;
-    use std::collections::HashMap;
+    use crate::HashMap;
 
     #[test]
     fn test_stringify_string() {
@@ -591,4 +662,14 @@ mod tests {
         assert!(result.is_ok());
         assert_eq!(destination.to_string(), "");
     }
+
+    #[test]
+    fn test_config_encodes_binary_strings() {
+        let mut dict = HashMap::new();
+        dict.insert("hashes".to_string(), Node::List(vec![Node::Bytes(vec![0xff]), Node::Str("ok".to_string())]));
+        let config = EncoderConfig::new().with_binary(crate::BinaryEncoding::Base64);
+        let mut destination = BufferDestination::new();
+        stringify_with_config(&Node::Dictionary(dict), &mut destination, &config).unwrap();
+        assert_eq!(destination.to_string(), "hashes = [\"/w==\", \"ok\"]\n");
+    }
 }
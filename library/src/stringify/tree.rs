@@ -0,0 +1,301 @@
+//! Indented tree listing of encoded bencode for inspection and debugging.
+//!
+//! [`print_tree`] reads the input with the tokenizer and writes one line per
+//! value: its byte offset, the length of its encoding, and the value itself,
+//! indented by depth under its dictionary key. Long strings are cut short and
+//! strings that are not UTF-8 are shown in hex, so the listing stays readable
+//! for binary fields such as a torrent's `pieces`. [`Node::to_pretty_string`]
+//! lays out an already parsed tree the same way, without the offsets.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use core::fmt::Write;
+
+use crate::error::messages::ERR_TRAILING_DATA;
+use crate::error::parse::ParseError;
+use crate::io::traits::IDestination;
+use crate::nodes::node::Node;
+use crate::parser::tokenizer::{Token, TokenKind, Tokenizer};
+
+/// Layout options for [`print_tree`] and [`Node::to_pretty_string`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TreeOptions {
+    /// Characters of a string (or hex digits of a binary string) shown before
+    /// it is cut short (default: 32)
+    pub max_value_length: usize,
+    /// Spaces of indentation per level of nesting (default: 2)
+    pub indent: usize,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TreeOptions {
+    /// Create options with the default layout
+    pub const fn new() -> Self {
+        Self {
+            max_value_length: 32,
+            indent: 2,
+        }
+    }
+
+    /// Set how much of a string is shown
+    pub const fn with_max_value_length(mut self, max_value_length: usize) -> Self {
+        self.max_value_length = max_value_length;
+        self
+    }
+
+    /// Set the indentation per level of nesting
+    pub const fn with_indent(mut self, indent: usize) -> Self {
+        self.indent = indent;
+        self
+    }
+}
+
+/// Describes a byte string: quoted and escaped if UTF-8, otherwise in hex
+fn describe_bytes(bytes: &[u8], max_length: usize) -> String {
+    match core::str::from_utf8(bytes) {
+        Ok(text) => {
+            let mut shown: String = text.chars().take(max_length).flat_map(char::escape_debug).collect();
+            if text.chars().count() > max_length {
+                shown.push_str("...");
+            }
+            format!("\"{}\"", shown)
+        }
+        Err(_) => {
+            let mut shown = String::new();
+            for byte in bytes.iter().take(max_length.div_ceil(2)) {
+                let _ = write!(shown, "{:02x}", byte);
+            }
+            shown.truncate(max_length);
+            if shown.len() < bytes.len() * 2 {
+                shown.push_str("...");
+            }
+            format!("<{} bytes: {}>", bytes.len(), shown)
+        }
+    }
+}
+
+/// Describes a dictionary key, unquoted when it is printable text
+fn describe_key(key: &[u8], max_length: usize) -> String {
+    match core::str::from_utf8(key) {
+        Ok(text) if !text.is_empty() && text.chars().all(|c| c.is_ascii_graphic()) && text.len() <= max_length => {
+            text.to_string()
+        }
+        _ => describe_bytes(key, max_length),
+    }
+}
+
+/// Describes a list of `count` items
+fn describe_list(count: usize) -> String {
+    format!("list ({} item{})", count, if count == 1 { "" } else { "s" })
+}
+
+/// Describes a dictionary of `count` entries
+fn describe_dictionary(count: usize) -> String {
+    format!("dictionary ({} entr{})", count, if count == 1 { "y" } else { "ies" })
+}
+
+/// Appends the lines for `node` and its children to `out`
+pub(crate) fn write_pretty(node: &Node, label: &str, depth: usize, options: &TreeOptions, out: &mut String) {
+    let value = match node {
+        Node::Integer(value) => value.to_string(),
+        Node::BigInteger(digits) => digits.clone(),
+        Node::Str(text) => {
+            let shown = describe_bytes(text.as_bytes(), options.max_value_length);
+            if text.chars().count() > options.max_value_length {
+                format!("{} ({} bytes)", shown, text.len())
+            } else {
+                shown
+            }
+        }
+        Node::Bytes(bytes) => describe_bytes(bytes, options.max_value_length),
+        Node::Raw(bytes) => format!("raw {}", describe_bytes(bytes, options.max_value_length)),
+        Node::List(items) => describe_list(items.len()),
+        Node::Dictionary(entries) => describe_dictionary(entries.len()),
+        Node::None => "none".to_string(),
+    };
+    let _ = writeln!(out, "{:indent$}{}{}", "", label, value, indent = depth * options.indent);
+    match node {
+        Node::List(items) => {
+            for item in items {
+                write_pretty(item, "", depth + 1, options, out);
+            }
+        }
+        Node::Dictionary(_) => {
+            for (key, value) in node.iter_sorted() {
+                let label = describe_key(key.as_bytes(), options.max_value_length) + ": ";
+                write_pretty(value, &label, depth + 1, options, out);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Writes an indented listing of the value in `source`, one line per value.
+///
+/// Each line holds the value's byte offset and encoded length, right aligned,
+/// followed by the value indented by depth and prefixed by its dictionary key.
+/// Nothing is written if the input is malformed.
+///
+/// # Arguments
+/// * `source` - The bencode data
+/// * `destination` - Where to write the listing
+/// * `options` - Truncation and indentation settings
+///
+/// # Returns
+/// * `Ok(())` - The listing was written
+/// * `Err(ParseError)` - If the input is malformed or has trailing data
+///
+/// # Example
+/// ```
+/// use bencode_lib::{print_tree, BufferDestination, TreeOptions};
+///
+/// let mut destination = BufferDestination::new();
+/// print_tree(b"d8:announce3:url4:infod4:name1:xee", &mut destination, &TreeOptions::new()).unwrap();
+/// assert_eq!(destination.to_string(), concat!(
+///     "       0       34  dictionary (2 entries)\n",
+///     "      11        5    announce: \"url\"\n",
+///     "      22       11    info: dictionary (1 entry)\n",
+///     "      29        3      name: \"x\"\n",
+/// ));
+/// ```
+pub fn print_tree(source: &[u8], destination: &mut dyn IDestination, options: &TreeOptions) -> Result<(), ParseError> {
+    let mut tokenizer = Tokenizer::new(source);
+    let tokens = tokenizer.by_ref().collect::<Result<Vec<Token>, _>>()?;
+    if tokenizer.position() != source.len() {
+        return Err(ParseError::new(ERR_TRAILING_DATA, tokenizer.position()));
+    }
+
+    // End offset and child count of each container, at the index of its start token
+    let mut containers = vec![(0, 0); tokens.len()];
+    let mut open: Vec<usize> = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::Key(_) => continue,
+            TokenKind::ListEnd | TokenKind::DictEnd => {
+                if let Some(start) = open.pop() {
+                    containers[start].0 = token.end;
+                }
+                continue;
+            }
+            _ => {}
+        }
+        if let Some(&parent) = open.last() {
+            containers[parent].1 += 1;
+        }
+        if matches!(token.kind, TokenKind::ListStart | TokenKind::DictStart) {
+            open.push(index);
+        }
+    }
+
+    let mut depth = 0;
+    let mut key = None;
+    for (index, token) in tokens.iter().enumerate() {
+        let (end, count) = containers[index];
+        let value = match token.kind {
+            TokenKind::Key(bytes) => {
+                key = Some(bytes);
+                continue;
+            }
+            TokenKind::ListEnd | TokenKind::DictEnd => {
+                depth -= 1;
+                continue;
+            }
+            TokenKind::Integer(value) => value.to_string(),
+            TokenKind::Bytes(bytes) => describe_bytes(bytes, options.max_value_length),
+            TokenKind::ListStart => describe_list(count),
+            TokenKind::DictStart => describe_dictionary(count),
+        };
+        let end = if end > 0 { end } else { token.end };
+        let label = key.take().map(|key| describe_key(key, options.max_value_length) + ": ").unwrap_or_default();
+        destination.add_bytes(&format!(
+            "{:>8} {:>8}  {:indent$}{}{}\n",
+            token.offset,
+            end - token.offset,
+            "",
+            label,
+            value,
+            indent = depth * options.indent
+        ));
+        if matches!(token.kind, TokenKind::ListStart | TokenKind::DictStart) {
+            depth += 1;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+    use crate::io::destinations::buffer::Buffer;
+
+    fn listing(source: &[u8], options: &TreeOptions) -> String {
+        let mut destination = Buffer::new();
+        print_tree(source, &mut destination, options).unwrap();
+        destination.to_string()
+    }
+
+    #[test]
+    fn lists_show_offsets_lengths_and_nesting() {
+        let text = listing(b"li-12eleli1ei2eee", &TreeOptions::new().with_indent(1));
+        let expected = [
+            "       0       17  list (3 items)",
+            "       1        5   -12",
+            "       6        2   list (0 items)",
+            "       8        8   list (2 items)",
+            "       9        3    1",
+            "      12        3    2",
+        ];
+        assert_eq!(text.lines().collect::<Vec<_>>(), expected);
+    }
+
+    #[test]
+    fn long_and_binary_strings_are_cut_short() {
+        let options = TreeOptions::new().with_max_value_length(4);
+        assert_eq!(listing(b"6:abc\"de", &options), "       0        8  \"abc\\\"...\"\n");
+        assert_eq!(listing(b"4:abcd", &options), "       0        6  \"abcd\"\n");
+        assert_eq!(listing(b"3:\xff\x00\x01", &options), "       0        5  <3 bytes: ff00...>\n");
+        assert_eq!(listing(b"d2:\xffzi1ee", &options), concat!(
+            "       0        9  dictionary (1 entry)\n",
+            "       5        3    <2 bytes: ff7a>: 1\n",
+        ));
+    }
+
+    #[test]
+    fn parsed_trees_are_listed_without_offsets() {
+        let node = crate::parse_bytes(b"d8:announce3:url4:infod6:lengthi5e4:name40:abcdefghijklmnopqrstuvwxyz012345678901236:pieces3:\xff\x00\x01ee").unwrap();
+        let options = TreeOptions::new().with_max_value_length(8);
+        let expected = [
+            "dictionary (2 entries)",
+            "  announce: \"url\"",
+            "  info: dictionary (3 entries)",
+            "    length: 5",
+            "    name: \"abcdefgh...\" (40 bytes)",
+            "    pieces: <3 bytes: ff0001>",
+        ];
+        assert_eq!(node.to_pretty_string(&options).lines().collect::<Vec<_>>(), expected);
+        let others = Node::List(vec![Node::None, Node::raw(b"i1e".to_vec()).unwrap(), Node::List(vec![])]);
+        assert_eq!(others.to_pretty_string(&TreeOptions::new()), "list (3 items)\n  none\n  raw \"i1e\"\n  list (0 items)\n");
+    }
+
+    #[test]
+    fn malformed_input_writes_nothing() {
+        let mut destination = Buffer::new();
+        let error = print_tree(b"li1e", &mut destination, &TreeOptions::new()).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::UnterminatedList);
+        let error = print_tree(b"i1ei2e", &mut destination, &TreeOptions::new()).unwrap_err();
+        assert_eq!((error.kind(), error.offset), (ErrorKind::TrailingData, 3));
+        assert_eq!(destination.to_string(), "");
+    }
+}
@@ -1,9 +1,14 @@
 #[cfg(not(feature = "std"))]
-use alloc::string::{String, ToString};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
 
+use crate::config::EncoderConfig;
 use crate::io::traits::IDestination;
 use crate::nodes::node::*;
-use crate::stringify::common::escape_string;
+use crate::stringify::common::{escape_string, write_binary};
+use crate::stringify::report::{ConversionReport, Format, analyze};
 
 /// Converts a bencode Node into XML format and writes it to the given destination.
 /// Each node type is wrapped in appropriate XML tags based on its type.
@@ -12,11 +17,45 @@ use crate::stringify::common::escape_string;
 /// * `node` - The bencode Node to convert
 /// * `destination` - The destination to write the XML output to
 pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(), String> {
+    stringify_with_config(node, destination, &EncoderConfig::new())
+}
+
+/// Converts a bencode Node into XML like [`stringify`], following `config`.
+///
+/// Dictionary items are sorted by key and `binary` chooses how byte strings
+/// that are not UTF-8 are written. The layout options only apply to JSON and YAML.
+///
+/// # Arguments
+/// * `node` - The bencode Node to convert
+/// * `destination` - The destination to write the XML output to
+/// * `config` - Binary encoding of the output
+///
+/// # Example
+/// ```
+/// use bencode_lib::{BinaryEncoding, BufferDestination, EncoderConfig, parse_bytes, to_xml_with_config};
+///
+/// let node = parse_bytes(b"d1:ai1e1:b1:\xffe").unwrap();
+/// let config = EncoderConfig::new().with_binary(BinaryEncoding::Base64);
+/// let mut destination = BufferDestination::new();
+/// to_xml_with_config(&node, &mut destination, &config).unwrap();
+/// assert_eq!(
+///     destination.to_string(),
+///     "<dictionary><item><key>a</key><value><integer>1</integer></value></item>\
+///      <item><key>b</key><value><string>/w==</string></value></item></dictionary>"
+/// );
+/// ```
+pub fn stringify_with_config(node: &Node, destination: &mut dyn IDestination, config: &EncoderConfig) -> Result<(), String> {
     match node {
         Node::Str(value) => {
             // Wrap string value in <string> tags
             destination.add_bytes("<string>");
-            escape_string(&value, destination);
+            escape_string(value, destination);
+            destination.add_bytes("</string>");
+        }
+        Node::Bytes(bytes) => {
+            // Binary strings are written in the configured encoding
+            destination.add_bytes("<string>");
+            write_binary(bytes, config.binary, destination);
             destination.add_bytes("</string>");
         }
         Node::Integer(value) => {
@@ -25,31 +64,53 @@ pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(),
             destination.add_bytes(&value.to_string());
             destination.add_bytes("</integer>");
         }
+        Node::BigInteger(digits) => {
+            destination.add_bytes("<integer>");
+            destination.add_bytes(digits);
+            destination.add_bytes("</integer>");
+        }
         Node::List(items) => {
             // Create a list container and recursively stringify each item
             destination.add_bytes("<list>");
             for item in items {
-                stringify(item, destination)?;
+                stringify_with_config(item, destination, config)?;
             }
             destination.add_bytes("</list>");
         }
-        Node::Dictionary(items) => {
+        Node::Dictionary(_) => {
             // Create a dictionary container with key-value pair items
             destination.add_bytes("<dictionary>");
-            for (key, value) in items {
+            for (key, value) in node.iter_sorted() {
                 destination.add_bytes("<item><key>");
                 destination.add_bytes(key);
                 destination.add_bytes("</key><value>");
-                stringify(value, destination)?;
+                stringify_with_config(value, destination, config)?;
                 destination.add_bytes("</value></item>");
             }
             destination.add_bytes("</dictionary>");
         }
+        Node::Raw(_) => stringify_with_config(&node.decode_raw()?, destination, config)?,
         Node::None => {}
     }
     Ok(())
 }
 
+/// Converts a Node structure to XML like [`stringify`], also reporting anything
+/// the XML output could not preserve.
+///
+/// # Arguments
+/// * `node` - The Node structure to convert
+/// * `destination` - The destination to write the XML output to
+///
+/// # Returns
+/// * `Ok(ConversionReport)` - What was lost; check `is_lossless()` to refuse lossy output
+/// * `Err(String)` - The node could not be converted at all
+pub fn stringify_with_report(node: &Node, destination: &mut dyn IDestination) -> Result<ConversionReport, String> {
+    let report = analyze(node, Format::Xml);
+    stringify(node, destination)?;
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -86,7 +147,7 @@ mod tests {
     #[test]
     fn test_dictionary_node() {
         let mut destination = Buffer::new();
-        let mut dict = std::collections::HashMap::new();
+        let mut dict = crate::HashMap::new();
         dict.insert("key".into(), Node::Str("value".into()));
         stringify(&Node::Dictionary(dict), &mut destination).unwrap();
         assert_eq!(
@@ -101,4 +162,29 @@ mod tests {
         stringify(&Node::None, &mut destination).unwrap();
         assert_eq!(destination.to_string(), "");
     }
+
+    #[test]
+    fn stringify_with_report_flags_skipped_none() {
+        let mut destination = Buffer::new();
+        let report = stringify_with_report(&Node::List(vec![Node::None]), &mut destination).unwrap();
+        assert_eq!(destination.to_string(), "<list></list>");
+        assert_eq!(report.losses[0].to_string(), "none value skipped at '0'");
+    }
+
+    #[test]
+    fn test_config_sorts_items_and_encodes_binary() {
+        let mut dict = crate::HashMap::new();
+        for key in ["c", "a", "b"] {
+            dict.insert(key.into(), Node::Bytes(vec![0xff, key.as_bytes()[0]]));
+        }
+        let config = EncoderConfig::new().with_binary(crate::BinaryEncoding::Hex);
+        let mut destination = Buffer::new();
+        stringify_with_config(&Node::Dictionary(dict), &mut destination, &config).unwrap();
+        assert_eq!(
+            destination.to_string(),
+            "<dictionary><item><key>a</key><value><string>ff61</string></value></item>\
+             <item><key>b</key><value><string>ff62</string></value></item>\
+             <item><key>c</key><value><string>ff63</string></value></item></dictionary>"
+        );
+    }
 }
@@ -8,35 +8,61 @@ use alloc::{
     vec::Vec,
 };
 
+use crate::config::EncoderConfig;
 use crate::io::traits::IDestination;
 use crate::nodes::node::*;
-use crate::stringify::common::escape_string;
+use crate::stringify::common::{escape_string, write_binary};
+use crate::stringify::report::{ConversionReport, Format, analyze};
 
 /// Writes the specified number of indentation spaces to the destination.
 ///
 /// # Arguments
-/// * `level` - The indentation level (number of 2-space indents)
+/// * `level` - The indentation level (number of indents of `config.indent` spaces)
 /// * `destination` - The output destination to write to
-fn write_indent(level: usize, destination: &mut dyn IDestination) {
+/// * `config` - Encoder configuration giving the indent width
+fn write_indent(level: usize, destination: &mut dyn IDestination, config: &EncoderConfig) {
     for _ in 1..level {
-        destination.add_bytes("  ");
+        for _ in 0..config.indent {
+            destination.add_byte(b' ');
+        }
     }
 }
 
+/// Returns true if a key reads back unchanged when written without quotes:
+/// plain keys are trimmed, and the other characters checked open quoted keys,
+/// sequences, flow collections or comments, or end the key early.
+fn is_plain_key(key: &str) -> bool {
+    !key.is_empty()
+        && key.trim() == key
+        && !key.starts_with(['"', '\'', '-', '#', '[', '{', '&', '*', '!', '|', '>', '%', '@', '`'])
+        && !key.contains(": ")
+        && !key.contains(" #")
+        && !key.ends_with(':')
+        && key.bytes().all(|byte| byte.is_ascii_graphic() || byte == b' ')
+}
+
 /// Recursively writes a Bencode node to the destination in YAML format.
 ///
 /// # Arguments
 /// * `node` - The Bencode node to serialize
 /// * `level` - Current indentation level
 /// * `destination` - The output destination to write to
-fn write_node(node: &Node, level: usize, destination: &mut dyn IDestination) {
+/// * `config` - Encoder configuration for indent and binary strings
+fn write_node(node: &Node, level: usize, destination: &mut dyn IDestination, config: &EncoderConfig) {
     match node {
         // Write integer values directly
         Node::Integer(n) => destination.add_bytes(&n.to_string()),
+        Node::BigInteger(digits) => destination.add_bytes(digits),
         // Write strings with quotes and proper UTF-8 encoding
         Node::Str(s) => {
             destination.add_byte(b'"');
-            escape_string(&s, destination);
+            escape_string(s, destination);
+            destination.add_byte(b'"');
+        }
+        // Binary strings are written in the configured encoding
+        Node::Bytes(bytes) => {
+            destination.add_byte(b'"');
+            write_binary(bytes, config.binary, destination);
             destination.add_byte(b'"');
         }
         // Write lists with proper YAML array formatting
@@ -46,9 +72,9 @@ fn write_node(node: &Node, level: usize, destination: &mut dyn IDestination) {
             } else {
                 destination.add_bytes("\n");
                 for item in items {
-                    write_indent(level + 1, destination);
+                    write_indent(level + 1, destination, config);
                     destination.add_bytes("- ");
-                    write_node(item, level + 1, destination);
+                    write_node(item, level + 1, destination, config);
                     destination.add_bytes("\n");
                 }
             }
@@ -59,16 +85,26 @@ fn write_node(node: &Node, level: usize, destination: &mut dyn IDestination) {
                 destination.add_bytes("{}")
             } else {
                 destination.add_bytes("\n");
-                let mut sorted: Vec<_> = dict.iter().collect();
-                sorted.sort_by(|a, b| a.0.cmp(b.0));
-                for (key, value) in sorted {
-                    write_indent(level + 1, destination);
-                    destination.add_bytes(&format!("{}: ", String::from_utf8_lossy(key.as_ref())));
-                    write_node(value, level + 1, destination);
+                for (key, value) in node.iter_sorted() {
+                    write_indent(level + 1, destination, config);
+                    if is_plain_key(key) {
+                        destination.add_bytes(key);
+                    } else {
+                        destination.add_byte(b'"');
+                        escape_string(key, destination);
+                        destination.add_byte(b'"');
+                    }
+                    destination.add_bytes(": ");
+                    write_node(value, level + 1, destination, config);
                     destination.add_bytes("\n");
                 }
             }
         }
+        // Write pre-encoded values as the tree they decode to
+        Node::Raw(_) => match node.decode_raw() {
+            Ok(decoded) => write_node(&decoded, level, destination, config),
+            Err(_) => destination.add_bytes("unknown"),
+        },
         // Handle unknown/unsupported node types
         _ => destination.add_bytes("unknown"),
     }
@@ -80,10 +116,50 @@ fn write_node(node: &Node, level: usize, destination: &mut dyn IDestination) {
 /// * `node` - The root Bencode node to serialize
 /// * `destination` - The output destination to write the YAML to
 pub fn stringify(node: &Node, destination: &mut dyn IDestination) -> Result<(), String> {
-    write_node(node, 0, destination);
+    stringify_with_config(node, destination, &EncoderConfig::new())
+}
+
+/// Converts a Bencode node to YAML like [`stringify`], laid out as `config` asks.
+///
+/// Keys are sorted, each level of nesting is indented by `indent` spaces and
+/// `binary` chooses how byte strings that are not UTF-8 are written. The
+/// `pretty` option only applies to JSON.
+///
+/// # Arguments
+/// * `node` - The root Bencode node to serialize
+/// * `destination` - The output destination to write the YAML to
+/// * `config` - Layout of the output
+///
+/// # Example
+/// ```
+/// use bencode_lib::{BufferDestination, EncoderConfig, parse_bytes, to_yaml_with_config};
+///
+/// let node = parse_bytes(b"d4:infod4:name1:xee").unwrap();
+/// let mut destination = BufferDestination::new();
+/// to_yaml_with_config(&node, &mut destination, &EncoderConfig::new().with_indent(4)).unwrap();
+/// assert_eq!(destination.to_string(), "\ninfo: \n    name: \"x\"\n\n");
+/// ```
+pub fn stringify_with_config(node: &Node, destination: &mut dyn IDestination, config: &EncoderConfig) -> Result<(), String> {
+    write_node(node, 0, destination, config);
     Ok(())
 }
 
+/// Converts a Node structure to YAML like [`stringify`], also reporting anything
+/// the YAML output could not preserve.
+///
+/// # Arguments
+/// * `node` - The Node structure to convert
+/// * `destination` - The destination to write the YAML output to
+///
+/// # Returns
+/// * `Ok(ConversionReport)` - What was lost; check `is_lossless()` to refuse lossy output
+/// * `Err(String)` - The node could not be converted at all
+pub fn stringify_with_report(node: &Node, destination: &mut dyn IDestination) -> Result<ConversionReport, String> {
+    let report = analyze(node, Format::Yaml);
+    stringify(node, destination)?;
+    Ok(report)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -110,7 +186,7 @@ mod tests {
     #[test]
     fn stringify_empty_dictionary_works() {
         let mut destination = Buffer::new();
-        let dict = std::collections::HashMap::new();
+        let dict = crate::HashMap::new();
         stringify(&Node::Dictionary(dict), &mut destination).unwrap();
         assert_eq!(destination.to_string(), "{}");
     }
@@ -118,7 +194,7 @@ mod tests {
     #[test]
     fn stringify_dictionary_works() {
         let mut destination = Buffer::new();
-        let mut dict = std::collections::HashMap::new();
+        let mut dict = crate::HashMap::new();
         dict.insert("key".to_string(), Node::Integer(1));
         stringify(&Node::Dictionary(dict), &mut destination).unwrap();
         assert_eq!(destination.to_string(), "\nkey: 1\n");
@@ -144,4 +220,14 @@ mod tests {
         stringify(&Node::None, &mut destination).unwrap();
         assert_eq!(destination.to_string(), "unknown");
     }
+
+    #[test]
+    fn stringify_with_config_sets_indent_and_binary_encoding() {
+        let mut dict = crate::HashMap::new();
+        dict.insert("hash".to_string(), Node::List(vec![Node::Bytes(b"\xde\xad".to_vec())]));
+        let config = EncoderConfig::new().with_indent(3).with_binary(crate::BinaryEncoding::Hex);
+        let mut destination = Buffer::new();
+        stringify_with_config(&Node::Dictionary(dict), &mut destination, &config).unwrap();
+        assert_eq!(destination.to_string(), "\nhash: \n   - \"dead\"\n\n");
+    }
 }
@@ -0,0 +1,185 @@
+//! Deterministic generators for synthetic bencode test inputs.
+//!
+//! These produce consistent, reproducible documents for benchmarks and user tests
+//! without having to commit real torrent files. The same arguments always give the
+//! same bytes, and every document is canonical bencode (dictionary keys sorted).
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::ToString, vec::Vec};
+
+/// Size in bytes of a SHA-1 piece hash
+const PIECE_HASH_LENGTH: usize = 20;
+
+/// Appends a bencoded byte string to `out`.
+fn push_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(bytes.len().to_string().as_bytes());
+    out.push(b':');
+    out.extend_from_slice(bytes);
+}
+
+/// Appends a bencoded integer to `out`.
+fn push_integer(out: &mut Vec<u8>, value: usize) {
+    out.push(b'i');
+    out.extend_from_slice(value.to_string().as_bytes());
+    out.push(b'e');
+}
+
+/// Length of the `index`th generated file: varies between files but never zero.
+fn file_length(index: usize, piece_len: usize) -> usize {
+    piece_len * (index % 3 + 1) + index * 37 + 1
+}
+
+/// Generates a multi-file torrent with `files` files and the given piece length.
+///
+/// File lengths vary deterministically and the `pieces` field holds one
+/// pseudo-random 20 byte hash per piece covering the combined length, so the
+/// output has the shape and size profile of a real torrent.
+///
+/// # Arguments
+/// * `files` - Number of entries in the `info.files` list
+/// * `piece_len` - Value of `info.piece length` (0 is treated as 1)
+///
+/// # Returns
+/// The bencode-encoded torrent
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "testdata")] {
+/// use bencode_lib::testdata::gen_multi_file_torrent;
+///
+/// let torrent = gen_multi_file_torrent(3, 16384);
+/// assert!(bencode_lib::validate_bencode(&torrent).is_ok());
+/// # }
+/// ```
+pub fn gen_multi_file_torrent(files: usize, piece_len: usize) -> Vec<u8> {
+    let piece_len = piece_len.max(1);
+    let total: usize = (0..files).map(|index| file_length(index, piece_len)).sum();
+    let pieces = total.div_ceil(piece_len);
+
+    let mut out = Vec::new();
+    out.push(b'd');
+    push_bytes(&mut out, b"announce");
+    push_bytes(&mut out, b"http://tracker.example.com/announce");
+    push_bytes(&mut out, b"created by");
+    push_bytes(&mut out, b"bencode_lib testdata");
+    push_bytes(&mut out, b"creation date");
+    push_integer(&mut out, 1_700_000_000);
+    push_bytes(&mut out, b"info");
+    out.push(b'd');
+    push_bytes(&mut out, b"files");
+    out.push(b'l');
+    for index in 0..files {
+        out.push(b'd');
+        push_bytes(&mut out, b"length");
+        push_integer(&mut out, file_length(index, piece_len));
+        push_bytes(&mut out, b"path");
+        out.push(b'l');
+        push_bytes(&mut out, b"dir");
+        push_bytes(&mut out, (index.to_string() + ".bin").as_bytes());
+        out.extend_from_slice(b"ee");
+    }
+    out.push(b'e');
+    push_bytes(&mut out, b"name");
+    push_bytes(&mut out, b"testdata");
+    push_bytes(&mut out, b"piece length");
+    push_integer(&mut out, piece_len);
+    push_bytes(&mut out, b"pieces");
+    // xorshift keeps the hashes reproducible and dependency free
+    let mut state: u32 = 0x9E37_79B9;
+    let hashes: Vec<u8> = (0..pieces * PIECE_HASH_LENGTH)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            state as u8
+        })
+        .collect();
+    push_bytes(&mut out, &hashes);
+    out.extend_from_slice(b"ee");
+    out
+}
+
+/// Generates `depth` nested lists wrapped around a single integer.
+///
+/// Useful for exercising recursion limits and the iterative parser.
+///
+/// # Arguments
+/// * `depth` - Number of nested lists (0 gives a bare integer)
+///
+/// # Returns
+/// The bencode-encoded document, e.g. `llli0eeee` for a depth of 3
+///
+/// # Example
+/// ```
+/// # #[cfg(feature = "testdata")] {
+/// use bencode_lib::testdata::gen_deeply_nested;
+///
+/// assert_eq!(gen_deeply_nested(2), b"lli0eee");
+/// # }
+/// ```
+pub fn gen_deeply_nested(depth: usize) -> Vec<u8> {
+    let mut out = Vec::with_capacity(depth * 2 + 3);
+    out.resize(depth, b'l');
+    out.extend_from_slice(b"i0e");
+    out.resize(depth * 2 + 3, b'e');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::nodes::borrowed::BorrowedNode;
+    use crate::nodes::node::Node;
+    use crate::parser::borrowed::parse_borrowed;
+    use crate::parser::borrowed::validate_bencode;
+    use crate::parser::default::parse_bytes;
+    use crate::parser::iterative::parse_bytes_iterative;
+
+    #[test]
+    fn multi_file_torrent_is_valid_bencode() {
+        let torrent = gen_multi_file_torrent(5, 1024);
+        assert!(validate_bencode(&torrent).is_ok());
+        let node = parse_bytes(&torrent).unwrap();
+        let info = node.get("info").unwrap();
+        assert_eq!(info.get("files").unwrap().as_list().unwrap().len(), 5);
+        assert_eq!(info.get("piece length").unwrap().as_integer(), Some(&1024));
+    }
+
+    #[test]
+    fn multi_file_torrent_has_one_hash_per_piece() {
+        let torrent = gen_multi_file_torrent(4, 100);
+        let BorrowedNode::Dictionary(root) = parse_borrowed(&torrent).unwrap() else {
+            panic!("Expected root dictionary");
+        };
+        let BorrowedNode::Dictionary(info) = &root[&b"info"[..]] else {
+            panic!("Expected info dictionary");
+        };
+        let total: usize = (0..4).map(|index| file_length(index, 100)).sum();
+        let pieces = info[&b"pieces"[..]].as_bytes().unwrap();
+        assert_eq!(pieces.len(), total.div_ceil(100) * PIECE_HASH_LENGTH);
+    }
+
+    #[test]
+    fn multi_file_torrent_is_deterministic() {
+        assert_eq!(gen_multi_file_torrent(3, 256), gen_multi_file_torrent(3, 256));
+        assert_ne!(gen_multi_file_torrent(3, 256), gen_multi_file_torrent(4, 256));
+    }
+
+    #[test]
+    fn multi_file_torrent_with_no_files() {
+        let torrent = gen_multi_file_torrent(0, 0);
+        let node = parse_bytes(&torrent).unwrap();
+        let info = node.get("info").unwrap();
+        assert_eq!(info.get("files"), Some(&Node::List(Vec::new())));
+        assert_eq!(info.get("piece length").unwrap().as_integer(), Some(&1));
+    }
+
+    #[test]
+    fn deeply_nested_shapes() {
+        assert_eq!(gen_deeply_nested(0), b"i0e");
+        assert_eq!(gen_deeply_nested(3), b"llli0eeee");
+        let deep = gen_deeply_nested(500);
+        assert!(validate_bencode(&deep).is_ok());
+        assert!(parse_bytes_iterative(&deep).is_ok());
+    }
+}
@@ -0,0 +1,458 @@
+//! Tracker announce scheduling.
+//!
+//! A client announces `started` when it begins a torrent, re-announces every
+//! `interval` seconds the tracker asks for, announces `completed` once and
+//! `stopped` when it leaves, and backs off when the tracker is unreachable.
+//! [`AnnounceScheduler`] keeps that state; callers supply the current time and
+//! do the networking themselves, so it works with any clock and HTTP/UDP stack.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::error::messages::ERR_INVALID_TRACKER_RESPONSE;
+use crate::nodes::node::Node;
+use crate::parser::default::parse_bytes;
+
+/// Re-announce interval used until a tracker supplies one, in seconds
+pub const DEFAULT_INTERVAL: u64 = 1800;
+/// First retry delay after a failed announce, in seconds
+pub const DEFAULT_BACKOFF: u64 = 15;
+/// Longest retry delay after repeated failures, in seconds
+pub const DEFAULT_MAX_BACKOFF: u64 = 1800;
+
+/// The `event` parameter of an announce.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnounceEvent {
+    /// First announce of a session
+    Started,
+    /// The download has just finished
+    Completed,
+    /// The client is leaving the swarm
+    Stopped,
+    /// A regular re-announce, sent without an event
+    Regular,
+}
+
+impl AnnounceEvent {
+    /// Returns the value of the `event` query parameter (empty for regular announces)
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            AnnounceEvent::Started => "started",
+            AnnounceEvent::Completed => "completed",
+            AnnounceEvent::Stopped => "stopped",
+            AnnounceEvent::Regular => "",
+        }
+    }
+}
+
+/// Parameters of the next announce to send.
+///
+/// Transfer counters (`uploaded`, `downloaded`, `left`) are known only to the
+/// client and are added by the caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnounceRequest {
+    /// The event to report
+    pub event: AnnounceEvent,
+    /// The `trackerid` from an earlier response, to be sent back
+    pub tracker_id: Option<Vec<u8>>,
+}
+
+/// The scheduling fields of a tracker's announce response.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackerResponse {
+    /// `failure reason`: the announce was refused and nothing else is valid
+    pub failure_reason: Option<String>,
+    /// `warning message`: the announce succeeded but the tracker has a warning
+    pub warning_message: Option<String>,
+    /// `interval`: seconds to wait before the next regular announce
+    pub interval: Option<u64>,
+    /// `min interval`: announces must not be sent more often than this
+    pub min_interval: Option<u64>,
+    /// `tracker id`: to be sent back in later announces
+    pub tracker_id: Option<Vec<u8>>,
+    /// `complete`: number of seeders
+    pub complete: Option<u64>,
+    /// `incomplete`: number of leechers
+    pub incomplete: Option<u64>,
+}
+
+impl TrackerResponse {
+    /// Reads the scheduling fields of a bencoded announce response (peers are ignored).
+    ///
+    /// # Returns
+    /// * `Ok(TrackerResponse)` - The response fields
+    /// * `Err(String)` - If the input is malformed, not a dictionary, or has
+    ///   neither a `failure reason` nor an `interval`
+    pub fn from_bytes(data: &[u8]) -> Result<TrackerResponse, String> {
+        Self::from_node(&parse_bytes(data)?)
+    }
+
+    /// Reads the scheduling fields of a parsed announce response (peers are ignored).
+    ///
+    /// # Returns
+    /// * `Ok(TrackerResponse)` - The response fields
+    /// * `Err(String)` - If the root is not a dictionary, or it has neither a
+    ///   `failure reason` nor an `interval`
+    pub fn from_node(root: &Node) -> Result<TrackerResponse, String> {
+        if !root.is_dictionary() {
+            return Err(ERR_INVALID_TRACKER_RESPONSE.to_string());
+        }
+        let text = |key: &str| root.get(key).and_then(Node::as_bytes).map(|b| String::from_utf8_lossy(b).into_owned());
+        let count = |key: &str| root.get(key).and_then(|n| n.as_u64().ok());
+        let response = TrackerResponse {
+            failure_reason: text("failure reason"),
+            warning_message: text("warning message"),
+            interval: count("interval"),
+            min_interval: count("min interval"),
+            tracker_id: root.get("tracker id").and_then(Node::as_bytes).map(<[u8]>::to_vec),
+            complete: count("complete"),
+            incomplete: count("incomplete"),
+        };
+        if response.failure_reason.is_none() && response.interval.is_none() {
+            return Err(ERR_INVALID_TRACKER_RESPONSE.to_string());
+        }
+        Ok(response)
+    }
+}
+
+/// Lifecycle of the session being announced
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Session {
+    /// `start` has not been called
+    Idle,
+    /// Announcing
+    Running,
+    /// `stopped` has been sent (or nothing needed sending)
+    Stopped,
+}
+
+/// Decides when to announce to one tracker and with which event.
+///
+/// Times are seconds on any monotonic clock chosen by the caller.
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::{AnnounceEvent, AnnounceScheduler, TrackerResponse};
+///
+/// let mut scheduler = AnnounceScheduler::new();
+/// scheduler.start(0);
+/// let request = scheduler.poll(0).unwrap();
+/// assert_eq!(request.event, AnnounceEvent::Started);
+///
+/// let response = TrackerResponse::from_bytes(b"d8:intervali600e5:peers0:e").unwrap();
+/// scheduler.on_response(1, &response);
+/// assert_eq!(scheduler.next_announce_at(), Some(601));
+/// assert!(scheduler.poll(300).is_none());
+/// assert_eq!(scheduler.poll(601).unwrap().event, AnnounceEvent::Regular);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AnnounceScheduler {
+    session: Session,
+    pending: Option<AnnounceEvent>,
+    in_flight: Option<AnnounceEvent>,
+    interval: u64,
+    min_interval: u64,
+    backoff: u64,
+    max_backoff: u64,
+    failures: u32,
+    announced: bool,
+    completed: bool,
+    last_sent: Option<u64>,
+    next_at: Option<u64>,
+    tracker_id: Option<Vec<u8>>,
+}
+
+impl Default for AnnounceScheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AnnounceScheduler {
+    /// Creates an idle scheduler with the default interval and backoff.
+    pub const fn new() -> Self {
+        Self {
+            session: Session::Idle,
+            pending: None,
+            in_flight: None,
+            interval: DEFAULT_INTERVAL,
+            min_interval: 0,
+            backoff: DEFAULT_BACKOFF,
+            max_backoff: DEFAULT_MAX_BACKOFF,
+            failures: 0,
+            announced: false,
+            completed: false,
+            last_sent: None,
+            next_at: None,
+            tracker_id: None,
+        }
+    }
+
+    /// Sets the re-announce interval used until the tracker supplies one
+    pub const fn with_interval(mut self, seconds: u64) -> Self {
+        self.interval = seconds;
+        self
+    }
+
+    /// Sets the first and longest retry delays after failed announces
+    pub const fn with_backoff(mut self, first: u64, max: u64) -> Self {
+        self.backoff = first;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Begins the session; a `started` announce is due immediately.
+    pub fn start(&mut self, now: u64) {
+        if self.session == Session::Idle {
+            self.session = Session::Running;
+            self.pending = Some(AnnounceEvent::Started);
+            self.next_at = Some(now);
+        }
+    }
+
+    /// Records that the download finished; a `completed` announce is due
+    /// immediately. Only the first call has an effect.
+    ///
+    /// If `started` has not been sent yet it is sent instead, as the tracker
+    /// learns of the completion from `left = 0`.
+    pub fn complete(&mut self, now: u64) {
+        if self.session != Session::Running || self.completed {
+            return;
+        }
+        self.completed = true;
+        if self.announced {
+            self.pending = Some(AnnounceEvent::Completed);
+            self.next_at = Some(now);
+        }
+    }
+
+    /// Ends the session; a `stopped` announce is due immediately if the
+    /// tracker has ever heard from this client, otherwise nothing is sent.
+    pub fn stop(&mut self, now: u64) {
+        if self.session != Session::Running {
+            self.session = Session::Stopped;
+            return;
+        }
+        if self.announced || self.in_flight.is_some() {
+            self.pending = Some(AnnounceEvent::Stopped);
+            self.next_at = Some(now);
+            self.failures = 0;
+        } else {
+            self.session = Session::Stopped;
+            self.pending = None;
+            self.next_at = None;
+        }
+    }
+
+    /// Returns the announce to send now, if one is due and none is in flight.
+    ///
+    /// The announce counts as in flight until `on_response` or `on_error` is called.
+    pub fn poll(&mut self, now: u64) -> Option<AnnounceRequest> {
+        if self.in_flight.is_some() || self.next_at.is_none_or(|at| now < at) {
+            return None;
+        }
+        let event = self.pending.take().unwrap_or(AnnounceEvent::Regular);
+        self.in_flight = Some(event);
+        self.last_sent = Some(now);
+        Some(AnnounceRequest {
+            event,
+            tracker_id: self.tracker_id.clone(),
+        })
+    }
+
+    /// Records the tracker's response to the announce in flight.
+    ///
+    /// A response carrying a `failure reason` is treated like `on_error`.
+    pub fn on_response(&mut self, now: u64, response: &TrackerResponse) {
+        if response.failure_reason.is_some() {
+            self.on_error(now);
+            return;
+        }
+        let Some(event) = self.in_flight.take() else {
+            return;
+        };
+        self.failures = 0;
+        self.announced = true;
+        if let Some(interval) = response.interval {
+            self.interval = interval;
+        }
+        if let Some(min_interval) = response.min_interval {
+            self.min_interval = min_interval;
+        }
+        if response.tracker_id.is_some() {
+            self.tracker_id = response.tracker_id.clone();
+        }
+        if event == AnnounceEvent::Stopped {
+            self.session = Session::Stopped;
+            self.next_at = None;
+        } else if self.pending.is_some() {
+            // An event arrived while this announce was in flight
+            self.next_at = Some(now);
+        } else {
+            self.next_at = Some(now + self.interval.max(self.min_interval));
+        }
+    }
+
+    /// Records that the announce in flight failed (no response or a refusal).
+    ///
+    /// Its event is kept for the retry, which is delayed by an exponentially
+    /// growing backoff (but never less than the tracker's `min interval`).
+    pub fn on_error(&mut self, now: u64) {
+        let Some(event) = self.in_flight.take() else {
+            return;
+        };
+        if event != AnnounceEvent::Regular && self.pending.is_none() {
+            self.pending = Some(event);
+        }
+        let delay = self
+            .backoff
+            .saturating_mul(1u64 << self.failures.min(32))
+            .min(self.max_backoff)
+            .max(self.min_interval);
+        self.failures = self.failures.saturating_add(1);
+        self.next_at = Some(now + delay);
+    }
+
+    /// Brings the next regular announce forward (e.g. when more peers are
+    /// needed), as far as the tracker's `min interval` allows.
+    ///
+    /// # Returns
+    /// True if an announce is now due
+    pub fn request_announce(&mut self, now: u64) -> bool {
+        if self.session != Session::Running || self.in_flight.is_some() {
+            return false;
+        }
+        let earliest = self.last_sent.map_or(now, |sent| sent + self.min_interval);
+        if earliest > now {
+            return false;
+        }
+        self.next_at = Some(self.next_at.map_or(now, |at| at.min(now)));
+        true
+    }
+
+    /// Returns when the next announce is due, or None if none will be sent
+    pub fn next_announce_at(&self) -> Option<u64> {
+        self.next_at
+    }
+
+    /// Returns the current re-announce interval in seconds
+    pub fn interval(&self) -> u64 {
+        self.interval
+    }
+
+    /// Returns the tracker's minimum announce interval in seconds (0 if none)
+    pub fn min_interval(&self) -> u64 {
+        self.min_interval
+    }
+
+    /// Returns the number of consecutive failed announces
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Returns true once the session has ended
+    pub fn is_stopped(&self) -> bool {
+        self.session == Session::Stopped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ok(interval: u64) -> TrackerResponse {
+        TrackerResponse {
+            interval: Some(interval),
+            ..TrackerResponse::default()
+        }
+    }
+
+    #[test]
+    fn parses_tracker_responses() {
+        let response =
+            TrackerResponse::from_bytes(b"d8:completei5e10:incompletei2e8:intervali900e12:min intervali60e10:tracker id2:abe")
+                .unwrap();
+        assert_eq!(response.interval, Some(900));
+        assert_eq!(response.min_interval, Some(60));
+        assert_eq!(response.tracker_id.as_deref(), Some(&b"ab"[..]));
+        assert_eq!((response.complete, response.incomplete), (Some(5), Some(2)));
+
+        let refused = TrackerResponse::from_bytes(b"d14:failure reason6:bannede").unwrap();
+        assert_eq!(refused.failure_reason.as_deref(), Some("banned"));
+        assert_eq!(TrackerResponse::from_bytes(b"de").unwrap_err(), ERR_INVALID_TRACKER_RESPONSE);
+        assert_eq!(TrackerResponse::from_bytes(b"li1ee").unwrap_err(), ERR_INVALID_TRACKER_RESPONSE);
+    }
+
+    #[test]
+    fn session_lifecycle() {
+        let mut scheduler = AnnounceScheduler::new();
+        assert!(scheduler.poll(0).is_none());
+        scheduler.start(10);
+        assert_eq!(scheduler.poll(10).unwrap().event, AnnounceEvent::Started);
+        assert!(scheduler.poll(10).is_none(), "one announce in flight at a time");
+        scheduler.on_response(11, &ok(100));
+        assert_eq!(scheduler.next_announce_at(), Some(111));
+
+        scheduler.complete(50);
+        assert_eq!(scheduler.poll(50).unwrap().event, AnnounceEvent::Completed);
+        scheduler.on_response(51, &ok(100));
+        scheduler.complete(60);
+        assert_eq!(scheduler.next_announce_at(), Some(151));
+        assert_eq!(scheduler.poll(151).unwrap().event, AnnounceEvent::Regular);
+        scheduler.on_response(152, &ok(100));
+
+        scheduler.stop(160);
+        assert_eq!(scheduler.poll(160).unwrap().event, AnnounceEvent::Stopped);
+        scheduler.on_response(161, &ok(100));
+        assert!(scheduler.is_stopped());
+        assert_eq!(scheduler.next_announce_at(), None);
+    }
+
+    #[test]
+    fn failures_back_off_and_keep_the_event() {
+        let mut scheduler = AnnounceScheduler::new().with_backoff(10, 25);
+        scheduler.start(0);
+        scheduler.poll(0).unwrap();
+        scheduler.on_error(1);
+        assert_eq!(scheduler.next_announce_at(), Some(11));
+        assert_eq!(scheduler.poll(11).unwrap().event, AnnounceEvent::Started);
+        scheduler.on_error(12);
+        assert_eq!(scheduler.next_announce_at(), Some(32));
+        scheduler.poll(32).unwrap();
+        scheduler.on_response(33, &TrackerResponse::from_bytes(b"d14:failure reason4:busye").unwrap());
+        assert_eq!(scheduler.next_announce_at(), Some(58));
+        assert_eq!(scheduler.failures(), 3);
+        assert_eq!(scheduler.poll(58).unwrap().event, AnnounceEvent::Started);
+        scheduler.on_response(59, &ok(100));
+        assert_eq!(scheduler.failures(), 0);
+    }
+
+    #[test]
+    fn min_interval_limits_manual_announces() {
+        let mut scheduler = AnnounceScheduler::new();
+        scheduler.start(0);
+        let request = scheduler.poll(0).unwrap();
+        assert_eq!(request.tracker_id, None);
+        let response = TrackerResponse {
+            min_interval: Some(30),
+            tracker_id: Some(b"id".to_vec()),
+            ..ok(600)
+        };
+        scheduler.on_response(1, &response);
+        assert!(!scheduler.request_announce(20));
+        assert!(scheduler.request_announce(30));
+        assert_eq!(scheduler.poll(30).unwrap().tracker_id.as_deref(), Some(&b"id"[..]));
+    }
+
+    #[test]
+    fn stop_before_any_announce_sends_nothing() {
+        let mut scheduler = AnnounceScheduler::new();
+        scheduler.start(0);
+        scheduler.stop(0);
+        assert!(scheduler.is_stopped());
+        assert!(scheduler.poll(0).is_none());
+    }
+}
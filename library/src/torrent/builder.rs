@@ -0,0 +1,628 @@
+//! Creation of torrents from files on disk.
+//!
+//! [`TorrentBuilder`] reads a file, or every file below a directory, cuts the
+//! payload into pieces and hashes them, and returns the result as a
+//! [`TorrentFile`] ready to be written with [`TorrentFile::write_file`].
+//! Directory entries are taken in byte order of their names so the same tree
+//! always produces the same torrent.
+//!
+//! Torrents are v1 unless [`TorrentBuilder::with_meta_version`] asks for a v2
+//! torrent, which hashes each file into its own merkle tree (BEP 52), or a
+//! hybrid torrent carrying both. A hybrid torrent pads each file to a piece
+//! boundary with a padding file (BEP 47), so v1 pieces never span two files.
+//!
+//! # Reproducible builds
+//!
+//! With [`TorrentBuilder::with_reproducible`] the encoded torrent depends only
+//! on the payload and the builder's settings, so a pipeline publishing a
+//! dataset gets a byte-for-byte identical `.torrent` file on every run and
+//! every platform:
+//! - files are ordered by their path within the torrent, compared as UTF-8
+//!   text, rather than by the platform's ordering of file names
+//! - the name is taken from the path as given (or [`TorrentBuilder::with_name`]),
+//!   not from the resolved path, so it does not depend on the working
+//!   directory or on symbolic links
+//! - `created by` is left out, as it usually names a tool version
+//! - `creation date` is written only if set with
+//!   [`TorrentBuilder::with_creation_date`] (for example from `SOURCE_DATE_EPOCH`)
+//!
+//! File contents, names and the directory layout still have to match; file
+//! times and permissions never affect a torrent.
+
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::HashMap;
+use crate::error::messages::{ERR_INVALID_FILE_ENTRY, ERR_INVALID_PIECE_LENGTH};
+use crate::nodes::node::Node;
+
+use super::hasher::{Hasher, SharedHasher};
+use super::merkle::{FileHashes, MerkleHasher, is_v2_piece_length};
+use super::metainfo::{Info, InfoFile, META_VERSION_2, MetaVersion, PieceLayers, TorrentFile, TreeFile};
+use super::url::TrackerUrl;
+
+/// Default piece length (256 KiB)
+pub const DEFAULT_PIECE_LENGTH: u64 = 256 * 1024;
+
+/// Size of the reads used while hashing
+const READ_BUFFER: usize = 64 * 1024;
+
+/// Builds a torrent from a file or directory.
+///
+/// # Example
+/// ```no_run
+/// use bencode_lib::torrent::TorrentBuilder;
+///
+/// let torrent = TorrentBuilder::new()
+///     .with_piece_length(512 * 1024)
+///     .with_announce("udp://tracker.example:6969")
+///     .with_comment("Holiday photos")
+///     .build("photos")
+///     .unwrap();
+/// torrent.write_file("photos.torrent").unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct TorrentBuilder {
+    piece_length: u64,
+    private: bool,
+    announce: Option<String>,
+    announce_list: Vec<Vec<String>>,
+    comment: Option<String>,
+    created_by: Option<String>,
+    creation_date: Option<u64>,
+    name: Option<String>,
+    reproducible: bool,
+    meta_version: MetaVersion,
+    hasher: SharedHasher,
+}
+
+impl Default for TorrentBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TorrentBuilder {
+    /// Creates a builder for a public, trackerless torrent with the default piece length.
+    pub const fn new() -> Self {
+        Self {
+            piece_length: DEFAULT_PIECE_LENGTH,
+            private: false,
+            announce: None,
+            announce_list: Vec::new(),
+            comment: None,
+            created_by: None,
+            creation_date: None,
+            name: None,
+            reproducible: false,
+            meta_version: MetaVersion::V1,
+            hasher: SharedHasher::new(),
+        }
+    }
+
+    /// Sets the piece length in bytes (must be positive; clients expect a power of two).
+    pub const fn with_piece_length(mut self, piece_length: u64) -> Self {
+        self.piece_length = piece_length;
+        self
+    }
+
+    /// Marks the torrent private, so peers only come from its trackers.
+    pub const fn with_private(mut self, private: bool) -> Self {
+        self.private = private;
+        self
+    }
+
+    /// Sets the creation time in seconds since the Unix epoch (not set by default,
+    /// so builds are reproducible).
+    pub const fn with_creation_date(mut self, seconds: u64) -> Self {
+        self.creation_date = Some(seconds);
+        self
+    }
+
+    /// Makes the encoded torrent depend only on the payload and the builder's
+    /// settings; see the [module documentation](self#reproducible-builds).
+    pub const fn with_reproducible(mut self, reproducible: bool) -> Self {
+        self.reproducible = reproducible;
+        self
+    }
+
+    /// Sets which protocol versions the torrent supports (v1 by default). v2
+    /// and hybrid torrents need a piece length that is a power of two of at
+    /// least 16 KiB.
+    pub const fn with_meta_version(mut self, version: MetaVersion) -> Self {
+        self.meta_version = version;
+        self
+    }
+
+    /// Hashes pieces with `hasher` rather than the default backend.
+    pub fn with_hasher(mut self, hasher: impl Hasher + 'static) -> Self {
+        self.hasher = SharedHasher::from(hasher);
+        self
+    }
+
+    /// Sets the main tracker URL, which is checked when the torrent is built.
+    pub fn with_announce(mut self, url: impl Into<String>) -> Self {
+        self.announce = Some(url.into());
+        self
+    }
+
+    /// Adds a tier of interchangeable trackers to the announce list.
+    ///
+    /// When tiers are given and no main tracker is set, the first URL of the
+    /// first tier is also used as `announce` for clients without BEP 12 support.
+    pub fn with_announce_tier<S: Into<String>>(mut self, urls: impl IntoIterator<Item = S>) -> Self {
+        self.announce_list.push(urls.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Sets the torrent's name, rather than taking it from the shared path.
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    /// Sets the free text comment.
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    /// Sets the name of the program creating the torrent.
+    pub fn with_created_by(mut self, created_by: impl Into<String>) -> Self {
+        self.created_by = Some(created_by.into());
+        self
+    }
+
+    /// Builds a torrent for a file (single-file mode) or a directory
+    /// (multi-file mode, with every regular file below it).
+    ///
+    /// Symbolic links are skipped.
+    ///
+    /// # Arguments
+    /// * `path` - The file or directory to share; its name becomes the
+    ///   torrent's name unless one is set with [`with_name`](Self::with_name)
+    ///
+    /// # Returns
+    /// * `Ok(TorrentFile)` - The torrent, validated
+    /// * `Err(String)` - If the piece length is zero (or unusable by a v2
+    ///   torrent), a tracker URL is
+    ///   malformed, the directory holds no files, a name is not UTF-8 (or,
+    ///   when reproducible, the path has no name, such as `.`), a file changes
+    ///   while it is read, or an I/O error occurs
+    ///
+    /// # Example
+    /// ```no_run
+    /// use bencode_lib::torrent::TorrentBuilder;
+    ///
+    /// // The same bytes on every run, from any checkout directory
+    /// let torrent = TorrentBuilder::new()
+    ///     .with_reproducible(true)
+    ///     .with_creation_date(1_700_000_000)
+    ///     .with_announce("udp://tracker.example:6969")
+    ///     .build("dataset")
+    ///     .unwrap();
+    /// torrent.write_file("dataset.torrent").unwrap();
+    /// ```
+    pub fn build<P: AsRef<Path>>(&self, path: P) -> Result<TorrentFile, String> {
+        let v2 = self.meta_version != MetaVersion::V1;
+        if self.piece_length == 0 || (v2 && !is_v2_piece_length(self.piece_length)) {
+            return Err(ERR_INVALID_PIECE_LENGTH.to_string());
+        }
+        // Check the trackers before spending time hashing the payload
+        let announce_list = self
+            .announce_list
+            .iter()
+            .map(|tier| tier.iter().map(|url| TrackerUrl::parse(url)).collect::<Result<Vec<_>, _>>())
+            .collect::<Result<Vec<_>, _>>()?;
+        let announce = match &self.announce {
+            Some(url) => Some(TrackerUrl::parse(url)?),
+            None => announce_list.first().and_then(|tier| tier.first()).cloned(),
+        };
+        let root = fs::canonicalize(&path).map_err(|e| e.to_string())?;
+        let named = if self.reproducible { path.as_ref() } else { root.as_path() };
+        let name = match &self.name {
+            Some(name) => name.clone(),
+            None => named
+                .file_name()
+                .and_then(|name| name.to_str())
+                .ok_or_else(|| ERR_INVALID_FILE_ENTRY.to_string())?
+                .to_string(),
+        };
+
+        let mut pieces = (self.meta_version != MetaVersion::V2).then(|| PieceHasher::new(self.piece_length, self.hasher.clone()));
+        let mut file_tree = Vec::new();
+        let mut piece_layers = PieceLayers::new();
+        let (length, files) = if root.is_dir() {
+            let mut found = Vec::new();
+            collect_files(&root, &mut Vec::new(), &mut found)?;
+            if found.is_empty() {
+                return Err(ERR_INVALID_FILE_ENTRY.to_string());
+            }
+            // The file tree is in path order, which a hybrid torrent's file list has to follow
+            if self.reproducible || v2 {
+                found.sort_by(|(_, a), (_, b)| a.cmp(b));
+            }
+            let last = found.len() - 1;
+            let mut files = Vec::with_capacity(found.len());
+            for (index, (path, segments)) in found.into_iter().enumerate() {
+                let (length, hashes) = self.hash_file(&path, pieces.as_mut())?;
+                if let Some(hashes) = hashes {
+                    file_tree.push(tree_file(segments.clone(), length, hashes, &mut piece_layers));
+                }
+                let Some(pieces) = pieces.as_mut() else {
+                    continue;
+                };
+                files.push(InfoFile {
+                    path: segments,
+                    length,
+                    extra: HashMap::new(),
+                });
+                if v2 && index != last {
+                    files.extend(padding_file(pieces.pad()));
+                }
+            }
+            (None, files)
+        } else {
+            let (length, hashes) = self.hash_file(&root, pieces.as_mut())?;
+            if let Some(hashes) = hashes {
+                file_tree.push(tree_file(vec![name.clone()], length, hashes, &mut piece_layers));
+            }
+            (pieces.is_some().then_some(length), Vec::new())
+        };
+
+        let torrent = TorrentFile {
+            announce,
+            announce_list,
+            comment: self.comment.clone(),
+            created_by: self.created_by.clone().filter(|_| !self.reproducible),
+            creation_date: self.creation_date,
+            encoding: None,
+            info: Info {
+                name,
+                piece_length: self.piece_length,
+                pieces: pieces.map(PieceHasher::finish).unwrap_or_default(),
+                private: self.private,
+                source: None,
+                length,
+                files,
+                meta_version: v2.then_some(META_VERSION_2),
+                file_tree,
+                extra: HashMap::new(),
+            },
+            piece_layers,
+            extra: HashMap::new(),
+        };
+        torrent.validate()?;
+        Ok(torrent)
+    }
+
+    /// Reads a file, feeding the v1 piece hasher if there is one, and returns
+    /// its length and, for a v2 or hybrid torrent, its merkle hashes
+    fn hash_file(&self, path: &Path, mut pieces: Option<&mut PieceHasher>) -> Result<(u64, Option<FileHashes>), String> {
+        let mut tree = match self.meta_version {
+            MetaVersion::V1 => None,
+            _ => Some(MerkleHasher::new(self.piece_length)?.with_shared_hasher(self.hasher.clone())),
+        };
+        let length = read_file(path, |data| {
+            if let Some(pieces) = pieces.as_deref_mut() {
+                pieces.update(data);
+            }
+            if let Some(tree) = tree.as_mut() {
+                tree.update(data);
+            }
+        })?;
+        Ok((length, tree.map(MerkleHasher::finish)))
+    }
+}
+
+/// Builds the file tree entry of a file, keeping its piece layer
+fn tree_file(
+    path: Vec<String>,
+    length: u64,
+    hashes: FileHashes,
+    piece_layers: &mut PieceLayers,
+) -> TreeFile {
+    if let Some(root) = hashes.pieces_root
+        && !hashes.piece_layer.is_empty()
+    {
+        piece_layers.insert(root, hashes.piece_layer);
+    }
+    TreeFile {
+        path,
+        length,
+        pieces_root: hashes.pieces_root,
+        extra: HashMap::new(),
+    }
+}
+
+/// Returns the padding file (BEP 47) of `length` zeros, if any are needed
+fn padding_file(length: u64) -> Option<InfoFile> {
+    (length > 0).then(|| InfoFile {
+        path: vec![".pad".to_string(), length.to_string()],
+        length,
+        extra: HashMap::from([("attr".to_string(), Node::from("p"))]),
+    })
+}
+
+/// Reads a whole file into `sink`, returning its length
+fn read_file(path: &Path, mut sink: impl FnMut(&[u8])) -> Result<u64, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let expected = file.metadata().map_err(|e| e.to_string())?.len();
+    let mut buffer = vec![0; READ_BUFFER];
+    let mut length = 0u64;
+    loop {
+        let read = file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if read == 0 {
+            break;
+        }
+        length += read as u64;
+        sink(&buffer[..read]);
+    }
+    if length != expected {
+        // The file changed while it was being read
+        return Err(ERR_INVALID_FILE_ENTRY.to_string());
+    }
+    Ok(length)
+}
+
+/// Appends the regular files below `directory` to `found`, in name order, with
+/// their path segments relative to the torrent root
+fn collect_files(directory: &Path, prefix: &mut Vec<String>, found: &mut Vec<(PathBuf, Vec<String>)>) -> Result<(), String> {
+    let mut entries = fs::read_dir(directory)
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+    entries.sort_by_key(|entry| entry.file_name());
+    for entry in entries {
+        let kind = entry.file_type().map_err(|e| e.to_string())?;
+        if kind.is_symlink() {
+            continue;
+        }
+        let name = entry
+            .file_name()
+            .into_string()
+            .map_err(|_| ERR_INVALID_FILE_ENTRY.to_string())?;
+        prefix.push(name);
+        if kind.is_dir() {
+            collect_files(&entry.path(), prefix, found)?;
+        } else if kind.is_file() {
+            found.push((entry.path(), prefix.clone()));
+        }
+        prefix.pop();
+    }
+    Ok(())
+}
+
+/// Hashes a payload piece by piece as its files are fed in
+struct PieceHasher {
+    piece_length: u64,
+    hasher: SharedHasher,
+    /// Bytes of the current piece read so far
+    piece: Vec<u8>,
+    pieces: Vec<u8>,
+}
+
+impl PieceHasher {
+    fn new(piece_length: u64, hasher: SharedHasher) -> Self {
+        Self {
+            piece_length,
+            hasher,
+            piece: Vec::new(),
+            pieces: Vec::new(),
+        }
+    }
+
+    /// Feeds payload bytes, completing pieces as they fill
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            let take = ((self.piece_length - self.piece.len() as u64) as usize).min(data.len());
+            self.piece.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.piece.len() as u64 == self.piece_length {
+                self.finish_piece();
+            }
+        }
+    }
+
+    /// Fills the current piece with zeros, returning how many were added
+    fn pad(&mut self) -> u64 {
+        if self.piece.is_empty() {
+            return 0;
+        }
+        let length = self.piece_length - self.piece.len() as u64;
+        self.piece.resize(self.piece_length as usize, 0);
+        self.finish_piece();
+        length
+    }
+
+    fn finish_piece(&mut self) {
+        self.pieces.extend_from_slice(&self.hasher.get().sha1(&self.piece));
+        self.piece.clear();
+    }
+
+    /// Hashes the final short piece, if any, and returns all piece hashes
+    fn finish(mut self) -> Vec<u8> {
+        if !self.piece.is_empty() {
+            self.finish_piece();
+        }
+        self.pieces
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+    use crate::test_support::create_test_dir;
+    use crate::torrent::sha1::sha1;
+    use crate::torrent::{IncrementalVerifier, PieceEvent, piece_map};
+
+    #[test]
+    fn single_file_torrents_hash_each_piece() {
+        let root = create_test_dir("builder_single", &[("data.bin", b"0123456789")]);
+        let torrent = TorrentBuilder::new()
+            .with_piece_length(4)
+            .with_private(true)
+            .with_comment("test")
+            .with_created_by("bencode_lib")
+            .build(root.join("data.bin"))
+            .unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(torrent.info.name, "data.bin");
+        assert_eq!(torrent.info.length, Some(10));
+        let mut expected = sha1(b"0123").to_vec();
+        expected.extend(sha1(b"4567"));
+        expected.extend(sha1(b"89"));
+        assert_eq!(torrent.info.pieces, expected);
+        assert!(torrent.info.private);
+        assert_eq!(torrent.comment.as_deref(), Some("test"));
+    }
+
+    /// Hashes every piece to its length
+    #[derive(Debug)]
+    struct Lengths;
+
+    impl Hasher for Lengths {
+        fn sha1(&self, data: &[u8]) -> [u8; 20] {
+            [data.len() as u8; 20]
+        }
+
+        fn sha256(&self, data: &[u8]) -> [u8; 32] {
+            [data.len() as u8; 32]
+        }
+    }
+
+    #[test]
+    fn pieces_are_hashed_with_the_given_backend() {
+        let root = create_test_dir("builder_hasher", &[("data.bin", b"0123456789")]);
+        let torrent = TorrentBuilder::new()
+            .with_piece_length(4)
+            .with_hasher(Lengths)
+            .build(root.join("data.bin"))
+            .unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(torrent.info.pieces, [[4; 20], [4; 20], [2; 20]].concat());
+    }
+
+    #[test]
+    fn directories_become_multi_file_torrents_in_name_order() {
+        let root = create_test_dir(
+            "builder_multi",
+            &[("b.txt", b"bbbbb"), ("a/z.txt", b"zz"), ("a/empty", b""), ("c.txt", b"c")],
+        );
+        let torrent = TorrentBuilder::new()
+            .with_piece_length(3)
+            .with_announce_tier(["udp://one", "udp://two"])
+            .with_announce_tier(["http://backup"])
+            .build(&root)
+            .unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(Some(torrent.info.name.as_ref()), root.file_name());
+        let paths: Vec<_> = torrent.info.files.iter().map(|file| file.path.join("/")).collect();
+        assert_eq!(paths, ["a/empty", "a/z.txt", "b.txt", "c.txt"]);
+        assert_eq!(torrent.announce.as_ref().map(TrackerUrl::as_str), Some("udp://one"));
+        assert_eq!(torrent.trackers(), ["udp://one", "udp://two", "http://backup"]);
+
+        // The pieces verify against the concatenated payload
+        let encoded = torrent.to_bytes().unwrap();
+        let mut verifier = IncrementalVerifier::new(piece_map(&encoded).unwrap());
+        let events = verifier.write(0, b"zzbbbbbc").unwrap();
+        assert_eq!(events.iter().filter(|e| matches!(e, PieceEvent::Passed { .. })).count(), 3);
+    }
+
+    #[test]
+    fn reproducible_builds_depend_only_on_the_payload() {
+        let files: &[(&str, &[u8])] = &[("b.txt", b"bbbbb"), ("a/z.txt", b"zz"), ("a-b", b"-")];
+        let first = create_test_dir("builder_reproducible_1/data", files);
+        let second = create_test_dir("builder_reproducible_2/data", &[files[2], files[1], files[0]]);
+        let build = |path: &Path| {
+            TorrentBuilder::new()
+                .with_reproducible(true)
+                .with_piece_length(4)
+                .with_created_by("tool 1.0")
+                .with_creation_date(1_700_000_000)
+                .build(path)
+                .unwrap()
+        };
+        let (one, two) = (build(&first), build(&second));
+        let nameless = TorrentBuilder::new().with_reproducible(true).build(first.join("a/.."));
+        let named = TorrentBuilder::new().with_reproducible(true).with_name("set").build(first.join("a/.."));
+        fs::remove_dir_all(first.parent().unwrap()).unwrap();
+        fs::remove_dir_all(second.parent().unwrap()).unwrap();
+
+        assert_eq!(one.to_bytes().unwrap(), two.to_bytes().unwrap());
+        let paths: Vec<_> = one.info.files.iter().map(|file| file.path.join("/")).collect();
+        assert_eq!(paths, ["a/z.txt", "a-b", "b.txt"]);
+        assert_eq!((one.info.name.as_str(), one.created_by, one.creation_date), ("data", None, Some(1_700_000_000)));
+        assert_eq!(ErrorKind::from_message(&nameless.unwrap_err()), ErrorKind::InvalidFileEntry);
+        assert_eq!(named.unwrap().info.name, "set");
+    }
+
+    #[test]
+    fn v2_and_hybrid_torrents_hash_each_file_into_a_tree() {
+        let large: Vec<u8> = (0..40000).map(|i| (i % 241) as u8).collect();
+        let root = create_test_dir("builder_v2", &[("a", &large), ("b/c", b"small"), ("e", b"")]);
+        let build = |version| {
+            TorrentBuilder::new()
+                .with_piece_length(16384)
+                .with_meta_version(version)
+                .build(&root)
+                .unwrap()
+        };
+        let (v2, hybrid) = (build(MetaVersion::V2), build(MetaVersion::Hybrid));
+        let single = TorrentBuilder::new()
+            .with_piece_length(16384)
+            .with_meta_version(MetaVersion::Hybrid)
+            .build(root.join("a"))
+            .unwrap();
+        let unusable = TorrentBuilder::new()
+            .with_piece_length(1000)
+            .with_meta_version(MetaVersion::V2)
+            .build(&root);
+        fs::remove_dir_all(&root).unwrap();
+
+        let mut hasher = MerkleHasher::new(16384).unwrap();
+        hasher.update(&large);
+        let hashes = hasher.finish();
+        assert_eq!(v2.version(), MetaVersion::V2);
+        assert!(v2.info.pieces.is_empty() && v2.info.files.is_empty());
+        let tree: Vec<_> = v2.info.file_tree.iter().map(|file| (file.path.join("/"), file.length)).collect();
+        assert_eq!(tree, [("a".to_string(), 40000), ("b/c".to_string(), 5), ("e".to_string(), 0)]);
+        assert_eq!(v2.info.file_tree[0].pieces_root, hashes.pieces_root);
+        assert_eq!(v2.piece_layers.get(&hashes.pieces_root.unwrap()), Some(&hashes.piece_layer));
+        assert_eq!(v2.piece_layers.len(), 1);
+        assert_eq!(TorrentFile::from_bytes(&v2.to_bytes().unwrap()).unwrap(), v2);
+
+        // Each file but the last is padded to a piece boundary
+        assert_eq!(hybrid.version(), MetaVersion::Hybrid);
+        let files: Vec<_> = hybrid.info.files.iter().map(|file| (file.path.join("/"), file.is_padding())).collect();
+        let padding = |name: &str| (name.to_string(), true);
+        assert_eq!(files, [("a".to_string(), false), padding(".pad/9152"), ("b/c".to_string(), false), padding(".pad/16379"), ("e".to_string(), false)]);
+        assert_eq!(hybrid.total_length(), 4 * 16384);
+        assert_eq!(hybrid.info.piece_count(), 4);
+        assert_eq!(hybrid.info.pieces[60..], sha1(&[b"small".as_slice(), &[0; 16379]].concat()));
+        assert_eq!((&hybrid.info.file_tree, &hybrid.piece_layers), (&v2.info.file_tree, &v2.piece_layers));
+        assert_eq!(TorrentFile::from_bytes(&hybrid.to_bytes().unwrap()).unwrap(), hybrid);
+
+        assert_eq!((single.info.length, single.is_multi_file()), (Some(40000), false));
+        assert_eq!(single.info.file_tree[0].path, ["a"]);
+        assert_eq!(ErrorKind::from_message(&unusable.unwrap_err()), ErrorKind::InvalidPieceLength);
+    }
+
+    #[test]
+    fn unusable_sources_are_rejected() {
+        let root = create_test_dir("builder_empty", &[]);
+        let error = TorrentBuilder::new().build(&root).unwrap_err();
+        assert_eq!(ErrorKind::from_message(&error), ErrorKind::InvalidFileEntry);
+        let error = TorrentBuilder::new().with_piece_length(0).build(&root).unwrap_err();
+        assert_eq!(ErrorKind::from_message(&error), ErrorKind::InvalidPieceLength);
+        let error = TorrentBuilder::new().with_announce_tier(["udp://one", "two"]).build(&root).unwrap_err();
+        assert_eq!(ErrorKind::from_message(&error), ErrorKind::InvalidTrackerUrl);
+        fs::remove_dir_all(&root).unwrap();
+        assert!(TorrentBuilder::new().build(&root).is_err());
+    }
+}
@@ -0,0 +1,530 @@
+//! Typed DHT messages (KRPC, BEP 5).
+//!
+//! Every DHT packet is one bencoded dictionary: `t` holds a transaction id
+//! echoed in the reply, `y` says whether it is a query (`q`), a response (`r`)
+//! or an error (`e`), and the payload sits under `a`, `r` or `e`. Node ids,
+//! info hashes, tokens and contact lists are binary strings. [`KrpcMessage`]
+//! converts between that dictionary and typed fields, so callers do not need
+//! to pick values out of the tree by hand.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use core::net::{Ipv4Addr, SocketAddr, SocketAddrV4};
+
+use crate::HashMap;
+use crate::error::messages::ERR_INVALID_KRPC_MESSAGE;
+use crate::nodes::integer::Integer;
+use crate::nodes::node::Node;
+use crate::parser::default::parse_bytes;
+use crate::stringify::default::stringify_to_bytes;
+
+use super::sha1::SHA1_LENGTH;
+use super::tracker::{encode_compact_peers, parse_compact_peers, parse_compact_peers6};
+
+/// Bytes per node in a compact node list: node id, IPv4 address, then port
+pub const COMPACT_NODE_LENGTH: usize = SHA1_LENGTH + 6;
+
+/// A 160-bit DHT node id, in the same space as info hashes
+pub type NodeId = [u8; SHA1_LENGTH];
+
+/// Contact information for a DHT node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CompactNode {
+    /// The node's id
+    pub id: NodeId,
+    /// Where the node listens for UDP packets
+    pub address: SocketAddrV4,
+}
+
+/// Decodes a compact node list (the `nodes` field of a response).
+///
+/// # Returns
+/// * `Ok(Vec<CompactNode>)` - The nodes in list order
+/// * `Err(String)` - If the length is not a multiple of 26 bytes
+pub fn parse_compact_nodes(bytes: &[u8]) -> Result<Vec<CompactNode>, String> {
+    if !bytes.len().is_multiple_of(COMPACT_NODE_LENGTH) {
+        return Err(ERR_INVALID_KRPC_MESSAGE.to_string());
+    }
+    Ok(bytes
+        .chunks_exact(COMPACT_NODE_LENGTH)
+        .map(|entry| {
+            let (id, contact) = entry.split_at(SHA1_LENGTH);
+            CompactNode {
+                id: id.try_into().unwrap_or_default(),
+                address: SocketAddrV4::new(
+                    Ipv4Addr::new(contact[0], contact[1], contact[2], contact[3]),
+                    u16::from_be_bytes([contact[4], contact[5]]),
+                ),
+            }
+        })
+        .collect())
+}
+
+/// Encodes nodes as a compact node list.
+pub fn encode_compact_nodes(nodes: &[CompactNode]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(nodes.len() * COMPACT_NODE_LENGTH);
+    for node in nodes {
+        bytes.extend_from_slice(&node.id);
+        bytes.extend_from_slice(&node.address.ip().octets());
+        bytes.extend_from_slice(&node.address.port().to_be_bytes());
+    }
+    bytes
+}
+
+/// Arguments of a `ping` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ping {
+    /// Id of the querying node
+    pub id: NodeId,
+}
+
+/// Arguments of a `find_node` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FindNode {
+    /// Id of the querying node
+    pub id: NodeId,
+    /// Id of the node being looked for
+    pub target: NodeId,
+}
+
+/// Arguments of a `get_peers` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetPeers {
+    /// Id of the querying node
+    pub id: NodeId,
+    /// Info hash of the torrent whose peers are wanted
+    pub info_hash: NodeId,
+}
+
+/// Arguments of an `announce_peer` query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnouncePeer {
+    /// Id of the querying node
+    pub id: NodeId,
+    /// Info hash of the torrent being announced
+    pub info_hash: NodeId,
+    /// Port the querying peer accepts connections on
+    pub port: u16,
+    /// `implied_port`: use the packet's source port instead of `port`
+    pub implied_port: bool,
+    /// Token from an earlier `get_peers` response of the queried node
+    pub token: Vec<u8>,
+}
+
+/// A query, by method.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Query {
+    /// `ping`
+    Ping(Ping),
+    /// `find_node`
+    FindNode(FindNode),
+    /// `get_peers`
+    GetPeers(GetPeers),
+    /// `announce_peer`
+    AnnouncePeer(AnnouncePeer),
+    /// A method this module does not model, such as the BEP 44 `get` and `put`
+    Other {
+        /// Value of `q`
+        method: String,
+        /// Value of `a`
+        arguments: HashMap<String, Node>,
+    },
+}
+
+impl Query {
+    /// Returns the method name sent in `q`
+    pub fn method(&self) -> &str {
+        match self {
+            Query::Ping(_) => "ping",
+            Query::FindNode(_) => "find_node",
+            Query::GetPeers(_) => "get_peers",
+            Query::AnnouncePeer(_) => "announce_peer",
+            Query::Other { method, .. } => method,
+        }
+    }
+
+    fn from_arguments(method: &str, arguments: &HashMap<String, Node>) -> Result<Query, String> {
+        let id = || node_id(arguments, "id");
+        Ok(match method {
+            "ping" => Query::Ping(Ping { id: id()? }),
+            "find_node" => Query::FindNode(FindNode {
+                id: id()?,
+                target: node_id(arguments, "target")?,
+            }),
+            "get_peers" => Query::GetPeers(GetPeers {
+                id: id()?,
+                info_hash: node_id(arguments, "info_hash")?,
+            }),
+            "announce_peer" => Query::AnnouncePeer(AnnouncePeer {
+                id: id()?,
+                info_hash: node_id(arguments, "info_hash")?,
+                port: arguments
+                    .get("port")
+                    .and_then(Node::as_integer)
+                    .and_then(|&port| u16::try_from(port).ok())
+                    .ok_or_else(invalid)?,
+                implied_port: arguments.get("implied_port").and_then(Node::as_integer).is_some_and(|&flag| flag != 0),
+                token: required_bytes(arguments, "token")?.to_vec(),
+            }),
+            _ => Query::Other {
+                method: method.to_string(),
+                arguments: arguments.clone(),
+            },
+        })
+    }
+
+    fn to_arguments(&self) -> HashMap<String, Node> {
+        let mut arguments = HashMap::new();
+        let mut add = |key: &str, value: Node| {
+            arguments.insert(key.to_string(), value);
+        };
+        match self {
+            Query::Ping(query) => add("id", Node::Bytes(query.id.to_vec())),
+            Query::FindNode(query) => {
+                add("id", Node::Bytes(query.id.to_vec()));
+                add("target", Node::Bytes(query.target.to_vec()));
+            }
+            Query::GetPeers(query) => {
+                add("id", Node::Bytes(query.id.to_vec()));
+                add("info_hash", Node::Bytes(query.info_hash.to_vec()));
+            }
+            Query::AnnouncePeer(query) => {
+                add("id", Node::Bytes(query.id.to_vec()));
+                add("info_hash", Node::Bytes(query.info_hash.to_vec()));
+                add("port", Node::Integer(Integer::from(query.port)));
+                if query.implied_port {
+                    add("implied_port", Node::Integer(1));
+                }
+                add("token", Node::Bytes(query.token.clone()));
+            }
+            Query::Other { arguments, .. } => return arguments.clone(),
+        }
+        arguments
+    }
+}
+
+/// The values of a response.
+///
+/// Responses do not name the query they answer; the caller matches them by
+/// transaction id. Fields a method does not return are left empty.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    /// Id of the responding node
+    pub id: NodeId,
+    /// `nodes`: closest nodes known to the responder (`find_node`, `get_peers`)
+    pub nodes: Vec<CompactNode>,
+    /// `values`: peers of the torrent (`get_peers`)
+    pub values: Vec<SocketAddr>,
+    /// `token`: to be sent back in `announce_peer` (`get_peers`)
+    pub token: Option<Vec<u8>>,
+    /// Other keys of the response
+    pub extra: HashMap<String, Node>,
+}
+
+impl Response {
+    /// Creates a response carrying only the responder's id, as for `ping`
+    pub fn new(id: NodeId) -> Self {
+        Response {
+            id,
+            nodes: Vec::new(),
+            values: Vec::new(),
+            token: None,
+            extra: HashMap::new(),
+        }
+    }
+
+    fn from_values(values: &HashMap<String, Node>) -> Result<Response, String> {
+        let mut peers = Vec::new();
+        let list = match values.get("values") {
+            Some(list) => list.as_list().ok_or_else(invalid)?.as_slice(),
+            None => &[],
+        };
+        for value in list {
+            let bytes = value.as_bytes().ok_or_else(invalid)?;
+            let decoded = match bytes.len() {
+                6 => parse_compact_peers(bytes),
+                18 => parse_compact_peers6(bytes),
+                _ => Err(invalid()),
+            };
+            peers.extend(decoded.map_err(|_| invalid())?);
+        }
+        let nodes = match values.get("nodes") {
+            Some(nodes) => parse_compact_nodes(nodes.as_bytes().ok_or_else(invalid)?)?,
+            None => Vec::new(),
+        };
+        Ok(Response {
+            id: node_id(values, "id")?,
+            nodes,
+            values: peers,
+            token: values.get("token").map(|token| token.as_bytes().map(<[u8]>::to_vec).ok_or_else(invalid)).transpose()?,
+            extra: values
+                .iter()
+                .filter(|(key, _)| !RESPONSE_KEYS.contains(&key.as_str()))
+                .map(|(key, value)| (key.clone(), value.clone()))
+                .collect(),
+        })
+    }
+
+    fn to_values(&self) -> HashMap<String, Node> {
+        let mut values = self.extra.clone();
+        values.insert("id".to_string(), Node::Bytes(self.id.to_vec()));
+        if !self.nodes.is_empty() {
+            values.insert("nodes".to_string(), Node::Bytes(encode_compact_nodes(&self.nodes)));
+        }
+        if !self.values.is_empty() {
+            let peers = self
+                .values
+                .iter()
+                .map(|peer| {
+                    let (peer4, peer6) = encode_compact_peers(core::slice::from_ref(peer));
+                    Node::Bytes(if peer4.is_empty() { peer6 } else { peer4 })
+                })
+                .collect();
+            values.insert("values".to_string(), Node::List(peers));
+        }
+        if let Some(token) = &self.token {
+            values.insert("token".to_string(), Node::Bytes(token.clone()));
+        }
+        values
+    }
+}
+
+/// Keys held in typed fields of [`Response`]
+const RESPONSE_KEYS: [&str; 4] = ["id", "nodes", "token", "values"];
+
+/// The payload of a message, by its `y` type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum KrpcBody {
+    /// `q`: a query and its arguments
+    Query(Query),
+    /// `r`: a response to a query
+    Response(Response),
+    /// `e`: an error code (201 generic, 202 server, 203 protocol, 204 unknown
+    /// method) and message
+    Error {
+        /// Error code
+        code: Integer,
+        /// Human readable description
+        message: String,
+    },
+}
+
+/// A DHT message.
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::dht::{FindNode, KrpcBody, KrpcMessage, Query};
+///
+/// let query = KrpcMessage::query(b"aa", Query::FindNode(FindNode { id: [1; 20], target: [2; 20] }));
+/// let packet = query.to_bytes().unwrap();
+/// assert!(packet.starts_with(b"d1:ad2:id20:"));
+///
+/// let received = KrpcMessage::from_bytes(&packet).unwrap();
+/// assert_eq!(received.transaction_id, b"aa");
+/// assert!(matches!(received.body, KrpcBody::Query(Query::FindNode(FindNode { target: [2, ..], .. }))));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct KrpcMessage {
+    /// `t`: transaction id chosen by the querying node, echoed in the reply
+    pub transaction_id: Vec<u8>,
+    /// The query, response or error
+    pub body: KrpcBody,
+    /// `v`: client version, if the sender included one
+    pub version: Option<Vec<u8>>,
+}
+
+fn invalid() -> String {
+    ERR_INVALID_KRPC_MESSAGE.to_string()
+}
+
+/// Reads a required binary string field
+fn required_bytes<'a>(dict: &'a HashMap<String, Node>, key: &str) -> Result<&'a [u8], String> {
+    dict.get(key).and_then(Node::as_bytes).ok_or_else(invalid)
+}
+
+/// Reads a required 20-byte id field
+fn node_id(dict: &HashMap<String, Node>, key: &str) -> Result<NodeId, String> {
+    required_bytes(dict, key)?.try_into().map_err(|_| invalid())
+}
+
+impl KrpcMessage {
+    /// Creates a query
+    pub fn query(transaction_id: &[u8], query: Query) -> Self {
+        Self::new(transaction_id, KrpcBody::Query(query))
+    }
+
+    /// Creates a response
+    pub fn response(transaction_id: &[u8], response: Response) -> Self {
+        Self::new(transaction_id, KrpcBody::Response(response))
+    }
+
+    /// Creates an error reply
+    pub fn error(transaction_id: &[u8], code: Integer, message: &str) -> Self {
+        let body = KrpcBody::Error {
+            code,
+            message: message.to_string(),
+        };
+        Self::new(transaction_id, body)
+    }
+
+    fn new(transaction_id: &[u8], body: KrpcBody) -> Self {
+        KrpcMessage {
+            transaction_id: transaction_id.to_vec(),
+            body,
+            version: None,
+        }
+    }
+
+    /// Reads a message from its parsed dictionary.
+    ///
+    /// # Returns
+    /// * `Ok(KrpcMessage)` - The message
+    /// * `Err(String)` - If `t` or `y` is missing, or the payload lacks a field
+    ///   its method requires or has one of the wrong type or size
+    pub fn from_node(node: &Node) -> Result<KrpcMessage, String> {
+        let root = node.as_dictionary().ok_or_else(invalid)?;
+        let payload = |key: &str| root.get(key).and_then(Node::as_dictionary).ok_or_else(invalid);
+        let body = match required_bytes(root, "y")? {
+            b"q" => {
+                let method = String::from_utf8_lossy(required_bytes(root, "q")?).into_owned();
+                KrpcBody::Query(Query::from_arguments(&method, payload("a")?)?)
+            }
+            b"r" => KrpcBody::Response(Response::from_values(payload("r")?)?),
+            b"e" => match root.get("e").and_then(Node::as_list).map(Vec::as_slice) {
+                Some([Node::Integer(code), message]) => KrpcBody::Error {
+                    code: *code,
+                    message: String::from_utf8_lossy(message.as_bytes().ok_or_else(invalid)?).into_owned(),
+                },
+                _ => return Err(invalid()),
+            },
+            _ => return Err(invalid()),
+        };
+        Ok(KrpcMessage {
+            transaction_id: required_bytes(root, "t")?.to_vec(),
+            body,
+            version: root.get("v").and_then(Node::as_bytes).map(<[u8]>::to_vec),
+        })
+    }
+
+    /// Reads a message from a received packet.
+    ///
+    /// # Returns
+    /// * `Ok(KrpcMessage)` - The message
+    /// * `Err(String)` - If the packet is malformed or the message invalid
+    pub fn from_bytes(packet: &[u8]) -> Result<KrpcMessage, String> {
+        Self::from_node(&parse_bytes(packet)?)
+    }
+
+    /// Builds the message dictionary.
+    pub fn to_node(&self) -> Node {
+        let mut root = HashMap::new();
+        root.insert("t".to_string(), Node::Bytes(self.transaction_id.clone()));
+        let kind = match &self.body {
+            KrpcBody::Query(query) => {
+                root.insert("q".to_string(), Node::from(query.method()));
+                root.insert("a".to_string(), Node::Dictionary(query.to_arguments()));
+                "q"
+            }
+            KrpcBody::Response(response) => {
+                root.insert("r".to_string(), Node::Dictionary(response.to_values()));
+                "r"
+            }
+            KrpcBody::Error { code, message } => {
+                root.insert("e".to_string(), Node::List(vec![Node::Integer(*code), Node::from(message.as_str())]));
+                "e"
+            }
+        };
+        root.insert("y".to_string(), Node::from(kind));
+        if let Some(version) = &self.version {
+            root.insert("v".to_string(), Node::Bytes(version.clone()));
+        }
+        Node::Dictionary(root)
+    }
+
+    /// Encodes the message as a packet.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u8>)` - The encoded dictionary
+    /// * `Err(String)` - If an `Other` query's arguments hold a None node
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        Ok(stringify_to_bytes(&self.to_node())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    fn round_trip(message: &KrpcMessage) -> KrpcMessage {
+        KrpcMessage::from_bytes(&message.to_bytes().unwrap()).unwrap()
+    }
+
+    #[test]
+    fn queries_round_trip() {
+        let queries = [
+            Query::Ping(Ping { id: [1; 20] }),
+            Query::FindNode(FindNode { id: [1; 20], target: [0xff; 20] }),
+            Query::GetPeers(GetPeers { id: [1; 20], info_hash: [3; 20] }),
+            Query::AnnouncePeer(AnnouncePeer {
+                id: [1; 20],
+                info_hash: [3; 20],
+                port: 6881,
+                implied_port: true,
+                token: b"\x00tok".to_vec(),
+            }),
+            Query::Other {
+                method: "get".to_string(),
+                arguments: HashMap::from([("id".to_string(), Node::Bytes(vec![0xff; 20]))]),
+            },
+        ];
+        for query in queries {
+            let mut message = KrpcMessage::query(b"\x00\x01", query);
+            message.version = Some(b"LT\x01\x02".to_vec());
+            assert_eq!(round_trip(&message), message);
+        }
+    }
+
+    #[test]
+    fn bep5_examples_decode() {
+        let ping = KrpcMessage::from_bytes(b"d1:ad2:id20:abcdefghij0123456789e1:q4:ping1:t2:aa1:y1:qe").unwrap();
+        assert_eq!(ping.body, KrpcBody::Query(Query::Ping(Ping { id: *b"abcdefghij0123456789" })));
+        let error = KrpcMessage::from_bytes(b"d1:eli201e23:A Generic Error Ocurrede1:t2:aa1:y1:ee").unwrap();
+        assert_eq!(error, KrpcMessage::error(b"aa", 201, "A Generic Error Ocurred"));
+        let reply = KrpcMessage::from_bytes(b"d1:rd2:id20:mnopqrstuvwxyz123456e1:t2:aa1:y1:re").unwrap();
+        assert_eq!(reply, KrpcMessage::response(b"aa", Response::new(*b"mnopqrstuvwxyz123456")));
+    }
+
+    #[test]
+    fn responses_carry_nodes_peers_and_tokens() {
+        let mut response = Response::new([9; 20]);
+        response.nodes = vec![CompactNode { id: [7; 20], address: "10.0.0.2:6881".parse().unwrap() }];
+        response.values = vec!["1.2.3.4:80".parse().unwrap(), "[::1]:81".parse().unwrap()];
+        response.token = Some(b"secret".to_vec());
+        response.extra.insert("ip".to_string(), Node::Bytes(vec![0xc0, 0xa8, 0, 1, 0, 80]));
+        let message = KrpcMessage::response(b"zz", response);
+        assert_eq!(round_trip(&message), message);
+        assert_eq!(encode_compact_nodes(&[]), b"");
+        assert_eq!(parse_compact_nodes(&[0; 27]).unwrap_err(), ERR_INVALID_KRPC_MESSAGE);
+    }
+
+    #[test]
+    fn invalid_messages_are_rejected() {
+        for packet in [
+            &b"d1:t2:aa1:y1:xe"[..],
+            b"d1:ad2:id3:abce1:q4:ping1:t2:aa1:y1:qe",
+            b"d1:ad2:id20:abcdefghij0123456789e1:q4:ping1:y1:qe",
+            b"d1:eli201ee1:t2:aa1:y1:ee",
+            b"d1:rd2:id20:mnopqrstuvwxyz1234565:nodes3:abce1:t2:aa1:y1:re",
+            b"li1ee",
+        ] {
+            let error = KrpcMessage::from_bytes(packet).unwrap_err();
+            assert_eq!(ErrorKind::from_message(&error), ErrorKind::InvalidKrpcMessage, "{:?}", packet);
+        }
+    }
+}
@@ -0,0 +1,339 @@
+//! Storage of verified pieces in the files of a torrent.
+//!
+//! [`DiskWriter`] uses a [`PieceMap`] to split each piece over the files it
+//! covers and writes it at the right offsets. Files are created on first use and
+//! sized to their full length straight away, which most filesystems store
+//! sparsely, so pieces can be written in any order. [`verify_pieces`] goes the
+//! other way, hashing the files already on disk to find out which pieces of a
+//! torrent they hold, as a seeding tool or integrity checker would.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use crate::error::messages::{ERR_INVALID_PIECE_DATA, ERR_UNSAFE_PATH};
+use crate::io::path::long_path;
+
+use super::hasher::{DefaultHasher, Hasher};
+use super::is_unsafe_segment;
+use super::metainfo::TorrentFile;
+use super::pieces::{PieceMap, piece_map};
+
+/// Converts a raw path segment to an OS string without loss where possible
+#[cfg(unix)]
+fn segment(bytes: &[u8]) -> PathBuf {
+    use std::ffi::OsStr;
+    use std::os::unix::ffi::OsStrExt;
+    PathBuf::from(OsStr::from_bytes(bytes))
+}
+
+/// Converts a raw path segment to an OS string (lossily if not UTF-8)
+#[cfg(not(unix))]
+fn segment(bytes: &[u8]) -> PathBuf {
+    PathBuf::from(String::from_utf8_lossy(bytes).into_owned())
+}
+
+/// Writes verified pieces to the files of a torrent under a download directory.
+///
+/// # Example
+/// ```no_run
+/// use bencode_lib::torrent::{piece_map, DiskWriter, IncrementalVerifier, PieceEvent};
+///
+/// let torrent = std::fs::read("files/demo.torrent").unwrap();
+/// let map = piece_map(&torrent).unwrap();
+/// let writer = DiskWriter::new("downloads", map.clone()).unwrap();
+/// let mut verifier = IncrementalVerifier::new(map);
+/// # let (offset, block) = (0u64, vec![0u8; 16384]);
+/// for event in verifier.write(offset, &block).unwrap() {
+///     if let PieceEvent::Passed { index, data } = event {
+///         writer.write_piece(index, &data).unwrap();
+///     }
+/// }
+/// ```
+pub struct DiskWriter {
+    map: PieceMap,
+    paths: Vec<PathBuf>,
+}
+
+impl DiskWriter {
+    /// Creates a writer placing the torrent's files under `root`.
+    ///
+    /// Nothing is created until a piece is written or [`allocate`](Self::allocate)
+    /// is called.
+    ///
+    /// # Arguments
+    /// * `root` - The download directory
+    /// * `map` - The torrent's piece layout
+    ///
+    /// # Returns
+    /// * `Ok(DiskWriter)` - The writer
+    /// * `Err(String)` - If a file path has an empty, `.` or `..` segment or a
+    ///   segment containing a separator, which could write outside `root`
+    pub fn new<P: AsRef<Path>>(root: P, map: PieceMap) -> Result<Self, String> {
+        let mut paths = Vec::with_capacity(map.files().len());
+        for file in map.files() {
+            if file.path.iter().any(|part| is_unsafe_segment(part)) {
+                return Err(ERR_UNSAFE_PATH.to_string());
+            }
+            let mut path = root.as_ref().to_path_buf();
+            path.extend(file.path.iter().map(|part| segment(part)));
+            paths.push(path);
+        }
+        Ok(Self { map, paths })
+    }
+
+    /// Returns the piece layout being written
+    pub fn map(&self) -> &PieceMap {
+        &self.map
+    }
+
+    /// Returns the path each file of the torrent is written to
+    pub fn paths(&self) -> &[PathBuf] {
+        &self.paths
+    }
+
+    /// Opens a file for writing, creating it (and its directories) at full length
+    fn open(&self, file: usize) -> Result<File, String> {
+        let path = long_path(&self.paths[file]);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let handle = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .map_err(|e| e.to_string())?;
+        let length = self.map.files()[file].length;
+        if handle.metadata().map_err(|e| e.to_string())?.len() < length {
+            handle.set_len(length).map_err(|e| e.to_string())?;
+        }
+        Ok(handle)
+    }
+
+    /// Creates every file of the torrent at its full length, including empty
+    /// files that no piece covers.
+    pub fn allocate(&self) -> Result<(), String> {
+        (0..self.paths.len()).try_for_each(|file| self.open(file).map(drop))
+    }
+
+    /// Writes a verified piece to the files and offsets it covers.
+    ///
+    /// # Arguments
+    /// * `index` - Index of the piece
+    /// * `data` - The piece, exactly as long as the piece map says
+    ///
+    /// # Returns
+    /// * `Ok(())` - The piece was written
+    /// * `Err(String)` - If the index or length does not match the piece map, or
+    ///   on an I/O error
+    pub fn write_piece(&self, index: usize, data: &[u8]) -> Result<(), String> {
+        if self.map.piece_size(index) != Some(data.len()) {
+            return Err(ERR_INVALID_PIECE_DATA.to_string());
+        }
+        for slice in self.map.file_slices(index) {
+            let mut file = self.open(slice.file)?;
+            file.seek(SeekFrom::Start(slice.file_offset)).map_err(|e| e.to_string())?;
+            file.write_all(&data[slice.piece_offset..slice.piece_offset + slice.length])
+                .map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Reads a piece back from disk, e.g. to recheck a resumed download.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u8>)` - The piece as currently stored (unwritten parts read as zeros)
+    /// * `Err(String)` - If the index is out of range or a file cannot be read
+    pub fn read_piece(&self, index: usize) -> Result<Vec<u8>, String> {
+        self.read(index).map(|(data, _)| data)
+    }
+
+    /// Reads a piece only if every byte of it is present on disk
+    fn read_piece_exact(&self, index: usize) -> Option<Vec<u8>> {
+        self.read(index).ok().and_then(|(data, complete)| complete.then_some(data))
+    }
+
+    /// Reads a piece, zero filling what short files lack, and reports whether
+    /// the files held all of it
+    fn read(&self, index: usize) -> Result<(Vec<u8>, bool), String> {
+        let size = self.map.piece_size(index).ok_or_else(|| ERR_INVALID_PIECE_DATA.to_string())?;
+        let mut data = vec![0; size];
+        let mut complete = true;
+        for slice in self.map.file_slices(index) {
+            let mut file = File::open(long_path(&self.paths[slice.file])).map_err(|e| e.to_string())?;
+            file.seek(SeekFrom::Start(slice.file_offset)).map_err(|e| e.to_string())?;
+            let target = &mut data[slice.piece_offset..slice.piece_offset + slice.length];
+            // A short file leaves the rest of the slice zeroed
+            let mut read = 0;
+            while read < target.len() {
+                match file.read(&mut target[read..]).map_err(|e| e.to_string())? {
+                    0 => break,
+                    n => read += n,
+                }
+            }
+            complete &= read == target.len();
+        }
+        Ok((data, complete))
+    }
+}
+
+/// Hashes the torrent's files under `root` piece by piece and checks each piece
+/// against the torrent's `pieces` string.
+///
+/// A piece is valid only if every file it covers can be read and the data hashes
+/// to the expected value, so missing, short or corrupt files just mark the
+/// pieces they cover as invalid.
+///
+/// # Arguments
+/// * `torrent` - The torrent to check the files against
+/// * `root` - The download directory, laid out as [`DiskWriter`] writes it
+/// * `progress` - Called with each piece index and whether it was valid, in order
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - A bitfield of the valid pieces in the peer wire protocol's
+///   layout: the high bit of the first byte is piece 0 and spare bits are zero
+/// * `Err(String)` - If the torrent fails [`TorrentFile::validate`], its pieces
+///   do not match its length or a file path could leave `root`
+///
+/// # Example
+/// ```no_run
+/// use bencode_lib::torrent::{TorrentFile, verify_pieces};
+///
+/// let torrent = TorrentFile::from_file("files/demo.torrent").unwrap();
+/// let bitfield = verify_pieces(&torrent, "downloads".as_ref(), |index, valid| {
+///     if !valid {
+///         println!("piece {} needs downloading", index);
+///     }
+/// })
+/// .unwrap();
+/// let have: u32 = bitfield.iter().map(|byte| byte.count_ones()).sum();
+/// println!("{} of {} pieces on disk", have, torrent.info.piece_count());
+/// ```
+pub fn verify_pieces(torrent: &TorrentFile, root: &Path, progress: impl FnMut(usize, bool)) -> Result<Vec<u8>, String> {
+    verify_pieces_with(torrent, root, &DefaultHasher, progress)
+}
+
+/// Checks the torrent's files under `root` as [`verify_pieces`] does, hashing
+/// with `hasher`.
+///
+/// # Arguments
+/// * `torrent` - The torrent to check the files against
+/// * `root` - The download directory
+/// * `hasher` - The SHA-1 backend
+/// * `progress` - Called with each piece index and whether it was valid, in order
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - A bitfield of the valid pieces, high bit first
+/// * `Err(String)` - If the torrent is invalid or a file path could leave `root`
+pub fn verify_pieces_with(
+    torrent: &TorrentFile,
+    root: &Path,
+    hasher: &dyn Hasher,
+    mut progress: impl FnMut(usize, bool),
+) -> Result<Vec<u8>, String> {
+    let map = piece_map(&torrent.to_bytes()?)?;
+    let count = map.piece_count();
+    let reader = DiskWriter::new(root, map)?;
+    let mut bitfield = vec![0u8; count.div_ceil(8)];
+    for index in 0..count {
+        let valid = reader
+            .read_piece_exact(index)
+            .is_some_and(|data| reader.map.piece_hash(index) == Some(&hasher.sha1(&data)));
+        if valid {
+            bitfield[index / 8] |= 0x80 >> (index % 8);
+        }
+        progress(index, valid);
+    }
+    Ok(bitfield)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::torrent::pieces::piece_map;
+    use crate::torrent::sha1::sha1;
+
+    const PAYLOAD: &[u8] = b"abcdefghij";
+
+    /// Builds a torrent of PAYLOAD split over files of 3, 0 and 7 bytes with
+    /// 4 byte pieces
+    fn torrent(name: &str, file: &str) -> Vec<u8> {
+        let mut data = b"d4:infod5:filesl".to_vec();
+        for (index, length) in [3, 0, 7].iter().enumerate() {
+            let file = format!("{}{}", file, index);
+            data.extend(format!("d6:lengthi{}e4:pathl3:sub{}:{}ee", length, file.len(), file).bytes());
+        }
+        data.extend(format!("e4:name{}:{}12:piece lengthi4e6:pieces60:", name.len(), name).bytes());
+        for piece in PAYLOAD.chunks(4) {
+            data.extend(sha1(piece));
+        }
+        data.extend(b"ee");
+        data
+    }
+
+    #[test]
+    fn pieces_are_written_across_files_in_any_order() {
+        let root = PathBuf::from("test_disk_writer");
+        let _ = fs::remove_dir_all(&root);
+        let map = piece_map(&torrent("demo", "f")).unwrap();
+        let writer = DiskWriter::new(&root, map).unwrap();
+        writer.write_piece(2, b"ij").unwrap();
+        // Files are sized up front, so later bytes are already in place
+        assert_eq!(fs::metadata(root.join("demo/sub/f2")).unwrap().len(), 7);
+        writer.write_piece(0, b"abcd").unwrap();
+        writer.write_piece(1, b"efgh").unwrap();
+        writer.allocate().unwrap();
+
+        let first = fs::read(root.join("demo/sub/f0")).unwrap();
+        let empty = fs::read(root.join("demo/sub/f1")).unwrap();
+        let last = fs::read(root.join("demo/sub/f2")).unwrap();
+        let reread = writer.read_piece(1).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(first, b"abc");
+        assert!(empty.is_empty());
+        assert_eq!(last, b"defghij");
+        assert_eq!(reread, b"efgh");
+    }
+
+    #[test]
+    fn verify_pieces_reports_the_pieces_on_disk() {
+        let root = PathBuf::from("test_disk_verify");
+        let _ = fs::remove_dir_all(&root);
+        let torrent = TorrentFile::from_bytes(&torrent("demo", "f")).unwrap();
+        fs::create_dir_all(root.join("demo/sub")).unwrap();
+        fs::write(root.join("demo/sub/f0"), b"abc").unwrap();
+        fs::write(root.join("demo/sub/f2"), b"defgXij").unwrap();
+        let mut seen = Vec::new();
+        let corrupt = verify_pieces(&torrent, &root, |index, valid| seen.push((index, valid))).unwrap();
+        // A truncated file fails the pieces it no longer covers in full
+        fs::write(root.join("demo/sub/f2"), b"defgh").unwrap();
+        let short = verify_pieces(&torrent, &root, |_, _| {}).unwrap();
+        fs::remove_file(root.join("demo/sub/f0")).unwrap();
+        let missing = verify_pieces(&torrent, &root, |_, _| {}).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(seen, [(0, true), (1, false), (2, true)]);
+        assert_eq!(corrupt, [0b1010_0000]);
+        assert_eq!(short, [0b1100_0000]);
+        assert_eq!(missing, [0b0100_0000]);
+        assert_eq!(verify_pieces(&torrent, Path::new("test_disk_verify_none"), |_, _| {}).unwrap(), [0]);
+    }
+
+    #[test]
+    fn unsafe_paths_are_rejected() {
+        let map = piece_map(&torrent("..", "f")).unwrap();
+        assert_eq!(DiskWriter::new("test_disk_unsafe", map).err().as_deref(), Some(ERR_UNSAFE_PATH));
+    }
+
+    #[test]
+    fn pieces_of_the_wrong_length_are_rejected() {
+        let map = piece_map(&torrent("demo", "f")).unwrap();
+        let writer = DiskWriter::new("test_disk_length", map).unwrap();
+        assert_eq!(writer.write_piece(0, b"abc").unwrap_err(), ERR_INVALID_PIECE_DATA);
+        assert_eq!(writer.write_piece(3, b"").unwrap_err(), ERR_INVALID_PIECE_DATA);
+        assert!(!Path::new("test_disk_length").exists());
+    }
+}
@@ -0,0 +1,379 @@
+//! Extension protocol messages: the extended handshake (BEP 10) and metadata
+//! exchange with `ut_metadata` (BEP 9).
+//!
+//! Both are bencoded dictionaries sent as the payload of an extended message,
+//! after its id byte. A `ut_metadata` data message is followed, in the same
+//! payload, by up to 16 KiB of raw info dictionary bytes that are not part of
+//! the bencode; [`MetadataMessage::from_bytes`] splits the two apart.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::HashMap;
+use crate::error::messages::{ERR_INVALID_EXTENSION_MESSAGE, ERR_NOT_A_DICTIONARY};
+use crate::nodes::node::Node;
+use crate::parser::default::parse_bytes;
+use crate::stringify::default::stringify_to_bytes;
+
+use super::dict_entries;
+use super::metainfo::{check_fits, integer, optional_count, others, text};
+
+/// Size of every metadata piece except the last
+pub const METADATA_PIECE_SIZE: usize = 16384;
+/// Name of the metadata exchange extension in a handshake's `m` dictionary
+pub const UT_METADATA: &str = "ut_metadata";
+
+/// Keys held in typed fields of [`MetadataMessage`]
+const MESSAGE_KEYS: [&str; 3] = ["msg_type", "piece", "total_size"];
+/// Keys held in typed fields of [`ExtendedHandshake`]
+const HANDSHAKE_KEYS: [&str; 5] = ["m", "metadata_size", "p", "reqq", "v"];
+
+/// Number of metadata pieces an info dictionary of `metadata_size` bytes is split into.
+pub fn metadata_piece_count(metadata_size: u64) -> u64 {
+    metadata_size.div_ceil(METADATA_PIECE_SIZE as u64)
+}
+
+/// The `msg_type` of a `ut_metadata` message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataMessageType {
+    /// Asks for a piece of the metadata (0)
+    Request,
+    /// Carries a piece of the metadata (1)
+    Data,
+    /// Refuses a request (2)
+    Reject,
+}
+
+impl MetadataMessageType {
+    /// Returns the value sent in `msg_type`
+    pub const fn code(self) -> u64 {
+        match self {
+            MetadataMessageType::Request => 0,
+            MetadataMessageType::Data => 1,
+            MetadataMessageType::Reject => 2,
+        }
+    }
+
+    /// Returns the message type for a `msg_type` value, or None if it is unknown
+    pub const fn from_code(code: u64) -> Option<Self> {
+        match code {
+            0 => Some(MetadataMessageType::Request),
+            1 => Some(MetadataMessageType::Data),
+            2 => Some(MetadataMessageType::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// A `ut_metadata` message.
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::{MetadataMessage, MetadataMessageType};
+///
+/// let message = MetadataMessage::data(0, 11, b"d4:name1:xe".to_vec());
+/// let payload = message.to_bytes().unwrap();
+/// assert_eq!(payload, b"d8:msg_typei1e5:piecei0e10:total_sizei11eed4:name1:xe");
+///
+/// let received = MetadataMessage::from_bytes(&payload).unwrap();
+/// assert_eq!(received.msg_type, MetadataMessageType::Data);
+/// assert_eq!(received.data, b"d4:name1:xe");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataMessage {
+    /// `msg_type`: what the message is for
+    pub msg_type: MetadataMessageType,
+    /// `piece`: index of the metadata piece
+    pub piece: u64,
+    /// `total_size`: size of the whole info dictionary, sent with data messages
+    pub total_size: Option<u64>,
+    /// Piece contents following the dictionary (empty except for data messages)
+    pub data: Vec<u8>,
+    /// Other keys of the dictionary
+    pub extra: HashMap<String, Node>,
+}
+
+impl MetadataMessage {
+    /// Creates a request for a metadata piece
+    pub fn request(piece: u64) -> Self {
+        Self::new(MetadataMessageType::Request, piece, None, Vec::new())
+    }
+
+    /// Creates a message carrying a metadata piece
+    pub fn data(piece: u64, total_size: u64, data: Vec<u8>) -> Self {
+        Self::new(MetadataMessageType::Data, piece, Some(total_size), data)
+    }
+
+    /// Creates a refusal of a request for a metadata piece
+    pub fn reject(piece: u64) -> Self {
+        Self::new(MetadataMessageType::Reject, piece, None, Vec::new())
+    }
+
+    fn new(msg_type: MetadataMessageType, piece: u64, total_size: Option<u64>, data: Vec<u8>) -> Self {
+        Self {
+            msg_type,
+            piece,
+            total_size,
+            data,
+            extra: HashMap::new(),
+        }
+    }
+
+    /// Reads a message from an extended message payload.
+    ///
+    /// # Arguments
+    /// * `payload` - The payload after the extended message id byte
+    ///
+    /// # Returns
+    /// * `Ok(MetadataMessage)` - The message, with the piece contents of a data message
+    /// * `Err(String)` - If the dictionary is malformed, or the message fails
+    ///   [`validate`](Self::validate)
+    pub fn from_bytes(payload: &[u8]) -> Result<MetadataMessage, String> {
+        let (_, end) = dict_entries(payload, 0)?;
+        let Node::Dictionary(dict) = parse_bytes(&payload[..end])? else {
+            return Err(ERR_NOT_A_DICTIONARY.to_string());
+        };
+        let required = |key| {
+            optional_count(&dict, key, ERR_INVALID_EXTENSION_MESSAGE)?
+                .ok_or_else(|| ERR_INVALID_EXTENSION_MESSAGE.to_string())
+        };
+        let msg_type = MetadataMessageType::from_code(required("msg_type")?)
+            .ok_or_else(|| ERR_INVALID_EXTENSION_MESSAGE.to_string())?;
+        let message = MetadataMessage {
+            msg_type,
+            piece: required("piece")?,
+            total_size: optional_count(&dict, "total_size", ERR_INVALID_EXTENSION_MESSAGE)?,
+            data: payload[end..].to_vec(),
+            extra: others(&dict, &MESSAGE_KEYS),
+        };
+        message.validate()?;
+        Ok(message)
+    }
+
+    /// Checks that the message can be sent.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The message is valid
+    /// * `Err(String)` - If a data message has no `total_size` or more than
+    ///   [`METADATA_PIECE_SIZE`] bytes of data, another message has data, or a
+    ///   count does not fit a bencode integer
+    pub fn validate(&self) -> Result<(), String> {
+        let valid = match self.msg_type {
+            MetadataMessageType::Data => self.total_size.is_some() && self.data.len() <= METADATA_PIECE_SIZE,
+            _ => self.data.is_empty(),
+        };
+        if !valid {
+            return Err(ERR_INVALID_EXTENSION_MESSAGE.to_string());
+        }
+        check_fits(self.piece)?;
+        self.total_size.map_or(Ok(()), check_fits)
+    }
+
+    /// Builds the message dictionary, without the piece contents.
+    pub fn to_node(&self) -> Node {
+        let mut dict = self.extra.clone();
+        dict.insert("msg_type".to_string(), integer(self.msg_type.code()));
+        dict.insert("piece".to_string(), integer(self.piece));
+        if let Some(total_size) = self.total_size {
+            dict.insert("total_size".to_string(), integer(total_size));
+        }
+        Node::Dictionary(dict)
+    }
+
+    /// Validates the message and encodes it as an extended message payload.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u8>)` - The encoded dictionary followed by the piece contents
+    /// * `Err(String)` - If the message fails [`validate`](Self::validate)
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        self.validate()?;
+        let mut payload = stringify_to_bytes(&self.to_node())?;
+        payload.extend_from_slice(&self.data);
+        Ok(payload)
+    }
+}
+
+/// The extended handshake, sent once by each peer to list the extensions it supports.
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::{ExtendedHandshake, UT_METADATA};
+///
+/// let handshake = ExtendedHandshake::from_bytes(b"d1:md11:ut_metadatai3ee13:metadata_sizei31235e1:v4:teste").unwrap();
+/// assert_eq!(handshake.extension_id(UT_METADATA), Some(3));
+/// assert_eq!(handshake.metadata_size, Some(31235));
+/// assert_eq!(handshake.client.as_deref(), Some("test"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExtendedHandshake {
+    /// `m`: extension names mapped to the message id the sender uses for them
+    /// (0 means the extension is disabled)
+    pub extensions: HashMap<String, u8>,
+    /// `metadata_size`: size of the torrent's info dictionary in bytes (BEP 9)
+    pub metadata_size: Option<u64>,
+    /// `v`: client name and version
+    pub client: Option<String>,
+    /// `p`: TCP port the sender listens on
+    pub listen_port: Option<u16>,
+    /// `reqq`: number of outstanding requests the sender accepts
+    pub request_queue: Option<u64>,
+    /// Other keys of the dictionary, such as `yourip`
+    pub extra: HashMap<String, Node>,
+}
+
+impl ExtendedHandshake {
+    /// Reads a handshake from its parsed dictionary.
+    ///
+    /// # Returns
+    /// * `Ok(ExtendedHandshake)` - The handshake
+    /// * `Err(String)` - If a known field has the wrong type or is out of range
+    pub fn from_node(node: &Node) -> Result<ExtendedHandshake, String> {
+        let dict = node.as_dictionary().ok_or_else(|| ERR_NOT_A_DICTIONARY.to_string())?;
+        let invalid = || ERR_INVALID_EXTENSION_MESSAGE.to_string();
+        let mut extensions = HashMap::new();
+        if let Some(m) = dict.get("m") {
+            for (name, id) in m.as_dictionary().ok_or_else(invalid)? {
+                let id = id.as_integer().and_then(|&id| u8::try_from(id).ok()).ok_or_else(invalid)?;
+                extensions.insert(name.clone(), id);
+            }
+        }
+        let listen_port = optional_count(dict, "p", ERR_INVALID_EXTENSION_MESSAGE)?
+            .map(|port| u16::try_from(port).map_err(|_| invalid()))
+            .transpose()?;
+        Ok(ExtendedHandshake {
+            extensions,
+            metadata_size: optional_count(dict, "metadata_size", ERR_INVALID_EXTENSION_MESSAGE)?,
+            client: dict.get("v").map(|v| text(v).map_err(|_| invalid())).transpose()?,
+            listen_port,
+            request_queue: optional_count(dict, "reqq", ERR_INVALID_EXTENSION_MESSAGE)?,
+            extra: others(dict, &HANDSHAKE_KEYS),
+        })
+    }
+
+    /// Reads a handshake from an extended message payload.
+    ///
+    /// # Returns
+    /// * `Ok(ExtendedHandshake)` - The handshake
+    /// * `Err(String)` - If the input is malformed or a known field is invalid
+    pub fn from_bytes(payload: &[u8]) -> Result<ExtendedHandshake, String> {
+        Self::from_node(&parse_bytes(payload)?)
+    }
+
+    /// Returns the message id the sender uses for an extension, or None if it
+    /// does not support it
+    pub fn extension_id(&self, name: &str) -> Option<u8> {
+        self.extensions.get(name).copied().filter(|&id| id != 0)
+    }
+
+    /// Builds the handshake dictionary.
+    pub fn to_node(&self) -> Node {
+        let mut dict = self.extra.clone();
+        let extensions = self
+            .extensions
+            .iter()
+            .map(|(name, &id)| (name.clone(), integer(u64::from(id))))
+            .collect();
+        dict.insert("m".to_string(), Node::Dictionary(extensions));
+        if let Some(client) = &self.client {
+            dict.insert("v".to_string(), Node::from(client.as_str()));
+        }
+        let counts = [
+            ("metadata_size", self.metadata_size),
+            ("p", self.listen_port.map(u64::from)),
+            ("reqq", self.request_queue),
+        ];
+        for (key, value) in counts {
+            if let Some(value) = value {
+                dict.insert(key.to_string(), integer(value));
+            }
+        }
+        Node::Dictionary(dict)
+    }
+
+    /// Encodes the handshake as an extended message payload.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u8>)` - The encoded dictionary
+    /// * `Err(String)` - If a count does not fit a bencode integer
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        for value in [self.metadata_size, self.request_queue].into_iter().flatten() {
+            check_fits(value)?;
+        }
+        Ok(stringify_to_bytes(&self.to_node())?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    fn kind(payload: &[u8]) -> ErrorKind {
+        ErrorKind::from_message(&MetadataMessage::from_bytes(payload).unwrap_err())
+    }
+
+    #[test]
+    fn metadata_messages_round_trip() {
+        for message in [
+            MetadataMessage::request(2),
+            MetadataMessage::reject(7),
+            MetadataMessage::data(1, 20000, vec![0xff; 20000 - METADATA_PIECE_SIZE]),
+        ] {
+            let payload = message.to_bytes().unwrap();
+            assert_eq!(MetadataMessage::from_bytes(&payload).unwrap(), message);
+        }
+        assert_eq!(MetadataMessage::request(2).to_bytes().unwrap(), b"d8:msg_typei0e5:piecei2ee");
+        assert_eq!(metadata_piece_count(20000), 2);
+        assert_eq!(metadata_piece_count(METADATA_PIECE_SIZE as u64), 1);
+    }
+
+    #[test]
+    fn unknown_keys_are_kept() {
+        let payload = b"d8:msg_typei0e5:piecei0e3:x-yi1ee";
+        let message = MetadataMessage::from_bytes(payload).unwrap();
+        assert_eq!(message.extra.get("x-y"), Some(&Node::from(1)));
+        assert_eq!(message.to_bytes().unwrap(), payload);
+    }
+
+    #[test]
+    fn invalid_metadata_messages_are_rejected() {
+        assert_eq!(kind(b"d8:msg_typei3e5:piecei0ee"), ErrorKind::InvalidExtensionMessage);
+        assert_eq!(kind(b"d8:msg_typei0ee"), ErrorKind::InvalidExtensionMessage);
+        assert_eq!(kind(b"d8:msg_typei1e5:piecei0ee"), ErrorKind::InvalidExtensionMessage);
+        // Only data messages carry bytes after the dictionary
+        assert_eq!(kind(b"d8:msg_typei0e5:piecei0eexyz"), ErrorKind::InvalidExtensionMessage);
+        assert_eq!(kind(b"d8:msg_typei0e5:piecei-1ee"), ErrorKind::InvalidExtensionMessage);
+        assert_eq!(kind(b"li1ee"), ErrorKind::NotADictionary);
+        assert_eq!(kind(b"d8:msg_typei0e"), ErrorKind::UnterminatedDictionary);
+        let oversized = MetadataMessage::data(0, 1, vec![0; METADATA_PIECE_SIZE + 1]);
+        assert_eq!(oversized.to_bytes().unwrap_err(), ERR_INVALID_EXTENSION_MESSAGE);
+    }
+
+    #[test]
+    fn handshakes_round_trip() {
+        let handshake = ExtendedHandshake {
+            extensions: HashMap::from([("ut_metadata".to_string(), 2), ("ut_pex".to_string(), 0)]),
+            metadata_size: Some(1234),
+            client: Some("client 1.0".to_string()),
+            listen_port: Some(6881),
+            request_queue: Some(250),
+            extra: HashMap::from([("yourip".to_string(), Node::Bytes(vec![192, 168, 0, 1]))]),
+        };
+        let payload = handshake.to_bytes().unwrap();
+        assert_eq!(ExtendedHandshake::from_bytes(&payload).unwrap(), handshake);
+        assert_eq!(handshake.extension_id(UT_METADATA), Some(2));
+        assert_eq!(handshake.extension_id("ut_pex"), None);
+        assert_eq!(ExtendedHandshake::default().to_bytes().unwrap(), b"d1:mdee");
+    }
+
+    #[test]
+    fn invalid_handshakes_are_rejected() {
+        for payload in [&b"d1:mi1ee"[..], b"d1:md1:xi256eee", b"d1:pi65536ee", b"d1:vi1ee"] {
+            let error = ExtendedHandshake::from_bytes(payload).unwrap_err();
+            assert_eq!(error, ERR_INVALID_EXTENSION_MESSAGE, "{:?}", payload);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! Pluggable SHA-1 and SHA-256 backends for info hashes and piece hashing.
+//!
+//! Unless told otherwise the library hashes with [`DefaultHasher`], which uses
+//! the RustCrypto `sha1` and `sha2` crates when the features of the same names
+//! are enabled and the library's own portable implementations,
+//! [`BuiltinHasher`], otherwise. Hashing a large payload is dominated by SHA-1,
+//! so a program that already links another implementation, such as `ring`, can
+//! implement [`Hasher`] for it and hand it to
+//! [`info_hash_with`](super::info_hash_with),
+//! [`IncrementalVerifier::with_hasher`](super::IncrementalVerifier::with_hasher),
+//! [`MerkleHasher::with_hasher`](super::merkle::MerkleHasher::with_hasher)
+//! or `TorrentBuilder::with_hasher`.
+
+#[cfg(not(feature = "std"))]
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use std::sync::Arc;
+
+use core::fmt;
+
+use super::sha1::SHA1_LENGTH;
+use super::sha256::SHA256_LENGTH;
+
+/// Computes the digests BitTorrent uses; implemented by the caller with the
+/// crypto of their choice.
+///
+/// The types that hash take ownership of their backend, so it may hold state
+/// such as a context created at run time; a `&'static` reference to a backend
+/// is a backend too.
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::{BuiltinHasher, Hasher, info_hash, info_hash_with};
+///
+/// /// Counts the bytes it hashes, passing the work to the built in backend
+/// #[derive(Debug)]
+/// struct Counting(std::sync::atomic::AtomicUsize);
+///
+/// impl Hasher for Counting {
+///     fn sha1(&self, data: &[u8]) -> [u8; 20] {
+///         self.0.fetch_add(data.len(), std::sync::atomic::Ordering::Relaxed);
+///         BuiltinHasher.sha1(data)
+///     }
+///
+///     fn sha256(&self, data: &[u8]) -> [u8; 32] {
+///         BuiltinHasher.sha256(data)
+///     }
+/// }
+///
+/// let torrent = b"d4:infod6:lengthi5e4:name1:xee";
+/// let counting = Counting(Default::default());
+/// assert_eq!(info_hash_with(torrent, &counting), info_hash(torrent));
+/// assert_eq!(counting.0.into_inner(), 22);
+/// ```
+pub trait Hasher: fmt::Debug + Send + Sync {
+    /// Returns the SHA-1 digest of `data`.
+    fn sha1(&self, data: &[u8]) -> [u8; SHA1_LENGTH];
+
+    /// Returns the SHA-256 digest of `data`.
+    fn sha256(&self, data: &[u8]) -> [u8; SHA256_LENGTH];
+}
+
+impl<H: Hasher + ?Sized> Hasher for &H {
+    fn sha1(&self, data: &[u8]) -> [u8; SHA1_LENGTH] {
+        (**self).sha1(data)
+    }
+
+    fn sha256(&self, data: &[u8]) -> [u8; SHA256_LENGTH] {
+        (**self).sha256(data)
+    }
+}
+
+/// The library's own portable SHA-1 and SHA-256.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct BuiltinHasher;
+
+impl Hasher for BuiltinHasher {
+    fn sha1(&self, data: &[u8]) -> [u8; SHA1_LENGTH] {
+        super::sha1::sha1(data)
+    }
+
+    fn sha256(&self, data: &[u8]) -> [u8; SHA256_LENGTH] {
+        super::sha256::sha256(data)
+    }
+}
+
+/// The backend used unless another is given: SHA-1 from the `sha1` crate and
+/// SHA-256 from the `sha2` crate when those features are enabled, each falling
+/// back to [`BuiltinHasher`] otherwise.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DefaultHasher;
+
+impl Hasher for DefaultHasher {
+    #[cfg(feature = "sha1")]
+    fn sha1(&self, data: &[u8]) -> [u8; SHA1_LENGTH] {
+        use sha1::Digest;
+        sha1::Sha1::digest(data).into()
+    }
+
+    #[cfg(not(feature = "sha1"))]
+    fn sha1(&self, data: &[u8]) -> [u8; SHA1_LENGTH] {
+        BuiltinHasher.sha1(data)
+    }
+
+    #[cfg(feature = "sha2")]
+    fn sha256(&self, data: &[u8]) -> [u8; SHA256_LENGTH] {
+        use sha2::Digest;
+        sha2::Sha256::digest(data).into()
+    }
+
+    #[cfg(not(feature = "sha2"))]
+    fn sha256(&self, data: &[u8]) -> [u8; SHA256_LENGTH] {
+        BuiltinHasher.sha256(data)
+    }
+}
+
+/// The backend held by a type that hashes: [`DefaultHasher`] until one is
+/// given, shared between clones of its holder.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SharedHasher(Option<Arc<dyn Hasher>>);
+
+impl SharedHasher {
+    /// Holds the default backend
+    pub(crate) const fn new() -> Self {
+        Self(None)
+    }
+
+    /// Holds `hasher`
+    pub(crate) fn from(hasher: impl Hasher + 'static) -> Self {
+        Self(Some(Arc::new(hasher)))
+    }
+
+    /// Returns the held backend
+    pub(crate) fn get(&self) -> &dyn Hasher {
+        match &self.0 {
+            Some(hasher) => hasher.as_ref(),
+            None => &DefaultHasher,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_hasher_matches_the_hash_functions() {
+        let hasher: &dyn Hasher = &BuiltinHasher;
+        assert_eq!(hasher.sha1(b"abc"), crate::torrent::sha1::sha1(b"abc"));
+        assert_eq!(hasher.sha256(b"abc"), crate::torrent::sha256::sha256(b"abc"));
+    }
+
+    #[test]
+    fn default_hasher_agrees_with_the_builtin_one() {
+        for data in [&b""[..], b"abc", &[7; 1000]] {
+            assert_eq!(DefaultHasher.sha1(data), BuiltinHasher.sha1(data));
+            assert_eq!(DefaultHasher.sha256(data), BuiltinHasher.sha256(data));
+        }
+    }
+
+    #[test]
+    fn shared_hasher_holds_the_given_backend() {
+        #[derive(Debug)]
+        struct Zeros;
+
+        impl Hasher for Zeros {
+            fn sha1(&self, _: &[u8]) -> [u8; SHA1_LENGTH] {
+                [0; SHA1_LENGTH]
+            }
+
+            fn sha256(&self, _: &[u8]) -> [u8; SHA256_LENGTH] {
+                [0; SHA256_LENGTH]
+            }
+        }
+
+        assert_eq!(SharedHasher::new().get().sha1(b"abc"), DefaultHasher.sha1(b"abc"));
+        let shared = SharedHasher::from(Zeros);
+        assert_eq!(shared.clone().get().sha256(b"abc"), [0; SHA256_LENGTH]);
+    }
+}
@@ -0,0 +1,297 @@
+//! Merkle hash trees of BitTorrent v2 (BEP 52).
+//!
+//! A v2 torrent hashes each file on its own. The file is cut into 16 KiB
+//! blocks, each block is hashed with SHA-256, and the block hashes are combined
+//! pairwise, level by level, up to the file's `pieces root`. Each level is
+//! padded to a power of two with the hash of an all-zero subtree of its height.
+//!
+//! The level whose hashes each cover one piece is the file's piece layer. It is
+//! stored in the torrent's `piece layers` (for files larger than a piece) so a
+//! downloader can check whole pieces against the root.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec::Vec};
+
+use crate::error::messages::ERR_INVALID_PIECE_LENGTH;
+
+use super::hasher::{DefaultHasher, Hasher, SharedHasher};
+use super::sha256::SHA256_LENGTH;
+
+/// Size of the blocks whose SHA-256 hashes are the leaves of a file's tree
+pub const BLOCK_SIZE: u64 = 16 * 1024;
+
+/// A node of a merkle tree
+type Hash = [u8; SHA256_LENGTH];
+
+/// Returns true if a v2 torrent can use `piece_length`: a power of two of at
+/// least [`BLOCK_SIZE`], so each piece is a whole subtree.
+pub fn is_v2_piece_length(piece_length: u64) -> bool {
+    piece_length >= BLOCK_SIZE && piece_length.is_power_of_two()
+}
+
+/// Returns the hash of two sibling nodes
+fn join(hasher: &dyn Hasher, left: &Hash, right: &Hash) -> Hash {
+    let mut pair = [0; 2 * SHA256_LENGTH];
+    pair[..SHA256_LENGTH].copy_from_slice(left);
+    pair[SHA256_LENGTH..].copy_from_slice(right);
+    hasher.sha256(&pair)
+}
+
+/// Returns the root of a subtree of `2^levels` zero leaves
+fn pad_hash(hasher: &dyn Hasher, levels: u32) -> Hash {
+    (0..levels).fold([0; SHA256_LENGTH], |pad, _| join(hasher, &pad, &pad))
+}
+
+/// Reduces a level of `width` nodes (a power of two), of which `nodes` are the
+/// first and the rest are `pad`, to its root
+fn root_of(hasher: &dyn Hasher, mut nodes: Vec<Hash>, mut width: usize, mut pad: Hash) -> Hash {
+    while width > 1 {
+        if nodes.len() % 2 == 1 {
+            nodes.push(pad);
+        }
+        nodes = nodes.chunks_exact(2).map(|pair| join(hasher, &pair[0], &pair[1])).collect();
+        pad = join(hasher, &pad, &pad);
+        width /= 2;
+    }
+    nodes.first().copied().unwrap_or(pad)
+}
+
+/// Splits concatenated hashes into nodes
+fn split(hashes: &[u8]) -> Vec<Hash> {
+    hashes
+        .chunks_exact(SHA256_LENGTH)
+        .map(|chunk| {
+            let mut hash = [0; SHA256_LENGTH];
+            hash.copy_from_slice(chunk);
+            hash
+        })
+        .collect()
+}
+
+/// Computes the pieces root a piece layer hashes to.
+///
+/// # Arguments
+/// * `layer` - The concatenated 32-byte hashes of a file's pieces
+/// * `piece_length` - The torrent's piece length
+///
+/// # Returns
+/// The root, or None if the layer is empty or not a whole number of hashes, or
+/// the piece length is not usable by a v2 torrent
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::merkle::{MerkleHasher, piece_layer_root};
+///
+/// let mut hasher = MerkleHasher::new(16384).unwrap();
+/// hasher.update(&[7; 40000]);
+/// let hashes = hasher.finish();
+/// assert_eq!(hashes.piece_layer.len(), 3 * 32);
+/// assert_eq!(piece_layer_root(&hashes.piece_layer, 16384), hashes.pieces_root);
+/// ```
+pub fn piece_layer_root(layer: &[u8], piece_length: u64) -> Option<[u8; SHA256_LENGTH]> {
+    if layer.is_empty() || !layer.len().is_multiple_of(SHA256_LENGTH) || !is_v2_piece_length(piece_length) {
+        return None;
+    }
+    let pieces = split(layer);
+    let width = pieces.len().next_power_of_two();
+    let pad = pad_hash(&DefaultHasher, (piece_length / BLOCK_SIZE).ilog2());
+    Some(root_of(&DefaultHasher, pieces, width, pad))
+}
+
+/// The hashes describing one file of a v2 torrent.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileHashes {
+    /// Root of the file's tree (None for an empty file)
+    pub pieces_root: Option<[u8; SHA256_LENGTH]>,
+    /// The file's piece layer, empty unless the file is larger than a piece
+    pub piece_layer: Vec<u8>,
+}
+
+/// Computes a file's pieces root and piece layer as its contents are fed in.
+///
+/// Only the current block and one hash per finished piece are kept, so memory
+/// use grows with the number of pieces rather than the size of the file.
+#[derive(Debug)]
+pub struct MerkleHasher {
+    piece_length: u64,
+    hasher: SharedHasher,
+    /// Bytes of the current block read so far
+    block: Vec<u8>,
+    /// Leaf hashes of the current piece
+    leaves: Vec<Hash>,
+    /// Hashes of the finished pieces
+    layer: Vec<u8>,
+    length: u64,
+}
+
+impl MerkleHasher {
+    /// Creates a hasher for a file of a torrent with the given piece length.
+    ///
+    /// # Returns
+    /// * `Ok(MerkleHasher)` - The hasher
+    /// * `Err(String)` - If the piece length is not a power of two of at least
+    ///   [`BLOCK_SIZE`]
+    pub fn new(piece_length: u64) -> Result<Self, String> {
+        if !is_v2_piece_length(piece_length) {
+            return Err(ERR_INVALID_PIECE_LENGTH.into());
+        }
+        Ok(Self {
+            piece_length,
+            hasher: SharedHasher::new(),
+            block: Vec::new(),
+            leaves: Vec::new(),
+            layer: Vec::new(),
+            length: 0,
+        })
+    }
+
+    /// Hashes with `hasher` rather than the default backend.
+    pub fn with_hasher(mut self, hasher: impl Hasher + 'static) -> Self {
+        self.hasher = SharedHasher::from(hasher);
+        self
+    }
+
+    /// Hashes with a backend already held by another type
+    #[cfg(feature = "std")]
+    pub(crate) fn with_shared_hasher(mut self, hasher: SharedHasher) -> Self {
+        self.hasher = hasher;
+        self
+    }
+
+    /// Returns the number of bytes fed in so far
+    pub fn length(&self) -> u64 {
+        self.length
+    }
+
+    /// Number of leaves under one piece
+    fn leaves_per_piece(&self) -> usize {
+        (self.piece_length / BLOCK_SIZE) as usize
+    }
+
+    /// Feeds the next bytes of the file.
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.length += data.len() as u64;
+        while !data.is_empty() {
+            let take = (BLOCK_SIZE as usize - self.block.len()).min(data.len());
+            self.block.extend_from_slice(&data[..take]);
+            data = &data[take..];
+            if self.block.len() as u64 == BLOCK_SIZE {
+                self.finish_block();
+            }
+        }
+    }
+
+    /// Hashes the current block, finishing the piece once it has all its leaves
+    fn finish_block(&mut self) {
+        self.leaves.push(self.hasher.get().sha256(&self.block));
+        self.block.clear();
+        if self.leaves.len() == self.leaves_per_piece() {
+            self.finish_piece();
+        }
+    }
+
+    /// Hashes the current piece's leaves, padded to a full piece
+    fn finish_piece(&mut self) {
+        let leaves = core::mem::take(&mut self.leaves);
+        let root = root_of(self.hasher.get(), leaves, self.leaves_per_piece(), [0; SHA256_LENGTH]);
+        self.layer.extend_from_slice(&root);
+    }
+
+    /// Hashes the final short block and piece, if any, and returns the file's hashes
+    pub fn finish(mut self) -> FileHashes {
+        if !self.block.is_empty() {
+            self.finish_block();
+        }
+        if self.length == 0 {
+            return FileHashes::default();
+        }
+        if self.length <= self.piece_length {
+            // A file of at most one piece has a tree only as wide as its blocks
+            let root = match split(&self.layer).first() {
+                Some(piece) => *piece,
+                None => {
+                    let width = self.leaves.len().next_power_of_two();
+                    root_of(self.hasher.get(), self.leaves, width, [0; SHA256_LENGTH])
+                }
+            };
+            return FileHashes {
+                pieces_root: Some(root),
+                piece_layer: Vec::new(),
+            };
+        }
+        if !self.leaves.is_empty() {
+            self.finish_piece();
+        }
+        let pieces = split(&self.layer);
+        let width = pieces.len().next_power_of_two();
+        let pad = pad_hash(self.hasher.get(), self.leaves_per_piece().ilog2());
+        FileHashes {
+            pieces_root: Some(root_of(self.hasher.get(), pieces, width, pad)),
+            piece_layer: self.layer,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::torrent::sha256::sha256;
+
+    /// Hashes a file in one go
+    fn hash(data: &[u8], piece_length: u64) -> FileHashes {
+        let mut hasher = MerkleHasher::new(piece_length).unwrap();
+        hasher.update(data);
+        hasher.finish()
+    }
+
+    fn pair(left: &Hash, right: &Hash) -> Hash {
+        join(&DefaultHasher, left, right)
+    }
+
+    #[test]
+    fn small_files_have_a_root_and_no_layer() {
+        assert_eq!(hash(b"", 16384), FileHashes::default());
+        assert_eq!(hash(b"abc", 16384).pieces_root, Some(sha256(b"abc")));
+        let block = [1u8; 16384];
+        let two_blocks = [&block[..], b"x"].concat();
+        let hashes = hash(&two_blocks, 65536);
+        assert_eq!(hashes.pieces_root, Some(pair(&sha256(&block), &sha256(b"x"))));
+        assert!(hashes.piece_layer.is_empty());
+        // Exactly one piece is still small enough to need no layer
+        assert_eq!(hash(&block, 16384).pieces_root, Some(sha256(&block)));
+        assert!(hash(&block, 16384).piece_layer.is_empty());
+    }
+
+    #[test]
+    fn roots_do_not_depend_on_the_piece_length() {
+        let data: Vec<u8> = (0..5 * 16384 - 100).map(|i| (i % 251) as u8).collect();
+        let leaves: Vec<Hash> = data.chunks(16384).map(sha256).collect();
+        let zero = [0; SHA256_LENGTH];
+        let expected = pair(
+            &pair(&pair(&leaves[0], &leaves[1]), &pair(&leaves[2], &leaves[3])),
+            &pair(&pair(&leaves[4], &zero), &pair(&zero, &zero)),
+        );
+        for piece_length in [16384, 32768, 65536, 131072] {
+            let hashes = hash(&data, piece_length);
+            assert_eq!(hashes.pieces_root, Some(expected), "piece length {}", piece_length);
+            if piece_length < 131072 {
+                assert_eq!(hashes.piece_layer.len() as u64, 32 * (data.len() as u64).div_ceil(piece_length));
+                assert_eq!(piece_layer_root(&hashes.piece_layer, piece_length), Some(expected));
+            }
+        }
+        // Feeding the data in odd sized writes gives the same hashes
+        let mut hasher = MerkleHasher::new(32768).unwrap();
+        data.chunks(1000).for_each(|chunk| hasher.update(chunk));
+        assert_eq!(hasher.length(), data.len() as u64);
+        assert_eq!(hasher.finish(), hash(&data, 32768));
+    }
+
+    #[test]
+    fn unusable_piece_lengths_are_rejected() {
+        assert!(MerkleHasher::new(8192).is_err());
+        assert!(MerkleHasher::new(3 * 16384).is_err());
+        assert_eq!(piece_layer_root(&[0; 32], 1000), None);
+        assert_eq!(piece_layer_root(&[0; 31], 16384), None);
+        assert_eq!(piece_layer_root(&[], 16384), None);
+    }
+}
@@ -0,0 +1,1159 @@
+//! Typed contents of a `.torrent` file.
+//!
+//! [`TorrentFile`] reads the metainfo dictionary into plain fields (tracker
+//! tiers, the info dictionary and its file list), checks that they describe a
+//! usable torrent, and writes them back. Keys the library does not know are kept
+//! in `extra` maps, so a torrent read and written again keeps all its data.
+//!
+//! Both BitTorrent v1 torrents, whose pieces are hashed across the concatenated
+//! files, and v2 (BEP 52) torrents, which describe their files in a `file tree`
+//! of per-file merkle roots with the hashes of each piece in `piece layers`, are
+//! read and written, as are hybrid torrents carrying both; see [`MetaVersion`].
+//!
+//! Text fields are decoded lossily when they are not UTF-8. Use
+//! [`info_hash`](super::info_hash) on the original bytes for the torrent's
+//! identity rather than re-encoding a `TorrentFile`.
+
+#[cfg(not(feature = "std"))]
+use alloc::collections::{BTreeMap, BTreeSet};
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+#[cfg(feature = "std")]
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::HashMap;
+use crate::error::messages::*;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::nodes::integer::Integer;
+use crate::nodes::node::Node;
+use crate::parser::borrowed::parse_borrowed;
+use crate::parser::default::parse_bytes;
+use crate::stringify::default::stringify_to_bytes;
+
+use super::is_unsafe_segment;
+use super::merkle::{is_v2_piece_length, piece_layer_root};
+use super::sha1::SHA1_LENGTH;
+use super::sha256::SHA256_LENGTH;
+use super::url::{TrackerUrl, group_by_host};
+
+/// Root keys held in typed fields of [`TorrentFile`]
+const ROOT_KEYS: [&str; 8] = [
+    "announce",
+    "announce-list",
+    "comment",
+    "created by",
+    "creation date",
+    "encoding",
+    "info",
+    "piece layers",
+];
+/// Info keys held in typed fields of [`Info`]
+const INFO_KEYS: [&str; 9] = [
+    "file tree",
+    "files",
+    "length",
+    "meta version",
+    "name",
+    "piece length",
+    "pieces",
+    "private",
+    "source",
+];
+/// File entry keys held in typed fields of [`InfoFile`]
+const FILE_KEYS: [&str; 2] = ["length", "path"];
+/// File tree entry keys held in typed fields of [`TreeFile`]
+const TREE_FILE_KEYS: [&str; 2] = ["length", "pieces root"];
+
+/// The only `meta version` defined, used by v2 and hybrid torrents
+pub(super) const META_VERSION_2: u64 = 2;
+
+/// Piece layers of a v2 torrent: each file's concatenated SHA-256 piece hashes,
+/// keyed by the file's pieces root
+pub type PieceLayers = BTreeMap<[u8; SHA256_LENGTH], Vec<u8>>;
+
+/// Which versions of the protocol a torrent can be downloaded with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MetaVersion {
+    /// Pieces are SHA-1 hashed across the concatenated files (BEP 3)
+    #[default]
+    V1,
+    /// Each file is hashed on its own into a SHA-256 merkle tree (BEP 52)
+    V2,
+    /// Both, so v1 and v2 clients can share one swarm
+    Hybrid,
+}
+
+/// One file of a multi-file torrent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InfoFile {
+    /// Path segments below the torrent's directory
+    pub path: Vec<String>,
+    /// Length of the file in bytes
+    pub length: u64,
+    /// Other keys of the entry, such as `md5sum` or `attr`
+    pub extra: HashMap<String, Node>,
+}
+
+impl InfoFile {
+    /// Returns true for a padding file (BEP 47), which only aligns the next
+    /// file to a piece boundary and is not written to disk
+    pub fn is_padding(&self) -> bool {
+        self.extra
+            .get("attr")
+            .and_then(Node::as_bytes)
+            .is_some_and(|attr| attr.contains(&b'p'))
+    }
+}
+
+/// One file of a v2 torrent's file tree.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TreeFile {
+    /// Path segments below the torrent's directory; for a single-file torrent
+    /// the tree holds one file whose path is just the torrent's name
+    pub path: Vec<String>,
+    /// Length of the file in bytes
+    pub length: u64,
+    /// Root of the file's merkle tree (None for an empty file)
+    pub pieces_root: Option<[u8; SHA256_LENGTH]>,
+    /// Other keys of the file's entry
+    pub extra: HashMap<String, Node>,
+}
+
+/// The `info` dictionary of a torrent.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Info {
+    /// File name of a single-file torrent, or directory name of a multi-file one
+    pub name: String,
+    /// Nominal length of each piece in bytes
+    pub piece_length: u64,
+    /// Concatenated 20-byte SHA-1 hashes of the pieces (empty for a v2 torrent)
+    pub pieces: Vec<u8>,
+    /// Whether peers may only come from the torrent's trackers
+    pub private: bool,
+    /// `source` tag set by some private trackers
+    pub source: Option<String>,
+    /// Length of a single-file torrent (None for multi-file torrents)
+    pub length: Option<u64>,
+    /// Files of a multi-file torrent (empty for single-file torrents)
+    pub files: Vec<InfoFile>,
+    /// `meta version`, 2 for v2 and hybrid torrents (None for v1 torrents)
+    pub meta_version: Option<u64>,
+    /// Files of a v2 or hybrid torrent's `file tree`, in path order
+    pub file_tree: Vec<TreeFile>,
+    /// Other keys of the info dictionary
+    pub extra: HashMap<String, Node>,
+}
+
+/// Contents of a `.torrent` file.
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::TorrentFile;
+///
+/// let data = b"d8:announce14:http://tracker4:infod6:lengthi5e4:name5:hello\
+/// 12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+/// let torrent = TorrentFile::from_bytes(data).unwrap();
+/// assert_eq!(torrent.info.name, "hello");
+/// assert_eq!(torrent.total_length(), 5);
+/// assert_eq!(torrent.trackers(), vec!["http://tracker"]);
+/// assert_eq!(torrent.trackers()[0].host(), "tracker");
+/// assert_eq!(torrent.to_bytes().unwrap(), data);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TorrentFile {
+    /// URL of the torrent's tracker
+    pub announce: Option<TrackerUrl>,
+    /// Tracker tiers (BEP 12); trackers within a tier are interchangeable
+    pub announce_list: Vec<Vec<TrackerUrl>>,
+    /// Free text comment
+    pub comment: Option<String>,
+    /// Name and version of the program that created the torrent
+    pub created_by: Option<String>,
+    /// Creation time in seconds since the Unix epoch
+    pub creation_date: Option<u64>,
+    /// Character encoding of the text fields, as declared by the creator
+    pub encoding: Option<String>,
+    /// The info dictionary
+    pub info: Info,
+    /// Piece layers of a v2 or hybrid torrent, for each file larger than a piece
+    pub piece_layers: PieceLayers,
+    /// Other keys of the root dictionary
+    pub extra: HashMap<String, Node>,
+}
+
+/// Reads a text value, decoding binary strings lossily
+pub(super) fn text(node: &Node) -> Result<String, String> {
+    match node {
+        Node::Str(text) => Ok(text.clone()),
+        Node::Bytes(bytes) => Ok(String::from_utf8_lossy(bytes).into_owned()),
+        _ => Err(ERR_INVALID_TORRENT_FIELD.to_string()),
+    }
+}
+
+/// Reads an optional text field of a dictionary
+fn optional_text(dict: &HashMap<String, Node>, key: &str) -> Result<Option<String>, String> {
+    dict.get(key).map(text).transpose()
+}
+
+/// Reads an optional non-negative integer field of a dictionary
+pub(super) fn optional_count(dict: &HashMap<String, Node>, key: &str, error: &str) -> Result<Option<u64>, String> {
+    match dict.get(key) {
+        Some(value) => value.as_u64().map(Some).map_err(|_| error.to_string()),
+        None => Ok(None),
+    }
+}
+
+/// Copies the entries whose keys are not in `known`
+pub(super) fn others(dict: &HashMap<String, Node>, known: &[&str]) -> HashMap<String, Node> {
+    dict.iter()
+        .filter(|(key, _)| !known.contains(&key.as_str()))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect()
+}
+
+/// Converts a count to a node integer (validation rejects counts that do not fit)
+pub(super) fn integer(value: u64) -> Node {
+    Node::Integer(Integer::try_from(value).unwrap_or(Integer::MAX))
+}
+
+/// Returns an error if a count does not fit a node integer
+pub(super) fn check_fits(value: u64) -> Result<(), String> {
+    match Integer::try_from(value) {
+        Ok(_) => Ok(()),
+        Err(_) => Err(ERR_INTEGER_OUT_OF_RANGE.to_string()),
+    }
+}
+
+/// Reads a tracker URL, decoding binary strings lossily
+fn tracker_url(node: &Node) -> Result<TrackerUrl, String> {
+    TrackerUrl::parse(&text(node)?)
+}
+
+/// Reads the announce-list tiers, dropping empty tiers
+fn announce_tiers(node: Option<&Node>) -> Result<Vec<Vec<TrackerUrl>>, String> {
+    let Some(node) = node else {
+        return Ok(Vec::new());
+    };
+    let tiers = node.as_list().ok_or_else(|| ERR_INVALID_TORRENT_FIELD.to_string())?;
+    let mut result = Vec::with_capacity(tiers.len());
+    for tier in tiers {
+        let urls = tier.as_list().ok_or_else(|| ERR_INVALID_TORRENT_FIELD.to_string())?;
+        if !urls.is_empty() {
+            result.push(urls.iter().map(tracker_url).collect::<Result<Vec<_>, _>>()?);
+        }
+    }
+    Ok(result)
+}
+
+/// Reads a 32-byte pieces root
+fn pieces_root(node: &Node) -> Result<[u8; SHA256_LENGTH], String> {
+    node.as_bytes()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ERR_INVALID_FILE_ENTRY.to_string())
+}
+
+/// Appends the files below a directory of the file tree to `files`
+fn read_tree(directory: &HashMap<String, Node>, prefix: &mut Vec<String>, files: &mut Vec<TreeFile>) -> Result<(), String> {
+    for (name, child) in directory {
+        let child = child.as_dictionary().ok_or_else(|| ERR_INVALID_FILE_ENTRY.to_string())?;
+        prefix.push(name.clone());
+        match child.get("") {
+            // A file is a dictionary whose only key is empty
+            Some(entry) if child.len() == 1 => files.push(TreeFile::from_node(prefix.clone(), entry)?),
+            Some(_) => return Err(ERR_INVALID_FILE_ENTRY.to_string()),
+            None => read_tree(child, prefix, files)?,
+        }
+        prefix.pop();
+    }
+    Ok(())
+}
+
+/// Builds the nested `file tree` dictionary
+fn tree_node(files: &[TreeFile]) -> Node {
+    let mut tree = Node::Dictionary(HashMap::new());
+    for file in files {
+        let mut segments = file.path.iter().map(String::as_str).chain([""]);
+        let entry = segments.try_fold(&mut tree, |node, segment| {
+            if node.is_none() {
+                *node = Node::Dictionary(HashMap::new());
+            }
+            match node {
+                Node::Dictionary(dict) => Some(dict.entry(segment.to_string()).or_insert(Node::None)),
+                _ => None,
+            }
+        });
+        if let Some(entry) = entry {
+            *entry = file.to_node();
+        }
+    }
+    tree
+}
+
+/// Reads the piece layers of a parsed torrent. Node dictionaries hold their
+/// keys as text, which cannot keep binary roots, so each layer is keyed by the
+/// root it hashes to.
+fn hashed_piece_layers(node: Option<&Node>, piece_length: u64) -> Result<PieceLayers, String> {
+    let Some(node) = node else {
+        return Ok(PieceLayers::new());
+    };
+    let layers = node.as_dictionary().ok_or_else(|| ERR_INVALID_PIECE_LAYERS.to_string())?;
+    layers
+        .values()
+        .map(|layer| {
+            let layer = layer.as_bytes().ok_or_else(|| ERR_INVALID_PIECE_LAYERS.to_string())?;
+            let root = piece_layer_root(layer, piece_length).ok_or_else(|| ERR_INVALID_PIECE_LAYERS.to_string())?;
+            Ok((root, layer.to_vec()))
+        })
+        .collect()
+}
+
+/// Reads the piece layers of an encoded torrent with their keys as written
+fn encoded_piece_layers(data: &[u8]) -> Result<Option<PieceLayers>, String> {
+    let Some(encoded) = BorrowedNode::slice(data, "piece layers") else {
+        return Ok(None);
+    };
+    let node = parse_borrowed(encoded).map_err(|_| ERR_INVALID_PIECE_LAYERS.to_string())?;
+    let layers = node.as_dictionary().ok_or_else(|| ERR_INVALID_PIECE_LAYERS.to_string())?;
+    layers
+        .iter()
+        .map(|(root, layer)| {
+            let root = (*root).try_into().map_err(|_| ERR_INVALID_PIECE_LAYERS.to_string())?;
+            let layer = layer.as_bytes().ok_or_else(|| ERR_INVALID_PIECE_LAYERS.to_string())?;
+            Ok((root, layer.to_vec()))
+        })
+        .collect::<Result<_, String>>()
+        .map(Some)
+}
+
+/// Encodes the piece layers, whose binary keys a Node dictionary cannot hold
+fn piece_layers_node(layers: &PieceLayers) -> Node {
+    let mut encoded = b"d".to_vec();
+    for (root, layer) in layers {
+        encoded.extend_from_slice(b"32:");
+        encoded.extend_from_slice(root);
+        encoded.extend_from_slice(layer.len().to_string().as_bytes());
+        encoded.push(b':');
+        encoded.extend_from_slice(layer);
+    }
+    encoded.push(b'e');
+    Node::Raw(encoded)
+}
+
+impl TreeFile {
+    /// Reads the entry a file's empty key holds
+    fn from_node(path: Vec<String>, node: &Node) -> Result<TreeFile, String> {
+        let entry = node.as_dictionary().ok_or_else(|| ERR_INVALID_FILE_ENTRY.to_string())?;
+        let length =
+            optional_count(entry, "length", ERR_INVALID_FILE_ENTRY)?.ok_or_else(|| ERR_INVALID_FILE_ENTRY.to_string())?;
+        Ok(TreeFile {
+            path,
+            length,
+            pieces_root: entry.get("pieces root").map(pieces_root).transpose()?,
+            extra: others(entry, &TREE_FILE_KEYS),
+        })
+    }
+
+    fn to_node(&self) -> Node {
+        let mut entry = self.extra.clone();
+        entry.insert("length".to_string(), integer(self.length));
+        if let Some(root) = &self.pieces_root {
+            entry.insert("pieces root".to_string(), Node::Bytes(root.to_vec()));
+        }
+        Node::Dictionary(entry)
+    }
+}
+
+impl InfoFile {
+    fn from_node(node: &Node) -> Result<InfoFile, String> {
+        let entry = node.as_dictionary().ok_or_else(|| ERR_INVALID_FILE_ENTRY.to_string())?;
+        let length =
+            optional_count(entry, "length", ERR_INVALID_FILE_ENTRY)?.ok_or_else(|| ERR_INVALID_FILE_ENTRY.to_string())?;
+        let path = match entry.get("path") {
+            Some(Node::List(segments)) => segments
+                .iter()
+                .map(|segment| text(segment).map_err(|_| ERR_INVALID_FILE_ENTRY.to_string()))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => return Err(ERR_INVALID_FILE_ENTRY.to_string()),
+        };
+        Ok(InfoFile {
+            path,
+            length,
+            extra: others(entry, &FILE_KEYS),
+        })
+    }
+
+    fn to_node(&self) -> Node {
+        let mut entry = self.extra.clone();
+        entry.insert("length".to_string(), integer(self.length));
+        entry.insert("path".to_string(), Node::List(self.path.iter().map(|s| Node::from(s.as_str())).collect()));
+        Node::Dictionary(entry)
+    }
+}
+
+impl Info {
+    fn from_dictionary(info: &HashMap<String, Node>) -> Result<Info, String> {
+        let name = match info.get("name") {
+            Some(node) => text(node).map_err(|_| ERR_INVALID_FILE_ENTRY.to_string())?,
+            None => return Err(ERR_INVALID_FILE_ENTRY.to_string()),
+        };
+        let piece_length = optional_count(info, "piece length", ERR_INVALID_PIECE_LENGTH)?
+            .ok_or_else(|| ERR_INVALID_PIECE_LENGTH.to_string())?;
+        let meta_version = optional_count(info, "meta version", ERR_UNSUPPORTED_META_VERSION)?;
+        let length = optional_count(info, "length", ERR_INVALID_FILE_ENTRY)?;
+        let pieces = match info.get("pieces") {
+            Some(pieces) => pieces.as_bytes().ok_or_else(|| ERR_INVALID_PIECES.to_string())?.to_vec(),
+            // Only a v2 torrent without a v1 file list does without
+            None if meta_version.is_some() && length.is_none() && !info.contains_key("files") => Vec::new(),
+            None => return Err(ERR_INVALID_PIECES.to_string()),
+        };
+        let private = match info.get("private") {
+            Some(flag) => flag.as_bool().map_err(|_| ERR_INVALID_TORRENT_FIELD.to_string())?,
+            None => false,
+        };
+        let files = match info.get("files") {
+            Some(Node::List(entries)) => entries.iter().map(InfoFile::from_node).collect::<Result<Vec<_>, _>>()?,
+            Some(_) => return Err(ERR_INVALID_FILE_ENTRY.to_string()),
+            None => Vec::new(),
+        };
+        let mut file_tree = Vec::new();
+        match info.get("file tree") {
+            Some(Node::Dictionary(tree)) => read_tree(tree, &mut Vec::new(), &mut file_tree)?,
+            Some(_) => return Err(ERR_INVALID_FILE_ENTRY.to_string()),
+            None => {}
+        }
+        file_tree.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(Info {
+            name,
+            piece_length,
+            pieces,
+            private,
+            source: optional_text(info, "source")?,
+            length,
+            files,
+            meta_version,
+            file_tree,
+            extra: others(info, &INFO_KEYS),
+        })
+    }
+
+    /// Returns true if the info has the v1 fields describing its files
+    fn has_v1_files(&self) -> bool {
+        self.length.is_some() || !self.files.is_empty()
+    }
+
+    /// Returns which protocol versions the torrent supports
+    pub fn version(&self) -> MetaVersion {
+        match (self.meta_version.is_some(), self.has_v1_files()) {
+            (false, _) => MetaVersion::V1,
+            (true, false) => MetaVersion::V2,
+            (true, true) => MetaVersion::Hybrid,
+        }
+    }
+
+    /// Returns the total payload length (the sum of the file lengths, which
+    /// for a hybrid torrent includes its padding files)
+    pub fn total_length(&self) -> u64 {
+        match self.length {
+            Some(length) => length,
+            None if self.files.is_empty() => self.file_tree.iter().map(|file| file.length).sum(),
+            None => self.files.iter().map(|file| file.length).sum(),
+        }
+    }
+
+    /// Returns the number of pieces the `pieces` field holds hashes for
+    pub fn piece_count(&self) -> usize {
+        self.pieces.len() / SHA1_LENGTH
+    }
+
+    /// Checks the fields describe a usable payload.
+    fn validate(&self) -> Result<(), String> {
+        if is_unsafe_segment(self.name.as_bytes()) {
+            return Err(ERR_UNSAFE_PATH.to_string());
+        }
+        if self.piece_length == 0 {
+            return Err(ERR_INVALID_PIECE_LENGTH.to_string());
+        }
+        check_fits(self.piece_length)?;
+        match self.meta_version {
+            None if self.file_tree.is_empty() => self.validate_v1(),
+            None => Err(ERR_INVALID_FILE_ENTRY.to_string()),
+            Some(META_VERSION_2) => {
+                self.validate_v2()?;
+                if self.has_v1_files() {
+                    self.validate_v1()?;
+                    self.validate_hybrid()
+                } else if self.pieces.is_empty() {
+                    Ok(())
+                } else {
+                    Err(ERR_INVALID_PIECES.to_string())
+                }
+            }
+            Some(_) => Err(ERR_UNSUPPORTED_META_VERSION.to_string()),
+        }
+    }
+
+    /// Checks the file tree of a v2 or hybrid torrent
+    fn validate_v2(&self) -> Result<(), String> {
+        if !is_v2_piece_length(self.piece_length) {
+            return Err(ERR_INVALID_PIECE_LENGTH.to_string());
+        }
+        if self.file_tree.is_empty() {
+            return Err(ERR_INVALID_FILE_ENTRY.to_string());
+        }
+        let mut total = 0u64;
+        for file in &self.file_tree {
+            if file.path.is_empty() || file.pieces_root.is_some() != (file.length > 0) {
+                return Err(ERR_INVALID_FILE_ENTRY.to_string());
+            }
+            if file.path.iter().any(|segment| is_unsafe_segment(segment.as_bytes())) {
+                return Err(ERR_UNSAFE_PATH.to_string());
+            }
+            check_fits(file.length)?;
+            total = total
+                .checked_add(file.length)
+                .ok_or_else(|| ERR_INVALID_FILE_ENTRY.to_string())?;
+        }
+        check_fits(total)?;
+        // In path order, with no file where another needs a directory
+        let ordered = self
+            .file_tree
+            .windows(2)
+            .all(|pair| pair[0].path < pair[1].path && !pair[1].path.starts_with(&pair[0].path));
+        if !ordered {
+            return Err(ERR_INVALID_FILE_ENTRY.to_string());
+        }
+        Ok(())
+    }
+
+    /// Checks the v1 files of a hybrid torrent, padding aside, are those of its file tree
+    fn validate_hybrid(&self) -> Result<(), String> {
+        let same = match self.length {
+            Some(length) => {
+                matches!(self.file_tree.as_slice(), [file] if file.path == [self.name.as_str()] && file.length == length)
+            }
+            None => self
+                .files
+                .iter()
+                .filter(|file| !file.is_padding())
+                .map(|file| (&file.path, file.length))
+                .eq(self.file_tree.iter().map(|file| (&file.path, file.length))),
+        };
+        if !same {
+            return Err(ERR_INVALID_FILE_ENTRY.to_string());
+        }
+        Ok(())
+    }
+
+    /// Checks the v1 file list or length and the piece hashes
+    fn validate_v1(&self) -> Result<(), String> {
+        // A torrent is either single-file (length) or multi-file (files), never both
+        let total = match (self.length, self.files.is_empty()) {
+            (Some(length), true) => length,
+            (None, false) => {
+                let mut total = 0u64;
+                for file in &self.files {
+                    if file.path.is_empty() {
+                        return Err(ERR_INVALID_FILE_ENTRY.to_string());
+                    }
+                    if file.path.iter().any(|segment| is_unsafe_segment(segment.as_bytes())) {
+                        return Err(ERR_UNSAFE_PATH.to_string());
+                    }
+                    check_fits(file.length)?;
+                    total = total
+                        .checked_add(file.length)
+                        .ok_or_else(|| ERR_INVALID_FILE_ENTRY.to_string())?;
+                }
+                total
+            }
+            _ => return Err(ERR_INVALID_FILE_ENTRY.to_string()),
+        };
+        check_fits(total)?;
+        if !self.pieces.len().is_multiple_of(SHA1_LENGTH)
+            || self.piece_count() as u64 != total.div_ceil(self.piece_length)
+        {
+            return Err(ERR_INVALID_PIECES.to_string());
+        }
+        Ok(())
+    }
+
+    fn to_node(&self) -> Node {
+        let mut info = self.extra.clone();
+        info.insert("name".to_string(), Node::from(self.name.as_str()));
+        info.insert("piece length".to_string(), integer(self.piece_length));
+        if self.version() != MetaVersion::V2 {
+            info.insert("pieces".to_string(), Node::Bytes(self.pieces.clone()));
+        }
+        if self.private {
+            info.insert("private".to_string(), Node::Integer(1));
+        }
+        if let Some(source) = &self.source {
+            info.insert("source".to_string(), Node::from(source.as_str()));
+        }
+        if let Some(length) = self.length {
+            info.insert("length".to_string(), integer(length));
+        }
+        if !self.files.is_empty() {
+            info.insert("files".to_string(), Node::List(self.files.iter().map(InfoFile::to_node).collect()));
+        }
+        if let Some(version) = self.meta_version {
+            info.insert("meta version".to_string(), integer(version));
+        }
+        if !self.file_tree.is_empty() {
+            info.insert("file tree".to_string(), tree_node(&self.file_tree));
+        }
+        Node::Dictionary(info)
+    }
+}
+
+impl TorrentFile {
+    /// Reads a torrent from a parsed metainfo dictionary and validates it.
+    ///
+    /// # Arguments
+    /// * `node` - The root dictionary of the torrent
+    ///
+    /// # Returns
+    /// * `Ok(TorrentFile)` - The torrent
+    /// * `Err(String)` - If a field is missing, has the wrong type, a tracker
+    ///   URL is malformed or the torrent fails [`validate`](Self::validate)
+    ///
+    /// A parsed node holds dictionary keys as text, so the binary keys of
+    /// `piece layers` are not kept; each layer is keyed by the root it hashes
+    /// to instead. [`from_bytes`](Self::from_bytes) reads the keys as written.
+    pub fn from_node(node: &Node) -> Result<TorrentFile, String> {
+        Self::read(node, None)
+    }
+
+    /// Reads a torrent, with its piece layers if already read from the encoded bytes
+    fn read(node: &Node, piece_layers: Option<PieceLayers>) -> Result<TorrentFile, String> {
+        let root = node.as_dictionary().ok_or_else(|| ERR_NOT_A_DICTIONARY.to_string())?;
+        let info = match root.get("info") {
+            Some(Node::Dictionary(info)) => Info::from_dictionary(info)?,
+            Some(_) => return Err(ERR_INFO_NOT_DICTIONARY.to_string()),
+            None => return Err(ERR_MISSING_INFO.to_string()),
+        };
+        let torrent = TorrentFile {
+            announce: root.get("announce").map(tracker_url).transpose()?,
+            announce_list: announce_tiers(root.get("announce-list"))?,
+            comment: optional_text(root, "comment")?,
+            created_by: optional_text(root, "created by")?,
+            creation_date: optional_count(root, "creation date", ERR_INVALID_TORRENT_FIELD)?,
+            encoding: optional_text(root, "encoding")?,
+            piece_layers: match piece_layers {
+                Some(layers) => layers,
+                None => hashed_piece_layers(root.get("piece layers"), info.piece_length)?,
+            },
+            info,
+            extra: others(root, &ROOT_KEYS),
+        };
+        torrent.validate()?;
+        Ok(torrent)
+    }
+
+    /// Reads a torrent from its bencoded bytes.
+    ///
+    /// # Returns
+    /// * `Ok(TorrentFile)` - The torrent
+    /// * `Err(String)` - If the input is malformed or not a valid torrent
+    pub fn from_bytes(data: &[u8]) -> Result<TorrentFile, String> {
+        let node = parse_bytes(data)?;
+        Self::read(&node, encoded_piece_layers(data)?)
+    }
+
+    /// Reads a torrent from a `.torrent` file.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file
+    ///
+    /// # Returns
+    /// * `Ok(TorrentFile)` - The torrent
+    /// * `Err(String)` - If the file cannot be read or is not a valid torrent
+    #[cfg(feature = "std")]
+    pub fn from_file<P: AsRef<std::path::Path>>(path: P) -> Result<TorrentFile, String> {
+        let data = std::fs::read(path).map_err(|e| e.to_string())?;
+        Self::from_bytes(&data)
+    }
+
+    /// Checks that the torrent can be downloaded and written back.
+    ///
+    /// # Returns
+    /// * `Ok(())` - The torrent is valid
+    /// * `Err(String)` - If the piece length is zero, the `pieces` field does
+    ///   not hold one hash per piece, the torrent has both or neither of
+    ///   `length` and `files`, a file path is empty or could escape the
+    ///   download directory, a tracker tier is empty, or a count does not fit
+    ///   a bencode integer. A v2 or hybrid torrent must also have a piece
+    ///   length that is a power of two of at least 16 KiB, a file tree in path
+    ///   order with a pieces root for each non-empty file, a piece layer
+    ///   hashing to the root of each file larger than a piece (and no other
+    ///   layers) and, if hybrid, the same files in its v1 fields
+    pub fn validate(&self) -> Result<(), String> {
+        if self.announce_list.iter().any(Vec::is_empty) {
+            return Err(ERR_INVALID_TORRENT_FIELD.to_string());
+        }
+        if let Some(date) = self.creation_date {
+            check_fits(date)?;
+        }
+        self.info.validate()?;
+        self.validate_piece_layers()
+    }
+
+    /// Checks there is exactly one piece layer, of the right length, for each
+    /// file larger than a piece, and that each hashes to its key
+    fn validate_piece_layers(&self) -> Result<(), String> {
+        let piece_length = self.info.piece_length;
+        let mut roots = BTreeSet::new();
+        for file in self.info.file_tree.iter().filter(|file| file.length > piece_length) {
+            let layer = file
+                .pieces_root
+                .and_then(|root| self.piece_layers.get(&root))
+                .ok_or_else(|| ERR_INVALID_PIECE_LAYERS.to_string())?;
+            if layer.len() as u64 != file.length.div_ceil(piece_length) * SHA256_LENGTH as u64 {
+                return Err(ERR_INVALID_PIECE_LAYERS.to_string());
+            }
+            roots.extend(file.pieces_root);
+        }
+        let hashed = self
+            .piece_layers
+            .iter()
+            .all(|(root, layer)| roots.contains(root) && piece_layer_root(layer, piece_length) == Some(*root));
+        if !hashed {
+            return Err(ERR_INVALID_PIECE_LAYERS.to_string());
+        }
+        Ok(())
+    }
+
+    /// Returns the total payload length in bytes
+    pub fn total_length(&self) -> u64 {
+        self.info.total_length()
+    }
+
+    /// Returns true if the torrent holds a directory of files
+    pub fn is_multi_file(&self) -> bool {
+        match self.info.file_tree.as_slice() {
+            _ if !self.info.files.is_empty() => true,
+            [] => false,
+            [file] => file.path != [self.info.name.as_str()],
+            _ => true,
+        }
+    }
+
+    /// Returns which protocol versions the torrent supports
+    pub fn version(&self) -> MetaVersion {
+        self.info.version()
+    }
+
+    /// Returns every tracker URL in tier order.
+    ///
+    /// As BEP 12 requires, `announce` is only used when there is no
+    /// `announce-list`.
+    pub fn trackers(&self) -> Vec<&TrackerUrl> {
+        if self.announce_list.is_empty() {
+            self.announce.iter().collect()
+        } else {
+            self.announce_list.iter().flatten().collect()
+        }
+    }
+
+    /// Returns the trackers of [`trackers`](Self::trackers) grouped by host,
+    /// in the order the hosts first appear.
+    pub fn trackers_by_host(&self) -> Vec<(&str, Vec<&TrackerUrl>)> {
+        group_by_host(self.trackers())
+    }
+
+    /// Builds the metainfo dictionary of the torrent.
+    pub fn to_node(&self) -> Node {
+        let mut root = self.extra.clone();
+        if let Some(announce) = &self.announce {
+            root.insert("announce".to_string(), Node::from(announce.as_str()));
+        }
+        let text_fields = [
+            ("comment", &self.comment),
+            ("created by", &self.created_by),
+            ("encoding", &self.encoding),
+        ];
+        for (key, value) in text_fields {
+            if let Some(value) = value {
+                root.insert(key.to_string(), Node::from(value.as_str()));
+            }
+        }
+        if !self.announce_list.is_empty() {
+            let tiers = self
+                .announce_list
+                .iter()
+                .map(|tier| Node::List(tier.iter().map(|url| Node::from(url.as_str())).collect()))
+                .collect();
+            root.insert("announce-list".to_string(), Node::List(tiers));
+        }
+        if let Some(date) = self.creation_date {
+            root.insert("creation date".to_string(), integer(date));
+        }
+        root.insert("info".to_string(), self.info.to_node());
+        if !self.piece_layers.is_empty() {
+            root.insert("piece layers".to_string(), piece_layers_node(&self.piece_layers));
+        }
+        Node::Dictionary(root)
+    }
+
+    /// Validates the torrent and encodes it as bencode.
+    ///
+    /// # Returns
+    /// * `Ok(Vec<u8>)` - The encoded torrent
+    /// * `Err(String)` - If the torrent fails [`validate`](Self::validate)
+    pub fn to_bytes(&self) -> Result<Vec<u8>, String> {
+        self.validate()?;
+        Ok(stringify_to_bytes(&self.to_node())?)
+    }
+
+    /// Validates the torrent and writes it to a `.torrent` file.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file, which is replaced if it exists
+    ///
+    /// # Returns
+    /// * `Ok(())` - The file was written
+    /// * `Err(String)` - If the torrent is invalid or the file cannot be written
+    #[cfg(feature = "std")]
+    pub fn write_file<P: AsRef<std::path::Path>>(&self, path: P) -> Result<(), String> {
+        self.write_file_with_options(path, &crate::config::WriteOptions::new())
+    }
+
+    /// Validates the torrent and writes it to a `.torrent` file, backing up
+    /// any file it replaces as `options` asks.
+    ///
+    /// # Arguments
+    /// * `path` - Path of the file
+    /// * `options` - The backup policy for an existing file
+    ///
+    /// # Returns
+    /// * `Ok(())` - The file was written
+    /// * `Err(String)` - If the torrent is invalid or the file cannot be
+    ///   backed up or written; an invalid torrent leaves the file untouched
+    #[cfg(feature = "std")]
+    pub fn write_file_with_options<P: AsRef<std::path::Path>>(
+        &self,
+        path: P,
+        options: &crate::config::WriteOptions,
+    ) -> Result<(), String> {
+        use std::io::Write;
+        let data = self.to_bytes()?;
+        crate::io::lock::create_locked(path.as_ref(), options)
+            .and_then(|mut file| file.write_all(&data))
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Copies a torrent for seeding on another tracker.
+///
+/// The copy's `info.source` is set to `new_source`, which deliberately gives it
+/// a different info hash so the two trackers see separate swarms, and its
+/// trackers are replaced: the first becomes `announce` and, when there are
+/// several, each gets its own tier in `announce-list` so they are tried in
+/// order. Every other field, including the pieces, is kept.
+///
+/// # Arguments
+/// * `torrent` - The torrent to copy
+/// * `new_source` - Source tag of the new tracker
+/// * `new_trackers` - Announce URLs of the new tracker, in order of preference
+///
+/// # Returns
+/// The retargeted copy
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::{cross_seed, info_hash_v1, TorrentFile, TrackerUrl};
+///
+/// let data = b"d8:announce9:udp://old4:infod6:lengthi5e4:name5:hello\
+/// 12:piece lengthi16384e6:pieces20:aaaaaaaaaaaaaaaaaaaa7:privatei1e6:source3:OLDee";
+/// let torrent = TorrentFile::from_bytes(data).unwrap();
+/// let copy = cross_seed(&torrent, "NEW", &[TrackerUrl::parse("https://new/announce").unwrap()]);
+/// assert_eq!(copy.info.source.as_deref(), Some("NEW"));
+/// assert_eq!(copy.trackers(), vec!["https://new/announce"]);
+/// assert_ne!(info_hash_v1(&copy.to_node()), info_hash_v1(&torrent.to_node()));
+/// ```
+pub fn cross_seed(torrent: &TorrentFile, new_source: &str, new_trackers: &[TrackerUrl]) -> TorrentFile {
+    let mut copy = torrent.clone();
+    copy.info.source = Some(new_source.to_string());
+    copy.announce = new_trackers.first().cloned();
+    copy.announce_list = if new_trackers.len() > 1 {
+        new_trackers.iter().map(|url| vec![url.clone()]).collect()
+    } else {
+        Vec::new()
+    };
+    copy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    const SINGLE: &[u8] = b"d8:announce9:udp://one13:announce-listll9:udp://one9:udp://twoel11:http://backee\
+7:comment2:hi13:creation datei1700000000e4:infod6:lengthi10e4:name3:one12:piece lengthi4e\
+6:pieces60:aaaaaaaaaaaaaaaaaaaabbbbbbbbbbbbbbbbbbbbcccccccccccccccccccc7:privatei1e6:source3:abce\
+9:x-websitel3:urlee";
+
+    fn multi_file() -> TorrentFile {
+        TorrentFile {
+            announce: Some(TrackerUrl::parse("http://tracker").unwrap()),
+            info: Info {
+                name: "demo".into(),
+                piece_length: 4,
+                pieces: vec![b'h'; 40],
+                files: vec![
+                    InfoFile { path: vec!["a.txt".into()], length: 3, ..InfoFile::default() },
+                    InfoFile { path: vec!["dir".into(), "b.txt".into()], length: 2, ..InfoFile::default() },
+                ],
+                ..Info::default()
+            },
+            ..TorrentFile::default()
+        }
+    }
+
+    /// Builds a v2 torrent of the given files with 16 KiB pieces
+    fn v2(files: &[(&[&str], Vec<u8>)]) -> TorrentFile {
+        let mut torrent = TorrentFile::default();
+        torrent.info.name = "demo".into();
+        torrent.info.piece_length = 16384;
+        torrent.info.meta_version = Some(2);
+        for (path, data) in files {
+            let mut hasher = crate::torrent::merkle::MerkleHasher::new(16384).unwrap();
+            hasher.update(data);
+            let hashes = hasher.finish();
+            if let Some(root) = hashes.pieces_root
+                && !hashes.piece_layer.is_empty()
+            {
+                torrent.piece_layers.insert(root, hashes.piece_layer);
+            }
+            torrent.info.file_tree.push(TreeFile {
+                path: path.iter().map(|segment| segment.to_string()).collect(),
+                length: data.len() as u64,
+                pieces_root: hashes.pieces_root,
+                ..TreeFile::default()
+            });
+        }
+        torrent
+    }
+
+    #[test]
+    fn v2_torrents_are_written_as_a_file_tree() {
+        let mut torrent = v2(&[(&["demo"], b"abc".to_vec())]);
+        let root = crate::torrent::sha256::sha256(b"abc");
+        let mut expected = b"d4:infod9:file treed4:demod0:d6:lengthi3e11:pieces root32:".to_vec();
+        expected.extend(root);
+        expected.extend(b"eee12:meta versioni2e4:name4:demo12:piece lengthi16384eee");
+        assert_eq!(torrent.to_bytes().unwrap(), expected);
+        assert_eq!(TorrentFile::from_bytes(&expected).unwrap(), torrent);
+        assert_eq!((torrent.version(), torrent.total_length(), torrent.is_multi_file()), (MetaVersion::V2, 3, false));
+
+        // A v1 file list with the same files makes it hybrid
+        torrent.info.length = Some(3);
+        torrent.info.pieces = crate::torrent::sha1::sha1(b"abc").to_vec();
+        assert_eq!(torrent.version(), MetaVersion::Hybrid);
+        assert_eq!(TorrentFile::from_bytes(&torrent.to_bytes().unwrap()).unwrap(), torrent);
+        torrent.info.length = Some(4);
+        assert!(torrent.validate().is_err());
+    }
+
+    #[test]
+    fn piece_layers_round_trip_through_bytes_and_nodes() {
+        let large: Vec<u8> = (0..50000).map(|i| (i % 253) as u8).collect();
+        let torrent = v2(&[
+            (&["a"], large.clone()),
+            (&["b", "c"], b"small".to_vec()),
+            (&["b", "d"], large),
+            (&["e"], Vec::new()),
+        ]);
+        // Two copies of one file share a layer
+        assert_eq!(torrent.piece_layers.len(), 1);
+        assert!(torrent.is_multi_file());
+        let encoded = torrent.to_bytes().unwrap();
+        assert_eq!(TorrentFile::from_bytes(&encoded).unwrap(), torrent);
+        let node = parse_bytes(&encoded).unwrap();
+        assert_eq!(TorrentFile::from_node(&node).unwrap(), torrent);
+        assert_eq!(stringify_to_bytes(&torrent.to_node()).unwrap(), encoded);
+    }
+
+    #[test]
+    fn validation_rejects_inconsistent_v2_torrents() {
+        let kind = |torrent: &TorrentFile| ErrorKind::from_message(&torrent.validate().unwrap_err());
+        let large = vec![1; 40000];
+        let valid = v2(&[(&["a"], large.clone()), (&["b"], b"x".to_vec())]);
+        assert!(valid.validate().is_ok());
+
+        let mut torrent = valid.clone();
+        torrent.piece_layers.clear();
+        assert_eq!(kind(&torrent), ErrorKind::InvalidPieceLayers);
+
+        let mut torrent = valid.clone();
+        torrent.piece_layers.values_mut().for_each(|layer| layer[0] ^= 1);
+        assert_eq!(kind(&torrent), ErrorKind::InvalidPieceLayers);
+
+        let mut torrent = valid.clone();
+        torrent.piece_layers.insert([0; 32], vec![0; 32]);
+        assert_eq!(kind(&torrent), ErrorKind::InvalidPieceLayers);
+
+        let mut torrent = valid.clone();
+        torrent.info.file_tree.swap(0, 1);
+        assert_eq!(kind(&torrent), ErrorKind::InvalidFileEntry);
+
+        let mut torrent = valid.clone();
+        torrent.info.file_tree[1].path = vec!["a".into(), "b".into()];
+        assert_eq!(kind(&torrent), ErrorKind::InvalidFileEntry);
+
+        let mut torrent = valid.clone();
+        torrent.info.file_tree[1].pieces_root = None;
+        assert_eq!(kind(&torrent), ErrorKind::InvalidFileEntry);
+
+        let mut torrent = valid.clone();
+        torrent.info.file_tree[0].path[0] = "..".into();
+        assert_eq!(kind(&torrent), ErrorKind::UnsafePath);
+
+        let mut torrent = valid.clone();
+        torrent.info.piece_length = 3 * 16384;
+        assert_eq!(kind(&torrent), ErrorKind::InvalidPieceLength);
+
+        let mut torrent = valid.clone();
+        torrent.info.meta_version = Some(3);
+        assert_eq!(kind(&torrent), ErrorKind::UnsupportedMetaVersion);
+
+        let mut torrent = valid;
+        torrent.info.meta_version = None;
+        assert_eq!(kind(&torrent), ErrorKind::InvalidFileEntry);
+    }
+
+    #[test]
+    fn cross_seeding_replaces_source_and_trackers_only() {
+        let torrent = TorrentFile::from_bytes(SINGLE).unwrap();
+        let urls = [TrackerUrl::parse("https://a").unwrap(), TrackerUrl::parse("https://b").unwrap()];
+        let copy = cross_seed(&torrent, "xyz", &urls);
+        assert_eq!(copy.info.source.as_deref(), Some("xyz"));
+        assert_eq!(copy.announce.as_ref().map(TrackerUrl::as_str), Some("https://a"));
+        assert_eq!(copy.announce_list, vec![vec!["https://a"], vec!["https://b"]]);
+        assert_eq!((&copy.info.pieces, copy.info.private, &copy.comment), (&torrent.info.pieces, true, &torrent.comment));
+        assert!(copy.validate().is_ok());
+        let hash = |torrent: &TorrentFile| crate::torrent::info_hash(&torrent.to_bytes().unwrap()).unwrap();
+        assert_ne!(hash(&copy), hash(&torrent));
+        // The same source gives the same info hash
+        assert_eq!(hash(&cross_seed(&torrent, "abc", &urls[..1])), hash(&torrent));
+
+        let copy = cross_seed(&torrent, "xyz", &[]);
+        assert!(copy.trackers().is_empty());
+    }
+
+    #[test]
+    fn reads_typed_fields_and_writes_them_back_unchanged() {
+        let torrent = TorrentFile::from_bytes(SINGLE).unwrap();
+        assert_eq!(torrent.announce_list, vec![vec!["udp://one", "udp://two"], vec!["http://back"]]);
+        assert_eq!(torrent.trackers(), vec!["udp://one", "udp://two", "http://back"]);
+        let hosts: Vec<_> = torrent.trackers_by_host().iter().map(|(host, urls)| (*host, urls.len())).collect();
+        assert_eq!(hosts, vec![("one", 1), ("two", 1), ("back", 1)]);
+        assert_eq!(torrent.creation_date, Some(1_700_000_000));
+        assert_eq!((torrent.info.length, torrent.info.piece_count()), (Some(10), 3));
+        assert!(torrent.info.private && !torrent.is_multi_file());
+        assert_eq!(torrent.info.source.as_deref(), Some("abc"));
+        assert!(torrent.extra.contains_key("x-website"));
+        assert_eq!(torrent.to_bytes().unwrap(), SINGLE);
+    }
+
+    #[test]
+    fn multi_file_torrents_round_trip() {
+        let torrent = multi_file();
+        let encoded = torrent.to_bytes().unwrap();
+        assert_eq!(TorrentFile::from_bytes(&encoded).unwrap(), torrent);
+        assert_eq!(torrent.total_length(), 5);
+        // The library's piece layout agrees with the typed fields
+        let map = crate::torrent::piece_map(&encoded).unwrap();
+        assert_eq!(map.files()[1].path, vec![b"demo".to_vec(), b"dir".to_vec(), b"b.txt".to_vec()]);
+    }
+
+    #[test]
+    fn validation_rejects_unusable_torrents() {
+        let kind = |torrent: &TorrentFile| ErrorKind::from_message(&torrent.validate().unwrap_err());
+        let mut torrent = multi_file();
+        torrent.info.pieces.truncate(20);
+        assert_eq!(kind(&torrent), ErrorKind::InvalidPieces);
+
+        let mut torrent = multi_file();
+        torrent.info.files[1].path[0] = "..".into();
+        assert_eq!(kind(&torrent), ErrorKind::UnsafePath);
+
+        let mut torrent = multi_file();
+        torrent.info.length = Some(5);
+        assert_eq!(kind(&torrent), ErrorKind::InvalidFileEntry);
+
+        let mut torrent = multi_file();
+        torrent.info.piece_length = 0;
+        assert_eq!(kind(&torrent), ErrorKind::InvalidPieceLength);
+
+        let mut torrent = multi_file();
+        torrent.announce_list = vec![vec![]];
+        assert_eq!(kind(&torrent), ErrorKind::InvalidTorrentField);
+        assert!(torrent.to_bytes().is_err());
+    }
+
+    #[test]
+    fn malformed_fields_are_reported() {
+        let error = |data: &[u8]| ErrorKind::from_message(&TorrentFile::from_bytes(data).unwrap_err());
+        assert_eq!(error(b"li1ee"), ErrorKind::NotADictionary);
+        assert_eq!(error(b"d8:announce1:ue"), ErrorKind::MissingInfo);
+        assert_eq!(error(b"d4:infoi1ee"), ErrorKind::InfoNotDictionary);
+        assert_eq!(error(b"d7:commenti1e4:infod6:lengthi0e4:name1:x12:piece lengthi1e6:pieces0:ee"), ErrorKind::InvalidTorrentField);
+        assert_eq!(error(b"d4:infod6:lengthi0e4:name1:x12:piece lengthi1e6:pieces0:7:privatei2eee"), ErrorKind::InvalidTorrentField);
+        assert_eq!(error(b"d4:infod6:lengthi-1e4:name1:x12:piece lengthi1e6:pieces0:ee"), ErrorKind::InvalidFileEntry);
+        assert_eq!(error(b"d8:announce7:tracker4:infod6:lengthi0e4:name1:x12:piece lengthi1e6:pieces0:ee"), ErrorKind::InvalidTrackerUrl);
+        assert_eq!(error(b"d13:announce-listll0:ee4:infod6:lengthi0e4:name1:x12:piece lengthi1e6:pieces0:ee"), ErrorKind::InvalidTrackerUrl);
+    }
+
+    #[test]
+    fn empty_tiers_are_dropped() {
+        let data = b"d13:announce-listllee4:infod6:lengthi0e4:name1:x12:piece lengthi1e6:pieces0:ee";
+        assert!(TorrentFile::from_bytes(data).unwrap().announce_list.is_empty());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn files_round_trip() {
+        let path = std::env::temp_dir().join("bencode_metainfo_round_trip.torrent");
+        let torrent = multi_file();
+        torrent.write_file(&path).unwrap();
+        assert_eq!(TorrentFile::from_file(&path).unwrap(), torrent);
+        std::fs::remove_file(&path).unwrap();
+        assert!(TorrentFile::from_file(&path).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn saves_keep_timestamped_backups() {
+        use crate::config::{BackupPolicy, WriteOptions};
+        let dir = std::env::temp_dir().join("bencode_metainfo_backups");
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("a.torrent");
+        let options = WriteOptions::new().with_backup(BackupPolicy::Timestamped(2));
+        let mut torrent = multi_file();
+        for comment in ["1", "2", "3", "4"] {
+            torrent.comment = Some(comment.to_string());
+            torrent.write_file_with_options(&path, &options).unwrap();
+        }
+        torrent.info.piece_length = 0;
+        let invalid = torrent.write_file_with_options(&path, &options);
+        let mut names: Vec<String> = std::fs::read_dir(&dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect();
+        names.sort();
+        let backups: Vec<_> = names[1..].iter().map(|name| TorrentFile::from_file(dir.join(name)).unwrap().comment).collect();
+        let current = TorrentFile::from_file(&path).unwrap().comment;
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert!(invalid.is_err());
+        assert_eq!(names.len(), 3);
+        assert_eq!(backups, [Some("2".to_string()), Some("3".to_string())]);
+        assert_eq!(current.as_deref(), Some("4"));
+    }
+}
@@ -0,0 +1,366 @@
+//! BitTorrent metainfo helpers that work on the raw encoded bytes.
+//!
+//! The info hash of a torrent is the SHA-1 of the `info` dictionary exactly as it
+//! appears in the file, so these helpers locate that span with the tokenizer rather
+//! than re-encoding a parsed tree (which could differ from the original bytes).
+//! [`TorrentFile`] gives typed access to the whole file for creating and editing torrents.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use core::ops::Range;
+
+use crate::error::messages::{ERR_INFO_NOT_DICTIONARY, ERR_MISSING_INFO, ERR_NOT_A_DICTIONARY, ERR_UNSUPPORTED_TYPE};
+use crate::error::parse::ParseError;
+use crate::nodes::node::Node;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+
+/// SHA-1 hashing used for info hashes
+pub mod sha1;
+
+/// SHA-256 hashing used for v2 info hashes
+pub mod sha256;
+
+/// Pluggable SHA-1 and SHA-256 backends
+pub mod hasher;
+
+/// Signing and verification of torrent metadata with user supplied crypto
+pub mod signature;
+
+/// Piece length, piece hashes and file layout of a torrent's payload
+pub mod pieces;
+
+/// Merkle trees, pieces roots and piece layers of v2 torrents (BEP 52)
+pub mod merkle;
+
+/// Incremental piece verification for downloads in progress
+pub mod verify;
+
+/// Tracker announce scheduling and response parsing
+pub mod announce;
+
+/// Announce responses with their peers, compact peer lists and scrape responses
+pub mod tracker;
+
+/// Tracker announce URLs split into scheme, host, port, path and query
+pub mod url;
+
+/// Typed DHT (KRPC) queries, responses and errors
+pub mod dht;
+
+/// Typed contents of a `.torrent` file
+pub mod metainfo;
+
+/// Extended handshake and ut_metadata messages of the extension protocol
+pub mod extension;
+
+/// Writing verified pieces to the torrent's files (requires `std` feature)
+#[cfg(feature = "std")]
+pub mod disk;
+
+/// Creating torrents from files on disk (requires `std` feature)
+#[cfg(feature = "std")]
+pub mod builder;
+
+/// Parsing directories of torrents across threads (requires `rayon` feature)
+#[cfg(feature = "rayon")]
+pub mod parallel;
+
+/// Re-export of the hash backend trait and the built in backend
+pub use hasher::{BuiltinHasher, DefaultHasher, Hasher};
+/// Re-export of the piece layout types
+pub use pieces::{FileEntry, FileSlice, PieceMap, piece_map};
+/// Re-export of the incremental verifier
+pub use verify::{IncrementalVerifier, PieceEvent};
+/// Re-export of the announce scheduler types
+pub use announce::{AnnounceEvent, AnnounceRequest, AnnounceScheduler, TrackerResponse};
+/// Re-export of the tracker response decoders
+pub use tracker::{
+    AnnounceResponse, Peer, PeerAddress, ScrapeResponse, ScrapeStats, encode_compact_peers, parse_compact_peers,
+    parse_compact_peers6,
+};
+/// Re-export of the tracker URL type
+pub use url::{TrackerUrl, group_by_host};
+/// Re-export of the typed torrent file
+pub use metainfo::{Info, InfoFile, MetaVersion, PieceLayers, TorrentFile, TreeFile, cross_seed};
+/// Re-export of the extension protocol messages
+pub use extension::{ExtendedHandshake, METADATA_PIECE_SIZE, MetadataMessage, MetadataMessageType, UT_METADATA};
+/// Re-export of the disk writer and on-disk piece verification (requires `std` feature)
+#[cfg(feature = "std")]
+pub use disk::{DiskWriter, verify_pieces, verify_pieces_with};
+/// Re-export of the torrent builder (requires `std` feature)
+#[cfg(feature = "std")]
+pub use builder::TorrentBuilder;
+/// Re-export of the parallel directory parser (requires `rayon` feature)
+#[cfg(feature = "rayon")]
+pub use parallel::parse_directory_parallel;
+
+use sha1::SHA1_LENGTH;
+use sha256::SHA256_LENGTH;
+
+/// Key of the info dictionary in a torrent file
+pub const INFO_KEY: &[u8] = b"info";
+
+/// Returns true if a path segment could escape the download directory
+pub(crate) fn is_unsafe_segment(bytes: &[u8]) -> bool {
+    bytes.is_empty() || bytes == b"." || bytes == b".." || bytes.iter().any(|b| matches!(b, b'/' | b'\\' | 0))
+}
+
+/// An entry of an encoded dictionary, located by byte offsets
+pub(crate) struct DictEntry<'a> {
+    /// Key content
+    pub key: &'a [u8],
+    /// Offset of the encoded key (its length prefix)
+    pub key_offset: usize,
+    /// Span of the encoded value
+    pub value: Range<usize>,
+}
+
+/// Reads the entries of the dictionary starting at `offset` without decoding values.
+///
+/// # Returns
+/// * `Ok((Vec<DictEntry>, usize))` - The entries in input order and the offset just past the dictionary
+/// * `Err(ParseError)` - If there is no well formed dictionary at `offset`
+pub(crate) fn dict_entries(source: &[u8], offset: usize) -> Result<(Vec<DictEntry<'_>>, usize), ParseError> {
+    let mut tokenizer = Tokenizer::new(&source[offset..]);
+    match tokenizer.next() {
+        Some(Ok(token)) if token.kind == TokenKind::DictStart => {}
+        Some(Err(e)) => return Err(ParseError::new(e.message, offset + e.offset)),
+        _ => return Err(ParseError::new(ERR_NOT_A_DICTIONARY, offset)),
+    }
+    let relocate = |e: ParseError| ParseError::new(e.message, offset + e.offset);
+    let mut entries = Vec::new();
+    let mut key = None;
+    while let Some(token) = tokenizer.next() {
+        let token = token.map_err(relocate)?;
+        let end = match token.kind {
+            TokenKind::Key(content) => {
+                key = Some((content, offset + token.offset));
+                continue;
+            }
+            TokenKind::DictEnd => return Ok((entries, offset + token.end)),
+            TokenKind::ListStart | TokenKind::DictStart => tokenizer.skip_container().map_err(relocate)?,
+            _ => token.end,
+        };
+        if let Some((key, key_offset)) = key.take() {
+            entries.push(DictEntry {
+                key,
+                key_offset,
+                value: offset + token.offset..offset + end,
+            });
+        }
+    }
+    // The tokenizer reports unterminated dictionaries, so this is not reached
+    Err(ParseError::new(ERR_NOT_A_DICTIONARY, offset))
+}
+
+/// Finds the byte span of the root `info` dictionary.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+///
+/// # Returns
+/// * `Ok(Range<usize>)` - Span of the encoded `info` dictionary
+/// * `Err(ParseError)` - If the input is malformed or has no `info` dictionary
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::info_span;
+///
+/// let data = b"d8:announce3:url4:infod4:name1:xee";
+/// assert_eq!(&data[info_span(data).unwrap()], b"d4:name1:xe");
+/// ```
+pub fn info_span(source: &[u8]) -> Result<Range<usize>, ParseError> {
+    let (entries, end) = dict_entries(source, 0)?;
+    match entries.into_iter().find(|entry| entry.key == INFO_KEY) {
+        Some(entry) if source[entry.value.start] == b'd' => Ok(entry.value),
+        Some(entry) => Err(ParseError::new(ERR_INFO_NOT_DICTIONARY, entry.value.start)),
+        None => Err(ParseError::new(ERR_MISSING_INFO, end)),
+    }
+}
+
+/// Computes the (v1) info hash: the SHA-1 of the encoded `info` dictionary.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+///
+/// # Returns
+/// * `Ok([u8; 20])` - The info hash
+/// * `Err(ParseError)` - If the input is malformed or has no `info` dictionary
+pub fn info_hash(source: &[u8]) -> Result<[u8; SHA1_LENGTH], ParseError> {
+    info_hash_with(source, &DefaultHasher)
+}
+
+/// Computes the (v1) info hash as [`info_hash`] does, hashing with `hasher`.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+/// * `hasher` - The SHA-1 backend
+///
+/// # Returns
+/// * `Ok([u8; 20])` - The info hash
+/// * `Err(ParseError)` - If the input is malformed or has no `info` dictionary
+pub fn info_hash_with(source: &[u8], hasher: &dyn Hasher) -> Result<[u8; SHA1_LENGTH], ParseError> {
+    let span = info_span(source)?;
+    Ok(hasher.sha1(&source[span]))
+}
+
+/// Returns true if the tree holds a None node, which has no encoding
+fn contains_none(node: &Node) -> bool {
+    match node {
+        Node::None => true,
+        Node::List(list) => list.iter().any(contains_none),
+        Node::Dictionary(dict) => dict.values().any(contains_none),
+        _ => false,
+    }
+}
+
+/// Encodes the root `info` dictionary of a parsed torrent canonically: keys
+/// sorted, integers in shortest form and Raw values re-encoded.
+fn canonical_info(torrent: &Node) -> Result<Vec<u8>, String> {
+    let root = torrent.as_dictionary().ok_or_else(|| ERR_NOT_A_DICTIONARY.to_string())?;
+    let info = root.get("info").ok_or_else(|| ERR_MISSING_INFO.to_string())?.expand_raw()?;
+    if !info.is_dictionary() {
+        return Err(ERR_INFO_NOT_DICTIONARY.to_string());
+    }
+    if contains_none(&info) {
+        return Err(ERR_UNSUPPORTED_TYPE.to_string());
+    }
+    Ok(crate::stringify_to_bytes(&info)?)
+}
+
+/// Computes the (v1) info hash of a parsed torrent: the SHA-1 of its `info`
+/// dictionary, re-encoded canonically.
+///
+/// This matches the hash of the original file when that file was canonical
+/// bencode, which any valid torrent is. Files with unsorted keys or binary
+/// dictionary keys do not survive a parse unchanged, so hash their bytes with
+/// [`info_hash`] instead.
+///
+/// # Arguments
+/// * `torrent` - The parsed torrent, or one being built
+///
+/// # Returns
+/// * `Ok([u8; 20])` - The info hash
+/// * `Err(String)` - If the root is not a dictionary, `info` is missing or not
+///   a dictionary, or it holds a None node
+///
+/// # Example
+/// ```
+/// use bencode_lib::{parse_bytes, torrent::{info_hash, info_hash_v1}};
+///
+/// let data = b"d8:announce3:url4:infod6:lengthi5e4:name1:xee";
+/// assert_eq!(info_hash_v1(&parse_bytes(data).unwrap()).unwrap(), info_hash(data).unwrap());
+/// ```
+pub fn info_hash_v1(torrent: &Node) -> Result<[u8; SHA1_LENGTH], String> {
+    info_hash_v1_with(torrent, &DefaultHasher)
+}
+
+/// Computes the (v1) info hash as [`info_hash_v1`] does, hashing with `hasher`.
+pub fn info_hash_v1_with(torrent: &Node, hasher: &dyn Hasher) -> Result<[u8; SHA1_LENGTH], String> {
+    canonical_info(torrent).map(|info| hasher.sha1(&info))
+}
+
+/// Computes the (v2, BEP 52) info hash of a parsed torrent: the SHA-256 of its
+/// `info` dictionary, re-encoded canonically.
+///
+/// # Returns
+/// * `Ok([u8; 32])` - The info hash
+/// * `Err(String)` - As for [`info_hash_v1`]
+pub fn info_hash_v2(torrent: &Node) -> Result<[u8; SHA256_LENGTH], String> {
+    info_hash_v2_with(torrent, &DefaultHasher)
+}
+
+/// Computes the (v2) info hash as [`info_hash_v2`] does, hashing with `hasher`.
+pub fn info_hash_v2_with(torrent: &Node, hasher: &dyn Hasher) -> Result<[u8; SHA256_LENGTH], String> {
+    canonical_info(torrent).map(|info| hasher.sha256(&info))
+}
+
+/// Formats bytes as lowercase hexadecimal.
+pub fn to_hex(bytes: &[u8]) -> String {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        hex.push(DIGITS[(byte >> 4) as usize] as char);
+        hex.push(DIGITS[(byte & 0x0f) as usize] as char);
+    }
+    hex
+}
+
+/// Parses a 40 character hexadecimal info hash (either case).
+///
+/// # Returns
+/// The hash, or None if `hex` is not exactly 40 hex digits
+pub fn hash_from_hex(hex: &str) -> Option<[u8; SHA1_LENGTH]> {
+    if hex.len() != SHA1_LENGTH * 2 {
+        return None;
+    }
+    let mut hash = [0; SHA1_LENGTH];
+    for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+        let pair = core::str::from_utf8(pair).ok()?;
+        *byte = u8::from_str_radix(pair, 16).ok()?;
+    }
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::messages::ERR_UNTERMINATED_DICTIONARY;
+
+    #[test]
+    fn info_span_skips_other_values() {
+        let data = b"d1:ad4:infoi1ee4:infod6:lengthi5ee4:zzzzi0ee";
+        assert_eq!(&data[info_span(data).unwrap()], b"d6:lengthi5ee");
+    }
+
+    #[test]
+    fn info_span_errors() {
+        assert_eq!(info_span(b"li1ee"), Err(ParseError::new(ERR_NOT_A_DICTIONARY, 0)));
+        assert_eq!(info_span(b"d1:ai1ee"), Err(ParseError::new(ERR_MISSING_INFO, 8)));
+        assert_eq!(info_span(b"d4:infoi1ee"), Err(ParseError::new(ERR_INFO_NOT_DICTIONARY, 7)));
+        assert_eq!(info_span(b"d4:infod1:ai1e"), Err(ParseError::new(ERR_UNTERMINATED_DICTIONARY, 7)));
+    }
+
+    #[test]
+    fn info_hash_uses_original_bytes() {
+        // Unsorted keys inside info must be hashed as they appear
+        let data = b"d4:infod1:bi1e1:ai2eee";
+        assert_eq!(info_hash(data).unwrap(), sha1::sha1(b"d1:bi1e1:ai2ee"));
+    }
+
+    #[test]
+    fn node_info_hashes_use_the_canonical_encoding() {
+        let data = b"d8:announce3:url4:infod5:filesld6:lengthi3e4:pathl1:aeee4:name4:demoee";
+        let node = crate::parse_bytes(data).unwrap();
+        assert_eq!(info_hash_v1(&node).unwrap(), info_hash(data).unwrap());
+        assert_eq!(info_hash_v2(&node).unwrap(), sha256::sha256(&data[info_span(data).unwrap()]));
+
+        // Raw info values are hashed the same as decoded ones
+        let raw = Node::raw(b"d5:filesld6:lengthi3e4:pathl1:aeee4:name4:demoe".to_vec()).unwrap();
+        let built = Node::from([("info", raw)]);
+        assert_eq!(info_hash_v1(&built).unwrap(), info_hash(data).unwrap());
+    }
+
+    #[test]
+    fn node_info_hash_errors() {
+        assert_eq!(info_hash_v1(&Node::from(1)).unwrap_err(), ERR_NOT_A_DICTIONARY);
+        assert_eq!(info_hash_v1(&Node::from([("a", 1)])).unwrap_err(), ERR_MISSING_INFO);
+        assert_eq!(info_hash_v2(&Node::from([("info", 1)])).unwrap_err(), ERR_INFO_NOT_DICTIONARY);
+        let with_none = Node::from([("info", Node::from([("x", Node::None)]))]);
+        assert_eq!(info_hash_v2(&with_none).unwrap_err(), ERR_UNSUPPORTED_TYPE);
+    }
+
+    #[test]
+    fn hex_round_trip() {
+        let hash = sha1::sha1(b"abc");
+        let hex = to_hex(&hash);
+        assert_eq!(hash_from_hex(&hex), Some(hash));
+        assert_eq!(hash_from_hex(&hex.to_uppercase()), Some(hash));
+        assert_eq!(hash_from_hex("abc"), None);
+        assert_eq!(hash_from_hex(&"zz".repeat(20)), None);
+    }
+}
@@ -0,0 +1,101 @@
+//! Parsing whole directories of torrents across threads.
+//!
+//! [`parse_directory_parallel`] finds every `.torrent` file below a directory
+//! and parses them on rayon's global thread pool. Each file is read and parsed
+//! on its own, so one unreadable or malformed file only fails its own entry.
+//! To bound the number of threads, call it inside [`rayon::ThreadPool::install`].
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+
+use crate::storage::TORRENT_EXTENSION;
+
+use super::metainfo::TorrentFile;
+
+/// Appends the `.torrent` files below `dir`, at any depth, to `paths`
+fn find_torrents(dir: &Path, paths: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        // Links are not followed, so a link cycle cannot recurse forever
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            find_torrents(&path, paths)?;
+        } else if file_type.is_file() && path.extension().is_some_and(|ext| ext == TORRENT_EXTENSION) {
+            paths.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Parses every `.torrent` file below `dir`, including in subdirectories,
+/// across threads (requires `rayon` feature).
+///
+/// # Arguments
+/// * `dir` - The directory holding the torrents
+///
+/// # Returns
+/// * `Ok(Vec<(PathBuf, Result<TorrentFile, String>)>)` - Each file, sorted by
+///   path, with its torrent or the reason it could not be read or is not a
+///   valid torrent
+/// * `Err(std::io::Error)` - If the directory or a subdirectory could not be listed
+///
+/// # Example
+/// ```no_run
+/// use bencode_lib::torrent::parse_directory_parallel;
+///
+/// for (path, torrent) in parse_directory_parallel("torrents").unwrap() {
+///     match torrent {
+///         Ok(torrent) => println!("{}: {}", path.display(), torrent.info.name),
+///         Err(e) => eprintln!("{}: {}", path.display(), e),
+///     }
+/// }
+/// ```
+pub fn parse_directory_parallel<P: AsRef<Path>>(dir: P) -> io::Result<Vec<(PathBuf, Result<TorrentFile, String>)>> {
+    let mut paths = Vec::new();
+    find_torrents(dir.as_ref(), &mut paths)?;
+    paths.sort();
+    Ok(paths
+        .into_par_iter()
+        .map(|path| {
+            let torrent = TorrentFile::from_file(&path);
+            (path, torrent)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+    use crate::test_support::create_test_dir;
+
+    #[test]
+    fn each_torrent_is_parsed_on_its_own() {
+        let torrent = b"d4:infod6:lengthi0e4:name4:demo12:piece lengthi16e6:pieces0:ee";
+        let mut files: Vec<(String, &[u8])> = (0..50).map(|i| (format!("sub/{:02}.torrent", i), &torrent[..])).collect();
+        files.push(("a.torrent".to_string(), b"d4:infoi1ee"));
+        files.push(("b.torrent".to_string(), b"l"));
+        files.push(("notes.txt".to_string(), b"not a torrent"));
+        let files: Vec<(&str, &[u8])> = files.iter().map(|(name, data)| (name.as_str(), *data)).collect();
+        let dir = create_test_dir("parallel_isolated", &files);
+        let results = parse_directory_parallel(&dir).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(results.len(), 52);
+        assert_eq!(results[0].0, dir.join("a.torrent"));
+        assert_eq!(ErrorKind::from_message(results[0].1.as_ref().unwrap_err()), ErrorKind::InfoNotDictionary);
+        assert!(results[1].1.as_ref().unwrap_err().contains("Unterminated list"));
+        assert_eq!(results[2].0, dir.join("sub").join("00.torrent"));
+        assert!(results[2..].iter().all(|(_, torrent)| torrent.as_ref().is_ok_and(|t| t.info.name == "demo")));
+    }
+
+    #[test]
+    fn missing_directory_is_an_error() {
+        let error = parse_directory_parallel(std::env::temp_dir().join("bencode_parallel_missing")).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+}
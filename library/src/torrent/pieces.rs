@@ -0,0 +1,289 @@
+//! Layout of a torrent's pieces over its files.
+//!
+//! A v1 torrent's payload is the concatenation of its files, cut into pieces of
+//! `piece length` bytes (the last one may be shorter), each identified by a SHA-1
+//! hash in the `pieces` string. [`PieceMap`] records that layout so downloaders
+//! can tell which piece a byte belongs to and which file ranges a piece covers.
+
+#[cfg(not(feature = "std"))]
+use alloc::{format, vec::Vec};
+
+use core::ops::Range;
+
+use crate::error::messages::{ERR_INVALID_FILE_ENTRY, ERR_INVALID_PIECE_LENGTH, ERR_INVALID_PIECES};
+use crate::error::parse::ParseError;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::parser::borrowed::parse_borrowed;
+
+use super::info_span;
+use super::sha1::SHA1_LENGTH;
+
+/// One file of the torrent payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+    /// Path segments relative to the download directory, exactly as stored in
+    /// the torrent (not necessarily UTF-8). For multi-file torrents the first
+    /// segment is the torrent's `name`.
+    pub path: Vec<Vec<u8>>,
+    /// Length of the file in bytes
+    pub length: u64,
+    /// Offset of the file's first byte within the payload
+    pub offset: u64,
+}
+
+/// The part of one file covered by (part of) a piece.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileSlice {
+    /// Index into [`PieceMap::files`]
+    pub file: usize,
+    /// Offset within the file
+    pub file_offset: u64,
+    /// Offset within the piece
+    pub piece_offset: usize,
+    /// Number of bytes
+    pub length: usize,
+}
+
+/// Piece length, piece hashes and file layout of a torrent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PieceMap {
+    piece_length: u64,
+    total_length: u64,
+    hashes: Vec<[u8; SHA1_LENGTH]>,
+    files: Vec<FileEntry>,
+}
+
+/// Reads a non-negative integer field of a dictionary
+fn length_field(dict: &BorrowedNode, key: &[u8]) -> Option<u64> {
+    dict.as_dictionary()?
+        .get(key)?
+        .as_integer()
+        .and_then(|value| u64::try_from(value).ok())
+}
+
+/// Reads one entry of a multi-file torrent's `files` list
+fn file_entry(name: &[u8], entry: &BorrowedNode, offset: u64) -> Option<FileEntry> {
+    let length = length_field(entry, b"length")?;
+    let segments = entry.as_dictionary()?.get(&b"path"[..])?.as_list()?;
+    let mut path = Vec::with_capacity(segments.len() + 1);
+    path.push(name.to_vec());
+    for segment in segments {
+        path.push(segment.as_bytes()?.to_vec());
+    }
+    (path.len() > 1).then_some(FileEntry { path, length, offset })
+}
+
+impl PieceMap {
+    /// Returns the nominal piece length
+    pub fn piece_length(&self) -> u64 {
+        self.piece_length
+    }
+
+    /// Returns the total payload length (the sum of the file lengths)
+    pub fn total_length(&self) -> u64 {
+        self.total_length
+    }
+
+    /// Returns the number of pieces
+    pub fn piece_count(&self) -> usize {
+        self.hashes.len()
+    }
+
+    /// Returns the expected SHA-1 hash of a piece
+    pub fn piece_hash(&self, index: usize) -> Option<&[u8; SHA1_LENGTH]> {
+        self.hashes.get(index)
+    }
+
+    /// Returns the files of the payload in order
+    pub fn files(&self) -> &[FileEntry] {
+        &self.files
+    }
+
+    /// Returns the payload byte range of a piece, or None if out of range
+    pub fn piece_range(&self, index: usize) -> Option<Range<u64>> {
+        if index >= self.hashes.len() {
+            return None;
+        }
+        let start = index as u64 * self.piece_length;
+        Some(start..(start + self.piece_length).min(self.total_length))
+    }
+
+    /// Returns the length of a piece (only the last piece can be short)
+    pub fn piece_size(&self, index: usize) -> Option<usize> {
+        self.piece_range(index).map(|range| (range.end - range.start) as usize)
+    }
+
+    /// Returns the index of the piece containing a payload offset
+    pub fn piece_at(&self, offset: u64) -> Option<usize> {
+        (offset < self.total_length).then(|| (offset / self.piece_length) as usize)
+    }
+
+    /// Returns the file ranges a piece covers, in payload order.
+    ///
+    /// Empty files are skipped, as they hold no piece data.
+    pub fn file_slices(&self, index: usize) -> Vec<FileSlice> {
+        let Some(range) = self.piece_range(index) else {
+            return Vec::new();
+        };
+        self.files
+            .iter()
+            .enumerate()
+            .filter_map(|(file, entry)| {
+                let start = range.start.max(entry.offset);
+                let end = range.end.min(entry.offset + entry.length);
+                (start < end).then(|| FileSlice {
+                    file,
+                    file_offset: start - entry.offset,
+                    piece_offset: (start - range.start) as usize,
+                    length: (end - start) as usize,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Reads the piece layout of a (v1) torrent.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+///
+/// # Returns
+/// * `Ok(PieceMap)` - The piece length, hashes and files of the torrent
+/// * `Err(ParseError)` - If the input is malformed, has no `info` dictionary or
+///   its piece or file fields are missing or inconsistent; the error's path
+///   names the offending field
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::piece_map;
+///
+/// let data = b"d4:infod6:lengthi5e4:name1:x12:piece lengthi4e6:pieces40:\
+/// aaaaaaaaaaaaaaaaaaaabbbbbbbbbbbbbbbbbbbbee";
+/// let map = piece_map(data).unwrap();
+/// assert_eq!(map.piece_count(), 2);
+/// assert_eq!(map.piece_range(1), Some(4..5));
+/// ```
+pub fn piece_map(source: &[u8]) -> Result<PieceMap, ParseError> {
+    let span = info_span(source)?;
+    let at = span.start;
+    let info = parse_borrowed(&source[span]).map_err(|e| ParseError::new(e, at))?;
+    let invalid = |message: &str, field: &str| ParseError::new(message, at).with_path(field);
+
+    let piece_length = length_field(&info, b"piece length")
+        .filter(|length| *length > 0)
+        .ok_or_else(|| invalid(ERR_INVALID_PIECE_LENGTH, "info.piece length"))?;
+    let dict = info.as_dictionary().ok_or_else(|| invalid(ERR_INVALID_FILE_ENTRY, "info"))?;
+    let name = dict
+        .get(&b"name"[..])
+        .and_then(BorrowedNode::as_bytes)
+        .ok_or_else(|| invalid(ERR_INVALID_FILE_ENTRY, "info.name"))?;
+
+    let files = match dict.get(&b"files"[..]) {
+        Some(list) => {
+            let entries = list.as_list().ok_or_else(|| invalid(ERR_INVALID_FILE_ENTRY, "info.files"))?;
+            let mut files = Vec::with_capacity(entries.len());
+            let mut offset = 0u64;
+            for (index, entry) in entries.iter().enumerate() {
+                let file = file_entry(name, entry, offset)
+                    .ok_or_else(|| invalid(ERR_INVALID_FILE_ENTRY, &format!("info.files.{}", index)))?;
+                offset = offset
+                    .checked_add(file.length)
+                    .ok_or_else(|| invalid(ERR_INVALID_FILE_ENTRY, &format!("info.files.{}", index)))?;
+                files.push(file);
+            }
+            files
+        }
+        None => {
+            let length = length_field(&info, b"length").ok_or_else(|| invalid(ERR_INVALID_FILE_ENTRY, "info.length"))?;
+            let path = [name.to_vec()].into();
+            [FileEntry { path, length, offset: 0 }].into()
+        }
+    };
+    let total_length = files.iter().map(|file| file.length).sum::<u64>();
+
+    let pieces = dict
+        .get(&b"pieces"[..])
+        .and_then(BorrowedNode::as_bytes)
+        .filter(|pieces| pieces.len().is_multiple_of(SHA1_LENGTH))
+        .filter(|pieces| pieces.len() as u64 / SHA1_LENGTH as u64 == total_length.div_ceil(piece_length))
+        .ok_or_else(|| invalid(ERR_INVALID_PIECES, "info.pieces"))?;
+    let hashes = pieces
+        .chunks_exact(SHA1_LENGTH)
+        .map(|chunk| {
+            let mut hash = [0; SHA1_LENGTH];
+            hash.copy_from_slice(chunk);
+            hash
+        })
+        .collect();
+
+    Ok(PieceMap {
+        piece_length,
+        total_length,
+        hashes,
+        files,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    /// Builds a multi-file torrent with 20-byte placeholder hashes
+    fn multi_file(piece_length: u64, lengths: &[u64]) -> Vec<u8> {
+        let total: u64 = lengths.iter().sum();
+        let pieces = total.div_ceil(piece_length) as usize;
+        let mut data = b"d4:infod5:filesl".to_vec();
+        for (index, length) in lengths.iter().enumerate() {
+            data.extend(format!("d6:lengthi{}e4:pathl3:dir5:file{}ee", length, index).bytes());
+        }
+        data.extend(format!("e4:name4:demo12:piece lengthi{}e6:pieces{}:", piece_length, pieces * 20).bytes());
+        data.extend(vec![b'h'; pieces * 20]);
+        data.extend(b"ee");
+        data
+    }
+
+    #[test]
+    fn single_file_layout() {
+        let data = b"d4:infod6:lengthi10e4:name3:one12:piece lengthi4e6:pieces60:\
+aaaaaaaaaaaaaaaaaaaabbbbbbbbbbbbbbbbbbbbccccccccccccccccccccee";
+        let map = piece_map(data).unwrap();
+        assert_eq!(map.total_length(), 10);
+        assert_eq!(map.piece_count(), 3);
+        assert_eq!(map.piece_size(2), Some(2));
+        assert_eq!(map.piece_hash(1), Some(&[b'b'; 20]));
+        assert_eq!(map.files()[0].path, vec![b"one".to_vec()]);
+        assert_eq!(map.piece_at(9), Some(2));
+        assert_eq!(map.piece_at(10), None);
+        assert_eq!(map.piece_range(3), None);
+    }
+
+    #[test]
+    fn pieces_spanning_files_are_sliced() {
+        let map = piece_map(&multi_file(4, &[3, 0, 6])).unwrap();
+        assert_eq!(map.files()[2].offset, 3);
+        assert_eq!(map.files()[2].path, vec![b"demo".to_vec(), b"dir".to_vec(), b"file2".to_vec()]);
+        assert_eq!(
+            map.file_slices(0),
+            vec![
+                FileSlice { file: 0, file_offset: 0, piece_offset: 0, length: 3 },
+                FileSlice { file: 2, file_offset: 0, piece_offset: 3, length: 1 },
+            ]
+        );
+        assert_eq!(map.file_slices(2), vec![FileSlice { file: 2, file_offset: 5, piece_offset: 0, length: 1 }]);
+    }
+
+    #[test]
+    fn inconsistent_fields_are_reported_with_their_path() {
+        let wrong_count = b"d4:infod6:lengthi10e4:name3:one12:piece lengthi4e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+        let error = piece_map(wrong_count).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidPieces);
+        assert_eq!(error.path.as_deref(), Some("info.pieces"));
+
+        let zero = b"d4:infod6:lengthi1e4:name1:x12:piece lengthi0e6:pieces0:ee";
+        assert_eq!(piece_map(zero).unwrap_err().kind(), ErrorKind::InvalidPieceLength);
+
+        let no_path = b"d4:infod5:filesld6:lengthi1eee4:name1:x12:piece lengthi1e6:pieces20:aaaaaaaaaaaaaaaaaaaaee";
+        assert_eq!(piece_map(no_path).unwrap_err().path.as_deref(), Some("info.files.0"));
+    }
+}
@@ -0,0 +1,373 @@
+//! Signed torrent metadata, following the layout of BEP 35.
+//!
+//! Signatures cover the encoded `info` dictionary and are stored either detached
+//! (kept by the caller) or embedded in a root `signatures` dictionary keyed by
+//! signer identity:
+//!
+//! ```text
+//! d ... 4:info d...e ... 10:signatures d <signer> d 11:certificate ... 9:signature ... e e e
+//! ```
+//!
+//! No cryptography is implemented here: callers plug in their own algorithms
+//! through the [`Signer`] and [`Verifier`] traits. Embedding a signature rewrites
+//! only the `signatures` entry, so the `info` dictionary and its info hash are
+//! preserved byte for byte.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use crate::error::messages::{ERR_INVALID_SIGNATURE_ENTRY, ERR_SIGNATURE_NOT_FOUND};
+use crate::error::parse::ParseError;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::parser::borrowed::parse_borrowed;
+use crate::torrent::sha1::sha1;
+use crate::torrent::{DictEntry, dict_entries, info_span};
+
+/// Root key of the embedded signatures dictionary
+pub const SIGNATURES_KEY: &[u8] = b"signatures";
+/// Key of the signature bytes within a signer's entry
+pub const SIGNATURE_KEY: &[u8] = b"signature";
+/// Key of the optional certificate within a signer's entry
+pub const CERTIFICATE_KEY: &[u8] = b"certificate";
+
+/// Produces signatures; implemented by the caller with the crypto of their choice.
+pub trait Signer {
+    /// Signs `message` (the encoded `info` dictionary).
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, String>;
+
+    /// Certificate to embed alongside the signature, if any.
+    fn certificate(&self) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+/// Checks signatures; implemented by the caller with the crypto of their choice.
+pub trait Verifier {
+    /// Returns true if `signature` is valid for `message`.
+    fn verify(&self, message: &[u8], signature: &[u8], certificate: Option<&[u8]>) -> bool;
+}
+
+/// Integrity only "signature": the SHA-1 of the message.
+///
+/// Detects accidental corruption of the `info` dictionary but offers no
+/// authenticity, as anyone can recompute it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Sha1Checksum;
+
+impl Signer for Sha1Checksum {
+    fn sign(&self, message: &[u8]) -> Result<Vec<u8>, String> {
+        Ok(sha1(message).to_vec())
+    }
+}
+
+impl Verifier for Sha1Checksum {
+    fn verify(&self, message: &[u8], signature: &[u8], _certificate: Option<&[u8]>) -> bool {
+        sha1(message) == signature
+    }
+}
+
+/// A signature embedded in a torrent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    /// Identity of the signer (the key in the `signatures` dictionary)
+    pub signer: Vec<u8>,
+    /// The signature bytes
+    pub signature: Vec<u8>,
+    /// The certificate, if one was embedded
+    pub certificate: Option<Vec<u8>>,
+}
+
+/// Returns the bytes covered by signatures: the encoded `info` dictionary.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+///
+/// # Returns
+/// * `Ok(&[u8])` - The signed message
+/// * `Err(ParseError)` - If the torrent is malformed or has no `info` dictionary
+pub fn signed_message(source: &[u8]) -> Result<&[u8], ParseError> {
+    Ok(&source[info_span(source)?])
+}
+
+/// Signs a torrent, returning a detached signature.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+/// * `signer` - Produces the signature
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The signature
+/// * `Err(String)` - If the torrent is malformed or signing failed
+pub fn sign_detached(source: &[u8], signer: &dyn Signer) -> Result<Vec<u8>, String> {
+    let message = signed_message(source).map_err(|e| e.to_string())?;
+    signer.sign(message)
+}
+
+/// Verifies a detached signature over a torrent.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+/// * `signature` - The detached signature
+/// * `verifier` - Checks the signature
+///
+/// # Returns
+/// * `Ok(bool)` - Whether the signature is valid
+/// * `Err(ParseError)` - If the torrent is malformed or has no `info` dictionary
+pub fn verify_detached(source: &[u8], signature: &[u8], verifier: &dyn Verifier) -> Result<bool, ParseError> {
+    let message = signed_message(source)?;
+    Ok(verifier.verify(message, signature, None))
+}
+
+/// Appends a bencoded byte string to `out`.
+fn push_bytes(out: &mut Vec<u8>, bytes: &[u8]) {
+    out.extend_from_slice(bytes.len().to_string().as_bytes());
+    out.push(b':');
+    out.extend_from_slice(bytes);
+}
+
+/// Writes a dictionary from existing entries, replacing or inserting `key` with
+/// the encoded `value` (or removing it if `value` is None). Entries are assumed
+/// sorted, and other entries are copied byte for byte.
+fn rewrite_entry(source: &[u8], entries: &[DictEntry], key: &[u8], value: Option<&[u8]>, out: &mut Vec<u8>) {
+    out.push(b'd');
+    let mut pending = value;
+    for entry in entries {
+        if entry.key >= key {
+            if let Some(value) = pending.take() {
+                push_bytes(out, key);
+                out.extend_from_slice(value);
+            }
+            if entry.key == key {
+                continue;
+            }
+        }
+        out.extend_from_slice(&source[entry.key_offset..entry.value.end]);
+    }
+    if let Some(value) = pending {
+        push_bytes(out, key);
+        out.extend_from_slice(value);
+    }
+    out.push(b'e');
+}
+
+/// Signs a torrent and embeds the signature under `signer_id` in the root
+/// `signatures` dictionary, replacing any previous signature by the same signer.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+/// * `signer_id` - Identity of the signer
+/// * `signer` - Produces the signature (and optional certificate)
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The signed torrent
+/// * `Err(String)` - If the torrent is malformed or signing failed
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::signature::{sign, verify, Sha1Checksum};
+/// use bencode_lib::torrent::info_hash;
+///
+/// let torrent = b"d8:announce3:url4:infod4:name1:xee";
+/// let signed = sign(torrent, "me", &Sha1Checksum).unwrap();
+/// assert_eq!(verify(&signed, "me", &Sha1Checksum), Ok(true));
+/// assert_eq!(info_hash(&signed), info_hash(torrent));
+/// ```
+pub fn sign(source: &[u8], signer_id: &str, signer: &dyn Signer) -> Result<Vec<u8>, String> {
+    let message = signed_message(source).map_err(|e| e.to_string())?;
+    let signature = signer.sign(message)?;
+    let mut entry = Vec::new();
+    entry.push(b'd');
+    if let Some(certificate) = signer.certificate() {
+        push_bytes(&mut entry, CERTIFICATE_KEY);
+        push_bytes(&mut entry, &certificate);
+    }
+    push_bytes(&mut entry, SIGNATURE_KEY);
+    push_bytes(&mut entry, &signature);
+    entry.push(b'e');
+
+    let (root, end) = dict_entries(source, 0).map_err(|e| e.to_string())?;
+    let existing = match root.iter().find(|entry| entry.key == SIGNATURES_KEY) {
+        Some(signatures) => dict_entries(source, signatures.value.start).map_err(|e| e.to_string())?.0,
+        None => Vec::new(),
+    };
+    let mut signatures = Vec::new();
+    rewrite_entry(source, &existing, signer_id.as_bytes(), Some(&entry), &mut signatures);
+
+    let mut out = Vec::with_capacity(source.len() + signatures.len() + SIGNATURES_KEY.len() + 4);
+    rewrite_entry(source, &root, SIGNATURES_KEY, Some(&signatures), &mut out);
+    out.extend_from_slice(&source[end..]);
+    Ok(out)
+}
+
+/// Returns the signatures embedded in a torrent, in signer order.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+///
+/// # Returns
+/// * `Ok(Vec<Signature>)` - The embedded signatures (empty if unsigned)
+/// * `Err(ParseError)` - If the torrent or its `signatures` dictionary is malformed
+pub fn signatures(source: &[u8]) -> Result<Vec<Signature>, ParseError> {
+    let (root, _) = dict_entries(source, 0)?;
+    let Some(signatures) = root.iter().find(|entry| entry.key == SIGNATURES_KEY) else {
+        return Ok(Vec::new());
+    };
+    let mut found = Vec::new();
+    for entry in dict_entries(source, signatures.value.start)?.0 {
+        let invalid = || ParseError::new(ERR_INVALID_SIGNATURE_ENTRY, entry.value.start);
+        let BorrowedNode::Dictionary(fields) = parse_borrowed(&source[entry.value.clone()]).map_err(|_| invalid())?
+        else {
+            return Err(invalid());
+        };
+        let signature = fields.get(SIGNATURE_KEY).and_then(|value| value.as_bytes()).ok_or_else(invalid)?;
+        let certificate = match fields.get(CERTIFICATE_KEY) {
+            Some(value) => Some(value.as_bytes().ok_or_else(invalid)?.to_vec()),
+            None => None,
+        };
+        found.push(Signature {
+            signer: entry.key.to_vec(),
+            signature: signature.to_vec(),
+            certificate,
+        });
+    }
+    Ok(found)
+}
+
+/// Verifies the embedded signature of `signer_id`.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+/// * `signer_id` - Identity of the signer to check
+/// * `verifier` - Checks the signature
+///
+/// # Returns
+/// * `Ok(bool)` - Whether the signature is valid
+/// * `Err(String)` - If the torrent is malformed or has no signature by `signer_id`
+pub fn verify(source: &[u8], signer_id: &str, verifier: &dyn Verifier) -> Result<bool, String> {
+    let message = signed_message(source).map_err(|e| e.to_string())?;
+    let signature = signatures(source)
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .find(|signature| signature.signer == signer_id.as_bytes())
+        .ok_or_else(|| ERR_SIGNATURE_NOT_FOUND.to_string())?;
+    Ok(verifier.verify(message, &signature.signature, signature.certificate.as_deref()))
+}
+
+/// Removes the embedded `signatures` dictionary, leaving everything else intact.
+///
+/// # Arguments
+/// * `source` - The bencode-encoded torrent
+///
+/// # Returns
+/// * `Ok(Vec<u8>)` - The unsigned torrent
+/// * `Err(ParseError)` - If the torrent is malformed
+pub fn strip_signatures(source: &[u8]) -> Result<Vec<u8>, ParseError> {
+    let (root, end) = dict_entries(source, 0)?;
+    let mut out = Vec::with_capacity(source.len());
+    rewrite_entry(source, &root, SIGNATURES_KEY, None, &mut out);
+    out.extend_from_slice(&source[end..]);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::borrowed::validate_bencode;
+    use crate::parser::key_order::check_key_order;
+    use crate::torrent::info_hash;
+
+    const TORRENT: &[u8] = b"d8:announce3:url4:infod6:lengthi5e4:name1:xe1:zi1ee";
+
+    /// Toy keyed signer: the checksum prefixed with a key byte
+    struct KeyedSigner(u8);
+
+    impl Signer for KeyedSigner {
+        fn sign(&self, message: &[u8]) -> Result<Vec<u8>, String> {
+            let mut signature = vec![self.0];
+            signature.extend_from_slice(&sha1(message));
+            Ok(signature)
+        }
+        fn certificate(&self) -> Option<Vec<u8>> {
+            Some(vec![b'k', self.0])
+        }
+    }
+
+    impl Verifier for KeyedSigner {
+        fn verify(&self, message: &[u8], signature: &[u8], certificate: Option<&[u8]>) -> bool {
+            certificate == Some(&[b'k', self.0][..]) && self.sign(message).is_ok_and(|s| s == signature)
+        }
+    }
+
+    struct FailingSigner;
+
+    impl Signer for FailingSigner {
+        fn sign(&self, _message: &[u8]) -> Result<Vec<u8>, String> {
+            Err("no key".to_string())
+        }
+    }
+
+    #[test]
+    fn embedded_signature_round_trips() {
+        let signed = sign(TORRENT, "alice", &KeyedSigner(7)).unwrap();
+        assert!(validate_bencode(&signed).is_ok());
+        assert_eq!(check_key_order(&signed), Ok(()));
+        assert_eq!(info_hash(&signed), info_hash(TORRENT));
+        assert_eq!(verify(&signed, "alice", &KeyedSigner(7)), Ok(true));
+        assert_eq!(verify(&signed, "alice", &KeyedSigner(8)), Ok(false));
+        assert_eq!(verify(&signed, "bob", &KeyedSigner(7)), Err(ERR_SIGNATURE_NOT_FOUND.to_string()));
+    }
+
+    #[test]
+    fn signatures_from_several_signers_are_kept_sorted() {
+        let signed = sign(TORRENT, "bob", &Sha1Checksum).unwrap();
+        let signed = sign(&signed, "alice", &KeyedSigner(1)).unwrap();
+        let resigned = sign(&signed, "bob", &Sha1Checksum).unwrap();
+        assert_eq!(resigned, signed);
+        let found = signatures(&signed).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].signer, b"alice");
+        assert_eq!(found[0].certificate, Some(vec![b'k', 1]));
+        assert_eq!(found[1].signer, b"bob");
+        assert_eq!(found[1].signature, sha1(b"d6:lengthi5e4:name1:xe"));
+        assert_eq!(found[1].certificate, None);
+        assert_eq!(check_key_order(&signed), Ok(()));
+    }
+
+    #[test]
+    fn tampered_info_fails_verification() {
+        let signed = sign(TORRENT, "me", &Sha1Checksum).unwrap();
+        let position = signed.windows(3).position(|w| w == b"i5e").unwrap();
+        let mut tampered = signed.clone();
+        tampered[position + 1] = b'6';
+        assert_eq!(verify(&tampered, "me", &Sha1Checksum), Ok(false));
+    }
+
+    #[test]
+    fn detached_signatures() {
+        let signature = sign_detached(TORRENT, &Sha1Checksum).unwrap();
+        assert_eq!(verify_detached(TORRENT, &signature, &Sha1Checksum), Ok(true));
+        assert_eq!(verify_detached(TORRENT, b"bogus", &Sha1Checksum), Ok(false));
+        assert_eq!(sign_detached(TORRENT, &FailingSigner), Err("no key".to_string()));
+    }
+
+    #[test]
+    fn strip_signatures_restores_original() {
+        let signed = sign(TORRENT, "me", &Sha1Checksum).unwrap();
+        assert_ne!(signed, TORRENT);
+        assert_eq!(strip_signatures(&signed).unwrap(), TORRENT);
+        assert_eq!(strip_signatures(TORRENT).unwrap(), TORRENT);
+        assert!(signatures(TORRENT).unwrap().is_empty());
+    }
+
+    #[test]
+    fn malformed_signature_entries_are_rejected() {
+        let data = b"d4:infod1:ai1ee10:signaturesd2:med9:signaturei5eeee";
+        assert_eq!(
+            signatures(data),
+            Err(ParseError::new(ERR_INVALID_SIGNATURE_ENTRY, 33))
+        );
+    }
+}
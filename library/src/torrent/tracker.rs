@@ -0,0 +1,334 @@
+//! Decoding of HTTP tracker announce and scrape responses.
+//!
+//! An announce response lists peers either as dictionaries or, far more often,
+//! in compact form (BEP 23): one binary string packing each IPv4 peer into six
+//! bytes, with `peers6` doing the same for IPv6 in eighteen (BEP 7). A scrape
+//! response is keyed by raw 20-byte info hashes, which are not text, so it is
+//! read with [`parse_borrowed`] to keep the key bytes intact.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use core::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+use crate::HashMap;
+use crate::error::messages::ERR_INVALID_TRACKER_RESPONSE;
+use crate::nodes::borrowed::BorrowedNode;
+use crate::nodes::node::Node;
+use crate::parser::borrowed::parse_borrowed;
+use crate::parser::default::parse_bytes;
+
+use super::announce::TrackerResponse;
+use super::sha1::SHA1_LENGTH;
+
+/// Bytes per peer in a compact IPv4 peer list: address then port, big endian
+pub const COMPACT_PEER_LENGTH: usize = 6;
+/// Bytes per peer in a compact IPv6 peer list
+pub const COMPACT_PEER6_LENGTH: usize = 18;
+
+/// Where a peer can be reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PeerAddress {
+    /// An IPv4 or IPv6 address
+    Ip(IpAddr),
+    /// A host name, which the dictionary peer form allows in `ip`
+    Host(String),
+}
+
+/// A peer returned by a tracker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+    /// Address of the peer
+    pub address: PeerAddress,
+    /// TCP port of the peer
+    pub port: u16,
+    /// `peer id`, which only the dictionary form carries
+    pub peer_id: Option<Vec<u8>>,
+}
+
+impl From<SocketAddr> for Peer {
+    fn from(address: SocketAddr) -> Self {
+        Peer {
+            address: PeerAddress::Ip(address.ip()),
+            port: address.port(),
+            peer_id: None,
+        }
+    }
+}
+
+/// Splits a compact peer list into entries of `length` bytes, address first
+fn compact_entries(bytes: &[u8], length: usize) -> Result<impl Iterator<Item = (&[u8], u16)>, String> {
+    if !bytes.len().is_multiple_of(length) {
+        return Err(ERR_INVALID_TRACKER_RESPONSE.to_string());
+    }
+    Ok(bytes.chunks_exact(length).map(move |entry| {
+        let (address, port) = entry.split_at(length - 2);
+        (address, u16::from_be_bytes([port[0], port[1]]))
+    }))
+}
+
+/// Decodes a compact IPv4 peer list (the binary form of `peers`).
+///
+/// # Returns
+/// * `Ok(Vec<SocketAddr>)` - The peers in list order
+/// * `Err(String)` - If the length is not a multiple of six bytes
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::parse_compact_peers;
+///
+/// let peers = parse_compact_peers(&[10, 0, 0, 1, 0x1a, 0xe1]).unwrap();
+/// assert_eq!(peers[0].to_string(), "10.0.0.1:6881");
+/// ```
+pub fn parse_compact_peers(bytes: &[u8]) -> Result<Vec<SocketAddr>, String> {
+    Ok(compact_entries(bytes, COMPACT_PEER_LENGTH)?
+        .map(|(address, port)| {
+            let address: [u8; 4] = address.try_into().unwrap_or_default();
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::from(address)), port)
+        })
+        .collect())
+}
+
+/// Decodes a compact IPv6 peer list (the binary form of `peers6`).
+///
+/// # Returns
+/// * `Ok(Vec<SocketAddr>)` - The peers in list order
+/// * `Err(String)` - If the length is not a multiple of eighteen bytes
+pub fn parse_compact_peers6(bytes: &[u8]) -> Result<Vec<SocketAddr>, String> {
+    Ok(compact_entries(bytes, COMPACT_PEER6_LENGTH)?
+        .map(|(address, port)| {
+            let address: [u8; 16] = address.try_into().unwrap_or_default();
+            SocketAddr::new(IpAddr::V6(Ipv6Addr::from(address)), port)
+        })
+        .collect())
+}
+
+/// Encodes peers as compact lists, returning the IPv4 (`peers`) and IPv6
+/// (`peers6`) lists.
+pub fn encode_compact_peers(peers: &[SocketAddr]) -> (Vec<u8>, Vec<u8>) {
+    let (mut peers4, mut peers6) = (Vec::new(), Vec::new());
+    for peer in peers {
+        let list = match peer.ip() {
+            IpAddr::V4(address) => {
+                peers4.extend_from_slice(&address.octets());
+                &mut peers4
+            }
+            IpAddr::V6(address) => {
+                peers6.extend_from_slice(&address.octets());
+                &mut peers6
+            }
+        };
+        list.extend_from_slice(&peer.port().to_be_bytes());
+    }
+    (peers4, peers6)
+}
+
+/// Reads one peer of the dictionary form
+fn dictionary_peer(node: &Node) -> Result<Peer, String> {
+    let invalid = || ERR_INVALID_TRACKER_RESPONSE.to_string();
+    let host = node.get("ip").and_then(Node::as_bytes).ok_or_else(invalid)?;
+    let host = String::from_utf8_lossy(host);
+    let port = node.get_int_optional("port").and_then(|port| u16::try_from(port).ok()).ok_or_else(invalid)?;
+    Ok(Peer {
+        address: match host.parse() {
+            Ok(ip) => PeerAddress::Ip(ip),
+            Err(_) => PeerAddress::Host(host.into_owned()),
+        },
+        port,
+        peer_id: node.get("peer id").and_then(Node::as_bytes).map(<[u8]>::to_vec),
+    })
+}
+
+/// A tracker's announce response with its peers.
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::AnnounceResponse;
+///
+/// let response = AnnounceResponse::from_bytes(b"d8:intervali900e5:peers6:\x0a\x00\x00\x01\x1a\xe1e").unwrap();
+/// assert_eq!(response.tracker.interval, Some(900));
+/// assert_eq!(response.peers[0].port, 6881);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnnounceResponse {
+    /// The scheduling fields
+    pub tracker: TrackerResponse,
+    /// Peers from `peers`, in either form, followed by those from `peers6`
+    pub peers: Vec<Peer>,
+}
+
+impl AnnounceResponse {
+    /// Reads a bencoded announce response.
+    ///
+    /// # Returns
+    /// * `Ok(AnnounceResponse)` - The response
+    /// * `Err(String)` - If the input is malformed, the response is invalid
+    ///   as for [`TrackerResponse::from_node`], or a peer list is malformed
+    pub fn from_bytes(data: &[u8]) -> Result<AnnounceResponse, String> {
+        let root = parse_bytes(data)?;
+        let tracker = TrackerResponse::from_node(&root)?;
+        let mut peers = Vec::new();
+        match root.get("peers") {
+            Some(Node::List(list)) => {
+                for peer in list {
+                    peers.push(dictionary_peer(peer)?);
+                }
+            }
+            Some(node) => {
+                let bytes = node.as_bytes().ok_or_else(|| ERR_INVALID_TRACKER_RESPONSE.to_string())?;
+                peers.extend(parse_compact_peers(bytes)?.into_iter().map(Peer::from));
+            }
+            None => {}
+        }
+        if let Some(node) = root.get("peers6") {
+            let bytes = node.as_bytes().ok_or_else(|| ERR_INVALID_TRACKER_RESPONSE.to_string())?;
+            peers.extend(parse_compact_peers6(bytes)?.into_iter().map(Peer::from));
+        }
+        Ok(AnnounceResponse { tracker, peers })
+    }
+}
+
+/// Swarm counts for one torrent in a scrape response.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ScrapeStats {
+    /// `complete`: number of seeders
+    pub complete: u64,
+    /// `downloaded`: number of completed downloads reported to the tracker
+    pub downloaded: u64,
+    /// `incomplete`: number of leechers
+    pub incomplete: u64,
+}
+
+/// A tracker's scrape response.
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::ScrapeResponse;
+///
+/// let mut data = b"d5:filesd20:".to_vec();
+/// data.extend_from_slice(&[0xab; 20]);
+/// data.extend_from_slice(b"d8:completei5e10:downloadedi50e10:incompletei10eeee");
+/// let response = ScrapeResponse::from_bytes(&data).unwrap();
+/// assert_eq!(response.files[&[0xab; 20]].downloaded, 50);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScrapeResponse {
+    /// `failure reason`: the scrape was refused and `files` is empty
+    pub failure_reason: Option<String>,
+    /// Counts for each torrent, by info hash
+    pub files: HashMap<[u8; SHA1_LENGTH], ScrapeStats>,
+}
+
+impl ScrapeResponse {
+    /// Reads a bencoded scrape response.
+    ///
+    /// Missing counts are read as zero.
+    ///
+    /// # Returns
+    /// * `Ok(ScrapeResponse)` - The response
+    /// * `Err(String)` - If the input is malformed, not a dictionary, has
+    ///   neither `files` nor a `failure reason`, or a key is not a 20-byte
+    ///   info hash or a count is not a non-negative integer
+    pub fn from_bytes(data: &[u8]) -> Result<ScrapeResponse, String> {
+        let invalid = || ERR_INVALID_TRACKER_RESPONSE.to_string();
+        let root = parse_borrowed(data)?;
+        let root = root.as_dictionary().ok_or_else(invalid)?;
+        if let Some(reason) = root.get(&b"failure reason"[..]) {
+            let reason = reason.as_bytes().ok_or_else(invalid)?;
+            return Ok(ScrapeResponse {
+                failure_reason: Some(String::from_utf8_lossy(reason).into_owned()),
+                files: HashMap::new(),
+            });
+        }
+        let files = root.get(&b"files"[..]).and_then(BorrowedNode::as_dictionary).ok_or_else(invalid)?;
+        let mut response = ScrapeResponse::default();
+        for (&info_hash, stats) in files {
+            let info_hash: [u8; SHA1_LENGTH] = info_hash.try_into().map_err(|_| invalid())?;
+            let stats = stats.as_dictionary().ok_or_else(invalid)?;
+            let count = |key: &[u8]| match stats.get(key) {
+                Some(value) => value.as_integer().and_then(|value| u64::try_from(value).ok()).ok_or_else(invalid),
+                None => Ok(0),
+            };
+            let stats = ScrapeStats {
+                complete: count(b"complete")?,
+                downloaded: count(b"downloaded")?,
+                incomplete: count(b"incomplete")?,
+            };
+            response.files.insert(info_hash, stats);
+        }
+        Ok(response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    fn peer(text: &str) -> SocketAddr {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn compact_peer_lists_round_trip() {
+        let peers = [peer("10.0.0.1:6881"), peer("[::1]:443"), peer("255.255.255.255:65535")];
+        let (peers4, peers6) = encode_compact_peers(&peers);
+        assert_eq!((peers4.len(), peers6.len()), (12, 18));
+        assert_eq!(parse_compact_peers(&peers4).unwrap(), [peers[0], peers[2]]);
+        assert_eq!(parse_compact_peers6(&peers6).unwrap(), [peers[1]]);
+        assert_eq!(parse_compact_peers(&peers4[..7]).unwrap_err(), ERR_INVALID_TRACKER_RESPONSE);
+        assert!(parse_compact_peers(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn announce_responses_read_both_peer_forms() {
+        let (peers4, peers6) = encode_compact_peers(&[peer("1.2.3.4:80"), peer("[::2]:81")]);
+        let mut data = b"d8:intervali60e5:peers6:".to_vec();
+        data.extend_from_slice(&peers4);
+        data.extend_from_slice(b"6:peers618:");
+        data.extend_from_slice(&peers6);
+        data.push(b'e');
+        let response = AnnounceResponse::from_bytes(&data).unwrap();
+        assert_eq!(response.peers, [Peer::from(peer("1.2.3.4:80")), Peer::from(peer("[::2]:81"))]);
+
+        let data = b"d8:intervali60e5:peersld2:ip7:1.2.3.47:peer id2:\xff\x014:porti80eed2:ip9:peer.test4:porti1eeee";
+        let response = AnnounceResponse::from_bytes(data).unwrap();
+        assert_eq!(response.peers[0].address, PeerAddress::Ip(peer("1.2.3.4:0").ip()));
+        assert_eq!(response.peers[0].peer_id.as_deref(), Some(&b"\xff\x01"[..]));
+        assert_eq!(response.peers[1].address, PeerAddress::Host("peer.test".to_string()));
+    }
+
+    #[test]
+    fn invalid_announce_responses_are_rejected() {
+        for data in [
+            &b"d8:intervali60e5:peers5:abcdee"[..],
+            b"d8:intervali60e5:peersld2:ip1:x4:porti70000eeee",
+            b"d8:intervali60e5:peersi1e6:peers60:e",
+            b"d5:peers0:e",
+        ] {
+            let error = AnnounceResponse::from_bytes(data).unwrap_err();
+            assert_eq!(ErrorKind::from_message(&error), ErrorKind::InvalidTrackerResponse, "{:?}", data);
+        }
+        let refused = AnnounceResponse::from_bytes(b"d14:failure reason6:bannede").unwrap();
+        assert!(refused.peers.is_empty());
+    }
+
+    #[test]
+    fn scrape_responses_keep_binary_info_hashes() {
+        // A hash that is not valid UTF-8 would be corrupted by a lossy key
+        let mut data = b"d5:filesd20:".to_vec();
+        data.extend_from_slice(&[0xff; 20]);
+        data.extend_from_slice(b"d8:completei1eee5:flagsdee");
+        let response = ScrapeResponse::from_bytes(&data).unwrap();
+        assert_eq!(response.files[&[0xff; 20]], ScrapeStats { complete: 1, ..ScrapeStats::default() });
+
+        let refused = ScrapeResponse::from_bytes(b"d14:failure reason4:nonee").unwrap();
+        assert_eq!(refused.failure_reason.as_deref(), Some("none"));
+        for data in [&b"de"[..], b"d5:filesd3:abcdeee", b"li1ee"] {
+            assert_eq!(ScrapeResponse::from_bytes(data).unwrap_err(), ERR_INVALID_TRACKER_RESPONSE);
+        }
+    }
+}
@@ -0,0 +1,280 @@
+//! Tracker announce URLs split into their parts.
+//!
+//! A [`TrackerUrl`] keeps the URL exactly as the torrent holds it, which is
+//! what [`Display`](core::fmt::Display) writes back, alongside its scheme,
+//! host, port, path and query parameters. Parsing checks only the shape of the
+//! URL, not whether the scheme is one a client can announce to. Query values
+//! are kept as written, without percent decoding, so a passkey read here can
+//! be compared with the one a private tracker hands out.
+
+#[cfg(not(feature = "std"))]
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use core::fmt;
+use core::str::FromStr;
+
+use crate::error::messages::ERR_INVALID_TRACKER_URL;
+
+/// Query parameters private trackers use to identify the user
+pub const PASSKEY_PARAMS: [&str; 4] = ["passkey", "authkey", "torrent_pass", "pk"];
+
+/// A tracker announce URL and its parts.
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::TrackerUrl;
+///
+/// let url: TrackerUrl = "https://Tracker.example:8443/announce?passkey=abc123&x=1".parse().unwrap();
+/// assert_eq!((url.scheme(), url.host(), url.port()), ("https", "tracker.example", Some(8443)));
+/// assert_eq!(url.path(), "/announce");
+/// assert_eq!(url.passkey(), Some("abc123"));
+/// assert_eq!(url.to_string(), "https://Tracker.example:8443/announce?passkey=abc123&x=1");
+/// assert!(TrackerUrl::parse("tracker.example/announce").is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct TrackerUrl {
+    /// The URL as written
+    url: String,
+    /// Scheme, lowercased
+    scheme: String,
+    /// Host name or address, lowercased and without IPv6 brackets
+    host: String,
+    /// Port, if the URL gives one
+    port: Option<u16>,
+    /// Path, starting with `/` unless empty
+    path: String,
+    /// Query parameters in order, as written
+    query: Vec<(String, String)>,
+}
+
+/// Returns true if `scheme` is a letter followed by letters, digits, `+`, `-` or `.`
+fn is_valid_scheme(scheme: &str) -> bool {
+    let mut chars = scheme.chars();
+    chars.next().is_some_and(|first| first.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '+' | '-' | '.'))
+}
+
+/// Splits an authority (without user information) into host and port
+fn split_host_port(authority: &str) -> Option<(&str, Option<u16>)> {
+    let (host, port) = if let Some(bracketed) = authority.strip_prefix('[') {
+        let (host, rest) = bracketed.split_once(']')?;
+        match rest {
+            "" => (host, None),
+            _ => (host, Some(rest.strip_prefix(':')?)),
+        }
+    } else {
+        match authority.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (authority, None),
+        }
+    };
+    let port = match port {
+        Some(port) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => Some(port.parse().ok()?),
+        Some(_) => return None,
+        None => None,
+    };
+    (!host.is_empty()).then_some((host, port))
+}
+
+impl TrackerUrl {
+    /// Parses an announce URL.
+    ///
+    /// # Arguments
+    /// * `url` - The URL, such as `udp://tracker.example:6969/announce`
+    ///
+    /// # Returns
+    /// * `Ok(TrackerUrl)` - The URL and its parts
+    /// * `Err(String)` - If the URL has no scheme or host, a port that is not a
+    ///   number below 65536, or contains whitespace or control characters
+    pub fn parse(url: &str) -> Result<TrackerUrl, String> {
+        let invalid = || ERR_INVALID_TRACKER_URL.to_string();
+        if url.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            return Err(invalid());
+        }
+        let (scheme, rest) = url.split_once("://").ok_or_else(invalid)?;
+        if !is_valid_scheme(scheme) {
+            return Err(invalid());
+        }
+        let rest = rest.split_once('#').map_or(rest, |(before, _)| before);
+        let authority_end = rest.find(['/', '?']).unwrap_or(rest.len());
+        let (authority, rest) = rest.split_at(authority_end);
+        let authority = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+        let (host, port) = split_host_port(authority).ok_or_else(invalid)?;
+        let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let query = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+                (name.to_string(), value.to_string())
+            })
+            .collect();
+        Ok(TrackerUrl {
+            url: url.to_string(),
+            scheme: scheme.to_ascii_lowercase(),
+            host: host.to_ascii_lowercase(),
+            port,
+            path: path.to_string(),
+            query,
+        })
+    }
+
+    /// Returns the URL as written
+    pub fn as_str(&self) -> &str {
+        &self.url
+    }
+
+    /// Returns the scheme in lowercase, such as `udp` or `https`
+    pub fn scheme(&self) -> &str {
+        &self.scheme
+    }
+
+    /// Returns the host in lowercase; IPv6 addresses are given without brackets
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Returns the port, if the URL gives one
+    pub fn port(&self) -> Option<u16> {
+        self.port
+    }
+
+    /// Returns the path, which is empty or starts with `/`
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the query parameters in order; a parameter without `=` has an empty value
+    pub fn query(&self) -> &[(String, String)] {
+        &self.query
+    }
+
+    /// Returns the value of the first query parameter called `name`
+    pub fn query_param(&self, name: &str) -> Option<&str> {
+        self.query.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the user's passkey, from the first query parameter named in
+    /// [`PASSKEY_PARAMS`] (ignoring case) with a value
+    pub fn passkey(&self) -> Option<&str> {
+        self.query
+            .iter()
+            .find(|(key, value)| !value.is_empty() && PASSKEY_PARAMS.iter().any(|param| key.eq_ignore_ascii_case(param)))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+impl FromStr for TrackerUrl {
+    type Err = String;
+
+    fn from_str(url: &str) -> Result<Self, Self::Err> {
+        TrackerUrl::parse(url)
+    }
+}
+
+impl fmt::Display for TrackerUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.url)
+    }
+}
+
+impl PartialEq<str> for TrackerUrl {
+    fn eq(&self, other: &str) -> bool {
+        self.url == other
+    }
+}
+
+impl PartialEq<&str> for TrackerUrl {
+    fn eq(&self, other: &&str) -> bool {
+        self.url == *other
+    }
+}
+
+/// Groups tracker URLs by host, keeping hosts and the URLs of each host in
+/// the order they are first seen.
+///
+/// # Arguments
+/// * `urls` - The URLs to group
+///
+/// # Returns
+/// Each host with its URLs
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::{TrackerUrl, group_by_host};
+///
+/// let urls: Vec<TrackerUrl> = ["udp://a.example:6969", "https://b.example/announce", "http://A.example/announce"]
+///     .iter()
+///     .map(|url| url.parse().unwrap())
+///     .collect();
+/// let groups = group_by_host(&urls);
+/// assert_eq!(groups.len(), 2);
+/// assert_eq!(groups[0].0, "a.example");
+/// assert_eq!(groups[0].1, ["udp://a.example:6969", "http://A.example/announce"]);
+/// ```
+pub fn group_by_host<'a>(urls: impl IntoIterator<Item = &'a TrackerUrl>) -> Vec<(&'a str, Vec<&'a TrackerUrl>)> {
+    let mut groups: Vec<(&str, Vec<&TrackerUrl>)> = Vec::new();
+    for url in urls {
+        match groups.iter_mut().find(|(host, _)| *host == url.host()) {
+            Some((_, members)) => members.push(url),
+            None => groups.push((url.host(), vec![url])),
+        }
+    }
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::kind::ErrorKind;
+
+    #[test]
+    fn urls_are_split_into_parts() {
+        let url = TrackerUrl::parse("UDP://user@[2001:db8::1]:6969").unwrap();
+        assert_eq!((url.scheme(), url.host(), url.port(), url.path()), ("udp", "2001:db8::1", Some(6969), ""));
+        assert!(url.query().is_empty());
+
+        let url = TrackerUrl::parse("http://tracker.example/a/announce?info=1&pk&authkey=K#top").unwrap();
+        assert_eq!((url.host(), url.port(), url.path()), ("tracker.example", None, "/a/announce"));
+        assert_eq!(url.query_param("info"), Some("1"));
+        assert_eq!(url.query_param("pk"), Some(""));
+        assert_eq!(url.passkey(), Some("K"));
+        assert_eq!(url.to_string(), "http://tracker.example/a/announce?info=1&pk&authkey=K#top");
+
+        let url = TrackerUrl::parse("wss://tracker.example?torrent_pass=p").unwrap();
+        assert_eq!((url.host(), url.path(), url.passkey()), ("tracker.example", "", Some("p")));
+    }
+
+    #[test]
+    fn malformed_urls_are_rejected() {
+        for url in [
+            "",
+            "tracker.example",
+            "://tracker.example",
+            "1udp://tracker.example",
+            "udp://",
+            "udp://:6969",
+            "udp://tracker.example:",
+            "udp://tracker.example:65536",
+            "udp://tracker.example:69a",
+            "udp://[::1",
+            "udp://[::1]6969",
+            "http://tracker.example/ announce",
+        ] {
+            let error = TrackerUrl::parse(url).unwrap_err();
+            assert_eq!(ErrorKind::from_message(&error), ErrorKind::InvalidTrackerUrl, "{}", url);
+        }
+    }
+
+    #[test]
+    fn urls_compare_by_their_text() {
+        let url = TrackerUrl::parse("udp://tracker.example:6969").unwrap();
+        assert_eq!(url, "udp://tracker.example:6969");
+        assert_ne!(url, TrackerUrl::parse("udp://TRACKER.example:6969").unwrap());
+        assert_eq!(url.as_str().parse::<TrackerUrl>(), Ok(url));
+    }
+}
@@ -0,0 +1,314 @@
+//! Verification of a torrent download as its data arrives.
+//!
+//! Peers deliver blocks in any order and of any size. [`IncrementalVerifier`]
+//! assembles them into pieces using a [`PieceMap`], hashes each piece as soon as
+//! its last byte arrives and reports whether it matched, so a downloader only has
+//! to feed it writes and act on the results.
+
+#[cfg(not(feature = "std"))]
+use alloc::{string::String, vec, vec::Vec};
+
+use core::ops::Range;
+
+use crate::HashMap;
+use crate::error::messages::ERR_WRITE_OUT_OF_RANGE;
+
+use super::hasher::{Hasher, SharedHasher};
+use super::pieces::PieceMap;
+
+/// Outcome of a piece whose last byte has been written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PieceEvent {
+    /// The piece matched its hash; its data is handed over for storage
+    Passed {
+        /// Index of the piece
+        index: usize,
+        /// The verified piece
+        data: Vec<u8>,
+    },
+    /// The piece did not match its hash and has been discarded, ready to be
+    /// downloaded again
+    Failed {
+        /// Index of the piece
+        index: usize,
+    },
+}
+
+/// A piece that has received some but not all of its data
+struct Partial {
+    /// Piece buffer, filled in as writes arrive
+    data: Vec<u8>,
+    /// Sorted, non-overlapping ranges of `data` that have been written
+    received: Vec<Range<usize>>,
+}
+
+impl Partial {
+    /// Records that `range` has been written, merging it with its neighbours
+    fn mark(&mut self, range: Range<usize>) {
+        self.received.push(range);
+        self.received.sort_by_key(|range| range.start);
+        let mut merged: Vec<Range<usize>> = Vec::with_capacity(self.received.len());
+        for range in self.received.drain(..) {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        self.received = merged;
+    }
+
+    /// Returns true once every byte of the piece has been written
+    fn is_complete(&self) -> bool {
+        matches!(self.received.as_slice(), [range] if range.start == 0 && range.end == self.data.len())
+    }
+}
+
+/// Assembles and verifies pieces from writes at arbitrary payload offsets.
+///
+/// # Example
+/// ```
+/// use bencode_lib::torrent::{piece_map, sha1::sha1, IncrementalVerifier, PieceEvent};
+///
+/// let mut data = b"d4:infod6:lengthi6e4:name1:x12:piece lengthi4e6:pieces40:".to_vec();
+/// data.extend(sha1(b"abcd"));
+/// data.extend(sha1(b"ef"));
+/// data.extend(b"ee");
+///
+/// let mut verifier = IncrementalVerifier::new(piece_map(&data).unwrap());
+/// assert!(verifier.write(2, b"cdef").unwrap().len() == 1); // piece 1 is complete
+/// let events = verifier.write(0, b"ab").unwrap();
+/// assert_eq!(events, vec![PieceEvent::Passed { index: 0, data: b"abcd".to_vec() }]);
+/// assert!(verifier.is_complete());
+/// ```
+pub struct IncrementalVerifier {
+    map: PieceMap,
+    hasher: SharedHasher,
+    partial: HashMap<usize, Partial>,
+    passed: Vec<bool>,
+    passed_count: usize,
+    failed_count: usize,
+}
+
+impl IncrementalVerifier {
+    /// Creates a verifier with every piece pending.
+    pub fn new(map: PieceMap) -> Self {
+        let pieces = map.piece_count();
+        Self {
+            map,
+            hasher: SharedHasher::new(),
+            partial: HashMap::new(),
+            passed: vec![false; pieces],
+            passed_count: 0,
+            failed_count: 0,
+        }
+    }
+
+    /// Hashes pieces with `hasher` rather than the default backend.
+    pub fn with_hasher(mut self, hasher: impl Hasher + 'static) -> Self {
+        self.hasher = SharedHasher::from(hasher);
+        self
+    }
+
+    /// Marks pieces already known to be good (e.g. when resuming a download)
+    /// as passed, so further writes to them are ignored.
+    pub fn with_passed(mut self, pieces: impl IntoIterator<Item = usize>) -> Self {
+        for index in pieces {
+            if let Some(passed) = self.passed.get_mut(index)
+                && !*passed
+            {
+                *passed = true;
+                self.passed_count += 1;
+                self.partial.remove(&index);
+            }
+        }
+        self
+    }
+
+    /// Returns the piece layout being verified
+    pub fn map(&self) -> &PieceMap {
+        &self.map
+    }
+
+    /// Writes downloaded bytes at a payload offset.
+    ///
+    /// Writes may arrive in any order, overlap and span piece boundaries. Bytes
+    /// for pieces that have already passed are ignored.
+    ///
+    /// # Arguments
+    /// * `offset` - Offset of `bytes` within the torrent payload
+    /// * `bytes` - The downloaded data
+    ///
+    /// # Returns
+    /// * `Ok(Vec<PieceEvent>)` - One event per piece completed by this write
+    /// * `Err(String)` - If the write extends past the end of the payload
+    pub fn write(&mut self, offset: u64, bytes: &[u8]) -> Result<Vec<PieceEvent>, String> {
+        let end = offset
+            .checked_add(bytes.len() as u64)
+            .filter(|end| *end <= self.map.total_length())
+            .ok_or_else(|| String::from(ERR_WRITE_OUT_OF_RANGE))?;
+        let mut events = Vec::new();
+        if bytes.is_empty() {
+            return Ok(events);
+        }
+        let first = self.map.piece_at(offset).unwrap_or(0);
+        let last = self.map.piece_at(end - 1).unwrap_or(first);
+        for index in first..=last {
+            if self.passed[index] {
+                continue;
+            }
+            let Some(range) = self.map.piece_range(index) else {
+                continue;
+            };
+            // Overlap of the write with this piece, relative to each
+            let start = offset.max(range.start);
+            let stop = end.min(range.end);
+            let in_piece = (start - range.start) as usize..(stop - range.start) as usize;
+            let in_bytes = (start - offset) as usize..(stop - offset) as usize;
+
+            let size = (range.end - range.start) as usize;
+            let partial = self.partial.entry(index).or_insert_with(|| Partial {
+                data: vec![0; size],
+                received: Vec::new(),
+            });
+            partial.data[in_piece.clone()].copy_from_slice(&bytes[in_bytes]);
+            partial.mark(in_piece);
+            if partial.is_complete() {
+                events.push(self.finish(index));
+            }
+        }
+        Ok(events)
+    }
+
+    /// Hashes a complete piece and records the outcome
+    fn finish(&mut self, index: usize) -> PieceEvent {
+        let data = self.partial.remove(&index).map(|partial| partial.data).unwrap_or_default();
+        if self.map.piece_hash(index) == Some(&self.hasher.get().sha1(&data)) {
+            self.passed[index] = true;
+            self.passed_count += 1;
+            PieceEvent::Passed { index, data }
+        } else {
+            self.failed_count += 1;
+            PieceEvent::Failed { index }
+        }
+    }
+
+    /// Returns true if a piece has passed verification
+    pub fn is_passed(&self, index: usize) -> bool {
+        self.passed.get(index).copied().unwrap_or(false)
+    }
+
+    /// Returns the number of pieces that have passed
+    pub fn passed_count(&self) -> usize {
+        self.passed_count
+    }
+
+    /// Returns the number of times a piece has failed verification
+    pub fn failed_count(&self) -> usize {
+        self.failed_count
+    }
+
+    /// Returns true once every piece has passed
+    pub fn is_complete(&self) -> bool {
+        self.passed_count == self.passed.len()
+    }
+
+    /// Returns the indexes of the pieces that have not yet passed
+    pub fn missing(&self) -> impl Iterator<Item = usize> + '_ {
+        self.passed.iter().enumerate().filter(|(_, passed)| !**passed).map(|(index, _)| index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::torrent::pieces::piece_map;
+    use crate::torrent::sha1::{SHA1_LENGTH, sha1};
+    use crate::torrent::sha256::SHA256_LENGTH;
+
+    const PAYLOAD: &[u8] = b"0123456789";
+
+    /// Builds a single-file torrent of PAYLOAD with 4 byte pieces
+    fn torrent() -> Vec<u8> {
+        let mut data = b"d4:infod6:lengthi10e4:name1:x12:piece lengthi4e6:pieces60:".to_vec();
+        for piece in PAYLOAD.chunks(4) {
+            data.extend(sha1(piece));
+        }
+        data.extend(b"ee");
+        data
+    }
+
+    fn verifier() -> IncrementalVerifier {
+        IncrementalVerifier::new(piece_map(&torrent()).unwrap())
+    }
+
+    #[test]
+    fn out_of_order_writes_complete_pieces() {
+        let mut verifier = verifier();
+        assert!(verifier.write(9, b"9").unwrap().is_empty());
+        assert!(verifier.write(1, b"123").unwrap().is_empty());
+        let events = verifier.write(0, b"0").unwrap();
+        assert_eq!(events, vec![PieceEvent::Passed { index: 0, data: b"0123".to_vec() }]);
+        let events = verifier.write(4, b"45678").unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(verifier.is_complete());
+        assert_eq!(verifier.missing().count(), 0);
+    }
+
+    #[test]
+    fn overlapping_writes_spanning_pieces() {
+        let mut verifier = verifier();
+        let events = verifier.write(2, b"23456789").unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(verifier.is_passed(1) && verifier.is_passed(2));
+        let events = verifier.write(0, b"0123").unwrap();
+        assert_eq!(events, vec![PieceEvent::Passed { index: 0, data: b"0123".to_vec() }]);
+        assert!(verifier.write(3, b"34").unwrap().is_empty());
+    }
+
+    #[test]
+    fn corrupt_pieces_fail_and_can_be_retried() {
+        let mut verifier = verifier();
+        assert_eq!(verifier.write(8, b"x9").unwrap(), vec![PieceEvent::Failed { index: 2 }]);
+        assert_eq!(verifier.failed_count(), 1);
+        assert!(verifier.write(8, b"8").unwrap().is_empty());
+        assert!(matches!(verifier.write(9, b"9").unwrap()[..], [PieceEvent::Passed { index: 2, .. }]));
+    }
+
+    #[test]
+    fn passed_pieces_ignore_writes() {
+        let mut verifier = verifier().with_passed([0, 2]);
+        assert_eq!(verifier.passed_count(), 2);
+        assert!(verifier.write(0, b"xxxx").unwrap().is_empty());
+        assert_eq!(verifier.write(4, b"4567").unwrap().len(), 1);
+        assert!(verifier.is_complete());
+    }
+
+    /// Reverses the data before hashing it
+    #[derive(Debug)]
+    struct Reversed;
+
+    impl Hasher for Reversed {
+        fn sha1(&self, data: &[u8]) -> [u8; SHA1_LENGTH] {
+            sha1(&data.iter().rev().copied().collect::<Vec<_>>())
+        }
+
+        fn sha256(&self, data: &[u8]) -> [u8; SHA256_LENGTH] {
+            crate::torrent::BuiltinHasher.sha256(data)
+        }
+    }
+
+    #[test]
+    fn pieces_are_hashed_with_the_given_backend() {
+        let mut verifier = verifier().with_hasher(Reversed);
+        assert_eq!(verifier.write(8, b"89").unwrap(), vec![PieceEvent::Failed { index: 2 }]);
+        let events = verifier.write(8, b"98").unwrap();
+        assert_eq!(events, vec![PieceEvent::Passed { index: 2, data: b"98".to_vec() }]);
+    }
+
+    #[test]
+    fn writes_past_the_end_are_rejected() {
+        let mut verifier = verifier();
+        assert_eq!(verifier.write(8, b"89x").unwrap_err(), ERR_WRITE_OUT_OF_RANGE);
+        assert!(verifier.write(10, b"").unwrap().is_empty());
+    }
+}